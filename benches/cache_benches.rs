@@ -2023,6 +2023,128 @@ fn bench_sharded_poll(c: &mut Criterion) {
     group.finish();
 }
 
+// ---------------------------------------------------------------------------
+// Per-key path of the sharded stores: a cache_get hit and a cache_set overwrite, on one
+// thread and on 8. A store still on the shard table it was built with routes without
+// pinning; once resharded, every per-key operation takes an epoch pin so a later reshard
+// cannot free the table under it. The "resharded" rows measure that pin, and the 8-thread
+// rows would show it if it touched a cache line shared between threads.
+// ---------------------------------------------------------------------------
+
+fn bench_sharded_per_key_path(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sharded per-key path: cache_get/cache_set, 1 and 8 threads");
+    group.sample_size(20);
+    group.warm_up_time(Duration::from_millis(200));
+    group.measurement_time(Duration::from_millis(500));
+
+    let ttl = ShardedTtlCache::<usize, usize>::builder()
+        .ttl(Duration::from_secs(3600))
+        .build()
+        .unwrap();
+    let lru = ShardedLruCache::<usize, usize>::builder()
+        .max_size(4 * N_KEYS)
+        .build()
+        .unwrap();
+    for i in 0..N_KEYS {
+        ttl.cache_set(i, i * 2).expect("infallible");
+        lru.cache_set(i, i * 2).expect("infallible");
+    }
+
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("ShardedTtlCache get, 1 thread", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i += 1;
+            black_box(ttl.cache_get(&read_key(i, 0)).expect("infallible"))
+        })
+    });
+    group.bench_function("ShardedTtlCache set, 1 thread", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i += 1;
+            ttl.cache_set(read_key(i, 0), i).expect("infallible")
+        })
+    });
+    group.bench_function("ShardedLruCache get, 1 thread", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i += 1;
+            black_box(lru.cache_get(&read_key(i, 0)).expect("infallible"))
+        })
+    });
+    group.bench_function("ShardedLruCache set, 1 thread", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i += 1;
+            lru.cache_set(read_key(i, 0), i).expect("infallible")
+        })
+    });
+
+    group.throughput(Throughput::Elements(N_THREADS_STORM as u64));
+    group.bench_function("ShardedTtlCache get, 8 threads", |b| {
+        b.iter_custom(|iters| {
+            let cache = ttl.clone();
+            run_concurrent_n!(N_THREADS_STORM, cache, iters, t, i, {
+                black_box(cache.cache_get(&read_key(i, t)).expect("infallible"));
+            })
+        })
+    });
+    group.bench_function("ShardedTtlCache set, 8 threads", |b| {
+        b.iter_custom(|iters| {
+            let cache = ttl.clone();
+            run_concurrent_n!(N_THREADS_STORM, cache, iters, t, i, {
+                cache.cache_set(read_key(i, t), i).expect("infallible");
+            })
+        })
+    });
+    group.bench_function("ShardedLruCache get, 8 threads", |b| {
+        b.iter_custom(|iters| {
+            let cache = lru.clone();
+            run_concurrent_n!(N_THREADS_STORM, cache, iters, t, i, {
+                black_box(cache.cache_get(&read_key(i, t)).expect("infallible"));
+            })
+        })
+    });
+    group.bench_function("ShardedLruCache set, 8 threads", |b| {
+        b.iter_custom(|iters| {
+            let cache = lru.clone();
+            run_concurrent_n!(N_THREADS_STORM, cache, iters, t, i, {
+                cache.cache_set(read_key(i, t), i).expect("infallible");
+            })
+        })
+    });
+
+    // Same store shape, moved to a second table so every operation takes the pinned path.
+    let resharded = ShardedLruCache::<usize, usize>::builder()
+        .max_size(4 * N_KEYS)
+        .build()
+        .unwrap();
+    for i in 0..N_KEYS {
+        resharded.cache_set(i, i * 2).expect("infallible");
+    }
+    resharded.reshard(resharded.shards() * 2).unwrap();
+
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("ShardedLruCache get, 1 thread, resharded", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i += 1;
+            black_box(resharded.cache_get(&read_key(i, 0)).expect("infallible"))
+        })
+    });
+    group.throughput(Throughput::Elements(N_THREADS_STORM as u64));
+    group.bench_function("ShardedLruCache get, 8 threads, resharded", |b| {
+        b.iter_custom(|iters| {
+            let cache = resharded.clone();
+            run_concurrent_n!(N_THREADS_STORM, cache, iters, t, i, {
+                black_box(cache.cache_get(&read_key(i, t)).expect("infallible"));
+            })
+        })
+    });
+
+    group.finish();
+}
+
 // ---------------------------------------------------------------------------
 // Build time: builder().build() for each sharded store, at the default shard count.
 // The default shard count calls `available_parallelism()` on every build, which
@@ -2154,6 +2276,139 @@ fn bench_large_value_lru(c: &mut Criterion) {
 // cost is recorded in the baseline rather than being a remembered constant.
 // ---------------------------------------------------------------------------
 
+/// The single-key paths of the stores kept in a `BucketMap` (`TtlCache`, `ExpiringCache` and
+/// their sharded forms), beside `UnboundCache` on a `std` `HashMap` as the reference: a hit, a
+/// miss, an overwrite, and a remove followed by a re-insert, over a warm table of N entries.
+fn bench_hash_map_stores(c: &mut Criterion) {
+    const N: usize = 100_000;
+    let mut group = c.benchmark_group("Hash-map stores at N=100,000: get/miss/overwrite/remove");
+    group.sample_size(20);
+    group.warm_up_time(Duration::from_millis(200));
+    group.measurement_time(Duration::from_millis(800));
+    let long_ttl = Duration::from_secs(3600);
+
+    let mut unbound = UnboundCache::<usize, usize>::builder().build().unwrap();
+    let mut ttl = TtlCache::<usize, usize>::builder()
+        .ttl(long_ttl)
+        .build()
+        .unwrap();
+    let mut expiring: ExpiringCache<usize, ExpiringValue> =
+        ExpiringCache::builder().build().unwrap();
+    let sharded_ttl = ShardedTtlCache::<usize, usize>::builder()
+        .ttl(long_ttl)
+        .build()
+        .unwrap();
+    let sharded_expiring: ShardedExpiringCache<usize, ExpiringValue> =
+        ShardedExpiringCache::builder().build().unwrap();
+    for i in 0..N {
+        unbound.cache_set(i, i);
+        ttl.cache_set(i, i);
+        expiring.cache_set(i, ExpiringValue { val: i });
+        sharded_ttl.cache_set(i, i).expect("infallible");
+        sharded_expiring
+            .cache_set(i, ExpiringValue { val: i })
+            .expect("infallible");
+    }
+
+    let mut key = 0;
+    let mut next = move || {
+        key = (key + 7919) % N;
+        key
+    };
+    group.bench_function("UnboundCache get", |b| {
+        b.iter(|| black_box(unbound.cache_get(black_box(&next())).copied()))
+    });
+    group.bench_function("TtlCache get", |b| {
+        b.iter(|| black_box(ttl.cache_get(black_box(&next())).copied()))
+    });
+    group.bench_function("ExpiringCache get", |b| {
+        b.iter(|| black_box(expiring.cache_get(black_box(&next())).is_some()))
+    });
+    group.bench_function("ShardedTtlCache get", |b| {
+        b.iter(|| {
+            black_box(
+                sharded_ttl
+                    .cache_get(black_box(&next()))
+                    .expect("infallible"),
+            )
+        })
+    });
+    group.bench_function("ShardedExpiringCache get", |b| {
+        b.iter(|| {
+            black_box(
+                sharded_expiring
+                    .cache_get(black_box(&next()))
+                    .expect("infallible")
+                    .is_some(),
+            )
+        })
+    });
+
+    let miss = N + 1;
+    group.bench_function("UnboundCache miss", |b| {
+        b.iter(|| black_box(unbound.cache_get(black_box(&miss)).is_none()))
+    });
+    group.bench_function("TtlCache miss", |b| {
+        b.iter(|| black_box(ttl.cache_get(black_box(&miss)).is_none()))
+    });
+    group.bench_function("ExpiringCache miss", |b| {
+        b.iter(|| black_box(expiring.cache_get(black_box(&miss)).is_none()))
+    });
+
+    group.bench_function("UnboundCache overwrite", |b| {
+        b.iter(|| black_box(unbound.cache_set(next(), 1)))
+    });
+    group.bench_function("TtlCache overwrite", |b| {
+        b.iter(|| black_box(ttl.cache_set(next(), 1)))
+    });
+    group.bench_function("ExpiringCache overwrite", |b| {
+        b.iter(|| {
+            black_box(
+                expiring
+                    .cache_set(next(), ExpiringValue { val: 1 })
+                    .is_some(),
+            )
+        })
+    });
+    group.bench_function("ShardedTtlCache overwrite", |b| {
+        b.iter(|| black_box(sharded_ttl.cache_set(next(), 1).expect("infallible")))
+    });
+    group.bench_function("ShardedExpiringCache overwrite", |b| {
+        b.iter(|| {
+            black_box(
+                sharded_expiring
+                    .cache_set(next(), ExpiringValue { val: 1 })
+                    .expect("infallible")
+                    .is_some(),
+            )
+        })
+    });
+
+    group.bench_function("UnboundCache remove + insert", |b| {
+        b.iter(|| {
+            let k = next();
+            black_box(unbound.cache_remove(&k));
+            unbound.cache_set(k, k);
+        })
+    });
+    group.bench_function("TtlCache remove + insert", |b| {
+        b.iter(|| {
+            let k = next();
+            black_box(ttl.cache_remove(&k));
+            ttl.cache_set(k, k);
+        })
+    });
+    group.bench_function("ExpiringCache remove + insert", |b| {
+        b.iter(|| {
+            let k = next();
+            black_box(expiring.cache_remove(&k).is_some());
+            expiring.cache_set(k, ExpiringValue { val: k });
+        })
+    });
+
+    group.finish();
+}
+
 fn bench_instant_now(c: &mut Criterion) {
     let mut group = c.benchmark_group("Clock cost");
     group.sample_size(50);
@@ -2184,8 +2439,10 @@ criterion_group!(
     bench_expiry_storm,
     bench_sharded_sweeps,
     bench_sharded_poll,
+    bench_sharded_per_key_path,
     bench_sharded_build_time,
     bench_large_value_lru,
+    bench_hash_map_stores,
     bench_instant_now,
 );
 criterion_main!(benches);
@@ -2,7 +2,7 @@ use crate::helpers::*;
 use darling::FromMeta;
 use darling::ast::NestedMeta;
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::spanned::Spanned;
 use syn::{Ident, ItemFn, ReturnType, Type, parse_macro_input, parse_str};
 
@@ -39,6 +39,10 @@ struct CachedMacroArgs {
     /// Mirrors the `max_size` builder/constructor naming on the cache stores.
     #[darling(default)]
     max_size: Option<usize>,
+    /// Eviction policy of the `max_size` bound: `"lru"` (the default, `LruCache`), `"arc"`
+    /// (`ArcCache`) or `"s3fifo"` (`S3FifoCache`). `None` = not specified.
+    #[darling(default)]
+    policy: Option<EvictionPolicy>,
     /// A cache TTL expressed as a `Duration` expression in a string literal
     /// (same convention as `create`/`convert`), e.g.
     /// `ttl = "core::time::Duration::from_secs(60)"`. Mutually exclusive with
@@ -129,6 +133,45 @@ struct CachedMacroArgs {
     /// function per concrete instantiation. See design record 0036.
     #[darling(default)]
     in_impl: bool,
+    /// Path of a `CacheSnapshot` file loaded into the store when the static is first
+    /// initialized, as a string literal or a `{ expr }` block. Emits a `{fn}_save_cache()`
+    /// companion that writes the store back to the same path and a `{fn}_restore_result()`
+    /// companion reporting the load. Requires the `persist` feature.
+    #[darling(default)]
+    persist_path: Option<PersistPath>,
+    /// Tags attached to every value this call caches, as an expression over the function
+    /// arguments yielding a slice-like collection of strings (`{ vec![format!("tenant:{}", t)] }`).
+    /// Stores through `CachedTags::set_with_tags`, so the store must implement `CachedTags`.
+    #[darling(default)]
+    tags: Option<syn::Expr>,
+    /// Per-entry TTL derived from the value being cached, as a `Duration` expression over
+    /// `result`, the value about to be stored (`{ compute_ttl(&result) }`). Stores through the
+    /// store's inherent `set_with_ttl`, so it needs a TTL store or a `ty`/`create` store
+    /// with that method.
+    #[darling(default)]
+    ttl_fn: Option<syn::Expr>,
+    /// Lifetime of a cached `None` (with `cache_none = true`), in the same three forms as
+    /// `ttl`. Stored through `set_with_ttl`, like `ttl_fn`.
+    #[darling(default)]
+    none_ttl: Option<TtlExpr>,
+    #[darling(default)]
+    none_ttl_secs: Option<u64>,
+    #[darling(default)]
+    none_ttl_millis: Option<u64>,
+    /// Lifetime of a cached `Err` (with `cache_err = true`); see `none_ttl`.
+    #[darling(default)]
+    err_ttl: Option<TtlExpr>,
+    #[darling(default)]
+    err_ttl_secs: Option<u64>,
+    #[darling(default)]
+    err_ttl_millis: Option<u64>,
+    /// Name the cache's statistics are published under through the `metrics` facade
+    /// (`cache = "<name>"`). Requires the `metrics` feature.
+    #[darling(default)]
+    metrics_name: Option<String>,
+    /// Add the cache to `cached::registry` when its static is first initialized.
+    #[darling(default)]
+    register: bool,
     // Removed attributes intercepted to provide helpful error messages
     #[darling(default)]
     result: Option<bool>,
@@ -136,6 +179,24 @@ struct CachedMacroArgs {
     option: Option<bool>,
 }
 
+/// `{fn}_save_cache` has to reach the cache static from module scope, which neither
+/// `in_impl` (function-local static) nor `companions = false` (no companions) allows.
+fn check_persist_path(args: &CachedMacroArgs, span: proc_macro2::Span) -> Result<(), syn::Error> {
+    if args.persist_path.is_none() {
+        return Ok(());
+    }
+    let message = if args.in_impl {
+        "`persist_path` is not supported with `in_impl = true`: the cache static is local \
+         to the method body, so no `{fn}_save_cache` companion can reach it"
+    } else if args.companions == Some(false) {
+        "`persist_path` requires companions: without `{fn}_save_cache` the snapshot could \
+         be loaded but never written. Remove `companions = false`"
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new(span, message))
+}
+
 fn default_sync_writes_buckets() -> usize {
     64
 }
@@ -167,6 +228,9 @@ fn check_create_conflicts(
     if args.max_size.is_some() {
         conflicting.push("max_size");
     }
+    if args.policy.is_some() {
+        conflicting.push("policy");
+    }
     if conflicting.is_empty() {
         return Ok(());
     }
@@ -324,6 +388,13 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         .into();
     }
 
+    if let Err(error) = check_persist_path(&args, fn_ident.span()) {
+        return error.to_compile_error().into();
+    }
+    if let Err(error) = check_register(args.register, args.in_impl, fn_ident.span()) {
+        return error.to_compile_error().into();
+    }
+
     // With `companions = false` and no `in_impl`, there is no companion item left for
     // `companions_vis` to apply to, so the value would be silently discarded. Reject the
     // inert pairing instead. Under `in_impl` the `{fn}_no_cache` sibling method survives
@@ -411,6 +482,7 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
     // `ttl_secs`/`ttl_millis` >= 1 validation, and parses the `ttl` expression.
     let (has_ttl, ttl_duration) = match resolve_ttl_duration(
         &krate,
+        "ttl",
         &args.ttl,
         args.ttl_secs,
         args.ttl_millis,
@@ -421,6 +493,46 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         Ok(v) => v,
         Err(e) => return e.to_compile_error().into(),
     };
+    if args.create.is_none()
+        && let Err(e) = check_policy(
+            args.policy,
+            args.max_size.is_some(),
+            has_ttl,
+            args.expires,
+            last_named_attr_span(&attr_args, &["policy"]).unwrap_or_else(attr_list_span),
+        )
+    {
+        return e.to_compile_error().into();
+    }
+
+    if args.ttl_fn.is_some() {
+        let ttl_fn_span =
+            last_named_attr_span(&attr_args, &["ttl_fn"]).unwrap_or_else(attr_list_span);
+        let message = if args.expires {
+            Some(
+                "`ttl_fn` and `expires` are mutually exclusive - `expires` reads each entry's \
+                 deadline from the value via the `Expires` trait",
+            )
+        } else if args.tags.is_some() {
+            Some(
+                "`ttl_fn` and `tags` cannot be combined - `tags` stores through \
+                 `CachedTags::set_with_tags`, which takes no per-entry TTL",
+            )
+        } else if !has_ttl && args.ty.is_none() && args.create.is_none() {
+            Some(
+                "`ttl_fn` requires a store with per-entry TTLs - set `ttl`/`ttl_secs`/\
+                 `ttl_millis` (a `TtlCache`, or an `LruTtlCache` with `max_size`), or name a \
+                 store with a `set_with_ttl` method through `ty`/`create`",
+            )
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return syn::Error::new(ttl_fn_span, message)
+                .to_compile_error()
+                .into();
+        }
+    }
 
     if args.time.is_some() {
         return syn::Error::new(
@@ -645,6 +757,36 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         .into();
     }
 
+    let negative_ttls = match resolve_negative_ttl(
+        &krate,
+        NegativeTtlAttr {
+            ttl: &args.none_ttl,
+            secs: args.none_ttl_secs,
+            millis: args.none_ttl_millis,
+            cached: args.cache_none,
+        },
+        NegativeTtlAttr {
+            ttl: &args.err_ttl,
+            secs: args.err_ttl_secs,
+            millis: args.err_ttl_millis,
+            cached: args.cache_err,
+        },
+        &NegativeTtlContext {
+            attr_args: &attr_args,
+            ttl_fn: args.ttl_fn.is_some(),
+            refresh: args.refresh,
+            tags: args.tags.is_some(),
+            none_within_ok: false,
+            store_hint: (!has_ttl && args.ty.is_none() && args.create.is_none()).then_some(
+                "set `ttl`/`ttl_secs`/`ttl_millis` (a `TtlCache`, or an `LruTtlCache` with \
+                 `max_size`), or name a store with a `set_with_ttl` method through `ty`/`create`",
+            ),
+        },
+    ) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
     // `is_smart_result`: cache only Ok, skip Err (default for Result returns; opt out with cache_err)
     // `is_smart_option`: cache only Some, skip None (default for Option returns; opt out with cache_none)
     let is_smart_result = is_result_return && !args.cache_err;
@@ -726,6 +868,16 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
             &args.create,
             &args.refresh,
         ) {
+            (Some(size), false, None, None, _) if args.policy == Some(EvictionPolicy::Arc) => {
+                let cache_ty = quote! {#krate::ArcCache<#cache_key_ty, #cache_value_ty>};
+                let cache_create = quote! {#krate::ArcCache::builder().max_size(#size).build().unwrap_or_else(|e| panic!("ArcCache build failed in #[cached]: {e}"))};
+                (cache_ty, cache_create)
+            }
+            (Some(size), false, None, None, _) if args.policy == Some(EvictionPolicy::S3Fifo) => {
+                let cache_ty = quote! {#krate::S3FifoCache<#cache_key_ty, #cache_value_ty>};
+                let cache_create = quote! {#krate::S3FifoCache::builder().max_size(#size).build().unwrap_or_else(|e| panic!("S3FifoCache build failed in #[cached]: {e}"))};
+                (cache_ty, cache_create)
+            }
             (Some(size), false, None, None, _) => {
                 let cache_ty = quote! {#krate::LruCache<#cache_key_ty, #cache_value_ty>};
                 let cache_create = quote! {#krate::LruCache::builder().max_size(#size).build().unwrap_or_else(|e| panic!("LruCache build failed in #[cached]: {e}"))};
@@ -786,6 +938,22 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     };
 
+    // `persist_path`: load the snapshot while the static initializes, before the lock
+    // wraps the store, so the file is read exactly once. The outcome is kept for
+    // `{fn}_restore_result`.
+    let restore_ident = format_ident!("{}_RESTORED", cache_ident);
+    let cache_create = match &args.persist_path {
+        Some(path) => {
+            let path = path.tokens();
+            quote! {{
+                let mut __cached_store = #cache_create;
+                let _ = #restore_ident.set(#krate::__private::persist_restore(&mut __cached_store, #path));
+                __cached_store
+            }}
+        }
+        None => cache_create,
+    };
+
     // make the set cache and return cache blocks
     //
     // The generated cache internals clone the value into the store on `cache_set`
@@ -809,21 +977,57 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
     // trait, E0782). Naming the trait in the path keeps nothing in the block's namespace,
     // matching the fully-qualified `#krate::CachedRead::cache_get_read` /
     // `#krate::CachedPeek::cache_peek` calls the rest of the codegen already uses.
+    // `tags`: evaluated next to the key, before the body runs, because the body takes the
+    // arguments by value and the set happens after it returns.
+    let tags_binding = match &args.tags {
+        Some(expr) => {
+            let tags = expr_value_tokens(expr);
+            quote! { let __cached_tags = #tags; }
+        }
+        None => quote! {},
+    };
+    // `ttl_fn`: the one store call that is not a trait method. `set_with_ttl` is inherent on
+    // the TTL stores, so the method call brings nothing into scope either.
     let cache_set_call = |key: proc_macro2::TokenStream, value: proc_macro2::TokenStream| {
-        quote! { #krate::Cached::cache_set(&mut *__cached_cache, #key, #value); }
+        if let Some(ttl_fn) = &args.ttl_fn {
+            let ttl_binding = ttl_fn_binding(ttl_fn, &value);
+            quote! {{
+                #ttl_binding
+                __cached_cache.set_with_ttl(#key, result, __cached_ttl);
+            }}
+        } else if args.tags.is_some() {
+            quote! { #krate::CachedTags::set_with_tags(&mut *__cached_cache, #key, #value, &__cached_tags); }
+        } else {
+            quote! { #krate::Cached::cache_set(&mut *__cached_cache, #key, #value); }
+        }
     };
     let cache_get_call = quote! {
         #krate::Cached::cache_get(&mut *__cached_cache, &__cached_key)
     };
 
+    // `metrics_name`: the call counts its own hits and misses (the store's counters, which
+    // `publish` would otherwise turn into the same counters, are masked out) and times the
+    // body on a miss.
+    let metrics = metrics_tokens(&krate, args.metrics_name.as_deref());
+    let metrics_record_hit = &metrics.record_hit;
+    let hit_outcome = call_outcome(&krate, "hit");
+    let record_hit = &quote! { #metrics_record_hit #hit_outcome };
+
     let (set_cache_block, return_cache_block) = match (is_smart_result, is_smart_option) {
         (false, false) => {
             let set = cache_set_call(quote! { __cached_key }, clone_owned.clone());
-            let set_cache_block = quote! { #set };
+            // `none_ttl` / `err_ttl`: only here, where `cache_none` / `cache_err` make the
+            // whole `Option` / `Result` the cached value.
+            let set_cache_block = NegativeTtl::set_or(
+                &negative_ttls,
+                quote! { __cached_result },
+                |ttl| quote! { __cached_cache.set_with_ttl(__cached_key, #clone_owned, #ttl); },
+                set,
+            );
             let return_cache_block = if args.with_cached_flag {
-                quote! { let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return __cached_r }
+                quote! { #record_hit let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return __cached_r }
             } else {
-                quote! { return #clone_borrowed }
+                quote! { #record_hit return #clone_borrowed }
             };
             (set_cache_block, return_cache_block)
         }
@@ -835,9 +1039,9 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
                 }
             };
             let return_cache_block = if args.with_cached_flag {
-                quote! { let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Ok(__cached_r) }
+                quote! { #record_hit let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Ok(__cached_r) }
             } else {
-                quote! { return Ok(#clone_borrowed) }
+                quote! { #record_hit return Ok(#clone_borrowed) }
             };
             (set_cache_block, return_cache_block)
         }
@@ -849,9 +1053,9 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
                 }
             };
             let return_cache_block = if args.with_cached_flag {
-                quote! { let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Some(__cached_r) }
+                quote! { #record_hit let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Some(__cached_r) }
             } else {
-                quote! { return Some(#clone_borrowed) }
+                quote! { #record_hit return Some(#clone_borrowed) }
             };
             (set_cache_block, return_cache_block)
         }
@@ -925,13 +1129,21 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         .into();
     }
 
-    if args.unsync_reads && args.ty.is_none() && (args.max_size.is_some() || has_ttl) {
+    // `S3FifoCache` counts a hit with an atomic, so it is the one `max_size` store that
+    // serves reads through `&self`.
+    let reads_by_ref = args.policy == Some(EvictionPolicy::S3Fifo) && !has_ttl;
+    if args.unsync_reads
+        && args.ty.is_none()
+        && (args.max_size.is_some() || has_ttl)
+        && !reads_by_ref
+    {
         return syn::Error::new(
             fn_ident.span(),
             "`unsync_reads` requires a store that implements `CachedRead` (reads take a shared \
-             lock, so they must not mutate the store). `LruCache`, `LruTtlCache`, and `TtlCache` \
-             do not implement `CachedRead` (their reads update LRU recency or TTL bookkeeping). \
-             Use the default store (`UnboundCache`), `TtlSortedCache`, or a custom `ty` that \
+             lock, so they must not mutate the store). `LruCache`, `ArcCache`, `LruTtlCache`, and \
+             `TtlCache` do not implement `CachedRead` (their reads update LRU recency or TTL \
+             bookkeeping). Use the default store (`UnboundCache`), `TtlSortedCache`, \
+             `max_size` with `policy = \"s3fifo\"` (`S3FifoCache`), or a custom `ty` that \
              implements `CachedRead`.",
         )
         .to_compile_error()
@@ -942,6 +1154,19 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         #set_cache_block
         __cached_result
     };
+    // What the cached fn itself runs on a miss; the prime companion keeps the plain forms
+    // above, since priming is neither a lookup nor a miss.
+    let publish = match &metrics.exporter {
+        Some(exporter) => quote! {
+            #krate::__private::publish_store_metrics(&#exporter, || #krate::CachedExt::metrics(&*__cached_cache));
+        },
+        None => quote! {},
+    };
+    let set_cache_publish_and_return = quote! {
+        #set_cache_block
+        #publish
+        __cached_result
+    };
 
     let use_rwlock = match args.sync_lock {
         Some(SyncLock::RwLock) => true,
@@ -979,6 +1204,38 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! {}
     };
 
+    // `register`: the registry's accessors are synchronous, so on an async fn they take the
+    // async lock with its blocking methods.
+    let cache_create = if args.register {
+        let (read, write) = match (use_rwlock, asyncness.is_some()) {
+            (true, false) => (quote! { read }, quote! { write }),
+            (true, true) => (quote! { read_blocking }, quote! { write_blocking }),
+            (false, false) => (quote! { lock }, quote! { lock }),
+            (false, true) => (quote! { lock_blocking }, quote! { lock_blocking }),
+        };
+        let as_cached = quote! { <#cache_ty as #krate::Cached<#cache_key_ty, #cache_value_ty>> };
+        let accessors = RegistryAccessors {
+            metrics: quote! {
+                <#cache_ty as #krate::CachedExt<#cache_key_ty, #cache_value_ty>>::metrics(&*#cache_ident.#read())
+            },
+            len: quote! { ::core::option::Option::Some(#as_cached::cache_size(&*#cache_ident.#read())) },
+            clear: quote! {{
+                #as_cached::cache_clear(&mut *#cache_ident.#write());
+                ::core::result::Result::Ok(())
+            }},
+        };
+        register_on_create(
+            &krate,
+            &fn_ident,
+            "Locked",
+            &cache_ty,
+            &accessors,
+            cache_create,
+        )
+    } else {
+        cache_create
+    };
+
     let no_cache_fn_ident = Ident::new(&format!("{}_no_cache", &fn_ident), fn_ident.span());
 
     // When the cached fn is a method (`in_impl`), the origin/no-cache fn is also
@@ -1033,6 +1290,20 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         };
     }
 
+    let miss_outcome = call_outcome(&krate, "miss");
+    let timed_function_call = match &metrics.exporter {
+        Some(exporter) => quote! {
+            #miss_outcome
+            let __cached_started = #krate::time::Instant::now();
+            #function_call
+            #krate::__private::record_miss(&#exporter, __cached_started);
+        },
+        None => quote! {
+            #miss_outcome
+            #function_call
+        },
+    };
+
     // `force_refresh`: an opt-in boolean expression block over the fn args,
     // written in curly braces like `convert` (e.g. `force_refresh = "{ id == 0 }"`).
     // When it evaluates `true`, the cached-hit early return is skipped so the body
@@ -1125,9 +1396,9 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
                     {
                         #by_key_cache_get_return_block
                     }
-                    #function_call
+                    #timed_function_call
                     let mut __cached_cache = __cached_cache_mutex.#lock_method()#await_if_async;
-                    #set_cache_and_return
+                    #set_cache_publish_and_return
                 }
             }
             SyncWriteMode::Default => {
@@ -1179,8 +1450,8 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
                                 #return_cache_block
                             }
                         }
-                        #function_call
-                        #set_cache_and_return
+                        #timed_function_call
+                        #set_cache_publish_and_return
                     }
                 } else {
                     quote! {
@@ -1190,8 +1461,8 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
                                 #return_cache_block
                             }
                         }
-                        #function_call
-                        #set_cache_and_return
+                        #timed_function_call
+                        #set_cache_publish_and_return
                     }
                 }
             }
@@ -1241,6 +1512,7 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
                     };
                     // Evaluate the `force_refresh` predicate once: `#force_refresh_guard`
                     // is `if !(block)`, so `if !(block) { false } else { true }` == `block`.
+                    let stale_outcome = call_outcome(&krate, "stale");
                     let force_refreshing_flag = if args.force_refresh.is_some() {
                         quote! { let __cached_force_refreshing = #force_refresh_guard { false } else { true }; }
                     } else {
@@ -1257,33 +1529,38 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
                             #capture_old_val
                             __cached_old_val
                         };
-                        #function_call
+                        #timed_function_call
                         let mut __cached_cache = #cache_ident.#lock_method()#await_if_async;
                         // Read the fallback under the SAME lock that the write below takes. The
                         // cache lock is released while the body runs, so a concurrent call may
                         // have stored a newer `Ok` in the meantime; falling back to a pre-body
                         // snapshot would overwrite that newer value with a stale one. Peeking
                         // is non-renewing and still returns expired entries, which is what a
-                        // stale fallback needs.
+                        // stale fallback needs. An `Expires` value can refuse to be served
+                        // (`serve_stale_on_error`), e.g. past its `stale-if-error` window.
                         let __cached_result = if __cached_result.is_err() {
+                            use #krate::__stale_dispatch::StaleDispatchFallback as _;
                             let (__cached_fallback, _) = #krate::CloneCached::cache_peek_with_expiry_status(&*__cached_cache, &__cached_key);
                             match __cached_fallback {
-                                Some(__cached_fallback) => Ok(__cached_fallback),
-                                None => __cached_result,
+                                Some(__cached_fallback) if #krate::__stale_dispatch::StaleDispatch(&__cached_fallback).serve_stale_on_error() => {
+                                    #stale_outcome
+                                    Ok(__cached_fallback)
+                                }
+                                _ => __cached_result,
                             }
                         } else {
                             __cached_result
                         };
-                        #set_cache_and_return
+                        #set_cache_publish_and_return
                     }
                 } else {
                     quote! {
                         {
                             #cache_get_return_block
                         }
-                        #function_call
+                        #timed_function_call
                         let mut __cached_cache = #cache_ident.#lock_method()#await_if_async;
-                        #set_cache_and_return
+                        #set_cache_publish_and_return
                     }
                 }
             }
@@ -1371,11 +1648,47 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
             #companions_visibility #prime_sig {
                 #body_static
                 let __cached_key = #key_convert_block;
+                #tags_binding
                 #prime_do_set_return_block
             }
         }
     };
 
+    // `persist_path`: `{fn}_save_cache` takes the read lock (a snapshot only clones) and
+    // writes the store to the path the static was restored from; `{fn}_restore_result`
+    // reports how that restore went.
+    let (save_fn, persist_feature_guard) = match &args.persist_path {
+        Some(persist_path) => {
+            let path = persist_path.tokens();
+            let save_fn_ident = Ident::new(&format!("{}_save_cache", &fn_ident), fn_ident.span());
+            let save_fn_doc = format!(
+                "Writes a snapshot of the cache behind [`{fn_ident}`] to {}, returning \
+                 the number of entries saved.",
+                persist_path.describe()
+            );
+            let restore_fn = persist_restore_result_fn(
+                &krate,
+                &fn_ident,
+                &restore_ident,
+                &static_cfg_attrs,
+                &companions_visibility,
+            );
+            let save_fn_asyncness = asyncness.map(|_| quote! { async });
+            let save_fn = quote! {
+                #(#static_cfg_attrs)*
+                #[doc = #save_fn_doc]
+                #[allow(dead_code)]
+                #companions_visibility #save_fn_asyncness fn #save_fn_ident() -> ::std::result::Result<usize, #krate::PersistError> {
+                    let __cached_cache = #cache_ident.#read_lock_method()#await_if_async;
+                    #krate::__private::persist_save(&*__cached_cache, #path)
+                }
+                #restore_fn
+            };
+            (save_fn, quote! { #krate::__require_persist_feature!{} })
+        }
+        None => (quote! {}, quote! {}),
+    };
+
     // On the `in_impl` path the `{fn}_no_cache` origin is a public impl method, so
     // it would otherwise surface in consumers' rustdoc as unintended API. Hide it
     // with `#[doc(hidden)]` (it stays callable as an escape hatch). Off `in_impl`
@@ -1427,10 +1740,25 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! {}
     };
 
+    let metrics_static = &metrics.static_item;
+    let metrics_feature_guard = &metrics.feature_guard;
+    let call_body = call_span(
+        &krate,
+        &fn_ident,
+        Some(quote! { &__cached_key }),
+        asyncness.is_some(),
+        quote! {
+            #tags_binding
+            #do_set_return_block
+        },
+    );
+
     // put it all together
     let expanded = quote! {
         #async_feature_guard
         #time_stores_guard
+        #persist_feature_guard
+        #metrics_feature_guard
         // Cached static (module scope unless `in_impl`)
         #module_static
         // No cache function (origin of the cached function); nested inside the
@@ -1440,12 +1768,15 @@ pub fn cached(args: TokenStream, input: TokenStream) -> TokenStream {
         #(#attributes)*
         #visibility #signature_no_muts {
             #body_static
+            #metrics_static
             #nested_origin_fn
             let __cached_key = #key_convert_block;
-            #do_set_return_block
+            #call_body
         }
         // Prime cached function (omitted for `in_impl` methods and `companions = false`)
         #prime_fn
+        // Snapshot writer (only with `persist_path`)
+        #save_fn
     };
 
     expanded.into()
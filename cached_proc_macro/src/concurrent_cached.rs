@@ -2,7 +2,7 @@ use crate::helpers::*;
 use darling::FromMeta;
 use darling::ast::NestedMeta;
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::spanned::Spanned;
 use syn::{GenericArgument, Ident, ItemFn, ReturnType, Type, parse_macro_input, parse_str};
 
@@ -71,6 +71,11 @@ struct ConcurrentCachedArgs {
     /// Only meaningful when `redis=false`, `disk=false`, and `create` is not set.
     #[darling(default)]
     max_size: Option<usize>,
+    /// Eviction policy of the `max_size` bound: `"lru"` (the default, `ShardedLruCache`),
+    /// `"arc"` (`ShardedArcCache`) or `"s3fifo"` (`ShardedS3FifoCache`). Same restrictions as
+    /// `max_size`.
+    #[darling(default)]
+    policy: Option<EvictionPolicy>,
     /// Number of shards for the default in-memory sharded store.
     /// Only meaningful when `redis=false`, `disk=false`, and `create` is not set.
     #[darling(default)]
@@ -109,6 +114,39 @@ struct ConcurrentCachedArgs {
     /// or memoize a free function per concrete instantiation. See design record 0036.
     #[darling(default)]
     in_impl: bool,
+    /// Path of a `CacheSnapshot` file, as a string literal or a `{ expr }` block: the store
+    /// restores it when the static is first initialized, a `{fn}_save_cache()` companion
+    /// writes the current contents back, and `{fn}_restore_result()` reports the restore.
+    /// In-memory stores only; requires the `persist` feature.
+    #[darling(default)]
+    persist_path: Option<PersistPath>,
+    /// Name the cache's statistics are published under through the `metrics` facade
+    /// (`cache = "<name>"`). Requires the `metrics` feature.
+    #[darling(default)]
+    metrics_name: Option<String>,
+    /// Add the cache to `cached::registry` when its static is first initialized.
+    #[darling(default)]
+    register: bool,
+    /// Per-entry TTL derived from the value being cached, as a `Duration` expression over
+    /// `result`, the value about to be stored. In-memory TTL stores (or a `ty`/`create` store
+    /// with an inherent `set_with_ttl(&self, ..)`) only.
+    #[darling(default)]
+    ttl_fn: Option<syn::Expr>,
+    /// Lifetime of a cached `None` (with `cache_none = true`), in the same three forms as
+    /// `ttl`. Stored through `set_with_ttl`, like `ttl_fn`.
+    #[darling(default)]
+    none_ttl: Option<TtlExpr>,
+    #[darling(default)]
+    none_ttl_secs: Option<u64>,
+    #[darling(default)]
+    none_ttl_millis: Option<u64>,
+    /// Lifetime of a cached `Err` (with `cache_err = true`); see `none_ttl`.
+    #[darling(default)]
+    err_ttl: Option<TtlExpr>,
+    #[darling(default)]
+    err_ttl_secs: Option<u64>,
+    #[darling(default)]
+    err_ttl_millis: Option<u64>,
 }
 
 /// When a `create` block is supplied the user fully constructs the store, so
@@ -145,6 +183,9 @@ fn check_create_conflicts(
     if args.max_size.is_some() {
         conflicting.push("max_size");
     }
+    if args.policy.is_some() {
+        conflicting.push("policy");
+    }
     if args.shards.is_some() {
         conflicting.push("shards");
     }
@@ -166,6 +207,31 @@ fn check_create_conflicts(
     ))
 }
 
+/// `persist_path` needs an in-memory store to snapshot and a module-level static for
+/// `{fn}_save_cache` to reach, so it is rejected wherever either is missing.
+fn check_persist_path(
+    args: &ConcurrentCachedArgs,
+    companions: bool,
+    span: proc_macro2::Span,
+) -> Result<(), syn::Error> {
+    if args.persist_path.is_none() {
+        return Ok(());
+    }
+    let message = if args.redis || args.disk {
+        "`persist_path` only applies to the in-memory stores; `redis` and `disk` \
+         caches already persist their entries"
+    } else if args.in_impl {
+        "`persist_path` is not supported with `in_impl = true`: the cache static is \
+         local to the method body, so no `{fn}_save_cache` companion can reach it"
+    } else if !companions {
+        "`persist_path` requires companions: without `{fn}_save_cache` the snapshot \
+         could be loaded but never written. Remove `companions = false`"
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new(span, message))
+}
+
 fn reject_cached_only_attrs(attr_args: &[NestedMeta]) -> Result<(), syn::Error> {
     for arg in attr_args {
         let Some(meta) = (match arg {
@@ -317,6 +383,23 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         .into();
     }
 
+    if let Err(error) = check_persist_path(&args, companions, fn_ident.span()) {
+        return error.to_compile_error().into();
+    }
+    if let Err(error) = check_register(args.register, args.in_impl, fn_ident.span()) {
+        return error.to_compile_error().into();
+    }
+    if args.register && args.redis && asyncness.is_some() {
+        return syn::Error::new(
+            fn_ident.span(),
+            "`register` is not supported on an async `redis = true` function: \
+             `AsyncRedisCache` can only be cleared asynchronously, and the registry's \
+             accessors are synchronous",
+        )
+        .to_compile_error()
+        .into();
+    }
+
     // Generic functions need the cache key pinned to a concrete type via
     // `key` + `convert` (and a concrete store `ty`/`create`): the cache is a
     // single monomorphic static and cannot name the function's type parameters.
@@ -408,6 +491,7 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
     // (A zero `ttl_secs`/`ttl_millis` is rejected here, before any store path runs.)
     let (has_ttl, ttl_duration) = match resolve_ttl_duration(
         &krate,
+        "ttl",
         &args.ttl,
         args.ttl_secs,
         args.ttl_millis,
@@ -419,6 +503,35 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         Err(e) => return e.to_compile_error().into(),
     };
 
+    if args.ttl_fn.is_some() {
+        let ttl_fn_span =
+            last_named_attr_span(&attr_args, &["ttl_fn"]).unwrap_or_else(attr_list_span);
+        let message = if args.redis || args.disk {
+            Some(
+                "`ttl_fn` only applies to the in-memory TTL stores, not `redis = true` or \
+                 `disk = true`",
+            )
+        } else if args.expires {
+            Some(
+                "`ttl_fn` and `expires` are mutually exclusive - `expires` reads each entry's \
+                 deadline from the value via the `Expires` trait",
+            )
+        } else if !has_ttl && args.ty.is_none() && args.create.is_none() {
+            Some(
+                "`ttl_fn` requires a store with per-entry TTLs - set `ttl`/`ttl_secs`/\
+                 `ttl_millis` (a `ShardedTtlCache`, or a `ShardedLruTtlCache` with \
+                 `max_size`), or name a store with a `set_with_ttl` method through `ty`/`create`",
+            )
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return syn::Error::new(ttl_fn_span, message)
+                .to_compile_error()
+                .into();
+        }
+    }
+
     if args.expires {
         if args.redis {
             return syn::Error::new(
@@ -517,7 +630,19 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         .to_compile_error()
         .into();
     }
-    if args.cache_none && !is_option_return {
+    // On redis/disk the function returns `Result` so store errors have somewhere to go;
+    // `cache_none` there covers the `Ok(None)` of a `Result<Option<T>, E>`.
+    let io_store = args.redis || args.disk;
+    if args.cache_none && io_store && !is_result_option_return_type(&output) {
+        return syn::Error::new(
+            fn_ident.span(),
+            "`cache_none = true` with `redis = true` or `disk = true` requires the function to \
+             return `Result<Option<T>, E>` - the store's errors are returned through the `Err`",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if args.cache_none && !io_store && !is_option_return {
         return syn::Error::new(
             fn_ident.span(),
             "`cache_none = true` requires the function to return `Option<T>`",
@@ -795,6 +920,14 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
                 .to_compile_error()
                 .into();
             }
+            if args.policy.is_some() {
+                return syn::Error::new(
+                    fn_ident.span(),
+                    "`policy` only applies to the default in-memory store, not `redis = true`",
+                )
+                .to_compile_error()
+                .into();
+            }
             match get_redis_cache_type_and_create(
                 &args,
                 &krate,
@@ -825,6 +958,14 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
                 .to_compile_error()
                 .into();
             }
+            if args.policy.is_some() {
+                return syn::Error::new(
+                    fn_ident.span(),
+                    "`policy` only applies to the default in-memory store, not `disk = true`",
+                )
+                .to_compile_error()
+                .into();
+            }
             match get_disk_cache_type_and_create(
                 &args,
                 &krate,
@@ -874,13 +1015,72 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     };
 
-    // cache_none / cache_err are only valid for the in-memory sharded default path; give
-    // targeted errors before the generic non-Result check below so the message names the
-    // offending attribute rather than the return type.
-    if args.cache_none && !infallible_default {
+    // `persist_path`: restore the saved snapshot inside the static's initializer, so the
+    // file is read once, on first use, by whichever call initializes the cache. The outcome
+    // is kept for `{fn}_restore_result`.
+    let restore_ident = format_ident!("{}_RESTORED", cache_ident);
+    let cache_create = match &args.persist_path {
+        Some(path) => {
+            let path = path.tokens();
+            quote! {{
+                let __cached_store = #cache_create;
+                let _ = #restore_ident.set(#krate::__private::persist_restore_concurrent(&__cached_store, #path));
+                __cached_store
+            }}
+        }
+        None => cache_create,
+    };
+
+    // `register`: an async fn's static is a `OnceCell` that may not be set yet; until it is,
+    // the registry sees an empty store (the cache registers from inside the initializer).
+    let cache_create = if args.register {
+        let store = if asyncness.is_some() {
+            quote! { #cache_ident.get() }
+        } else {
+            quote! { ::core::option::Option::Some(&*#cache_ident) }
+        };
+        let accessors = RegistryAccessors {
+            metrics: quote! {
+                #store
+                    .map(<#cache_ty as #krate::ConcurrentCacheBase>::metrics)
+                    .unwrap_or_default()
+            },
+            len: quote! {
+                #store.and_then(|__cached_store| {
+                    <#cache_ty as #krate::ConcurrentCacheBase>::cache_size(__cached_store).ok().flatten()
+                })
+            },
+            clear: quote! {
+                match #store {
+                    ::core::option::Option::Some(__cached_store) => {
+                        <#cache_ty as #krate::ConcurrentCached<#cache_key_ty, #cache_value_ty>>::cache_clear(__cached_store)
+                            .map_err(::core::convert::Into::into)
+                    }
+                    ::core::option::Option::None => ::core::result::Result::Ok(()),
+                }
+            },
+        };
+        register_on_create(
+            &krate,
+            &fn_ident,
+            "Concurrent",
+            &cache_ty,
+            &accessors,
+            cache_create,
+        )
+    } else {
+        cache_create
+    };
+
+    // cache_none / cache_err are valid for the in-memory sharded default path and for
+    // redis/disk, whose stores have `set_with_ttl` for `none_ttl` / `err_ttl`; give targeted
+    // errors before the generic non-Result check below so the message names the offending
+    // attribute rather than the return type.
+    if args.cache_none && !infallible_default && !io_store {
         return syn::Error::new(
             fn_ident.span(),
-            "`cache_none = true` is only supported for the default in-memory sharded stores",
+            "`cache_none = true` is only supported for the default in-memory sharded stores \
+             and `redis = true` / `disk = true`",
         )
         .to_compile_error()
         .into();
@@ -907,15 +1107,49 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         .into();
     }
 
-    if args.cache_err && !infallible_default {
+    if args.cache_err && !infallible_default && !io_store {
         return syn::Error::new(
             fn_ident.span(),
-            "`cache_err = true` is only supported for the default in-memory sharded stores",
+            "`cache_err = true` is only supported for the default in-memory sharded stores \
+             and `redis = true` / `disk = true`",
         )
         .to_compile_error()
         .into();
     }
 
+    // `cache_none` / `cache_err` have pinned the store to the in-memory sharded default or to
+    // redis/disk by now. Redis and redb stores all have `set_with_ttl`; of the in-memory ones
+    // only the two TTL stores do.
+    let negative_ttls = match resolve_negative_ttl(
+        &krate,
+        NegativeTtlAttr {
+            ttl: &args.none_ttl,
+            secs: args.none_ttl_secs,
+            millis: args.none_ttl_millis,
+            cached: args.cache_none,
+        },
+        NegativeTtlAttr {
+            ttl: &args.err_ttl,
+            secs: args.err_ttl_secs,
+            millis: args.err_ttl_millis,
+            cached: args.cache_err,
+        },
+        &NegativeTtlContext {
+            attr_args: &attr_args,
+            ttl_fn: args.ttl_fn.is_some(),
+            refresh: args.refresh,
+            tags: false,
+            none_within_ok: args.cache_none && args.cache_err,
+            store_hint: (infallible_default && !has_ttl).then_some(
+                "set `ttl`/`ttl_secs`/`ttl_millis` (a `ShardedTtlCache`, or a \
+                 `ShardedLruTtlCache` with `max_size`)",
+            ),
+        },
+    ) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
     if args.result_fallback && !infallible_default {
         return syn::Error::new(
             fn_ident.span(),
@@ -1056,8 +1290,20 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
     // shadow a user item the way a named `use ::cached::Cached;` would. It is also
     // confined to this block, which contains no user tokens. Every other trait the
     // codegen calls is named through a fully-qualified path for the same reason.
+    //
+    // `ttl_fn` bypasses the shim: `set_with_ttl` is an inherent method of the in-memory TTL
+    // stores, which take the value owned and are infallible.
     let set_call = |value_ref: proc_macro2::TokenStream| {
-        if asyncness.is_some() {
+        if let Some(ttl_fn) = &args.ttl_fn {
+            let value = clone_cached_value(&cache_value_ty, output_span, value_ref);
+            let ttl_binding = ttl_fn_binding(ttl_fn, &value);
+            quote! {
+                {
+                    #ttl_binding
+                    __cached_cache.set_with_ttl(__cached_key, result, __cached_ttl);
+                }
+            }
+        } else if asyncness.is_some() {
             quote! {
                 {
                     use #krate::__set_dispatch_async::SetDispatchAsyncFallback as _;
@@ -1076,6 +1322,32 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     };
 
+    // The `none_ttl` / `err_ttl` set. `set_with_ttl` is infallible on the in-memory TTL stores
+    // and returns the store error on redis/disk, where an async fn's `RedbCache` spells it
+    // `async_set_with_ttl`.
+    let negative_set = |value: proc_macro2::TokenStream, ttl: &proc_macro2::TokenStream| {
+        if infallible_default {
+            quote! { __cached_cache.set_with_ttl(__cached_key, #value, #ttl); }
+        } else if asyncness.is_some() && args.disk {
+            quote! {
+                __cached_cache.async_set_with_ttl(__cached_key, #value, #ttl).await #cache_set_unwrap_async;
+            }
+        } else if asyncness.is_some() {
+            quote! {
+                __cached_cache.set_with_ttl(__cached_key, #value, #ttl).await #cache_set_unwrap_async;
+            }
+        } else {
+            quote! { __cached_cache.set_with_ttl(__cached_key, #value, #ttl) #cache_set_unwrap; }
+        }
+    };
+
+    // `metrics_name`: as on `#[cached]`, the call counts its own hits and misses and times
+    // the body on a miss.
+    let metrics = metrics_tokens(&krate, args.metrics_name.as_deref());
+    let metrics_record_hit = &metrics.record_hit;
+    let hit_outcome = call_outcome(&krate, "hit");
+    let record_hit = &quote! { #metrics_record_hit #hit_outcome };
+
     // make the set cache and return cache blocks
     let (set_cache_block, return_cache_block) = if with_cached_flag_result {
         // Result<Return<T>, E>: cache the inner T from Ok(Return<T>).
@@ -1086,7 +1358,7 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
                     #set
                 }
             },
-            quote! { let mut __cached_r = #krate::Return::new(__cached_result); __cached_r.set_was_cached(true); return Ok(__cached_r) },
+            quote! { #record_hit let mut __cached_r = #krate::Return::new(__cached_result); __cached_r.set_was_cached(true); return Ok(__cached_r) },
         )
     } else if with_cached_flag_option {
         // Option<Return<T>>: cache the inner T from Some(Return<T>), skip None.
@@ -1097,25 +1369,38 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
                     #set
                 }
             },
-            quote! { let mut __cached_r = #krate::Return::new(__cached_result); __cached_r.set_was_cached(true); return Some(__cached_r) },
+            quote! { #record_hit let mut __cached_r = #krate::Return::new(__cached_result); __cached_r.set_was_cached(true); return Some(__cached_r) },
         )
     } else if args.with_cached_flag {
         // Plain Return<T>: cache the inner T directly.
         let set = set_call(quote! { &*__cached_result });
         (
             set,
-            quote! { let mut __cached_r = #krate::Return::new(__cached_result); __cached_r.set_was_cached(true); return __cached_r },
+            quote! { #record_hit let mut __cached_r = #krate::Return::new(__cached_result); __cached_r.set_was_cached(true); return __cached_r },
         )
     } else if is_smart_result {
         // Result<T, E> return type: cache only Ok(T), skip Err
         let set = set_call(quote! { __cached_inner });
+        // `none_ttl` on redis/disk, where `cache_none` covers the `Ok(None)` of a
+        // `Result<Option<T>, E>`.
+        let set = NegativeTtl::set_or(
+            &negative_ttls,
+            quote! { __cached_inner },
+            |ttl| {
+                negative_set(
+                    clone_cached_value(&cache_value_ty, output_span, quote! { __cached_inner }),
+                    ttl,
+                )
+            },
+            set,
+        );
         (
             quote! {
                 if let Ok(__cached_inner) = &__cached_result {
                     #set
                 }
             },
-            quote! { return Ok(__cached_result) },
+            quote! { #record_hit return Ok(__cached_result) },
         )
     } else if is_smart_option {
         // Option<T>: cache Some(T), skip None. infallible_default guaranteed.
@@ -1126,13 +1411,25 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
                     #set
                 }
             },
-            quote! { return Some(__cached_result) },
+            quote! { #record_hit return Some(__cached_result) },
         )
     } else {
-        // Plain return type - infallible_default is guaranteed true here.
+        // Plain return type - the in-memory sharded default, or `cache_err` on redis/disk.
         // No Ok/Err wrapping: the result is the value directly.
         let set = set_call(quote! { &__cached_result });
-        (set, quote! { return __cached_result })
+        // `cache_none` / `cache_err` make the whole `Option` / `Result` the cached value here.
+        let set = NegativeTtl::set_or(
+            &negative_ttls,
+            quote! { __cached_result },
+            |ttl| {
+                negative_set(
+                    clone_cached_value(&cache_value_ty, output_span, quote! { &__cached_result }),
+                    ttl,
+                )
+            },
+            set,
+        );
+        (set, quote! { #record_hit return __cached_result })
     };
 
     // Clone the full original signature and rename it to `__cached_inner`. Quoting
@@ -1214,72 +1511,120 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
     // the non-renewing `cache_peek_with_expiry_status` so the bypassed entry has no read side
     // effects (#146); a genuine (non-bypass) hit still uses the renewing
     // `cache_get_with_expiry_status` and takes the early `#return_cache_block`.
-    let do_set_return_block = if args.result_fallback && asyncness.is_some() {
-        quote! {
-            #inner_nested_def
-            let __cached_cache = #cache_ident.get_or_init(|| async { #cache_create }).await;
-            let __cached_old_val = if #force_refresh_bypass {
-                // Bypassing this entry: peek for the stale fallback without side effects.
-                let (__cached_stale, _) = #krate::ConcurrentCloneCached::cache_peek_with_expiry_status(__cached_cache, &__cached_key);
-                __cached_stale
+    let stale_outcome = call_outcome(&krate, "stale");
+    let miss_outcome = call_outcome(&krate, "miss");
+    let build_do_set_return_block =
+        |start: &proc_macro2::TokenStream,
+         missed: &proc_macro2::TokenStream,
+         publish: &proc_macro2::TokenStream| {
+            if args.result_fallback && asyncness.is_some() {
+                quote! {
+                    #inner_nested_def
+                    let __cached_cache = #cache_ident.get_or_init(|| async { #cache_create }).await;
+                    let __cached_old_val = if #force_refresh_bypass {
+                        // Bypassing this entry: peek for the stale fallback without side effects.
+                        let (__cached_stale, _) = #krate::ConcurrentCloneCached::cache_peek_with_expiry_status(__cached_cache, &__cached_key);
+                        __cached_stale
+                    } else {
+                        let (__cached_val, __cached_expired) = #krate::ConcurrentCloneCached::cache_get_with_expiry_status(__cached_cache, &__cached_key);
+                        match (__cached_val, __cached_expired) {
+                            (Some(__cached_result), false) => { #return_cache_block }
+                            (__cached_stale, _) => __cached_stale,
+                        }
+                    };
+                    #start
+                    let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*).await;
+                    #missed
+                    use #krate::__stale_dispatch::StaleDispatchFallback as _;
+                    let __cached_result = match (__cached_result.is_err(), __cached_old_val) {
+                        (true, Some(__cached_old_val)) if #krate::__stale_dispatch::StaleDispatch(&__cached_old_val).serve_stale_on_error() => {
+                        #stale_outcome
+                        Ok(__cached_old_val)
+                    }
+                        _ => __cached_result,
+                    };
+                    if let Ok(__cached_ok_val) = &__cached_result {
+                        #fallback_set
+                    }
+                    #publish
+                    __cached_result
+                }
+            } else if args.result_fallback {
+                quote! {
+                    #inner_nested_def
+                    let __cached_cache = &*#cache_ident;
+                    let __cached_old_val = if #force_refresh_bypass {
+                        // Bypassing this entry: peek for the stale fallback without side effects.
+                        let (__cached_stale, _) = #krate::ConcurrentCloneCached::cache_peek_with_expiry_status(__cached_cache, &__cached_key);
+                        __cached_stale
+                    } else {
+                        let (__cached_val, __cached_expired) = #krate::ConcurrentCloneCached::cache_get_with_expiry_status(__cached_cache, &__cached_key);
+                        match (__cached_val, __cached_expired) {
+                            (Some(__cached_result), false) => { #return_cache_block }
+                            (__cached_stale, _) => __cached_stale,
+                        }
+                    };
+                    #start
+                    let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*);
+                    #missed
+                    use #krate::__stale_dispatch::StaleDispatchFallback as _;
+                    let __cached_result = match (__cached_result.is_err(), __cached_old_val) {
+                        (true, Some(__cached_old_val)) if #krate::__stale_dispatch::StaleDispatch(&__cached_old_val).serve_stale_on_error() => {
+                        #stale_outcome
+                        Ok(__cached_old_val)
+                    }
+                        _ => __cached_result,
+                    };
+                    if let Ok(__cached_ok_val) = &__cached_result {
+                        #fallback_set
+                    }
+                    #publish
+                    __cached_result
+                }
+            } else if asyncness.is_some() {
+                quote! {
+                    #inner_nested_def
+                    #start
+                    let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*).await;
+                    #missed
+                    let __cached_cache = #cache_ident.get_or_init(|| async { #cache_create }).await;
+                    #set_cache_block
+                    #publish
+                    __cached_result
+                }
             } else {
-                let (__cached_val, __cached_expired) = #krate::ConcurrentCloneCached::cache_get_with_expiry_status(__cached_cache, &__cached_key);
-                match (__cached_val, __cached_expired) {
-                    (Some(__cached_result), false) => { #return_cache_block }
-                    (__cached_stale, _) => __cached_stale,
+                quote! {
+                    #inner_nested_def
+                    #start
+                    let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*);
+                    #missed
+                    let __cached_cache = &*#cache_ident;
+                    #set_cache_block
+                    #publish
+                    __cached_result
                 }
-            };
-            let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*).await;
-            let __cached_result = match (__cached_result.is_err(), __cached_old_val) {
-                (true, Some(__cached_old_val)) => Ok(__cached_old_val),
-                _ => __cached_result,
-            };
-            if let Ok(__cached_ok_val) = &__cached_result {
-                #fallback_set
             }
-            __cached_result
-        }
-    } else if args.result_fallback {
-        quote! {
-            #inner_nested_def
-            let __cached_cache = &*#cache_ident;
-            let __cached_old_val = if #force_refresh_bypass {
-                // Bypassing this entry: peek for the stale fallback without side effects.
-                let (__cached_stale, _) = #krate::ConcurrentCloneCached::cache_peek_with_expiry_status(__cached_cache, &__cached_key);
-                __cached_stale
+        };
+    let do_set_return_block = match &metrics.exporter {
+        Some(exporter) => {
+            // The IO stores are not published per miss: their entry count is a round trip.
+            let publish = if args.redis || args.disk {
+                quote! {}
             } else {
-                let (__cached_val, __cached_expired) = #krate::ConcurrentCloneCached::cache_get_with_expiry_status(__cached_cache, &__cached_key);
-                match (__cached_val, __cached_expired) {
-                    (Some(__cached_result), false) => { #return_cache_block }
-                    (__cached_stale, _) => __cached_stale,
+                quote! {
+                    #krate::__private::publish_store_metrics(&#exporter, || #krate::ConcurrentCacheBase::metrics(__cached_cache));
                 }
             };
-            let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*);
-            let __cached_result = match (__cached_result.is_err(), __cached_old_val) {
-                (true, Some(__cached_old_val)) => Ok(__cached_old_val),
-                _ => __cached_result,
-            };
-            if let Ok(__cached_ok_val) = &__cached_result {
-                #fallback_set
-            }
-            __cached_result
-        }
-    } else if asyncness.is_some() {
-        quote! {
-            #inner_nested_def
-            let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*).await;
-            let __cached_cache = #cache_ident.get_or_init(|| async { #cache_create }).await;
-            #set_cache_block
-            __cached_result
-        }
-    } else {
-        quote! {
-            #inner_nested_def
-            let __cached_result = #self_prefix #inner_fn_ident(#(#input_names),*);
-            let __cached_cache = &*#cache_ident;
-            #set_cache_block
-            __cached_result
+            build_do_set_return_block(
+                &quote! {
+                    #miss_outcome
+                    let __cached_started = #krate::time::Instant::now();
+                },
+                &quote! { #krate::__private::record_miss(&#exporter, __cached_started); },
+                &publish,
+            )
         }
+        None => build_do_set_return_block(&quote! { #miss_outcome }, &quote! {}, &quote! {}),
     };
 
     let signature_no_muts = get_mut_signature(signature);
@@ -1308,7 +1653,7 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
     // `prime_do_set_return_block`: used by the priming function. For `result_fallback`,
     // prime unconditionally reruns the function and stores the result - no old_val fallback,
     // no early-return on fresh hit. For all other paths, prime reuses `do_set_return_block`
-    // which already implements "run inner and set cache".
+    // which already implements "run inner and set cache", built without the metrics calls.
     let prime_do_set_return_block = if args.result_fallback && asyncness.is_some() {
         quote! {
             #inner_nested_def
@@ -1330,7 +1675,7 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
             __cached_result
         }
     } else {
-        do_set_return_block.clone()
+        build_do_set_return_block(&quote! {}, &quote! {}, &quote! {})
     };
 
     // `initial_cache_lookup`: the early-return guard block emitted at the start of the cached
@@ -1427,6 +1772,51 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         }
     };
 
+    // `persist_path`: `{fn}_save_cache` snapshots the static and writes it to the path.
+    // An async fn's static is a `OnceCell`, so saving before the first call initializes
+    // it (restoring whatever the file already holds) and writes that back unchanged.
+    let (save_fn, persist_feature_guard) = match &args.persist_path {
+        Some(persist_path) => {
+            let path = persist_path.tokens();
+            let save_fn_ident = Ident::new(&format!("{}_save_cache", &fn_ident), fn_ident.span());
+            let save_fn_doc = format!(
+                "Writes a snapshot of the cache behind [`{fn_ident}`] to {}, returning \
+                 the number of entries saved.",
+                persist_path.describe()
+            );
+            let restore_fn = persist_restore_result_fn(
+                &krate,
+                &fn_ident,
+                &restore_ident,
+                &static_cfg_attrs,
+                &companions_visibility,
+            );
+            let save_fn = if asyncness.is_some() {
+                quote! {
+                    #(#static_cfg_attrs)*
+                    #[doc = #save_fn_doc]
+                    #[allow(dead_code)]
+                    #companions_visibility async fn #save_fn_ident() -> ::std::result::Result<usize, #krate::PersistError> {
+                        let __cached_cache = #cache_ident.get_or_init(|| async { #cache_create }).await;
+                        #krate::__private::persist_save_concurrent(__cached_cache, #path)
+                    }
+                }
+            } else {
+                quote! {
+                    #(#static_cfg_attrs)*
+                    #[doc = #save_fn_doc]
+                    #[allow(dead_code)]
+                    #companions_visibility fn #save_fn_ident() -> ::std::result::Result<usize, #krate::PersistError> {
+                        #krate::__private::persist_save_concurrent(&*#cache_ident, #path)
+                    }
+                }
+            };
+            let save_fn = quote! { #save_fn #restore_fn };
+            (save_fn, quote! { #krate::__require_persist_feature!{} })
+        }
+        None => (quote! {}, quote! {}),
+    };
+
     // UX-1: emit a guard macro invocation for async fns so that a missing
     // `async` feature produces a clear `compile_error!` rather than an obscure
     // "cannot find `async_sync`" error. The proc-macro cannot inspect downstream
@@ -1481,51 +1871,50 @@ pub fn concurrent_cached(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! {}
     };
 
-    // put it all together
-    let expanded = if asyncness.is_some() {
-        quote! {
-            // Backend guards first: the redis guard must not be pre-empted by the async
-            // guard on an async redis fn (0042).
-            #redb_store_guard
-            #redis_feature_guard
-            #async_feature_guard
-            #time_stores_guard
-            // Cached static (module scope unless `in_impl`)
-            #module_static
-            // Inner origin fn as a sibling impl method (only when `in_impl`)
-            #inner_sibling_def
-            // Cached function
-            #(#attributes)*
-            #visibility #signature_no_muts {
-                #body_static
-                let __cached_key = #key_convert_block;
-                #initial_cache_lookup_async
-                #do_set_return_block
-            }
-            // Prime cached function (omitted for `in_impl` methods and `companions = false`)
-            #prime_fn
-        }
+    let metrics_static = &metrics.static_item;
+    let metrics_feature_guard = &metrics.feature_guard;
+    let initial_cache_lookup = if asyncness.is_some() {
+        &initial_cache_lookup_async
     } else {
+        &initial_cache_lookup_sync
+    };
+    let call_body = call_span(
+        &krate,
+        &fn_ident,
+        Some(quote! { &__cached_key }),
+        asyncness.is_some(),
         quote! {
-            #redb_store_guard
-            #redis_feature_guard
-            #async_feature_guard
-            #time_stores_guard
-            // Cached static (module scope unless `in_impl`)
-            #module_static
-            // Inner origin fn as a sibling impl method (only when `in_impl`)
-            #inner_sibling_def
-            // Cached function
-            #(#attributes)*
-            #visibility #signature_no_muts {
-                #body_static
-                let __cached_key = #key_convert_block;
-                #initial_cache_lookup_sync
-                #do_set_return_block
-            }
-            // Prime cached function (omitted for `in_impl` methods and `companions = false`)
-            #prime_fn
+            #initial_cache_lookup
+            #do_set_return_block
+        },
+    );
+
+    // put it all together
+    let expanded = quote! {
+        // Backend guards first: the redis guard must not be pre-empted by the async
+        // guard on an async redis fn (0042).
+        #redb_store_guard
+        #redis_feature_guard
+        #async_feature_guard
+        #time_stores_guard
+        #persist_feature_guard
+        #metrics_feature_guard
+        // Cached static (module scope unless `in_impl`)
+        #module_static
+        // Inner origin fn as a sibling impl method (only when `in_impl`)
+        #inner_sibling_def
+        // Cached function
+        #(#attributes)*
+        #visibility #signature_no_muts {
+            #body_static
+            #metrics_static
+            let __cached_key = #key_convert_block;
+            #call_body
         }
+        // Prime cached function (omitted for `in_impl` methods and `companions = false`)
+        #prime_fn
+        // Snapshot writer (only with `persist_path`)
+        #save_fn
     };
 
     expanded.into()
@@ -1730,7 +2119,7 @@ fn get_disk_cache_type_and_create(
 /// | max_size | ttl | expires | store |
 /// |----------|-----|---------|-------|
 /// |  no  |  no |   no    | `ShardedUnboundCache` |
-/// | yes  |  no |   no    | `ShardedLruCache` |
+/// | yes  |  no |   no    | `ShardedLruCache` (`ShardedArcCache` / `ShardedS3FifoCache` with `policy`) |
 /// |  no  | yes |   no    | `ShardedTtlCache`         (requires `time_stores` feature on `cached`) |
 /// | yes  | yes |   no    | `ShardedLruTtlCache`      (requires `time_stores` feature on `cached`) |
 /// |  no  |  -  |   yes   | `ShardedExpiringCache`    (per-value expiry; `ttl` is rejected with `expires`) |
@@ -1761,6 +2150,13 @@ fn get_sharded_cache_type_and_create(
             "`refresh` requires a TTL (`ttl`/`ttl_secs`/`ttl_millis`) to be set on the default in-memory sharded path",
         ));
     }
+    check_policy(
+        args.policy,
+        args.max_size.is_some(),
+        ttl_duration.is_some(),
+        args.expires,
+        fn_ident.span(),
+    )?;
 
     // Reject attributes that don't apply to the in-memory default path.
     let mut conflicting = Vec::new();
@@ -1829,6 +2225,30 @@ fn get_sharded_cache_type_and_create(
                 };
                 (ty, create)
             }
+            (Some(size), None) if args.policy == Some(EvictionPolicy::Arc) => {
+                let ty = quote! { #krate::ShardedArcCache<#cache_key_ty, #cache_value_ty> };
+                let create = match args.shards {
+                    Some(n) => {
+                        quote! { #krate::ShardedArcCache::builder().max_size(#size).shards(#n).build().unwrap_or_else(|e| panic!("ShardedArcCache build failed in #[concurrent_cached]: {e}")) }
+                    }
+                    None => {
+                        quote! { #krate::ShardedArcCache::builder().max_size(#size).build().unwrap_or_else(|e| panic!("ShardedArcCache build failed in #[concurrent_cached]: {e}")) }
+                    }
+                };
+                (ty, create)
+            }
+            (Some(size), None) if args.policy == Some(EvictionPolicy::S3Fifo) => {
+                let ty = quote! { #krate::ShardedS3FifoCache<#cache_key_ty, #cache_value_ty> };
+                let create = match args.shards {
+                    Some(n) => {
+                        quote! { #krate::ShardedS3FifoCache::builder().max_size(#size).shards(#n).build().unwrap_or_else(|e| panic!("ShardedS3FifoCache build failed in #[concurrent_cached]: {e}")) }
+                    }
+                    None => {
+                        quote! { #krate::ShardedS3FifoCache::builder().max_size(#size).build().unwrap_or_else(|e| panic!("ShardedS3FifoCache build failed in #[concurrent_cached]: {e}")) }
+                    }
+                };
+                (ty, create)
+            }
             (Some(size), None) => {
                 let ty = quote! { #krate::ShardedLruCache<#cache_key_ty, #cache_value_ty> };
                 let create = match args.shards {
@@ -181,6 +181,101 @@ pub(super) fn is_option_return_type(output: &ReturnType) -> bool {
     }
 }
 
+/// Returns `true` if `output` is a `Result<Option<...>, ...>` type, the shape `cache_none`
+/// takes on the redis and disk stores, whose errors need the `Result`.
+pub(super) fn is_result_option_return_type(output: &ReturnType) -> bool {
+    let ReturnType::Type(_, ty) = output else {
+        return false;
+    };
+    if !is_result_return_type(output) {
+        return false;
+    }
+    match first_type_arg(ty, Span::call_site(), "", "") {
+        Ok(GenericArgument::Type(Type::Path(tp))) => tp
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| !matches!(s.arguments, PathArguments::None) && s.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Custom `FromMeta` type for the `persist_path` macro attribute.
+///
+/// Either a string literal naming the file (`persist_path = "cache.snapshot"`) or a
+/// `{ expr }` block yielding anything `AsRef<Path>`, evaluated each time the file is read or
+/// written (`persist_path = { state_dir().join("cache.snapshot") }`).
+#[derive(Debug, Clone)]
+pub(super) enum PersistPath {
+    Literal(String),
+    Expr(syn::Expr),
+}
+
+impl FromMeta for PersistPath {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        Ok(Self::Literal(value.to_string()))
+    }
+
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        match expr {
+            syn::Expr::Block(_) => Ok(Self::Expr(expr.clone())),
+            syn::Expr::Group(group) => Self::from_expr(&group.expr),
+            syn::Expr::Lit(lit) => Self::from_value(&lit.lit),
+            _ => Err(
+                Error::custom("`persist_path` takes a string literal or a `{ expr }` block")
+                    .with_span(expr),
+            ),
+        }
+    }
+}
+
+impl PersistPath {
+    /// The path as an expression for the generated code.
+    pub(super) fn tokens(&self) -> TokenStream2 {
+        match self {
+            Self::Literal(path) => quote! { #path },
+            Self::Expr(expr) => expr_value_tokens(expr),
+        }
+    }
+
+    /// The path as the companions' docs name it.
+    pub(super) fn describe(&self) -> String {
+        match self {
+            Self::Literal(path) => format!("`{path}`"),
+            Self::Expr(_) => "the path `persist_path` evaluates to".to_string(),
+        }
+    }
+}
+
+/// The `persist_path` restore outcome: a `{CACHE}_RESTORED` static the cache initializer
+/// fills, and the `{fn}_restore_result` companion that reads it. Shared by `#[cached]` and
+/// `#[concurrent_cached]`.
+pub(super) fn persist_restore_result_fn(
+    krate: &TokenStream2,
+    fn_ident: &syn::Ident,
+    restore_ident: &syn::Ident,
+    static_cfg_attrs: &[Attribute],
+    companions_visibility: &TokenStream2,
+) -> TokenStream2 {
+    let restore_fn_ident = format_ident!("{}_restore_result", fn_ident);
+    let restore_fn_doc = format!(
+        "How restoring the snapshot went when the cache behind [`{fn_ident}`] initialized: \
+         the number of entries restored (`Ok(0)` if there was no file), the error that left \
+         the cache cold, or `None` before the first call."
+    );
+    quote! {
+        #(#static_cfg_attrs)*
+        #[allow(non_upper_case_globals)]
+        static #restore_ident: ::std::sync::OnceLock<::std::result::Result<usize, #krate::PersistError>> = ::std::sync::OnceLock::new();
+        #(#static_cfg_attrs)*
+        #[doc = #restore_fn_doc]
+        #[allow(dead_code)]
+        #companions_visibility fn #restore_fn_ident() -> ::core::option::Option<&'static ::std::result::Result<usize, #krate::PersistError>> {
+            #restore_ident.get()
+        }
+    }
+}
+
 /// The migration message emitted when `ttl` is given as a bare integer literal
 /// (the old `ttl = 60` whole-seconds form). Shared by all three macros so the
 /// message stays identical everywhere.
@@ -227,6 +322,8 @@ impl FromMeta for TtlExpr {
 
 /// Build the internal `ttl_duration` token and `has_ttl` flag from the three
 /// mutually exclusive TTL attributes (`ttl` expr, `ttl_secs`, `ttl_millis`).
+/// `name` is the family's base attribute, so the same resolution serves `none_ttl` and
+/// `err_ttl` (`none_ttl`, `none_ttl_secs`, `none_ttl_millis`).
 ///
 /// Returns `Ok((has_ttl, ttl_duration))` where `ttl_duration` is `Some` when any
 /// TTL is set. Performs the 3-way mutual-exclusion check, the `ttl_secs >= 1` /
@@ -236,6 +333,7 @@ impl FromMeta for TtlExpr {
 /// attribute (0043b); `span` covers the remaining validations.
 pub(super) fn resolve_ttl_duration(
     krate: &TokenStream2,
+    name: &str,
     ttl: &Option<TtlExpr>,
     ttl_secs: Option<u64>,
     ttl_millis: Option<u64>,
@@ -248,16 +346,21 @@ pub(super) fn resolve_ttl_duration(
     if set_count > 1 {
         return Err(syn::Error::new(
             exclusive_span,
-            "`ttl`, `ttl_secs`, and `ttl_millis` are mutually exclusive - \
-             `ttl` takes a `Duration` expression, `ttl_secs` whole seconds, \
-             `ttl_millis` milliseconds; use exactly one",
+            format!(
+                "`{name}`, `{name}_secs`, and `{name}_millis` are mutually exclusive - \
+                 `{name}` takes a `Duration` expression, `{name}_secs` whole seconds, \
+                 `{name}_millis` milliseconds; use exactly one"
+            ),
         ));
     }
     if matches!(ttl_secs, Some(0)) {
-        return Err(syn::Error::new(span, "`ttl_secs` must be >= 1"));
+        return Err(syn::Error::new(span, format!("`{name}_secs` must be >= 1")));
     }
     if matches!(ttl_millis, Some(0)) {
-        return Err(syn::Error::new(span, "`ttl_millis` must be >= 1"));
+        return Err(syn::Error::new(
+            span,
+            format!("`{name}_millis` must be >= 1"),
+        ));
     }
     let ttl_duration = if let Some(ttl_expr) = ttl {
         let err_span = ttl_expr.span.unwrap_or(span);
@@ -265,9 +368,9 @@ pub(super) fn resolve_ttl_duration(
             syn::Error::new(
                 err_span,
                 format!(
-                    "unable to parse `ttl` as a Duration expression: {error}; \
-                     `ttl` takes a `Duration` expression as a string literal, e.g. \
-                     `ttl = \"core::time::Duration::from_secs(60)\"`"
+                    "unable to parse `{name}` as a Duration expression: {error}; \
+                     `{name}` takes a `Duration` expression as a string literal, e.g. \
+                     `{name} = \"core::time::Duration::from_secs(60)\"`"
                 ),
             )
         })?;
@@ -307,6 +410,76 @@ impl FromMeta for SyncWriteMode {
     }
 }
 
+/// Eviction policy of the `max_size`-bounded in-memory store
+/// (`policy = "lru" | "arc" | "s3fifo"`).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub(super) enum EvictionPolicy {
+    #[default]
+    Lru,
+    Arc,
+    S3Fifo,
+}
+
+impl EvictionPolicy {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Lru => "lru",
+            Self::Arc => "arc",
+            Self::S3Fifo => "s3fifo",
+        }
+    }
+}
+
+impl FromMeta for EvictionPolicy {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "lru" => Ok(Self::Lru),
+            "arc" => Ok(Self::Arc),
+            "s3fifo" => Ok(Self::S3Fifo),
+            _ => Err(Error::unknown_value(value)),
+        }
+    }
+}
+
+/// Validate `policy` against the attributes it is combined with. `policy` only picks how the
+/// `max_size` bound evicts, so it needs `max_size`; the TTL and `expires` stores exist only
+/// in LRU form.
+pub(super) fn check_policy(
+    policy: Option<EvictionPolicy>,
+    has_max_size: bool,
+    has_ttl: bool,
+    expires: bool,
+    span: Span,
+) -> Result<(), syn::Error> {
+    let Some(policy) = policy else {
+        return Ok(());
+    };
+    if !has_max_size {
+        return Err(syn::Error::new(
+            span,
+            "`policy` selects how the `max_size` bound evicts and requires `max_size`",
+        ));
+    }
+    if policy == EvictionPolicy::Lru {
+        return Ok(());
+    }
+    let conflict = if expires {
+        "`expires`"
+    } else if has_ttl {
+        "a TTL (`ttl`/`ttl_secs`/`ttl_millis`)"
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new(
+        span,
+        format!(
+            "`policy = \"{}\"` cannot be combined with {conflict} - the TTL and \
+             per-value expiring stores are LRU-bounded, so only `policy = \"lru\"` applies",
+            policy.as_str()
+        ),
+    ))
+}
+
 pub(super) fn validate_sync_writes_buckets(
     buckets: usize,
     span: proc_macro2::Span,
@@ -341,6 +514,120 @@ pub(super) fn by_key_lock_block(
     }
 }
 
+/// Generated pieces for `metrics_name = "..."`; all empty without the attribute.
+pub(super) struct MetricsTokens {
+    /// The function-local exporter static, emitted at the top of the cached fn's body.
+    pub(super) static_item: TokenStream2,
+    /// Path of that static, for the miss timing and the publish after a store.
+    pub(super) exporter: Option<TokenStream2>,
+    /// Counts a hit; spliced in front of every cached-value return.
+    pub(super) record_hit: TokenStream2,
+    /// `compile_error!`s a missing `metrics` feature, like the other feature guards.
+    pub(super) feature_guard: TokenStream2,
+}
+
+pub(super) fn metrics_tokens(krate: &TokenStream2, metrics_name: Option<&str>) -> MetricsTokens {
+    let Some(name) = metrics_name else {
+        return MetricsTokens {
+            static_item: quote! {},
+            exporter: None,
+            record_hit: quote! {},
+            feature_guard: quote! {},
+        };
+    };
+    MetricsTokens {
+        static_item: quote! {
+            static __CACHED_METRICS: ::std::sync::LazyLock<#krate::MetricsExporter> =
+                ::std::sync::LazyLock::new(|| #krate::MetricsExporter::new(#name));
+        },
+        exporter: Some(quote! { __CACHED_METRICS }),
+        record_hit: quote! { __CACHED_METRICS.record_hit(); },
+        feature_guard: quote! { #krate::__require_metrics_feature!{} },
+    }
+}
+
+/// How a `register = true` cache is reached from `cached::registry`: expressions over the cache
+/// static for its metrics, its length, and a clear returning `Result<(), ClearError>`.
+pub(super) struct RegistryAccessors {
+    pub(super) metrics: TokenStream2,
+    pub(super) len: TokenStream2,
+    pub(super) clear: TokenStream2,
+}
+
+/// `register = true`: wraps `cache_create` so the static adds itself to `cached::registry`
+/// once the store is built. The accessors become non-capturing closures coerced to `fn`
+/// pointers; they name the static, which is only read after its initializer has returned.
+pub(super) fn register_on_create(
+    krate: &TokenStream2,
+    fn_ident: &syn::Ident,
+    kind: &str,
+    store_ty: &TokenStream2,
+    accessors: &RegistryAccessors,
+    cache_create: TokenStream2,
+) -> TokenStream2 {
+    use syn::ext::IdentExt;
+    let name = fn_ident.unraw().to_string();
+    let kind = format_ident!("{}", kind);
+    let RegistryAccessors {
+        metrics,
+        len,
+        clear,
+    } = accessors;
+    quote! {{
+        let __cached_store = #cache_create;
+        #krate::__private::register_cache(
+            ::core::concat!(::core::module_path!(), "::", #name),
+            #krate::registry::CacheKind::#kind,
+            ::core::any::type_name::<#store_ty>(),
+            || #metrics,
+            || #len,
+            || #clear,
+        );
+        __cached_store
+    }}
+}
+
+/// `register` needs a module-level static for its accessors to name, and a function path that
+/// identifies the cache; an `in_impl` method has neither.
+pub(super) fn check_register(register: bool, in_impl: bool, span: Span) -> Result<(), syn::Error> {
+    if register && in_impl {
+        return Err(syn::Error::new(
+            span,
+            "`register` is not supported with `in_impl = true`: the cache static is local to \
+             the method body, and the function path would not identify the `impl`",
+        ));
+    }
+    Ok(())
+}
+
+/// Wraps what a cached fn runs after binding its key in `cached::__cached_call_span!`, which
+/// opens a `cached.call` span when the `cached` crate is built with `tracing` and expands to
+/// `body` unchanged otherwise. `key` is the expression whose hash the span records.
+pub(super) fn call_span(
+    krate: &TokenStream2,
+    fn_ident: &syn::Ident,
+    key: Option<TokenStream2>,
+    is_async: bool,
+    body: TokenStream2,
+) -> TokenStream2 {
+    use syn::ext::IdentExt;
+    let name = fn_ident.unraw().to_string();
+    let key = key.map(|key| quote! { , #key });
+    let mode = if is_async {
+        quote! { async }
+    } else {
+        quote! { sync }
+    };
+    quote! {
+        #krate::__cached_call_span!{ #name #key; #mode { #body } }
+    }
+}
+
+/// Records `outcome` (`hit`, `miss` or `stale`) on the enclosing `cached.call` span.
+pub(super) fn call_outcome(krate: &TokenStream2, outcome: &str) -> TokenStream2 {
+    quote! { #krate::__cached_call_outcome!{ #outcome } }
+}
+
 // if you define arguments as mutable, e.g.
 // #[cached]
 // fn mutable_args(mut a: i32, mut b: i32) -> (i32, i32) {
@@ -761,6 +1048,151 @@ pub(super) fn expr_value_tokens(expr: &syn::Expr) -> TokenStream2 {
     quote! { #expr }
 }
 
+/// Bind `result` to `value`, the owned copy about to be stored, and `__cached_ttl` to the
+/// `ttl_fn` expression evaluated over it. The caller then stores `result`, so the
+/// expression can borrow it (`{ compute_ttl(&result) }`) without an extra clone. Shared by
+/// `#[cached]` and `#[concurrent_cached]`.
+pub(super) fn ttl_fn_binding(ttl_fn: &syn::Expr, value: &TokenStream2) -> TokenStream2 {
+    let ttl = expr_value_tokens(ttl_fn);
+    quote! {
+        let result = #value;
+        let __cached_ttl: ::core::time::Duration = #ttl;
+    }
+}
+
+/// One `none_ttl` / `err_ttl` attribute family as parsed, with whether its `cache_none` /
+/// `cache_err` opt-in is set.
+pub(super) struct NegativeTtlAttr<'a> {
+    pub(super) ttl: &'a Option<TtlExpr>,
+    pub(super) secs: Option<u64>,
+    pub(super) millis: Option<u64>,
+    pub(super) cached: bool,
+}
+
+/// What `none_ttl` / `err_ttl` are checked against.
+pub(super) struct NegativeTtlContext<'a> {
+    pub(super) attr_args: &'a [NestedMeta],
+    pub(super) ttl_fn: bool,
+    pub(super) refresh: bool,
+    pub(super) tags: bool,
+    /// `cache_err` makes the whole `Result<Option<T>, E>` the cached value, so `none_ttl`
+    /// picks out `Ok(None)` rather than `None`.
+    pub(super) none_within_ok: bool,
+    /// `None` when the store the macro builds has `set_with_ttl`, else how to get one that
+    /// does.
+    pub(super) store_hint: Option<&'a str>,
+}
+
+/// One of `none_ttl` / `err_ttl`, resolved: the pattern picking the cached `None`s or `Err`s
+/// out of the return value and the `Duration` they are stored for.
+pub(super) struct NegativeTtl {
+    pattern: TokenStream2,
+    ttl: TokenStream2,
+}
+
+impl NegativeTtl {
+    /// Store the value through `set_with_ttl` with the TTL of the first of `ttls` whose
+    /// pattern `place` (the returned value) matches, and run `otherwise` (the regular set)
+    /// when none does: a chain of `if matches!(..) {..} else if ..`, or `otherwise` alone
+    /// when `ttls` is empty. `set_with_ttl` builds the store's call from the TTL tokens; the
+    /// key is moved by whichever branch runs.
+    pub(super) fn set_or(
+        ttls: &[NegativeTtl],
+        place: TokenStream2,
+        set_with_ttl: impl Fn(&TokenStream2) -> TokenStream2,
+        otherwise: TokenStream2,
+    ) -> TokenStream2 {
+        if ttls.is_empty() {
+            return otherwise;
+        }
+        let branches = ttls.iter().map(|NegativeTtl { pattern, ttl }| {
+            let set_with_ttl = set_with_ttl(ttl);
+            quote! {
+                if ::core::matches!(#place, #pattern) {
+                    #set_with_ttl
+                }
+            }
+        });
+        quote! {
+            #(#branches else)* {
+                #otherwise
+            }
+        }
+    }
+}
+
+/// Resolve and check `none_ttl` and `err_ttl`, shared by `#[cached]` and
+/// `#[concurrent_cached]`. Each needs its opt-in. Both may be set: their patterns
+/// (`Ok(None)` and `Err(_)` when both opt-ins make the whole `Result` the cached value) never
+/// match the same value, so each cached value still gets at most one TTL.
+pub(super) fn resolve_negative_ttl(
+    krate: &TokenStream2,
+    none: NegativeTtlAttr<'_>,
+    err: NegativeTtlAttr<'_>,
+    cx: &NegativeTtlContext<'_>,
+) -> Result<Vec<NegativeTtl>, syn::Error> {
+    let families = [
+        (
+            "none_ttl",
+            "cache_none",
+            "`None`",
+            none,
+            if cx.none_within_ok {
+                quote! { ::core::result::Result::Ok(::core::option::Option::None) }
+            } else {
+                quote! { ::core::option::Option::None }
+            },
+        ),
+        (
+            "err_ttl",
+            "cache_err",
+            "`Err`",
+            err,
+            quote! { ::core::result::Result::Err(_) },
+        ),
+    ];
+    let mut resolved = Vec::new();
+    for (name, opt_in, values, attr, pattern) in families {
+        let secs = format!("{name}_secs");
+        let millis = format!("{name}_millis");
+        let span = last_named_attr_span(cx.attr_args, &[name, &secs, &millis])
+            .unwrap_or_else(attr_list_span);
+        let (_, ttl) =
+            resolve_ttl_duration(krate, name, attr.ttl, attr.secs, attr.millis, span, span)?;
+        let Some(ttl) = ttl else {
+            continue;
+        };
+        let message = if !attr.cached {
+            format!(
+                "`{name}` requires `{opt_in} = true` - {values} values are not cached \
+                 without it, so `{name}` would never apply"
+            )
+        } else if cx.ttl_fn {
+            format!(
+                "`{name}` and `ttl_fn` are mutually exclusive - `ttl_fn` already sets the TTL \
+                 of every entry, {values} values included"
+            )
+        } else if cx.refresh {
+            format!(
+                "`{name}` cannot be combined with `refresh` - a hit re-arms the entry with \
+                 the cache TTL, so a cached {values} would outlive `{name}`"
+            )
+        } else if cx.tags {
+            format!(
+                "`{name}` and `tags` cannot be combined - `tags` stores through \
+                 `CachedTags::set_with_tags`, which takes no per-entry TTL"
+            )
+        } else if let Some(hint) = cx.store_hint {
+            format!("`{name}` requires a store with per-entry TTLs - {hint}")
+        } else {
+            resolved.push(NegativeTtl { pattern, ttl });
+            continue;
+        };
+        return Err(syn::Error::new(span, message));
+    }
+    Ok(resolved)
+}
+
 /// Build the `force_refresh` guard token that wraps a cached-hit early return.
 ///
 /// `force_refresh` is an opt-in boolean expression block over the function args,
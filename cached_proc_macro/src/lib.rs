@@ -10,6 +10,10 @@ use proc_macro::TokenStream;
 /// # Attributes
 /// - `name`: (optional, string) specify the name for the generated cache, defaults to the function name uppercase.
 /// - `max_size`: (optional, usize) specify an LRU max size, implies the cache type is a `LruCache` or `LruTtlCache`.
+/// - `policy`: (optional, string) how the `max_size` bound evicts: `"lru"` (the default), `"arc"`,
+///   which selects `ArcCache` (adaptive replacement, scan-resistant), or `"s3fifo"`, which selects
+///   `S3FifoCache` (FIFO queues with a ghost queue; hits relink nothing). Requires `max_size`;
+///   `"arc"` and `"s3fifo"` cannot be combined with a TTL or `expires`, and no value with `create`.
 /// - `ttl`: (optional, Duration string) specify a cache TTL as a Duration-expression string literal,
 ///   e.g. `ttl = "Duration::from_secs(60)"`. Implies the cache type is a `TtlCache` or `LruTtlCache`
 ///   (requires the `time_stores` feature). Mutually exclusive with `ttl_secs`, `ttl_millis`, and `expires`.
@@ -24,6 +28,18 @@ use proc_macro::TokenStream;
 /// - `refresh`: (optional, bool) specify whether to refresh the TTL on cache hits.
 ///   Requires a TTL (`ttl`, `ttl_secs`, or `ttl_millis`); setting `refresh = true` without a TTL
 ///   is a compile error.
+/// - `ttl_fn`: (optional, expression block) a per-entry TTL computed from the value being cached,
+///   e.g. `ttl_fn = { compute_ttl(&result) }`. `result` is the value about to be stored (the `Ok` or
+///   `Some` payload when only those are cached) and the block must evaluate to a `Duration`; a zero
+///   `Duration` caches the value without expiry. Stores through `set_with_ttl`, so it requires a TTL
+///   (`TtlCache` / `LruTtlCache`) or a `ty`/`create` store with that method. Mutually exclusive with
+///   `expires` and `tags`.
+/// - `none_ttl` / `none_ttl_secs` / `none_ttl_millis`: (optional) a shorter TTL for cached `None`
+///   values, in the same three forms as `ttl`. Requires `cache_none = true`; `Some` values keep
+///   the cache TTL. Stores `None` through `set_with_ttl`, so the store requirement is the one
+///   `ttl_fn` has. Mutually exclusive with `ttl_fn`, `refresh`, and `tags`.
+/// - `err_ttl` / `err_ttl_secs` / `err_ttl_millis`: (optional) the same for cached `Err` values;
+///   requires `cache_err = true`.
 /// - `force_refresh`: (optional, expression block) a boolean expression over the function arguments,
 ///   written in curly braces like `convert` (it is evaluated, not a magic flag and not a required
 ///   bool parameter). When it evaluates to `true`, any cached value is bypassed and the function body
@@ -78,10 +94,12 @@ use proc_macro::TokenStream;
 /// - `unsync_reads`: (optional, bool) use `CachedRead::cache_get_read` under a shared read lock for the initial
 ///   cache lookup, while keeping writes synchronized. This only works for stores that implement `CachedRead`;
 ///   recency-updating or refresh-on-hit stores intentionally do not. The built-in compliant stores are
-///   `UnboundCache` (the default) and `TtlSortedCache`; a custom `ty` may also implement `CachedRead`.
+///   `UnboundCache` (the default), `TtlSortedCache`, and `S3FifoCache` (`max_size` with
+///   `policy = "s3fifo"`); a custom `ty` may also implement `CachedRead`.
 ///   For non-mutating diagnostic lookups, use the separate `CachedPeek` trait directly on stores.
 /// - `ty`: (optional, string type) The cache store type to use. Defaults to `UnboundCache`.
-///   When `max_size` is specified, defaults to `LruCache`.
+///   When `max_size` is specified, defaults to `LruCache` (`ArcCache` with `policy = "arc"`,
+///   `S3FifoCache` with `policy = "s3fifo"`).
 ///   When `ttl` is specified, defaults to `TtlCache`.
 ///   When `max_size` and `ttl` are specified, defaults to `LruTtlCache`. When `ty` is
 ///   specified, `create` must also be specified.
@@ -113,6 +131,8 @@ use proc_macro::TokenStream;
 ///   `.set_was_cached`). Use a different name for any non-`cached` `Return` type.
 /// - `result_fallback`: (optional, bool) If your function returns a `Result` and it fails, the cache will instead serve the expired `Ok` value.
 ///   In other words, refreshes are best-effort - returning `Ok` refreshes as usual but `Err` falls back to the last `Ok`.
+///   A value implementing `Expires` is only served while its `serve_stale_on_error()` is true
+///   (`cached::http::HttpCached` uses this for `stale-if-error`).
 ///   **Note (TTL stores):** the stale value's TTL is refreshed on *every* `Err` call - if the underlying
 ///   operation stays down indefinitely, the stale entry will never expire. `ttl` bounds staleness
 ///   under normal (transient) failure; it does not bound it under permanent failure.
@@ -265,6 +285,8 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
 /// |---|---|
 /// | (none) | `ShardedUnboundCache` - unbounded, no TTL |
 /// | `max_size = N` | `ShardedLruCache` - LRU-bounded |
+/// | `max_size = N, policy = "arc"` | `ShardedArcCache` - ARC-bounded (adaptive replacement) |
+/// | `max_size = N, policy = "s3fifo"` | `ShardedS3FifoCache` - S3-FIFO-bounded, hits under the read lock |
 /// | `ttl = T` | `ShardedTtlCache` - TTL-expiring, unbounded (`time_stores` feature) |
 /// | `max_size = N, ttl = T` | `ShardedLruTtlCache` - LRU + TTL (`time_stores` feature) |
 /// | `expires = true` | `ShardedExpiringCache` - per-value expiry, unbounded |
@@ -329,6 +351,11 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
 ///   **Note:** effective capacity may exceed `N` - shards enforce a 16-entry minimum floor, so
 ///   `max_size = 4` on an 8-shard build silently gives 128 effective slots. For a strict cap use
 ///   `shards = 1` or the builder's `per_shard_max_size`.
+/// - `policy`: (optional, string) how the `max_size` bound evicts: `"lru"` (the default), `"arc"`,
+///   which selects `ShardedArcCache` (adaptive replacement per shard), or `"s3fifo"`, which
+///   selects `ShardedS3FifoCache` (S3-FIFO per shard, `cache_get` under the shard read lock);
+///   `shards` still applies. Requires `max_size`; `"arc"` and `"s3fifo"` cannot be combined with
+///   a TTL or `expires`, and no value with `redis`, `disk`, or `create`.
 /// - `ttl`: (optional, Duration string) TTL as a Duration-expression string literal, e.g.
 ///   `ttl = "Duration::from_secs(60)"`. For the default in-memory path, selects `ShardedTtlCache`
 ///   or `ShardedLruTtlCache` (requires the `time_stores` feature). For `redis` stores without a
@@ -376,6 +403,18 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
 ///   in-memory path, setting `refresh = true` without `ttl` is a compile error (`refresh = false`
 ///   without `ttl` is accepted but has no effect). On `redis`/`disk` paths `refresh` is forwarded
 ///   to the backend store builder.
+/// - `ttl_fn`: (optional, expression block) a per-entry TTL computed from the value being cached,
+///   as on `#[cached]`: `ttl_fn = { compute_ttl(&result) }` with `result` the value about to be
+///   stored. Stores through `set_with_ttl`, so it requires `ttl` on the default in-memory path
+///   (`ShardedTtlCache` / `ShardedLruTtlCache`) or a `ty`/`create` store with that method.
+///   Mutually exclusive with `redis`, `disk`, and `expires`.
+/// - `none_ttl` / `none_ttl_secs` / `none_ttl_millis`, `err_ttl` / `err_ttl_secs` / `err_ttl_millis`:
+///   (optional) a shorter TTL for a cached `None` / `Err`, as on `#[cached]`. Require
+///   `cache_none = true` / `cache_err = true`, and on the default in-memory path `ttl`
+///   (`ShardedTtlCache` / `ShardedLruTtlCache`); with `redis`/`disk` the store's own
+///   `set_with_ttl` is used. Both may be set, with both opt-ins: `none_ttl` then applies to a
+///   cached `Ok(None)` and `err_ttl` to a cached `Err`. Mutually exclusive with `ttl_fn` and
+///   `refresh`.
 /// - `expires`: (optional, bool) select a per-value expiry store. The cached value type must
 ///   implement the `Expires` trait. Without `max_size`, selects `ShardedExpiringCache` (unbounded);
 ///   with `max_size = N`, selects `ShardedExpiringLruCache` (LRU-bounded). Mutually exclusive with
@@ -410,14 +449,17 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
 ///   `key` or `ty` must also be set.
 /// - `cache_none`: (optional, bool) If your function returns an `Option<T>`, also cache `None` values.
 ///   By default `None` is returned without being stored; set `cache_none = true` to store `None` as well.
-///   Only supported on the default in-memory sharded path; combining it with `redis`/`disk`/custom `ty` is a compile error.
+///   With `redis`/`disk` the function returns `Result<Option<T>, E>`, whose `Ok(None)` is stored
+///   already; the attribute there only opts into `none_ttl`. Combining it with a custom `ty` on
+///   the default path is a compile error.
 ///   Mutually exclusive with `with_cached_flag`.
 ///   **Note:** when `cache_none = true`, the underlying store holds `Option<T>` as its value type,
 ///   so a direct `.cache_get()` call returns `Option<Option<T>>` - the outer `Option` is the
 ///   cache hit/miss indicator; the inner `Option` is the cached value.
 /// - `cache_err`: (optional, bool) If your function returns a `Result<T, E>`, also cache `Err` values.
 ///   By default only `Ok(T)` is cached; set `cache_err = true` to store `Err` values too.
-///   Only supported on the default in-memory sharded path; combining it with `redis`/`disk`/custom `ty` is a compile error.
+///   Supported on the default in-memory sharded path and with `redis`/`disk`, where `E` must be
+///   serializable too; combining it with a custom `ty` on the default path is a compile error.
 ///   **Note:** when `cache_err = true`, the underlying store holds `Result<T, E>` as its value type,
 ///   so a direct `.cache_get()` call returns `Option<Result<T, E>>` - the outer `Option` is the
 ///   cache hit/miss indicator; the inner `Result` is the cached value.
@@ -427,7 +469,8 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
 ///   the cache was cleared), the original `Err` is returned as-is. Refreshes are best-effort:
 ///   an `Ok` return refreshes the cache as usual; an `Err` return re-caches the stale value.
 ///   This is useful for keeping the last successful result available during transient
-///   failures, e.g. network disconnects.
+///   failures, e.g. network disconnects. With `expires = true`, a value whose
+///   `Expires::serve_stale_on_error()` is false is not served and the `Err` is returned.
 ///   **Requires expiring entries** - a refresh only happens once an entry expires, so without
 ///   expiry the body is never re-run for a cached key and the fallback can never fire. Satisfy
 ///   it either way:
@@ -78,6 +78,13 @@ struct OnceMacroArgs {
     /// path, so `{fn}_prime_cache` is the only free function it suppresses (0024).
     #[darling(default)]
     companions: Option<bool>,
+    /// Name the cache's hits, misses and miss durations are published under through the
+    /// `metrics` facade (`cache = "<name>"`). Requires the `metrics` feature.
+    #[darling(default)]
+    metrics_name: Option<String>,
+    /// Add the cache to `cached::registry` when its static is first initialized.
+    #[darling(default)]
+    register: bool,
     // Removed attributes intercepted to provide helpful error messages
     #[darling(default)]
     result: Option<bool>,
@@ -242,6 +249,9 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
         .to_compile_error()
         .into();
     }
+    if let Err(error) = check_register(args.register, args.in_impl, fn_ident.span()) {
+        return error.to_compile_error().into();
+    }
 
     // Note: `#[once]` supports generic functions, but only when the cache value
     // type is concrete (does not name any of the function's own type or const
@@ -298,6 +308,7 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
     // `ttl_secs`/`ttl_millis` >= 1 validation, and parses the `ttl` expression.
     let (has_ttl, ttl_duration) = match resolve_ttl_duration(
         &krate,
+        "ttl",
         &args.ttl,
         args.ttl_secs,
         args.ttl_millis,
@@ -627,6 +638,33 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
         (quote! { Option<#cache_value_ty> }, quote! { None })
     };
 
+    // `register`: a single value reports its presence; clearing drops it.
+    let cache_create = if args.register {
+        let (read, write) = if asyncness.is_some() {
+            (quote! { read_blocking }, quote! { write_blocking })
+        } else {
+            (quote! { read }, quote! { write })
+        };
+        let accessors = RegistryAccessors {
+            metrics: quote! { #krate::__private::single_value_metrics(#cache_ident.#read().is_some()) },
+            len: quote! { ::core::option::Option::Some(usize::from(#cache_ident.#read().is_some())) },
+            clear: quote! {{
+                *#cache_ident.#write() = ::core::option::Option::None;
+                ::core::result::Result::Ok(())
+            }},
+        };
+        register_on_create(
+            &krate,
+            &fn_ident,
+            "Once",
+            &cache_ty,
+            &accessors,
+            cache_create,
+        )
+    } else {
+        cache_create
+    };
+
     // `force_refresh`: when its expression evaluates `true`, the cached-hit early
     // return is skipped so the body re-runs and re-caches the single shared value.
     // The guard wraps the whole cached-value check (not just the return), so a
@@ -649,6 +687,12 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
     let clone_borrowed =
         clone_cached_value(&cache_value_ty, output_span, quote! { __cached_result });
     let clone_inner = clone_cached_value(&cache_value_ty, output_span, quote! { __cached_inner });
+    // `metrics_name`: a single-value cache has no store statistics worth publishing, so
+    // only the calls themselves are counted and the body timed on a miss.
+    let metrics = metrics_tokens(&krate, args.metrics_name.as_deref());
+    let metrics_record_hit = &metrics.record_hit;
+    let hit_outcome = call_outcome(&krate, "hit");
+    let record_hit = &quote! { #metrics_record_hit #hit_outcome };
     let (set_cache_block, return_cache_block) = match (is_smart_result, is_smart_option) {
         (false, false) => {
             let set_cache_block = if has_ttl {
@@ -662,9 +706,9 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
             };
 
             let return_cache_block = if args.with_cached_flag {
-                quote! { let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return __cached_r }
+                quote! { #record_hit let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return __cached_r }
             } else {
-                quote! { return #clone_borrowed }
+                quote! { #record_hit return #clone_borrowed }
             };
             let return_cache_block = gen_return_cache_block(
                 &krate,
@@ -690,9 +734,9 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
             };
 
             let return_cache_block = if args.with_cached_flag {
-                quote! { let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Ok(__cached_r) }
+                quote! { #record_hit let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Ok(__cached_r) }
             } else {
-                quote! { return Ok(#clone_borrowed) }
+                quote! { #record_hit return Ok(#clone_borrowed) }
             };
             let return_cache_block = gen_return_cache_block(
                 &krate,
@@ -718,9 +762,9 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
             };
 
             let return_cache_block = if args.with_cached_flag {
-                quote! { let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Some(__cached_r) }
+                quote! { #record_hit let mut __cached_r = #clone_borrowed; __cached_r.set_was_cached(true); return Some(__cached_r) }
             } else {
-                quote! { return Some(#clone_borrowed) }
+                quote! { #record_hit return Some(#clone_borrowed) }
             };
             let return_cache_block = gen_return_cache_block(
                 &krate,
@@ -848,6 +892,19 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
             }
         });
     }
+    let miss_outcome = call_outcome(&krate, "miss");
+    let timed_function_call = match &metrics.exporter {
+        Some(exporter) => quote! {
+            #miss_outcome
+            let __cached_started = #krate::time::Instant::now();
+            #function_call
+            #krate::__private::record_miss(&#exporter, __cached_started);
+        },
+        None => quote! {
+            #miss_outcome
+            #function_call
+        },
+    };
     let module_ty = make_static(&quote! { #visibility });
     let body_ty = make_static(&quote! {});
 
@@ -923,14 +980,14 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
                         #return_cache_block
                     }
                 }
-                #function_call
+                #timed_function_call
                 #set_cache_and_return
             }
         }
         SyncWriteMode::ByKey => unreachable!("ByKey rejected above"),
         SyncWriteMode::Disabled => quote! {
             #r_lock_return_cache_block
-            #function_call
+            #timed_function_call
             #w_lock
             #set_cache_and_return
         },
@@ -1021,8 +1078,22 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! {}
     };
 
+    let metrics_static = &metrics.static_item;
+    let metrics_feature_guard = &metrics.feature_guard;
+    let call_body = call_span(
+        &krate,
+        &fn_ident,
+        None,
+        asyncness.is_some(),
+        quote! {
+            #now_block
+            #do_set_return_block
+        },
+    );
+
     let expanded = quote! {
         #async_feature_guard
+        #metrics_feature_guard
         // Cached static (module scope unless `in_impl`)
         #module_static
         // Inner origin fn as a sibling impl method (only when `in_impl`)
@@ -1031,8 +1102,8 @@ pub fn once(args: TokenStream, input: TokenStream) -> TokenStream {
         #(#attributes)*
         #visibility #signature_no_muts {
             #body_static
-            #now_block
-            #do_set_return_block
+            #metrics_static
+            #call_body
         }
         // Prime cached function (omitted for `in_impl` methods and `companions = false`)
         #prime_fn
@@ -1,5 +1,6 @@
 /*
-Three 3.0 resilience attributes on `#[cached]` functions - in-memory, no external services.
+Three 3.0 resilience attributes on `#[cached]` functions, and a store that fails on purpose -
+in-memory, no external services.
 
 1. `sync_writes = "by_key"`: concurrent first calls for the same key are deduplicated -
    only one thread runs the function body; the others wait and reuse that result.
@@ -14,11 +15,17 @@ Three 3.0 resilience attributes on `#[cached]` functions - in-memory, no externa
    over the function arguments is true. Uses a call counter to show the difference between
    a cache hit (expression false) and a forced recompute (expression true).
 
+4. `FaultyCache`: wraps a store and fails a seeded fraction of its operations, to exercise
+   the caller's handling of cache errors. Here a `#[concurrent_cached]` function maps the
+   store error with `map_error`, and its caller falls back to loading the value directly.
+
 Run:
     cargo run --example resilience --features "proc_macro,time_stores"
 */
 
-use cached::macros::cached;
+use cached::macros::{cached, concurrent_cached};
+use cached::stores::{FaultyCache, InjectedFault};
+use cached::{ConcurrentCached, ShardedUnboundCache};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Barrier};
 use std::thread;
@@ -288,10 +295,83 @@ fn demo_force_refresh() {
     println!("  PASS: force_refresh confirmed");
 }
 
+// ============================================================================
+// 4. FaultyCache
+//
+// The store fails 30% of its operations (seeded, so every run fails the same
+// calls). A failed cache lookup becomes `LookupError::Cache` through
+// `map_error`; the caller treats that as "cache unavailable" and computes the
+// value directly instead of failing the request.
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum LookupError {
+    Cache,
+}
+
+static LOOKUP_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[concurrent_cached(
+    ty = "FaultyCache<ShardedUnboundCache<u32, String>>",
+    create = r#"{
+        FaultyCache::builder(ShardedUnboundCache::builder().build().unwrap())
+            .fail_rate(0.3)
+            .seed(2024)
+            .build()
+            .unwrap()
+    }"#,
+    map_error = r#"|_e: InjectedFault| LookupError::Cache"#
+)]
+fn lookup_user(id: u32) -> Result<String, LookupError> {
+    Ok(load_user(id))
+}
+
+// The uncached load, standing in for a database query.
+fn load_user(id: u32) -> String {
+    LOOKUP_CALL_COUNT.fetch_add(1, Ordering::SeqCst);
+    format!("user-{id}")
+}
+
+fn lookup_user_resilient(id: u32) -> String {
+    match lookup_user(id) {
+        Ok(user) => user,
+        Err(LookupError::Cache) => load_user(id),
+    }
+}
+
+fn demo_faulty_cache() {
+    println!("\n--- 4. FaultyCache ---");
+
+    let cache_errors = (0..20)
+        .filter(|_| lookup_user(1) == Err(LookupError::Cache))
+        .count();
+    println!("  20 cached calls for id=1: {cache_errors} failed with a cache error");
+    assert!(
+        cache_errors > 0,
+        "a 30% fail rate must fail some of 20 calls"
+    );
+
+    let users: Vec<String> = (0..20).map(|_| lookup_user_resilient(1)).collect();
+    assert!(users.iter().all(|user| user == "user-1"));
+    println!("  20 resilient calls for id=1: all returned 'user-1'");
+
+    // With injection off, the store behaves normally.
+    LOOKUP_USER.set_enabled(false);
+    LOOKUP_USER.cache_clear().unwrap();
+    LOOKUP_CALL_COUNT.store(0, Ordering::SeqCst);
+    lookup_user(2).unwrap();
+    lookup_user(2).unwrap();
+    assert_eq!(LOOKUP_CALL_COUNT.load(Ordering::SeqCst), 1);
+    println!("  injection off: second call for id=2 was a cache hit");
+
+    println!("  PASS: cache faults handled");
+}
+
 fn main() {
     demo_sync_writes_by_key();
     demo_result_fallback();
     demo_force_refresh();
+    demo_faulty_cache();
 
     println!("\ndone!");
 }
@@ -0,0 +1,202 @@
+/*!
+Per-value expiry from HTTP response headers.
+
+[`HttpCached`] pairs a value fetched over HTTP with the freshness its response headers gave it
+and implements [`Expires`] with that deadline, so an `#[cached(expires = true)]` function that
+returns one lets each response decide how long it is reused:
+
+```rust,no_run
+use cached::http::HttpCached;
+use cached::macros::cached;
+
+# fn get(_url: &str) -> Result<http::Response<String>, String> { unimplemented!() }
+#[cached(expires = true, result_fallback = true, key = "String", convert = r#"{ url.to_owned() }"#)]
+fn fetch(url: &str) -> Result<HttpCached<String>, String> {
+    let (parts, body) = get(url)?.into_parts();
+    Ok(HttpCached::from_headers(body, &parts.headers))
+}
+```
+
+The lifetime is `s-maxage`, else `max-age`, else `Expires` minus `Date` (or minus now when
+there is no `Date`); it is zero under `no-cache` or `no-store`, when none of these is present,
+and when `Expires` is not a valid HTTP date. `Age` is counted against it, so a response that
+spent 50 of its 60 seconds in an upstream cache is reused for 10, and one whose `Age` is past
+its lifetime is stored already expired.
+
+With `result_fallback`, a stale value is returned in place of an `Err` only within the
+response's `stale-if-error` window, measured from its expiry; without that directive the
+fallback is unbounded, as for any other [`Expires`] value. `stale-while-revalidate` is parsed
+and exposed ([`stale_while_revalidate_until`](HttpCached::stale_while_revalidate_until)) but not
+acted on by the macros.
+*/
+
+use http::header::{self, HeaderMap, HeaderValue};
+
+use crate::stores::Expires;
+use crate::time::{Duration, Instant, SystemTime};
+
+/// A value with the expiry its HTTP response headers gave it. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct HttpCached<T> {
+    value: T,
+    /// When the response was received.
+    received: Instant,
+    /// Its `Age` header: how long it had already spent in upstream caches.
+    upstream_age: Duration,
+    /// `None` when the lifetime runs past what `Instant` can represent.
+    expires_at: Option<Instant>,
+    stale_while_revalidate: Option<Duration>,
+    stale_if_error: Option<Duration>,
+}
+
+impl<T> HttpCached<T> {
+    /// Wraps `value`, reading its freshness from `headers` as received now.
+    #[must_use]
+    pub fn from_headers(value: T, headers: &HeaderMap) -> Self {
+        let received = Instant::now();
+        let (mut max_age, mut s_maxage) = (None, None);
+        let (mut stale_while_revalidate, mut stale_if_error) = (None, None);
+        let mut uncacheable = false;
+        for (name, value) in cache_control(headers) {
+            match name.as_str() {
+                "no-cache" | "no-store" => uncacheable = true,
+                "max-age" => max_age = seconds(value.as_ref()),
+                "s-maxage" => s_maxage = seconds(value.as_ref()),
+                "stale-while-revalidate" => stale_while_revalidate = seconds(value.as_ref()),
+                "stale-if-error" => stale_if_error = seconds(value.as_ref()),
+                _ => {}
+            }
+        }
+        let lifetime = if uncacheable {
+            Duration::ZERO
+        } else {
+            s_maxage
+                .or(max_age)
+                .unwrap_or_else(|| expires_lifetime(headers))
+        };
+        // `Age` comes off the lifetime rather than off `received`: an `Instant` cannot go back
+        // further than the process has run, and an `Age` at or past the lifetime must still
+        // give an entry that is already expired. Whatever it overshoots by comes off the stale
+        // windows, which are measured from the expiry.
+        let upstream_age = age(headers);
+        let overdue = upstream_age.saturating_sub(lifetime);
+        HttpCached {
+            value,
+            received,
+            upstream_age,
+            expires_at: received.checked_add(lifetime.saturating_sub(upstream_age)),
+            stale_while_revalidate: stale_while_revalidate.map(|w| w.saturating_sub(overdue)),
+            stale_if_error: stale_if_error.map(|w| w.saturating_sub(overdue)),
+        }
+    }
+
+    /// The wrapped value.
+    #[must_use]
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Unwraps the value.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// The response's age: time since it was received plus its `Age` header.
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        self.received.elapsed().saturating_add(self.upstream_age)
+    }
+
+    /// The end of the `stale-while-revalidate` window, or `None` without that directive or
+    /// when the window ends past what `Instant` can represent.
+    #[must_use]
+    pub fn stale_while_revalidate_until(&self) -> Option<Instant> {
+        self.window_end(self.stale_while_revalidate)
+    }
+
+    /// The end of the `stale-if-error` window, or `None` without that directive or when the
+    /// window ends past what `Instant` can represent (so the fallback is unbounded).
+    #[must_use]
+    pub fn stale_if_error_until(&self) -> Option<Instant> {
+        self.window_end(self.stale_if_error)
+    }
+
+    fn window_end(&self, window: Option<Duration>) -> Option<Instant> {
+        self.expires_at?.checked_add(window?)
+    }
+}
+
+impl<T> Expires for HttpCached<T> {
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    fn expires_at(&self) -> Option<Instant> {
+        self.expires_at
+    }
+
+    fn serve_stale_on_error(&self) -> bool {
+        self.stale_if_error_until()
+            .is_none_or(|until| Instant::now() < until)
+    }
+}
+
+/// `Expires` minus `Date`, or minus now without a valid `Date`. Zero when `Expires` is absent,
+/// invalid or in the past.
+fn expires_lifetime(headers: &HeaderMap) -> Duration {
+    let Some(expires) = http_date(headers, header::EXPIRES) else {
+        return Duration::ZERO;
+    };
+    let date = http_date(headers, header::DATE).unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+    });
+    expires.saturating_sub(date)
+}
+
+/// The header's HTTP date as time since the Unix epoch.
+fn http_date(headers: &HeaderMap, name: header::HeaderName) -> Option<Duration> {
+    parse_http_date(headers.get(name)?)
+}
+
+/// An HTTP date header value as time since the Unix epoch.
+pub(crate) fn parse_http_date(value: &HeaderValue) -> Option<Duration> {
+    let date = httpdate::parse_http_date(value.to_str().ok()?).ok()?;
+    date.duration_since(std::time::UNIX_EPOCH).ok()
+}
+
+/// The response's `Age`, or zero without a valid one.
+pub(crate) fn age(headers: &HeaderMap) -> Duration {
+    headers
+        .get(header::AGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map_or(Duration::ZERO, Duration::from_secs)
+}
+
+/// The `Cache-Control` directives in `headers`, names lowercased and values unquoted.
+pub(crate) fn cache_control(headers: &HeaderMap) -> Vec<(String, Option<String>)> {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|directive| {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                return None;
+            }
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name, Some(value.trim().trim_matches('"').to_owned())),
+                None => (directive, None),
+            };
+            Some((name.trim().to_ascii_lowercase(), value))
+        })
+        .collect()
+}
+
+pub(crate) fn seconds(value: Option<&String>) -> Option<Duration> {
+    value?.parse().ok().map(Duration::from_secs)
+}
@@ -118,7 +118,20 @@ the sync methods.
   Implies `async` and `redis_store`, but is runtime-agnostic (`redis/cache-aio` needs only `redis/aio`): pair it with a
   runtime feature (`redis_tokio*` or `redis_smol*`) or the build has no runtime to connect with. Does not enable TLS.
 - `redb_store`: Include disk cache store
-- `time_stores`: Include time-based cache stores ([`TtlCache`](https://docs.rs/cached/latest/cached/struct.TtlCache.html), [`LruTtlCache`](https://docs.rs/cached/latest/cached/struct.LruTtlCache.html), [`TtlSortedCache`](https://docs.rs/cached/latest/cached/struct.TtlSortedCache.html), [`ShardedTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedTtlCache.html), and [`ShardedLruTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedLruTtlCache.html)).
+- `persist`: Snapshot and restore the in-memory stores through `serde` (`CacheSnapshot`, `CachedSnapshot`,
+  `ConcurrentCachedSnapshot`), and the `persist_path` attribute on `#[cached]`/`#[concurrent_cached]` that loads a
+  saved snapshot on first use and emits a `{fn}_save_cache()` companion to write it.
+- `metrics`: Publish store statistics through the [`metrics`](https://docs.rs/metrics) facade: `metrics_name(...)` on
+  the store builders, `publish_metrics()`, and the `metrics_name` attribute on the macros, which also times misses.
+- `tracing`: Emit [`tracing`](https://docs.rs/tracing) spans: a `cached.call` span around each macro-generated
+  function call recording its outcome (`hit`, `miss`, `stale`) and key hash, and spans around the Redis and redb
+  operations covering the round trip or transaction, (de)serialization, and self-heal deletes.
+- `tower`: `CacheLayer`, a [`tower`](https://docs.rs/tower) middleware that caches HTTP responses in any
+  `ConcurrentCachedAsync` store (a `ShardedExpiringLruCache` by default), honoring `Cache-Control`, `Age`, `Vary`,
+  `If-None-Match` and `If-Modified-Since`. Implies `async`.
+- `http`: `cached::http::HttpCached<T>`, an `Expires` wrapper whose deadline comes from a response's
+  `Cache-Control`, `Expires` and `Age` headers, and whose `stale-if-error` bounds `result_fallback`.
+- `time_stores`: Include time-based cache stores ([`TtlCache`](https://docs.rs/cached/latest/cached/struct.TtlCache.html), [`LruTtlCache`](https://docs.rs/cached/latest/cached/struct.LruTtlCache.html), [`TtlSortedCache`](https://docs.rs/cached/latest/cached/struct.TtlSortedCache.html), [`ShardedTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedTtlCache.html), [`ShardedTtlSortedCache`](https://docs.rs/cached/latest/cached/struct.ShardedTtlSortedCache.html), and [`ShardedLruTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedLruTtlCache.html)).
   Also required when using `#[cached(ttl_secs = ...)]`, `#[cached(ttl = ...)]`, `#[cached(ttl_millis = ...)]`, `#[concurrent_cached(ttl_secs = ...)]`, `#[concurrent_cached(ttl = ...)]`, or `#[concurrent_cached(ttl_millis = ...)]` on the default in-memory path. (`#[once]` has its own ungated timer, so `#[once(ttl_secs = ...)]` does NOT require this feature.)
   Disable this feature when targeting environments without system time support (e.g. `wasm32-unknown-unknown` without WASI or JS).
 
@@ -145,8 +158,10 @@ Any custom cache that implements `cached::ConcurrentCached`/`cached::ConcurrentC
 | Don't cache `Err` returns (implicit for `Result<T, E>`) | `#[cached] fn load(id: u64) -> Result<Data, E>` |
 | Force-cache `None` returns | `#[cached(cache_none = true)] fn find(id: u64) -> Option<User>` |
 | Force-cache `Err` returns | `#[cached(cache_err = true)] fn load(id: u64) -> Result<Data, E>` |
+| Cache `None` / `Err` returns for less time than values | `#[cached(ttl_secs = 3600, cache_none = true, none_ttl_secs = 30)] fn find(id: u64) -> Option<User>` |
 | Serve stale value when function returns `Err` | `#[cached(result_fallback = true, ttl_secs = 60)] fn fetch(id: u64) -> Result<Data, E>` |
 | Per-value / dynamic per-entry TTL (value carries its own expiry) | `#[cached(expires = true)] fn token(scope: String) -> Token` |
+| Per-entry TTL computed from the returned value | `#[cached(ttl_secs = 60, ttl_fn = { ttl_of(&result) })] fn fetch(id: u64) -> Data` |
 | Deduplicate concurrent first calls per key (opt-in; do not use on recursive functions) | `#[cached(ttl_secs = 30, sync_writes = "by_key")] fn expensive(id: u64) -> Payload` |
 | Recompute when an expression over the args is true | `#[cached(force_refresh = { id == 0 })] fn fetch(id: u64) -> Data` |
 | Force-refresh via a dedicated flag (exclude it from the key) | `#[cached(key = "u64", convert = { id }, force_refresh = { refresh })] fn fetch(id: u64, refresh: bool) -> Data { let _ = refresh; … }` — the generated guard reads `refresh` to decide whether to bypass the cache; the function body still receives `refresh` as a normal parameter, so if your body does not otherwise use it, add `let _ = refresh;` (or `#[allow(unused_variables)]`) to silence the unused-variable warning |
@@ -163,6 +178,8 @@ Any custom cache that implements `cached::ConcurrentCached`/`cached::ConcurrentC
 | **`#[concurrent_cached]`** | |
 | Thread-safe sharded memoize (no global lock per call) | `#[concurrent_cached] fn compute(x: u64) -> u64` |
 | Sharded with LRU | `#[concurrent_cached(max_size = 1_000)] fn lookup(id: u64) -> Row` |
+| Sharded, scan-resistant (ARC) | `#[concurrent_cached(max_size = 1_000, policy = "arc")] fn lookup(id: u64) -> Row` |
+| Sharded, read-heavy and bounded (S3-FIFO) | `#[concurrent_cached(max_size = 1_000, policy = "s3fifo")] fn lookup(id: u64) -> Row` |
 | Sharded with TTL | `#[concurrent_cached(ttl_secs = 60)] fn fetch(url: String) -> Body` |
 | Sharded LRU + TTL with custom shard count | `#[concurrent_cached(max_size = 1_000, ttl_secs = 60, shards = 32)] fn query(id: u64) -> Row` |
 | TTL in milliseconds (sub-second; Redis honors millisecond TTL via PSETEX/PEXPIRE) | `#[concurrent_cached(ttl_millis = 500)] fn poll(id: u64) -> Status` |
@@ -170,7 +187,9 @@ Any custom cache that implements `cached::ConcurrentCached`/`cached::ConcurrentC
 | Per-value expiry with LRU bound | `#[concurrent_cached(expires = true, max_size = 1_000)] fn session(id: u32) -> Token` |
 | Cache only successful results (implicit for `Result<T, E>`) | `#[concurrent_cached] fn load(id: u64) -> Result<Row, DbError>` |
 | Don't cache `None` returns (implicit for `Option<T>`) | `#[concurrent_cached] fn find(id: u64) -> Option<Row>` |
+| Cache `Err` returns for less time than values | `#[concurrent_cached(ttl_secs = 3600, cache_err = true, err_ttl_secs = 30)] fn load(id: u64) -> Result<Row, DbError>` |
 | Serve stale value when function returns `Err` | `#[concurrent_cached(result_fallback = true, ttl_secs = 60)] fn fetch(id: u64) -> Result<Data, E>` |
+| Per-entry TTL computed from the returned value | `#[concurrent_cached(ttl_secs = 60, ttl_fn = { ttl_of(&result) })] fn fetch(id: u64) -> Data` |
 | Recompute when an expression over the args is true | `#[concurrent_cached(force_refresh = { id == 0 })] fn fetch(id: u64) -> Data` |
 | Force-refresh via a dedicated flag (exclude it from the key) | `#[concurrent_cached(key = "u64", convert = { id }, force_refresh = { refresh })] fn fetch(id: u64, refresh: bool) -> Data { let _ = refresh; … }` — the generated guard reads `refresh` to decide whether to bypass the cache; the body still receives it as a normal parameter, so add `let _ = refresh;` (or `#[allow(unused_variables)]`) if your body does not otherwise use it |
 | Cache a method inside an `impl` block (one cache shared across all instances) | `#[concurrent_cached(in_impl = true)] fn load(&self, id: u64) -> Data` |
@@ -195,33 +214,42 @@ For `disk` and `redis` stores, `Result<T, E>` is required. `map_error` is option
 |---|---|---|---|---|---|---|---|
 | [`UnboundCache`](https://docs.rs/cached/latest/cached/struct.UnboundCache.html) | None (unbounded) | No | No | N/A | On explicit remove | No | Yes |
 | [`LruCache`](https://docs.rs/cached/latest/cached/struct.LruCache.html) | LRU | Yes | No | N/A | Yes | No | Yes |
-| [`TtlCache`](https://docs.rs/cached/latest/cached/struct.TtlCache.html) | TTL (insert time) | No | Global | Optional | Yes | No | Yes |
-| [`LruTtlCache`](https://docs.rs/cached/latest/cached/struct.LruTtlCache.html) | LRU + TTL | Yes | Global | Optional | Yes | No | Yes |
-| [`TtlSortedCache`](https://docs.rs/cached/latest/cached/struct.TtlSortedCache.html) | TTL (expiry-ordered) | Optional | Global | No | Yes | No | Yes |
+| [`ArcCache`](https://docs.rs/cached/latest/cached/struct.ArcCache.html) | ARC (adaptive recency/frequency) | Yes | No | N/A | Yes | No | Yes |
+| [`S3FifoCache`](https://docs.rs/cached/latest/cached/struct.S3FifoCache.html) | S3-FIFO (small/main/ghost queues) | Yes | No | N/A | Yes | No | Yes |
+| [`LfuCache`](https://docs.rs/cached/latest/cached/struct.LfuCache.html) | LFU (frequency buckets, optional halving) | Yes | No | N/A | Yes | No | Yes |
+| [`PolicyCache`](https://docs.rs/cached/latest/cached/struct.PolicyCache.html) | Pluggable ([`EvictionPolicy`](https://docs.rs/cached/latest/cached/trait.EvictionPolicy.html)) | Yes | Optional | No | Yes | No | Yes |
+| [`TtlCache`](https://docs.rs/cached/latest/cached/struct.TtlCache.html) | TTL (insert time) | No | Global + per-entry | Optional | Yes | No | Yes |
+| [`LruTtlCache`](https://docs.rs/cached/latest/cached/struct.LruTtlCache.html) | LRU + TTL | Yes | Global + per-entry | Optional | Yes | No | Yes |
+| [`TtlSortedCache`](https://docs.rs/cached/latest/cached/struct.TtlSortedCache.html) | TTL (expiry-ordered) | Optional | Global + per-entry | No | Yes | No | Yes |
 | [`ExpiringLruCache`](https://docs.rs/cached/latest/cached/struct.ExpiringLruCache.html) | LRU + value-defined | Yes | Per-value | N/A | Yes | No | Yes |
 | [`ExpiringCache`](https://docs.rs/cached/latest/cached/struct.ExpiringCache.html) | Value-defined | No | Per-value | N/A | Yes | No | Yes |
 | [`ShardedUnboundCache`](https://docs.rs/cached/latest/cached/struct.ShardedUnboundCache.html) | None (unbounded) | No | No | N/A | On explicit remove | Yes (`Arc`) | Yes |
 | [`ShardedLruCache`](https://docs.rs/cached/latest/cached/struct.ShardedLruCache.html) | LRU | Yes | No | N/A | Yes | Yes (`Arc`) | Yes |
-| [`ShardedTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedTtlCache.html) | TTL (insert time) | No | Global | Optional | Yes | Yes (`Arc`) | Yes |
-| [`ShardedLruTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedLruTtlCache.html) | LRU + TTL | Yes | Global | Optional | Yes (†) | Yes (`Arc`) | Yes |
+| [`ShardedArcCache`](https://docs.rs/cached/latest/cached/struct.ShardedArcCache.html) | ARC (per shard) | Yes | No | N/A | Yes | Yes (`Arc`) | Yes |
+| [`ShardedS3FifoCache`](https://docs.rs/cached/latest/cached/struct.ShardedS3FifoCache.html) | S3-FIFO (per shard, read-locked hits) | Yes | No | N/A | Yes | Yes (`Arc`) | Yes |
+| [`ShardedLfuCache`](https://docs.rs/cached/latest/cached/struct.ShardedLfuCache.html) | LFU (per shard) | Yes | No | N/A | Yes | Yes (`Arc`) | Yes |
+| [`ShardedPolicyCache`](https://docs.rs/cached/latest/cached/struct.ShardedPolicyCache.html) | Pluggable (one policy per shard) | Yes | Optional | No | Yes | Yes (`Arc`) | Yes |
+| [`ShardedTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedTtlCache.html) | TTL (insert time) | No | Global + per-entry | Optional | Yes | Yes (`Arc`) | Yes |
+| [`ShardedTtlSortedCache`](https://docs.rs/cached/latest/cached/struct.ShardedTtlSortedCache.html) | TTL (expiry-ordered per shard, read-locked hits) | Optional | Global + per-entry | No | Yes | Yes (`Arc`) | Yes |
+| [`ShardedLruTtlCache`](https://docs.rs/cached/latest/cached/struct.ShardedLruTtlCache.html) | LRU + TTL | Yes | Global + per-entry | Optional | Yes (†) | Yes (`Arc`) | Yes |
 | [`ShardedExpiringCache`](https://docs.rs/cached/latest/cached/struct.ShardedExpiringCache.html) | Value-defined | No | Per-value | N/A | Yes | Yes (`Arc`) | Yes |
 | [`ShardedExpiringLruCache`](https://docs.rs/cached/latest/cached/struct.ShardedExpiringLruCache.html) | LRU + value-defined | Yes | Per-value | N/A | Yes | Yes (`Arc`) | Yes |
 
 > "On explicit remove" — `on_evict` fires only on `cache_remove`; there is no capacity eviction or TTL expiry trigger for these stores.
 > † `ShardedLruTtlCacheBuilder::on_evict` requires `K: 'static + V: 'static`; see the builder docs for details.
 
-`TtlCache`/`LruTtlCache`/`TtlSortedCache`/`ShardedTtlCache`/`ShardedLruTtlCache` require the `time_stores` feature.
+`TtlCache`/`LruTtlCache`/`TtlSortedCache`/`ShardedTtlCache`/`ShardedTtlSortedCache`/`ShardedLruTtlCache` require the `time_stores` feature.
 
 `ShardedUnboundCache` and its variants are partitioned across power-of-two shards, each protected by a `parking_lot::RwLock`. The default shard count is derived in two steps:
 
 - The host default is `available_parallelism() × 4`, clamped to 8–1024 and rounded up to a power of two. It is sampled once per process and reused by every cache built afterward.
 - On the default path, the three LRU-bounded sharded stores (`ShardedLruCache`, `ShardedLruTtlCache`, `ShardedExpiringLruCache`) scale that down to match a total `max_size`: the count is `next_power_of_two(max_size / 16)`, clamped into `[1, host_default]`. This keeps each shard holding roughly 16 entries instead of preallocating an oversized shard array for a small cache — e.g. `ShardedLruCache::new(100)` builds 8 shards (`100 / 16 = 6` → `8`) with a total capacity of 128 (the 16-per-shard floor below), rather than one shard per host default.
 
-Everything else keeps the plain host default: the unbounded stores (`ShardedUnboundCache`, and `ShardedTtlCache` / `ShardedExpiringCache` built without a `max_size`), the builder's `per_shard_max_size` path, and any explicit `shards = N` / `.shards(n)`. An explicit shard count is rounded up to a power of two but never clamped.
+Everything else keeps the plain host default: the unbounded stores (`ShardedUnboundCache`, and `ShardedTtlCache` / `ShardedTtlSortedCache` / `ShardedExpiringCache` built without a `max_size`), the builder's `per_shard_max_size` path, and any explicit `shards = N` / `.shards(n)`. An explicit shard count is rounded up to a power of two but never clamped.
 
 Shard structs are padded to 128-byte alignment (covering Intel adjacent-line prefetch and Apple Silicon 128-byte L1 lines) to eliminate false sharing; on a 64-shard deployment this amounts to ~8 KB of padding overhead per cache array. The outer type is an `Arc` — cloning is a reference share, not a deep copy (use `deep_clone()` for an independent copy; note that `deep_clone()` is an inherent method on each concrete sharded type, not part of any trait). They implement `ConcurrentCached`/`ConcurrentCachedAsync` and are the default store selected by `#[concurrent_cached]`.
 For sharded LRU variants, eviction is enforced independently per shard. `max_size = N` is divided across shards with ceiling division. Use the builder's `per_shard_max_size` method for an exact per-shard cap (builder-only; `#[concurrent_cached]` does not expose a `per_shard_max_size` attribute — use `shards` to control parallelism and `max_size` for total capacity). **Capacity Fragmentation Warning**: To protect against premature evictions due to hash collisions in extremely small caches (where a shard capacity could drop to 1-2 entries), when sharding is active (`shards > 1`) we enforce a minimum capacity of `16` entries **per shard** (e.g., minimum total capacity of `128` on a single-core machine with 8 shards, or `256` on a 4-core machine with 16 shards). If you require smaller, strict limits under low capacities, configure `shards = 1` or specify `per_shard_max_size` directly (builder-only; not available via `#[concurrent_cached]`).
-Because LRU caches require updating access recency, `ShardedLruCache`, `ShardedLruTtlCache`, and `ShardedExpiringLruCache` must acquire an exclusive **write lock** on accessed shards during read hits, which can lead to contention under highly concurrent read-heavy workloads. Unbounded `ShardedUnboundCache`, time-only `ShardedTtlCache` (when `refresh_on_hit` is disabled -- enabling it promotes read hits to exclusive write locks), and expiring `ShardedExpiringCache` require only a **shared read lock** on read hits, avoiding this contention. To mitigate contention on LRU variants, consider increasing the number of `shards` to distribute writes. Note: this write-lock-on-read behavior is a known limitation of the strict-LRU sharded stores. A future read-optimized variant that relaxes strict recency ordering will ship as a separate store type; the existing stores will not change semantics.
+Because LRU caches require updating access recency, `ShardedLruCache`, `ShardedLruTtlCache`, and `ShardedExpiringLruCache` must acquire an exclusive **write lock** on accessed shards during read hits, which can lead to contention under highly concurrent read-heavy workloads. Unbounded `ShardedUnboundCache`, time-only `ShardedTtlCache` (when `refresh_on_hit` is disabled -- enabling it promotes read hits to exclusive write locks), `ShardedTtlSortedCache`, and expiring `ShardedExpiringCache` require only a **shared read lock** on read hits, avoiding this contention. To mitigate contention on LRU variants, consider increasing the number of `shards` to distribute writes. Note: this write-lock-on-read behavior is a known limitation of the strict-LRU sharded stores. A future read-optimized variant that relaxes strict recency ordering will ship as a separate store type; the existing stores will not change semantics.
 
 > **Custom shard hashers:** Every sharded store carries a third, defaulted type parameter for its [`ShardHasher`] — `ShardedUnboundCache<K, V, H = DefaultShardHasher>`, `ShardedLruCache<K, V, H = DefaultShardHasher>`, and so on — mirroring `std::collections::HashMap<K, V, S = RandomState>`. Writing `ShardedLruCache<K, V>` therefore gets the default hasher, which is what most users want; name the third parameter only when routing keys through a custom `ShardHasher`. Construct such a cache through the builder's `hasher` method: `ShardedLruCache::builder().hasher(my_hasher)` switches the builder's hasher type and `build` yields a `ShardedLruCache<K, V, H>` over `my_hasher`. `new`/`builder` are defined only on the default-hasher instantiation, so a custom hasher is always introduced through `hasher`, never a `ShardedLruCache::<_, _, H>` turbofish (which would otherwise silently drop the hasher).
 
@@ -313,8 +341,9 @@ Because LRU caches require updating access recency, `ShardedLruCache`, `ShardedL
   (`async_cache_peek`, with an `async_peek` alias); it carries the identical no-recency,
   no-TTL-refresh, no-metrics, no-lazy-expiry contract and is deliberately not implemented by the
   IO stores.
-  The four expiry-capable sharded stores ([`ShardedTtlCache`], [`ShardedLruTtlCache`],
-  [`ShardedExpiringCache`], [`ShardedExpiringLruCache`]) implement [`ConcurrentCloneCached`],
+  The expiry-capable sharded stores ([`ShardedTtlCache`], [`ShardedTtlSortedCache`],
+  [`ShardedLruTtlCache`], [`ShardedExpiringCache`], [`ShardedExpiringLruCache`]) implement
+  [`ConcurrentCloneCached`],
   which provides `cache_get_with_expiry_status` for reading stale entries without evicting them, and
   `cache_peek_with_expiry_status` as a side-effect-free counterpart (a read with no hit/miss
   counting, LRU promotion, or TTL renewal).
@@ -416,6 +445,24 @@ assert_eq!(c.value_order().into_values(), vec![1, 2]);
 assert_eq!(c.iter_order().into_values(), vec![1, 2]);
 ```
 
+The same stores and their sharded counterparts can pin entries that must stay resident:
+`pin(&k)` or `set_pinned(k, v)` exempts an entry from capacity eviction until `unpin(&k)`.
+Pinned entries do not count toward `max_size`, still expire, and come first in the order
+methods with `CacheValue::is_pinned()` set. A builder's `max_pinned(n)` caps them, and a pin
+beyond the cap fails with `PinError::LimitReached`.
+
+```rust
+use cached::{CachedExt, LruCache};
+
+let mut c: LruCache<&str, u32> = LruCache::new(2);
+c.set_pinned("config", 0).unwrap();
+for (i, k) in ["a", "b", "c", "d"].into_iter().enumerate() {
+    c.set(k, i as u32);
+}
+assert_eq!(c.key_order(), vec!["config", "d", "c"]);
+assert!(c.iter_order()[0].1.is_pinned());
+```
+
 [`TtlSortedCache::set_with`] starts a builder-style insert with a per-entry TTL override and an
 opt-in expiry sweep, terminated by `.set()`. Plain `set` uses the cache's default TTL and never
 runs the sweep (size-limit enforcement is unaffected by either).
@@ -436,6 +483,15 @@ assert_eq!(c.len(), 2);
 # }
 ```
 
+`TtlCache`, `LruTtlCache`, `ShardedTtlCache` and `ShardedLruTtlCache` take the same override as
+a plain call: `set_with_ttl(k, v, ttl)` for a TTL, `set_until(k, v, deadline)` for an absolute
+`Instant`. `LruTtlCache::iter_order` reports either through `CacheValue::expires_at`. In the
+macros, `ttl_fn = { ... }` computes the TTL from the value being cached, bound as `result`,
+and `none_ttl_secs` / `err_ttl_secs` (or the `_millis` and `Duration` forms) store a cached
+`None` or `Err` for a shorter TTL than a value, so a miss is neither re-fetched on every call
+nor served long after the backend recovers. `RedbCache`, `RedisCache` and `AsyncRedisCache`
+have `set_with_ttl(k, v, ttl)` as well.
+
 **Performance**
 
 v3 reworks the hot paths of the in-memory and sharded stores. Steady-state `O(1)` reads and
@@ -460,7 +516,7 @@ overwrite now promotes the key to most-recently-used, which adds a small cost on
 
 **Per-Value Expiry via the `Expires` Trait**
 
-While standard timed stores (`TtlCache`, `LruTtlCache`, `TtlSortedCache`) enforce a single, global Time-To-Live (TTL) duration applied to all entries in the cache, [`ExpiringLruCache`] and [`ExpiringCache`] let each individual value determine its own expiration. This is accomplished by storing values that implement the [`Expires`] trait.
+While standard timed stores (`TtlCache`, `LruTtlCache`, `TtlSortedCache`) apply one Time-To-Live (TTL) duration to every entry unless the insert overrides it, [`ExpiringLruCache`] and [`ExpiringCache`] let each individual value determine its own expiration. This is accomplished by storing values that implement the [`Expires`] trait.
 
 This approach is highly useful when caching payloads like OAuth tokens, HTTP responses with varying `Cache-Control` headers, or database records that contain their own absolute expiration timestamps.
 
@@ -504,7 +560,12 @@ For concurrent (multi-thread, no external lock) use, the sharded equivalents [`S
 > [`ConcurrentCacheEvict`] (`use cached::ConcurrentCacheEvict;`, `&self`) or its inherent
 > `evict(&self)` method; note that `evict()` on sharded TTL and expiring stores requires
 > `K: Clone`. Alternatively, prefer `ExpiringLruCache` / `ShardedExpiringLruCache` with a
-> `max_size` bound.
+> `max_size` bound. Building either store with `.timer_wheel(resolution)` makes `evict()`, and
+> the budgeted `evict_some(n)`, visit only the entries whose
+> [`Expires::expires_at`](crate::Expires::expires_at) deadline has passed instead of every entry,
+> plus a scan for expired values while any value without a deadline is stored.
+> To spread a sweep over time on any store, call `evict_budgeted(n)` or `evict_for(duration)`
+> instead: each call examines a bounded slice and the next one resumes where it stopped.
 
 ```rust
 use cached::{CachedExt, Expires, ExpiringCache, ExpiringLruCache};
@@ -847,18 +908,36 @@ pub use stores::{AsyncRedisCache, AsyncRedisCacheBuilder};
 // in the dependency, which compiles before `cached`, so a `compile_error!` here could not
 // preempt it. The requirement is documented on each capability feature in Cargo.toml: pair it
 // with a `redis_tokio*` or `redis_smol*` runtime feature.
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub use stores::MetricsExporter;
+#[cfg(any(feature = "redb_store", feature = "redis_store"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "redb_store", feature = "redis_store"))))]
+pub use stores::StoredEntry;
 pub use stores::{
-    BuildError, CacheEvict, CacheValue, ConcurrentCacheEvict, DefaultHashBuilder,
-    DefaultShardHasher, Expires, ExpiringCache, ExpiringCacheBuilder, ExpiringLruCache,
-    ExpiringLruCacheBuilder, IntoValues, LruCache, LruCacheBuilder, SetMaxSizeError, SetTtlError,
-    ShardHasher, ShardedExpiringCache, ShardedExpiringCacheBuilder, ShardedExpiringLruCache,
-    ShardedExpiringLruCacheBuilder, ShardedLruCache, ShardedLruCacheBuilder, ShardedUnboundCache,
-    ShardedUnboundCacheBuilder, UnboundCache, UnboundCacheBuilder,
+    ArcCache, ArcCacheBuilder, BuildError, CacheEvict, CacheValue, CachedTags,
+    ConcurrentCacheEvict, ConcurrentCachedTags, DeepSize, DefaultHashBuilder, DefaultShardHasher,
+    EvictProgress, EvictionPolicy, Expires, ExpiringCache, ExpiringCacheBuilder, ExpiringLruCache,
+    ExpiringLruCacheBuilder, FaultyCache, FaultyCacheBuilder, HotKey, InjectedFault, IntoValues,
+    LfuCache, LfuCacheBuilder, LruCache, LruCacheBuilder, LruPolicy, PinError, PolicyCache,
+    PolicyCacheBuilder, ReshardError, S3FifoCache, S3FifoCacheBuilder, SetMaxSizeError,
+    SetTtlError, ShardHasher, ShardStats, ShardedArcCache, ShardedArcCacheBuilder,
+    ShardedExpiringCache, ShardedExpiringCacheBuilder, ShardedExpiringLruCache,
+    ShardedExpiringLruCacheBuilder, ShardedLfuCache, ShardedLfuCacheBuilder, ShardedLruCache,
+    ShardedLruCacheBuilder, ShardedPolicyCache, ShardedPolicyCacheBuilder, ShardedS3FifoCache,
+    ShardedS3FifoCacheBuilder, ShardedUnboundCache, ShardedUnboundCacheBuilder, UnboundCache,
+    UnboundCacheBuilder,
+};
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+pub use stores::{
+    CacheSnapshot, CachedSnapshot, ConcurrentCachedSnapshot, PersistError, SnapshotEntry,
 };
 #[cfg(feature = "redis_store")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redis_store")))]
 pub use stores::{
-    ConnectionString, RedisCache, RedisCacheBuildError, RedisCacheBuilder, RedisCacheError,
+    ConnectionString, MockRedisCache, MockRedisCacheBuilder, MockRedisFault, MockRedisServer,
+    RedisCache, RedisCacheBuildError, RedisCacheBuilder, RedisCacheError, RedisScan, RedisSizeMode,
 };
 #[cfg(feature = "time_stores")]
 #[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
@@ -867,18 +946,28 @@ pub use stores::{HasEvict, NoEvict};
 #[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
 pub use stores::{
     LruTtlCache, LruTtlCacheBuilder, ShardedLruTtlCache, ShardedLruTtlCacheBuilder,
-    ShardedTtlCache, ShardedTtlCacheBuilder, TtlCache, TtlCacheBuilder, TtlSortedCache,
-    TtlSortedCacheBuilder, TtlSortedSetBuilder,
+    ShardedTtlCache, ShardedTtlCacheBuilder, ShardedTtlSortedCache, ShardedTtlSortedCacheBuilder,
+    ShardedTtlSortedSetBuilder, TtlCache, TtlCacheBuilder, TtlSortedCache, TtlSortedCacheBuilder,
+    TtlSortedSetBuilder,
 };
 #[cfg(feature = "redb_store")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redb_store")))]
-pub use stores::{RedbCache, RedbCacheBuildError, RedbCacheBuilder, RedbCacheError};
+pub use stores::{
+    RedbCache, RedbCacheBuildError, RedbCacheBuilder, RedbCacheError, RedbIter, RedbKeys,
+};
 
+#[cfg(feature = "http")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub mod http;
 mod lru_list;
 #[cfg(feature = "proc_macro")]
 #[cfg_attr(docsrs, doc(cfg(feature = "proc_macro")))]
 pub mod macros;
+pub mod registry;
 pub mod stores;
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+pub mod tower;
 /// Re-export of the [`web_time`](https://docs.rs/web_time) crate,
 /// which provides time types compatible with both native and WebAssembly targets.
 pub use web_time as time;
@@ -1057,6 +1146,117 @@ macro_rules! __require_redis_feature {
     };
 }
 
+/// Guard macro emitted by `#[cached(persist_path = "...")]` and
+/// `#[concurrent_cached(persist_path = "...")]`. Expands to nothing when the `persist` feature is
+/// enabled and to a `compile_error!` naming it otherwise, instead of the "cannot find
+/// `PersistError` in `cached`" errors the generated `{fn}_save_cache` would produce.
+///
+/// Invoked as `cached::__require_persist_feature!{}`.
+///
+/// This is an internal implementation detail; do not call it from user code.
+#[cfg(feature = "persist")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_persist_feature {
+    () => {};
+}
+
+#[cfg(not(feature = "persist"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_persist_feature {
+    () => {
+        compile_error!("`persist_path` requires the `persist` feature of the `cached` crate");
+    };
+}
+
+/// Invoked as `cached::__require_metrics_feature!{}`.
+///
+/// This is an internal implementation detail; do not call it from user code.
+#[cfg(feature = "metrics")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_metrics_feature {
+    () => {};
+}
+
+#[cfg(not(feature = "metrics"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_metrics_feature {
+    () => {
+        compile_error!("`metrics_name` requires the `metrics` feature of the `cached` crate");
+    };
+}
+
+/// Wraps the rest of a macro-generated cached function in a `cached.call` span. With the
+/// `tracing` feature off it expands to the body unchanged, so the macros can emit it
+/// unconditionally without a feature guard.
+///
+/// Invoked as `cached::__cached_call_span!{ "fn_name", &key; sync { ... } }` (or `async`); the
+/// key is optional. An `async` body runs in an `async move` block instrumented with the span,
+/// since an entered span must not be held across an `.await`.
+///
+/// This is an internal implementation detail; do not call it from user code.
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cached_call_span {
+    ($name:literal $(, $key:expr)?; sync { $($body:tt)* }) => {{
+        let __cached_entered = $crate::__private::call_span(
+            $name,
+            $crate::__cached_call_span!(@hash $($key)?),
+        )
+        .entered();
+        $($body)*
+    }};
+    ($name:literal $(, $key:expr)?; async { $($body:tt)* }) => {{
+        // Opened before the block below moves the key.
+        let __cached_span = $crate::__private::call_span(
+            $name,
+            $crate::__cached_call_span!(@hash $($key)?),
+        );
+        $crate::__private::Instrument::instrument(async move { $($body)* }, __cached_span).await
+    }};
+    (@hash $key:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{HashableKey as _, UnhashableKey as _};
+        (&$crate::__private::KeyHashProbe($key)).key_hash()
+    }};
+    (@hash) => {
+        ::std::option::Option::None
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cached_call_span {
+    ($name:literal $(, $key:expr)?; $mode:ident { $($body:tt)* }) => {
+        $($body)*
+    };
+}
+
+/// Records `hit`, `miss` or `stale` as the outcome of the current `cached.call` span; nothing
+/// without the `tracing` feature.
+///
+/// This is an internal implementation detail; do not call it from user code.
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cached_call_outcome {
+    ($outcome:literal) => {
+        $crate::__private::record_call_outcome($outcome);
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cached_call_outcome {
+    ($outcome:literal) => {};
+}
+
 /// Internal support types used by macro-generated code.
 ///
 /// Doc-hidden and **not** a stable public API: the contents may change in any release. It lives
@@ -1138,6 +1338,189 @@ pub mod __private {
             &self.cache
         }
     }
+
+    /// Restores the snapshot at `path` into a freshly built store, backing the `persist_path`
+    /// initializer, and returns how many entries were restored. A missing file is a cold start
+    /// (`Ok(0)`). Any other failure also leaves the store empty, since a cache that cannot warm
+    /// up still has to come up, but is returned so `{fn}_restore_result` can report it.
+    #[cfg(feature = "persist")]
+    pub fn persist_restore<C, K, V>(
+        cache: &mut C,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<usize, crate::PersistError>
+    where
+        C: crate::CachedSnapshot<K, V>,
+        K: serde::de::DeserializeOwned,
+        V: serde::de::DeserializeOwned,
+    {
+        Ok(load_snapshot(path)?.map_or(0, |snapshot| cache.restore(snapshot)))
+    }
+
+    /// Concurrent-store counterpart of [`persist_restore`].
+    #[cfg(feature = "persist")]
+    pub fn persist_restore_concurrent<C, K, V>(
+        cache: &C,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<usize, crate::PersistError>
+    where
+        C: crate::ConcurrentCachedSnapshot<K, V>,
+        K: serde::de::DeserializeOwned,
+        V: serde::de::DeserializeOwned,
+    {
+        Ok(load_snapshot(path)?.map_or(0, |snapshot| cache.restore(snapshot)))
+    }
+
+    /// [`CacheSnapshot::load`](crate::CacheSnapshot::load), with a missing file read as no
+    /// snapshot rather than an error.
+    #[cfg(feature = "persist")]
+    fn load_snapshot<K, V>(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Option<crate::CacheSnapshot<K, V>>, crate::PersistError>
+    where
+        K: serde::de::DeserializeOwned,
+        V: serde::de::DeserializeOwned,
+    {
+        match crate::CacheSnapshot::load(path) {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(crate::PersistError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Body of the generated `{fn}_save_cache` companion: snapshot, write, report the count.
+    #[cfg(feature = "persist")]
+    pub fn persist_save<C, K, V>(
+        cache: &C,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<usize, crate::PersistError>
+    where
+        C: crate::CachedSnapshot<K, V>,
+        K: serde::Serialize,
+        V: serde::Serialize,
+    {
+        let snapshot = cache.snapshot();
+        snapshot.save(path)?;
+        Ok(snapshot.len())
+    }
+
+    /// Concurrent-store counterpart of [`persist_save`].
+    #[cfg(feature = "persist")]
+    pub fn persist_save_concurrent<C, K, V>(
+        cache: &C,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<usize, crate::PersistError>
+    where
+        C: crate::ConcurrentCachedSnapshot<K, V>,
+        K: serde::Serialize,
+        V: serde::Serialize,
+    {
+        let snapshot = cache.snapshot();
+        snapshot.save(path)?;
+        Ok(snapshot.len())
+    }
+
+    /// Publishes a macro cache's store after a miss. The generated code counts hits and misses
+    /// per call, so the store's own counters are left out rather than published twice.
+    #[cfg(feature = "metrics")]
+    pub fn publish_store_metrics(
+        exporter: &crate::MetricsExporter,
+        snapshot: impl FnOnce() -> crate::CacheMetrics,
+    ) {
+        exporter.publish(|| {
+            let mut metrics = snapshot();
+            metrics.hits = None;
+            metrics.misses = None;
+            metrics
+        });
+    }
+
+    /// Counts a macro cache's miss and records how long the body took since `started`.
+    #[cfg(feature = "metrics")]
+    pub fn record_miss(exporter: &crate::MetricsExporter, started: crate::time::Instant) {
+        exporter.record_miss();
+        exporter.record_miss_duration(started.elapsed());
+    }
+
+    /// Adds a `register = true` cache to [`crate::registry`]; called by its static's
+    /// initializer once the store is built.
+    pub fn register_cache(
+        path: &'static str,
+        kind: crate::registry::CacheKind,
+        store_type: &'static str,
+        metrics: fn() -> crate::CacheMetrics,
+        len: fn() -> Option<usize>,
+        clear: fn() -> Result<(), crate::registry::ClearError>,
+    ) {
+        crate::registry::register(crate::registry::RegisteredCache {
+            path,
+            kind,
+            store_type,
+            metrics,
+            len,
+            clear,
+        });
+    }
+
+    /// The registry's metrics for a `#[once]` cache, which holds at most one value and
+    /// counts nothing else.
+    pub fn single_value_metrics(present: bool) -> crate::CacheMetrics {
+        crate::CacheMetrics {
+            entry_count: Some(usize::from(present)),
+            capacity: Some(1),
+            ..crate::CacheMetrics::default()
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    pub use tracing::Instrument;
+
+    /// The span `__cached_call_span!` opens around a cached function call. The key is
+    /// identified by its hash only, so span data never carries argument values.
+    #[cfg(feature = "tracing")]
+    pub fn call_span(function: &'static str, key_hash: Option<u64>) -> tracing::Span {
+        tracing::debug_span!(
+            "cached.call",
+            function,
+            key_hash,
+            outcome = tracing::field::Empty,
+        )
+    }
+
+    /// Backs `__cached_call_outcome!`.
+    #[cfg(feature = "tracing")]
+    pub fn record_call_outcome(outcome: &'static str) {
+        tracing::Span::current().record("outcome", outcome);
+    }
+
+    /// Autoref probe behind the `key_hash` span field: `(&KeyHashProbe(&key)).key_hash()`
+    /// resolves to [`HashableKey`] when the key type is `Hash` and to [`UnhashableKey`]
+    /// (`None`) otherwise, so a Redis key that is only `Display` still gets a span.
+    #[cfg(feature = "tracing")]
+    pub struct KeyHashProbe<'a, T: ?Sized>(pub &'a T);
+
+    #[cfg(feature = "tracing")]
+    pub trait HashableKey {
+        fn key_hash(&self) -> Option<u64>;
+    }
+
+    #[cfg(feature = "tracing")]
+    impl<T: std::hash::Hash + ?Sized> HashableKey for KeyHashProbe<'_, T> {
+        fn key_hash(&self) -> Option<u64> {
+            use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+            // Fixed keys, so a key hashes the same in every process running this build.
+            Some(BuildHasherDefault::<DefaultHasher>::default().hash_one(self.0))
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    pub trait UnhashableKey {
+        fn key_hash(&self) -> Option<u64> {
+            None
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl<T: ?Sized> UnhashableKey for &KeyHashProbe<'_, T> {}
 }
 
 /// Convenience re-exports of the commonly-needed cache traits.
@@ -1153,9 +1536,9 @@ pub mod __private {
 pub mod prelude {
     pub use crate::{
         CacheEvict, CacheMetrics, Cached, CachedExt, CachedIter, CachedPeek, CachedRead,
-        CloneCached, ConcurrentCacheBase, ConcurrentCacheEvict, ConcurrentCachePeek,
+        CachedTags, CloneCached, ConcurrentCacheBase, ConcurrentCacheEvict, ConcurrentCachePeek,
         ConcurrentCacheRefreshOnHit, ConcurrentCacheTtl, ConcurrentCached, ConcurrentCachedExt,
-        ConcurrentCloneCached, Expires, IntoValues, SerializeCached,
+        ConcurrentCachedTags, ConcurrentCloneCached, Expires, IntoValues, SerializeCached,
     };
 
     // Unconditional, like `ConcurrentCacheTtl` above: the traits themselves are
@@ -1170,6 +1553,10 @@ pub mod prelude {
         CachedGetOrSetAsync, ConcurrentCachePeekAsync, ConcurrentCachedAsync,
         ConcurrentCachedAsyncExt, SerializeCachedAsync,
     };
+
+    #[cfg(feature = "persist")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+    pub use crate::{CachedSnapshot, ConcurrentCachedSnapshot};
 }
 
 /// Core cache operations for single-owner (non-concurrent) stores.
@@ -1454,6 +1841,39 @@ pub trait Cached<K, V> {
     fn cache_evictions(&self) -> Option<u64> {
         None
     }
+
+    /// Return the exporter the store publishes its metrics through, if it was built with a
+    /// `metrics_name`. See [`CachedExt::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    fn cache_metrics_exporter(&self) -> Option<&MetricsExporter> {
+        None
+    }
+
+    /// Return the `n` most looked-up keys with their approximate lookup counts, highest first,
+    /// or `None` if the store was not built with `hot_keys` / `hot_key_hashes`.
+    ///
+    /// Only [`cache_get`](Cached::cache_get) is counted, hits and misses alike. The built-in
+    /// in-memory stores implement this; the default returns `None`.
+    #[must_use]
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<HotKey<K>>> {
+        let _ = n;
+        None
+    }
+
+    /// Return an estimate of the bytes the store holds for its entries, or `None` if it
+    /// cannot tell.
+    ///
+    /// The built-in in-memory stores count their hash table and list allocations by capacity,
+    /// including the inline size of every key and value. Heap memory owned by the keys and
+    /// values is added only for a store built with `deep_size`, which walks every entry
+    /// through [`DeepSize`]; without it this is O(1). The estimate leaves out allocator
+    /// overhead, tag indexes and hot-key summaries.
+    #[must_use]
+    fn cache_memory_usage(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// Short-alias extension for [`Cached`] stores.
@@ -1622,6 +2042,31 @@ pub trait CachedExt<K, V>: Cached<K, V> {
     /// Return a snapshot of cache metrics.
     #[must_use]
     fn metrics(&self) -> CacheMetrics;
+
+    /// Return the `n` most looked-up keys, if tracked. Delegates to
+    /// [`cache_top_keys`](Cached::cache_top_keys).
+    #[must_use]
+    fn top_keys(&self, n: usize) -> Option<Vec<HotKey<K>>>;
+
+    /// Return the store's estimated memory usage in bytes, if known. Delegates to
+    /// [`cache_memory_usage`](Cached::cache_memory_usage).
+    #[must_use]
+    fn memory_usage(&self) -> Option<usize>;
+
+    /// Publish [`metrics`](Self::metrics) through the store's
+    /// [`cache_metrics_exporter`](Cached::cache_metrics_exporter) as labelled `metrics`
+    /// counters and gauges. Does nothing for a store built without a `metrics_name`.
+    ///
+    /// A hand-built store publishes only when this is called, so call it wherever the
+    /// exported values should be current, e.g. just before the recorder is scraped. The
+    /// caches behind `#[cached(metrics_name = "...")]` publish on their own.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    fn publish_metrics(&self) {
+        if let Some(exporter) = self.cache_metrics_exporter() {
+            exporter.publish(|| self.metrics());
+        }
+    }
 }
 
 impl<K, V, T: Cached<K, V>> CachedExt<K, V> for T {
@@ -1740,8 +2185,17 @@ impl<K, V, T: Cached<K, V>> CachedExt<K, V> for T {
             evictions: self.cache_evictions(),
             entry_count: Some(self.cache_size()),
             capacity: self.cache_capacity(),
+            mem_bytes: self.cache_memory_usage(),
         }
     }
+
+    fn top_keys(&self, n: usize) -> Option<Vec<HotKey<K>>> {
+        self.cache_top_keys(n)
+    }
+
+    fn memory_usage(&self) -> Option<usize> {
+        self.cache_memory_usage()
+    }
 }
 
 /// Iteration over cache contents for stores that can expose borrowed entries.
@@ -1818,6 +2272,9 @@ pub struct CacheMetrics {
     pub entry_count: Option<usize>,
     /// Maximum capacity, if bounded.
     pub capacity: Option<usize>,
+    /// Approximate bytes held by the store's entries, if it can estimate them. See
+    /// [`Cached::cache_memory_usage`].
+    pub mem_bytes: Option<usize>,
 }
 
 impl CacheMetrics {
@@ -1969,9 +2426,9 @@ pub trait CloneCached<K, V> {
 /// value if a refresh fails. Takes `&self` instead of `&mut self` because sharded stores are
 /// internally synchronized and never need exclusive ownership from the caller.
 ///
-/// Implemented by the four expiry-capable sharded stores:
-/// [`ShardedTtlCache`], [`ShardedLruTtlCache`], [`ShardedExpiringCache`], and
-/// [`ShardedExpiringLruCache`].
+/// Implemented by the expiry-capable sharded stores:
+/// [`ShardedTtlCache`], [`ShardedTtlSortedCache`], [`ShardedLruTtlCache`],
+/// [`ShardedExpiringCache`], and [`ShardedExpiringLruCache`].
 /// Non-expiry stores ([`ShardedUnboundCache`], [`ShardedLruCache`]) do not implement this trait,
 /// mirroring how [`CloneCached`] is absent on [`UnboundCache`] and [`LruCache`].
 ///
@@ -2270,14 +2727,11 @@ pub trait ConcurrentCacheBase {
     /// determine it cheaply.
     ///
     /// Returns `Ok(Some(n))` for stores that track their own size (all in-memory sharded
-    /// stores), and `Ok(None)` for stores that cannot answer without an expensive or
-    /// semantically-ambiguous query — `RedisCache` / `AsyncRedisCache` (the key count is a
-    /// server-side `DBSIZE`/`SCAN` over a shared keyspace, not just this cache's entries) and
-    /// `RedbCache` (an `O(n)` scan of the backing table). Those stores return `Ok(None)` rather
-    /// than pay that cost implicitly. For `RedisCache` / `AsyncRedisCache` you can query the server
-    /// directly if you need a count; `RedbCache` holds an exclusive lock on its file and exposes no
-    /// live database handle (`RedbCache::disk_path()` gives the file location for offline inspection,
-    /// but the database cannot be opened by a second instance while the cache is live).
+    /// stores, and `RedbCache`, which reads its table's row count). `RedisCache` returns
+    /// `Ok(None)` by default, since counting its entries is a server-side `SCAN` over a shared
+    /// keyspace; its `RedisSizeMode` builder option opts into a capped `SCAN` count or an
+    /// exact index maintained on every write. `AsyncRedisCache` cannot answer this synchronous
+    /// method and always returns `Ok(None)`; use its `async_cache_size` instead.
     ///
     /// This is the concurrent analogue of [`Cached::cache_size`], widened to
     /// `Result<Option<usize>, _>` because concurrent stores may be fallible and may not know
@@ -2347,6 +2801,19 @@ pub trait ConcurrentCacheBase {
         None
     }
 
+    /// Return an estimate of the bytes the store holds for its entries, or `None` if it
+    /// cannot tell.
+    ///
+    /// The sharded in-memory stores sum the estimate of every shard; their inherent
+    /// `shard_memory_usage` reports it per shard. The Redis and redb stores keep their entries
+    /// outside the process and return `None`.
+    ///
+    /// This mirrors [`Cached::cache_memory_usage`] on the non-concurrent family.
+    #[must_use]
+    fn cache_memory_usage(&self) -> Option<usize> {
+        None
+    }
+
     /// Return a snapshot of cache metrics.
     ///
     /// Aggregates hits, misses, evictions, entry count, and capacity across all shards (for
@@ -2368,6 +2835,33 @@ pub trait ConcurrentCacheBase {
             evictions: self.cache_evictions(),
             entry_count,
             capacity: self.cache_capacity(),
+            mem_bytes: self.cache_memory_usage(),
+        }
+    }
+
+    /// Return the exporter the store publishes its metrics through, if it was built with a
+    /// `metrics_name`.
+    ///
+    /// This mirrors [`Cached::cache_metrics_exporter`] on the non-concurrent family.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    fn cache_metrics_exporter(&self) -> Option<&MetricsExporter> {
+        None
+    }
+
+    /// Publish [`metrics`](Self::metrics) through the store's
+    /// [`cache_metrics_exporter`](Self::cache_metrics_exporter). Does nothing for a store built
+    /// without a `metrics_name`.
+    ///
+    /// The Redis and redb stores count hits and misses on the exporter as lookups happen; what
+    /// this adds for them is the entry count, which `AsyncRedisCache` cannot report from this
+    /// synchronous method. This mirrors [`CachedExt::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    fn publish_metrics(&self) {
+        if let Some(exporter) = self.cache_metrics_exporter() {
+            exporter.publish(|| self.metrics());
         }
     }
 }
@@ -2376,7 +2870,8 @@ pub trait ConcurrentCacheBase {
 ///
 /// Mirrors the single-owner [`CacheTtl`] trait but with `&self` methods, since concurrent stores
 /// are internally synchronized and held behind an `Arc`/`static`. Only the ttl-capable concurrent
-/// stores implement it: the sharded TTL stores (`ShardedTtlCache`, `ShardedLruTtlCache`),
+/// stores implement it: the sharded TTL stores (`ShardedTtlCache`, `ShardedTtlSortedCache`,
+/// `ShardedLruTtlCache`),
 /// `RedisCache`, `AsyncRedisCache`, and `RedbCache`. Non-ttl concurrent stores
 /// (`ShardedUnboundCache`, `ShardedLruCache`, `ShardedExpiringCache`, `ShardedExpiringLruCache`)
 /// deliberately do **not** implement it — they have no global TTL knob, so `set_ttl` simply does
@@ -2824,6 +3319,18 @@ pub trait ConcurrentCached<K, V>: ConcurrentCacheBase {
         Ok(())
     }
 
+    /// Return the `n` most looked-up keys with their approximate lookup counts, highest first,
+    /// or `None` if the store was not built with `hot_keys` / `hot_key_hashes`.
+    ///
+    /// The sharded in-memory stores count [`cache_get`](ConcurrentCached::cache_get) in one
+    /// summary per shard and merge them here. This mirrors [`Cached::cache_top_keys`]; the
+    /// default, used by the Redis and redb stores, returns `None`.
+    #[must_use]
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<HotKey<K>>> {
+        let _ = n;
+        None
+    }
+
     /// Return the cached value for `k`, or compute `f()`, store it, and return it.
     ///
     /// This is a non-atomic get-then-set: on a miss, another thread may store a value
@@ -3005,6 +3512,16 @@ pub trait ConcurrentCachedExt<K, V>: ConcurrentCached<K, V> {
     /// [`cache_evictions`](ConcurrentCacheBase::cache_evictions).
     #[must_use]
     fn evictions(&self) -> Option<u64>;
+
+    /// Return the `n` most looked-up keys, if tracked. Delegates to
+    /// [`cache_top_keys`](ConcurrentCached::cache_top_keys).
+    #[must_use]
+    fn top_keys(&self, n: usize) -> Option<Vec<HotKey<K>>>;
+
+    /// Return the store's estimated memory usage in bytes, if known. Delegates to
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage).
+    #[must_use]
+    fn memory_usage(&self) -> Option<usize>;
 }
 
 impl<K, V, T: ConcurrentCached<K, V>> ConcurrentCachedExt<K, V> for T {
@@ -3081,6 +3598,14 @@ impl<K, V, T: ConcurrentCached<K, V>> ConcurrentCachedExt<K, V> for T {
     fn evictions(&self) -> Option<u64> {
         self.cache_evictions()
     }
+
+    fn top_keys(&self, n: usize) -> Option<Vec<HotKey<K>>> {
+        self.cache_top_keys(n)
+    }
+
+    fn memory_usage(&self) -> Option<usize> {
+        self.cache_memory_usage()
+    }
 }
 
 /// **Direct-call syntax warning**:
@@ -3751,6 +4276,37 @@ pub mod __set_dispatch_async {
     }
 }
 
+/// Autoref-specialization shim used by the generated `result_fallback` paths to ask a stale
+/// value whether it may stand in for an `Err` ([`Expires::serve_stale_on_error`]) when the
+/// value implements [`Expires`], and to allow it unconditionally otherwise. Internal
+/// implementation detail of the proc-macros; no stability guarantee.
+#[doc(hidden)]
+pub mod __stale_dispatch {
+    use super::Expires;
+
+    pub struct StaleDispatch<'v, V>(pub &'v V);
+
+    // PREFERRED arm: inherent method, only exists when V: Expires.
+    impl<V: Expires> StaleDispatch<'_, V> {
+        #[inline]
+        pub fn serve_stale_on_error(&self) -> bool {
+            Expires::serve_stale_on_error(self.0)
+        }
+    }
+
+    // FALLBACK arm: trait method, reached only when the inherent one is pruned.
+    pub trait StaleDispatchFallback {
+        fn serve_stale_on_error(&self) -> bool;
+    }
+
+    impl<V> StaleDispatchFallback for StaleDispatch<'_, V> {
+        #[inline]
+        fn serve_stale_on_error(&self) -> bool {
+            true
+        }
+    }
+}
+
 // `Cached` stores are single-owner (`&mut self`); to share one across threads,
 // bring your own lock or use a macro (`#[cached]`/`#[once]` generate the lock).
 // `ConcurrentCached`/`ConcurrentCachedAsync` is the contract for stores that
@@ -13,36 +13,41 @@ struct ListEntry<T> {
 
 /// Free and occupied cells are each linked into a cyclic list with one auxiliary cell.
 /// Cell #0 is on the list of free cells, element #1 is on the list of occupied cells.
+/// Cell #2 heads a second occupied list for pinned cells, which `back` never reaches and
+/// the iterators walk before the first.
 ///
 impl<T> LRUList<T> {
     const FREE: usize = 0;
     const OCCUPIED: usize = 1;
+    const PINNED: usize = 2;
+    const SENTINELS: usize = 3;
+
+    fn push_sentinels(values: &mut Vec<ListEntry<T>>) {
+        for cell in 0..Self::SENTINELS {
+            values.push(ListEntry::<T> {
+                value: None,
+                next: cell,
+                prev: cell,
+            });
+        }
+    }
 
     pub(crate) fn with_capacity(capacity: usize) -> LRUList<T> {
-        let cap = capacity.saturating_add(2);
+        let cap = capacity.saturating_add(Self::SENTINELS);
         let mut values = Vec::with_capacity(cap);
-        values.push(ListEntry::<T> {
-            value: None,
-            next: 0,
-            prev: 0,
-        });
-        values.push(ListEntry::<T> {
-            value: None,
-            next: 1,
-            prev: 1,
-        });
+        Self::push_sentinels(&mut values);
         LRUList { values }
     }
 
     pub(crate) fn try_with_capacity(
         capacity: usize,
     ) -> Result<LRUList<T>, crate::stores::BuildError> {
-        let capacity = capacity
-            .checked_add(2)
-            .ok_or(crate::stores::BuildError::InvalidValue {
+        let capacity = capacity.checked_add(Self::SENTINELS).ok_or(
+            crate::stores::BuildError::InvalidValue {
                 field: "max_size",
                 reason: "capacity overflow",
-            })?;
+            },
+        )?;
         let mut values = Vec::new();
         values.try_reserve_exact(capacity).map_err(|_| {
             crate::stores::BuildError::InvalidValue {
@@ -50,16 +55,7 @@ impl<T> LRUList<T> {
                 reason: "allocation failed",
             }
         })?;
-        values.push(ListEntry::<T> {
-            value: None,
-            next: 0,
-            prev: 0,
-        });
-        values.push(ListEntry::<T> {
-            value: None,
-            next: 1,
-            prev: 1,
-        });
+        Self::push_sentinels(&mut values);
         Ok(LRUList { values })
     }
 
@@ -83,6 +79,35 @@ impl<T> LRUList<T> {
         self.link_after(index, Self::OCCUPIED);
     }
 
+    /// Relink the occupied cell `index` directly in front of (newer than) the occupied cell
+    /// `next`. A no-op when they are the same cell.
+    pub(crate) fn move_before(&mut self, index: usize, next: usize) {
+        if index == next {
+            return;
+        }
+        self.unlink(index);
+        self.link_after(index, self.values[next].prev);
+    }
+
+    /// Relink the occupied cell `index` at the front of the pinned list. Used both to pin a
+    /// cell and to promote one that is already pinned.
+    pub(crate) fn move_to_pinned_front(&mut self, index: usize) {
+        self.unlink(index);
+        self.link_after(index, Self::PINNED);
+    }
+
+    pub(crate) fn move_to_back(&mut self, index: usize) {
+        self.unlink(index);
+        self.link_after(index, self.values[Self::OCCUPIED].prev);
+    }
+
+    /// The occupied cell one step toward the back (older than) `index`, or `None` when
+    /// `index` is the back.
+    pub(crate) fn next_of(&self, index: usize) -> Option<usize> {
+        let next = self.values[index].next;
+        (next != Self::OCCUPIED).then_some(next)
+    }
+
     pub(crate) fn push_front(&mut self, value: T) -> usize {
         if self.values[Self::FREE].next == Self::FREE {
             self.values.push(ListEntry::<T> {
@@ -105,6 +130,13 @@ impl<T> LRUList<T> {
         self.values[index].value.take().expect("invalid index")
     }
 
+    /// Bytes allocated for the slab, including free cells and the two auxiliary cells.
+    pub(crate) fn allocation_size(&self) -> usize {
+        self.values.capacity() * std::mem::size_of::<ListEntry<T>>()
+    }
+
+    /// The least recently used unpinned cell. Pinned cells are never returned; callers must
+    /// know there is at least one unpinned cell.
     pub(crate) fn back(&self) -> usize {
         self.values[Self::OCCUPIED].prev
     }
@@ -123,47 +155,51 @@ impl<T> LRUList<T> {
 
     pub(crate) fn clear(&mut self) {
         self.values.clear();
-        self.values.push(ListEntry::<T> {
-            value: None,
-            next: 0,
-            prev: 0,
-        });
-        self.values.push(ListEntry::<T> {
-            value: None,
-            next: 1,
-            prev: 1,
-        });
-    }
-
-    /// Move every occupied value into `out` in MRU -> LRU order (leaving the list
-    /// empty), then reset the two sentinel cells so the list is immediately reusable.
+        Self::push_sentinels(&mut self.values);
+    }
+
+    /// Move every occupied value into `out` in MRU -> LRU order, pinned cells first
+    /// (leaving the list empty), then reset the sentinel cells so the list is immediately
+    /// reusable.
     ///
     /// This is the allocation-free counterpart of "collect the keys, then remove them
     /// one at a time": it walks the occupied chain once taking owned values, so callers
     /// clearing a whole cache never clone a key or re-hash anything. The backing `Vec`'s
     /// capacity is retained.
     pub(crate) fn drain_into(&mut self, out: &mut Vec<T>) {
-        let mut index = self.values[Self::OCCUPIED].next;
-        while index != Self::OCCUPIED {
-            let next = self.values[index].next;
-            if let Some(value) = self.values[index].value.take() {
-                out.push(value);
+        for head in [Self::PINNED, Self::OCCUPIED] {
+            let mut index = self.values[head].next;
+            while index != head {
+                let next = self.values[index].next;
+                if let Some(value) = self.values[index].value.take() {
+                    out.push(value);
+                }
+                index = next;
             }
-            index = next;
         }
-        // Reset the free/occupied sentinels; every cell is now vacant.
+        // Reset the sentinels; every cell is now vacant.
         self.clear();
     }
 
+    /// The occupied cell after `index` in iteration order: along the pinned list, then on
+    /// from its end to the unpinned list. `index` is a cell on either list or a sentinel.
+    fn next_cell(&self, index: usize) -> Option<usize> {
+        let mut next = self.values[index].next;
+        if next == Self::PINNED {
+            next = self.values[Self::OCCUPIED].next;
+        }
+        (next != Self::OCCUPIED).then_some(next)
+    }
+
     pub fn iter(&self) -> LRUListIterator<'_, T> {
         LRUListIterator::<T> {
             list: self,
-            index: Self::OCCUPIED,
+            index: Self::PINNED,
         }
     }
 
-    /// Iterate the *slot indices* of the occupied cells in MRU -> LRU order (the same
-    /// order as [`iter`](Self::iter)).
+    /// Iterate the *slot indices* of the occupied cells in MRU -> LRU order, pinned cells
+    /// first (the same order as [`iter`](Self::iter)).
     ///
     /// Lets a sweep collect a `Vec<usize>` of the slots it intends to touch instead of
     /// cloning every candidate key. Slot indices are stable across removals of *other*
@@ -173,9 +209,24 @@ impl<T> LRUList<T> {
     pub(crate) fn iter_indices(&self) -> LRUListIndexIterator<'_, T> {
         LRUListIndexIterator::<T> {
             list: self,
-            index: Self::OCCUPIED,
+            index: Self::PINNED,
         }
     }
+
+    /// Iterate the occupied cells in slab order, from slot index `from` on, with their slot
+    /// indices.
+    ///
+    /// Unlike the list order, slab order does not change as entries are touched, so a sweep
+    /// that stops part-way can resume from the slot after the last one it saw.
+    pub(crate) fn iter_slots_from(&self, from: usize) -> impl Iterator<Item = (usize, &T)> {
+        let from = from.max(Self::SENTINELS);
+        self.values
+            .get(from..)
+            .unwrap_or_default()
+            .iter()
+            .zip(from..)
+            .filter_map(|(entry, index)| entry.value.as_ref().map(|value| (index, value)))
+    }
 }
 
 #[derive(Debug)]
@@ -188,14 +239,9 @@ impl<'a, T> Iterator for LRUListIterator<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let next = self.list.values[self.index].next;
-        if next == LRUList::<T>::OCCUPIED {
-            None
-        } else {
-            let value = self.list.values[next].value.as_ref();
-            self.index = next;
-            value
-        }
+        let next = self.list.next_cell(self.index)?;
+        self.index = next;
+        self.list.values[next].value.as_ref()
     }
 }
 
@@ -211,13 +257,9 @@ impl<T> Iterator for LRUListIndexIterator<'_, T> {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let next = self.list.values[self.index].next;
-        if next == LRUList::<T>::OCCUPIED {
-            None
-        } else {
-            self.index = next;
-            Some(next)
-        }
+        let next = self.list.next_cell(self.index)?;
+        self.index = next;
+        Some(next)
     }
 }
 
@@ -342,6 +384,51 @@ mod tests {
         assert_eq!(*l.get(b), 7);
     }
 
+    #[test]
+    fn move_before_and_move_to_back_keep_both_link_directions_intact() {
+        let mut l = LRUList::with_capacity(4);
+        let a = l.push_front(1);
+        let b = l.push_front(2);
+        let c = l.push_front(3);
+        l.move_before(a, b);
+        assert_eq!(order(&l), vec![3, 1, 2]);
+        assert_eq!(order_reversed(&l), order(&l));
+        l.move_before(c, c);
+        l.move_to_back(c);
+        assert_eq!(order(&l), vec![1, 2, 3]);
+        assert_eq!(order_reversed(&l), order(&l));
+        assert_eq!(l.next_of(a), Some(b));
+        assert_eq!(l.next_of(c), None);
+    }
+
+    #[test]
+    fn pinned_cells_iterate_first_and_are_never_the_back() {
+        let mut l = LRUList::with_capacity(4);
+        let a = l.push_front(1);
+        let b = l.push_front(2);
+        let c = l.push_front(3);
+        l.move_to_pinned_front(a);
+        assert_eq!(order(&l), vec![1, 3, 2]);
+        assert_eq!(l.back(), b);
+        l.move_to_pinned_front(b);
+        assert_eq!(order(&l), vec![2, 1, 3]);
+        assert_eq!(l.back(), c);
+        assert_eq!(l.iter_indices().collect::<Vec<_>>(), vec![b, a, c]);
+
+        // Unpinning is a move back onto the unpinned list.
+        l.move_to_front(a);
+        assert_eq!(order(&l), vec![2, 1, 3]);
+        assert_eq!(l.back(), c);
+        assert_eq!(l.remove(c), 3);
+        assert_eq!(l.back(), a);
+        assert_eq!(order_reversed(&l), vec![1]);
+
+        let mut drained = Vec::new();
+        l.drain_into(&mut drained);
+        assert_eq!(drained, vec![2, 1]);
+        assert!(order(&l).is_empty());
+    }
+
     #[test]
     fn set_replaces_and_clear_resets() {
         let mut l = LRUList::with_capacity(2);
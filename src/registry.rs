@@ -0,0 +1,149 @@
+/*!
+Process-wide registry of the caches behind `#[cached]`, `#[concurrent_cached]` and `#[once]`.
+
+A macro-generated cache is a static the caller cannot name generically, so an admin endpoint
+has no way to list the caches or clear one of them. With `register = true` on the macro, the
+static adds itself here when it is first initialized (on the first call, prime or save),
+under the function's path (`module_path!()` plus the function name). [`all`] lists what has
+registered so far and [`get`] finds one cache by path; each [`RegisteredCache`] exposes the
+store's metrics, length and `clear` without naming its key, value or store type.
+
+```rust,no_run
+# #[cfg(feature = "proc_macro")]
+# {
+use cached::macros::cached;
+
+#[cached(register = true)]
+fn lookup(id: u64) -> String {
+    id.to_string()
+}
+
+lookup(1);
+for cache in cached::registry::all() {
+    println!("{} ({}): {:?} entries", cache.path(), cache.store_type(), cache.len());
+}
+cached::registry::get(concat!(module_path!(), "::lookup"))
+    .unwrap()
+    .clear()
+    .unwrap();
+# }
+```
+
+A cache that has never been used has nothing to report and is not listed. `in_impl` methods
+cannot register, since their function path does not identify the `impl` they belong to.
+
+The accessors lock the cache the way a call does. For an async function the store sits behind
+an async lock, which the accessors acquire by blocking; they do not await, so calling them
+from an async task holds that worker thread until the lock is free.
+*/
+
+use crate::CacheMetrics;
+use parking_lot::Mutex;
+
+/// Which macro generated a [`RegisteredCache`].
+///
+/// The variants are named for how the store is held rather than after the macros: a variant
+/// named `Cached` would make rustc spell the `Cached` trait as `cached::Cached` in every
+/// diagnostic that mentions it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CacheKind {
+    /// `#[cached]`: a `Cached` store behind a lock.
+    Locked,
+    /// `#[concurrent_cached]`: an internally synchronized `ConcurrentCached` store.
+    Concurrent,
+    /// `#[once]`: a single value.
+    Once,
+}
+
+/// Error returned by [`RegisteredCache::clear`]: the store's own error, boxed so the registry
+/// can hold stores with different error types.
+pub type ClearError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A macro-generated cache, with its store type erased.
+///
+/// Cheap to copy: it holds the cache's path and accessors that reach the static directly.
+#[derive(Debug, Clone, Copy)]
+pub struct RegisteredCache {
+    pub(crate) path: &'static str,
+    pub(crate) kind: CacheKind,
+    pub(crate) store_type: &'static str,
+    pub(crate) metrics: fn() -> CacheMetrics,
+    pub(crate) len: fn() -> Option<usize>,
+    pub(crate) clear: fn() -> Result<(), ClearError>,
+}
+
+impl RegisteredCache {
+    /// The function's path, e.g. `my_crate::users::lookup`.
+    #[must_use]
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The macro that generated the cache.
+    #[must_use]
+    pub fn kind(&self) -> CacheKind {
+        self.kind
+    }
+
+    /// The store's type name, as given by [`std::any::type_name`]. Meant for display; the
+    /// exact text is not stable across compiler versions.
+    #[must_use]
+    pub fn store_type(&self) -> &'static str {
+        self.store_type
+    }
+
+    /// A snapshot of the store's metrics (`CachedExt::metrics` or
+    /// `ConcurrentCacheBase::metrics`). A `#[once]` cache reports only its entry count.
+    #[must_use]
+    pub fn metrics(&self) -> CacheMetrics {
+        (self.metrics)()
+    }
+
+    /// The number of entries, or `None` when the store cannot report it (e.g. a Redis store
+    /// without a size mode) or reading it failed.
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        (self.len)()
+    }
+
+    /// `Some(true)` if the store is known to be empty; `None` when [`len`](Self::len) is.
+    #[must_use]
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|n| n == 0)
+    }
+
+    /// Remove every entry, keeping the store's metrics (`cache_clear`).
+    ///
+    /// # Errors
+    ///
+    /// The store's error, for the fallible `#[concurrent_cached]` stores (Redis, redb).
+    pub fn clear(&self) -> Result<(), ClearError> {
+        (self.clear)()
+    }
+}
+
+static REGISTRY: Mutex<Vec<RegisteredCache>> = Mutex::new(Vec::new());
+
+/// The caches registered so far, in the order they were first initialized.
+///
+/// The list is copied out before it is returned, so the accessors of one cache may be called
+/// while another cache is registering.
+pub fn all() -> impl Iterator<Item = RegisteredCache> {
+    REGISTRY.lock().clone().into_iter()
+}
+
+/// The registered cache at `path` (`module_path!()` plus the function name), if it has
+/// been initialized.
+#[must_use]
+pub fn get(path: &str) -> Option<RegisteredCache> {
+    REGISTRY
+        .lock()
+        .iter()
+        .find(|cache| cache.path == path)
+        .copied()
+}
+
+pub(crate) fn register(cache: RegisteredCache) {
+    REGISTRY.lock().push(cache);
+}
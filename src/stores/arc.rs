@@ -0,0 +1,1139 @@
+use super::{Cached, DefaultHashBuilder};
+use crate::lru_list::LRUList;
+use crate::{CachedIter, CachedPeek};
+use hashbrown::HashTable;
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+#[cfg(feature = "async_core")]
+use {super::CachedGetOrSetAsync, std::future::Future};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The four ARC lists. `T1`/`T2` hold resident entries, `B1`/`B2` only the keys of entries
+/// recently evicted from `T1`/`T2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum List {
+    T1,
+    T2,
+    B1,
+    B2,
+}
+
+/// Where a key lives: its list and its slot in that list's slab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Slot {
+    list: List,
+    index: usize,
+}
+
+impl Slot {
+    fn is_resident(self) -> bool {
+        matches!(self.list, List::T1 | List::T2)
+    }
+}
+
+/// The lists and their lengths, kept apart from the hash table so a table probe can borrow
+/// the lists while the table is borrowed mutably.
+#[derive(Clone)]
+struct Lists<K, V> {
+    t1: LRUList<(K, V)>,
+    t2: LRUList<(K, V)>,
+    b1: LRUList<K>,
+    b2: LRUList<K>,
+    /// Lengths of `[t1, t2, b1, b2]`; `LRUList` does not track its own.
+    lens: [usize; 4],
+}
+
+impl<K, V> Lists<K, V> {
+    fn with_capacity(capacity: usize) -> Result<Self, super::BuildError> {
+        Ok(Self {
+            t1: LRUList::try_with_capacity(capacity)?,
+            t2: LRUList::try_with_capacity(capacity)?,
+            b1: LRUList::with_capacity(0),
+            b2: LRUList::with_capacity(0),
+            lens: [0; 4],
+        })
+    }
+
+    fn len(&self, list: List) -> usize {
+        self.lens[list as usize]
+    }
+
+    fn resident(&self) -> usize {
+        self.lens[List::T1 as usize] + self.lens[List::T2 as usize]
+    }
+
+    fn key(&self, slot: Slot) -> &K {
+        match slot.list {
+            List::T1 => &self.t1.get(slot.index).0,
+            List::T2 => &self.t2.get(slot.index).0,
+            List::B1 => self.b1.get(slot.index),
+            List::B2 => self.b2.get(slot.index),
+        }
+    }
+
+    fn resident_list(&self, list: List) -> &LRUList<(K, V)> {
+        match list {
+            List::T1 => &self.t1,
+            List::T2 => &self.t2,
+            List::B1 | List::B2 => unreachable!("ghost lists hold no values"),
+        }
+    }
+
+    fn resident_list_mut(&mut self, list: List) -> &mut LRUList<(K, V)> {
+        match list {
+            List::T1 => &mut self.t1,
+            List::T2 => &mut self.t2,
+            List::B1 | List::B2 => unreachable!("ghost lists hold no values"),
+        }
+    }
+
+    fn ghost_list_mut(&mut self, list: List) -> &mut LRUList<K> {
+        match list {
+            List::B1 => &mut self.b1,
+            List::B2 => &mut self.b2,
+            List::T1 | List::T2 => unreachable!("resident lists are not ghost lists"),
+        }
+    }
+
+    fn back(&self, list: List) -> usize {
+        match list {
+            List::T1 => self.t1.back(),
+            List::T2 => self.t2.back(),
+            List::B1 => self.b1.back(),
+            List::B2 => self.b2.back(),
+        }
+    }
+
+    fn value(&self, slot: Slot) -> &V {
+        &self.resident_list(slot.list).get(slot.index).1
+    }
+
+    fn value_mut(&mut self, slot: Slot) -> &mut V {
+        &mut self.resident_list_mut(slot.list).get_mut(slot.index).1
+    }
+
+    fn push_resident(&mut self, list: List, pair: (K, V)) -> Slot {
+        self.lens[list as usize] += 1;
+        let index = self.resident_list_mut(list).push_front(pair);
+        Slot { list, index }
+    }
+
+    fn remove_resident(&mut self, slot: Slot) -> (K, V) {
+        self.lens[slot.list as usize] -= 1;
+        self.resident_list_mut(slot.list).remove(slot.index)
+    }
+
+    fn push_ghost(&mut self, list: List, key: K) -> Slot {
+        self.lens[list as usize] += 1;
+        let index = self.ghost_list_mut(list).push_front(key);
+        Slot { list, index }
+    }
+
+    fn remove_ghost(&mut self, slot: Slot) -> K {
+        self.lens[slot.list as usize] -= 1;
+        self.ghost_list_mut(slot.list).remove(slot.index)
+    }
+
+    fn clear(&mut self) {
+        self.t1.clear();
+        self.t2.clear();
+        self.b1.clear();
+        self.b2.clear();
+        self.lens = [0; 4];
+    }
+
+    fn allocation_size(&self) -> usize {
+        self.t1.allocation_size()
+            + self.t2.allocation_size()
+            + self.b1.allocation_size()
+            + self.b2.allocation_size()
+    }
+
+    /// Resident entries: `T1` most-recent first, then `T2` most-recent first.
+    fn iter(&self) -> impl Iterator<Item = &(K, V)> {
+        self.t1.iter().chain(self.t2.iter())
+    }
+}
+
+/// Adaptive Replacement Cache
+///
+/// Holds up to `max_size` entries split across two LRU lists: `T1` for keys seen once
+/// recently and `T2` for keys seen at least twice. Two ghost lists, `B1` and `B2`, remember
+/// the keys (not the values) most recently evicted from each. Writing a key that is on a
+/// ghost list shifts the adaptive target size of `T1` toward the list that lost it, so the
+/// cache leans toward recency or frequency as the workload does. A scan of one-off keys only
+/// churns `T1` and leaves the frequently used entries in `T2` in place.
+///
+/// Eviction, `on_evict` and the counters follow [`LruCache`](crate::LruCache): an evicted
+/// entry fires `on_evict` and counts in `evictions` before its key moves to a ghost list.
+/// Ghost keys are not entries: they are invisible to lookups, `cache_size` and iteration, and
+/// hold at most `max_size` further clones of `K`.
+///
+/// Note: This cache is in-memory only
+///
+/// The optional type parameter `S` selects the hash builder, as on `LruCache`. Supply a custom
+/// `S` via [`ArcCacheBuilder::hasher`].
+///
+/// # Example
+///
+/// ```rust
+/// use cached::{ArcCache, Cached};
+///
+/// let mut cache = ArcCache::new(2);
+/// cache.cache_set(1, "one");
+/// cache.cache_get(&1); // a second use moves key 1 to the frequent list
+/// cache.cache_set(2, "two");
+/// cache.cache_set(3, "three"); // evicts 2, the least recent once-seen key
+/// assert_eq!(cache.cache_get(&1), Some(&"one"));
+/// assert_eq!(cache.cache_get(&2), None);
+/// ```
+pub struct ArcCache<K, V, S = DefaultHashBuilder> {
+    // `store` maps a hash of K -> the key's slot on one of the four lists
+    store: HashTable<Slot>,
+    hash_builder: S,
+    lists: Lists<K, V>,
+    /// ARC's adaptive target size for `T1`, in `0..=capacity`.
+    target: usize,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// When false, lookups skip incrementing `hits` and `misses`. The sharded store keeps its
+    /// own per-shard counters.
+    pub(crate) track_hit_miss: bool,
+    tags: super::TagIndex<K>,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeys<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
+}
+
+impl<K, V, S> Clone for ArcCache<K, V, S>
+where
+    K: Clone + Hash + Eq,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            hash_builder: self.hash_builder.clone(),
+            lists: self.lists.clone(),
+            target: self.target,
+            capacity: self.capacity,
+            hits: AtomicU64::new(self.hits.load(Ordering::Relaxed)),
+            misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
+            evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
+            on_evict: self.on_evict.clone(),
+            track_hit_miss: self.track_hit_miss,
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for ArcCache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcCache")
+            .field("capacity", &self.capacity)
+            .field("target", &self.target)
+            .field("recent", &self.lists.len(List::T1))
+            .field("frequent", &self.lists.len(List::T2))
+            .field("hits", &self.hits.load(Ordering::Relaxed))
+            .field("misses", &self.misses.load(Ordering::Relaxed))
+            .field("evictions", &self.evictions.load(Ordering::Relaxed))
+            .field("on_evict", &self.on_evict.as_ref().map(|_| "on_evict"))
+            .finish()
+    }
+}
+
+/// Builder for [`ArcCache`].
+pub struct ArcCacheBuilder<K, V, S = DefaultHashBuilder> {
+    size: Option<usize>,
+    on_evict: Option<super::OnEvict<K, V>>,
+    hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
+}
+
+impl<K, V> Default for ArcCacheBuilder<K, V, DefaultHashBuilder> {
+    fn default() -> Self {
+        Self {
+            size: None,
+            on_evict: None,
+            hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+        }
+    }
+}
+
+impl<K, V> ArcCacheBuilder<K, V> {
+    /// Create a builder with default settings. Equivalent to [`ArcCache::builder`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, S> ArcCacheBuilder<K, V, S> {
+    /// Set the maximum number of entries. Required -- `build` returns `Err` if not set.
+    #[doc(alias = "size")]
+    #[doc(alias = "capacity")]
+    #[must_use]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.size = Some(max_size);
+        self
+    }
+
+    /// Set a callback to be invoked when an entry is evicted or removed.
+    ///
+    /// Use [`cache_clear_with_on_evict`](ArcCache::cache_clear_with_on_evict)
+    /// instead of [`cache_clear`](crate::Cached::cache_clear) to opt into callback
+    /// firing and eviction counter increments when clearing all entries.
+    #[must_use]
+    pub fn on_evict(mut self, on_evict: impl Fn(&K, &V) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(on_evict));
+        self
+    }
+
+    /// Switch to a custom hash builder `S2`, returning a builder parameterized on `S2`.
+    #[doc(alias = "with_hasher")]
+    #[must_use]
+    pub fn hasher<S2: BuildHasher>(self, hasher: S2) -> ArcCacheBuilder<K, V, S2> {
+        ArcCacheBuilder {
+            size: self.size,
+            on_evict: self.on_evict,
+            hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
+        }
+    }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Ghost keys are not counted.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
+    /// Build the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError::MissingRequired`](super::BuildError::MissingRequired) if `max_size`
+    /// was not set, or [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `max_size` is `0` or capacity pre-allocation fails.
+    pub fn build(self) -> Result<ArcCache<K, V, S>, super::BuildError>
+    where
+        K: Hash + Eq + Clone,
+        S: BuildHasher,
+    {
+        let size = self
+            .size
+            .ok_or(super::BuildError::MissingRequired("max_size"))?;
+        if size == 0 {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "must be greater than zero",
+            });
+        }
+        let mut store = HashTable::new();
+        if store.try_reserve(size, |_: &Slot| 0).is_err() {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "allocation failed",
+            });
+        }
+        Ok(ArcCache {
+            store,
+            hash_builder: self.hasher,
+            lists: Lists::with_capacity(size)?,
+            target: 0,
+            capacity: size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            on_evict: self.on_evict,
+            track_hit_miss: true,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
+        })
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> ArcCache<K, V> {
+    /// Construct a ready-to-use [`ArcCache`] holding up to `max_size` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is `0`, or if pre-allocating the backing store fails. Use
+    /// [`builder`](Self::builder) with [`build`](ArcCacheBuilder::build) to handle those cases
+    /// without panicking.
+    #[must_use]
+    pub fn new(max_size: usize) -> Self {
+        Self::builder()
+            .max_size(max_size)
+            .build()
+            .expect("ArcCache::new requires a non-zero max_size with a valid allocation")
+    }
+
+    /// Return a builder for constructing an [`ArcCache`].
+    #[must_use]
+    pub fn builder() -> ArcCacheBuilder<K, V> {
+        ArcCacheBuilder::default()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> ArcCache<K, V, S> {
+    /// Returns the maximum number of entries this cache will hold before evicting.
+    #[doc(alias = "size")]
+    #[doc(alias = "max_size")]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The current adaptive target size of the recent list `T1`, in `0..=capacity`. It grows
+    /// when a key evicted from `T1` is written again, and shrinks when a key evicted from `T2`
+    /// is.
+    #[must_use]
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Keys seen once since they were last admitted (`T1`), most recently used first.
+    #[must_use]
+    pub fn recent_keys(&self) -> Vec<K> {
+        self.lists.t1.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    /// Keys seen at least twice (`T2`), most recently used first.
+    #[must_use]
+    pub fn frequent_keys(&self) -> Vec<K> {
+        self.lists.t2.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    /// Number of remembered ghost keys, `(B1, B2)`: keys recently evicted from the recent and
+    /// the frequent list.
+    #[must_use]
+    pub fn ghost_len(&self) -> (usize, usize) {
+        (self.lists.len(List::B1), self.lists.len(List::B2))
+    }
+
+    /// Change the maximum number of entries, returning the previous bound as
+    /// `Some(prev_capacity)`, like [`LruCache::set_max_size`](crate::LruCache::set_max_size).
+    ///
+    /// Shrinking evicts entries as ARC would on insert (firing `on_evict`), clamps the target
+    /// and forgets ghost keys beyond the new bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0. Use [`try_set_max_size`](ArcCache::try_set_max_size)
+    /// to validate first and avoid the panic.
+    pub fn set_max_size(&mut self, max_size: usize) -> Option<usize> {
+        assert!(max_size > 0, "max_size must be greater than zero");
+        let prev = self.capacity;
+        self.capacity = max_size;
+        self.target = self.target.min(max_size);
+        while self.lists.resident() > max_size {
+            self.replace(false);
+        }
+        while self.lists.len(List::T1) + self.lists.len(List::B1) > max_size
+            && self.lists.len(List::B1) > 0
+        {
+            self.forget_lru(List::B1);
+        }
+        while self.lists.lens.iter().sum::<usize>() > 2 * max_size {
+            let ghost = if self.lists.len(List::B2) > 0 {
+                List::B2
+            } else {
+                List::B1
+            };
+            self.forget_lru(ghost);
+        }
+        Some(prev)
+    }
+
+    /// Fallible counterpart of [`set_max_size`](ArcCache::set_max_size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SetMaxSizeError::ZeroMaxSize`](super::SetMaxSizeError) if `max_size` is 0.
+    pub fn try_set_max_size(
+        &mut self,
+        max_size: usize,
+    ) -> Result<Option<usize>, super::SetMaxSizeError> {
+        if max_size == 0 {
+            return Err(super::SetMaxSizeError::ZeroMaxSize);
+        }
+        Ok(self.set_max_size(max_size))
+    }
+
+    /// Removes entries for which `keep` returns `false`, firing `on_evict` and counting an
+    /// eviction for each, like [`LruCache::retain`](crate::LruCache::retain). Removed keys
+    /// do not become ghosts. Returns the number of entries removed.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&mut self, mut keep: F) -> usize {
+        let mut doomed = Vec::new();
+        for list in [List::T1, List::T2] {
+            let entries = self.lists.resident_list(list);
+            doomed.extend(
+                entries
+                    .iter_indices()
+                    .filter(|&index| {
+                        let (k, v) = entries.get(index);
+                        !keep(k, v)
+                    })
+                    .map(|index| Slot { list, index }),
+            );
+        }
+        let removed = doomed.len();
+        for slot in doomed {
+            let (key, value) = self.remove_resident(slot);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(&key, &value);
+            }
+        }
+        removed
+    }
+
+    /// Remove all entries and fire the `on_evict` callback for each one, incrementing the
+    /// evictions counter. Ghost keys are forgotten too.
+    pub fn cache_clear_with_on_evict(&mut self) {
+        let removed = self.drain_all();
+        if !removed.is_empty() {
+            self.evictions
+                .fetch_add(removed.len() as u64, Ordering::Relaxed);
+        }
+        if let Some(on_evict) = &self.on_evict {
+            for (k, v) in &removed {
+                on_evict(k, v);
+            }
+        }
+    }
+
+    /// Remove every entry and ghost key, returning the resident pairs. Fires no callback and
+    /// touches no counters.
+    pub(super) fn drain_all(&mut self) -> Vec<(K, V)> {
+        let mut drained = Vec::with_capacity(self.lists.resident());
+        self.lists.t1.drain_into(&mut drained);
+        self.lists.t2.drain_into(&mut drained);
+        self.lists.clear();
+        self.store.clear();
+        self.target = 0;
+        drained
+    }
+
+    /// Remove `k` if it is resident, returning the stored pair. Fires no callback and touches
+    /// no counters; a ghost key is left in place.
+    pub(super) fn pop_raw<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash(k), k).filter(|s| s.is_resident())?;
+        Some(self.remove_resident(slot))
+    }
+
+    /// The resident entries, recent list first. Used by the sharded store's `retain`.
+    pub(super) fn iter_raw(&self) -> impl Iterator<Item = &(K, V)> {
+        self.lists.iter()
+    }
+
+    pub(super) fn add_evictions(&self, n: u64) {
+        self.evictions.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Bytes allocated for the hash table and the four lists, by capacity.
+    pub(super) fn allocated_bytes(&self) -> usize {
+        self.store.allocation_size() + self.lists.allocation_size()
+    }
+
+    /// Sum `heap` over every resident entry.
+    pub(super) fn entries_heap_size(&self, heap: impl Fn(&K, &V) -> usize) -> usize {
+        self.lists.iter().map(|(k, v)| heap(k, v)).sum()
+    }
+
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.hash_builder.hash_one(key)
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<Slot>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store
+            .find(hash, |&slot| key == self.lists.key(slot).borrow())
+            .copied()
+    }
+
+    fn insert_slot(&mut self, hash: u64, slot: Slot) {
+        let lists = &self.lists;
+        let hash_builder = &self.hash_builder;
+        self.store
+            .insert_unique(hash, slot, |&s| hash_builder.hash_one(lists.key(s)));
+    }
+
+    /// Point the table entry for `from` at `to`. `hash` is the key's hash.
+    fn relink(&mut self, hash: u64, from: Slot, to: Slot) {
+        match self.store.find_mut(hash, |&s| s == from) {
+            Some(slot) => *slot = to,
+            None => {
+                unreachable!("ArcCache internal invariant violated: lists and table out of sync")
+            }
+        }
+    }
+
+    fn unlink(&mut self, hash: u64, slot: Slot) {
+        match self.store.find_entry(hash, |&s| s == slot) {
+            Ok(entry) => {
+                entry.remove();
+            }
+            Err(_) => {
+                unreachable!("ArcCache internal invariant violated: lists and table out of sync")
+            }
+        }
+    }
+
+    fn remove_resident(&mut self, slot: Slot) -> (K, V) {
+        let hash = self.hash(self.lists.key(slot));
+        self.unlink(hash, slot);
+        self.lists.remove_resident(slot)
+    }
+
+    /// Move a resident entry to the front of `T2`, returning its new slot.
+    fn promote(&mut self, hash: u64, slot: Slot) -> Slot {
+        if slot.list == List::T2 {
+            self.lists.t2.move_to_front(slot.index);
+            return slot;
+        }
+        let pair = self.lists.remove_resident(slot);
+        let to = self.lists.push_resident(List::T2, pair);
+        self.relink(hash, slot, to);
+        to
+    }
+
+    /// Drop the least recent key of ghost list `list`.
+    fn forget_lru(&mut self, list: List) {
+        let slot = Slot {
+            list,
+            index: self.lists.back(list),
+        };
+        let hash = self.hash(self.lists.key(slot));
+        self.unlink(hash, slot);
+        self.lists.remove_ghost(slot);
+    }
+
+    /// Evict the least recent entry of `T1` without remembering its key.
+    fn evict_recent_lru(&mut self) {
+        let slot = Slot {
+            list: List::T1,
+            index: self.lists.back(List::T1),
+        };
+        let (key, value) = self.remove_resident(slot);
+        self.evictions.fetch_add(1, Ordering::Relaxed);
+        if let Some(on_evict) = &self.on_evict {
+            on_evict(&key, &value);
+        }
+    }
+
+    /// ARC's REPLACE: evict the least recent entry of `T1` or `T2`, chosen against the target,
+    /// and remember its key on the matching ghost list. `in_b2` is true when the key being
+    /// admitted was found on `B2`.
+    fn replace(&mut self, in_b2: bool) {
+        let t1 = self.lists.len(List::T1);
+        let from = if t1 > 0
+            && ((in_b2 && t1 == self.target) || t1 > self.target || self.lists.len(List::T2) == 0)
+        {
+            List::T1
+        } else {
+            List::T2
+        };
+        let ghost = if from == List::T1 { List::B1 } else { List::B2 };
+        let slot = Slot {
+            list: from,
+            index: self.lists.back(from),
+        };
+        let (key, value) = self.lists.remove_resident(slot);
+        let hash = self.hash(&key);
+        let to = self.lists.push_ghost(ghost, key);
+        self.relink(hash, slot, to);
+        // The entry is already off the resident lists, so a panicking callback leaves the
+        // cache within capacity.
+        self.evictions.fetch_add(1, Ordering::Relaxed);
+        if let Some(on_evict) = &self.on_evict {
+            on_evict(self.lists.key(to), &value);
+        }
+    }
+
+    /// Make room for a key that is on no list, as ARC's case IV does, before it is pushed
+    /// onto `T1`.
+    fn admit_new(&mut self) {
+        let c = self.capacity;
+        let t1 = self.lists.len(List::T1);
+        if t1 + self.lists.len(List::B1) >= c {
+            if t1 < c {
+                self.forget_lru(List::B1);
+                if self.lists.resident() >= c {
+                    self.replace(false);
+                }
+            } else {
+                self.evict_recent_lru();
+            }
+        } else if self.lists.resident() + self.lists.len(List::B1) + self.lists.len(List::B2) >= c {
+            if self.lists.lens.iter().sum::<usize>() >= 2 * c {
+                self.forget_lru(List::B2);
+            }
+            if self.lists.resident() >= c {
+                self.replace(false);
+            }
+        }
+    }
+
+    /// Insert or overwrite `key`, returning the displaced stored pair and the entry's slot.
+    fn put(&mut self, hash: u64, key: K, val: V) -> (Option<(K, V)>, Slot) {
+        match self.find(hash, &key) {
+            Some(slot) if slot.is_resident() => {
+                let displaced = self
+                    .lists
+                    .resident_list_mut(slot.list)
+                    .set(slot.index, (key, val));
+                (displaced, self.promote(hash, slot))
+            }
+            Some(ghost) => {
+                let (b1, b2) = (self.lists.len(List::B1), self.lists.len(List::B2));
+                let in_b2 = ghost.list == List::B2;
+                self.target = if in_b2 {
+                    self.target.saturating_sub((b1 / b2).max(1))
+                } else {
+                    (self.target + (b2 / b1).max(1)).min(self.capacity)
+                };
+                self.unlink(hash, ghost);
+                self.lists.remove_ghost(ghost);
+                if self.lists.resident() >= self.capacity {
+                    self.replace(in_b2);
+                }
+                let slot = self.lists.push_resident(List::T2, (key, val));
+                self.insert_slot(hash, slot);
+                (None, slot)
+            }
+            None => {
+                self.admit_new();
+                let slot = self.lists.push_resident(List::T1, (key, val));
+                self.insert_slot(hash, slot);
+                (None, slot)
+            }
+        }
+    }
+
+    /// The resident slot for `key`, counting a hit or a miss.
+    fn lookup<Q>(&mut self, key: &Q) -> Option<Slot>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash(key);
+        let slot = self.find(hash, key).filter(|s| s.is_resident());
+        if self.track_hit_miss {
+            let counter = if slot.is_some() {
+                &self.hits
+            } else {
+                &self.misses
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        slot.map(|slot| self.promote(hash, slot))
+    }
+
+    fn get_or_set_slot<F: FnOnce() -> V>(&mut self, key: K, f: F) -> Slot {
+        match self.lookup(&key) {
+            Some(slot) => slot,
+            None => {
+                let hash = self.hash(&key);
+                self.put(hash, key, f()).1
+            }
+        }
+    }
+
+    fn try_get_or_set_slot<E, F: FnOnce() -> Result<V, E>>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<Slot, E> {
+        match self.lookup(&key) {
+            Some(slot) => Ok(slot),
+            None => {
+                let value = f()?;
+                let hash = self.hash(&key);
+                Ok(self.put(hash, key, value).1)
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for ArcCache<K, V, S> {
+    type Error = std::convert::Infallible;
+
+    fn cache_get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(hot) = &self.hot_keys {
+            let stored = self
+                .find(self.hash(key), key)
+                .filter(|s| s.is_resident())
+                .map(|s| self.lists.key(s));
+            hot.record(hot.hash(key), || stored);
+        }
+        let slot = self.lookup(key)?;
+        Some(self.lists.value(slot))
+    }
+
+    fn cache_get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.lookup(key)?;
+        Some(self.lists.value_mut(slot))
+    }
+
+    /// Insert or replace a cache entry, returning the previous value.
+    ///
+    /// Overwriting a resident key counts as a use and moves it to the frequent list. Writing
+    /// a key that is on a ghost list adapts the target and admits it straight to the frequent
+    /// list.
+    fn cache_set(&mut self, key: K, val: V) -> Option<V> {
+        let hash = self.hash(&key);
+        self.put(hash, key, val).0.map(|(_, v)| v)
+    }
+
+    fn cache_get_or_set_with_mut<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        let slot = self.get_or_set_slot(key, f);
+        self.lists.value_mut(slot)
+    }
+
+    fn cache_try_get_or_set_with_mut<F: FnOnce() -> Result<V, E>, E>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<&mut V, E> {
+        let slot = self.try_get_or_set_slot(key, f)?;
+        Ok(self.lists.value_mut(slot))
+    }
+
+    fn cache_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        <Self as Cached<K, V>>::cache_remove_entry(self, k).map(|(_, v)| v)
+    }
+
+    fn cache_remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = self.pop_raw(k);
+        if let Some((ref key, ref value)) = removed {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(key, value);
+            }
+        }
+        removed
+    }
+
+    fn cache_clear(&mut self) {
+        self.store.clear();
+        self.lists.clear();
+        self.target = 0;
+        self.tags.clear();
+    }
+
+    fn cache_reset(&mut self) {
+        self.cache_clear();
+        self.cache_reset_metrics();
+    }
+
+    fn cache_reset_metrics(&mut self) {
+        self.misses.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
+    }
+
+    fn cache_size(&self) -> usize {
+        self.lists.resident()
+    }
+
+    fn cache_hits(&self) -> Option<u64> {
+        Some(self.hits.load(Ordering::Relaxed))
+    }
+
+    fn cache_misses(&self) -> Option<u64> {
+        Some(self.misses.load(Ordering::Relaxed))
+    }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self
+            .deep_size
+            .map_or(0, |heap| self.entries_heap_size(|k, v| heap.entry(k, v)));
+        Some(self.allocated_bytes() + entries)
+    }
+
+    fn cache_evictions(&self) -> Option<u64> {
+        Some(self.evictions.load(Ordering::Relaxed))
+    }
+
+    fn cache_capacity(&self) -> Option<usize> {
+        Some(self.capacity)
+    }
+
+    /// Peek-based: records no hit/miss metrics and moves nothing between lists.
+    fn cache_contains<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        CachedPeek::cache_peek(self, k).is_some()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedIter<K, V> for ArcCache<K, V, S> {
+    /// Yields the recent list, then the frequent list, each most recently used first.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)> + 'a
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.lists.iter().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedPeek<K, V> for ArcCache<K, V, S> {
+    fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash(k), k).filter(|s| s.is_resident())?;
+        Some(self.lists.value(slot))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> super::CachedTags<K, V> for ArcCache<K, V, S> {
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.lists.resident()) {
+            let mut index = std::mem::replace(&mut self.tags, super::TagIndex::new());
+            index.retain_keys(|k| CachedPeek::cache_peek(self, k).is_some());
+            self.tags = index;
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, S> CachedGetOrSetAsync<K, V> for ArcCache<K, V, S>
+where
+    K: Hash + Eq + Clone + Send,
+    S: BuildHasher + Send,
+{
+    fn async_cache_get_or_set_with_mut<'a, F, Fut>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = &'a mut V> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = V> + Send + 'a,
+    {
+        async move {
+            let slot = match self.lookup(&k) {
+                Some(slot) => slot,
+                None => {
+                    let value = f().await;
+                    let hash = self.hash(&k);
+                    self.put(hash, k, value).1
+                }
+            };
+            self.lists.value_mut(slot)
+        }
+    }
+
+    fn async_cache_try_get_or_set_with_mut<'a, F, Fut, E>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = Result<&'a mut V, E>> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        E: 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<V, E>> + Send + 'a,
+    {
+        async move {
+            let slot = match self.lookup(&k) {
+                Some(slot) => slot,
+                None => {
+                    let value = f().await?;
+                    let hash = self.hash(&k);
+                    self.put(hash, k, value).1
+                }
+            };
+            Ok(self.lists.value_mut(slot))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Total entries on all four lists never exceed `2 * capacity`, `T1 + B1` never exceeds
+    /// `capacity`, and every list entry has exactly one table entry.
+    fn assert_invariants<K: Hash + Eq + Clone, V>(c: &ArcCache<K, V>) {
+        let [t1, t2, b1, b2] = c.lists.lens;
+        assert!(t1 + t2 <= c.capacity);
+        assert!(t1 + b1 <= c.capacity);
+        assert!(t1 + t2 + b1 + b2 <= 2 * c.capacity);
+        assert!(c.target <= c.capacity);
+        assert_eq!(c.store.len(), t1 + t2 + b1 + b2);
+        assert_eq!(c.lists.t1.iter().count(), t1);
+        assert_eq!(c.lists.t2.iter().count(), t2);
+        assert_eq!(c.lists.b1.iter().count(), b1);
+        assert_eq!(c.lists.b2.iter().count(), b2);
+    }
+
+    #[test]
+    fn second_use_moves_a_key_to_the_frequent_list() {
+        let mut c = ArcCache::new(4);
+        c.cache_set(1, 1);
+        c.cache_set(2, 2);
+        assert_eq!(c.recent_keys(), [2, 1]);
+        assert_eq!(c.cache_get(&1), Some(&1));
+        assert_eq!(c.recent_keys(), [2]);
+        assert_eq!(c.frequent_keys(), [1]);
+        assert_invariants(&c);
+    }
+
+    #[test]
+    fn ghost_hits_adapt_the_target() {
+        let mut c = ArcCache::new(2);
+        c.cache_set(1, 1);
+        c.cache_set(2, 2);
+        c.cache_get(&2);
+        c.cache_set(3, 3); // T1 = [3], evicts 1 to B1
+        assert_eq!(c.ghost_len(), (1, 0));
+        assert_eq!(c.target(), 0);
+        c.cache_set(1, 1); // B1 hit: the recent list deserved more room
+        assert_eq!(c.target(), 1);
+        assert_eq!(c.frequent_keys().first(), Some(&1));
+        assert_invariants(&c);
+    }
+
+    #[test]
+    fn random_workload_keeps_the_invariants() {
+        let mut c = ArcCache::new(8);
+        let mut x: u64 = 7;
+        for _ in 0..5000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let key = x % 24;
+            match x % 5 {
+                0 => {
+                    let _ = c.cache_remove(&key);
+                }
+                1 | 2 => {
+                    c.cache_get(&key);
+                }
+                _ => {
+                    c.cache_set(key, key);
+                }
+            }
+            assert_invariants(&c);
+        }
+        c.set_max_size(3);
+        assert_invariants(&c);
+    }
+}
@@ -0,0 +1,476 @@
+//! A hash map over a [`HashTable`] whose entries can be addressed by bucket index, for the
+//! stores whose budgeted sweeps walk the map a window at a time.
+//!
+//! `std::collections::HashMap` can only be walked from its first entry, so a sweep resumed
+//! from the middle had to skip over every entry before it. Here a step starts at a bucket
+//! index and stops once it has examined its budget, and removing an entry never moves another
+//! one to a different bucket. Growing or shrinking the table does move them, so a window
+//! position records the table size it was taken against, and a window resumed on a table of
+//! another size starts again from bucket 0 instead of trusting a stale index. A rehash in
+//! place, which only clears tombstones, moves entries to earlier slots of their own probe
+//! sequence; one moved back past the cursor waits for the next sweep.
+//!
+//! The table never shrinks on removal, so a step also stops after probing
+//! [`PROBES_PER_ENTRY`] buckets for each entry of its budget; otherwise a step over a table
+//! that removals left mostly empty could walk the whole allocation looking for its entries.
+
+use hashbrown::HashTable;
+use hashbrown::hash_table;
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+/// A key-value map with the subset of the `HashMap` API the expiring and TTL stores use, plus
+/// bucket-indexed windows for [`take_window`](Self::take_window).
+pub(crate) struct BucketMap<K, V, S> {
+    table: HashTable<(K, V)>,
+    hasher: S,
+}
+
+/// A view into a single entry of a [`BucketMap`], like `std::collections::hash_map::Entry`.
+pub(crate) enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// An entry holding a value. Overwriting it keeps the stored key.
+pub(crate) struct OccupiedEntry<'a, K, V> {
+    inner: hash_table::OccupiedEntry<'a, (K, V)>,
+}
+
+/// An empty slot for a key, reserved when the entry was looked up.
+pub(crate) struct VacantEntry<'a, K, V> {
+    key: K,
+    inner: hash_table::VacantEntry<'a, (K, V)>,
+}
+
+impl<K, V, S> BucketMap<K, V, S> {
+    pub(crate) fn with_hasher(hasher: S) -> Self {
+        Self {
+            table: HashTable::new(),
+            hasher,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn capacity(&self) -> usize {
+        self.table.capacity()
+    }
+
+    /// Bytes of the table's allocation: a `(K, V)` slot and a control byte per bucket.
+    pub(crate) fn allocation_bytes(&self) -> usize {
+        super::hash_table_bytes::<(K, V)>(self.table.num_buckets())
+    }
+
+    /// Remove every entry, keeping the allocation.
+    pub(crate) fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.table.iter().map(|(k, v)| (k, v))
+    }
+
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = (K, V)> + '_ {
+        self.table.drain()
+    }
+
+    /// Remove and yield the entries `f` returns `true` for, as `HashMap::extract_if` does.
+    #[cfg(feature = "time_stores")]
+    pub(crate) fn extract_if<'a, F>(&'a mut self, mut f: F) -> impl Iterator<Item = (K, V)> + 'a
+    where
+        F: FnMut(&K, &mut V) -> bool + 'a,
+    {
+        self.table.extract_if(move |(k, v)| f(k, v))
+    }
+
+    /// Keep only the entries `keep` returns `true` for.
+    #[cfg(feature = "time_stores")]
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        self.table.retain(|(k, v)| keep(k, v));
+    }
+
+    /// Phase 1 and 2 of [`take_doomed`](super::take_doomed) over the whole map: run `doomed`
+    /// over every entry, then remove the ones it selected, by bucket.
+    pub(crate) fn take_doomed(&mut self, mut doomed: impl FnMut(&K, &V) -> bool) -> Vec<(K, V)> {
+        let buckets: Vec<usize> = self
+            .table
+            .iter_buckets()
+            .filter(|&bucket| {
+                self.table
+                    .get_bucket(bucket)
+                    .is_some_and(|(k, v)| doomed(k, v))
+            })
+            .collect();
+        self.remove_buckets(buckets)
+    }
+
+    /// [`take_doomed`](Self::take_doomed) over a window of the map, for a budgeted sweep:
+    /// `doomed` runs over at most `budget` entries, in bucket order from position `from`, which
+    /// is 0 for a new walk or the `next` of the window before, and the window probes at most
+    /// [`PROBES_PER_ENTRY`] buckets per entry of `budget`.
+    ///
+    /// Returns the removed entries, how much of the budget the window spent (the entries it
+    /// held, or all of it if it ran out of probes first), and the position the next window
+    /// starts at. Removing an entry leaves every other entry in its bucket, so
+    /// `next` still names the first entry this window did not reach. If the table was
+    /// reallocated since `from` was taken, this window starts from bucket 0 instead; the table
+    /// grows by doubling, so the restarts of one sweep add up to no more than its final size.
+    pub(crate) fn take_window(
+        &mut self,
+        from: usize,
+        budget: usize,
+        mut doomed: impl FnMut(&K, &V) -> bool,
+    ) -> (Vec<(K, V)>, usize, usize) {
+        let buckets = self.table.num_buckets();
+        let mut bucket = window_bucket(from, buckets);
+        let mut probes = budget.saturating_mul(PROBES_PER_ENTRY);
+        let mut visited = 0;
+        let mut flagged = Vec::new();
+        while visited < budget && probes > 0 && bucket < buckets {
+            if let Some((k, v)) = self.table.get_bucket(bucket) {
+                visited += 1;
+                if doomed(k, v) {
+                    flagged.push(bucket);
+                }
+            }
+            probes -= 1;
+            bucket += 1;
+        }
+        let spent = if probes == 0 { budget } else { visited };
+        (
+            self.remove_buckets(flagged),
+            spent,
+            window_position(bucket, buckets),
+        )
+    }
+
+    /// Phase 2: remove the entries in `buckets`, which phase 1 saw full. Runs no user code.
+    fn remove_buckets(&mut self, buckets: Vec<usize>) -> Vec<(K, V)> {
+        let mut removed = Vec::with_capacity(buckets.len());
+        for bucket in buckets {
+            if let Ok(entry) = self.table.get_bucket_entry(bucket) {
+                removed.push(entry.remove().0);
+            }
+        }
+        removed
+    }
+}
+
+impl<K, V, S> BucketMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub(crate) fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            table: HashTable::with_capacity(capacity),
+            hasher,
+        }
+    }
+
+    /// Shrink the allocation as far as `min_capacity` allows.
+    pub(crate) fn shrink_to(&mut self, min_capacity: usize) {
+        let hasher = &self.hasher;
+        self.table
+            .shrink_to(min_capacity, |(k, _)| hasher.hash_one(k));
+    }
+
+    pub(crate) fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    pub(crate) fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table
+            .find(self.hasher.hash_one(k), |(key, _)| key.borrow() == k)
+            .map(|(k, v)| (k, v))
+    }
+
+    pub(crate) fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table
+            .find_mut(self.hasher.hash_one(k), |(key, _)| key.borrow() == k)
+            .map(|(_, v)| v)
+    }
+
+    pub(crate) fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(k).is_some()
+    }
+
+    /// Insert `v` under `k`, returning the value it replaced. An overwrite keeps the stored
+    /// key and drops `k`, as `HashMap::insert` does.
+    pub(crate) fn insert(&mut self, k: K, v: V) -> Option<V> {
+        match self.entry(k) {
+            Entry::Occupied(mut occupied) => Some(occupied.insert(v)),
+            Entry::Vacant(vacant) => {
+                vacant.insert(v);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table
+            .find_entry(self.hasher.hash_one(k), |(key, _)| key.borrow() == k)
+            .ok()
+            .map(|entry| entry.remove().0)
+    }
+
+    /// The entry for `k`. Looking up a vacant entry reserves its slot, which may reallocate.
+    pub(crate) fn entry(&mut self, k: K) -> Entry<'_, K, V> {
+        let hash = self.hasher.hash_one(&k);
+        let hasher = &self.hasher;
+        match self
+            .table
+            .entry(hash, |(key, _)| *key == k, |(key, _)| hasher.hash_one(key))
+        {
+            hash_table::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            hash_table::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { key: k, inner }),
+        }
+    }
+}
+
+/// How many buckets a window may probe for each entry of its budget. A table is at least 7/16
+/// full after it grows, so a window over one that removals have not thinned finds its entries
+/// well within this.
+const PROBES_PER_ENTRY: usize = 8;
+
+/// Pack the bucket a window stopped before (at most `buckets`) with the size of the table it
+/// walked: `2 * buckets` is the top set bit and the bucket sits below it. Positions never
+/// collide with 0, the start of a walk, because a table has at least one bucket.
+fn window_position(bucket: usize, buckets: usize) -> usize {
+    2 * buckets + bucket
+}
+
+/// The bucket to resume at from `position` on a table of `buckets`: the packed bucket if the
+/// table is still the size it was, and 0 for a new walk or a reallocated table.
+fn window_bucket(position: usize, buckets: usize) -> usize {
+    match position.checked_ilog2() {
+        Some(top) if 1 << top == 2 * buckets => position - 2 * buckets,
+        _ => 0,
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub(crate) fn key(&self) -> &K {
+        &self.inner.get().0
+    }
+
+    pub(crate) fn get(&self) -> &V {
+        &self.inner.get().1
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut V {
+        &mut self.inner.get_mut().1
+    }
+
+    pub(crate) fn into_mut(self) -> &'a mut V {
+        &mut self.inner.into_mut().1
+    }
+
+    /// Replace the value, returning the old one. The stored key stays.
+    pub(crate) fn insert(&mut self, v: V) -> V {
+        std::mem::replace(self.get_mut(), v)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    pub(crate) fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub(crate) fn insert(self, v: V) -> &'a mut V {
+        &mut self.inner.insert((self.key, v)).into_mut().1
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> Clone for BucketMap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<K, V, S: Default> Default for BucketMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for BucketMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Equal when both hold the same keys with equal values, in any order.
+impl<K, V, S> PartialEq for BucketMap<K, V, S>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V, S> Eq for BucketMap<K, V, S>
+where
+    K: Hash + Eq,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K, V, S> IntoIterator for BucketMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = hash_table::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.table.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::RandomState;
+
+    fn map(keys: std::ops::Range<u32>) -> BucketMap<u32, u32, RandomState> {
+        let mut map = BucketMap::with_hasher(RandomState::new());
+        for k in keys {
+            map.insert(k, k);
+        }
+        map
+    }
+
+    /// Windows taken one after another, each starting where the last said to, visit every
+    /// entry exactly once even as doomed entries are removed from earlier windows.
+    #[test]
+    fn windows_cover_the_map_once() {
+        let mut map = map(0..100);
+        let mut seen = Vec::new();
+        let mut removed = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (taken, visited, next) = map.take_window(cursor, 7, |&k, _| {
+                seen.push(k);
+                k % 3 == 0
+            });
+            assert!(visited <= 7);
+            removed.extend(taken.into_iter().map(|(k, _)| k));
+            if super::super::advance_evict_cursor(&mut cursor, visited, 7, next) {
+                break;
+            }
+        }
+        assert_eq!(cursor, 0);
+        seen.sort_unstable();
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+        removed.sort_unstable();
+        assert_eq!(removed, (0..100).filter(|k| k % 3 == 0).collect::<Vec<_>>());
+        assert_eq!(map.len(), 66);
+    }
+
+    /// Inserts between windows that force the table to grow restart the sweep rather than
+    /// leave entries behind a stale bucket index, and every window stays within its budget.
+    #[test]
+    fn windows_survive_growth_between_steps() {
+        let mut map = map(0..100);
+        let mut removed = Vec::new();
+        let mut cursor = 0;
+        let mut next_key = 1_000;
+        let mut growths = 0;
+        loop {
+            let (taken, visited, next) = map.take_window(cursor, 7, |&k, _| k < 100);
+            assert!(visited <= 7);
+            removed.extend(taken.into_iter().map(|(k, _)| k));
+            if super::super::advance_evict_cursor(&mut cursor, visited, 7, next) {
+                break;
+            }
+            if growths < 3 {
+                let buckets = map.table.num_buckets();
+                assert_eq!(window_bucket(cursor, buckets), next - 2 * buckets);
+                while map.table.num_buckets() == buckets {
+                    map.insert(next_key, next_key);
+                    next_key += 1;
+                }
+                growths += 1;
+            }
+        }
+        assert_eq!(growths, 3);
+        removed.sort_unstable();
+        assert_eq!(removed, (0..100).collect::<Vec<_>>());
+        assert!(map.iter().all(|(&k, _)| k >= 1_000));
+    }
+
+    /// A table emptied by removals keeps its buckets, and a window over it stops after its
+    /// probes rather than walking on to the next full bucket.
+    #[test]
+    fn windows_over_a_mostly_empty_table_stay_within_budget() {
+        let mut map = map(0..10_000);
+        for k in 10..10_000 {
+            map.remove_entry(&k);
+        }
+        let buckets = map.table.num_buckets();
+        assert!(buckets >= 10_000);
+        let mut steps = 0;
+        let mut removed = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let before = window_bucket(cursor, buckets);
+            let (taken, spent, next) = map.take_window(cursor, 16, |_, _| true);
+            assert!(spent <= 16);
+            assert!(next - 2 * buckets - before <= 16 * PROBES_PER_ENTRY);
+            steps += 1;
+            removed.extend(taken.into_iter().map(|(k, _)| k));
+            if super::super::advance_evict_cursor(&mut cursor, spent, 16, next) {
+                break;
+            }
+        }
+        assert_eq!(steps, buckets / (16 * PROBES_PER_ENTRY) + 1);
+        removed.sort_unstable();
+        assert_eq!(removed, (0..10).collect::<Vec<_>>());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn overwrites_keep_the_stored_key() {
+        let mut map = map(0..3);
+        assert_eq!(map.insert(1, 10), Some(1));
+        assert_eq!(map.get(&1), Some(&10));
+        assert_eq!(map.len(), 3);
+        assert_eq!(map, map.clone());
+        assert_eq!(map.remove_entry(&1), Some((1, 10)));
+        assert!(!map.contains_key(&1));
+    }
+}
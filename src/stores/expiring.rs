@@ -1,12 +1,13 @@
-use super::{CacheEvict, Cached, DefaultHashBuilder, Expires};
+use super::bucket_map::BucketMap;
+use super::{CacheEvict, Cached, DefaultHashBuilder, EvictProgress, Expires};
+use crate::time::{Duration, Instant};
 use crate::{CachedIter, CachedPeek, CloneCached};
-use std::collections::HashMap;
 use std::hash::{BuildHasher, Hash};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 #[cfg(feature = "async_core")]
-use {super::CachedGetOrSetAsync, std::collections::hash_map::Entry, std::future::Future};
+use {super::CachedGetOrSetAsync, super::bucket_map::Entry, std::future::Future};
 
 /// Size-unbounded cache where each value controls its own expiry via [`Expires`].
 ///
@@ -53,12 +54,84 @@ use {super::CachedGetOrSetAsync, std::collections::hash_map::Entry, std::future:
 ///
 /// Note: This cache is in-memory only.
 pub struct ExpiringCache<K, V, S = DefaultHashBuilder> {
-    pub(super) store: HashMap<K, V, S>,
-    pub(super) initial_capacity: Option<usize>,
+    pub(super) store: BucketMap<K, V, S>,
+    /// The builder's `initial_capacity`, or zero: what `cache_reset` shrinks back toward.
+    pub(super) initial_capacity: usize,
     pub(super) hits: AtomicU64,
     pub(super) misses: AtomicU64,
     pub(super) evictions: AtomicU64,
     pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// Keys written through [`CachedTags::set_with_tags`](super::CachedTags), by tag.
+    pub(super) tags: super::TagIndex<K>,
+    pub(super) metrics_exporter: super::ExporterSlot,
+    pub(super) hot_keys: Option<super::HotKeys<K>>,
+    pub(super) deep_size: Option<super::HeapSize<K, V>>,
+    /// Value deadlines for [`evict`](Self::evict) when built with `timer_wheel`, and where a
+    /// wheel-less [`evict_budgeted`](Self::evict_budgeted) step starts.
+    pub(super) sweep: Sweep<K>,
+}
+
+/// The sweep state of an [`ExpiringCache`]. Boxed, and allocated only once it holds a wheel
+/// or a sweep stops part-way, so a store using neither stays a pointer wider.
+#[derive(Clone)]
+pub(super) struct Sweep<K>(Option<Box<SweepState<K>>>);
+
+#[derive(Clone)]
+struct SweepState<K> {
+    wheel: Option<super::TimerWheel<K>>,
+    /// A bucket index into the map, tagged with the table size it was taken against.
+    cursor: usize,
+    /// With a wheel, whether a scan for values without a deadline is under way, and if so
+    /// whether it has met a live one yet.
+    scan: Option<bool>,
+}
+
+impl<K> Sweep<K> {
+    fn new(wheel: Option<super::TimerWheel<K>>) -> Self {
+        Self(wheel.map(|wheel| {
+            Box::new(SweepState {
+                wheel: Some(wheel),
+                cursor: 0,
+                scan: None,
+            })
+        }))
+    }
+
+    fn wheel(&mut self) -> Option<&mut super::TimerWheel<K>> {
+        self.0.as_deref_mut()?.wheel.as_mut()
+    }
+
+    fn wheel_ref(&self) -> Option<&super::TimerWheel<K>> {
+        self.0.as_deref()?.wheel.as_ref()
+    }
+
+    fn cursor(&self) -> usize {
+        self.0.as_ref().map_or(0, |state| state.cursor)
+    }
+
+    fn set_cursor(&mut self, cursor: usize) {
+        match &mut self.0 {
+            Some(state) => state.cursor = cursor,
+            None if cursor != 0 => {
+                self.0 = Some(Box::new(SweepState {
+                    wheel: None,
+                    cursor,
+                    scan: None,
+                }));
+            }
+            None => {}
+        }
+    }
+
+    fn scan(&self) -> Option<bool> {
+        self.0.as_ref()?.scan
+    }
+
+    fn set_scan(&mut self, scan: Option<bool>) {
+        if let Some(state) = &mut self.0 {
+            state.scan = scan;
+        }
+    }
 }
 
 impl<K, V, S> std::fmt::Debug for ExpiringCache<K, V, S> {
@@ -68,6 +141,10 @@ impl<K, V, S> std::fmt::Debug for ExpiringCache<K, V, S> {
             .field("misses", &self.misses.load(Ordering::Relaxed))
             .field("evictions", &self.evictions.load(Ordering::Relaxed))
             .field("on_evict", &self.on_evict.as_ref().map(|_| "on_evict"))
+            .field(
+                "timer_wheel",
+                &self.sweep.wheel_ref().map(|wheel| wheel.len()),
+            )
             .finish()
     }
 }
@@ -108,6 +185,11 @@ where
             misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
             evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
             on_evict: self.on_evict.clone(),
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+            sweep: self.sweep.clone(),
         }
     }
 }
@@ -123,6 +205,10 @@ pub struct ExpiringCacheBuilder<K, V, S = DefaultHashBuilder> {
     capacity: Option<usize>,
     on_evict: Option<super::OnEvict<K, V>>,
     hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
+    timer_wheel: Option<super::TimerWheelConfig<K>>,
 }
 
 impl<K, V> Default for ExpiringCacheBuilder<K, V, DefaultHashBuilder> {
@@ -131,6 +217,10 @@ impl<K, V> Default for ExpiringCacheBuilder<K, V, DefaultHashBuilder> {
             capacity: None,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+            timer_wheel: None,
         }
     }
 }
@@ -172,7 +262,7 @@ impl<K, V, S> ExpiringCacheBuilder<K, V, S> {
 
     /// Switch to a custom hash builder `S2`, returning a builder parameterized on `S2`.
     ///
-    /// The hasher is used to hash keys in the internal `UnboundCache`. Calling this method
+    /// The hasher is used to hash keys in the internal hash table. Calling this method
     /// changes the builder's type parameter so `build()` returns an `ExpiringCache<K, V, S2>`.
     ///
     /// # Example
@@ -198,32 +288,120 @@ impl<K, V, S> ExpiringCacheBuilder<K, V, S> {
             capacity: self.capacity,
             on_evict: self.on_evict,
             hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
+            timer_wheel: self.timer_wheel,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Each estimate then visits every entry.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
+    /// Keep a hierarchical timing wheel of value deadlines, so [`evict`](ExpiringCache::evict)
+    /// and [`evict_some`](ExpiringCache::evict_some) visit only entries whose deadline has
+    /// passed instead of scanning the whole store.
+    ///
+    /// The wheel files a clone of each key under its value's [`expires_at`](Expires::expires_at),
+    /// rounded up to a multiple of `resolution`: a coarser resolution moves keys between slots
+    /// less often but sweeps them up to one tick late. A value whose `expires_at` is `None`
+    /// cannot be filed. While the store may hold one, each sweep also scans the map and checks
+    /// every entry's [`is_expired`](Expires::is_expired), as a sweep without a wheel does,
+    /// until a scan ends without meeting a live one; a store whose values all report a deadline
+    /// pays only for the due ones. A value that reports `is_expired` before its `expires_at` is
+    /// swept once `expires_at` passes, or by such a scan. Expired values are still hidden and
+    /// removed on access, and [`retain`](ExpiringCache::retain) still scans every entry. A
+    /// deadline moved later in place through `cache_get_mut` is picked up when the old one
+    /// comes due, and one taken away in place starts the scans then. A removed key stays filed
+    /// until its deadline and is dropped then.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `resolution` is zero.
+    #[must_use]
+    pub fn timer_wheel(mut self, resolution: Duration) -> Self
+    where
+        K: Clone,
+    {
+        self.timer_wheel = Some(super::TimerWheelConfig::new(resolution));
+        self
+    }
+
     /// Build the cache.
     ///
-    /// `ExpiringCache` has no required fields and this call never fails.
+    /// `ExpiringCache` has no required fields.
     ///
     /// # Errors
     ///
-    /// This method currently never returns an error.
+    /// Returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if the `hot_keys`
+    /// capacity or the `timer_wheel` resolution is zero.
     pub fn build(self) -> Result<ExpiringCache<K, V, S>, super::BuildError>
     where
         K: Hash + Eq,
         S: BuildHasher,
     {
         let store = match self.capacity {
-            Some(cap) => HashMap::with_capacity_and_hasher(cap, self.hasher),
-            None => HashMap::with_hasher(self.hasher),
+            Some(cap) => BucketMap::with_capacity_and_hasher(cap, self.hasher),
+            None => BucketMap::with_hasher(self.hasher),
         };
         Ok(ExpiringCache {
             store,
-            initial_capacity: self.capacity,
+            initial_capacity: self.capacity.unwrap_or(0),
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
             evictions: AtomicU64::new(0),
             on_evict: self.on_evict,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
+            sweep: Sweep::new(
+                self.timer_wheel
+                    .as_ref()
+                    .map(super::TimerWheelConfig::build)
+                    .transpose()?,
+            ),
         })
     }
 }
@@ -253,23 +431,167 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> ExpiringCache<K, V, S> {
     /// Returns the number of entries removed. Fires the `on_evict` callback for each
     /// removed entry. Use this periodically for high-cardinality workloads to reclaim
     /// memory from entries that expire but are never re-accessed.
+    ///
+    /// With a [`timer_wheel`](ExpiringCacheBuilder::timer_wheel) this visits the entries whose
+    /// deadline has passed, and scans the rest only while the store may hold values without a
+    /// deadline, as [`evict_some`](Self::evict_some) with no budget does.
     #[must_use]
     pub fn evict(&mut self) -> usize {
+        if self.sweep.wheel_ref().is_some() {
+            return self.evict_some(usize::MAX);
+        }
         // Two-phase: select, then remove, then count, then notify. Counting or notifying
-        // from inside a `HashMap::retain` predicate would fire the side effects *before*
+        // from inside a `retain` predicate would fire the side effects *before*
         // the map drops the entry, so a panicking `on_evict` would leave an entry counted
         // (and cleaned up) while still stored and served.
         let removed = self.take_doomed(|_key, value| value.is_expired());
         self.notify_evicted(&removed)
     }
 
+    /// Evict expired entries, checking at most `budget` of them, and return how many were
+    /// removed.
+    ///
+    /// This is an [`evict_budgeted`](Self::evict_budgeted) step without the progress report,
+    /// so the budget counts entries examined, whether or not they turn out to be expired. With
+    /// a [`timer_wheel`](ExpiringCacheBuilder::timer_wheel) it goes first to the keys the wheel
+    /// has due, so the cost follows the number of due entries rather than the size of the
+    /// store; keys past the budget stay due for the next call. A key whose value is still live
+    /// is filed again at its current deadline, and a key no longer stored is dropped; both
+    /// count. Without a wheel the step walks the map from where the last one stopped.
+    pub fn evict_some(&mut self, budget: usize) -> usize {
+        self.evict_budgeted(budget).reclaimed
+    }
+
+    /// Take one step of an incremental [`evict`](Self::evict): check at most `max_entries`
+    /// entries, remove the expired ones, and report how many were removed and whether the
+    /// sweep reached its end.
+    ///
+    /// With a [`timer_wheel`](ExpiringCacheBuilder::timer_wheel) a step checks the keys the
+    /// wheel has due, then spends what is left of the budget scanning the map for values
+    /// without a deadline, while the store may hold any. The sweep is finished once a step
+    /// runs out of due keys and, if it scanned, reaches the end of the map. Without a wheel,
+    /// steps walk the map from where the last one stopped. A walk spends the whole budget
+    /// once it has probed eight buckets per entry of it, so a map that removals left sparse
+    /// does not make a step long. See
+    /// [`CacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        if self.sweep.wheel_ref().is_some() {
+            return self.evict_due(max_entries);
+        }
+        let (reclaimed, finished) =
+            self.evict_window(max_entries, |_key, value| value.is_expired());
+        EvictProgress {
+            reclaimed,
+            finished,
+        }
+    }
+
+    /// Remove what `doomed` selects from the next `budget` entries of the map, starting where
+    /// the last window stopped, and return how many were removed and whether the walk reached
+    /// the end of the map.
+    fn evict_window<F: FnMut(&K, &V) -> bool>(
+        &mut self,
+        budget: usize,
+        doomed: F,
+    ) -> (usize, bool) {
+        let (removed, visited, next) = self.store.take_window(self.sweep.cursor(), budget, doomed);
+        let mut cursor = 0;
+        let finished = crate::stores::advance_evict_cursor(&mut cursor, visited, budget, next);
+        self.sweep.set_cursor(cursor);
+        (self.notify_evicted(&removed), finished)
+    }
+
+    /// One sweep step with a wheel: check up to `budget` keys the wheel has due, then hand the
+    /// rest of the budget to [`scan_unscheduled`](Self::scan_unscheduled).
+    fn evict_due(&mut self, budget: usize) -> EvictProgress {
+        let Some(wheel) = self.sweep.wheel() else {
+            return EvictProgress {
+                reclaimed: 0,
+                finished: true,
+            };
+        };
+        // Two-phase, as in the full sweep: every `is_expired` call comes before the first
+        // removal. A panic there drops the rest of this batch from the wheel, not from the store.
+        let due = wheel.poll(Instant::now(), budget);
+        let polled = due.len();
+        let mut doomed = Vec::new();
+        for key in due {
+            let Some(value) = self.store.get(&key) else {
+                continue;
+            };
+            if value.is_expired() {
+                doomed.push(key);
+            } else if let Some(deadline) = value.expires_at() {
+                wheel.reschedule(key, deadline);
+            } else {
+                // Its deadline was taken away in place: only a scan will see it now.
+                wheel.mark_unscheduled();
+            }
+        }
+        let removed: Vec<(K, V)> = doomed
+            .iter()
+            .filter_map(|key| self.store.remove_entry(key))
+            .collect();
+        // Counted and notified before the scan runs any more user code.
+        let reclaimed = self.notify_evicted(&removed);
+        if polled == budget {
+            return EvictProgress {
+                reclaimed,
+                finished: false,
+            };
+        }
+        let (scanned, finished) = self.scan_unscheduled(budget - polled);
+        EvictProgress {
+            reclaimed: reclaimed + scanned,
+            finished,
+        }
+    }
+
+    /// Check up to `budget` entries of the map for expiry, for the values without a deadline
+    /// that the wheel cannot file. A scan starts only once the wheel has noted such a value,
+    /// resumes where the last step's stopped, and notes again at its end if it met a live
+    /// one, so the next sweep scans too. Returns how many entries were removed and whether
+    /// no scan is left under way.
+    fn scan_unscheduled(&mut self, budget: usize) -> (usize, bool) {
+        let met = match self.sweep.scan() {
+            Some(met) => met,
+            None => {
+                if !self
+                    .sweep
+                    .wheel()
+                    .is_some_and(|wheel| wheel.take_unscheduled())
+                {
+                    return (0, true);
+                }
+                false
+            }
+        };
+        let mut met_now = false;
+        let (reclaimed, finished) = self.evict_window(budget, |_key, value| {
+            value.is_expired() || {
+                met_now |= value.expires_at().is_none();
+                false
+            }
+        });
+        let met = met || met_now;
+        if finished {
+            self.sweep.set_scan(None);
+            if let (true, Some(wheel)) = (met, self.sweep.wheel()) {
+                wheel.mark_unscheduled();
+            }
+        } else {
+            self.sweep.set_scan(Some(met));
+        }
+        (reclaimed, finished)
+    }
+
     /// Phase 1 of a two-phase sweep: run `doomed` over every entry and hand back the
     /// entries it selected, removed from the store.
     ///
-    /// See [`take_doomed`](crate::stores::take_doomed) for why the sweep is split in two and
-    /// what ties the passes together.
+    /// See [`take_doomed`](crate::stores::take_doomed) for why the sweep is split in two.
     fn take_doomed<F: FnMut(&K, &V) -> bool>(&mut self, doomed: F) -> Vec<(K, V)> {
-        crate::stores::take_doomed(&mut self.store, doomed)
+        self.store.take_doomed(doomed)
     }
 
     /// Phase 2 of a two-phase sweep: count `removed` as evictions and then notify
@@ -299,6 +621,9 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> ExpiringCache<K, V, S> {
     /// and increments `evictions`. The eviction count does not depend on whether an `on_evict`
     /// callback is configured.
     pub fn cache_clear_with_on_evict(&mut self) {
+        if let Some(wheel) = self.sweep.wheel() {
+            wheel.clear();
+        }
         let entries: Vec<(K, V)> = self.store.drain().collect();
         let count = entries.len() as u64;
         if count > 0 {
@@ -349,6 +674,9 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
         K: std::borrow::Borrow<Q>,
         Q: std::hash::Hash + Eq + ?Sized,
     {
+        if let Some(hot) = &self.hot_keys {
+            hot.record(hot.hash(k), || self.store.get_key_value(k).map(|(k, _)| k));
+        }
         // Two lookups on the hit path: the first checks expiry (releasing the borrow via
         // `.map`), the second returns the reference. A single-lookup approach is not possible
         // SAFELY in stable Rust because returning `&'1 V` from inside an `if let` block ties
@@ -413,7 +741,7 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
 
     fn cache_get_or_set_with_mut<F: FnOnce() -> V>(&mut self, k: K, f: F) -> &mut V {
         match self.store.entry(k) {
-            std::collections::hash_map::Entry::Occupied(mut occupied) => {
+            super::bucket_map::Entry::Occupied(mut occupied) => {
                 if !occupied.get().is_expired() {
                     self.hits.fetch_add(1, Ordering::Relaxed);
                     occupied.into_mut()
@@ -424,6 +752,10 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
                     // on_evict / counting here would double-fire when the next call
                     // finally evicts the same physical entry.
                     let new_val = f();
+                    if let Some(wheel) = self.sweep.wheel() {
+                        let previous = occupied.get().expires_at();
+                        wheel.schedule_replacing(occupied.key(), previous, new_val.expires_at());
+                    }
                     // Replace FIRST, then count, then notify -- as `cache_set` does.
                     // Firing the side effects while the expired entry is still installed
                     // would let a panicking `on_evict` leave it in place *and* counted, so
@@ -438,9 +770,13 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
                     occupied.into_mut()
                 }
             }
-            std::collections::hash_map::Entry::Vacant(vacant) => {
+            super::bucket_map::Entry::Vacant(vacant) => {
                 self.misses.fetch_add(1, Ordering::Relaxed);
-                vacant.insert(f())
+                let value = f();
+                if let Some(wheel) = self.sweep.wheel() {
+                    wheel.schedule(vacant.key(), value.expires_at());
+                }
+                vacant.insert(value)
             }
         }
     }
@@ -451,7 +787,7 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
         f: F,
     ) -> Result<&mut V, E> {
         match self.store.entry(k) {
-            std::collections::hash_map::Entry::Occupied(mut occupied) => {
+            super::bucket_map::Entry::Occupied(mut occupied) => {
                 if !occupied.get().is_expired() {
                     self.hits.fetch_add(1, Ordering::Relaxed);
                     Ok(occupied.into_mut())
@@ -460,6 +796,10 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
                     // Same ordering as `cache_get_or_set_with_mut`: compute, replace,
                     // count, then notify.
                     let new_val = f()?;
+                    if let Some(wheel) = self.sweep.wheel() {
+                        let previous = occupied.get().expires_at();
+                        wheel.schedule_replacing(occupied.key(), previous, new_val.expires_at());
+                    }
                     let old = occupied.insert(new_val);
                     self.evictions.fetch_add(1, Ordering::Relaxed);
                     if let Some(on_evict) = &self.on_evict {
@@ -468,17 +808,25 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
                     Ok(occupied.into_mut())
                 }
             }
-            std::collections::hash_map::Entry::Vacant(vacant) => {
+            super::bucket_map::Entry::Vacant(vacant) => {
                 self.misses.fetch_add(1, Ordering::Relaxed);
-                Ok(vacant.insert(f()?))
+                let value = f()?;
+                if let Some(wheel) = self.sweep.wheel() {
+                    wheel.schedule(vacant.key(), value.expires_at());
+                }
+                Ok(vacant.insert(value))
             }
         }
     }
 
     fn cache_set(&mut self, k: K, v: V) -> Option<V> {
-        use std::collections::hash_map::Entry;
+        use super::bucket_map::Entry;
         match self.store.entry(k) {
             Entry::Occupied(mut occupied) => {
+                if let Some(wheel) = self.sweep.wheel() {
+                    let previous = occupied.get().expires_at();
+                    wheel.schedule_replacing(occupied.key(), previous, v.expires_at());
+                }
                 let old = occupied.insert(v);
                 if old.is_expired() {
                     // The previous value had expired, so it is filtered from the return
@@ -496,6 +844,9 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
                 }
             }
             Entry::Vacant(vacant) => {
+                if let Some(wheel) = self.sweep.wheel() {
+                    wheel.schedule(vacant.key(), v.expires_at());
+                }
                 vacant.insert(v);
                 None
             }
@@ -548,13 +899,21 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
 
     fn cache_clear(&mut self) {
         self.store.clear();
+        self.tags.clear();
+        if let Some(wheel) = self.sweep.wheel() {
+            wheel.clear();
+        }
     }
 
     fn cache_reset(&mut self) {
         // Clear all entries and shrink capacity back toward the initial hint, matching
         // `UnboundCache::cache_reset` (which this store used to delegate to).
         self.store.clear();
-        self.store.shrink_to(self.initial_capacity.unwrap_or(0));
+        self.store.shrink_to(self.initial_capacity);
+        self.tags.clear();
+        if let Some(wheel) = self.sweep.wheel() {
+            wheel.clear();
+        }
         self.cache_reset_metrics();
     }
 
@@ -570,6 +929,23 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
         Some(self.misses.load(Ordering::Relaxed))
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self.deep_size.map_or(0, |heap| {
+            self.store.iter().map(|(k, v)| heap.entry(k, v)).sum()
+        });
+        let wheel = self.sweep.wheel_ref().map_or(0, |wheel| wheel.heap_bytes());
+        Some(self.store.allocation_bytes() + entries + wheel)
+    }
+
     fn cache_evictions(&self) -> Option<u64> {
         Some(self.evictions.load(Ordering::Relaxed))
     }
@@ -578,6 +954,9 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> Cached<K, V> for ExpiringCache<K,
         self.hits.store(0, Ordering::Relaxed);
         self.misses.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
 
     /// Check whether the cache contains a live (non-expired) entry for `k`.
@@ -605,6 +984,58 @@ impl<K: Hash + Eq, V: Expires, S: BuildHasher> CachedIter<K, V> for ExpiringCach
     }
 }
 
+impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> super::CachedTags<K, V>
+    for ExpiringCache<K, V, S>
+{
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.store.len()) {
+            let store = &self.store;
+            self.tags.retain_keys(|k| store.contains_key(k));
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K: Hash + Eq + Clone, V: Expires + Clone, S: BuildHasher> super::CachedSnapshot<K, V>
+    for ExpiringCache<K, V, S>
+{
+    /// Values carry their own expiry, so `V`'s serialized form is what survives a restart;
+    /// [`Expires::expires_at`] is recorded as the entry's deadline when the value reports one.
+    fn snapshot(&self) -> super::CacheSnapshot<K, V> {
+        let clock = super::SnapshotClock::now();
+        let entries = self
+            .store
+            .iter()
+            .filter(|(_, v)| !v.is_expired())
+            .filter_map(|(k, v)| clock.entry(k.clone(), v.clone(), v.expires_at()))
+            .collect();
+        super::CacheSnapshot::from_shards(vec![entries])
+    }
+
+    /// Values that report [`is_expired`](Expires::is_expired) once restored are dropped.
+    fn restore(&mut self, snapshot: super::CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(super::SnapshotClock::now()) {
+            if v.is_expired() {
+                continue;
+            }
+            self.cache_set(k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 impl<K: Hash + Eq, V: Expires, S: BuildHasher> CachedPeek<K, V> for ExpiringCache<K, V, S> {
     fn cache_peek<Q>(&self, key: &Q) -> Option<&V>
     where
@@ -651,6 +1082,14 @@ where
                         // Same ordering as the sync path: compute, replace, count,
                         // then notify.
                         let new_val = f().await;
+                        if let Some(wheel) = self.sweep.wheel() {
+                            let previous = occupied.get().expires_at();
+                            wheel.schedule_replacing(
+                                occupied.key(),
+                                previous,
+                                new_val.expires_at(),
+                            );
+                        }
                         let old = occupied.insert(new_val);
                         self.evictions.fetch_add(1, Ordering::Relaxed);
                         if let Some(on_evict) = &self.on_evict {
@@ -661,7 +1100,11 @@ where
                 }
                 Entry::Vacant(vacant) => {
                     self.misses.fetch_add(1, Ordering::Relaxed);
-                    vacant.insert(f().await)
+                    let value = f().await;
+                    if let Some(wheel) = self.sweep.wheel() {
+                        wheel.schedule(vacant.key(), value.expires_at());
+                    }
+                    vacant.insert(value)
                 }
             }
         }
@@ -690,6 +1133,14 @@ where
                         // Same ordering as the sync path: compute, replace, count,
                         // then notify.
                         let new_val = f().await?;
+                        if let Some(wheel) = self.sweep.wheel() {
+                            let previous = occupied.get().expires_at();
+                            wheel.schedule_replacing(
+                                occupied.key(),
+                                previous,
+                                new_val.expires_at(),
+                            );
+                        }
                         let old = occupied.insert(new_val);
                         self.evictions.fetch_add(1, Ordering::Relaxed);
                         if let Some(on_evict) = &self.on_evict {
@@ -700,7 +1151,11 @@ where
                 }
                 Entry::Vacant(vacant) => {
                     self.misses.fetch_add(1, Ordering::Relaxed);
-                    vacant.insert(f().await?)
+                    let value = f().await?;
+                    if let Some(wheel) = self.sweep.wheel() {
+                        wheel.schedule(vacant.key(), value.expires_at());
+                    }
+                    vacant.insert(value)
                 }
             };
             Ok(v)
@@ -759,6 +1214,10 @@ impl<K: std::hash::Hash + Eq, V: Expires, S: BuildHasher> CacheEvict for Expirin
     fn evict(&mut self) -> usize {
         ExpiringCache::evict(self)
     }
+
+    fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        ExpiringCache::evict_budgeted(self, max_entries)
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use super::{CacheEvict, Cached, DefaultHashBuilder, LruCache};
+use super::{CacheEvict, Cached, DefaultHashBuilder, EvictProgress, LruCache};
 use crate::{CachedIter, CachedPeek, CloneCached};
 use std::hash::{BuildHasher, Hash};
 use std::sync::Arc;
@@ -49,13 +49,32 @@ pub trait Expires {
     ///
     /// The default implementation returns `None`. Override this in types that record a
     /// concrete deadline to enable observability (logging, metrics) and to allow callers
-    /// to extend or compare deadlines without re-computing them.
+    /// to extend or compare deadlines without re-computing them. An
+    /// [`ExpiringCache`](crate::ExpiringCache) or
+    /// [`ShardedExpiringCache`](crate::ShardedExpiringCache) built with `timer_wheel` files
+    /// each value under this deadline and checks it once the deadline passes. A value that
+    /// returns `None` cannot be filed, so while the store may hold one, every sweep (`evict`,
+    /// or the budget `evict_some` has left after the due keys) also scans the map calling
+    /// `is_expired`, which costs a walk of the whole store per sweep instead of work that
+    /// follows the number of due entries. Return a deadline wherever there is one to keep
+    /// the wheel's sweeps cheap.
     ///
     /// `is_expired()` remains the authoritative liveness check; `expires_at` is advisory
     /// and must not be used as a substitute for `is_expired`.
     fn expires_at(&self) -> Option<crate::time::Instant> {
         None
     }
+
+    /// Whether `result_fallback` may still return this value in place of an `Err` from the
+    /// cached function, typically once the value has expired.
+    ///
+    /// The default returns `true`: a stale value is served for as long as it stays in the
+    /// cache. Override it to bound that window, as `cached::http::HttpCached` (feature `http`)
+    /// does with `stale-if-error`. Only the macros' `result_fallback` path consults it; lookups
+    /// ignore it.
+    fn serve_stale_on_error(&self) -> bool {
+        true
+    }
 }
 
 /// LRU-bounded cache with per-value expiry.
@@ -85,6 +104,12 @@ pub struct ExpiringLruCache<K, V, S = DefaultHashBuilder> {
     pub(super) misses: AtomicU64,
     pub(super) evictions: AtomicU64,
     pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    pub(super) metrics_exporter: super::ExporterSlot,
+    pub(super) hot_keys: Option<super::HotKeys<K>>,
+    pub(super) deep_size: Option<super::HeapSize<K, V>>,
+    /// Where the next [`evict_budgeted`](Self::evict_budgeted) step starts, as a slot of the
+    /// inner store's entry list.
+    pub(super) evict_cursor: usize,
 }
 
 impl<K, V, S> std::fmt::Debug for ExpiringLruCache<K, V, S> {
@@ -134,6 +159,10 @@ where
             misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
             evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
             on_evict: self.on_evict.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+            evict_cursor: self.evict_cursor,
         }
     }
 }
@@ -147,16 +176,24 @@ where
 #[doc(alias = "ttl")]
 pub struct ExpiringLruCacheBuilder<K, V, S = DefaultHashBuilder> {
     size: Option<usize>,
+    max_pinned: Option<usize>,
     on_evict: Option<super::OnEvict<K, V>>,
     hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for ExpiringLruCacheBuilder<K, V, DefaultHashBuilder> {
     fn default() -> Self {
         Self {
             size: None,
+            max_pinned: None,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -179,6 +216,14 @@ impl<K, V, S> ExpiringLruCacheBuilder<K, V, S> {
         self
     }
 
+    /// Cap the number of pinned entries, which do not count toward `max_size`. See
+    /// [`LruCacheBuilder::max_pinned`](super::LruCacheBuilder::max_pinned).
+    #[must_use]
+    pub fn max_pinned(mut self, max_pinned: usize) -> Self {
+        self.max_pinned = Some(max_pinned);
+        self
+    }
+
     /// Set a callback to be invoked when an entry is evicted. The callback fires for:
     /// - LRU capacity eviction: inserting past `max_size` evicts the least-recently-used entry.
     /// - Capacity shrink via [`set_max_size`](ExpiringLruCache::set_max_size) /
@@ -232,17 +277,66 @@ impl<K, V, S> ExpiringLruCacheBuilder<K, V, S> {
     pub fn hasher<S2: BuildHasher>(self, hasher: S2) -> ExpiringLruCacheBuilder<K, V, S2> {
         ExpiringLruCacheBuilder {
             size: self.size,
+            max_pinned: self.max_pinned,
             on_evict: self.on_evict,
             hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Each estimate then visits every entry.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
     /// Build the cache.
     ///
     /// # Errors
     ///
     /// Returns [`BuildError::MissingRequired`](super::BuildError) if `max_size` was not set,
-    /// or [`BuildError::InvalidValue`](super::BuildError) if `max_size` is `0`.
+    /// or [`BuildError::InvalidValue`](super::BuildError) if `max_size` or `max_pinned` is `0`.
     pub fn build(self) -> Result<ExpiringLruCache<K, V, S>, super::BuildError>
     where
         K: Hash + Eq + Clone,
@@ -251,10 +345,12 @@ impl<K, V, S> ExpiringLruCacheBuilder<K, V, S> {
         let size = self
             .size
             .ok_or(super::BuildError::MissingRequired("max_size"))?;
+        super::check_max_pinned(self.max_pinned)?;
         let mut store = LruCache::builder()
             .max_size(size)
             .hasher(self.hasher)
             .build()?;
+        store.max_pinned = self.max_pinned;
         store.disable_hit_miss_tracking();
         // Two separate callbacks for two separate eviction causes:
         //   cache.on_evict    -- fires when ExpiringLruCache itself removes an expired entry
@@ -266,6 +362,10 @@ impl<K, V, S> ExpiringLruCacheBuilder<K, V, S> {
             misses: AtomicU64::new(0),
             evictions: AtomicU64::new(0),
             on_evict: self.on_evict.clone(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
+            evict_cursor: 0,
         };
         if let Some(on_evict) = self.on_evict {
             cache.store.on_evict = Some(on_evict);
@@ -356,6 +456,28 @@ impl<K: Clone + Hash + Eq, V: Expires, S: BuildHasher> ExpiringLruCache<K, V, S>
         self.remove_and_notify(doomed)
     }
 
+    /// Take one step of an incremental [`evict`](Self::evict): check at most `max_entries`
+    /// values from where the last step stopped, remove the expired ones, and report how many
+    /// were removed and whether the sweep reached the end of the store.
+    ///
+    /// Steps walk the entries in storage order rather than recency order, so touching an
+    /// entry mid-sweep does not move it past the cursor. See
+    /// [`CacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        let (doomed, visited, next) =
+            self.store
+                .doomed_slots(self.evict_cursor, max_entries, |_key, value| {
+                    value.is_expired()
+                });
+        let finished =
+            crate::stores::advance_evict_cursor(&mut self.evict_cursor, visited, max_entries, next);
+        EvictProgress {
+            reclaimed: self.remove_and_notify(doomed),
+            finished,
+        }
+    }
+
     /// Phase 1 of a two-phase sweep: inner-store slot indices (MRU -> LRU) of the entries
     /// `doomed` selects.
     ///
@@ -446,7 +568,8 @@ impl<K: Clone + Hash + Eq, V: Expires, S: BuildHasher> ExpiringLruCache<K, V, S>
     /// Return all live entries in current LRU order (most-recently-used first)
     /// as `(K, `[`CacheValue<V>`](super::CacheValue)`)` pairs. `ExpiringLruCache`
     /// carries no per-entry metadata beyond what `V: Expires` itself exposes, so
-    /// the wrapper's metadata type is `()`; the wrapper `Deref`s to `V`.
+    /// the wrapper's metadata type is `()`; the wrapper `Deref`s to `V`. Pinned entries
+    /// come first and report [`is_pinned`](super::CacheValue::is_pinned).
     /// Expired entries are excluded.
     #[must_use]
     pub fn iter_order(&self) -> Vec<(K, super::CacheValue<V>)>
@@ -455,10 +578,9 @@ impl<K: Clone + Hash + Eq, V: Expires, S: BuildHasher> ExpiringLruCache<K, V, S>
         V: Clone,
     {
         self.store
-            .iter_order_raw()
+            .iter_order()
             .into_iter()
             .filter(|(_, v)| !v.is_expired())
-            .map(|(k, v)| (k, super::CacheValue::new(v, ())))
             .collect()
     }
 
@@ -492,14 +614,109 @@ impl<K: Clone + Hash + Eq, V: Expires, S: BuildHasher> ExpiringLruCache<K, V, S>
         // Upper-bound pre-size on the raw stored count (may include expired entries not
         // yet swept); the filter below can only shrink the final length.
         let mut out = Vec::with_capacity(self.store.cache_size());
-        out.extend(self.store.order.iter().filter_map(|(_, v)| {
-            if v.is_expired() {
+        // The order walks the pinned entries first, so the first `pinned_len` are pinned.
+        let pinned = self.store.pinned_len();
+        out.extend(
+            self.store
+                .order
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (_, v))| {
+                    if v.is_expired() {
+                        None
+                    } else {
+                        Some(super::CacheValue::new(v.clone(), ()).with_pinned(i < pinned))
+                    }
+                }),
+        );
+        out
+    }
+
+    /// Pin the live entry for `k` so LRU eviction never selects it; it still expires.
+    /// Returns `Ok(false)` when `k` is absent or expired. See
+    /// [`LruCache::pin`](super::LruCache::pin).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`](super::PinError::LimitReached) when the entry is
+    /// not pinned yet and the cache already holds
+    /// [`max_pinned`](ExpiringLruCacheBuilder::max_pinned) pinned entries.
+    pub fn pin<Q>(&mut self, k: &Q) -> Result<bool, super::PinError>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let store = &mut self.store;
+        match store.get_index(store.hash(k), k) {
+            Some(index) if !store.order.get(index).1.is_expired() => {
+                store.pin_index(index)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Unpin the entry for `k`, returning whether it was pinned. See
+    /// [`LruCache::unpin`](super::LruCache::unpin).
+    pub fn unpin<Q>(&mut self, k: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store.unpin(k)
+    }
+
+    /// Insert or replace the entry for `k` and pin it, returning the previous value only
+    /// if it had not yet expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`](super::PinError::LimitReached) when `k` is not
+    /// pinned yet and the cache already holds
+    /// [`max_pinned`](ExpiringLruCacheBuilder::max_pinned) pinned entries. Nothing is stored.
+    pub fn set_pinned(&mut self, k: K, v: V) -> Result<Option<V>, super::PinError> {
+        let displaced = self.store.set_pinned_returning_entry(k, v)?;
+        Ok(self.settle_displaced(displaced))
+    }
+
+    /// Whether the entry for `k` is pinned, expired or not.
+    #[must_use]
+    pub fn is_pinned<Q>(&self, k: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store.is_pinned(k)
+    }
+
+    /// Number of pinned entries, including expired ones not yet removed.
+    #[must_use]
+    pub fn pinned_len(&self) -> usize {
+        self.store.pinned_len()
+    }
+
+    /// Report the value displaced by an insert: a live one is returned, an expired one
+    /// is counted as an eviction and handed to `on_evict` instead.
+    fn settle_displaced(&mut self, displaced: Option<(K, V)>) -> Option<V> {
+        // `cache_set_returning_entry` hands back the STORED key/value of the displaced
+        // entry, so no caller-side key clone is needed to feed `on_evict`. Like the plain
+        // `LruCache::cache_set` it does NOT fire `on_evict` on an overwrite itself, so an
+        // expired displaced value would otherwise be dropped silently; filter it from the
+        // return and fire `on_evict` + count once here, with the STORED key, matching
+        // `LruTtlCache::set_entry`.
+        match displaced {
+            Some((stored_key, old)) if old.is_expired() => {
+                // Count BEFORE notifying: a panicking callback must never leave an
+                // entry removed-but-uncounted.
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+                if let Some(on_evict) = &self.on_evict {
+                    on_evict(&stored_key, &old);
+                }
                 None
-            } else {
-                Some(super::CacheValue::new(v.clone(), ()))
             }
-        }));
-        out
+            Some((_, old)) => Some(old),
+            None => None,
+        }
     }
 }
 
@@ -512,11 +729,14 @@ impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> Cached<K, V> for Expiring
         K: std::borrow::Borrow<Q>,
         Q: std::hash::Hash + Eq + ?Sized,
     {
+        if let Some(hot) = &self.hot_keys {
+            hot.record(hot.hash(k), || self.store.stored_key(k));
+        }
         let hash = self.store.hash(k);
         if let Some(index) = self.store.get_index(hash, k) {
             let value = &self.store.order.get(index).1;
             if !value.is_expired() {
-                self.store.order.move_to_front(index);
+                self.store.touch(index);
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 Some(&self.store.order.get(index).1)
             } else {
@@ -548,7 +768,7 @@ impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> Cached<K, V> for Expiring
         if let Some(index) = self.store.get_index(hash, key) {
             let value = &self.store.order.get(index).1;
             if !value.is_expired() {
-                self.store.order.move_to_front(index);
+                self.store.touch(index);
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 Some(&mut self.store.order.get_mut(index).1)
             } else {
@@ -635,27 +855,8 @@ impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> Cached<K, V> for Expiring
         Ok(v)
     }
     fn cache_set(&mut self, k: K, v: V) -> Option<V> {
-        // `cache_set_returning_entry` hands back the STORED key/value of the displaced
-        // entry, so no caller-side key clone is needed to feed `on_evict` (unlike the old
-        // `LruCache::cache_set` + `k.clone()` combination, which cloned the key on every
-        // insert whenever `on_evict` was configured). Like the plain `LruCache::cache_set`
-        // it does NOT fire `on_evict` on an overwrite itself, so an expired displaced value
-        // would otherwise be dropped silently; filter it from the return and fire
-        // `on_evict` + count once here -- now with the STORED key, matching
-        // `LruTtlCache::set_entry`.
-        match self.store.cache_set_returning_entry(k, v) {
-            Some((stored_key, old)) if old.is_expired() => {
-                // Count BEFORE notifying: a panicking callback must never leave an
-                // entry removed-but-uncounted.
-                self.evictions.fetch_add(1, Ordering::Relaxed);
-                if let Some(on_evict) = &self.on_evict {
-                    on_evict(&stored_key, &old);
-                }
-                None
-            }
-            Some((_, old)) => Some(old),
-            None => None,
-        }
+        let displaced = self.store.cache_set_returning_entry(k, v);
+        self.settle_displaced(displaced)
     }
     /// Removes the entry and returns the value only if it is still live;
     /// an expired value is removed but reported as `None`. Use
@@ -713,6 +914,9 @@ impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> Cached<K, V> for Expiring
         self.hits.store(0, Ordering::Relaxed);
         self.misses.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
     fn cache_size(&self) -> usize {
         self.store.cache_size()
@@ -728,6 +932,22 @@ impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> Cached<K, V> for Expiring
     fn cache_misses(&self) -> Option<u64> {
         Some(self.misses.load(Ordering::Relaxed))
     }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self.deep_size.map_or(0, |heap| {
+            self.store.entries_heap_size(|k, v| heap.entry(k, v))
+        });
+        Some(self.store.allocated_bytes() + entries)
+    }
     fn cache_evictions(&self) -> Option<u64> {
         Some(self.evictions.load(Ordering::Relaxed) + self.store.cache_evictions().unwrap_or(0))
     }
@@ -736,6 +956,9 @@ impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> Cached<K, V> for Expiring
         self.misses.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
         self.store.cache_reset_metrics();
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
 
     /// Check whether the cache contains a live (non-expired) entry for `k`.
@@ -766,6 +989,55 @@ impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> CachedIter<K, V>
     }
 }
 
+impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> super::CachedTags<K, V>
+    for ExpiringLruCache<K, V, S>
+{
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        self.store.prune_tags();
+        self.store.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.store.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K: Hash + Eq + Clone, V: Expires + Clone, S: BuildHasher> super::CachedSnapshot<K, V>
+    for ExpiringLruCache<K, V, S>
+{
+    /// Entries are recorded least-recently-used first; see
+    /// [`ExpiringCache`](super::ExpiringCache)'s impl for how value expiry is carried.
+    fn snapshot(&self) -> super::CacheSnapshot<K, V> {
+        let clock = super::SnapshotClock::now();
+        let order: Vec<&(K, V)> = self.store.order.iter().collect();
+        let entries = order
+            .into_iter()
+            .rev()
+            .filter(|(_, v)| !v.is_expired())
+            .filter_map(|(k, v)| clock.entry(k.clone(), v.clone(), v.expires_at()))
+            .collect();
+        super::CacheSnapshot::from_shards(vec![entries])
+    }
+
+    fn restore(&mut self, snapshot: super::CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(super::SnapshotClock::now()) {
+            if v.is_expired() {
+                continue;
+            }
+            self.cache_set(k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 impl<K: Hash + Eq + Clone, V: Expires, S: BuildHasher> CachedPeek<K, V>
     for ExpiringLruCache<K, V, S>
 {
@@ -894,7 +1166,7 @@ impl<K: Hash + Eq + Clone, V: Expires + Clone, S: BuildHasher> CloneCached<K, V>
                 // use it during revalidation.
                 (Some(self.store.order.get(index).1.clone()), true)
             } else {
-                self.store.order.move_to_front(index);
+                self.store.touch(index);
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 (Some(self.store.order.get(index).1.clone()), false)
             }
@@ -931,6 +1203,10 @@ impl<K: std::hash::Hash + Eq + Clone, V: Expires, S: BuildHasher> CacheEvict
     fn evict(&mut self) -> usize {
         ExpiringLruCache::evict(self)
     }
+
+    fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        ExpiringLruCache::evict_budgeted(self, max_entries)
+    }
 }
 
 #[cfg(test)]
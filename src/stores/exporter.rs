@@ -0,0 +1,202 @@
+//! Publishing store statistics through the [`metrics`](https://docs.rs/metrics) facade (the
+//! `metrics` feature).
+//!
+//! A store built with `metrics_name("...")` carries a [`MetricsExporter`], and
+//! [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics) /
+//! [`ConcurrentCacheBase::publish_metrics`](crate::ConcurrentCacheBase::publish_metrics) hand the
+//! store's [`CacheMetrics`] to it. Every value is labelled `cache = "<name>"`:
+//!
+//! | metric                          | kind      | source                              |
+//! |---------------------------------|-----------|-------------------------------------|
+//! | `cached_hits_total`             | counter   | `CacheMetrics::hits`                |
+//! | `cached_misses_total`           | counter   | `CacheMetrics::misses`              |
+//! | `cached_evictions_total`        | counter   | `CacheMetrics::evictions`           |
+//! | `cached_entries`                | gauge     | `CacheMetrics::entry_count`         |
+//! | `cached_capacity`               | gauge     | `CacheMetrics::capacity`            |
+//! | `cached_memory_bytes`           | gauge     | `CacheMetrics::mem_bytes`           |
+//! | `cached_miss_duration_seconds`  | histogram | time spent computing a missed value |
+//!
+//! A field the store reports as `None` is not emitted. The Redis and redb stores keep no
+//! hit/miss counters of their own, so they count each lookup on the exporter as it happens
+//! instead. The miss-computation histogram is recorded by the `#[cached]`,
+//! `#[concurrent_cached]` and `#[once]` macros, which are the only callers that see the
+//! computation; code that fills a store by hand can time its own and call
+//! [`MetricsExporter::record_miss_duration`].
+
+use std::sync::Arc;
+
+use metrics::{Label, counter, gauge, histogram};
+use parking_lot::Mutex;
+
+use crate::CacheMetrics;
+use crate::time::Duration;
+
+const HITS: &str = "cached_hits_total";
+const MISSES: &str = "cached_misses_total";
+const EVICTIONS: &str = "cached_evictions_total";
+const ENTRIES: &str = "cached_entries";
+const CAPACITY: &str = "cached_capacity";
+const MEMORY: &str = "cached_memory_bytes";
+const MISS_DURATION: &str = "cached_miss_duration_seconds";
+
+/// Counter values as of the last publish, which the next publish turns into increments.
+#[derive(Clone, Copy, Debug, Default)]
+struct Published {
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+/// Emits one cache's statistics as labelled `metrics` counters, gauges and histograms.
+///
+/// The store's counters are cumulative, while a `metrics` counter is incremented, so the
+/// exporter remembers what it last published and emits the difference. A counter that went
+/// down since then was reset (`cache_reset_metrics`), and its whole new value is emitted.
+///
+/// Cloning an exporter copies what it has published so far. That matches how the stores clone
+/// their counters: the clone and the original then publish their own increments under the same
+/// name, and the exported totals are their sum.
+#[derive(Debug)]
+pub struct MetricsExporter {
+    name: Arc<str>,
+    labels: [Label; 1],
+    published: Mutex<Published>,
+}
+
+impl Clone for MetricsExporter {
+    fn clone(&self) -> Self {
+        Self {
+            name: Arc::clone(&self.name),
+            labels: self.labels.clone(),
+            published: Mutex::new(*self.published.lock()),
+        }
+    }
+}
+
+impl MetricsExporter {
+    /// Create an exporter that labels everything it emits `cache = name`.
+    #[must_use]
+    pub fn new(name: impl Into<Arc<str>>) -> Self {
+        let name = name.into();
+        Self {
+            labels: [Label::new("cache", Arc::clone(&name))],
+            name,
+            published: Mutex::new(Published::default()),
+        }
+    }
+
+    /// The value of the `cache` label.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Take a snapshot with `snapshot` and publish it.
+    ///
+    /// The snapshot is taken while the exporter's lock is held, so concurrent publishes see
+    /// the store's counters in order and a smaller value can only mean a reset.
+    pub fn publish(&self, snapshot: impl FnOnce() -> CacheMetrics) {
+        let mut published = self.published.lock();
+        let metrics = snapshot();
+        if let Some(hits) = metrics.hits {
+            self.advance(HITS, &mut published.hits, hits);
+        }
+        if let Some(misses) = metrics.misses {
+            self.advance(MISSES, &mut published.misses, misses);
+        }
+        if let Some(evictions) = metrics.evictions {
+            self.advance(EVICTIONS, &mut published.evictions, evictions);
+        }
+        if let Some(entries) = metrics.entry_count {
+            gauge!(ENTRIES, self.labels.iter()).set(entries as f64);
+        }
+        if let Some(capacity) = metrics.capacity {
+            gauge!(CAPACITY, self.labels.iter()).set(capacity as f64);
+        }
+        if let Some(bytes) = metrics.mem_bytes {
+            gauge!(MEMORY, self.labels.iter()).set(bytes as f64);
+        }
+    }
+
+    /// Count one lookup that found its key. For stores and callers that do not track hits in
+    /// `CacheMetrics`; publishing a snapshot with `hits` set as well would count them twice.
+    pub fn record_hit(&self) {
+        counter!(HITS, self.labels.iter()).increment(1);
+    }
+
+    /// Count one lookup that did not find its key. See [`record_hit`](Self::record_hit).
+    pub fn record_miss(&self) {
+        counter!(MISSES, self.labels.iter()).increment(1);
+    }
+
+    /// Record how long computing a missed value took.
+    pub fn record_miss_duration(&self, elapsed: Duration) {
+        histogram!(MISS_DURATION, self.labels.iter()).record(elapsed.as_secs_f64());
+    }
+
+    fn advance(&self, name: &'static str, last: &mut u64, current: u64) {
+        let delta = if current >= *last {
+            current - *last
+        } else {
+            current
+        };
+        *last = current;
+        if delta > 0 {
+            counter!(name, self.labels.iter()).increment(delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+    // `Snapshotter::snapshot` drains, so each call returns what was added since the last.
+    fn counter_value(snapshotter: &Snapshotter, name: &str) -> Option<u64> {
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .find_map(|(key, _, _, value)| match value {
+                DebugValue::Counter(n) if key.key().name() == name => Some(n),
+                _ => None,
+            })
+    }
+
+    fn snapshot(hits: u64) -> CacheMetrics {
+        CacheMetrics {
+            hits: Some(hits),
+            ..CacheMetrics::default()
+        }
+    }
+
+    #[test]
+    fn publishes_increments_and_restarts_after_a_reset() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let exporter = MetricsExporter::new("deltas");
+            exporter.publish(|| snapshot(3));
+            exporter.publish(|| snapshot(5));
+            assert_eq!(counter_value(&snapshotter, HITS), Some(5));
+            // Reset to zero, then two more hits: both are new.
+            exporter.publish(|| snapshot(2));
+            assert_eq!(counter_value(&snapshotter, HITS), Some(2));
+        });
+    }
+
+    #[test]
+    fn a_clone_publishes_only_its_own_increments() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let exporter = MetricsExporter::new("clone");
+            exporter.publish(|| snapshot(4));
+            let clone = exporter.clone();
+            clone.publish(|| snapshot(6));
+            exporter.publish(|| snapshot(5));
+            assert_eq!(counter_value(&snapshotter, HITS), Some(7));
+        });
+    }
+}
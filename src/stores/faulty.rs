@@ -0,0 +1,588 @@
+//! [`FaultyCache`]: a fault-injecting wrapper around any concurrent store.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+
+#[cfg(feature = "async_core")]
+use crate::ConcurrentCachedAsync;
+use crate::stores::BuildError;
+use crate::time::Duration;
+use crate::{CacheMetrics, ConcurrentCacheBase, ConcurrentCached, HotKey};
+
+type ErrorFn<E> = Arc<dyn Fn() -> E + Send + Sync>;
+
+/// The error a [`FaultyCache`] returns for an injected failure unless
+/// [`error`](FaultyCacheBuilder::error) supplies another.
+///
+/// It converts from [`Infallible`](std::convert::Infallible), so it serves as the error type of
+/// a `FaultyCache` around any of the in-memory stores.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InjectedFault;
+
+impl std::fmt::Display for InjectedFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("injected cache fault")
+    }
+}
+
+impl std::error::Error for InjectedFault {}
+
+impl From<std::convert::Infallible> for InjectedFault {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+/// Wraps a [`ConcurrentCached`] / [`ConcurrentCachedAsync`] store and makes it misbehave, for
+/// testing how callers cope with a slow, failing or inconsistent cache.
+///
+/// Each operation is first delayed by the configured [latency](FaultyCacheBuilder::latency),
+/// then fails with the supplied error at the [fail rate](FaultyCacheBuilder::fail_rate). If it
+/// does not fail, a `cache_set` is dropped at the
+/// [drop-write rate](FaultyCacheBuilder::drop_write_rate) (returning `Ok(None)` without
+/// writing), and a `cache_get` returns a stale value at the
+/// [stale rate](FaultyCacheBuilder::stale_rate): the value the key held before its last
+/// overwrite or removal through this wrapper, if there was one. Everything else goes to the
+/// wrapped store, whose errors are converted into `E` with `From`.
+///
+/// The decisions come from a random generator seeded by [`seed`](FaultyCacheBuilder::seed), so
+/// a single-threaded test sees the same faults on every run; with several threads sharing the
+/// wrapper, which operation gets which draw depends on their interleaving. Only the key-based
+/// operations and `cache_clear` / `cache_reset` are faulted; the [`ConcurrentCacheBase`]
+/// introspection methods pass straight through.
+///
+/// ```rust
+/// use cached::stores::{FaultyCache, InjectedFault};
+/// use cached::{ConcurrentCached, ShardedUnboundCache};
+///
+/// let store: ShardedUnboundCache<u32, &str> = ShardedUnboundCache::builder().build().unwrap();
+/// let cache = FaultyCache::builder(store)
+///     .fail_rate(0.5)
+///     .seed(7)
+///     .build()
+///     .unwrap();
+///
+/// let results: Vec<_> = (0..20).map(|k| cache.cache_set(k, "v")).collect();
+/// assert!(results.contains(&Err(InjectedFault)));
+/// assert!(results.contains(&Ok(None)));
+///
+/// cache.set_enabled(false);
+/// assert!((0..20).all(|k| cache.cache_get(&k).is_ok()));
+/// ```
+pub struct FaultyCache<S, E = InjectedFault> {
+    inner: S,
+    error: ErrorFn<E>,
+    fail_rate: f64,
+    drop_write_rate: f64,
+    stale_rate: f64,
+    latency: Duration,
+    latency_jitter: Duration,
+    enabled: AtomicBool,
+    rng: Mutex<u64>,
+    /// The displaced values `stale_rate` serves: a `HashMap<K, V>`, created on first use.
+    /// Type-erased so `K` and `V` stay off the wrapper's type.
+    stale: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+/// Builder for [`FaultyCache`]. Every fault is off until configured.
+pub struct FaultyCacheBuilder<S, E = InjectedFault> {
+    inner: S,
+    error: ErrorFn<E>,
+    fail_rate: f64,
+    drop_write_rate: f64,
+    stale_rate: f64,
+    latency: Duration,
+    latency_jitter: Duration,
+    seed: u64,
+}
+
+impl<S> FaultyCache<S> {
+    /// Start building a `FaultyCache` around `inner`.
+    #[must_use]
+    pub fn builder(inner: S) -> FaultyCacheBuilder<S> {
+        FaultyCacheBuilder {
+            inner,
+            error: Arc::new(|| InjectedFault),
+            fail_rate: 0.0,
+            drop_write_rate: 0.0,
+            stale_rate: 0.0,
+            latency: Duration::ZERO,
+            latency_jitter: Duration::ZERO,
+            seed: 0,
+        }
+    }
+}
+
+impl<S, E> FaultyCacheBuilder<S, E> {
+    /// Fraction of operations, from `0.0` to `1.0`, that fail with the supplied error.
+    #[must_use]
+    pub fn fail_rate(mut self, rate: f64) -> Self {
+        self.fail_rate = rate;
+        self
+    }
+
+    /// Build the error injected failures return. Defaults to [`InjectedFault`]; any other type
+    /// must also convert from the wrapped store's error.
+    #[must_use]
+    pub fn error<E2>(
+        self,
+        error: impl Fn() -> E2 + Send + Sync + 'static,
+    ) -> FaultyCacheBuilder<S, E2> {
+        FaultyCacheBuilder {
+            inner: self.inner,
+            error: Arc::new(error),
+            fail_rate: self.fail_rate,
+            drop_write_rate: self.drop_write_rate,
+            stale_rate: self.stale_rate,
+            latency: self.latency,
+            latency_jitter: self.latency_jitter,
+            seed: self.seed,
+        }
+    }
+
+    /// Fraction of `cache_set` calls, from `0.0` to `1.0`, that report success without writing.
+    #[must_use]
+    pub fn drop_write_rate(mut self, rate: f64) -> Self {
+        self.drop_write_rate = rate;
+        self
+    }
+
+    /// Fraction of `cache_get` calls, from `0.0` to `1.0`, that return the key's previous value
+    /// when it has one. Keeping previous values costs memory per key written, until the wrapper
+    /// is dropped.
+    #[must_use]
+    pub fn stale_rate(mut self, rate: f64) -> Self {
+        self.stale_rate = rate;
+        self
+    }
+
+    /// Delay added to every operation. The sync methods block the calling thread; the async
+    /// ones wait on a timer thread, so they work under any executor.
+    #[must_use]
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Random extra delay, uniform between zero and `jitter`, added on top of
+    /// [`latency`](Self::latency).
+    #[must_use]
+    pub fn latency_jitter(mut self, jitter: Duration) -> Self {
+        self.latency_jitter = jitter;
+        self
+    }
+
+    /// Seed for the fault decisions. Defaults to `0`.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Build the wrapper.
+    ///
+    /// # Errors
+    ///
+    /// `BuildError::InvalidValue` if a rate is not between `0.0` and `1.0`.
+    pub fn build(self) -> Result<FaultyCache<S, E>, BuildError> {
+        for (field, rate) in [
+            ("fail_rate", self.fail_rate),
+            ("drop_write_rate", self.drop_write_rate),
+            ("stale_rate", self.stale_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(BuildError::InvalidValue {
+                    field,
+                    reason: "rate must be between 0.0 and 1.0",
+                });
+            }
+        }
+        Ok(FaultyCache {
+            inner: self.inner,
+            error: self.error,
+            fail_rate: self.fail_rate,
+            drop_write_rate: self.drop_write_rate,
+            stale_rate: self.stale_rate,
+            latency: self.latency,
+            latency_jitter: self.latency_jitter,
+            enabled: AtomicBool::new(true),
+            rng: Mutex::new(self.seed),
+            stale: Mutex::new(None),
+        })
+    }
+}
+
+impl<S, E> FaultyCache<S, E> {
+    /// The wrapped store, for setup and assertions that must not be faulted.
+    #[must_use]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwrap the store.
+    #[must_use]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Turn fault injection on or off, returning the previous setting. While off, operations
+    /// go straight to the wrapped store and draw nothing from the generator; previous values
+    /// are still recorded for [`stale_rate`](FaultyCacheBuilder::stale_rate).
+    pub fn set_enabled(&self, enabled: bool) -> bool {
+        self.enabled.swap(enabled, Ordering::Relaxed)
+    }
+
+    /// Whether fault injection is on. It is on when built.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// SplitMix64: a uniform draw in `[0, 1)`.
+    fn draw(&self) -> f64 {
+        let mut state = self.rng.lock();
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Whether a fault with probability `rate` fires now.
+    fn roll(&self, rate: f64) -> bool {
+        rate > 0.0 && self.is_enabled() && self.draw() < rate
+    }
+
+    /// The delay for one operation: zero while disabled.
+    fn delay(&self) -> Duration {
+        if !self.is_enabled() {
+            return Duration::ZERO;
+        }
+        if self.latency_jitter.is_zero() {
+            return self.latency;
+        }
+        self.latency + self.latency_jitter.mul_f64(self.draw())
+    }
+
+    /// The injected error, if this operation fails.
+    fn fail(&self) -> Result<(), E> {
+        if self.roll(self.fail_rate) {
+            Err((self.error)())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn with_stale<K, V, R>(&self, f: impl FnOnce(&mut HashMap<K, V>) -> R) -> Option<R>
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+    {
+        let mut stale = self.stale.lock();
+        stale
+            .get_or_insert_with(|| Box::new(HashMap::<K, V>::new()))
+            .downcast_mut::<HashMap<K, V>>()
+            .map(f)
+    }
+
+    /// Remember `old` as `key`'s previous value.
+    fn record<K, V>(&self, key: &K, old: Option<&V>)
+    where
+        K: Hash + Eq + Clone + Send + 'static,
+        V: Clone + Send + 'static,
+    {
+        if let Some(old) = old.filter(|_| self.stale_rate > 0.0) {
+            self.with_stale(|stale: &mut HashMap<K, V>| stale.insert(key.clone(), old.clone()));
+        }
+    }
+
+    /// The stale value to serve for `key`, if this `cache_get` serves one.
+    fn stale_value<K, V>(&self, key: &K) -> Option<V>
+    where
+        K: Hash + Eq + Send + 'static,
+        V: Clone + Send + 'static,
+    {
+        if !self.roll(self.stale_rate) {
+            return None;
+        }
+        self.with_stale(|stale: &mut HashMap<K, V>| stale.get(key).cloned())
+            .flatten()
+    }
+}
+
+impl<S, E> std::fmt::Debug for FaultyCache<S, E>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FaultyCache")
+            .field("inner", &self.inner)
+            .field("fail_rate", &self.fail_rate)
+            .field("drop_write_rate", &self.drop_write_rate)
+            .field("stale_rate", &self.stale_rate)
+            .field("latency", &self.latency)
+            .field("latency_jitter", &self.latency_jitter)
+            .field("enabled", &self.is_enabled())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, E> ConcurrentCacheBase for FaultyCache<S, E>
+where
+    S: ConcurrentCacheBase,
+    E: From<S::Error> + std::error::Error + Send + Sync + 'static,
+{
+    type Error = E;
+
+    fn cache_size(&self) -> Result<Option<usize>, E> {
+        self.inner.cache_size().map_err(E::from)
+    }
+
+    fn cache_hits(&self) -> Option<u64> {
+        self.inner.cache_hits()
+    }
+
+    fn cache_misses(&self) -> Option<u64> {
+        self.inner.cache_misses()
+    }
+
+    fn cache_capacity(&self) -> Option<usize> {
+        self.inner.cache_capacity()
+    }
+
+    fn cache_evictions(&self) -> Option<u64> {
+        self.inner.cache_evictions()
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        self.inner.cache_memory_usage()
+    }
+
+    fn metrics(&self) -> CacheMetrics {
+        self.inner.metrics()
+    }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.cache_metrics_exporter()
+    }
+}
+
+impl<S, E, K, V> ConcurrentCached<K, V> for FaultyCache<S, E>
+where
+    S: ConcurrentCached<K, V>,
+    E: From<S::Error> + std::error::Error + Send + Sync + 'static,
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    fn cache_get(&self, k: &K) -> Result<Option<V>, E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        if let Some(stale) = self.stale_value(k) {
+            return Ok(Some(stale));
+        }
+        Ok(self.inner.cache_get(k)?)
+    }
+
+    fn cache_set(&self, k: K, v: V) -> Result<Option<V>, E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        if self.roll(self.drop_write_rate) {
+            return Ok(None);
+        }
+        let key = (self.stale_rate > 0.0).then(|| k.clone());
+        let old = self.inner.cache_set(k, v)?;
+        if let Some(key) = key {
+            self.record(&key, old.as_ref());
+        }
+        Ok(old)
+    }
+
+    fn cache_remove(&self, k: &K) -> Result<Option<V>, E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        let old = self.inner.cache_remove(k)?;
+        self.record(k, old.as_ref());
+        Ok(old)
+    }
+
+    fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        let old = self.inner.cache_remove_entry(k)?;
+        self.record(k, old.as_ref().map(|(_, v)| v));
+        Ok(old)
+    }
+
+    fn cache_delete(&self, k: &K) -> Result<bool, E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        Ok(self.inner.cache_delete(k)?)
+    }
+
+    fn cache_contains(&self, k: &K) -> Result<bool, E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        Ok(self.inner.cache_contains(k)?)
+    }
+
+    fn cache_clear(&self) -> Result<(), E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        Ok(self.inner.cache_clear()?)
+    }
+
+    fn cache_reset(&self) -> Result<(), E> {
+        std::thread::sleep(self.delay());
+        self.fail()?;
+        Ok(self.inner.cache_reset()?)
+    }
+
+    fn cache_reset_metrics(&self) -> Result<(), E> {
+        Ok(self.inner.cache_reset_metrics()?)
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<HotKey<K>>> {
+        self.inner.cache_top_keys(n)
+    }
+}
+
+/// Whether a [`Delay`] has elapsed, and the task to wake when it does.
+#[cfg(feature = "async_core")]
+type Fired = Arc<Mutex<(bool, Option<std::task::Waker>)>>;
+
+/// A runtime-agnostic timer: the first poll starts a thread that sleeps, then wakes the task.
+#[cfg(feature = "async_core")]
+struct Delay {
+    duration: Duration,
+    fired: Option<Fired>,
+}
+
+#[cfg(feature = "async_core")]
+impl std::future::Future for Delay {
+    type Output = ();
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<()> {
+        use std::task::Poll;
+        if self.duration.is_zero() {
+            return Poll::Ready(());
+        }
+        if let Some(fired) = &self.fired {
+            let mut fired = fired.lock();
+            if fired.0 {
+                return Poll::Ready(());
+            }
+            fired.1 = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let fired: Fired = Arc::new(Mutex::new((false, Some(cx.waker().clone()))));
+        let timer = Arc::clone(&fired);
+        let duration = self.duration;
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            let mut fired = timer.lock();
+            fired.0 = true;
+            if let Some(waker) = fired.1.take() {
+                waker.wake();
+            }
+        });
+        self.fired = Some(fired);
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "async_core")]
+impl<S, E> FaultyCache<S, E> {
+    fn sleep(&self) -> Delay {
+        Delay {
+            duration: self.delay(),
+            fired: None,
+        }
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<S, E, K, V> ConcurrentCachedAsync<K, V> for FaultyCache<S, E>
+where
+    S: ConcurrentCachedAsync<K, V> + Sync,
+    E: From<S::Error> + std::error::Error + Send + Sync + 'static,
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Clone + Send + 'static,
+{
+    async fn async_cache_get(&self, k: &K) -> Result<Option<V>, E> {
+        self.sleep().await;
+        self.fail()?;
+        if let Some(stale) = self.stale_value(k) {
+            return Ok(Some(stale));
+        }
+        Ok(self.inner.async_cache_get(k).await?)
+    }
+
+    async fn async_cache_set(&self, k: K, v: V) -> Result<Option<V>, E> {
+        self.sleep().await;
+        self.fail()?;
+        if self.roll(self.drop_write_rate) {
+            return Ok(None);
+        }
+        let key = (self.stale_rate > 0.0).then(|| k.clone());
+        let old = self.inner.async_cache_set(k, v).await?;
+        if let Some(key) = key {
+            self.record(&key, old.as_ref());
+        }
+        Ok(old)
+    }
+
+    async fn async_cache_remove(&self, k: &K) -> Result<Option<V>, E> {
+        self.sleep().await;
+        self.fail()?;
+        let old = self.inner.async_cache_remove(k).await?;
+        self.record(k, old.as_ref());
+        Ok(old)
+    }
+
+    async fn async_cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, E> {
+        self.sleep().await;
+        self.fail()?;
+        let old = self.inner.async_cache_remove_entry(k).await?;
+        self.record(k, old.as_ref().map(|(_, v)| v));
+        Ok(old)
+    }
+
+    async fn async_cache_delete(&self, k: &K) -> Result<bool, E> {
+        self.sleep().await;
+        self.fail()?;
+        Ok(self.inner.async_cache_delete(k).await?)
+    }
+
+    async fn async_cache_contains(&self, k: &K) -> Result<bool, E> {
+        self.sleep().await;
+        self.fail()?;
+        Ok(self.inner.async_cache_contains(k).await?)
+    }
+
+    async fn async_cache_clear(&self) -> Result<(), E> {
+        self.sleep().await;
+        self.fail()?;
+        Ok(self.inner.async_cache_clear().await?)
+    }
+
+    async fn async_cache_reset(&self) -> Result<(), E> {
+        self.sleep().await;
+        self.fail()?;
+        Ok(self.inner.async_cache_reset().await?)
+    }
+
+    async fn async_cache_reset_metrics(&self) -> Result<(), E> {
+        Ok(self.inner.async_cache_reset_metrics().await?)
+    }
+}
@@ -0,0 +1,291 @@
+//! Approximate heavy-hitter tracking for the in-memory stores (`hot_keys` on the builders).
+//!
+//! Each tracked store keeps a Space-Saving summary: a fixed number of slots, each holding a key
+//! hash, its estimated lookup count and the most that estimate can overcount by. A lookup of a
+//! tracked hash bumps its slot; a lookup of an untracked hash takes over the slot with the
+//! lowest count and inherits that count as its error. Any key looked up more than
+//! `lookups / capacity` times is guaranteed to hold a slot, and its count is never lower than
+//! its true count.
+//!
+//! The sharded stores keep one summary per shard they were built with, each under its own lock,
+//! so tracking does not add a store-wide lock to `cache_get`. A summary is picked from the key's
+//! shard hash the same way the build-time shard was, and a `reshard` does not change that, so
+//! the summaries cover disjoint keys and merging them is a plain union ordered by count.
+
+use super::{BuildError, DefaultHashBuilder};
+use parking_lot::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+/// One entry of a store's hot-key summary, as returned by
+/// [`CachedExt::top_keys`](crate::CachedExt::top_keys) and
+/// [`ConcurrentCachedExt::top_keys`](crate::ConcurrentCachedExt::top_keys).
+///
+/// Counts cover the lookups since the store was built or its metrics were last reset: `count`
+/// never undercounts them and `count - error` never overcounts them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HotKey<K> {
+    /// The key's hash: the shard hash on a sharded store, otherwise one the summary computes
+    /// itself. Stable for the life of the store, so it identifies a key across calls even
+    /// when [`key`](Self::key) is `None`.
+    pub hash: u64,
+    /// A clone of the key. `None` for a store built with `hot_key_hashes`, or when the key
+    /// has not been seen in the store since it took its slot.
+    pub key: Option<K>,
+    /// Estimated number of lookups; never below the true count.
+    pub count: u64,
+    /// The most `count` can overestimate by.
+    pub error: u64,
+}
+
+impl<K> HotKey<K> {
+    /// The number of lookups this key is certain to have had (`count - error`).
+    #[must_use]
+    pub fn guaranteed_count(&self) -> u64 {
+        self.count - self.error
+    }
+}
+
+/// What a builder's `hot_keys` / `hot_key_hashes` asked for; turned into [`HotKeys`] by `build`.
+pub(crate) struct HotKeyConfig<K> {
+    capacity: usize,
+    clone_key: Option<fn(&K) -> K>,
+}
+
+impl<K> HotKeyConfig<K> {
+    /// Track hashes only; works for any key type.
+    pub(super) fn hashes(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clone_key: None,
+        }
+    }
+
+    /// Track hashes and keep a clone of each tracked key.
+    pub(super) fn keys(capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        Self {
+            capacity,
+            clone_key: Some(K::clone),
+        }
+    }
+}
+
+/// A store's hot-key summaries: one per shard, or a single one for a single-owner store.
+pub(crate) struct HotKeys<K> {
+    sketches: Box<[Mutex<SpaceSaving<K>>]>,
+    clone_key: Option<fn(&K) -> K>,
+    hasher: DefaultHashBuilder,
+}
+
+impl<K> HotKeys<K> {
+    /// Build the summaries for a store with `shards` shards, or `None` when the builder did not
+    /// enable tracking.
+    pub(super) fn build(
+        config: Option<HotKeyConfig<K>>,
+        shards: usize,
+    ) -> Result<Option<Self>, BuildError> {
+        let Some(config) = config else {
+            return Ok(None);
+        };
+        if config.capacity == 0 {
+            return Err(BuildError::InvalidValue {
+                field: "hot_keys",
+                reason: "must be greater than zero",
+            });
+        }
+        Ok(Some(Self {
+            sketches: (0..shards)
+                .map(|_| Mutex::new(SpaceSaving::new(config.capacity)))
+                .collect(),
+            clone_key: config.clone_key,
+            hasher: super::new_default_hash_builder(),
+        }))
+    }
+
+    /// Hash a key for a single-owner store. The sharded stores pass their shard hash instead.
+    pub(super) fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        BuildHasher::hash_one(&self.hasher, key)
+    }
+
+    /// Count one lookup of `hash`. `stored_key` yields the store's own copy of the key and is
+    /// only called when the key's slot does not hold a clone yet.
+    pub(super) fn record<'a>(&self, hash: u64, stored_key: impl FnOnce() -> Option<&'a K>)
+    where
+        K: 'a,
+    {
+        let sketch = super::sharded::shard_index(hash, self.sketches.len() - 1);
+        let mut sketch = self.sketches[sketch].lock();
+        let slot = sketch.record(hash);
+        if let Some(clone_key) = self.clone_key
+            && slot.key.is_none()
+        {
+            slot.key = stored_key().map(clone_key);
+        }
+    }
+
+    /// The `n` keys with the highest counts across every shard, highest first.
+    pub(super) fn top(&self, n: usize) -> Vec<HotKey<K>> {
+        let mut merged: Vec<HotKey<K>> = Vec::new();
+        for sketch in self.sketches.iter() {
+            let sketch = sketch.lock();
+            merged.extend(sketch.slots.iter().map(|slot| HotKey {
+                hash: slot.hash,
+                key: slot.key.as_ref().zip(self.clone_key).map(|(k, f)| f(k)),
+                count: slot.count,
+                error: slot.error,
+            }));
+        }
+        merged.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(a.hash.cmp(&b.hash)));
+        merged.truncate(n);
+        merged
+    }
+
+    /// Forget every tracked key, as `cache_reset_metrics` does for the hit/miss counters.
+    pub(super) fn reset(&self) {
+        for sketch in self.sketches.iter() {
+            sketch.lock().clear();
+        }
+    }
+}
+
+impl<K> Clone for HotKeys<K> {
+    fn clone(&self) -> Self {
+        Self {
+            sketches: self
+                .sketches
+                .iter()
+                .map(|sketch| Mutex::new(sketch.lock().copy(self.clone_key)))
+                .collect(),
+            clone_key: self.clone_key,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+struct Slot<K> {
+    hash: u64,
+    key: Option<K>,
+    count: u64,
+    error: u64,
+}
+
+/// A Space-Saving summary over key hashes.
+struct SpaceSaving<K> {
+    capacity: usize,
+    slots: Vec<Slot<K>>,
+    /// Slot index by hash.
+    index: HashMap<u64, usize>,
+    /// `(count, slot index)` for every slot, so the slot to replace is the first element.
+    by_count: BTreeSet<(u64, usize)>,
+}
+
+impl<K> SpaceSaving<K> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            slots: Vec::new(),
+            index: HashMap::new(),
+            by_count: BTreeSet::new(),
+        }
+    }
+
+    fn record(&mut self, hash: u64) -> &mut Slot<K> {
+        let i = if let Some(&i) = self.index.get(&hash) {
+            let slot = &mut self.slots[i];
+            self.by_count.remove(&(slot.count, i));
+            slot.count += 1;
+            self.by_count.insert((slot.count, i));
+            i
+        } else if self.slots.len() < self.capacity {
+            let i = self.slots.len();
+            self.slots.push(Slot {
+                hash,
+                key: None,
+                count: 1,
+                error: 0,
+            });
+            self.index.insert(hash, i);
+            self.by_count.insert((1, i));
+            i
+        } else {
+            let (min, i) = self
+                .by_count
+                .pop_first()
+                .expect("a full summary has at least one slot");
+            self.index.remove(&self.slots[i].hash);
+            self.slots[i] = Slot {
+                hash,
+                key: None,
+                count: min + 1,
+                error: min,
+            };
+            self.index.insert(hash, i);
+            self.by_count.insert((min + 1, i));
+            i
+        };
+        &mut self.slots[i]
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+        self.index.clear();
+        self.by_count.clear();
+    }
+
+    fn copy(&self, clone_key: Option<fn(&K) -> K>) -> Self {
+        Self {
+            capacity: self.capacity,
+            slots: self
+                .slots
+                .iter()
+                .map(|slot| Slot {
+                    hash: slot.hash,
+                    key: slot.key.as_ref().zip(clone_key).map(|(k, f)| f(k)),
+                    count: slot.count,
+                    error: slot.error,
+                })
+                .collect(),
+            index: self.index.clone(),
+            by_count: self.by_count.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_new_hash_takes_the_lowest_slot_and_inherits_its_count_as_error() {
+        let mut sketch: SpaceSaving<()> = SpaceSaving::new(2);
+        for hash in [1, 1, 1, 2, 2] {
+            sketch.record(hash);
+        }
+        let slot = sketch.record(3);
+        assert_eq!((slot.hash, slot.count, slot.error), (3, 3, 2));
+        assert_eq!(sketch.index.len(), 2);
+        assert!(!sketch.index.contains_key(&2));
+    }
+
+    #[test]
+    fn counts_stay_consistent_through_replacements() {
+        let mut sketch: SpaceSaving<()> = SpaceSaving::new(3);
+        for i in 0..1_000u64 {
+            sketch.record(i % 7);
+            sketch.record(100);
+        }
+        let total: u64 = sketch.slots.iter().map(|s| s.count).sum();
+        assert_eq!(total, 2_000, "every lookup is counted exactly once");
+        assert_eq!(sketch.by_count.len(), sketch.slots.len());
+        for (i, slot) in sketch.slots.iter().enumerate() {
+            assert_eq!(sketch.index[&slot.hash], i);
+            assert!(sketch.by_count.contains(&(slot.count, i)));
+        }
+        let heavy = &sketch.slots[sketch.index[&100]];
+        assert!(heavy.count >= 1_000 && heavy.count - heavy.error <= 1_000);
+    }
+}
@@ -0,0 +1,963 @@
+use super::{Cached, DefaultHashBuilder};
+use crate::lru_list::LRUList;
+use crate::{CachedIter, CachedPeek};
+use hashbrown::HashTable;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+#[cfg(feature = "async_core")]
+use {super::CachedGetOrSetAsync, std::future::Future};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Clone)]
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    freq: u64,
+}
+
+/// Least Frequently Used Cache
+///
+/// Stores up to `max_size` entries and evicts the one used least often, the oldest of those
+/// on a tie. Every write and hit counts as a use; `cache_peek`, `cache_contains` and
+/// [`frequency`](LfuCache::frequency) do not. Eviction, lookups and count updates are O(1):
+/// entries sit on one list ordered by count, and a table of frequency buckets points at the
+/// newest entry of each count so an entry moves to its next bucket in one relink.
+///
+/// Counts only grow, so a key that was popular once can outstay keys that are popular now.
+/// [`LfuCacheBuilder::halve_every`] sets a number of uses after which every count is halved,
+/// letting old popularity decay; [`halve_frequencies`](LfuCache::halve_frequencies) does the
+/// same on demand. Halving keeps the relative order of entries.
+///
+/// Eviction, `on_evict` and the counters follow [`LruCache`](crate::LruCache).
+///
+/// Note: This cache is in-memory only
+///
+/// The optional type parameter `S` selects the hash builder, as on `LruCache`. Supply a custom
+/// `S` via [`LfuCacheBuilder::hasher`].
+///
+/// # Example
+///
+/// ```rust
+/// use cached::{Cached, LfuCache};
+///
+/// let mut cache = LfuCache::new(2);
+/// cache.cache_set("popular", 1);
+/// cache.cache_get(&"popular");
+/// cache.cache_set("once", 2);
+/// cache.cache_set("new", 3); // evicts "once", the least used
+/// assert_eq!(cache.frequency(&"popular"), Some(2));
+/// assert_eq!(cache.cache_get(&"once"), None);
+/// ```
+pub struct LfuCache<K, V, S = DefaultHashBuilder> {
+    // `store` maps a hash of K -> index of the entry in `order`
+    store: HashTable<usize>,
+    hash_builder: S,
+    /// Entries from the most to the least frequently used; within one count, newest first.
+    order: LRUList<Entry<K, V>>,
+    /// Frequency buckets: each count held by some entry -> index of its newest entry.
+    buckets: HashMap<u64, usize>,
+    capacity: usize,
+    halve_every: Option<u64>,
+    /// Uses recorded since the counts were last halved.
+    uses: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// When false, lookups skip incrementing `hits` and `misses`. The sharded store keeps its
+    /// own per-shard counters.
+    pub(crate) track_hit_miss: bool,
+    tags: super::TagIndex<K>,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeys<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
+}
+
+impl<K, V, S> Clone for LfuCache<K, V, S>
+where
+    K: Clone + Hash + Eq,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            hash_builder: self.hash_builder.clone(),
+            order: self.order.clone(),
+            buckets: self.buckets.clone(),
+            capacity: self.capacity,
+            halve_every: self.halve_every,
+            uses: self.uses,
+            hits: AtomicU64::new(self.hits.load(Ordering::Relaxed)),
+            misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
+            evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
+            on_evict: self.on_evict.clone(),
+            track_hit_miss: self.track_hit_miss,
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for LfuCache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LfuCache")
+            .field("capacity", &self.capacity)
+            .field("size", &self.store.len())
+            .field("buckets", &self.buckets.len())
+            .field("halve_every", &self.halve_every)
+            .field("hits", &self.hits.load(Ordering::Relaxed))
+            .field("misses", &self.misses.load(Ordering::Relaxed))
+            .field("evictions", &self.evictions.load(Ordering::Relaxed))
+            .field("on_evict", &self.on_evict.as_ref().map(|_| "on_evict"))
+            .finish()
+    }
+}
+
+/// Builder for [`LfuCache`].
+pub struct LfuCacheBuilder<K, V, S = DefaultHashBuilder> {
+    size: Option<usize>,
+    halve_every: Option<u64>,
+    on_evict: Option<super::OnEvict<K, V>>,
+    hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
+}
+
+impl<K, V> Default for LfuCacheBuilder<K, V, DefaultHashBuilder> {
+    fn default() -> Self {
+        Self {
+            size: None,
+            halve_every: None,
+            on_evict: None,
+            hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+        }
+    }
+}
+
+impl<K, V> LfuCacheBuilder<K, V> {
+    /// Create a builder with default settings. Equivalent to [`LfuCache::builder`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, S> LfuCacheBuilder<K, V, S> {
+    /// Set the maximum number of entries. Required -- `build` returns `Err` if not set.
+    #[doc(alias = "size")]
+    #[doc(alias = "capacity")]
+    #[must_use]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.size = Some(max_size);
+        self
+    }
+
+    /// Halve every entry's use count after each `uses` writes and hits, so that popularity
+    /// decays. Unset by default: counts only grow.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `uses` is zero.
+    #[must_use]
+    pub fn halve_every(mut self, uses: u64) -> Self {
+        self.halve_every = Some(uses);
+        self
+    }
+
+    /// Set a callback to be invoked when an entry is evicted or removed.
+    ///
+    /// Use [`cache_clear_with_on_evict`](LfuCache::cache_clear_with_on_evict)
+    /// instead of [`cache_clear`](crate::Cached::cache_clear) to opt into callback
+    /// firing and eviction counter increments when clearing all entries.
+    #[must_use]
+    pub fn on_evict(mut self, on_evict: impl Fn(&K, &V) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(on_evict));
+        self
+    }
+
+    /// Switch to a custom hash builder `S2`, returning a builder parameterized on `S2`.
+    #[doc(alias = "with_hasher")]
+    #[must_use]
+    pub fn hasher<S2: BuildHasher>(self, hasher: S2) -> LfuCacheBuilder<K, V, S2> {
+        LfuCacheBuilder {
+            size: self.size,
+            halve_every: self.halve_every,
+            on_evict: self.on_evict,
+            hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
+        }
+    }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
+    /// Build the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError::MissingRequired`](super::BuildError::MissingRequired) if `max_size`
+    /// was not set, or [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `max_size` or `halve_every` is `0`, or capacity pre-allocation fails.
+    pub fn build(self) -> Result<LfuCache<K, V, S>, super::BuildError>
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let size = self
+            .size
+            .ok_or(super::BuildError::MissingRequired("max_size"))?;
+        if size == 0 {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "must be greater than zero",
+            });
+        }
+        if self.halve_every == Some(0) {
+            return Err(super::BuildError::InvalidValue {
+                field: "halve_every",
+                reason: "must be greater than zero",
+            });
+        }
+        let mut store = HashTable::new();
+        if store.try_reserve(size, |_: &usize| 0).is_err() {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "allocation failed",
+            });
+        }
+        Ok(LfuCache {
+            store,
+            hash_builder: self.hasher,
+            order: LRUList::try_with_capacity(size)?,
+            buckets: HashMap::new(),
+            capacity: size,
+            halve_every: self.halve_every,
+            uses: 0,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            on_evict: self.on_evict,
+            track_hit_miss: true,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
+        })
+    }
+}
+
+impl<K: Hash + Eq, V> LfuCache<K, V> {
+    /// Construct a ready-to-use [`LfuCache`] holding up to `max_size` entries, without count
+    /// halving.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is `0`, or if pre-allocating the backing store fails. Use
+    /// [`builder`](Self::builder) with [`build`](LfuCacheBuilder::build) to handle those cases
+    /// without panicking.
+    #[must_use]
+    pub fn new(max_size: usize) -> Self {
+        Self::builder()
+            .max_size(max_size)
+            .build()
+            .expect("LfuCache::new requires a non-zero max_size with a valid allocation")
+    }
+
+    /// Return a builder for constructing an [`LfuCache`].
+    #[must_use]
+    pub fn builder() -> LfuCacheBuilder<K, V> {
+        LfuCacheBuilder::default()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> LfuCache<K, V, S> {
+    /// Returns the maximum number of entries this cache will hold before evicting.
+    #[doc(alias = "size")]
+    #[doc(alias = "max_size")]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The use count of `k`, or `None` if it is not cached. Counts one for the write that
+    /// inserted it, plus one per hit and overwrite since, less any halving. Does not itself
+    /// count as a use.
+    #[must_use]
+    pub fn frequency<Q>(&self, k: &Q) -> Option<u64>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(k), k)?;
+        Some(self.order.get(index).freq)
+    }
+
+    /// Halve every entry's use count now (rounding down), as
+    /// [`halve_every`](LfuCacheBuilder::halve_every) does periodically. O(n) in the number of
+    /// entries; the eviction order is unchanged.
+    pub fn halve_frequencies(&mut self) {
+        let indices: Vec<usize> = self.order.iter_indices().collect();
+        self.buckets.clear();
+        for index in indices {
+            let entry = self.order.get_mut(index);
+            entry.freq /= 2;
+            // `order` runs newest first within a count, so the first index seen is the head.
+            self.buckets.entry(entry.freq).or_insert(index);
+        }
+        self.uses = 0;
+    }
+
+    /// Change the maximum number of entries, returning the previous bound as
+    /// `Some(prev_capacity)`, like [`LruCache::set_max_size`](crate::LruCache::set_max_size).
+    /// Shrinking evicts the least frequently used entries, firing `on_evict`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0. Use [`try_set_max_size`](LfuCache::try_set_max_size)
+    /// to validate first and avoid the panic.
+    pub fn set_max_size(&mut self, max_size: usize) -> Option<usize> {
+        assert!(max_size > 0, "max_size must be greater than zero");
+        let prev = self.capacity;
+        self.capacity = max_size;
+        while self.store.len() > max_size {
+            self.evict_lfu();
+        }
+        Some(prev)
+    }
+
+    /// Fallible counterpart of [`set_max_size`](LfuCache::set_max_size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SetMaxSizeError::ZeroMaxSize`](super::SetMaxSizeError) if `max_size` is 0.
+    pub fn try_set_max_size(
+        &mut self,
+        max_size: usize,
+    ) -> Result<Option<usize>, super::SetMaxSizeError> {
+        if max_size == 0 {
+            return Err(super::SetMaxSizeError::ZeroMaxSize);
+        }
+        Ok(self.set_max_size(max_size))
+    }
+
+    /// Removes entries for which `keep` returns `false`, firing `on_evict` and counting an
+    /// eviction for each, like [`LruCache::retain`](crate::LruCache::retain). Returns the
+    /// number of entries removed.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&mut self, mut keep: F) -> usize {
+        let order = &self.order;
+        let doomed: Vec<usize> = order
+            .iter_indices()
+            .filter(|&index| {
+                let entry = order.get(index);
+                !keep(&entry.key, &entry.value)
+            })
+            .collect();
+        let removed = doomed.len();
+        for index in doomed {
+            let (key, value) = self.remove_index(index);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(&key, &value);
+            }
+        }
+        removed
+    }
+
+    /// Remove all entries and fire the `on_evict` callback for each one, incrementing the
+    /// evictions counter.
+    pub fn cache_clear_with_on_evict(&mut self) {
+        let removed = self.drain_all();
+        if !removed.is_empty() {
+            self.evictions
+                .fetch_add(removed.len() as u64, Ordering::Relaxed);
+        }
+        if let Some(on_evict) = &self.on_evict {
+            for (k, v) in &removed {
+                on_evict(k, v);
+            }
+        }
+    }
+
+    /// Remove every entry, returning the stored pairs. Fires no callback and touches no
+    /// counters.
+    pub(super) fn drain_all(&mut self) -> Vec<(K, V)> {
+        let mut entries = Vec::with_capacity(self.store.len());
+        self.order.drain_into(&mut entries);
+        self.store.clear();
+        self.buckets.clear();
+        self.uses = 0;
+        entries.into_iter().map(|e| (e.key, e.value)).collect()
+    }
+
+    /// Remove `k` if present, returning the stored pair. Fires no callback and touches no
+    /// counters.
+    pub(super) fn pop_raw<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(k), k)?;
+        Some(self.remove_index(index))
+    }
+
+    /// The entries, most frequently used first. Used by the sharded store's `retain`.
+    pub(super) fn iter_raw(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.order.iter().map(|e| (&e.key, &e.value))
+    }
+
+    pub(super) fn add_evictions(&self, n: u64) {
+        self.evictions.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Bytes allocated for the hash table, the entry list and the bucket table, by capacity.
+    pub(super) fn allocated_bytes(&self) -> usize {
+        self.store.allocation_size()
+            + self.order.allocation_size()
+            + self.buckets.capacity() * size_of::<(u64, usize)>()
+    }
+
+    /// Sum `heap` over every entry.
+    pub(super) fn entries_heap_size(&self, heap: impl Fn(&K, &V) -> usize) -> usize {
+        self.iter_raw().map(|(k, v)| heap(k, v)).sum()
+    }
+
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.hash_builder.hash_one(key)
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store
+            .find(hash, |&index| key == self.order.get(index).key.borrow())
+            .copied()
+    }
+
+    /// Take `index` out of its frequency bucket, handing the bucket to the next older entry
+    /// of the same count if there is one.
+    fn leave_bucket(&mut self, index: usize) {
+        let freq = self.order.get(index).freq;
+        if self.buckets.get(&freq) != Some(&index) {
+            return;
+        }
+        match self
+            .order
+            .next_of(index)
+            .filter(|&older| self.order.get(older).freq == freq)
+        {
+            Some(older) => {
+                self.buckets.insert(freq, older);
+            }
+            None => {
+                self.buckets.remove(&freq);
+            }
+        }
+    }
+
+    /// Count a use of the entry at `index`: move it to the front of the next bucket.
+    fn bump(&mut self, index: usize) {
+        let freq = self.order.get(index).freq;
+        if let Some(next) = freq.checked_add(1) {
+            self.leave_bucket(index);
+            // In front of the next bucket's newest entry; failing that, in front of what is
+            // left of its own bucket, which borders the next-higher bucket.
+            if let Some(&head) = self.buckets.get(&next).or(self.buckets.get(&freq)) {
+                self.order.move_before(index, head);
+            }
+            self.order.get_mut(index).freq = next;
+            self.buckets.insert(next, index);
+        }
+        self.record_use();
+    }
+
+    fn record_use(&mut self) {
+        if let Some(every) = self.halve_every {
+            self.uses += 1;
+            if self.uses >= every {
+                self.halve_frequencies();
+            }
+        }
+    }
+
+    fn remove_index(&mut self, index: usize) -> (K, V) {
+        self.leave_bucket(index);
+        let hash = self.hash(&self.order.get(index).key);
+        match self.store.find_entry(hash, |&i| i == index) {
+            Ok(entry) => {
+                entry.remove();
+            }
+            Err(_) => {
+                unreachable!("LfuCache internal invariant violated: list and table out of sync")
+            }
+        }
+        let entry = self.order.remove(index);
+        (entry.key, entry.value)
+    }
+
+    /// Evict the least frequently used entry, the oldest among equals.
+    fn evict_lfu(&mut self) {
+        let (key, value) = self.remove_index(self.order.back());
+        self.evictions.fetch_add(1, Ordering::Relaxed);
+        if let Some(on_evict) = &self.on_evict {
+            on_evict(&key, &value);
+        }
+    }
+
+    /// Insert or overwrite `key`, returning the displaced value and the entry's index.
+    fn put(&mut self, hash: u64, key: K, val: V) -> (Option<V>, usize) {
+        if let Some(index) = self.find(hash, &key) {
+            let displaced = std::mem::replace(&mut self.order.get_mut(index).value, val);
+            self.bump(index);
+            return (Some(displaced), index);
+        }
+        if self.store.len() >= self.capacity {
+            self.evict_lfu();
+        }
+        let index = self.order.push_front(Entry {
+            key,
+            value: val,
+            freq: 1,
+        });
+        // Counts below 1 only exist after halving; a new entry ranks above them.
+        match self.buckets.get(&1).or(self.buckets.get(&0)) {
+            Some(&head) => self.order.move_before(index, head),
+            None => self.order.move_to_back(index),
+        }
+        self.buckets.insert(1, index);
+        let order = &self.order;
+        let hash_builder = &self.hash_builder;
+        self.store
+            .insert_unique(hash, index, |&i| hash_builder.hash_one(&order.get(i).key));
+        self.record_use();
+        (None, index)
+    }
+
+    /// The index of `key`'s entry, counting a use and a hit or a miss.
+    fn lookup<Q>(&mut self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key);
+        if self.track_hit_miss {
+            let counter = if index.is_some() {
+                &self.hits
+            } else {
+                &self.misses
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(index) = index {
+            self.bump(index);
+        }
+        index
+    }
+
+    fn get_or_set_index<F: FnOnce() -> V>(&mut self, key: K, f: F) -> usize {
+        match self.lookup(&key) {
+            Some(index) => index,
+            None => {
+                let hash = self.hash(&key);
+                self.put(hash, key, f()).1
+            }
+        }
+    }
+
+    fn try_get_or_set_index<E, F: FnOnce() -> Result<V, E>>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<usize, E> {
+        match self.lookup(&key) {
+            Some(index) => Ok(index),
+            None => {
+                let value = f()?;
+                let hash = self.hash(&key);
+                Ok(self.put(hash, key, value).1)
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for LfuCache<K, V, S> {
+    type Error = std::convert::Infallible;
+
+    fn cache_get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(hot) = &self.hot_keys {
+            let stored = self
+                .find(self.hash(key), key)
+                .map(|index| &self.order.get(index).key);
+            hot.record(hot.hash(key), || stored);
+        }
+        let index = self.lookup(key)?;
+        Some(&self.order.get(index).value)
+    }
+
+    fn cache_get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.lookup(key)?;
+        Some(&mut self.order.get_mut(index).value)
+    }
+
+    /// Insert or replace a cache entry, returning the previous value. Overwriting a key counts
+    /// as a use of it.
+    fn cache_set(&mut self, key: K, val: V) -> Option<V> {
+        let hash = self.hash(&key);
+        self.put(hash, key, val).0
+    }
+
+    fn cache_get_or_set_with_mut<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        let index = self.get_or_set_index(key, f);
+        &mut self.order.get_mut(index).value
+    }
+
+    fn cache_try_get_or_set_with_mut<F: FnOnce() -> Result<V, E>, E>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<&mut V, E> {
+        let index = self.try_get_or_set_index(key, f)?;
+        Ok(&mut self.order.get_mut(index).value)
+    }
+
+    fn cache_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        <Self as Cached<K, V>>::cache_remove_entry(self, k).map(|(_, v)| v)
+    }
+
+    fn cache_remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = self.pop_raw(k);
+        if let Some((ref key, ref value)) = removed {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(key, value);
+            }
+        }
+        removed
+    }
+
+    fn cache_clear(&mut self) {
+        self.store.clear();
+        self.order.clear();
+        self.buckets.clear();
+        self.uses = 0;
+        self.tags.clear();
+    }
+
+    fn cache_reset(&mut self) {
+        self.cache_clear();
+        self.cache_reset_metrics();
+    }
+
+    fn cache_reset_metrics(&mut self) {
+        self.misses.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
+    }
+
+    fn cache_size(&self) -> usize {
+        self.store.len()
+    }
+
+    fn cache_hits(&self) -> Option<u64> {
+        Some(self.hits.load(Ordering::Relaxed))
+    }
+
+    fn cache_misses(&self) -> Option<u64> {
+        Some(self.misses.load(Ordering::Relaxed))
+    }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self
+            .deep_size
+            .map_or(0, |heap| self.entries_heap_size(|k, v| heap.entry(k, v)));
+        Some(self.allocated_bytes() + entries)
+    }
+
+    fn cache_evictions(&self) -> Option<u64> {
+        Some(self.evictions.load(Ordering::Relaxed))
+    }
+
+    fn cache_capacity(&self) -> Option<usize> {
+        Some(self.capacity)
+    }
+
+    /// Peek-based: records no hit/miss metrics and does not count a use.
+    fn cache_contains<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        CachedPeek::cache_peek(self, k).is_some()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> CachedIter<K, V> for LfuCache<K, V, S> {
+    /// Yields entries from the most to the least frequently used; the last one is the next to
+    /// be evicted.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)> + 'a
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter_raw()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> CachedPeek<K, V> for LfuCache<K, V, S> {
+    fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(k), k)?;
+        Some(&self.order.get(index).value)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> super::CachedTags<K, V> for LfuCache<K, V, S> {
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.store.len()) {
+            let mut index = std::mem::replace(&mut self.tags, super::TagIndex::new());
+            index.retain_keys(|k| CachedPeek::cache_peek(self, k).is_some());
+            self.tags = index;
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, S> CachedGetOrSetAsync<K, V> for LfuCache<K, V, S>
+where
+    K: Hash + Eq + Send,
+    S: BuildHasher + Send,
+{
+    fn async_cache_get_or_set_with_mut<'a, F, Fut>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = &'a mut V> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = V> + Send + 'a,
+    {
+        async move {
+            let index = match self.lookup(&k) {
+                Some(index) => index,
+                None => {
+                    let value = f().await;
+                    let hash = self.hash(&k);
+                    self.put(hash, k, value).1
+                }
+            };
+            &mut self.order.get_mut(index).value
+        }
+    }
+
+    fn async_cache_try_get_or_set_with_mut<'a, F, Fut, E>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = Result<&'a mut V, E>> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        E: 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<V, E>> + Send + 'a,
+    {
+        async move {
+            let index = match self.lookup(&k) {
+                Some(index) => index,
+                None => {
+                    let value = f().await?;
+                    let hash = self.hash(&k);
+                    self.put(hash, k, value).1
+                }
+            };
+            Ok(&mut self.order.get_mut(index).value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `order` is sorted by count (highest first), every bucket points at the newest entry of
+    /// its count, and every entry has exactly one table entry.
+    fn assert_invariants<K: Hash + Eq, V>(c: &LfuCache<K, V>) {
+        assert!(c.store.len() <= c.capacity);
+        let freqs: Vec<u64> = c.order.iter().map(|e| e.freq).collect();
+        assert_eq!(freqs.len(), c.store.len());
+        assert!(freqs.windows(2).all(|w| w[0] >= w[1]), "{freqs:?}");
+        let mut heads = HashMap::new();
+        for index in c.order.iter_indices() {
+            heads.entry(c.order.get(index).freq).or_insert(index);
+        }
+        assert_eq!(heads, c.buckets);
+    }
+
+    #[test]
+    fn uses_move_entries_between_buckets() {
+        let mut c = LfuCache::new(4);
+        c.cache_set(1, 1);
+        c.cache_set(2, 2);
+        c.cache_set(3, 3);
+        c.cache_get(&1);
+        c.cache_get(&1);
+        c.cache_get(&3);
+        let keys: Vec<u32> = c.keys().copied().collect();
+        assert_eq!(keys, [1, 3, 2]);
+        assert_eq!(c.frequency(&1), Some(3));
+        assert_invariants(&c);
+    }
+
+    #[test]
+    fn halving_keeps_the_order_and_rebuilds_the_buckets() {
+        let mut c = LfuCache::builder()
+            .max_size(4)
+            .halve_every(8)
+            .build()
+            .unwrap();
+        for k in 0..4u32 {
+            c.cache_set(k, k);
+            for _ in 0..k {
+                c.cache_get(&k);
+            }
+        }
+        // 4 writes + 6 hits: one halving after the 8th use, then two more uses.
+        let keys: Vec<u32> = c.keys().copied().collect();
+        assert_eq!(keys, [3, 2, 1, 0]);
+        assert_eq!(c.frequency(&0), Some(0));
+        assert_eq!(c.frequency(&3), Some(3));
+        assert_invariants(&c);
+    }
+
+    #[test]
+    fn random_workload_keeps_the_invariants() {
+        let mut c = LfuCache::builder()
+            .max_size(8)
+            .halve_every(50)
+            .build()
+            .unwrap();
+        let mut x: u64 = 7;
+        for _ in 0..5000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let key = x % 24;
+            match x % 5 {
+                0 => {
+                    let _ = c.cache_remove(&key);
+                }
+                1 | 2 => {
+                    c.cache_get(&key);
+                }
+                _ => {
+                    c.cache_set(key, key);
+                }
+            }
+            assert_invariants(&c);
+        }
+        c.set_max_size(3);
+        assert_invariants(&c);
+    }
+}
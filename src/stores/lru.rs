@@ -4,6 +4,7 @@ use crate::{CachedIter, CachedPeek};
 use hashbrown::HashTable;
 use std::borrow::Borrow;
 use std::cmp::Eq;
+use std::collections::HashSet;
 use std::fmt;
 use std::hash::{BuildHasher, Hash, Hasher};
 
@@ -47,6 +48,14 @@ pub struct LruCache<K, V, S = DefaultHashBuilder> {
     /// `misses`. Used by wrapper stores that maintain their own counters and delegate to this
     /// cache solely for LRU ordering / storage — avoids a redundant atomic op per access.
     pub(crate) track_hit_miss: bool,
+    /// Keys written through [`CachedTags::set_with_tags`](super::CachedTags), by tag.
+    pub(super) tags: super::TagIndex<K>,
+    pub(super) metrics_exporter: super::ExporterSlot,
+    pub(super) hot_keys: Option<super::HotKeys<K>>,
+    pub(super) deep_size: Option<super::HeapSize<K, V>>,
+    /// Slot indices of the pinned entries, which `order` keeps on its pinned list.
+    pub(super) pinned: HashSet<usize>,
+    pub(super) max_pinned: Option<usize>,
 }
 
 impl<K, V, S> Clone for LruCache<K, V, S>
@@ -65,7 +74,13 @@ where
             misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
             evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
             on_evict: self.on_evict.clone(),
+            tags: self.tags.clone(),
             track_hit_miss: self.track_hit_miss,
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+            pinned: self.pinned.clone(),
+            max_pinned: self.max_pinned,
         }
     }
 }
@@ -111,16 +126,24 @@ where
 /// Builder for [`LruCache`].
 pub struct LruCacheBuilder<K, V, S = DefaultHashBuilder> {
     size: Option<usize>,
+    max_pinned: Option<usize>,
     on_evict: Option<super::OnEvict<K, V>>,
     hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for LruCacheBuilder<K, V, DefaultHashBuilder> {
     fn default() -> Self {
         Self {
             size: None,
+            max_pinned: None,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -143,6 +166,15 @@ impl<K, V, S> LruCacheBuilder<K, V, S> {
         self
     }
 
+    /// Cap the number of pinned entries. Pinned entries do not count toward `max_size`, so
+    /// without a cap [`pin`](LruCache::pin) and [`set_pinned`](LruCache::set_pinned) can
+    /// grow the cache past it without bound. Optional; `build` returns `Err` for `0`.
+    #[must_use]
+    pub fn max_pinned(mut self, max_pinned: usize) -> Self {
+        self.max_pinned = Some(max_pinned);
+        self
+    }
+
     /// Set a callback to be invoked when an entry is evicted.
     ///
     /// Use [`cache_clear_with_on_evict`](LruCache::cache_clear_with_on_evict)
@@ -178,18 +210,67 @@ impl<K, V, S> LruCacheBuilder<K, V, S> {
     pub fn hasher<S2: BuildHasher>(self, hasher: S2) -> LruCacheBuilder<K, V, S2> {
         LruCacheBuilder {
             size: self.size,
+            max_pinned: self.max_pinned,
             on_evict: self.on_evict,
             hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Each estimate then visits every entry.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
     /// Build the cache.
     ///
     /// # Errors
     ///
     /// Returns [`BuildError::MissingRequired`](super::BuildError::MissingRequired) if `max_size` was not set,
-    /// or [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if `max_size` is `0` or capacity
-    /// pre-allocation fails.
+    /// or [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if `max_size` or `max_pinned`
+    /// is `0` or capacity pre-allocation fails.
     pub fn build(self) -> Result<LruCache<K, V, S>, super::BuildError>
     where
         K: Hash + Eq + Clone,
@@ -204,6 +285,7 @@ impl<K, V, S> LruCacheBuilder<K, V, S> {
                 reason: "must be greater than zero",
             });
         }
+        super::check_max_pinned(self.max_pinned)?;
 
         let mut store = HashTable::new();
         // Use a temporary hasher for pre-reservation; the actual hash_builder is stored on the cache.
@@ -227,7 +309,13 @@ impl<K, V, S> LruCacheBuilder<K, V, S> {
             misses: AtomicU64::new(0),
             evictions: AtomicU64::new(0),
             on_evict: None,
+            tags: super::TagIndex::new(),
             track_hit_miss: true,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
+            pinned: HashSet::new(),
+            max_pinned: self.max_pinned,
         };
         cache.on_evict = self.on_evict;
         Ok(cache)
@@ -312,7 +400,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         self.capacity = max_size;
         // `check_capacity` evicts at most one entry per call (it normally runs after
         // a single insert), so loop until the cache fits the new, smaller bound.
-        while self.store.len() > self.capacity {
+        while self.unpinned_len() > self.capacity {
             self.check_capacity();
         }
         Some(prev)
@@ -335,9 +423,157 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         Ok(self.set_max_size(max_size))
     }
 
+    /// Pin the entry for `k`, exempting it from capacity eviction until it is unpinned or
+    /// removed. Returns `Ok(true)` once the entry is pinned, including when it already was,
+    /// and `Ok(false)` when there is no entry for `k`.
+    ///
+    /// Pinned entries do not count toward `max_size`: the bound applies to the unpinned
+    /// entries, and when they exceed it the least recently used of them is evicted. A pinned
+    /// entry is still removed by `cache_remove`, `retain` and the clear methods. Pinning
+    /// moves the entry to the front of the order methods' output, ahead of every unpinned
+    /// entry, and a hit keeps it there.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`](super::PinError::LimitReached) when the entry is
+    /// not pinned yet and the cache already holds
+    /// [`max_pinned`](LruCacheBuilder::max_pinned) pinned entries.
+    pub fn pin<Q>(&mut self, k: &Q) -> Result<bool, super::PinError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(index) = self.get_index(self.hash(k), k) else {
+            return Ok(false);
+        };
+        self.pin_index(index)?;
+        Ok(true)
+    }
+
+    /// Unpin the entry for `k`, returning whether it was pinned. The entry becomes the most
+    /// recently used unpinned entry and counts toward `max_size` again, so unpinning into a
+    /// full cache evicts the least recently used entry.
+    pub fn unpin<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_index(self.hash(k), k) {
+            Some(index) => self.unpin_index(index),
+            None => false,
+        }
+    }
+
+    /// Insert or replace the entry for `k` and pin it, returning the displaced value. See
+    /// [`pin`](Self::pin).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`](super::PinError::LimitReached) when `k` is not
+    /// pinned yet and the cache already holds
+    /// [`max_pinned`](LruCacheBuilder::max_pinned) pinned entries. Nothing is stored.
+    pub fn set_pinned(&mut self, key: K, val: V) -> Result<Option<V>, super::PinError> {
+        Ok(self.set_pinned_returning_entry(key, val)?.map(|(_, v)| v))
+    }
+
+    /// Whether the entry for `k` is pinned.
+    #[must_use]
+    pub fn is_pinned<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        !self.pinned.is_empty()
+            && self
+                .get_index(self.hash(k), k)
+                .is_some_and(|index| self.pinned.contains(&index))
+    }
+
+    /// Number of pinned entries.
+    #[must_use]
+    pub fn pinned_len(&self) -> usize {
+        self.pinned.len()
+    }
+
+    /// Number of entries that count toward `max_size`.
+    fn unpinned_len(&self) -> usize {
+        self.store.len() - self.pinned.len()
+    }
+
+    /// Pin the entry in slot `index`, or promote it if it is already pinned.
+    pub(super) fn pin_index(&mut self, index: usize) -> Result<(), super::PinError> {
+        if !self.pinned.contains(&index) {
+            self.check_pin_room()?;
+            self.pinned.insert(index);
+        }
+        self.order.move_to_pinned_front(index);
+        Ok(())
+    }
+
+    /// Unpin the entry in slot `index`, returning whether it was pinned.
+    pub(super) fn unpin_index(&mut self, index: usize) -> bool {
+        if !self.pinned.remove(&index) {
+            return false;
+        }
+        self.order.move_to_front(index);
+        self.check_capacity();
+        true
+    }
+
+    fn check_pin_room(&self) -> Result<(), super::PinError> {
+        match self.max_pinned {
+            Some(max_pinned) if self.pinned.len() >= max_pinned => {
+                Err(super::PinError::LimitReached { max_pinned })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Promote the entry in slot `index` to the front of its list: the pinned list for a
+    /// pinned entry, the LRU order otherwise.
+    pub(super) fn touch(&mut self, index: usize) {
+        if self.is_pinned_index(index) {
+            self.order.move_to_pinned_front(index);
+        } else {
+            self.order.move_to_front(index);
+        }
+    }
+
+    pub(super) fn is_pinned_index(&self, index: usize) -> bool {
+        !self.pinned.is_empty() && self.pinned.contains(&index)
+    }
+
+    /// [`set_pinned`](Self::set_pinned) returning the **stored** key and value it displaced,
+    /// as [`cache_set_returning_entry`](Self::cache_set_returning_entry) does.
+    pub(super) fn set_pinned_returning_entry(
+        &mut self,
+        key: K,
+        val: V,
+    ) -> Result<Option<(K, V)>, super::PinError> {
+        let hash = self.hash(&key);
+        let index = self.get_index(hash, &key);
+        if !index.is_some_and(|index| self.pinned.contains(&index)) {
+            self.check_pin_room()?;
+        }
+        let (index, displaced) = match index {
+            Some(index) => (index, self.order.set(index, (key, val))),
+            None => {
+                let index = self.order.push_front((key, val));
+                self.insert_index(hash, index);
+                (index, None)
+            }
+        };
+        // Pinning never adds an unpinned entry, so there is nothing to evict.
+        self.pinned.insert(index);
+        self.order.move_to_pinned_front(index);
+        Ok(displaced)
+    }
+
     /// Return all entries in current LRU order (most-recently-used first) as a `Vec` of
     /// `(K, `[`CacheValue<V>`](super::CacheValue)`)` pairs. `LruCache` carries no per-entry
     /// metadata, so the wrapper's metadata type is `()`; the wrapper `Deref`s to `V`.
+    /// Pinned entries come first, each reporting
+    /// [`is_pinned`](super::CacheValue::is_pinned).
     #[must_use]
     pub fn iter_order(&self) -> Vec<(K, super::CacheValue<V>)>
     where
@@ -347,11 +583,14 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         // `LRUListIterator` has no `size_hint`, so `collect` would grow the Vec from
         // zero. The live entry count is known here, so pre-size instead.
         let mut out = Vec::with_capacity(self.store.len());
-        out.extend(
-            self.order
-                .iter()
-                .map(|(k, v)| (k.clone(), super::CacheValue::new(v.clone(), ()))),
-        );
+        // The order walks the pinned entries first, so the first `pinned_len` are pinned.
+        let pinned = self.pinned.len();
+        out.extend(self.order.iter().enumerate().map(|(i, (k, v))| {
+            (
+                k.clone(),
+                super::CacheValue::new(v.clone(), ()).with_pinned(i < pinned),
+            )
+        }));
         out
     }
 
@@ -387,10 +626,12 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         V: Clone,
     {
         let mut out = Vec::with_capacity(self.store.len());
+        let pinned = self.pinned.len();
         out.extend(
             self.order
                 .iter()
-                .map(|(_k, v)| super::CacheValue::new(v.clone(), ())),
+                .enumerate()
+                .map(|(i, (_k, v))| super::CacheValue::new(v.clone(), ()).with_pinned(i < pinned)),
         );
         out
     }
@@ -422,7 +663,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         {
             Ok(entry) => {
                 let index = entry.remove().0;
-                Some(self.order.remove(index))
+                Some(self.remove_slot(index))
             }
             Err(_) => None,
         }
@@ -459,6 +700,39 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
                 "LruCache internal invariant violated: LRU order and hash table out of sync"
             ),
         }
+        self.remove_slot(index)
+    }
+
+    /// Phase 1 of a budgeted sweep: the slot indices `doomed` selects among the next `budget`
+    /// occupied slots in slab order from slot `from`, how many slots it examined, and the slot
+    /// the next step starts at.
+    ///
+    /// Reads only, like the full sweeps' scans; the caller removes the selected slots with
+    /// [`remove_index`](Self::remove_index).
+    pub(super) fn doomed_slots(
+        &self,
+        from: usize,
+        budget: usize,
+        mut doomed: impl FnMut(&K, &V) -> bool,
+    ) -> (Vec<usize>, usize, usize) {
+        let mut selected = Vec::new();
+        let mut visited = 0;
+        let mut next = from;
+        for (index, (key, value)) in self.order.iter_slots_from(from).take(budget) {
+            visited += 1;
+            next = index + 1;
+            if doomed(key, value) {
+                selected.push(index);
+            }
+        }
+        (selected, visited, next)
+    }
+
+    /// Free slot `index`, releasing its pin.
+    fn remove_slot(&mut self, index: usize) -> (K, V) {
+        if !self.pinned.is_empty() {
+            self.pinned.remove(&index);
+        }
         self.order.remove(index)
     }
 
@@ -473,6 +747,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         let mut drained = Vec::with_capacity(self.store.len());
         self.order.drain_into(&mut drained);
         self.store.clear();
+        self.pinned.clear();
         drained
     }
 
@@ -506,12 +781,47 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
             .copied()
     }
 
+    /// Bytes allocated for the hash table, the entry list and the pinned slot set, by
+    /// capacity (the set's control bytes are not counted).
+    pub(super) fn allocated_bytes(&self) -> usize {
+        self.store.allocation_size()
+            + self.order.allocation_size()
+            + self.pinned.capacity() * std::mem::size_of::<usize>()
+    }
+
+    /// Sum `heap` over every stored entry, expired or not.
+    pub(super) fn entries_heap_size(&self, heap: impl Fn(&K, &V) -> usize) -> usize {
+        self.order.iter().map(|(k, v)| heap(k, v)).sum()
+    }
+
+    /// The stored copy of `key`, if present (expired or not).
+    pub(super) fn stored_key<Q>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index(self.hash(key), key)
+            .map(|i| &self.order.get(i).0)
+    }
+
+    /// Drop keys this store no longer holds from the tag index once it has outgrown the
+    /// store. Shared with the LRU-backed wrappers, whose index lives here.
+    pub(super) fn prune_tags(&mut self) {
+        if !self.tags.is_oversized(self.store.len()) {
+            return;
+        }
+        let mut tags = std::mem::replace(&mut self.tags, super::TagIndex::new());
+        tags.retain_keys(|k| self.get_index(self.hash(k), k).is_some());
+        self.tags = tags;
+    }
+
     fn check_capacity(&mut self) {
         // `while` (not `if`) plus pop-before-notify: remove the victim from both
         // the store and the LRU order BEFORE invoking `on_evict`, so a panicking
         // callback can never leave an entry behind over capacity, and the loop
-        // self-heals `len <= capacity` after any earlier panic (SHARD-4).
-        while self.store.len() > self.capacity {
+        // self-heals `len <= capacity` after any earlier panic (SHARD-4). Pinned entries
+        // are not counted, and `back` never returns one.
+        while self.unpinned_len() > self.capacity {
             let index = self.order.back();
             let (key, _value) = self.order.get(index);
             let hasher = &mut self.hash_builder.build_hasher();
@@ -545,7 +855,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         if let Some(index) = self.get_index(self.hash(key), key)
             && is_valid(&self.order.get(index).1)
         {
-            self.order.move_to_front(index);
+            self.touch(index);
             if self.track_hit_miss {
                 self.hits.fetch_add(1, Ordering::Relaxed);
             }
@@ -569,7 +879,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         if let Some(index) = self.get_index(self.hash(key), key)
             && is_valid(&self.order.get(index).1)
         {
-            self.order.move_to_front(index);
+            self.touch(index);
             if self.track_hit_miss {
                 self.hits.fetch_add(1, Ordering::Relaxed);
             }
@@ -606,7 +916,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
             } else {
                 None
             };
-            self.order.move_to_front(index);
+            self.touch(index);
             (
                 true,
                 !replace_existing,
@@ -650,7 +960,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
             } else {
                 None
             };
-            self.order.move_to_front(index);
+            self.touch(index);
             Ok((
                 true,
                 !replace_existing,
@@ -736,7 +1046,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruCache<K, V, S> {
         let hash = self.hash(&key);
         let entry = if let Some(index) = self.get_index(hash, &key) {
             let displaced = self.order.set(index, (key, val));
-            self.order.move_to_front(index);
+            self.touch(index);
             displaced
         } else {
             let index = self.order.push_front((key, val));
@@ -804,7 +1114,7 @@ where
             } else {
                 None
             };
-            self.order.move_to_front(index);
+            self.touch(index);
             (
                 true,
                 !replace_existing,
@@ -852,7 +1162,7 @@ where
             } else {
                 None
             };
-            self.order.move_to_front(index);
+            self.touch(index);
             Ok((
                 true,
                 !replace_existing,
@@ -880,6 +1190,9 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruCache<K, V, S>
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
+        if let Some(hot) = &self.hot_keys {
+            hot.record(hot.hash(key), || self.stored_key(key));
+        }
         self.get_if(key, |_| true)
     }
 
@@ -905,7 +1218,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruCache<K, V, S>
         let hash = self.hash(&key);
         let v = if let Some(index) = self.get_index(hash, &key) {
             let displaced = self.order.set(index, (key, val)).map(|(_, v)| v);
-            self.order.move_to_front(index);
+            self.touch(index);
             displaced
         } else {
             let index = self.order.push_front((key, val));
@@ -958,6 +1271,8 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruCache<K, V, S>
     fn cache_clear(&mut self) {
         self.store.clear();
         self.order.clear();
+        self.pinned.clear();
+        self.tags.clear();
     }
     fn cache_reset(&mut self) {
         // Entries are dropped in-place; `on_evict` is NOT called for cleared entries.
@@ -973,12 +1288,17 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruCache<K, V, S>
             .unwrap_or_else(|_| LRUList::<(K, V)>::with_capacity(0));
         self.store = new_store;
         self.order = new_order;
+        self.pinned.clear();
+        self.tags.clear();
         self.cache_reset_metrics();
     }
     fn cache_reset_metrics(&mut self) {
         self.misses.store(0, Ordering::Relaxed);
         self.hits.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
     fn cache_size(&self) -> usize {
         self.store.len()
@@ -989,6 +1309,22 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruCache<K, V, S>
     fn cache_misses(&self) -> Option<u64> {
         Some(self.misses.load(Ordering::Relaxed))
     }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self
+            .deep_size
+            .map_or(0, |heap| self.entries_heap_size(|k, v| heap.entry(k, v)));
+        Some(self.allocated_bytes() + entries)
+    }
     fn cache_evictions(&self) -> Option<u64> {
         Some(self.evictions.load(Ordering::Relaxed))
     }
@@ -1020,6 +1356,51 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedIter<K, V> for LruCache<K, V
     }
 }
 
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> super::CachedTags<K, V> for LruCache<K, V, S> {
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        self.prune_tags();
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> super::CachedSnapshot<K, V>
+    for LruCache<K, V, S>
+{
+    /// Entries are recorded least-recently-used first.
+    fn snapshot(&self) -> super::CacheSnapshot<K, V> {
+        let clock = super::SnapshotClock::now();
+        // The LRU chain only walks MRU -> LRU; collect the links to reverse them.
+        let order: Vec<&(K, V)> = self.order.iter().collect();
+        let entries = order
+            .into_iter()
+            .rev()
+            .filter_map(|(k, v)| clock.entry(k.clone(), v.clone(), None))
+            .collect();
+        super::CacheSnapshot::from_shards(vec![entries])
+    }
+
+    /// Entries are inserted in snapshot order, so the last (most-recently-used) entry ends
+    /// up at the head and a snapshot larger than `capacity` keeps its most recent entries.
+    fn restore(&mut self, snapshot: super::CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(super::SnapshotClock::now()) {
+            self.cache_set(k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedPeek<K, V> for LruCache<K, V, S> {
     fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
     where
@@ -1080,7 +1461,7 @@ where
 mod tests {
     use super::*;
     use crate::CachedExt;
-    use crate::stores::Cached;
+    use crate::stores::{BuildError, Cached, PinError};
 
     #[test]
     fn new_returns_ready_cache_respecting_max_size() {
@@ -3075,6 +3456,100 @@ mod tests {
         assert!(empty.iter_order_raw().is_empty());
     }
 
+    #[test]
+    fn pinned_entries_survive_eviction_and_do_not_count_toward_max_size() {
+        let mut c: LruCache<u32, u32> = LruCache::new(2);
+        c.cache_set(1, 10);
+        assert_eq!(c.pin(&1), Ok(true));
+        assert_eq!(c.pin(&9), Ok(false));
+        assert_eq!(c.set_pinned(2, 20), Ok(None));
+        for k in 10..20 {
+            c.cache_set(k, k);
+        }
+        assert_eq!(c.cache_size(), 4);
+        assert_eq!(c.pinned_len(), 2);
+        assert_eq!(c.key_order(), vec![2, 1, 19, 18]);
+        assert_eq!(c.cache_get(&1), Some(&10));
+        assert_eq!(c.key_order(), vec![1, 2, 19, 18]);
+
+        let order = c.iter_order();
+        let pinned: Vec<bool> = order.iter().map(|(_, v)| v.is_pinned()).collect();
+        assert_eq!(pinned, vec![true, true, false, false]);
+
+        c.set_max_size(1);
+        assert_eq!(c.key_order(), vec![1, 2, 19]);
+    }
+
+    #[test]
+    fn max_pinned_caps_new_pins_only() {
+        let mut c: LruCache<u32, u32> = LruCache::builder()
+            .max_size(4)
+            .max_pinned(1)
+            .build()
+            .unwrap();
+        c.cache_set(1, 1);
+        c.cache_set(2, 2);
+        assert_eq!(c.pin(&1), Ok(true));
+        assert_eq!(c.pin(&1), Ok(true));
+        assert_eq!(c.set_pinned(1, 10), Ok(Some(1)));
+        let full = PinError::LimitReached { max_pinned: 1 };
+        assert_eq!(c.pin(&2), Err(full.clone()));
+        assert_eq!(c.set_pinned(3, 3), Err(full));
+        assert!(c.cache_get(&3).is_none());
+        assert!(!c.is_pinned(&2));
+
+        assert!(c.unpin(&1));
+        assert!(!c.unpin(&1));
+        assert_eq!(c.pin(&2), Ok(true));
+
+        let err = LruCache::<u32, u32>::builder()
+            .max_size(4)
+            .max_pinned(0)
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BuildError::InvalidValue {
+                field: "max_pinned",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn unpinning_into_a_full_cache_evicts_the_lru_entry() {
+        let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = evicted.clone();
+        let mut c: LruCache<u32, u32> = LruCache::builder()
+            .max_size(2)
+            .on_evict(move |k, _| seen.lock().unwrap().push(*k))
+            .build()
+            .unwrap();
+        c.set_pinned(1, 1).unwrap();
+        c.cache_set(2, 2);
+        c.cache_set(3, 3);
+        assert!(c.unpin(&1));
+        assert_eq!(*evicted.lock().unwrap(), vec![2]);
+        assert_eq!(c.key_order(), vec![1, 3]);
+    }
+
+    #[test]
+    fn removing_a_pinned_entry_releases_its_slot_and_pin() {
+        let mut c: LruCache<u32, u32> = LruCache::new(2);
+        c.set_pinned(1, 1).unwrap();
+        assert_eq!(c.cache_remove(&1), Some(1));
+        assert_eq!(c.pinned_len(), 0);
+        // The freed slot is reused unpinned.
+        c.cache_set(2, 2);
+        assert!(!c.is_pinned(&2));
+        c.set_pinned(3, 3).unwrap();
+        c.cache_clear();
+        assert_eq!(c.pinned_len(), 0);
+        c.cache_set(4, 4);
+        assert!(!c.is_pinned(&4));
+        assert_store_and_order_agree(&c);
+    }
+
     #[test]
     fn store_len_and_live_chain_never_diverge_under_mixed_operations() {
         // The `*_order` pre-sizing assumes `store.len()` equals the live chain length.
@@ -8,7 +8,7 @@ use {super::CachedGetOrSetAsync, std::future::Future};
 
 use crate::{CachedIter, CachedPeek, CloneCached};
 
-use super::{CacheEvict, Cached, DefaultHashBuilder, LruCache, TimedEntry};
+use super::{CacheEvict, Cached, DefaultHashBuilder, EvictProgress, LruCache, TimedEntry};
 use std::hash::BuildHasher;
 use std::marker::PhantomData;
 use std::sync::Arc;
@@ -43,6 +43,12 @@ pub struct LruTtlCache<K, V, S = DefaultHashBuilder> {
     pub(super) evictions: AtomicU64,
     pub(super) refresh: bool,
     pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    pub(super) metrics_exporter: super::ExporterSlot,
+    pub(super) hot_keys: Option<super::HotKeys<K>>,
+    pub(super) deep_size: Option<super::HeapSize<K, V>>,
+    /// Where the next [`evict_budgeted`](Self::evict_budgeted) step starts, as a slot of the
+    /// inner store's entry list.
+    pub(super) evict_cursor: usize,
 }
 
 impl<K, V, S> std::fmt::Debug for LruTtlCache<K, V, S> {
@@ -76,6 +82,10 @@ where
             evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
             refresh: self.refresh,
             on_evict: self.on_evict.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+            evict_cursor: self.evict_cursor,
         }
     }
 }
@@ -114,22 +124,30 @@ pub struct HasEvict;
 ///   can be wired into the inner LRU eviction path.
 pub struct LruTtlCacheBuilder<K, V, S = DefaultHashBuilder, E = NoEvict> {
     size: Option<usize>,
+    max_pinned: Option<usize>,
     ttl: Option<Duration>,
     refresh: bool,
     on_evict: Option<super::OnEvict<K, V>>,
     hasher: S,
     _evict: PhantomData<E>,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for LruTtlCacheBuilder<K, V> {
     fn default() -> Self {
         Self {
             size: None,
+            max_pinned: None,
             ttl: None,
             refresh: false,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
             _evict: PhantomData,
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -153,6 +171,14 @@ impl<K, V, S, E> LruTtlCacheBuilder<K, V, S, E> {
         self
     }
 
+    /// Cap the number of pinned entries, which do not count toward `max_size`. See
+    /// [`LruCacheBuilder::max_pinned`](super::LruCacheBuilder::max_pinned).
+    #[must_use]
+    pub fn max_pinned(mut self, max_pinned: usize) -> Self {
+        self.max_pinned = Some(max_pinned);
+        self
+    }
+
     /// Set the TTL for cache entries. Required.
     ///
     /// Overrides any previously set ttl/ttl_secs/ttl_millis on this builder.
@@ -214,13 +240,62 @@ impl<K, V, S, E> LruTtlCacheBuilder<K, V, S, E> {
     pub fn hasher<S2: BuildHasher>(self, hasher: S2) -> LruTtlCacheBuilder<K, V, S2, E> {
         LruTtlCacheBuilder {
             size: self.size,
+            max_pinned: self.max_pinned,
             ttl: self.ttl,
             refresh: self.refresh,
             on_evict: self.on_evict,
             hasher,
             _evict: PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Each estimate then visits every entry.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
 }
 
 // on_evict transitions the builder from NoEvict -> HasEvict
@@ -256,11 +331,15 @@ impl<K, V, S> LruTtlCacheBuilder<K, V, S, NoEvict> {
     ) -> LruTtlCacheBuilder<K, V, S, HasEvict> {
         LruTtlCacheBuilder {
             size: self.size,
+            max_pinned: self.max_pinned,
             ttl: self.ttl,
             refresh: self.refresh,
             on_evict: Some(Arc::new(on_evict)),
             hasher: self.hasher,
             _evict: PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 }
@@ -271,7 +350,7 @@ impl<K, V, S: BuildHasher> LruTtlCacheBuilder<K, V, S, NoEvict> {
     ///
     /// # Errors
     ///
-    /// Returns [`BuildError`](super::BuildError) if `max_size` or `ttl` was not set, if `ttl` is zero, or if `max_size` is `0`.
+    /// Returns [`BuildError`](super::BuildError) if `max_size` or `ttl` was not set, if `ttl` is zero, or if `max_size` or `max_pinned` is `0`.
     pub fn build(self) -> Result<LruTtlCache<K, V, S>, super::BuildError>
     where
         K: Hash + Eq + Clone,
@@ -281,7 +360,13 @@ impl<K, V, S: BuildHasher> LruTtlCacheBuilder<K, V, S, NoEvict> {
             .ok_or(super::BuildError::MissingRequired("max_size"))?;
         let ttl = self.ttl.ok_or(super::BuildError::MissingRequired("ttl"))?;
         super::validate_ttl(ttl)?;
-        LruTtlCache::new_internal(size, ttl, self.refresh, self.hasher)
+        super::check_max_pinned(self.max_pinned)?;
+        let mut cache = LruTtlCache::new_internal(size, ttl, self.refresh, self.hasher)?;
+        cache.store.max_pinned = self.max_pinned;
+        cache.metrics_exporter = self.metrics_exporter;
+        cache.hot_keys = super::HotKeys::build(self.hot_keys, 1)?;
+        cache.deep_size = self.deep_size;
+        Ok(cache)
     }
 }
 
@@ -291,7 +376,7 @@ impl<K, V, S: BuildHasher> LruTtlCacheBuilder<K, V, S, HasEvict> {
     ///
     /// # Errors
     ///
-    /// Returns [`BuildError`](super::BuildError) if `max_size` or `ttl` was not set, if `ttl` is zero, or if `max_size` is `0`.
+    /// Returns [`BuildError`](super::BuildError) if `max_size` or `ttl` was not set, if `ttl` is zero, or if `max_size` or `max_pinned` is `0`.
     pub fn build(self) -> Result<LruTtlCache<K, V, S>, super::BuildError>
     where
         K: Hash + Eq + Clone + 'static,
@@ -302,8 +387,13 @@ impl<K, V, S: BuildHasher> LruTtlCacheBuilder<K, V, S, HasEvict> {
             .ok_or(super::BuildError::MissingRequired("max_size"))?;
         let ttl = self.ttl.ok_or(super::BuildError::MissingRequired("ttl"))?;
         super::validate_ttl(ttl)?;
+        super::check_max_pinned(self.max_pinned)?;
         let mut cache = LruTtlCache::new_internal(size, ttl, self.refresh, self.hasher)?;
+        cache.store.max_pinned = self.max_pinned;
         cache.on_evict = self.on_evict;
+        cache.metrics_exporter = self.metrics_exporter;
+        cache.hot_keys = super::HotKeys::build(self.hot_keys, 1)?;
+        cache.deep_size = self.deep_size;
         cache.sync_on_evict();
         Ok(cache)
     }
@@ -334,11 +424,15 @@ impl<K: Hash + Eq + Clone, V> LruTtlCache<K, V> {
     pub fn builder() -> LruTtlCacheBuilder<K, V> {
         LruTtlCacheBuilder {
             size: None,
+            max_pinned: None,
             ttl: None,
             refresh: false,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
             _evict: PhantomData,
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -389,7 +483,18 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruTtlCache<K, V, S> {
     /// `entry.expires_at`), used to decide whether the displaced entry was still live --
     /// avoids a second `Instant::now()` call here.
     fn set_entry(&mut self, key: K, entry: TimedEntry<V>, now: Instant) -> Option<V> {
-        match self.store.cache_set_returning_entry(key, entry) {
+        let displaced = self.store.cache_set_returning_entry(key, entry);
+        self.settle_displaced(displaced, now)
+    }
+
+    /// The displaced-entry half of [`set_entry`](Self::set_entry), shared with
+    /// [`set_pinned`](Self::set_pinned).
+    fn settle_displaced(
+        &mut self,
+        displaced: Option<(K, TimedEntry<V>)>,
+        now: Instant,
+    ) -> Option<V> {
+        match displaced {
             Some((_, old)) if Self::entry_live_at(old.expires_at, now) => Some(old.value),
             Some((stored_key, old)) => {
                 // Count BEFORE notifying: a panicking callback must never leave
@@ -457,13 +562,18 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruTtlCache<K, V, S> {
             evictions: AtomicU64::new(0),
             refresh,
             on_evict: None,
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+            evict_cursor: 0,
         })
     }
 
     /// Return all live entries in the current order from most to least recently
     /// used, as `(K, `[`CacheValue`](super::CacheValue)`)` pairs. The wrapper
     /// `Deref`s to `V` and exposes the entry's expiry via
-    /// [`expires_at`](super::CacheValue::expires_at).
+    /// [`expires_at`](super::CacheValue::expires_at). Pinned entries come first and
+    /// report [`is_pinned`](super::CacheValue::is_pinned).
     /// Items past their expiry will be excluded.
     #[must_use]
     pub fn iter_order(&self) -> Vec<(K, super::CacheValue<V, Option<Instant>>)>
@@ -477,17 +587,26 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruTtlCache<K, V, S> {
         // `LRUListIterator` has no `size_hint`, so `collect` would grow the Vec from
         // zero; the stored entry count is a known upper bound on the live entries.
         let mut out = Vec::with_capacity(self.store.cache_size());
-        out.extend(self.store.order.iter().filter_map(|(k, entry)| {
-            let expires_at = entry.expires_at;
-            if Self::entry_live_at(expires_at, now) {
-                Some((
-                    k.clone(),
-                    super::CacheValue::new(entry.value.clone(), expires_at),
-                ))
-            } else {
-                None
-            }
-        }));
+        // The order walks the pinned entries first, so the first `pinned_len` are pinned.
+        let pinned = self.store.pinned_len();
+        out.extend(
+            self.store
+                .order
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (k, entry))| {
+                    let expires_at = entry.expires_at;
+                    if Self::entry_live_at(expires_at, now) {
+                        Some((
+                            k.clone(),
+                            super::CacheValue::new(entry.value.clone(), expires_at)
+                                .with_pinned(i < pinned),
+                        ))
+                    } else {
+                        None
+                    }
+                }),
+        );
         out
     }
 
@@ -523,14 +642,24 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruTtlCache<K, V, S> {
         // Single clock reading + pre-sized output, as in `iter_order`.
         let now = Instant::now();
         let mut out = Vec::with_capacity(self.store.cache_size());
-        out.extend(self.store.order.iter().filter_map(|(_k, entry)| {
-            let expires_at = entry.expires_at;
-            if Self::entry_live_at(expires_at, now) {
-                Some(super::CacheValue::new(entry.value.clone(), expires_at))
-            } else {
-                None
-            }
-        }));
+        let pinned = self.store.pinned_len();
+        out.extend(
+            self.store
+                .order
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (_k, entry))| {
+                    let expires_at = entry.expires_at;
+                    if Self::entry_live_at(expires_at, now) {
+                        Some(
+                            super::CacheValue::new(entry.value.clone(), expires_at)
+                                .with_pinned(i < pinned),
+                        )
+                    } else {
+                        None
+                    }
+                }),
+        );
         out
     }
 
@@ -605,6 +734,29 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruTtlCache<K, V, S> {
         self.remove_and_notify(doomed)
     }
 
+    /// Take one step of an incremental [`evict`](Self::evict): examine at most `max_entries`
+    /// entries from where the last step stopped, remove the expired ones, and report how
+    /// many were removed and whether the sweep reached the end of the store.
+    ///
+    /// Steps walk the entries in storage order rather than recency order, so touching an
+    /// entry mid-sweep does not move it past the cursor. See
+    /// [`CacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        let now = Instant::now();
+        let (doomed, visited, next) =
+            self.store
+                .doomed_slots(self.evict_cursor, max_entries, |_key, entry| {
+                    !Self::entry_live_at(entry.expires_at, now)
+                });
+        let finished =
+            crate::stores::advance_evict_cursor(&mut self.evict_cursor, visited, max_entries, next);
+        EvictProgress {
+            reclaimed: self.remove_and_notify(doomed),
+            finished,
+        }
+    }
+
     /// Phase 1 of a two-phase sweep: inner-store slot indices (MRU -> LRU) of the entries
     /// `doomed` selects.
     ///
@@ -698,6 +850,101 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> LruTtlCache<K, V, S> {
             }
         }
     }
+
+    /// Insert a key-value pair that expires `ttl` from now instead of after the cache's TTL,
+    /// returning the previous value only if it had not yet expired. A zero `ttl` stores an
+    /// entry that never expires. The entry takes the most-recently-used slot and can evict
+    /// the least recently used one, exactly like
+    /// [`cache_set`](crate::Cached::cache_set).
+    ///
+    /// [`iter_order`](Self::iter_order) and [`value_order`](Self::value_order) report the
+    /// overridden deadline through [`CacheValue::expires_at`](super::CacheValue::expires_at).
+    /// The override sets the entry's first deadline only: with
+    /// [`refresh_on_hit`](LruTtlCacheBuilder::refresh_on_hit) a hit re-arms it with the
+    /// cache's TTL, as it does every other entry.
+    pub fn set_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = Instant::now();
+        let expires_at = Self::compute_expires_at(ttl, now);
+        self.set_entry(key, TimedEntry { expires_at, value }, now)
+    }
+
+    /// Insert a key-value pair that expires at `deadline`, returning the previous value only
+    /// if it had not yet expired. A `deadline` that has already passed stores an entry that
+    /// is already expired. Otherwise behaves as [`set_with_ttl`](Self::set_with_ttl).
+    pub fn set_until(&mut self, key: K, value: V, deadline: Instant) -> Option<V> {
+        let entry = TimedEntry {
+            expires_at: Some(deadline),
+            value,
+        };
+        self.set_entry(key, entry, Instant::now())
+    }
+
+    /// Pin the live entry for `k`, exempting it from capacity eviction. Returns `Ok(false)`
+    /// when there is no live entry for `k`. A pinned entry still expires. See
+    /// [`LruCache::pin`](super::LruCache::pin).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`](super::PinError::LimitReached) when the entry is
+    /// not pinned yet and the cache already holds
+    /// [`max_pinned`](LruTtlCacheBuilder::max_pinned) pinned entries.
+    pub fn pin<Q>(&mut self, k: &Q) -> Result<bool, super::PinError>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let store = &mut self.store;
+        match store.get_index(store.hash(k), k) {
+            Some(index) if Self::entry_live(store.order.get(index).1.expires_at) => {
+                store.pin_index(index)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Unpin the entry for `k`, returning whether it was pinned. See
+    /// [`LruCache::unpin`](super::LruCache::unpin).
+    pub fn unpin<Q>(&mut self, k: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store.unpin(k)
+    }
+
+    /// Insert or replace the entry for `k` with the cache's TTL and pin it, returning the
+    /// previous value only if it had not yet expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`](super::PinError::LimitReached) when `k` is not
+    /// pinned yet and the cache already holds
+    /// [`max_pinned`](LruTtlCacheBuilder::max_pinned) pinned entries. Nothing is stored.
+    pub fn set_pinned(&mut self, key: K, value: V) -> Result<Option<V>, super::PinError> {
+        let now = Instant::now();
+        let expires_at = Self::compute_expires_at(self.ttl, now);
+        let displaced = self
+            .store
+            .set_pinned_returning_entry(key, TimedEntry { expires_at, value })?;
+        Ok(self.settle_displaced(displaced, now))
+    }
+
+    /// Whether the entry for `k` is pinned, expired or not.
+    #[must_use]
+    pub fn is_pinned<Q>(&self, k: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store.is_pinned(k)
+    }
+
+    /// Number of pinned entries, including expired ones not yet removed.
+    #[must_use]
+    pub fn pinned_len(&self) -> usize {
+        self.store.pinned_len()
+    }
 }
 
 impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruTtlCache<K, V, S> {
@@ -708,6 +955,9 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruTtlCache<K, V,
         K: std::borrow::Borrow<Q>,
         Q: std::hash::Hash + Eq + ?Sized,
     {
+        if let Some(hot) = &self.hot_keys {
+            hot.record(hot.hash(key), || self.store.stored_key(key));
+        }
         let hash = self.store.hash(key);
         if let Some(index) = self.store.get_index(hash, key) {
             // Sample the clock ONCE for this hit and reuse it for both the liveness
@@ -716,7 +966,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruTtlCache<K, V,
             let now = Instant::now();
             let entry = &self.store.order.get(index).1;
             if Self::entry_live_at(entry.expires_at, now) {
-                self.store.order.move_to_front(index);
+                self.store.touch(index);
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 if self.refresh {
                     let new_exp = Self::refreshed_expires_at(
@@ -759,7 +1009,7 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruTtlCache<K, V,
             let now = Instant::now();
             let entry = &self.store.order.get(index).1;
             if Self::entry_live_at(entry.expires_at, now) {
-                self.store.order.move_to_front(index);
+                self.store.touch(index);
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 if self.refresh {
                     let new_exp = Self::refreshed_expires_at(
@@ -966,12 +1216,18 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruTtlCache<K, V,
         self.hits.store(0, Ordering::Relaxed);
         self.misses.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
     fn cache_reset_metrics(&mut self) {
         self.misses.store(0, Ordering::Relaxed);
         self.hits.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
         self.store.cache_reset_metrics();
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
     fn cache_size(&self) -> usize {
         self.store.cache_size()
@@ -982,6 +1238,22 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for LruTtlCache<K, V,
     fn cache_misses(&self) -> Option<u64> {
         Some(self.misses.load(Ordering::Relaxed))
     }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self.deep_size.map_or(0, |heap| {
+            self.store.entries_heap_size(|k, e| heap.entry(k, &e.value))
+        });
+        Some(self.store.allocated_bytes() + entries)
+    }
     fn cache_evictions(&self) -> Option<u64> {
         // Combined evictions from underlying store and our time-based removals
         Some(self.evictions.load(Ordering::Relaxed) + self.store.cache_evictions().unwrap_or(0))
@@ -1026,6 +1298,51 @@ impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedIter<K, V> for LruTtlCache<K
     }
 }
 
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> super::CachedTags<K, V> for LruTtlCache<K, V, S> {
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        self.store.prune_tags();
+        self.store.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.store.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> super::CachedSnapshot<K, V>
+    for LruTtlCache<K, V, S>
+{
+    /// Entries are recorded least-recently-used first.
+    fn snapshot(&self) -> super::CacheSnapshot<K, V> {
+        let clock = super::SnapshotClock::now();
+        let order: Vec<&(K, TimedEntry<V>)> = self.store.order.iter().collect();
+        let entries = order
+            .into_iter()
+            .rev()
+            .filter_map(|(k, e)| clock.entry(k.clone(), e.value.clone(), e.expires_at))
+            .collect();
+        super::CacheSnapshot::from_shards(vec![entries])
+    }
+
+    /// Restored entries keep their recorded deadline rather than starting a fresh TTL, and
+    /// are inserted in snapshot order so the recency chain is rebuilt.
+    fn restore(&mut self, snapshot: super::CacheSnapshot<K, V>) -> usize {
+        let clock = super::SnapshotClock::now();
+        let mut restored = 0;
+        for (key, value, expires_at) in snapshot.into_live(clock) {
+            self.set_entry(key, TimedEntry { expires_at, value }, clock.instant());
+            restored += 1;
+        }
+        restored
+    }
+}
+
 impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedPeek<K, V> for LruTtlCache<K, V, S> {
     fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
     where
@@ -1093,7 +1410,7 @@ impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> CloneCached<K, V>
                 self.misses.fetch_add(1, Ordering::Relaxed);
                 (Some(self.store.order.get(index).1.value.clone()), true)
             } else {
-                self.store.order.move_to_front(index);
+                self.store.touch(index);
                 self.hits.fetch_add(1, Ordering::Relaxed);
                 if self.refresh {
                     let new_exp = Self::refreshed_expires_at(
@@ -1265,6 +1582,10 @@ impl<K: std::hash::Hash + Eq + Clone, V, S: BuildHasher> CacheEvict for LruTtlCa
     fn evict(&mut self) -> usize {
         LruTtlCache::evict(self)
     }
+
+    fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        LruTtlCache::evict_budgeted(self, max_entries)
+    }
 }
 
 #[cfg(test)]
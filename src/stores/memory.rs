@@ -0,0 +1,269 @@
+//! Approximate memory accounting for the in-memory stores (`memory_usage` and
+//! `CacheMetrics::mem_bytes`).
+//!
+//! A store's estimate has two parts. The shallow part is the allocations the store itself owns:
+//! its hash table buckets and control bytes, the `LRUList` slab on the LRU stores, and the
+//! expiry index on `TtlSortedCache`. It is computed from allocated capacities, so it is cheap
+//! and includes the room a table has grown into but not filled. The deep part is the heap
+//! memory the keys and values own (a `String`'s buffer, a `Vec`'s elements), which the store
+//! cannot see on its own: a builder's `deep_size` adds it through the [`DeepSize`] impls of
+//! `K` and `V`, at the cost of visiting every entry on each call.
+//!
+//! Neither part counts allocator overhead, the tag index or the hot-key summaries, so the
+//! estimate is a floor on what the store holds, meant for trends and alerts rather than
+//! exact RSS accounting.
+
+use std::collections::HashMap;
+use std::mem::size_of;
+
+/// Heap memory owned by a value, for the `deep_size` option on the in-memory store builders.
+///
+/// `heap_size` reports the bytes the value owns *outside* its own `size_of`: the stores already
+/// count the inline part of every key and value. Implementations for the standard library
+/// types count allocated capacity (not length) and recurse into their elements.
+///
+/// ```rust
+/// use cached::DeepSize;
+///
+/// struct User {
+///     id: u64,
+///     name: String,
+///     roles: Vec<String>,
+/// }
+///
+/// impl DeepSize for User {
+///     fn heap_size(&self) -> usize {
+///         self.name.heap_size() + self.roles.heap_size()
+///     }
+/// }
+/// ```
+pub trait DeepSize {
+    /// Bytes of heap memory owned by `self`, excluding `size_of_val(self)`.
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! no_heap {
+    ($($t:ty),* $(,)?) => {
+        $(impl DeepSize for $t {
+            #[inline]
+            fn heap_size(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+no_heap!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &'static str,
+    std::time::Duration,
+);
+
+impl DeepSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl DeepSize for Box<str> {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: DeepSize> DeepSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T: DeepSize> DeepSize for Box<[T]> {
+    fn heap_size(&self) -> usize {
+        self.iter().map(|t| size_of::<T>() + t.heap_size()).sum()
+    }
+}
+
+impl<T: DeepSize> DeepSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(DeepSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: DeepSize> DeepSize for std::collections::VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(DeepSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: DeepSize> DeepSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, DeepSize::heap_size)
+    }
+}
+
+impl<T: DeepSize, E: DeepSize> DeepSize for Result<T, E> {
+    fn heap_size(&self) -> usize {
+        match self {
+            Ok(t) => t.heap_size(),
+            Err(e) => e.heap_size(),
+        }
+    }
+}
+
+/// Shared: the pointee is counted where it is owned, not once per handle.
+impl<T: ?Sized> DeepSize for std::sync::Arc<T> {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Shared: the pointee is counted where it is owned, not once per handle.
+impl<T: ?Sized> DeepSize for std::rc::Rc<T> {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: DeepSize, const N: usize> DeepSize for [T; N] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(DeepSize::heap_size).sum()
+    }
+}
+
+macro_rules! tuple_heap {
+    ($($name:ident)+) => {
+        impl<$($name: DeepSize),+> DeepSize for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn heap_size(&self) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.heap_size())+
+            }
+        }
+    };
+}
+
+tuple_heap!(A);
+tuple_heap!(A B);
+tuple_heap!(A B C);
+tuple_heap!(A B C D);
+tuple_heap!(A B C D E);
+tuple_heap!(A B C D E F);
+
+/// What a builder's `deep_size` stores: the [`DeepSize`] impls of `K` and `V` as plain
+/// function pointers, so the stores themselves need no `DeepSize` bound.
+pub(crate) struct HeapSize<K, V> {
+    pub(crate) key: fn(&K) -> usize,
+    pub(crate) value: fn(&V) -> usize,
+}
+
+impl<K: DeepSize, V: DeepSize> HeapSize<K, V> {
+    pub(crate) fn of() -> Self {
+        Self {
+            key: K::heap_size,
+            value: V::heap_size,
+        }
+    }
+}
+
+impl<K, V> HeapSize<K, V> {
+    /// Heap bytes owned by one entry's key and value.
+    pub(crate) fn entry(&self, key: &K, value: &V) -> usize {
+        (self.key)(key) + (self.value)(value)
+    }
+}
+
+impl<K, V> Clone for HeapSize<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for HeapSize<K, V> {}
+
+/// Control bytes past the last bucket, so a SIMD group load never reads out of bounds.
+const GROUP_WIDTH: usize = 16;
+
+/// Estimated allocation of a `std::collections::HashMap`, from its capacity.
+///
+/// The standard map does not expose its bucket count, so this inverts its load factor: a
+/// table of `b` buckets holds `b - 1` entries below eight buckets and `7b / 8` from there on.
+pub(crate) fn hash_map_bytes<K, V, S>(map: &HashMap<K, V, S>) -> usize {
+    let capacity = map.capacity();
+    if capacity == 0 {
+        return 0;
+    }
+    let buckets = if capacity < 8 {
+        (capacity + 1).next_power_of_two()
+    } else {
+        (capacity * 8 / 7).next_power_of_two()
+    };
+    hash_table_bytes::<(K, V)>(buckets)
+}
+
+/// Allocation of a hashbrown table of `buckets` slots holding `T`, with a control byte each.
+pub(crate) fn hash_table_bytes<T>(buckets: usize) -> usize {
+    if buckets == 0 {
+        return 0;
+    }
+    buckets * size_of::<T>() + buckets + GROUP_WIDTH
+}
+
+/// Estimated allocation of a `BTreeSet` of `len` elements: leaf nodes of eleven slots,
+/// assumed about two thirds full, plus a parent pointer and lengths per node.
+#[cfg(feature = "time_stores")]
+pub(crate) fn btree_set_bytes<T>(len: usize) -> usize {
+    const NODE_CAPACITY: usize = 11;
+    const NODE_HEADER: usize = size_of::<usize>() + 2 * size_of::<u16>();
+    let nodes = len.div_ceil(NODE_CAPACITY * 2 / 3);
+    nodes * (NODE_CAPACITY * size_of::<T>() + NODE_HEADER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_map_estimate_tracks_the_bucket_count() {
+        let empty: HashMap<u64, u64> = HashMap::new();
+        assert_eq!(hash_map_bytes(&empty), 0);
+
+        let mut map: HashMap<u64, u64> = HashMap::with_capacity(100);
+        // 100 entries need 128 buckets at a 7/8 load factor.
+        assert!(map.capacity() >= 100);
+        let before = hash_map_bytes(&map);
+        assert_eq!(before, 128 * 16 + 128 + GROUP_WIDTH);
+        map.extend((0..50).map(|i| (i, i)));
+        assert_eq!(
+            hash_map_bytes(&map),
+            before,
+            "filling spare capacity allocates nothing"
+        );
+    }
+
+    #[test]
+    fn deep_size_counts_capacity_and_recurses() {
+        let s = String::with_capacity(32);
+        assert_eq!(s.heap_size(), 32);
+        let v: Vec<String> = vec!["ab".to_string(), "cde".to_string()];
+        assert_eq!(v.heap_size(), 2 * size_of::<String>() + 2 + 3);
+        assert_eq!((7u32, Some("x".to_string())).heap_size(), 1);
+        assert_eq!(Box::new(5u64).heap_size(), 8);
+        assert_eq!(std::sync::Arc::new("shared".to_string()).heap_size(), 0);
+    }
+}
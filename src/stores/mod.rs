@@ -146,16 +146,44 @@ fn thread_stripe() -> usize {
 #[cfg(feature = "async_core")]
 use {super::CachedGetOrSetAsync, std::future::Future};
 
+/// Evaluate `$body` inside a `$name` span: a step of an IO store operation (the round trip or
+/// transaction, (de)serialization, a self-heal delete). Defined ahead of the store modules so
+/// it is in scope there; without the `tracing` feature it expands to `$body` alone.
+#[cfg(any(feature = "redb_store", feature = "redis_store"))]
+macro_rules! traced {
+    ($name:literal, $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _entered = tracing::debug_span!($name).entered();
+        $body
+    }};
+}
+
+mod arc;
+mod bucket_map;
 mod expiring;
 mod expiring_lru;
+#[cfg(feature = "metrics")]
+mod exporter;
+mod faulty;
+mod hot_keys;
+mod lfu;
 mod lru;
 #[cfg(feature = "time_stores")]
 mod lru_ttl;
+mod memory;
+mod policy;
 #[cfg(feature = "redb_store")]
 mod redb;
 #[cfg(feature = "redis_store")]
 mod redis;
+mod s3fifo;
 pub mod sharded;
+#[cfg(feature = "persist")]
+mod snapshot;
+#[cfg(any(feature = "redb_store", feature = "redis_store"))]
+mod stored;
+mod tags;
+mod timer_wheel;
 #[cfg(feature = "time_stores")]
 mod ttl;
 #[cfg(feature = "time_stores")]
@@ -173,6 +201,30 @@ use crate::time::Instant;
 
 pub(super) type OnEvict<K, V> = std::sync::Arc<dyn Fn(&K, &V) + Send + Sync>;
 
+/// The exporter a store was built with (`metrics_name`). Zero-sized without the `metrics`
+/// feature, so stores and builders carry the field unconditionally and only the builder
+/// method that sets it is gated.
+#[cfg(feature = "metrics")]
+pub(super) type ExporterSlot = Option<MetricsExporter>;
+#[cfg(not(feature = "metrics"))]
+#[derive(Clone, Debug, Default)]
+pub(super) struct ExporterSlot(());
+
+/// Count one lookup on a store's exporter. The Redis and redb stores keep no hit/miss counters
+/// for `CacheMetrics`, so they report each lookup as it happens instead.
+#[cfg(any(feature = "redb_store", feature = "redis_store"))]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(super) fn record_lookup(slot: &ExporterSlot, hit: bool) {
+    #[cfg(feature = "metrics")]
+    if let Some(exporter) = slot {
+        if hit {
+            exporter.record_hit();
+        } else {
+            exporter.record_miss();
+        }
+    }
+}
+
 /// Error returned by cache builder `build()` methods.
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -233,6 +285,80 @@ impl std::fmt::Display for SetMaxSizeError {
 
 impl std::error::Error for SetMaxSizeError {}
 
+/// Error returned by `pin` and `set_pinned` on the LRU-family stores.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinError {
+    /// The store already holds `max_pinned` pinned entries; nothing was pinned or stored.
+    LimitReached {
+        /// The configured cap on pinned entries.
+        max_pinned: usize,
+    },
+}
+
+impl std::fmt::Display for PinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PinError::LimitReached { max_pinned } => {
+                write!(
+                    f,
+                    "the store already holds max_pinned = {max_pinned} pinned entries"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PinError {}
+
+/// Error returned by `reshard` on the sharded stores.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReshardError {
+    /// A shard count of zero was supplied.
+    ZeroShards,
+    /// The shard count, rounded up to a power of two, overflows `usize`.
+    ShardCountOverflow,
+    /// The store's capacity cannot be re-split across the new shard count without overflowing
+    /// `usize`; see [`SetMaxSizeError::CapacityOverflow`].
+    CapacityOverflow,
+    /// `reshard` was called on the thread already running a whole-cache call (`len`, `clear`,
+    /// `retain`, ...) or another reshard on the same store, typically from an `on_evict`
+    /// callback. Waiting for that call to finish would deadlock, so nothing was moved.
+    Reentrant,
+}
+
+impl std::fmt::Display for ReshardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReshardError::ZeroShards => write!(f, "shard count must be >= 1"),
+            ReshardError::ShardCountOverflow => {
+                write!(f, "rounded shard count overflows usize")
+            }
+            ReshardError::CapacityOverflow => {
+                write!(f, "effective sharded capacity overflows usize")
+            }
+            ReshardError::Reentrant => write!(
+                f,
+                "reshard called from inside a whole-cache operation or reshard on the same cache"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReshardError {}
+
+/// Builder validation shared by every store with a `max_pinned` option.
+pub(crate) fn check_max_pinned(max_pinned: Option<usize>) -> Result<(), BuildError> {
+    if max_pinned == Some(0) {
+        return Err(BuildError::InvalidValue {
+            field: "max_pinned",
+            reason: "must be greater than zero",
+        });
+    }
+    Ok(())
+}
+
 /// Error returned by [`CacheTtl::try_set_ttl`](crate::CacheTtl::try_set_ttl).
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -261,6 +387,8 @@ impl std::error::Error for SetTtlError {}
 /// [`Deref`](std::ops::Deref)s to `V`, so read access is transparent; metadata is
 /// exposed through typed accessors (e.g.
 /// [`expires_at`](CacheValue::expires_at)) that exist only for the carrying `M`.
+/// Whether the entry is pinned is reported for every `M` by
+/// [`is_pinned`](CacheValue::is_pinned).
 /// [`Display`](std::fmt::Display) forwards to the wrapped value, so `println!("{v}")`
 /// works directly on an order-method result.
 ///
@@ -281,11 +409,28 @@ impl std::error::Error for SetTtlError {}
 pub struct CacheValue<V, M = ()> {
     value: V,
     meta: M,
+    pinned: bool,
 }
 
 impl<V, M> CacheValue<V, M> {
     pub(crate) fn new(value: V, meta: M) -> Self {
-        Self { value, meta }
+        Self {
+            value,
+            meta,
+            pinned: false,
+        }
+    }
+
+    pub(crate) fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Whether the entry was pinned, and so exempt from capacity eviction, when the order
+    /// method ran.
+    #[must_use]
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
     }
 
     /// Shared reference to the wrapped value.
@@ -387,7 +532,9 @@ impl<K, V, M> IntoValues<V> for Vec<(K, CacheValue<V, M>)> {
 /// entries it selected, removed from the map.
 ///
 /// Shared by every `HashMap`-backed store, single-owner and sharded alike, so the ordering
-/// assumption below lives in exactly one place.
+/// assumption below lives in exactly one place. The stores on a
+/// [`BucketMap`](bucket_map::BucketMap) take the same two passes, tied together by bucket
+/// index instead of position.
 ///
 /// # Why two passes
 ///
@@ -431,6 +578,20 @@ where
         .collect()
 }
 
+/// Move a single-owner store's budgeted-sweep `cursor` on after a step that examined
+/// `visited` of the `budget` entries it was allowed and stopped before position `next`.
+/// Returns whether the sweep finished, in which case the cursor goes back to the start.
+pub(crate) fn advance_evict_cursor(
+    cursor: &mut usize,
+    visited: usize,
+    budget: usize,
+    next: usize,
+) -> bool {
+    let finished = visited < budget;
+    *cursor = if finished { 0 } else { next };
+    finished
+}
+
 /// Validate that `ttl` is non-zero; used by all TTL-capable store builders.
 #[cfg(any(
     feature = "time_stores",
@@ -479,18 +640,48 @@ impl<V: Clone> Clone for TimedEntry<V> {
 
 #[cfg(feature = "redb_store")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redb_store")))]
-pub use crate::stores::redb::{RedbCache, RedbCacheBuildError, RedbCacheBuilder, RedbCacheError};
+pub use crate::stores::redb::{
+    RedbCache, RedbCacheBuildError, RedbCacheBuilder, RedbCacheError, RedbIter, RedbKeys,
+};
 #[cfg(feature = "redis_store")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redis_store")))]
 pub use crate::stores::redis::{
-    ConnectionString, RedisCache, RedisCacheBuildError, RedisCacheBuilder, RedisCacheError,
+    ConnectionString, MockRedisCache, MockRedisCacheBuilder, MockRedisFault, MockRedisServer,
+    RedisCache, RedisCacheBuildError, RedisCacheBuilder, RedisCacheError, RedisScan, RedisSizeMode,
 };
+pub use arc::{ArcCache, ArcCacheBuilder};
 pub use expiring::{ExpiringCache, ExpiringCacheBuilder};
 pub use expiring_lru::{Expires, ExpiringLruCache, ExpiringLruCacheBuilder};
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub use exporter::MetricsExporter;
+pub use faulty::{FaultyCache, FaultyCacheBuilder, InjectedFault};
+pub use hot_keys::HotKey;
+pub(crate) use hot_keys::{HotKeyConfig, HotKeys};
+pub use lfu::{LfuCache, LfuCacheBuilder};
 pub use lru::{LruCache, LruCacheBuilder};
 #[cfg(feature = "time_stores")]
 #[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
 pub use lru_ttl::{HasEvict, LruTtlCache, LruTtlCacheBuilder, NoEvict};
+pub use memory::DeepSize;
+#[cfg(feature = "time_stores")]
+pub(crate) use memory::btree_set_bytes;
+pub(crate) use memory::{HeapSize, hash_map_bytes, hash_table_bytes};
+pub use policy::{EvictionPolicy, LruPolicy, PolicyCache, PolicyCacheBuilder};
+pub use s3fifo::{S3FifoCache, S3FifoCacheBuilder};
+#[cfg(feature = "persist")]
+pub(crate) use snapshot::SnapshotClock;
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+pub use snapshot::{
+    CacheSnapshot, CachedSnapshot, ConcurrentCachedSnapshot, PersistError, SnapshotEntry,
+};
+#[cfg(any(feature = "redb_store", feature = "redis_store"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "redb_store", feature = "redis_store"))))]
+pub use stored::StoredEntry;
+pub(crate) use tags::TagIndex;
+pub use tags::{CachedTags, ConcurrentCachedTags};
+pub(crate) use timer_wheel::{TimerWheel, TimerWheelConfig};
 #[cfg(feature = "time_stores")]
 #[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
 pub use ttl::{TtlCache, TtlCacheBuilder};
@@ -500,14 +691,17 @@ pub use ttl_sorted::{TtlSortedCache, TtlSortedCacheBuilder, TtlSortedSetBuilder}
 pub use unbound::{UnboundCache, UnboundCacheBuilder};
 
 pub use sharded::{
-    DefaultShardHasher, ShardHasher, ShardedExpiringCache, ShardedExpiringCacheBuilder,
-    ShardedExpiringLruCache, ShardedExpiringLruCacheBuilder, ShardedLruCache,
-    ShardedLruCacheBuilder, ShardedUnboundCache, ShardedUnboundCacheBuilder,
+    DefaultShardHasher, ShardHasher, ShardStats, ShardedArcCache, ShardedArcCacheBuilder,
+    ShardedExpiringCache, ShardedExpiringCacheBuilder, ShardedExpiringLruCache,
+    ShardedExpiringLruCacheBuilder, ShardedLfuCache, ShardedLfuCacheBuilder, ShardedLruCache,
+    ShardedLruCacheBuilder, ShardedPolicyCache, ShardedPolicyCacheBuilder, ShardedS3FifoCache,
+    ShardedS3FifoCacheBuilder, ShardedUnboundCache, ShardedUnboundCacheBuilder,
 };
 #[cfg(feature = "time_stores")]
 #[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
 pub use sharded::{
     ShardedLruTtlCache, ShardedLruTtlCacheBuilder, ShardedTtlCache, ShardedTtlCacheBuilder,
+    ShardedTtlSortedCache, ShardedTtlSortedCacheBuilder, ShardedTtlSortedSetBuilder,
 };
 
 // Canonical `AsyncRedisCache` availability gate (kept in sync with src/lib.rs and
@@ -715,6 +909,72 @@ pub trait CacheEvict {
     /// inherent `evict(&self)` method.
     #[must_use]
     fn evict(&mut self) -> usize;
+
+    /// Take one step of an incremental sweep: examine at most `max_entries` entries, remove
+    /// the expired ones among them, and report how many were removed and whether the sweep
+    /// reached the end of the store.
+    ///
+    /// The store keeps a cursor between calls, so repeated calls walk the whole store a
+    /// bounded slice at a time; the call after a finished sweep starts a new one. Entries
+    /// written while a sweep is in progress may be missed by it, and are picked up by the
+    /// next. Removals fire `on_evict` and count toward `cache_evictions()` as with
+    /// [`evict`](Self::evict).
+    ///
+    /// The default runs a full [`evict`](Self::evict) and reports the sweep finished. Every
+    /// store in this crate overrides it.
+    #[must_use]
+    fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        let _ = max_entries;
+        EvictProgress {
+            reclaimed: self.evict(),
+            finished: true,
+        }
+    }
+
+    /// Take [`evict_budgeted`](Self::evict_budgeted) steps until the sweep finishes or
+    /// `budget` has passed, and report their total.
+    ///
+    /// The clock is read between steps of a few hundred entries, so the call always takes
+    /// at least one step and can run over `budget` by up to one step.
+    #[must_use]
+    fn evict_for(&mut self, budget: crate::time::Duration) -> EvictProgress {
+        evict_for_steps(budget, |max_entries| self.evict_budgeted(max_entries))
+    }
+}
+
+/// How far a budgeted eviction sweep got, as returned by
+/// [`CacheEvict::evict_budgeted`] and [`ConcurrentCacheEvict::evict_budgeted`] and their
+/// `evict_for` counterparts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EvictProgress {
+    /// Expired entries removed.
+    pub reclaimed: usize,
+    /// `true` if the sweep reached the end of the store, so the next call starts a new one;
+    /// `false` if it stopped on its budget and the next call resumes where it stopped.
+    pub finished: bool,
+}
+
+/// Entries examined per step by `evict_for`.
+const EVICT_FOR_STEP: usize = 256;
+
+/// Run budgeted eviction `step`s until one finishes the sweep or `budget` has passed.
+fn evict_for_steps(
+    budget: crate::time::Duration,
+    mut step: impl FnMut(usize) -> EvictProgress,
+) -> EvictProgress {
+    let start = crate::time::Instant::now();
+    let mut total = EvictProgress::default();
+    loop {
+        let progress = step(EVICT_FOR_STEP);
+        total.reclaimed += progress.reclaimed;
+        if progress.finished {
+            total.finished = true;
+            return total;
+        }
+        if start.elapsed() >= budget {
+            return total;
+        }
+    }
 }
 
 /// Concurrent counterpart of [`CacheEvict`] for internally-synchronized stores.
@@ -725,8 +985,8 @@ pub trait CacheEvict {
 ///
 /// `evict()` is the explicit way to physically remove expired entries, reclaim
 /// memory, and obtain an accurate live count on the sharded expiry-capable stores
-/// (`ShardedTtlCache`, `ShardedLruTtlCache`, `ShardedExpiringCache`,
-/// `ShardedExpiringLruCache`). After calling `evict()`, `len()` (the inherent method)
+/// (`ShardedTtlCache`, `ShardedTtlSortedCache`, `ShardedLruTtlCache`, `ShardedExpiringCache`,
+/// `ShardedExpiringLruCache`, `ShardedPolicyCache`). After calling `evict()`, `len()` (the inherent method)
 /// reflects only live entries.
 pub trait ConcurrentCacheEvict {
     /// Physically remove all expired entries across all shards and return the count removed.
@@ -735,6 +995,38 @@ pub trait ConcurrentCacheEvict {
     /// entries. Fires `on_evict` and increments `cache_evictions()` for each removed entry.
     #[must_use]
     fn evict(&self) -> usize;
+
+    /// Take one step of an incremental sweep across the shards: examine at most
+    /// `max_entries` entries, remove the expired ones among them, and report how many were
+    /// removed and whether the sweep reached the end of the last shard.
+    ///
+    /// The store keeps a cursor (a shard and a position within it) between calls, so each
+    /// call holds a shard's lock for at most `max_entries` entries and repeated calls cover
+    /// every shard in turn; the call after a finished sweep starts a new one from the first
+    /// shard. Entries written while a sweep is in progress may be missed by it, and are
+    /// picked up by the next. Concurrent calls share the one cursor. `on_evict` fires after
+    /// each shard's lock is released.
+    ///
+    /// The default runs a full [`evict`](Self::evict) and reports the sweep finished. Every
+    /// store in this crate overrides it.
+    #[must_use]
+    fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        let _ = max_entries;
+        EvictProgress {
+            reclaimed: self.evict(),
+            finished: true,
+        }
+    }
+
+    /// Take [`evict_budgeted`](Self::evict_budgeted) steps until the sweep finishes or
+    /// `budget` has passed, and report their total.
+    ///
+    /// The clock is read between steps of a few hundred entries, so the call always takes
+    /// at least one step and can run over `budget` by up to one step.
+    #[must_use]
+    fn evict_for(&self, budget: crate::time::Duration) -> EvictProgress {
+        evict_for_steps(budget, |max_entries| self.evict_budgeted(max_entries))
+    }
 }
 
 #[cfg(test)]
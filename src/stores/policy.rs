@@ -0,0 +1,1076 @@
+use super::{Cached, DefaultHashBuilder};
+use crate::lru_list::LRUList;
+use crate::time::Instant;
+use crate::{CachedIter, CachedPeek};
+use hashbrown::HashTable;
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::time::Duration;
+
+#[cfg(feature = "async_core")]
+use {super::CachedGetOrSetAsync, std::future::Future};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Decides which entry a [`PolicyCache`] evicts when it is full.
+///
+/// The cache owns the keys and values and tells the policy about every change to its key set;
+/// the policy keeps whatever per-key state it needs, usually a clone of the key, and names a
+/// victim on demand. Calls arrive in order, one at a time, under the cache's `&mut self` (or the
+/// shard's write lock in [`ShardedPolicyCache`](crate::ShardedPolicyCache)), so a policy needs
+/// no synchronization of its own.
+///
+/// [`LruPolicy`] is the reference implementation: a `PolicyCache` over it evicts in the same
+/// order as [`LruCache`](crate::LruCache).
+///
+/// # Example
+///
+/// A policy that always evicts the largest key:
+///
+/// ```rust
+/// use cached::{Cached, EvictionPolicy, PolicyCache};
+/// use std::collections::BTreeSet;
+///
+/// #[derive(Clone, Default)]
+/// struct LargestFirst(BTreeSet<u32>);
+///
+/// impl EvictionPolicy<u32> for LargestFirst {
+///     fn record_insert(&mut self, key: &u32) {
+///         self.0.insert(*key);
+///     }
+///     fn record_access(&mut self, _key: &u32) {}
+///     fn record_remove(&mut self, key: &u32) {
+///         self.0.remove(key);
+///     }
+///     fn choose_victim(&mut self) -> Option<u32> {
+///         self.0.pop_last()
+///     }
+/// }
+///
+/// let mut cache = PolicyCache::new(2, LargestFirst::default());
+/// cache.cache_set(5, "five");
+/// cache.cache_set(1, "one");
+/// cache.cache_set(3, "three"); // evicts 5
+/// assert_eq!(cache.cache_get(&5), None);
+/// assert_eq!(cache.cache_size(), 2);
+/// ```
+pub trait EvictionPolicy<K> {
+    /// `key` entered the cache. Called once per new key, after any eviction it caused.
+    fn record_insert(&mut self, key: &K);
+
+    /// `key` was used: a hit or an overwrite. Peeks and `cache_contains` are not reported.
+    fn record_access(&mut self, key: &K);
+
+    /// `key` left the cache other than as a victim: an explicit removal, `retain`, expiry, a
+    /// tag invalidation or a clear.
+    fn record_remove(&mut self, key: &K);
+
+    /// Name the entry to evict from a full cache and stop tracking it.
+    ///
+    /// A key the cache does not hold is skipped and the policy asked again. Returning `None`
+    /// lets the pending insert proceed without an eviction, leaving the cache over its bound
+    /// until a later insert finds a victim.
+    fn choose_victim(&mut self) -> Option<K>;
+}
+
+/// Least-recently-used [`EvictionPolicy`]: hits and overwrites make a key the most recent, and
+/// the least recent key is the victim. Each operation is O(1).
+#[derive(Clone)]
+pub struct LruPolicy<K, S = DefaultHashBuilder> {
+    /// Maps a hash of K -> index of the key in `order`.
+    index: HashTable<usize>,
+    /// Keys from the most to the least recently used.
+    order: LRUList<K>,
+    hash_builder: S,
+}
+
+impl<K> LruPolicy<K> {
+    /// An empty policy with the default hasher.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_hasher(super::new_default_hash_builder())
+    }
+}
+
+impl<K> Default for LruPolicy<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, S> LruPolicy<K, S> {
+    /// An empty policy hashing keys with `hash_builder`.
+    #[must_use]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            index: HashTable::new(),
+            order: LRUList::with_capacity(0),
+            hash_builder,
+        }
+    }
+}
+
+impl<K: Hash + Eq, S: BuildHasher> LruPolicy<K, S> {
+    /// The tracked keys, most recently used first.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.order.iter()
+    }
+
+    fn find(&self, hash: u64, key: &K) -> Option<usize> {
+        self.index
+            .find(hash, |&i| self.order.get(i) == key)
+            .copied()
+    }
+}
+
+impl<K: Hash + Eq + Clone, S: BuildHasher> EvictionPolicy<K> for LruPolicy<K, S> {
+    fn record_insert(&mut self, key: &K) {
+        let hash = self.hash_builder.hash_one(key);
+        if let Some(i) = self.find(hash, key) {
+            self.order.move_to_front(i);
+            return;
+        }
+        let i = self.order.push_front(key.clone());
+        let order = &self.order;
+        let hash_builder = &self.hash_builder;
+        self.index
+            .insert_unique(hash, i, |&i| hash_builder.hash_one(order.get(i)));
+    }
+
+    fn record_access(&mut self, key: &K) {
+        if let Some(i) = self.find(self.hash_builder.hash_one(key), key) {
+            self.order.move_to_front(i);
+        }
+    }
+
+    fn record_remove(&mut self, key: &K) {
+        let hash = self.hash_builder.hash_one(key);
+        let order = &self.order;
+        if let Ok(entry) = self.index.find_entry(hash, |&i| order.get(i) == key) {
+            let (i, _) = entry.remove();
+            self.order.remove(i);
+        }
+    }
+
+    fn choose_victim(&mut self) -> Option<K> {
+        if self.index.is_empty() {
+            return None;
+        }
+        let i = self.order.back();
+        let hash = self.hash_builder.hash_one(self.order.get(i));
+        if let Ok(entry) = self.index.find_entry(hash, |&j| j == i) {
+            entry.remove();
+        }
+        Some(self.order.remove(i))
+    }
+}
+
+impl<K, S> fmt::Debug for LruPolicy<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruPolicy")
+            .field("len", &self.index.len())
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+struct Slot<V> {
+    value: V,
+    /// `None` = never expires.
+    expires_at: Option<Instant>,
+}
+
+impl<V> Slot<V> {
+    fn live_at(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|at| now < at)
+    }
+}
+
+/// Bounded Cache with a Pluggable Eviction Policy
+///
+/// Stores up to `max_size` entries and, when full, evicts the key chosen by the
+/// [`EvictionPolicy`] `P`. The cache handles storage, the optional TTL, hit/miss/eviction
+/// counters, `on_evict` and the trait impls; the policy only ranks keys. See
+/// [`EvictionPolicy`] for when each of its methods is called.
+///
+/// With [`ttl`](PolicyCacheBuilder::ttl), entries expire that long after they were last
+/// written. An expired entry is a miss; lookups remove it lazily, and
+/// [`evict`](PolicyCache::evict) sweeps all of them. Expiry and capacity evictions both fire
+/// `on_evict`.
+///
+/// Iteration follows the hash table, not the policy. `K` must be `Clone` for the
+/// [`CachedTags`](super::CachedTags) impl only.
+///
+/// Note: This cache is in-memory only
+///
+/// # Example
+///
+/// ```rust
+/// use cached::{Cached, LruPolicy, PolicyCache};
+///
+/// let mut cache = PolicyCache::new(2, LruPolicy::new());
+/// cache.cache_set(1, "a");
+/// cache.cache_set(2, "b");
+/// cache.cache_get(&1);
+/// cache.cache_set(3, "c"); // evicts 2, the least recently used
+/// assert_eq!(cache.cache_get(&2), None);
+/// ```
+pub struct PolicyCache<K, V, P, S = DefaultHashBuilder> {
+    store: HashTable<(K, Slot<V>)>,
+    hash_builder: S,
+    policy: P,
+    capacity: usize,
+    ttl: Option<Duration>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// When false, lookups skip incrementing `hits` and `misses`. The sharded store keeps its
+    /// own per-shard counters.
+    pub(crate) track_hit_miss: bool,
+    tags: super::TagIndex<K>,
+    metrics_exporter: super::ExporterSlot,
+    /// Where the next [`evict_budgeted`](Self::evict_budgeted) step starts, as a bucket of
+    /// `store`.
+    evict_cursor: usize,
+}
+
+impl<K, V, P, S> Clone for PolicyCache<K, V, P, S>
+where
+    K: Clone,
+    V: Clone,
+    P: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            hash_builder: self.hash_builder.clone(),
+            policy: self.policy.clone(),
+            capacity: self.capacity,
+            ttl: self.ttl,
+            hits: AtomicU64::new(self.hits.load(Ordering::Relaxed)),
+            misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
+            evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
+            on_evict: self.on_evict.clone(),
+            track_hit_miss: self.track_hit_miss,
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            evict_cursor: self.evict_cursor,
+        }
+    }
+}
+
+impl<K, V, P, S> fmt::Debug for PolicyCache<K, V, P, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PolicyCache")
+            .field("capacity", &self.capacity)
+            .field("size", &self.store.len())
+            .field("ttl", &self.ttl)
+            .field("policy", &std::any::type_name::<P>())
+            .field("hits", &self.hits.load(Ordering::Relaxed))
+            .field("misses", &self.misses.load(Ordering::Relaxed))
+            .field("evictions", &self.evictions.load(Ordering::Relaxed))
+            .field("on_evict", &self.on_evict.as_ref().map(|_| "on_evict"))
+            .finish()
+    }
+}
+
+/// Builder for [`PolicyCache`].
+pub struct PolicyCacheBuilder<K, V, P, S = DefaultHashBuilder> {
+    size: Option<usize>,
+    policy: Option<P>,
+    ttl: Option<Duration>,
+    on_evict: Option<super::OnEvict<K, V>>,
+    hasher: S,
+    metrics_exporter: super::ExporterSlot,
+}
+
+impl<K, V, P> Default for PolicyCacheBuilder<K, V, P, DefaultHashBuilder> {
+    fn default() -> Self {
+        Self {
+            size: None,
+            policy: None,
+            ttl: None,
+            on_evict: None,
+            hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+        }
+    }
+}
+
+impl<K, V, P> PolicyCacheBuilder<K, V, P> {
+    /// Create a builder with default settings. Equivalent to [`PolicyCache::builder`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, P, S> PolicyCacheBuilder<K, V, P, S> {
+    /// Set the maximum number of entries. Required -- `build` returns `Err` if not set.
+    #[doc(alias = "size")]
+    #[doc(alias = "capacity")]
+    #[must_use]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.size = Some(max_size);
+        self
+    }
+
+    /// Set the eviction policy. Required -- `build` returns `Err` if not set. The policy
+    /// should be empty; the cache reports only the keys it inserts itself.
+    #[must_use]
+    pub fn policy(mut self, policy: P) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Expire entries `ttl` after they were last written. Unset by default: entries leave only
+    /// as victims or by removal. A zero `ttl` disables expiry.
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set a callback to be invoked when an entry is evicted, expires or is removed.
+    ///
+    /// Use [`cache_clear_with_on_evict`](PolicyCache::cache_clear_with_on_evict)
+    /// instead of [`cache_clear`](crate::Cached::cache_clear) to opt into callback
+    /// firing and eviction counter increments when clearing all entries.
+    #[must_use]
+    pub fn on_evict(mut self, on_evict: impl Fn(&K, &V) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(on_evict));
+        self
+    }
+
+    /// Switch to a custom hash builder `S2`, returning a builder parameterized on `S2`.
+    #[doc(alias = "with_hasher")]
+    #[must_use]
+    pub fn hasher<S2: BuildHasher>(self, hasher: S2) -> PolicyCacheBuilder<K, V, P, S2> {
+        PolicyCacheBuilder {
+            size: self.size,
+            policy: self.policy,
+            ttl: self.ttl,
+            on_evict: self.on_evict,
+            hasher,
+            metrics_exporter: self.metrics_exporter,
+        }
+    }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Build the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError::MissingRequired`](super::BuildError::MissingRequired) if
+    /// `max_size` or `policy` was not set, or
+    /// [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if `max_size` is `0`, or
+    /// capacity pre-allocation fails.
+    pub fn build(self) -> Result<PolicyCache<K, V, P, S>, super::BuildError>
+    where
+        K: Hash + Eq,
+        P: EvictionPolicy<K>,
+        S: BuildHasher,
+    {
+        let size = self
+            .size
+            .ok_or(super::BuildError::MissingRequired("max_size"))?;
+        if size == 0 {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "must be greater than zero",
+            });
+        }
+        let policy = self
+            .policy
+            .ok_or(super::BuildError::MissingRequired("policy"))?;
+        let mut store = HashTable::new();
+        if store.try_reserve(size, |_: &(K, Slot<V>)| 0).is_err() {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "allocation failed",
+            });
+        }
+        Ok(PolicyCache {
+            store,
+            hash_builder: self.hasher,
+            policy,
+            capacity: size,
+            ttl: self.ttl.filter(|ttl| !ttl.is_zero()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            on_evict: self.on_evict,
+            track_hit_miss: true,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            evict_cursor: 0,
+        })
+    }
+}
+
+impl<K: Hash + Eq, V, P: EvictionPolicy<K>> PolicyCache<K, V, P> {
+    /// Construct a ready-to-use [`PolicyCache`] holding up to `max_size` entries, evicting by
+    /// `policy`, without a TTL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is `0`, or if pre-allocating the backing store fails. Use
+    /// [`builder`](Self::builder) with [`build`](PolicyCacheBuilder::build) to handle those
+    /// cases without panicking.
+    #[must_use]
+    pub fn new(max_size: usize, policy: P) -> Self {
+        Self::builder()
+            .max_size(max_size)
+            .policy(policy)
+            .build()
+            .expect("PolicyCache::new requires a non-zero max_size with a valid allocation")
+    }
+
+    /// Return a builder for constructing a [`PolicyCache`].
+    #[must_use]
+    pub fn builder() -> PolicyCacheBuilder<K, V, P> {
+        PolicyCacheBuilder::default()
+    }
+}
+
+impl<K: Hash + Eq, V, P: EvictionPolicy<K>, S: BuildHasher> PolicyCache<K, V, P, S> {
+    /// Returns the maximum number of entries this cache will hold before evicting.
+    #[doc(alias = "size")]
+    #[doc(alias = "max_size")]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The time-to-live set by [`PolicyCacheBuilder::ttl`], or `None` if entries do not expire.
+    #[must_use]
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// The eviction policy, for inspecting its state.
+    #[must_use]
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Change the maximum number of entries, returning the previous bound as
+    /// `Some(prev_capacity)`, like [`LruCache::set_max_size`](crate::LruCache::set_max_size).
+    /// Shrinking evicts victims chosen by the policy, firing `on_evict`, until the cache fits
+    /// or the policy returns `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0. Use [`try_set_max_size`](PolicyCache::try_set_max_size)
+    /// to validate first and avoid the panic.
+    pub fn set_max_size(&mut self, max_size: usize) -> Option<usize> {
+        assert!(max_size > 0, "max_size must be greater than zero");
+        let prev = self.capacity;
+        self.capacity = max_size;
+        while self.store.len() > max_size && self.evict_victim() {}
+        Some(prev)
+    }
+
+    /// Fallible counterpart of [`set_max_size`](PolicyCache::set_max_size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SetMaxSizeError::ZeroMaxSize`](super::SetMaxSizeError) if `max_size` is 0.
+    pub fn try_set_max_size(
+        &mut self,
+        max_size: usize,
+    ) -> Result<Option<usize>, super::SetMaxSizeError> {
+        if max_size == 0 {
+            return Err(super::SetMaxSizeError::ZeroMaxSize);
+        }
+        Ok(self.set_max_size(max_size))
+    }
+
+    /// Remove every expired entry, firing `on_evict` and counting an eviction for each, and
+    /// return how many were removed. Always 0 without a TTL.
+    #[must_use]
+    pub fn evict(&mut self) -> usize {
+        let removed = self.take_expired();
+        self.notify_evicted(&removed)
+    }
+
+    /// Take one step of an incremental [`evict`](Self::evict): examine at most `max_entries`
+    /// entries from where the last step stopped, remove the expired ones, and report how
+    /// many were removed and whether the sweep reached the end of the table. Without a TTL
+    /// every step reports the sweep finished.
+    ///
+    /// See [`CacheEvict::evict_budgeted`](super::CacheEvict::evict_budgeted) for how steps
+    /// add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&mut self, max_entries: usize) -> super::EvictProgress {
+        let (removed, visited, next) = self.take_expired_window(self.evict_cursor, max_entries);
+        let finished =
+            super::advance_evict_cursor(&mut self.evict_cursor, visited, max_entries, next);
+        super::EvictProgress {
+            reclaimed: self.notify_evicted(&removed),
+            finished,
+        }
+    }
+
+    /// Removes expired entries and entries for which `keep` returns `false`, firing `on_evict`
+    /// and counting an eviction for each, like [`TtlCache::retain`](crate::TtlCache::retain).
+    /// Returns the number of entries removed.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&mut self, mut keep: F) -> usize {
+        let removed = self.take_rejected(&mut keep);
+        self.notify_evicted(&removed)
+    }
+
+    /// Remove all entries and fire the `on_evict` callback for each one, incrementing the
+    /// evictions counter.
+    pub fn cache_clear_with_on_evict(&mut self) {
+        let removed = self.drain_all();
+        self.notify_evicted(&removed);
+    }
+
+    /// Remove every entry, returning the stored pairs and reporting each key to the policy.
+    /// Fires no callback and touches no counters.
+    pub(super) fn drain_all(&mut self) -> Vec<(K, V)> {
+        let drained: Vec<(K, V)> = self
+            .store
+            .drain()
+            .map(|(key, slot)| (key, slot.value))
+            .collect();
+        for (key, _) in &drained {
+            self.policy.record_remove(key);
+        }
+        drained
+    }
+
+    /// Remove the expired entries, returning them. Fires no callback and touches no counters.
+    pub(super) fn take_expired(&mut self) -> Vec<(K, V)> {
+        if self.ttl.is_none() {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        self.take_doomed(|_, slot| !slot.live_at(now))
+    }
+
+    /// Remove the expired entries among the next `budget` entries in bucket order from bucket
+    /// `from`, returning them with how many entries were examined and the bucket the next
+    /// step starts at. A removal never moves another entry to a different bucket. Fires no
+    /// callback and touches no counters.
+    pub(super) fn take_expired_window(
+        &mut self,
+        from: usize,
+        budget: usize,
+    ) -> (Vec<(K, V)>, usize, usize) {
+        if self.ttl.is_none() {
+            return (Vec::new(), 0, 0);
+        }
+        let now = Instant::now();
+        let mut removed = Vec::new();
+        let mut visited = 0;
+        let mut bucket = from;
+        while visited < budget && bucket < self.store.num_buckets() {
+            if let Ok(entry) = self.store.get_bucket_entry(bucket) {
+                visited += 1;
+                if !entry.get().1.live_at(now) {
+                    let ((key, slot), _) = entry.remove();
+                    removed.push((key, slot.value));
+                }
+            }
+            bucket += 1;
+        }
+        for (key, _) in &removed {
+            self.policy.record_remove(key);
+        }
+        (removed, visited, bucket)
+    }
+
+    /// Remove the expired entries and those `keep` rejects, returning them. Fires no callback
+    /// and touches no counters.
+    pub(super) fn take_rejected(&mut self, mut keep: impl FnMut(&K, &V) -> bool) -> Vec<(K, V)> {
+        let now = Instant::now();
+        self.take_doomed(|k, slot| !slot.live_at(now) || !keep(k, &slot.value))
+    }
+
+    /// Remove `k` if present, live or not, returning the stored pair. Fires no callback and
+    /// touches no counters.
+    pub(super) fn pop_raw<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash(k);
+        let ((key, slot), _) = self
+            .store
+            .find_entry(hash, |(key, _)| k == key.borrow())
+            .ok()?
+            .remove();
+        self.policy.record_remove(&key);
+        Some((key, slot.value))
+    }
+
+    /// The live entries, in table order. Used by the sharded store's `retain`.
+    pub(super) fn iter_raw(&self) -> impl Iterator<Item = (&K, &V)> {
+        let now = Instant::now();
+        self.store
+            .iter()
+            .filter(move |(_, slot)| slot.live_at(now))
+            .map(|(k, slot)| (k, &slot.value))
+    }
+
+    pub(super) fn add_evictions(&self, n: u64) {
+        self.evictions.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.hash_builder.hash_one(key)
+    }
+
+    /// Remove the entries `doomed` selects, reporting each key to the policy. Side effects on
+    /// counters and callbacks are left to the caller, after every removal is done.
+    fn take_doomed(&mut self, mut doomed: impl FnMut(&K, &Slot<V>) -> bool) -> Vec<(K, V)> {
+        let removed: Vec<(K, V)> = self
+            .store
+            .extract_if(|(k, slot)| doomed(k, slot))
+            .map(|(key, slot)| (key, slot.value))
+            .collect();
+        for (key, _) in &removed {
+            self.policy.record_remove(key);
+        }
+        removed
+    }
+
+    /// Count and report entries already removed from the store; returns how many there were.
+    fn notify_evicted(&self, removed: &[(K, V)]) -> usize {
+        if !removed.is_empty() {
+            self.evictions
+                .fetch_add(removed.len() as u64, Ordering::Relaxed);
+        }
+        if let Some(on_evict) = &self.on_evict {
+            for (k, v) in removed {
+                on_evict(k, v);
+            }
+        }
+        removed.len()
+    }
+
+    /// Ask the policy for a victim and evict it. Returns `false` if the policy has none.
+    fn evict_victim(&mut self) -> bool {
+        while let Some(victim) = self.policy.choose_victim() {
+            let hash = self.hash(&victim);
+            if let Ok(entry) = self.store.find_entry(hash, |(k, _)| *k == victim) {
+                let ((key, slot), _) = entry.remove();
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+                if let Some(on_evict) = &self.on_evict {
+                    on_evict(&key, &slot.value);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The live value for `key`, reporting a hit to the policy. An expired entry is removed,
+    /// and counts as a miss and an eviction.
+    fn lookup<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash(key);
+        let now = Instant::now();
+        // The hit arm returns a borrow of `store`, which keeps `store` borrowed for the rest of
+        // the function: the miss path below touches the other fields directly rather than
+        // through `&self` helpers.
+        let expired = match self.store.find_entry(hash, |(k, _)| key == k.borrow()) {
+            Ok(entry) if entry.get().1.live_at(now) => {
+                if self.track_hit_miss {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                }
+                let (k, slot) = entry.into_mut();
+                self.policy.record_access(k);
+                return Some(&mut slot.value);
+            }
+            Ok(entry) => {
+                let ((k, slot), _) = entry.remove();
+                self.policy.record_remove(&k);
+                Some((k, slot.value))
+            }
+            Err(_) => None,
+        };
+        if self.track_hit_miss {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some((k, v)) = expired {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(&k, &v);
+            }
+        }
+        None
+    }
+
+    /// Insert or overwrite `key`. Overwriting a live entry is a use and returns its value;
+    /// overwriting an expired one evicts it and counts as a new insert.
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hash(&key);
+        let now = Instant::now();
+        let expires_at = self.ttl.and_then(|ttl| now.checked_add(ttl));
+        if let Some((stored, slot)) = self.store.find_mut(hash, |(k, _)| *k == key) {
+            let old = std::mem::replace(&mut slot.value, value);
+            let was_live = slot.live_at(now);
+            slot.expires_at = expires_at;
+            if was_live {
+                self.policy.record_access(stored);
+                return Some(old);
+            }
+            self.policy.record_remove(stored);
+            self.policy.record_insert(stored);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(&key, &old);
+            }
+            return None;
+        }
+        while self.store.len() >= self.capacity && self.evict_victim() {}
+        self.policy.record_insert(&key);
+        let hash_builder = &self.hash_builder;
+        self.store
+            .insert_unique(hash, (key, Slot { value, expires_at }), |(k, _)| {
+                hash_builder.hash_one(k)
+            });
+        None
+    }
+
+    /// The value for a key known to be present, such as one just inserted.
+    fn inserted(&mut self, key: &K) -> &mut V {
+        let hash = self.hash(key);
+        match self.store.find_mut(hash, |(k, _)| k == key) {
+            Some((_, slot)) => &mut slot.value,
+            None => unreachable!("PolicyCache internal invariant violated: inserted key missing"),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, P: EvictionPolicy<K>, S: BuildHasher> PolicyCache<K, V, P, S> {
+    fn get_or_set<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        if self.lookup(&key).is_none() {
+            self.put(key.clone(), f());
+        }
+        self.inserted(&key)
+    }
+
+    fn try_get_or_set<E, F: FnOnce() -> Result<V, E>>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<&mut V, E> {
+        if self.lookup(&key).is_none() {
+            let value = f()?;
+            self.put(key.clone(), value);
+        }
+        Ok(self.inserted(&key))
+    }
+}
+
+impl<K, V, P, S> Cached<K, V> for PolicyCache<K, V, P, S>
+where
+    K: Hash + Eq + Clone,
+    P: EvictionPolicy<K>,
+    S: BuildHasher,
+{
+    type Error = std::convert::Infallible;
+
+    fn cache_get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lookup(key).map(|v| &*v)
+    }
+
+    fn cache_get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lookup(key)
+    }
+
+    /// Insert or replace a cache entry, returning the previous value if it was live.
+    /// Overwriting a live key reports it to the policy as an access.
+    fn cache_set(&mut self, key: K, val: V) -> Option<V> {
+        self.put(key, val)
+    }
+
+    fn cache_get_or_set_with_mut<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        self.get_or_set(key, f)
+    }
+
+    fn cache_try_get_or_set_with_mut<F: FnOnce() -> Result<V, E>, E>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<&mut V, E> {
+        self.try_get_or_set(key, f)
+    }
+
+    fn cache_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        <Self as Cached<K, V>>::cache_remove_entry(self, k).map(|(_, v)| v)
+    }
+
+    fn cache_remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = self.pop_raw(k)?;
+        self.notify_evicted(std::slice::from_ref(&removed));
+        Some(removed)
+    }
+
+    /// Remove every entry without firing `on_evict`, reporting each key to the policy through
+    /// [`record_remove`](EvictionPolicy::record_remove).
+    fn cache_clear(&mut self) {
+        drop(self.drain_all());
+        self.tags.clear();
+    }
+
+    fn cache_reset(&mut self) {
+        self.cache_clear();
+        self.cache_reset_metrics();
+    }
+
+    fn cache_reset_metrics(&mut self) {
+        self.misses.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+    }
+
+    /// Counts expired entries not yet removed; call [`evict`](PolicyCache::evict) first for a
+    /// live count.
+    fn cache_size(&self) -> usize {
+        self.store.len()
+    }
+
+    fn cache_hits(&self) -> Option<u64> {
+        Some(self.hits.load(Ordering::Relaxed))
+    }
+
+    fn cache_misses(&self) -> Option<u64> {
+        Some(self.misses.load(Ordering::Relaxed))
+    }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_evictions(&self) -> Option<u64> {
+        Some(self.evictions.load(Ordering::Relaxed))
+    }
+
+    fn cache_capacity(&self) -> Option<usize> {
+        Some(self.capacity)
+    }
+
+    /// Peek-based: records no hit/miss metrics and reports nothing to the policy.
+    fn cache_contains<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        CachedPeek::cache_peek(self, k).is_some()
+    }
+}
+
+impl<K, V, P, S> CachedIter<K, V> for PolicyCache<K, V, P, S>
+where
+    K: Hash + Eq,
+    P: EvictionPolicy<K>,
+    S: BuildHasher,
+{
+    /// Yields the live entries in hash-table order, which says nothing about the policy's
+    /// ranking.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)> + 'a
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter_raw()
+    }
+}
+
+impl<K, V, P, S> CachedPeek<K, V> for PolicyCache<K, V, P, S>
+where
+    K: Hash + Eq,
+    P: EvictionPolicy<K>,
+    S: BuildHasher,
+{
+    fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (_, slot) = self
+            .store
+            .find(self.hash(k), |(key, _)| k == key.borrow())?;
+        slot.live_at(Instant::now()).then_some(&slot.value)
+    }
+}
+
+impl<K, V, P, S> super::CachedTags<K, V> for PolicyCache<K, V, P, S>
+where
+    K: Hash + Eq + Clone,
+    P: EvictionPolicy<K>,
+    S: BuildHasher,
+{
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.store.len()) {
+            let mut index = std::mem::replace(&mut self.tags, super::TagIndex::new());
+            index.retain_keys(|k| CachedPeek::cache_peek(self, k).is_some());
+            self.tags = index;
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+impl<K, V, P, S> super::CacheEvict for PolicyCache<K, V, P, S>
+where
+    K: Hash + Eq,
+    P: EvictionPolicy<K>,
+    S: BuildHasher,
+{
+    fn evict(&mut self) -> usize {
+        PolicyCache::evict(self)
+    }
+
+    fn evict_budgeted(&mut self, max_entries: usize) -> super::EvictProgress {
+        PolicyCache::evict_budgeted(self, max_entries)
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, P, S> CachedGetOrSetAsync<K, V> for PolicyCache<K, V, P, S>
+where
+    K: Hash + Eq + Clone + Send,
+    P: EvictionPolicy<K> + Send,
+    S: BuildHasher + Send,
+{
+    fn async_cache_get_or_set_with_mut<'a, F, Fut>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = &'a mut V> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = V> + Send + 'a,
+    {
+        async move {
+            if self.lookup(&k).is_none() {
+                let value = f().await;
+                self.put(k.clone(), value);
+            }
+            self.inserted(&k)
+        }
+    }
+
+    fn async_cache_try_get_or_set_with_mut<'a, F, Fut, E>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = Result<&'a mut V, E>> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        E: 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<V, E>> + Send + 'a,
+    {
+        async move {
+            if self.lookup(&k).is_none() {
+                let value = f().await?;
+                self.put(k.clone(), value);
+            }
+            Ok(self.inserted(&k))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LruCache;
+
+    #[test]
+    fn lru_policy_evicts_in_the_same_order_as_lru_cache() {
+        let mut lru = LruCache::new(8);
+        let mut policy = PolicyCache::new(8, LruPolicy::new());
+        let mut x: u64 = 11;
+        for _ in 0..5000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let key = x % 24;
+            match x % 5 {
+                0 => assert_eq!(lru.cache_remove(&key), policy.cache_remove(&key)),
+                1 | 2 => assert_eq!(lru.cache_get(&key), policy.cache_get(&key)),
+                _ => assert_eq!(lru.cache_set(key, x), policy.cache_set(key, x)),
+            }
+            assert_eq!(policy.policy().keys().count(), policy.cache_size());
+        }
+        let lru_order: Vec<u64> = lru.keys().copied().collect();
+        let policy_order: Vec<u64> = policy.policy().keys().copied().collect();
+        assert_eq!(lru_order, policy_order);
+        assert_eq!(lru.cache_evictions(), policy.cache_evictions());
+    }
+
+    #[test]
+    fn a_policy_without_a_victim_lets_the_cache_overflow() {
+        #[derive(Default)]
+        struct Never;
+        impl EvictionPolicy<u32> for Never {
+            fn record_insert(&mut self, _: &u32) {}
+            fn record_access(&mut self, _: &u32) {}
+            fn record_remove(&mut self, _: &u32) {}
+            fn choose_victim(&mut self) -> Option<u32> {
+                None
+            }
+        }
+        let mut c = PolicyCache::new(2, Never);
+        for k in 0..4 {
+            c.cache_set(k, k);
+        }
+        assert_eq!(c.cache_size(), 4);
+        assert_eq!(c.cache_evictions(), Some(0));
+    }
+}
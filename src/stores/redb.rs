@@ -5,7 +5,10 @@ use crate::{
 };
 use directories::BaseDirs;
 use parking_lot::Mutex;
-use redb::{Builder, Database, Durability, ReadableDatabase, ReadableTable, TableDefinition};
+use redb::{
+    Builder, Database, Durability, ReadableDatabase, ReadableTable, ReadableTableMetadata,
+    TableDefinition,
+};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::io::ErrorKind;
@@ -15,6 +18,26 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use super::StoredEntry;
+use super::stored::{EncodedKey, KeyEncoder, encode_key};
+
+/// Carry the caller's span into a closure handed to [`blocking::unblock`], so the
+/// transaction spans opened on the blocking thread nest under the async operation.
+#[cfg(all(feature = "async", feature = "tracing"))]
+macro_rules! in_current_span {
+    ($f:expr) => {{
+        let span = tracing::Span::current();
+        let f = $f;
+        move || span.in_scope(f)
+    }};
+}
+#[cfg(all(feature = "async", not(feature = "tracing")))]
+macro_rules! in_current_span {
+    ($f:expr) => {
+        $f
+    };
+}
+
 /// The single redb table used for all disk cache entries. Keys are the
 /// stringified cache keys, values are the rmp-serialized [`CachedDiskValue`].
 const TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("cached_disk_cache");
@@ -26,6 +49,8 @@ pub struct RedbCacheBuilder<K, V> {
     disk_dir: Option<PathBuf>,
     cache_name: Option<String>,
     strict_deserialization: bool,
+    key_encoder: Option<KeyEncoder<K>>,
+    metrics_exporter: super::ExporterSlot,
     // fn-pointer phantom — see the rationale on `RedbCache::_phantom`; keeps the
     // type unconditionally `Send + Sync` regardless of `K`/`V`.
     _phantom: PhantomData<fn() -> (K, V)>,
@@ -101,8 +126,9 @@ static DISK_FILE_PREFIX: &str = "cached_disk_cache";
 // name, and the MessagePack layout of `CachedDiskValue`) is stable for the 3.x
 // series: files written by any 3.x release remain readable by every later 3.x
 // release. Values are written with `rmp_serde::to_vec`, never `to_vec_named`, so
-// the layout is a POSITIONAL array of `value` then `created_at`: the field names
-// are not on the wire and the field ORDER is part of the frozen format.
+// the layout is a POSITIONAL array of `value` then `created_at` (then the optional
+// trailing `key` and `ttl`): the field names are not on the wire and the field ORDER
+// is part of the frozen format.
 // Reordering, inserting or removing a field of `CachedDiskValue` reinterprets
 // every stored entry and is a format change like any other. A format change bumps
 // this version (isolating old files rather than corrupting them) and is otherwise
@@ -137,6 +163,8 @@ where
             disk_dir: None,
             cache_name: None,
             strict_deserialization: false,
+            key_encoder: None,
+            metrics_exporter: super::ExporterSlot::default(),
             _phantom: Default::default(),
         }
     }
@@ -259,6 +287,17 @@ where
         self
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// Every `cache_get` counts a hit or a miss as it happens;
+    /// [`publish_metrics`](crate::ConcurrentCacheBase::publish_metrics) adds the entry count.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
     fn default_disk_dir_candidates() -> Vec<PathBuf> {
         let exe_name = std::env::current_exe()
             .ok()
@@ -424,11 +463,31 @@ where
             disk_path,
             connection: Arc::new(db),
             strict_deserialization: self.strict_deserialization,
+            key_encoder: self.key_encoder,
+            metrics_exporter: self.metrics_exporter,
             _phantom: self._phantom,
         })
     }
 }
 
+impl<K: Serialize, V> RedbCacheBuilder<K, V> {
+    /// Set whether each write also stores the original key, so that
+    /// [`RedbCache::iter`] can hand it back through [`StoredEntry::original_key`]
+    /// (default `false`).
+    ///
+    /// The table is keyed by `key.to_string()`, which is not reversible in general. With this
+    /// set, the key is MessagePack-encoded and appended to the stored value as an optional
+    /// third field. Entries written without it keep the two-field layout and stay readable,
+    /// and turning the setting on or off never invalidates existing entries. Releases that
+    /// predate the field read an entry carrying it as corrupt, so do not enable it on a file
+    /// that an older 3.x release still opens.
+    #[must_use]
+    pub fn store_keys(mut self, store: bool) -> Self {
+        self.key_encoder = store.then_some(encode_key::<K> as KeyEncoder<K>);
+        self
+    }
+}
+
 /// The `source` recorded on [`RedbCacheBuildError::Storage`] when a `redb` call
 /// unwinds instead of returning an error. Private: the concrete source type is
 /// explicitly not part of the public API.
@@ -626,6 +685,9 @@ fn reject_if_symlink(path: &Path, what: &str) -> Result<(), std::io::Error> {
 /// [`RedbCacheBuilder::ttl`] / [`RedbCacheBuilder::ttl_secs`] / [`RedbCacheBuilder::ttl_millis`]
 /// at build time, or update it at runtime with [`ConcurrentCacheTtl::set_ttl`].
 ///
+/// [`set_with_ttl`](RedbCache::set_with_ttl) stores an entry with its own TTL, which it
+/// keeps whatever the cache's TTL is later set to.
+///
 /// TTL liveness is evaluated against **wall-clock time** (`SystemTime`): redb stores each
 /// entry's `created_at` and liveness is recomputed at read time as `now - created_at < ttl`.
 /// Unlike the in-memory stores, which use a monotonic clock, this is sensitive to system-clock
@@ -662,7 +724,10 @@ fn reject_if_symlink(path: &Path, what: &str) -> Result<(), std::io::Error> {
 /// names absent from the wire. Field order is part of the frozen layout:
 /// reordering, inserting or removing a field of the stored value reinterprets
 /// every existing entry, so it is a format change and must bump the embedded
-/// version, exactly as a type change would.
+/// version, exactly as a type change would. The one exception is the optional
+/// trailing fields, the original key written under [`RedbCacheBuilder::store_keys`]
+/// and the TTL written by [`RedbCache::set_with_ttl`]: they are omitted when unset,
+/// so entries without them are exactly the two-element form.
 pub struct RedbCache<K, V> {
     pub(super) ttl: Mutex<Option<Duration>>,
     pub(super) refresh: AtomicBool,
@@ -670,11 +735,15 @@ pub struct RedbCache<K, V> {
     disk_path: PathBuf,
     connection: Arc<Database>,
     strict_deserialization: bool,
+    /// Set by [`RedbCacheBuilder::store_keys`]. A fn pointer, so it is `Send + Sync` like
+    /// the phantom below, but it makes the type invariant rather than covariant in `K`.
+    key_encoder: Option<KeyEncoder<K>>,
+    metrics_exporter: super::ExporterSlot,
     // `RedbCache`/`RedbCacheBuilder` own no live `K`/`V` (values are serialized
     // to disk; `K`/`V` only appear in method signatures). Use a fn-pointer
     // phantom so the type is unconditionally `Send + Sync` and does not impose
-    // `K: Sync`/`V: Sync` on callers (e.g. the async impl). Variance is
-    // unchanged: covariant in `K` and `V`, same as `PhantomData<(K, V)>`.
+    // `K: Sync`/`V: Sync` on callers (e.g. the async impl). It is covariant in
+    // `K` and `V`, same as `PhantomData<(K, V)>`.
     _phantom: PhantomData<fn() -> (K, V)>,
 }
 
@@ -715,6 +784,32 @@ where
         &self.disk_path
     }
 
+    /// Insert `key`/`value` to expire `ttl` after now instead of after the cache's TTL,
+    /// returning the previous live value like [`cache_set`](ConcurrentCached::cache_set). A
+    /// zero `ttl` stores the entry without expiry, whatever the cache's TTL.
+    ///
+    /// The TTL is stored with the entry, so unlike the cache's own it is not retroactive:
+    /// [`set_ttl`](ConcurrentCacheTtl::set_ttl) and
+    /// [`unset_ttl`](ConcurrentCacheTtl::unset_ttl) leave this entry's lifetime alone. A hit
+    /// with [`refresh_on_hit`](RedbCacheBuilder::refresh_on_hit) re-arms it with the cache's
+    /// TTL, as the in-memory stores do.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redb.set", level = "debug", skip_all, fields(path = %self.disk_path.display())))]
+    pub fn set_with_ttl(
+        &self,
+        key: K,
+        value: V,
+        ttl: Duration,
+    ) -> Result<Option<V>, RedbCacheError> {
+        let serialized = self.encode_entry(&key, &value, Some(ttl))?;
+        disk_cache_set(
+            &self.connection,
+            &key.to_string(),
+            serialized,
+            *self.ttl.lock(),
+            self.durable,
+        )
+    }
+
     /// Remove all entries whose TTL has elapsed, returning the number of entries
     /// removed. This is `RedbCache`'s counterpart to the in-memory stores'
     /// [`ConcurrentCacheEvict::evict`](crate::ConcurrentCacheEvict::evict) (which also
@@ -747,6 +842,208 @@ where
     pub fn flush(&self) -> Result<(), RedbCacheError> {
         redb_flush(&self.connection)
     }
+
+    /// Retain only entries that are unexpired and satisfy `keep`, returning the number of
+    /// entries removed.
+    ///
+    /// `keep` receives the key as stored (its `to_string()` form) and the decoded value.
+    /// TTL-expired entries are removed without consulting `keep`, as
+    /// [`TtlCache::retain`](crate::TtlCache::retain) does, and so are undecodable entries in
+    /// the default mode, as [`remove_expired_entries`](Self::remove_expired_entries) does.
+    ///
+    /// The whole pass is one write transaction, so concurrent writers wait for it and a
+    /// failure removes nothing: in strict-deserialization mode the first corrupt entry
+    /// aborts the pass with [`RedbCacheError::CacheDeserialization`], and a panicking `keep`
+    /// unwinds through the uncommitted transaction. Every entry is decoded, so the cost is
+    /// O(n) in the size of the table.
+    pub fn retain<F: FnMut(&str, &V) -> bool>(&self, keep: F) -> Result<usize, RedbCacheError> {
+        retain_impl(
+            &self.connection,
+            *self.ttl.lock(),
+            self.strict_deserialization,
+            self.durable,
+            keep,
+        )
+    }
+
+    /// Iterate over the keys of the live entries, in the table's (byte-wise) key order.
+    ///
+    /// Each key is the `to_string()` form the table is keyed by. The iterator streams from one
+    /// read transaction opened here, so it sees the table as of this call and never blocks
+    /// or is blocked by a writer; like every redb read, it does hold on to the pages of that
+    /// version until it is dropped. Expiry is judged once, against the time of this call.
+    ///
+    /// An undecodable entry is skipped in the default mode (a read transaction cannot remove
+    /// it; the next `cache_get` or [`remove_expired_entries`](Self::remove_expired_entries)
+    /// will) and yielded as [`RedbCacheError::CacheDeserialization`] in strict mode, after
+    /// which iteration continues.
+    pub fn keys(&self) -> Result<RedbKeys, RedbCacheError> {
+        self.entries().map(|inner| RedbKeys { inner })
+    }
+
+    fn entries(&self) -> Result<RedbEntries, RedbCacheError> {
+        let rtxn = self
+            .connection
+            .begin_read()
+            .map_err(RedbCacheError::storage)?;
+        let table = rtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
+        let range = table.range::<&str>(..).map_err(RedbCacheError::storage)?;
+        Ok(RedbEntries {
+            range,
+            ttl: *self.ttl.lock(),
+            strict: self.strict_deserialization,
+            now: SystemTime::now(),
+        })
+    }
+
+    /// Serialize `value` for a write under `key`, appending the encoded key when the cache
+    /// was built with [`store_keys`](RedbCacheBuilder::store_keys) and the entry's own `ttl`
+    /// when it has one.
+    fn encode_entry(
+        &self,
+        key: &K,
+        value: &V,
+        ttl: Option<Duration>,
+    ) -> Result<Vec<u8>, RedbCacheError> {
+        let key = self
+            .key_encoder
+            .map(|encode| encode(key))
+            .transpose()
+            .map_err(RedbCacheError::serialization)?;
+        traced!(
+            "cached.serialize",
+            rmp_serde::to_vec(&CachedDiskValueRef::new(value, key, ttl))
+        )
+        .map_err(RedbCacheError::serialization)
+    }
+}
+
+impl<K, V> RedbCache<K, V>
+where
+    K: ToString + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    /// Iterate over the live entries, in the same order and under the same read transaction
+    /// rules as [`keys`](Self::keys), yielding each decoded value with its remaining TTL.
+    ///
+    /// [`StoredEntry::original_key`] is `Some` for entries written while
+    /// [`store_keys`](RedbCacheBuilder::store_keys) was on. An entry whose stored key fails to
+    /// decode as `K` is treated like an undecodable value.
+    pub fn iter(&self) -> Result<RedbIter<K, V>, RedbCacheError> {
+        self.entries().map(|inner| RedbIter {
+            inner,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Cursor shared by [`RedbKeys`] and [`RedbIter`]: the read transaction's range plus what is
+/// needed to judge expiry. The range owns its transaction, so nothing here borrows the cache.
+struct RedbEntries {
+    range: redb::Range<'static, &'static str, &'static [u8]>,
+    ttl: Option<Duration>,
+    strict: bool,
+    now: SystemTime,
+}
+
+/// A live row: its key string, the decoded payload and the remaining TTL.
+type LiveEntry<T> = (String, T, Option<Duration>);
+
+impl RedbEntries {
+    /// The next live entry, decoded with `decode`, and its remaining TTL. Expired entries are
+    /// skipped, as are undecodable ones unless in strict mode.
+    /// `decode` also returns the entry's `created_at` and its own TTL, if any.
+    fn next_with<T>(
+        &mut self,
+        mut decode: impl FnMut(&[u8]) -> Result<(T, SystemTime, Option<Duration>), RedbCacheError>,
+    ) -> Option<Result<LiveEntry<T>, RedbCacheError>> {
+        loop {
+            let (key, raw) = match self.range.next()? {
+                Ok(item) => item,
+                Err(e) => return Some(Err(RedbCacheError::storage(e))),
+            };
+            let (decoded, created_at, own_ttl) = match decode(raw.value()) {
+                Ok(decoded) => decoded,
+                Err(e) if self.strict => return Some(Err(e)),
+                Err(_) => continue,
+            };
+            let remaining = match entry_lifetime(own_ttl, self.ttl) {
+                None => None,
+                Some(ttl) => {
+                    let age = self
+                        .now
+                        .duration_since(created_at)
+                        .unwrap_or(Duration::from_secs(0));
+                    match ttl.checked_sub(age) {
+                        Some(left) if !left.is_zero() => Some(left),
+                        _ => continue,
+                    }
+                }
+            };
+            return Some(Ok((key.value().to_string(), decoded, remaining)));
+        }
+    }
+}
+
+/// Iterator over the live keys of a [`RedbCache`]; see [`RedbCache::keys`].
+pub struct RedbKeys {
+    inner: RedbEntries,
+}
+
+impl Iterator for RedbKeys {
+    type Item = Result<String, RedbCacheError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_with(|raw| {
+                // `IgnoredAny` skips the value without materializing it.
+                rmp_serde::from_slice::<CachedDiskValue<serde::de::IgnoredAny>>(raw)
+                    .map(|entry| ((), entry.created_at, entry.ttl))
+                    .map_err(|e| RedbCacheError::deserialization(e, raw.to_vec()))
+            })
+            .map(|item| item.map(|(key, (), _)| key))
+    }
+}
+
+impl std::fmt::Debug for RedbKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedbKeys").finish_non_exhaustive()
+    }
+}
+
+/// Iterator over the live entries of a [`RedbCache`]; see [`RedbCache::iter`].
+pub struct RedbIter<K, V> {
+    inner: RedbEntries,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K: DeserializeOwned, V: DeserializeOwned> Iterator for RedbIter<K, V> {
+    type Item = Result<StoredEntry<K, V>, RedbCacheError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_with(|raw| {
+                let corrupt = |e| RedbCacheError::deserialization(e, raw.to_vec());
+                let entry = rmp_serde::from_slice::<CachedDiskValue<V>>(raw).map_err(corrupt)?;
+                let original_key = entry.key.as_ref().map(EncodedKey::decode).transpose();
+                Ok((
+                    (original_key.map_err(corrupt)?, entry.value),
+                    entry.created_at,
+                    entry.ttl,
+                ))
+            })
+            .map(|item| {
+                item.map(|(key, (original_key, value), ttl)| {
+                    StoredEntry::new(key, original_key, value, ttl)
+                })
+            })
+    }
+}
+
+impl<K, V> std::fmt::Debug for RedbIter<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedbIter").finish_non_exhaustive()
+    }
 }
 
 /// Async counterparts of the blocking `RedbCache` maintenance methods.
@@ -777,6 +1074,40 @@ where
         })
         .await
     }
+
+    /// Async counterpart of [`set_with_ttl`](RedbCache::set_with_ttl): serializes the entry,
+    /// then writes it on a background thread (via the [`blocking`] crate).
+    pub async fn async_set_with_ttl(
+        &self,
+        key: K,
+        value: V,
+        ttl: Duration,
+    ) -> Result<Option<V>, RedbCacheError>
+    where
+        K: ToString,
+    {
+        let connection = self.connection.clone();
+        let serialized = self.encode_entry(&key, &value, Some(ttl))?;
+        let key = key.to_string();
+        let (cache_ttl, durable) = (*self.ttl.lock(), self.durable);
+        blocking::unblock(in_current_span!(move || {
+            disk_cache_set::<V>(&connection, &key, serialized, cache_ttl, durable)
+        }))
+        .await
+    }
+
+    /// Async counterpart of [`retain`](RedbCache::retain): runs the pass on a background
+    /// thread (via the [`blocking`] crate), which is why `keep` must be `Send + 'static`.
+    pub async fn async_retain<F>(&self, keep: F) -> Result<usize, RedbCacheError>
+    where
+        F: FnMut(&str, &V) -> bool + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        let ttl = *self.ttl.lock();
+        let strict = self.strict_deserialization;
+        let durable = self.durable;
+        blocking::unblock(move || retain_impl(&connection, ttl, strict, durable, keep)).await
+    }
 }
 
 #[non_exhaustive]
@@ -871,46 +1202,144 @@ impl RedbCacheError {
 
 /// Stored entry. Serialized with `rmp_serde::to_vec` (never `to_vec_named`), so
 /// the on-disk form is a 2-element positional MessagePack array of `value` then
-/// `created_at`. Field order is frozen for 3.x: see `DISK_FILE_VERSION`.
-#[derive(serde::Serialize, serde::Deserialize)]
+/// `created_at`, followed by the optional trailing fields: the original `key` (see
+/// [`RedbCacheBuilder::store_keys`]) and the entry's own `ttl` (see
+/// [`RedbCache::set_with_ttl`]). Field order is frozen for 3.x: see
+/// `DISK_FILE_VERSION`. The trailing fields are written only up to the last one set
+/// (see [`serialize_entry`]) and defaulted when missing, so the 2-element form is
+/// unchanged and still decodes.
+#[derive(serde::Deserialize)]
 struct CachedDiskValue<V> {
     value: V,
     created_at: SystemTime,
+    #[serde(default)]
+    key: Option<EncodedKey>,
+    /// Overrides the cache's TTL for this entry; zero means it never expires.
+    #[serde(default)]
+    ttl: Option<Duration>,
+}
+
+impl<V: Serialize> Serialize for CachedDiskValue<V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_entry(
+            serializer,
+            &self.value,
+            &self.created_at,
+            &self.key,
+            &self.ttl,
+        )
+    }
 }
 
 impl<V> CachedDiskValue<V> {
+    #[cfg(test)]
     fn new(value: V) -> Self {
         Self {
             value,
             created_at: SystemTime::now(),
+            key: None,
+            ttl: None,
         }
     }
 
+    /// Restart the entry's lifetime on a refreshing hit. Like the in-memory stores, this
+    /// re-arms it with the cache's TTL, dropping any TTL it was written with.
     fn refresh_created_at(&mut self) {
         self.created_at = SystemTime::now();
+        self.ttl = None;
+    }
+
+    /// See [`entry_lifetime`].
+    fn lifetime(&self, ttl: Option<Duration>) -> Option<Duration> {
+        entry_lifetime(self.ttl, ttl)
+    }
+
+    /// Whether the entry has outlived its [`lifetime`](Self::lifetime) at `now`.
+    fn is_expired(&self, ttl: Option<Duration>, now: SystemTime) -> bool {
+        self.lifetime(ttl).is_some_and(|lifetime| {
+            now.duration_since(self.created_at)
+                .unwrap_or(Duration::from_secs(0))
+                >= lifetime
+        })
+    }
+}
+
+/// How long an entry written with `own` lives under the cache TTL `ttl`: its own TTL when
+/// it has one, the cache's otherwise. `None` means it never expires.
+fn entry_lifetime(own: Option<Duration>, ttl: Option<Duration>) -> Option<Duration> {
+    match own {
+        Some(own) => (!own.is_zero()).then_some(own),
+        None => ttl,
     }
 }
 
-/// Borrowed counterpart of [`CachedDiskValue`] used by `cache_set_ref` to
-/// serialize from a `&V` without cloning. It serializes to the same bytes as
-/// `CachedDiskValue::new(value)`: the encoding is positional, so the two structs
+/// Borrowed counterpart of [`CachedDiskValue`], used by every write to serialize
+/// from a `&V` without cloning. It serializes to the same bytes as the owned
+/// struct with the same fields: the encoding is positional, so the two structs
 /// must keep the same fields in the same order for values written through either
 /// path to deserialize identically.
-#[derive(serde::Serialize)]
 struct CachedDiskValueRef<'a, V> {
     value: &'a V,
     created_at: SystemTime,
+    key: Option<EncodedKey>,
+    ttl: Option<Duration>,
+}
+
+impl<V: Serialize> Serialize for CachedDiskValueRef<'_, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_entry(
+            serializer,
+            self.value,
+            &self.created_at,
+            &self.key,
+            &self.ttl,
+        )
+    }
 }
 
 impl<'a, V> CachedDiskValueRef<'a, V> {
-    fn new(value: &'a V) -> Self {
+    fn new(value: &'a V, key: Option<EncodedKey>, ttl: Option<Duration>) -> Self {
         Self {
             value,
             created_at: SystemTime::now(),
+            key,
+            ttl,
         }
     }
 }
 
+/// The positional layout shared by both entry structs. A trailing optional field is
+/// written only when it or a later one is set, as nil when it is not, so each entry is
+/// the shortest form that keeps every field in its position: an entry with neither is the
+/// original 2-element array.
+fn serialize_entry<S: serde::Serializer, V: Serialize>(
+    serializer: S,
+    value: &V,
+    created_at: &SystemTime,
+    key: &Option<EncodedKey>,
+    ttl: &Option<Duration>,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+
+    let len = if ttl.is_some() {
+        4
+    } else if key.is_some() {
+        3
+    } else {
+        2
+    };
+    let mut entry = serializer.serialize_struct("CachedDiskValue", len)?;
+    entry.serialize_field("value", value)?;
+    entry.serialize_field("created_at", created_at)?;
+    if len > 2 {
+        entry.serialize_field("key", key)?;
+    }
+    if len > 3 {
+        entry.serialize_field("ttl", ttl)?;
+    }
+    entry.end()
+}
+
 // ── Connection-level disk operations ─────────────────────────────────────────
 //
 // These free functions hold the single source of truth for the on-disk
@@ -954,7 +1383,7 @@ where
     // Fast path: read the entry under a read transaction. For the common case
     // where no mutation is required (no TTL, or a fresh entry with refresh
     // disabled) we return immediately, keeping a single read txn with no write.
-    let raw_bytes: Vec<u8> = {
+    let raw_bytes: Vec<u8> = traced!("redb.read_txn", {
         let rtxn = connection.begin_read().map_err(RedbCacheError::storage)?;
         let table = rtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
         let Some(guard) = table.get(key).map_err(RedbCacheError::storage)? else {
@@ -962,9 +1391,13 @@ where
         };
         // Clone bytes before the guard/table/txn are dropped.
         guard.value().to_vec()
-    };
+    });
 
-    let cached: CachedDiskValue<V> = match rmp_serde::from_slice::<CachedDiskValue<V>>(&raw_bytes) {
+    let decoded = traced!(
+        "cached.deserialize",
+        rmp_serde::from_slice::<CachedDiskValue<V>>(&raw_bytes)
+    );
+    let cached: CachedDiskValue<V> = match decoded {
         Ok(v) => v,
         Err(e) if !strict => {
             // D2/C5: self-heal. The entry read under the (now-dropped) read txn
@@ -978,30 +1411,33 @@ where
             // re-read at the mutation path below). Only delete if the entry is
             // *still* corrupt; if it now decodes, adopt the fresh value and fall
             // through to the normal TTL handling.
-            let wtxn = begin_write(connection, durable)?;
-            let healed: Result<Option<CachedDiskValue<V>>, RedbCacheError> = {
-                let mut table = wtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
-                // guard is dropped after .map(); table can be mutated below.
-                let current_bytes: Option<Vec<u8>> = table
-                    .get(key)
-                    .map_err(RedbCacheError::storage)?
-                    .map(|guard| guard.value().to_vec());
-                match current_bytes {
-                    // Entry vanished (concurrent remove/clear): nothing to heal.
-                    None => Ok(None),
-                    Some(bytes) => match rmp_serde::from_slice::<CachedDiskValue<V>>(&bytes) {
-                        // A concurrent writer stored a valid value; keep it.
-                        Ok(v) => Ok(Some(v)),
-                        // Still corrupt under the write txn: evict it.
-                        Err(_) => {
-                            table.remove(key).map_err(RedbCacheError::storage)?;
-                            Ok(None)
-                        }
-                    },
-                }
-            };
-            let healed = healed?;
-            wtxn.commit().map_err(RedbCacheError::storage)?;
+            let healed = traced!("redb.self_heal", {
+                let wtxn = begin_write(connection, durable)?;
+                let healed: Result<Option<CachedDiskValue<V>>, RedbCacheError> = {
+                    let mut table = wtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
+                    // guard is dropped after .map(); table can be mutated below.
+                    let current_bytes: Option<Vec<u8>> = table
+                        .get(key)
+                        .map_err(RedbCacheError::storage)?
+                        .map(|guard| guard.value().to_vec());
+                    match current_bytes {
+                        // Entry vanished (concurrent remove/clear): nothing to heal.
+                        None => Ok(None),
+                        Some(bytes) => match rmp_serde::from_slice::<CachedDiskValue<V>>(&bytes) {
+                            // A concurrent writer stored a valid value; keep it.
+                            Ok(v) => Ok(Some(v)),
+                            // Still corrupt under the write txn: evict it.
+                            Err(_) => {
+                                table.remove(key).map_err(RedbCacheError::storage)?;
+                                Ok(None)
+                            }
+                        },
+                    }
+                };
+                let healed = healed?;
+                wtxn.commit().map_err(RedbCacheError::storage)?;
+                healed
+            });
             let _ = e;
             match healed {
                 None => return Ok(None),
@@ -1011,8 +1447,8 @@ where
         Err(e) => return Err(RedbCacheError::deserialization(e, raw_bytes)),
     };
 
-    let Some(ttl) = ttl else {
-        // No TTL: entries never expire; no mutation needed.
+    let Some(lifetime) = cached.lifetime(ttl) else {
+        // No TTL: the entry never expires; no mutation needed.
         return Ok(Some(cached.value));
     };
 
@@ -1020,7 +1456,7 @@ where
         .duration_since(cached.created_at)
         .unwrap_or(Duration::from_secs(0));
 
-    if age < ttl && !refresh {
+    if age < lifetime && !refresh {
         // Entry is fresh and no refresh is requested: fast path, no write.
         return Ok(Some(cached.value));
     }
@@ -1040,6 +1476,8 @@ where
     //
     // redb serialises write transactions, so the re-read + conditional mutate
     // below is atomic against any concurrent writer.
+    #[cfg(feature = "tracing")]
+    let _write_txn = tracing::debug_span!("redb.write_txn").entered();
     let wtxn = begin_write(connection, durable)?;
     // Use a Result<Option<V>> block so errors propagate and table is dropped
     // before commit (redb requires WriteTransaction to outlive open Tables).
@@ -1078,11 +1516,7 @@ where
                         Err(e) => return Err(RedbCacheError::deserialization(e, bytes)),
                     };
 
-                let current_age = SystemTime::now()
-                    .duration_since(current.created_at)
-                    .unwrap_or(Duration::from_secs(0));
-
-                if current_age < ttl {
+                if !current.is_expired(ttl, SystemTime::now()) {
                     // Entry is still fresh under the write txn. The current
                     // value is authoritative — it may differ from what the read
                     // txn saw if a concurrent `cache_set` replaced it. Refresh
@@ -1122,20 +1556,23 @@ fn disk_cache_set<V>(
 where
     V: DeserializeOwned,
 {
-    let wtxn = begin_write(connection, durable)?;
     // Copy the previous value's bytes (owned) before the guard/table are dropped,
     // but defer deserialization until after the commit: the new value must be
     // written regardless of whether the displaced value can be decoded. The set
     // itself succeeded, so an undecodable previous value is reported as `None`
     // (there is no recoverable previous value) rather than surfaced as an error.
-    let previous_bytes: Option<Vec<u8>> = {
-        let mut table = wtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
-        table
-            .insert(key, serialized.as_slice())
-            .map_err(RedbCacheError::storage)?
-            .map(|guard| guard.value().to_vec())
-    };
-    wtxn.commit().map_err(RedbCacheError::storage)?;
+    let previous_bytes: Option<Vec<u8>> = traced!("redb.write_txn", {
+        let wtxn = begin_write(connection, durable)?;
+        let previous_bytes = {
+            let mut table = wtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
+            table
+                .insert(key, serialized.as_slice())
+                .map_err(RedbCacheError::storage)?
+                .map(|guard| guard.value().to_vec())
+        };
+        wtxn.commit().map_err(RedbCacheError::storage)?;
+        previous_bytes
+    });
     // `cache_set` returns the previous LIVE value only: a displaced entry that
     // was already past the TTL is reported as `None`, matching the
     // `ConcurrentCached::cache_set` contract and the in-memory/redis stores
@@ -1144,15 +1581,14 @@ where
     // this filter only decides what the caller is handed back. Same shape as
     // `disk_cache_remove`, which filters the removed entry identically.
     Ok(previous_bytes
-        .and_then(|bytes| rmp_serde::from_slice::<CachedDiskValue<V>>(&bytes).ok())
-        .filter(|cached| {
-            ttl.is_none_or(|ttl| {
-                SystemTime::now()
-                    .duration_since(cached.created_at)
-                    .unwrap_or(Duration::from_secs(0))
-                    < ttl
-            })
+        .and_then(|bytes| {
+            traced!(
+                "cached.deserialize",
+                rmp_serde::from_slice::<CachedDiskValue<V>>(&bytes)
+            )
+            .ok()
         })
+        .filter(|cached| !cached.is_expired(ttl, SystemTime::now()))
         .map(|cached| cached.value))
 }
 
@@ -1186,40 +1622,32 @@ fn disk_cache_remove<V>(
 where
     V: DeserializeOwned,
 {
-    let wtxn = begin_write(connection, durable)?;
     // Copy the removed bytes (owned) and commit before deserializing, so the entry
     // is removed regardless of whether its value can be decoded. The removal
     // succeeded, so an undecodable value is reported as `None` rather than an error.
-    let removed_bytes: Option<Vec<u8>> = {
-        let mut table = wtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
-        table
-            .remove(key)
-            .map_err(RedbCacheError::storage)?
-            .map(|guard| guard.value().to_vec())
-    };
-    wtxn.commit().map_err(RedbCacheError::storage)?;
-
-    let removed =
-        removed_bytes.and_then(|bytes| rmp_serde::from_slice::<CachedDiskValue<V>>(&bytes).ok());
-    let result = if let Some(cached) = removed {
-        if let Some(ttl) = ttl {
-            if SystemTime::now()
-                .duration_since(cached.created_at)
-                .unwrap_or(Duration::from_secs(0))
-                < ttl
-            {
-                Some(cached.value)
-            } else {
-                None
-            }
-        } else {
-            Some(cached.value)
-        }
-    } else {
-        None
-    };
+    let removed_bytes: Option<Vec<u8>> = traced!("redb.write_txn", {
+        let wtxn = begin_write(connection, durable)?;
+        let removed_bytes = {
+            let mut table = wtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
+            table
+                .remove(key)
+                .map_err(RedbCacheError::storage)?
+                .map(|guard| guard.value().to_vec())
+        };
+        wtxn.commit().map_err(RedbCacheError::storage)?;
+        removed_bytes
+    });
 
-    Ok(result)
+    let removed = removed_bytes.and_then(|bytes| {
+        traced!(
+            "cached.deserialize",
+            rmp_serde::from_slice::<CachedDiskValue<V>>(&bytes)
+        )
+        .ok()
+    });
+    Ok(removed
+        .filter(|cached| !cached.is_expired(ttl, SystemTime::now()))
+        .map(|cached| cached.value))
 }
 
 fn disk_cache_remove_entry<V>(
@@ -1300,11 +1728,6 @@ fn remove_expired_entries_impl<V>(
 where
     V: Serialize + DeserializeOwned,
 {
-    // DISK-5: if no TTL is configured, there are no TTL-based expirations.
-    let Some(ttl) = ttl else {
-        return Ok(0);
-    };
-
     // Take a single time snapshot for both the scan and write passes (B3: two separate
     // `SystemTime::now()` calls could yield different instants, causing an entry to be
     // judged unexpired in the scan and expired in the write or vice-versa).
@@ -1323,11 +1746,7 @@ where
             let raw_vec = raw.to_vec();
             match rmp_serde::from_slice::<CachedDiskValue<V>>(&raw_vec) {
                 Ok(cached) => {
-                    if now
-                        .duration_since(cached.created_at)
-                        .unwrap_or(Duration::from_secs(0))
-                        >= ttl
-                    {
+                    if cached.is_expired(ttl, now) {
                         expired_keys.push(key.value().to_string());
                     }
                 }
@@ -1373,11 +1792,7 @@ where
                 Some(bytes) => {
                     match rmp_serde::from_slice::<CachedDiskValue<V>>(&bytes) {
                         Ok(entry) => {
-                            if now
-                                .duration_since(entry.created_at)
-                                .unwrap_or(Duration::from_secs(0))
-                                >= ttl
-                            {
+                            if entry.is_expired(ttl, now) {
                                 table
                                     .remove(key.as_str())
                                     .map_err(RedbCacheError::storage)?;
@@ -1406,6 +1821,58 @@ where
     Ok(removed)
 }
 
+/// Shared implementation of [`RedbCache::retain`] and [`RedbCache::async_retain`].
+///
+/// Unlike the expiry sweep this runs in a single write transaction: the predicate is
+/// arbitrary, so there is no cheap way to re-check a candidate found by an earlier read
+/// pass, and holding the write lock for the pass is what keeps a concurrent `cache_set`
+/// from being judged on the value it replaced.
+fn retain_impl<V, F>(
+    connection: &Database,
+    ttl: Option<Duration>,
+    strict: bool,
+    durable: bool,
+    mut keep: F,
+) -> Result<usize, RedbCacheError>
+where
+    V: DeserializeOwned,
+    F: FnMut(&str, &V) -> bool,
+{
+    let now = SystemTime::now();
+    let mut removed = 0usize;
+    // `Table::retain` takes an infallible predicate, so a strict-mode decode failure is
+    // parked here, every later entry is kept, and the transaction is dropped uncommitted.
+    let mut failure: Option<RedbCacheError> = None;
+    let wtxn = begin_write(connection, durable)?;
+    {
+        let mut table = wtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
+        table
+            .retain(|key, raw| {
+                if failure.is_some() {
+                    return true;
+                }
+                let retained = match rmp_serde::from_slice::<CachedDiskValue<V>>(raw) {
+                    Ok(entry) => !entry.is_expired(ttl, now) && keep(key, &entry.value),
+                    Err(_) if !strict => false,
+                    Err(e) => {
+                        failure = Some(RedbCacheError::deserialization(e, raw.to_vec()));
+                        true
+                    }
+                };
+                if !retained {
+                    removed += 1;
+                }
+                retained
+            })
+            .map_err(RedbCacheError::storage)?;
+    }
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    wtxn.commit().map_err(RedbCacheError::storage)?;
+    Ok(removed)
+}
+
 /// Behavior on a corrupt stored value (one whose bytes fail to deserialize):
 ///
 /// In the default mode (`strict_deserialization = false`), `cache_get` self-heals: the
@@ -1421,6 +1888,28 @@ where
 /// refreshed expiry fails.
 impl<K, V> ConcurrentCacheBase for RedbCache<K, V> {
     type Error = RedbCacheError;
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    /// The table's row count, read from its b-tree header in a read transaction: `O(1)`,
+    /// and exact as of that transaction.
+    ///
+    /// Like the lazily-evicting in-memory stores, the count includes rows that have expired
+    /// but not been removed yet; call
+    /// [`remove_expired_entries`](RedbCache::remove_expired_entries) first for a count of
+    /// live entries only.
+    fn cache_size(&self) -> Result<Option<usize>, RedbCacheError> {
+        let rtxn = self
+            .connection
+            .begin_read()
+            .map_err(RedbCacheError::storage)?;
+        let table = rtxn.open_table(TABLE).map_err(RedbCacheError::storage)?;
+        let len = table.len().map_err(RedbCacheError::storage)?;
+        Ok(Some(usize::try_from(len).unwrap_or(usize::MAX)))
+    }
 }
 
 impl<K, V> ConcurrentCacheTtl for RedbCache<K, V> {
@@ -1435,10 +1924,11 @@ impl<K, V> ConcurrentCacheTtl for RedbCache<K, V> {
     /// redb persists only each entry's `created_at` timestamp, never an absolute per-entry
     /// expiry, so liveness is recomputed at read time as `now - created_at < ttl` against
     /// whatever TTL is current. Lowering the TTL can immediately expire already-stored
-    /// entries; raising it can revive entries a shorter TTL would have expired.
+    /// entries; raising it can revive entries a shorter TTL would have expired. Entries
+    /// written through [`set_with_ttl`](RedbCache::set_with_ttl) keep their own TTL.
     ///
     /// A zero `ttl` disables expiry, exactly equivalent to `unset_ttl`: with no TTL, every
-    /// stored entry is considered live regardless of age.
+    /// stored entry without a TTL of its own is considered live regardless of age.
     fn set_ttl(&self, ttl: Duration) -> Option<Duration> {
         let mut guard = self.ttl.lock();
         if ttl.is_zero() {
@@ -1451,8 +1941,8 @@ impl<K, V> ConcurrentCacheTtl for RedbCache<K, V> {
     /// Disable expiry, returning the previous TTL (`None` if it was already disabled).
     ///
     /// This is retroactive: because liveness is recomputed at read time from each entry's
-    /// stored `created_at`, disabling the TTL makes every stored entry live regardless of
-    /// age, including entries the prior TTL would have expired.
+    /// stored `created_at`, disabling the TTL makes every stored entry without a TTL of its
+    /// own live regardless of age, including entries the prior TTL would have expired.
     fn unset_ttl(&self) -> Option<Duration> {
         self.ttl.lock().take()
     }
@@ -1473,17 +1963,22 @@ where
     K: ToString + Clone,
     V: Serialize + DeserializeOwned,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redb.get", level = "debug", skip_all, fields(path = %self.disk_path.display())))]
     fn cache_get(&self, key: &K) -> Result<Option<V>, RedbCacheError> {
         let ttl = *self.ttl.lock();
         let refresh = self.refresh.load(Ordering::Relaxed);
-        disk_cache_get(
+        let found = disk_cache_get(
             &self.connection,
             &key.to_string(),
             ttl,
             refresh,
             self.durable,
             self.strict_deserialization,
-        )
+        );
+        if let Ok(found) = &found {
+            super::record_lookup(&self.metrics_exporter, found.is_some());
+        }
+        found
     }
 
     /// Insert `key`/`value`, returning the previous **live** value at that key.
@@ -1491,10 +1986,10 @@ where
     /// A displaced entry that was already past the TTL is filtered to `None`
     /// (per the [`ConcurrentCached::cache_set`] contract); it is overwritten on
     /// disk either way.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redb.set", level = "debug", skip_all, fields(path = %self.disk_path.display())))]
     fn cache_set(&self, key: K, value: V) -> Result<Option<V>, RedbCacheError> {
         let ttl = *self.ttl.lock();
-        let serialized = rmp_serde::to_vec(&CachedDiskValue::new(value))
-            .map_err(RedbCacheError::serialization)?;
+        let serialized = self.encode_entry(&key, &value, None)?;
         disk_cache_set(
             &self.connection,
             &key.to_string(),
@@ -1504,6 +1999,7 @@ where
         )
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redb.remove", level = "debug", skip_all, fields(path = %self.disk_path.display())))]
     fn cache_remove(&self, key: &K) -> Result<Option<V>, RedbCacheError> {
         let ttl = *self.ttl.lock();
         disk_cache_remove(&self.connection, &key.to_string(), ttl, self.durable)
@@ -1571,8 +2067,7 @@ where
     /// taking ownership of `value` and does not read back the previous value.
     /// Call [`ConcurrentCached::cache_get`] first if you need the prior value.
     fn cache_set_ref(&self, key: &K, value: &V) -> Result<(), RedbCacheError> {
-        let serialized = rmp_serde::to_vec(&CachedDiskValueRef::new(value))
-            .map_err(RedbCacheError::serialization)?;
+        let serialized = self.encode_entry(key, value, None)?;
         disk_cache_set_no_return(&self.connection, &key.to_string(), serialized, self.durable)
     }
 }
@@ -1601,6 +2096,7 @@ where
     K: ToString + Clone + Send + Sync,
     V: Serialize + DeserializeOwned + Send + 'static,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redb.get", level = "debug", skip_all, fields(path = %self.disk_path.display())))]
     async fn async_cache_get(&self, key: &K) -> Result<Option<V>, RedbCacheError> {
         let connection = self.connection.clone();
         let key = key.to_string();
@@ -1610,29 +2106,39 @@ where
             self.durable,
             self.strict_deserialization,
         );
-        blocking::unblock(move || {
+        let found = blocking::unblock(in_current_span!(move || {
             disk_cache_get::<V>(&connection, &key, ttl, refresh, durable, strict)
-        })
-        .await
+        }))
+        .await;
+        if let Ok(found) = &found {
+            super::record_lookup(&self.metrics_exporter, found.is_some());
+        }
+        found
     }
 
     /// Async counterpart of [`ConcurrentCached::cache_set`]: returns the previous
     /// **live** value, filtering an already-expired displaced entry to `None`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redb.set", level = "debug", skip_all, fields(path = %self.disk_path.display())))]
     async fn async_cache_set(&self, key: K, value: V) -> Result<Option<V>, RedbCacheError> {
         let connection = self.connection.clone();
+        let serialized = self.encode_entry(&key, &value, None)?;
         let key = key.to_string();
         let (ttl, durable) = (*self.ttl.lock(), self.durable);
-        let serialized = rmp_serde::to_vec(&CachedDiskValue::new(value))
-            .map_err(RedbCacheError::serialization)?;
-        blocking::unblock(move || disk_cache_set::<V>(&connection, &key, serialized, ttl, durable))
-            .await
+        blocking::unblock(in_current_span!(move || {
+            disk_cache_set::<V>(&connection, &key, serialized, ttl, durable)
+        }))
+        .await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redb.remove", level = "debug", skip_all, fields(path = %self.disk_path.display())))]
     async fn async_cache_remove(&self, key: &K) -> Result<Option<V>, RedbCacheError> {
         let connection = self.connection.clone();
         let key = key.to_string();
         let (ttl, durable) = (*self.ttl.lock(), self.durable);
-        blocking::unblock(move || disk_cache_remove::<V>(&connection, &key, ttl, durable)).await
+        blocking::unblock(in_current_span!(move || {
+            disk_cache_remove::<V>(&connection, &key, ttl, durable)
+        }))
+        .await
     }
 
     async fn async_cache_remove_entry(&self, key: &K) -> Result<Option<(K, V)>, Self::Error> {
@@ -1704,11 +2210,10 @@ where
         value: &V,
     ) -> impl std::future::Future<Output = Result<(), RedbCacheError>> + Send {
         let connection = self.connection.clone();
+        // Serialize eagerly; defer any error into the future.
+        let serialized = self.encode_entry(key, value, None);
         let key = key.to_string();
         let durable = self.durable;
-        // Serialize eagerly; defer any error into the future.
-        let serialized = rmp_serde::to_vec(&CachedDiskValueRef::new(value))
-            .map_err(RedbCacheError::serialization);
         async move {
             let serialized = serialized?;
             blocking::unblock(move || {
@@ -1749,6 +2254,8 @@ mod tests {
         let stored = CachedDiskValue {
             value: "hi".to_string(),
             created_at,
+            key: None,
+            ttl: None,
         };
         let bytes = rmp_serde::to_vec(&stored).expect("serialize");
         // 0x92: 2-element fixarray (a named encoding would start 0x82, fixmap).
@@ -1766,12 +2273,14 @@ mod tests {
         assert_eq!(value, "hi");
         assert_eq!((secs, nanos), (1, 0));
 
-        // The borrowed entry used by `cache_set_ref` must be byte-identical,
+        // The borrowed entry every write goes through must be byte-identical,
         // which requires it to keep the same fields in the same order.
         let borrowed = "hi".to_string();
         let borrowed_bytes = rmp_serde::to_vec(&CachedDiskValueRef {
             value: &borrowed,
             created_at,
+            key: None,
+            ttl: None,
         })
         .expect("serialize borrowed");
         assert_eq!(
@@ -1780,6 +2289,34 @@ mod tests {
         );
     }
 
+    /// An entry's own TTL is a fourth positional element, so an entry without a stored key
+    /// writes nil in the key's position rather than shifting the TTL into it.
+    #[test]
+    fn per_entry_ttl_is_written_after_a_nil_key() {
+        let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+        let bytes = rmp_serde::to_vec(&CachedDiskValueRef {
+            value: &"hi".to_string(),
+            created_at,
+            key: None,
+            ttl: Some(Duration::from_secs(5)),
+        })
+        .expect("serialize");
+        // 0x94: 4-element fixarray; 0xc0: nil key; then the TTL as seconds and nanoseconds.
+        assert_eq!(
+            bytes,
+            vec![
+                0x94, 0xa2, b'h', b'i', 0x92, 0x01, 0x00, 0xc0, 0x92, 0x05, 0x00
+            ]
+        );
+        let decoded: CachedDiskValue<String> = rmp_serde::from_slice(&bytes).expect("decode");
+        assert!(decoded.key.is_none());
+        assert_eq!(decoded.ttl, Some(Duration::from_secs(5)));
+        assert_eq!(
+            decoded.lifetime(Some(Duration::from_secs(60))),
+            Some(Duration::from_secs(5))
+        );
+    }
+
     #[test]
     fn ttl_secs_and_ttl_millis_set_duration() {
         // No disk needed -- inspect the builder's ttl field without calling build().
@@ -2422,8 +2959,8 @@ mod tests {
         );
     }
 
-    /// DISK-5: `remove_expired_entries` returns `Ok(0)` immediately when no TTL
-    /// is configured, without scanning any entries.
+    /// DISK-5: `remove_expired_entries` removes nothing when no TTL is configured and
+    /// no entry was written with its own.
     #[test]
     fn remove_expired_entries_returns_zero_when_no_ttl_configured() {
         let tmp_dir = temp_dir!();
@@ -2467,6 +3004,8 @@ mod tests {
             created_at: SystemTime::now()
                 .checked_sub(Duration::from_secs(3600))
                 .expect("subtracting an hour must not underflow"),
+            key: None,
+            ttl: None,
         };
         raw_insert(
             &cache,
@@ -2575,6 +3114,163 @@ mod tests {
         assert!(raw_get(&cache, &1u32.to_string()).is_none());
     }
 
+    #[test]
+    fn retain_removes_rejected_expired_and_corrupt_entries() {
+        let tmp_dir = temp_dir!();
+        let cache: RedbCache<u32, u32> = RedbCache::builder("retain-mixed")
+            .disk_dir(tmp_dir.path())
+            .ttl(LIFE_SPAN_1_SEC)
+            .build()
+            .expect("error building disk cache");
+
+        cache.cache_set(1, 10).unwrap();
+        sleep(LIFE_SPAN_1_SEC + Duration::from_millis(50));
+        for k in 2..=5 {
+            cache.cache_set(k, k * 10).unwrap();
+        }
+        raw_insert(&cache, &9u32.to_string(), vec![0xc1, 0xc1, 0xc1]);
+
+        let mut seen = Vec::new();
+        let removed = cache
+            .retain(|k, v| {
+                seen.push(k.to_string());
+                *v != 30
+            })
+            .unwrap();
+        // Expired `1`, rejected `3` and corrupt `9`; `keep` saw only the live entries.
+        assert_eq!(removed, 3);
+        assert_eq!(seen, ["2", "3", "4", "5"]);
+        for (k, present) in [
+            (1, false),
+            (2, true),
+            (3, false),
+            (4, true),
+            (5, true),
+            (9, false),
+        ] {
+            assert_eq!(
+                raw_get(&cache, &k.to_string()).is_some(),
+                present,
+                "key {k}"
+            );
+        }
+    }
+
+    #[test]
+    fn retain_strict_mode_error_removes_nothing() {
+        let tmp_dir = temp_dir!();
+        let cache: RedbCache<u32, u32> = RedbCache::builder("retain-strict")
+            .disk_dir(tmp_dir.path())
+            .strict_deserialization(true)
+            .build()
+            .expect("error building disk cache");
+
+        cache.cache_set(1, 10).unwrap();
+        raw_insert(&cache, &5u32.to_string(), vec![0xc1, 0xc1, 0xc1]);
+
+        assert!(matches!(
+            cache.retain(|_, _| false),
+            Err(RedbCacheError::CacheDeserialization { .. })
+        ));
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(10));
+        assert!(raw_get(&cache, &5u32.to_string()).is_some());
+    }
+
+    #[test]
+    fn keys_and_iter_skip_expired_and_corrupt_entries() {
+        let tmp_dir = temp_dir!();
+        let cache: RedbCache<u32, String> = RedbCache::builder("iter-mixed")
+            .disk_dir(tmp_dir.path())
+            .ttl(LIFE_SPAN_1_SEC)
+            .build()
+            .expect("error building disk cache");
+
+        cache.cache_set(1, "one".into()).unwrap();
+        sleep(LIFE_SPAN_1_SEC + Duration::from_millis(50));
+        cache.cache_set(2, "two".into()).unwrap();
+        cache.cache_set(3, "three".into()).unwrap();
+        raw_insert(&cache, &9u32.to_string(), vec![0xc1, 0xc1, 0xc1]);
+
+        let keys: Vec<String> = cache.keys().unwrap().map(Result::unwrap).collect();
+        assert_eq!(keys, ["2", "3"]);
+
+        let entries: Vec<StoredEntry<u32, String>> =
+            cache.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            (entries[0].key(), entries[0].value().as_str()),
+            ("2", "two")
+        );
+        // Written without `store_keys`, so only the string form is known.
+        assert_eq!(entries[0].original_key(), None);
+        let left = entries[0].ttl().expect("ttl is set");
+        assert!(left > Duration::ZERO && left <= LIFE_SPAN_1_SEC, "{left:?}");
+        // Iterating is read-only: the corrupt entry is still there.
+        assert!(raw_get(&cache, &9u32.to_string()).is_some());
+    }
+
+    #[test]
+    fn iter_strict_mode_yields_the_corrupt_entry_and_continues() {
+        let tmp_dir = temp_dir!();
+        let cache: RedbCache<u32, u32> = RedbCache::builder("iter-strict")
+            .disk_dir(tmp_dir.path())
+            .strict_deserialization(true)
+            .build()
+            .expect("error building disk cache");
+
+        raw_insert(&cache, "1", vec![0xc1]);
+        cache.cache_set(2, 20).unwrap();
+
+        let mut iter = cache.iter().unwrap();
+        assert!(matches!(
+            iter.next(),
+            Some(Err(RedbCacheError::CacheDeserialization { .. }))
+        ));
+        let entry = iter.next().unwrap().unwrap();
+        assert_eq!((entry.key(), *entry.value(), entry.ttl()), ("2", 20, None));
+        assert!(iter.next().is_none());
+    }
+
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct UserKey {
+        tenant: String,
+        id: u8,
+    }
+
+    impl std::fmt::Display for UserKey {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}/{}", self.tenant, self.id)
+        }
+    }
+
+    #[test]
+    fn store_keys_round_trips_the_original_key() {
+        let tmp_dir = temp_dir!();
+        let cache: RedbCache<UserKey, u32> = RedbCache::builder("iter-store-keys")
+            .disk_dir(tmp_dir.path())
+            .store_keys(true)
+            .build()
+            .expect("error building disk cache");
+        let a = UserKey {
+            tenant: "a".into(),
+            id: 7,
+        };
+        let b = UserKey {
+            tenant: "b".into(),
+            id: 8,
+        };
+        cache.cache_set(a.clone(), 1).unwrap();
+        crate::SerializeCached::cache_set_ref(&cache, &b, &2).unwrap();
+
+        let entries: Vec<_> = cache.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(entries[0].key(), "a/7");
+        assert_eq!(entries[0].original_key(), Some(&a));
+        assert_eq!(entries[1].original_key(), Some(&b));
+        // The regular read path decodes the three-element envelope too.
+        assert_eq!(cache.cache_get(&a).unwrap(), Some(1));
+        assert_eq!(cache.cache_remove(&a).unwrap(), Some(1));
+    }
+
     /// D2: after a self-healed miss the corrupt entry is gone; recomputing via
     /// `cache_set` and reading again must produce a HIT. Guards that the self-heal
     /// delete does not poison subsequent writes to the same key.
@@ -1,6 +1,7 @@
 use crate::time::Duration;
 use crate::{
     ConcurrentCacheBase, ConcurrentCacheRefreshOnHit, ConcurrentCacheTtl, ConcurrentCached,
+    ConcurrentCachedTags,
 };
 use parking_lot::Mutex;
 use serde::Serialize;
@@ -10,6 +11,9 @@ use std::marker::PhantomData;
 use std::sync::LazyLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use super::StoredEntry;
+use super::stored::{EncodedKey, KeyEncoder, encode_key};
+
 /// Conditional self-heal delete (C6). Redis has no native compare-and-delete, so
 /// the GET-then-DEL self-heal is closed with a Lua script that deletes the key
 /// only if its current value still equals the corrupt bytes we read. This makes
@@ -24,6 +28,166 @@ static SELF_HEAL_CONDITIONAL_DEL: LazyLock<redis::Script> = LazyLock::new(|| {
     )
 });
 
+/// `scan`: the value and remaining TTL of each key in `KEYS`, read in one step so the two
+/// agree. A key that vanished since the `SCAN` comes back as a nil value.
+static SCAN_FETCH: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        "local out = {} \
+         for i, key in ipairs(KEYS) do \
+           out[i] = {redis.call('GET', key), redis.call('PTTL', key)} \
+         end \
+         return out",
+    )
+});
+
+/// `invalidate_tag`: delete the members of the tag set in `KEYS[1]` passed after it, remove
+/// them from the set, and return how many still existed. When `ARGV[1]` is `1`, `KEYS[2]` is
+/// the size index and the members follow it. Only the members passed are removed from the
+/// set, so one a concurrent `set_with_tags` added after the caller read the set stays in it
+/// along with its entry, as if written after the invalidation.
+static INVALIDATE_TAG: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        "local first = 2 \
+         if ARGV[1] == '1' then first = 3 end \
+         local removed = 0 \
+         for i = first, #KEYS do \
+           removed = removed + redis.call('DEL', KEYS[i]) \
+           redis.call('SREM', KEYS[1], KEYS[i]) \
+           if first == 3 then redis.call('ZREM', KEYS[2], KEYS[i]) end \
+         end \
+         return removed",
+    )
+});
+
+/// `set_with_tags`: remove from the tag set in `KEYS[1]` each member in `KEYS[2..]` whose data
+/// key no longer exists, returning how many were removed. The check and the removal are one
+/// step, so a member whose entry is being rewritten is either kept or added back by the
+/// writer's own `SADD`.
+static TAG_PRUNE: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        "local pruned = 0 \
+         for i = 2, #KEYS do \
+           if redis.call('EXISTS', KEYS[i]) == 0 then \
+             pruned = pruned + redis.call('SREM', KEYS[1], KEYS[i]) \
+           end \
+         end \
+         return pruned",
+    )
+});
+
+/// Members of each tag set [`TAG_PRUNE`] checks per `set_with_tags`. Each write adds one
+/// member and checks two, so a set settles at about twice its live members: the same bound
+/// the in-memory tag index keeps.
+const TAG_PRUNE_SAMPLE: usize = 2;
+
+/// Members [`INVALIDATE_TAG`] is passed per invocation.
+const INVALIDATE_TAG_CHUNK: usize = 500;
+
+/// [`RedisSizeMode::Exact`]: bring the size index in `KEYS[1]` in line with each data key in
+/// `KEYS[2..]`, scoring a live key by its expiry instant in server milliseconds (`+inf` when
+/// it has none) and dropping one that no longer exists. It reads each key's current state
+/// rather than being told what was written, so writers that each run it after their own
+/// write leave the index right in whatever order the two steps interleave.
+///
+/// `replicate_commands` lets a write follow `TIME` on servers older than 5.0; later servers
+/// always replicate script effects and treat the call as a no-op.
+static SIZE_INDEX_SYNC: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        "redis.replicate_commands() \
+         local t = redis.call('TIME') \
+         local now = t[1] * 1000 + math.floor(t[2] / 1000) \
+         for i = 2, #KEYS do \
+           local pttl = redis.call('PTTL', KEYS[i]) \
+           if pttl == -2 then redis.call('ZREM', KEYS[1], KEYS[i]) \
+           elseif pttl == -1 then redis.call('ZADD', KEYS[1], '+inf', KEYS[i]) \
+           else redis.call('ZADD', KEYS[1], now + pttl, KEYS[i]) end \
+         end \
+         return 0",
+    )
+});
+
+/// [`RedisSizeMode::Exact`]: drop the members of the size index in `KEYS[1]` whose expiry
+/// has passed, by server time, and return how many remain.
+static SIZE_INDEX_COUNT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        "redis.replicate_commands() \
+         local t = redis.call('TIME') \
+         local now = t[1] * 1000 + math.floor(t[2] / 1000) \
+         redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now) \
+         return redis.call('ZCARD', KEYS[1])",
+    )
+});
+
+/// How [`RedisCache`] and [`AsyncRedisCache`](crate::stores::AsyncRedisCache) answer
+/// [`cache_size`](crate::ConcurrentCacheBase::cache_size), and so fill
+/// [`CacheMetrics::entry_count`](crate::CacheMetrics::entry_count). Set with
+/// [`RedisCacheBuilder::size_mode`]; the default reports no size and costs nothing.
+///
+/// Caches that share a namespace and prefix share one keyspace, so they should agree on
+/// the mode: an [`Exact`](Self::Exact) count only sees the writes of caches that keep it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RedisSizeMode {
+    /// Report an unknown size (`Ok(None)`).
+    #[default]
+    Unknown,
+    /// Count the cache's entries with a cursored `SCAN` of its scope on every call, stopping
+    /// once `limit` have been seen, so a result equal to `limit` means "at least `limit`".
+    /// Costs a round trip per batch of about 100 keys and nothing on writes. Approximate:
+    /// `SCAN` may count an entry rewritten during the walk twice, or miss one written
+    /// meanwhile, and it counts expired keys Redis has not reclaimed yet.
+    Scan {
+        /// Most entries counted per call. Must be non-zero.
+        limit: usize,
+    },
+    /// Keep an exact count in a sorted set at `{namespace}:{prefix}:meta:size` holding every
+    /// data key scored by its expiry. Every write, removal and TTL refresh updates it with a
+    /// script in one extra round trip, and `cache_size` prunes the expired members before
+    /// counting. The index is inside the `cache_clear` scope and is deleted with it.
+    ///
+    /// Keys that vanish behind the cache's back (Redis `maxmemory` eviction, a `DEL` from
+    /// another client) stay counted until their recorded expiry, forever for entries without
+    /// a TTL; a clear racing concurrent writes can likewise leave the count off. Rebuilding
+    /// it takes a `cache_clear`.
+    Exact,
+}
+
+/// Key of the [`RedisSizeMode::Exact`] index: `{namespace}:{prefix}:meta:size`. Like a tag set
+/// key it carries three separators, so it is never a data key, and its `meta` marker keeps it
+/// apart from every tag set.
+fn size_index_key(namespace: &str, prefix: &str) -> String {
+    join_key_fields(
+        &escape_key_field(canonical_namespace(namespace)),
+        &escape_key_field(prefix),
+        "meta:size",
+    )
+}
+
+/// Reject a `Scan` limit of zero, which would report every cache as empty.
+fn validate_size_mode(mode: RedisSizeMode) -> Result<(), RedisCacheBuildError> {
+    if mode == (RedisSizeMode::Scan { limit: 0 }) {
+        return Err(super::BuildError::InvalidValue {
+            field: "size_mode",
+            reason: "a Scan size mode needs a non-zero limit",
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// `SIZE_INDEX_SYNC` for `keys`, against the index at `index`.
+fn size_index_sync<T: redis::ToRedisArgs>(
+    index: &str,
+    keys: &[T],
+) -> redis::ScriptInvocation<'static> {
+    let mut invocation = SIZE_INDEX_SYNC.prepare_invoke();
+    invocation.key(index);
+    for key in keys {
+        invocation.key(key);
+    }
+    invocation
+}
+
 pub struct RedisCacheBuilder<K, V> {
     ttl: Option<Duration>,
     refresh: bool,
@@ -36,6 +200,9 @@ pub struct RedisCacheBuilder<K, V> {
     pool_idle_timeout: Option<Duration>,
     pool_connection_timeout: Option<Duration>,
     strict_deserialization: bool,
+    size_mode: RedisSizeMode,
+    key_encoder: Option<KeyEncoder<K>>,
+    metrics_exporter: super::ExporterSlot,
     // fn-pointer phantom — see the rationale on `RedisCache::_phantom`.
     _phantom: PhantomData<fn() -> (K, V)>,
 }
@@ -181,6 +348,15 @@ fn generate_redis_key(namespace: &str, prefix: &str, key: &str) -> String {
 /// arity closes the same overlap for an empty namespace: the scope is `:p:*`,
 /// not `p:*`, so it cannot reach a cache whose namespace is `p`.
 fn clear_match_pattern(namespace: &str, prefix: &str) -> String {
+    key_match_pattern(namespace, prefix, "*")
+}
+
+/// `SCAN MATCH` glob for the keys of this `namespace`/`prefix` whose key field matches
+/// `key_glob`. The scope is escaped as in [`clear_match_pattern`]; `key_glob` keeps its glob
+/// syntax and is only percent-escaped, so a literal `:` or `%` in it matches the escaped form
+/// [`generate_redis_key`] writes. Percent-escaping never touches a glob metacharacter, so the
+/// caller's wildcards, classes and `\` escapes survive unchanged.
+fn key_match_pattern(namespace: &str, prefix: &str, key_glob: &str) -> String {
     fn escape_glob(s: &str) -> String {
         let mut out = String::with_capacity(s.len());
         for c in s.chars() {
@@ -194,14 +370,110 @@ fn clear_match_pattern(namespace: &str, prefix: &str) -> String {
     join_key_fields(
         &escape_glob(&escape_key_field(canonical_namespace(namespace))),
         &escape_glob(&escape_key_field(prefix)),
-        "*",
+        &escape_key_field(key_glob),
+    )
+}
+
+/// Whether a key found by a scoped `SCAN` is an entry rather than a tag set: data keys carry
+/// exactly the two separators of [`join_key_fields`], tag set keys ([`tag_set_key`]) three.
+fn is_entry_key(key: &[u8]) -> bool {
+    key.iter()
+        .filter(|&&b| b == KEY_FIELD_SEPARATOR as u8)
+        .count()
+        == 2
+}
+
+/// The key field of a data key, unescaped back to the `Display` form it was written from, or
+/// `None` for a key that is not valid UTF-8 and so was not written by this store.
+fn entry_key_field(key: &[u8]) -> Option<String> {
+    let key = std::str::from_utf8(key).ok()?;
+    let field = key.splitn(3, KEY_FIELD_SEPARATOR).nth(2)?;
+    let mut out = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(at) = rest.find(KEY_FIELD_ESCAPE) {
+        out.push_str(&rest[..at]);
+        let (decoded, tail) = match rest.get(at..at + 3) {
+            Some("%3A") => (KEY_FIELD_SEPARATOR, &rest[at + 3..]),
+            Some("%25") => (KEY_FIELD_ESCAPE, &rest[at + 3..]),
+            // Not an escape this store writes; keep the `%` as is.
+            _ => (KEY_FIELD_ESCAPE, &rest[at + 1..]),
+        };
+        out.push(decoded);
+        rest = tail;
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// Key of the Redis set holding the data keys tagged `tag`:
+/// `{namespace}:{prefix}:tag:{tag}`, with every field percent-escaped.
+///
+/// The `tag:` marker and the tag share the key field, and the separator between them is
+/// written unescaped, so a tag set key carries three separators where every data key from
+/// [`generate_redis_key`] carries exactly two: the two can never collide, whatever the key
+/// or tag. It stays inside the [`clear_match_pattern`] scope, so `cache_clear` drops the tag
+/// sets along with the entries they index.
+fn tag_set_key(namespace: &str, prefix: &str, tag: &str) -> String {
+    let mut field = String::with_capacity(tag.len() + 4);
+    field.push_str("tag");
+    field.push(KEY_FIELD_SEPARATOR);
+    field.push_str(&escape_key_field(tag));
+    join_key_fields(
+        &escape_key_field(canonical_namespace(namespace)),
+        &escape_key_field(prefix),
+        &field,
     )
 }
 
+#[cfg(test)]
+mod tag_key_tests {
+    // No Redis server needed: pins the tag set key layout.
+    use super::{
+        KEY_FIELD_SEPARATOR, clear_match_pattern, generate_redis_key, is_entry_key, size_index_key,
+        tag_set_key,
+    };
+
+    #[test]
+    fn tag_set_key_is_scoped_and_escaped() {
+        assert_eq!(tag_set_key("ns", "p", "tenant:42"), "ns:p:tag:tenant%3A42");
+        assert_eq!(tag_set_key("ns:", "p", "50%"), "ns:p:tag:50%25");
+        let scope = clear_match_pattern("ns", "p");
+        assert!(tag_set_key("ns", "p", "t").starts_with(scope.trim_end_matches('*')));
+    }
+
+    #[test]
+    fn tag_set_key_never_equals_a_data_key() {
+        // A data key spelled like a tag set key has its `:` escaped.
+        let data = generate_redis_key("ns", "p", "tag:t");
+        assert_eq!(data, "ns:p:tag%3At");
+        assert_ne!(data, tag_set_key("ns", "p", "t"));
+        assert_eq!(data.matches(KEY_FIELD_SEPARATOR).count(), 2);
+        assert_eq!(
+            tag_set_key("ns", "p", "t")
+                .matches(KEY_FIELD_SEPARATOR)
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn size_index_key_is_scoped_and_apart_from_entries_and_tags() {
+        assert_eq!(size_index_key("ns:", "a:b"), "ns:a%3Ab:meta:size");
+        let index = size_index_key("ns", "p");
+        assert!(index.starts_with(clear_match_pattern("ns", "p").trim_end_matches('*')));
+        assert!(!is_entry_key(index.as_bytes()));
+        assert_ne!(index, tag_set_key("ns", "p", "size"));
+        assert_ne!(index, generate_redis_key("ns", "p", "meta:size"));
+    }
+}
+
 #[cfg(test)]
 mod clear_pattern_tests {
     // No Redis server needed — pins the `SCAN MATCH` pattern used by `cache_clear`.
-    use super::{clear_match_pattern, escape_key_field, generate_redis_key};
+    use super::{
+        clear_match_pattern, entry_key_field, escape_key_field, generate_redis_key, is_entry_key,
+        key_match_pattern,
+    };
 
     #[test]
     fn plain_segments_get_scope_and_trailing_star() {
@@ -228,6 +500,29 @@ mod clear_pattern_tests {
         assert_eq!(clear_match_pattern("100%", "p"), "100%25:p:*");
     }
 
+    #[test]
+    fn key_glob_is_percent_escaped_but_keeps_its_wildcards() {
+        assert_eq!(
+            key_match_pattern("ns", "p", "user:42:*"),
+            "ns:p:user%3A42%3A*"
+        );
+        assert_eq!(key_match_pattern("ns", "p", "50%?"), "ns:p:50%25?");
+        assert_eq!(key_match_pattern("n*s", "p", "[ab]*"), "n\\*s:p:[ab]*");
+        assert!(is_entry_key(
+            generate_redis_key("ns", "p", "a:b").as_bytes()
+        ));
+    }
+
+    #[test]
+    fn entry_key_field_inverts_the_key_escaping() {
+        for key in ["plain", "a:b", "50%", "%3A", "x%25:y", ""] {
+            let generated = generate_redis_key("n:s", "p%", key);
+            assert_eq!(entry_key_field(generated.as_bytes()).as_deref(), Some(key));
+        }
+        assert_eq!(entry_key_field(b"ns:p:100%"), Some("100%".to_string()));
+        assert_eq!(entry_key_field(&[b'n', b':', b'p', b':', 0xff]), None);
+    }
+
     #[test]
     fn percent_escaping_and_glob_escaping_do_not_interfere() {
         // Percent-escaping runs first (it defines the literal bytes of the key
@@ -641,6 +936,21 @@ mod builder_empty_prefix_tests {
             "prefix guard must not fire when prefix is non-empty"
         );
     }
+
+    #[test]
+    fn zero_scan_limit_is_rejected() {
+        let result = RedisCacheBuilder::<String, String>::new()
+            .prefix("p")
+            .size_mode(super::RedisSizeMode::Scan { limit: 0 })
+            .build();
+        assert!(matches!(
+            result,
+            Err(RedisCacheBuildError::Build(BuildError::InvalidValue {
+                field: "size_mode",
+                ..
+            }))
+        ));
+    }
 }
 
 #[cfg(test)]
@@ -1193,6 +1503,9 @@ where
             pool_idle_timeout: None,
             pool_connection_timeout: None,
             strict_deserialization: false,
+            size_mode: RedisSizeMode::Unknown,
+            key_encoder: None,
+            metrics_exporter: super::ExporterSlot::default(),
             _phantom: PhantomData,
         }
     }
@@ -1342,6 +1655,27 @@ where
         self
     }
 
+    /// Set how [`cache_size`](crate::ConcurrentCacheBase::cache_size) is answered (default
+    /// [`RedisSizeMode::Unknown`]). A `Scan` limit of zero is rejected by
+    /// [`build`](Self::build) with `InvalidValue`.
+    #[must_use]
+    pub fn size_mode(mut self, mode: RedisSizeMode) -> Self {
+        self.size_mode = mode;
+        self
+    }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// Every `cache_get` counts a hit or a miss as it happens;
+    /// [`publish_metrics`](crate::ConcurrentCacheBase::publish_metrics) adds the entry count
+    /// when the [`size_mode`](Self::size_mode) can report one.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
     /// Return the current connection string or load from the env var: `CACHED_REDIS_CONNECTION_STRING`.
     ///
     /// The value is wrapped in a redacting [`ConnectionString`]: its
@@ -1365,8 +1699,12 @@ where
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.connect", level = "debug", skip_all, fields(connection = tracing::field::Empty)))]
     fn create_pool(&self) -> Result<r2d2::Pool<redis::Client>, RedisCacheBuildError> {
         let s = self.resolve_connection_string()?;
+        // `ConnectionString` displays redacted, so the span never carries credentials.
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("connection", tracing::field::display(&s));
         // Open the client, catching any error and replacing it with a sanitized
         // `Connection` error. A malformed URL such as `redis://:password@host`
         // would otherwise surface the raw connection string (including the
@@ -1466,6 +1804,7 @@ where
             }
             .into());
         }
+        validate_size_mode(self.size_mode)?;
         let connection_string = self.resolve_connection_string()?;
         let pool = self.create_pool()?;
         Ok(RedisCache {
@@ -1476,11 +1815,31 @@ where
             namespace: self.namespace,
             prefix: self.prefix.unwrap_or_default(),
             strict_deserialization: self.strict_deserialization,
+            size_mode: self.size_mode,
+            key_encoder: self.key_encoder,
+            metrics_exporter: self.metrics_exporter,
             _phantom: PhantomData,
         })
     }
 }
 
+impl<K: Serialize, V> RedisCacheBuilder<K, V> {
+    /// Set whether each write also stores the original key, so that [`RedisCache::scan`] can
+    /// hand it back through [`StoredEntry::original_key`] (default `false`).
+    ///
+    /// Keys are addressed by their `Display` form, which is not reversible in general. With
+    /// this set, the key is MessagePack-encoded and appended to the value envelope as an
+    /// optional third field. Entries written without it keep the two-field layout and stay
+    /// readable, so the setting can change between deployments. Releases that predate the
+    /// field read an entry carrying it as corrupt, so do not enable it while an older 3.x
+    /// release still reads the same keys.
+    #[must_use]
+    pub fn store_keys(mut self, store: bool) -> Self {
+        self.key_encoder = store.then_some(encode_key::<K> as KeyEncoder<K>);
+        self
+    }
+}
+
 /// Cache store backed by redis
 ///
 /// The TTL is optional and enforced by redis itself: entries built with a TTL
@@ -1494,7 +1853,9 @@ where
 /// the field names absent from the wire. **Field order is part of the frozen
 /// layout**: reordering, inserting or removing a field of the stored envelope
 /// changes how existing bytes decode and must bump the embedded version, exactly
-/// as a type change would.
+/// as a type change would. The one exception is the optional trailing original
+/// key written under [`RedisCacheBuilder::store_keys`], which is omitted when
+/// unset.
 ///
 /// That layout is stable for the 3.x series: entries written by any 3.x release
 /// remain readable by every later 3.x release. A schema change bumps the embedded
@@ -1521,6 +1882,11 @@ pub struct RedisCache<K, V> {
     connection_string: ConnectionString,
     pool: r2d2::Pool<redis::Client>,
     strict_deserialization: bool,
+    size_mode: RedisSizeMode,
+    /// Set by [`RedisCacheBuilder::store_keys`]. A fn pointer, so `Send + Sync` like the
+    /// phantom below, but it makes the type invariant rather than covariant in `K`.
+    key_encoder: Option<KeyEncoder<K>>,
+    metrics_exporter: super::ExporterSlot,
     // `RedisCache` owns no live `K`/`V` — values are serialized to Redis and
     // `K`/`V` appear only in method signatures. Use a fn-pointer phantom so the
     // type is unconditionally `Send + Sync` regardless of whether `K`/`V` are
@@ -1528,7 +1894,7 @@ pub struct RedisCache<K, V> {
     // emits a `LazyLock<RedisCache<_, _>>` static directly (no inner lock — the
     // `&self`-API of `ConcurrentCached` is self-synchronizing), so the cache
     // type must itself be `Sync` for the static to be `Sync`.
-    // Variance is unchanged: covariant in `K` and `V`, same as `PhantomData<(K, V)>`.
+    // The phantom itself is covariant in `K` and `V`, same as `PhantomData<(K, V)>`.
     _phantom: PhantomData<fn() -> (K, V)>,
 }
 
@@ -1556,6 +1922,9 @@ impl<K, V> Clone for RedisCache<K, V> {
             connection_string: self.connection_string.clone(),
             pool: self.pool.clone(),
             strict_deserialization: self.strict_deserialization,
+            size_mode: self.size_mode,
+            key_encoder: self.key_encoder,
+            metrics_exporter: self.metrics_exporter.clone(),
             _phantom: PhantomData,
         }
     }
@@ -1600,6 +1969,159 @@ where
         clear_match_pattern(&self.namespace, &self.prefix)
     }
 
+    /// Key of the set indexing the entries tagged `tag`; see [`tag_set_key`].
+    fn generate_tag_key(&self, tag: &str) -> String {
+        tag_set_key(&self.namespace, &self.prefix, tag)
+    }
+
+    /// Key of the size index, if this cache keeps one ([`RedisSizeMode::Exact`]).
+    fn size_index(&self) -> Option<String> {
+        (self.size_mode == RedisSizeMode::Exact)
+            .then(|| size_index_key(&self.namespace, &self.prefix))
+    }
+
+    /// Bring the size index in line with `keys` after a write, removal or TTL refresh of
+    /// them; a no-op unless the cache keeps one.
+    fn sync_size_index<T: redis::ToRedisArgs>(
+        &self,
+        conn: &mut redis::Connection,
+        keys: &[T],
+    ) -> Result<(), RedisCacheError> {
+        let Some(index) = self.size_index() else {
+            return Ok(());
+        };
+        size_index_sync(&index, keys)
+            .invoke(conn)
+            .map_err(RedisCacheError::redis)
+    }
+
+    /// Drop members whose entries are gone from a sample of each of `tags`' sets; see
+    /// [`TAG_PRUNE`].
+    fn prune_tag_sets<T: AsRef<str>>(
+        &self,
+        conn: &mut redis::Connection,
+        tags: &[T],
+    ) -> Result<(), RedisCacheError> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+        let sets: Vec<String> = tags
+            .iter()
+            .map(|tag| self.generate_tag_key(tag.as_ref()))
+            .collect();
+        let mut pipe = redis::pipe();
+        for set in &sets {
+            pipe.cmd("SRANDMEMBER").arg(set).arg(TAG_PRUNE_SAMPLE);
+        }
+        let samples: Vec<Vec<String>> = pipe.query(conn).map_err(RedisCacheError::redis)?;
+        for (set, sample) in sets.iter().zip(samples) {
+            if sample.is_empty() {
+                continue;
+            }
+            let mut invocation = TAG_PRUNE.prepare_invoke();
+            invocation.key(set);
+            for member in &sample {
+                invocation.key(member);
+            }
+            invocation
+                .invoke::<usize>(conn)
+                .map_err(RedisCacheError::redis)?;
+        }
+        Ok(())
+    }
+
+    /// Remove every entry whose key matches the glob `pattern`, returning the number of
+    /// entries removed. The redis counterpart of the in-memory stores' `retain`.
+    ///
+    /// `pattern` is a Redis glob (`*`, `?`, `[...]`, `\` escapes) over the key's `Display`
+    /// form, matched within this cache's `{namespace}:{prefix}:` scope only, so
+    /// `remove_matching("user:42:*")` removes the keys `user:42:...` of this cache and nothing
+    /// of any other. A `:` or `%` in the pattern matches the same character in the key.
+    ///
+    /// Runs the same cursored `SCAN` as [`cache_clear`](ConcurrentCached::cache_clear) and
+    /// removes each batch with `UNLINK`, which frees the values off the server's main thread.
+    /// The count is what `UNLINK` reports, so an entry that expired or was removed between the
+    /// scan and the unlink is not counted, and keys written during the scan may or may not be
+    /// removed. Tag sets are never matched.
+    pub fn remove_matching(&self, pattern: &str) -> Result<usize, RedisCacheError> {
+        let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
+        let pattern = key_match_pattern(&self.namespace, &self.prefix, pattern);
+        let mut removed = 0;
+        let mut cursor: u64 = 0;
+        loop {
+            // Binary-safe for the same reason as `cache_clear`.
+            let (next, mut keys): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query(&mut *conn)
+                .map_err(RedisCacheError::redis)?;
+            keys.retain(|k| is_entry_key(k));
+            if !keys.is_empty() {
+                let unlinked: usize = redis::cmd("UNLINK")
+                    .arg(&keys)
+                    .query(&mut *conn)
+                    .map_err(RedisCacheError::redis)?;
+                removed += unlinked;
+                self.sync_size_index(&mut conn, &keys)?;
+            }
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        Ok(removed)
+    }
+
+    /// Insert `key`/`val` to expire `ttl` from now instead of after the cache's TTL,
+    /// returning the previous value like [`cache_set`](ConcurrentCached::cache_set). A zero
+    /// `ttl` writes the key without expiry.
+    ///
+    /// The TTL is the key's own server-side expiry (`PSETEX`), so the entry lives exactly
+    /// that long whatever the cache's TTL is later set to. A hit with
+    /// [`refresh_on_hit`](RedisCacheBuilder::refresh_on_hit) re-arms it with the cache's
+    /// TTL, as it does any other entry.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.set", level = "debug", skip_all, fields(namespace = %self.namespace, prefix = %self.prefix)))]
+    pub fn set_with_ttl(
+        &self,
+        key: K,
+        val: V,
+        ttl: Duration,
+    ) -> Result<Option<V>, RedisCacheError> {
+        let mut conn = traced!("redis.pool", self.pool.get()).map_err(RedisCacheError::pool_err)?;
+        let mut pipe = redis::pipe();
+        let key_str = self.generate_key(&key);
+
+        let serialized = traced!(
+            "cached.serialize",
+            encode_value(self.key_encoder, &key, &val)
+        )?;
+        pipe.get(&key_str);
+        if ttl.is_zero() {
+            // Disabled TTL: write the key without expiry (plain `SET`).
+            pipe.set::<&str, Vec<u8>>(&key_str, serialized).ignore();
+        } else {
+            pipe.pset_ex::<&str, Vec<u8>>(&key_str, serialized, ttl_millis(ttl)?)
+                .ignore();
+        }
+
+        let res: (Option<Vec<u8>>,) =
+            traced!("redis.query", pipe.query(&mut *conn)).map_err(RedisCacheError::redis)?;
+        self.sync_size_index(&mut conn, &[&key_str])?;
+        // REDIS-10: if the displaced previous value fails to decode, the new write
+        // succeeded — return Ok(None) (garbage old value) rather than surfacing an error.
+        Ok(res.0.and_then(|bytes| {
+            traced!(
+                "cached.deserialize",
+                deserialize_cached_redis_value::<V>(&bytes)
+            )
+            .ok()
+            .map(|v| v.value)
+        }))
+    }
+
     /// Return the redis connection string as a [`ConnectionString`].
     ///
     /// `ConnectionString`'s `Debug`/`Display` render `[REDACTED connection string]`,
@@ -1711,8 +2233,7 @@ impl RedisCacheError {
 }
 
 /// On-disk schema version stamped into every value written by this store.
-/// Shared by [`CachedRedisValue::new`] and [`CachedRedisValueRef::new`] so the
-/// two constructors cannot drift. The field type is `Option<u64>`.
+/// Stamped by [`CachedRedisValueRef::new`], through which every write goes. The field type is `Option<u64>`.
 ///
 /// Stability: the on-wire encoding is MessagePack in its compact (non-named)
 /// form: every write goes through `rmp_serde::to_vec`, never `to_vec_named`, so
@@ -1727,40 +2248,63 @@ impl RedisCacheError {
 const REDIS_VALUE_VERSION: Option<u64> = Some(1);
 
 /// Stored value envelope. Serialized positionally (see [`REDIS_VALUE_VERSION`]):
-/// the wire form is a 2-element MessagePack array of `value` then `version`.
-/// Field order is frozen for 3.x.
+/// the wire form is a 2-element MessagePack array of `value` then `version`, or 3
+/// elements when the original `key` is stored (see [`RedisCacheBuilder::store_keys`]).
+/// Field order is frozen for 3.x. The trailing `key` is skipped when absent and
+/// defaulted when missing, so the 2-element form is unchanged and still decodes.
 #[derive(serde::Serialize, serde::Deserialize)]
 struct CachedRedisValue<V> {
     value: V,
     version: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<EncodedKey>,
 }
+#[cfg(test)]
 impl<V> CachedRedisValue<V> {
     fn new(value: V) -> Self {
         Self {
             value,
             version: REDIS_VALUE_VERSION,
+            key: None,
         }
     }
 }
 
-/// Borrowed counterpart of [`CachedRedisValue`] used by `cache_set_ref` to
-/// serialize from a `&V` without cloning. Produces the same MessagePack bytes as
-/// `CachedRedisValue::new(value)`: the encoding is positional, so the two structs
+/// Borrowed counterpart of [`CachedRedisValue`], used by every write to serialize
+/// from a `&V` without cloning. Produces the same MessagePack bytes as the owned
+/// struct with the same fields: the encoding is positional, so the two structs
 /// must keep the same fields in the same order.
 #[derive(serde::Serialize)]
 struct CachedRedisValueRef<'a, V> {
     value: &'a V,
     version: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<EncodedKey>,
 }
 impl<'a, V> CachedRedisValueRef<'a, V> {
-    fn new(value: &'a V) -> Self {
+    fn new(value: &'a V, key: Option<EncodedKey>) -> Self {
         Self {
             value,
             version: REDIS_VALUE_VERSION,
+            key,
         }
     }
 }
 
+/// Serialize `value` for a write under `key`, appending the encoded key when the cache was
+/// built with `store_keys(true)`. Shared by every sync and async write path.
+fn encode_value<K, V: Serialize>(
+    key_encoder: Option<KeyEncoder<K>>,
+    key: &K,
+    value: &V,
+) -> Result<Vec<u8>, RedisCacheError> {
+    let key = key_encoder
+        .map(|encode| encode(key))
+        .transpose()
+        .map_err(RedisCacheError::serialization)?;
+    rmp_serde::to_vec(&CachedRedisValueRef::new(value, key)).map_err(RedisCacheError::serialization)
+}
+
 /// Deserialize a stored [`CachedRedisValue`] from its raw Redis bytes, reading
 /// both the current MessagePack format and the pre-3.0 JSON format.
 ///
@@ -1805,6 +2349,48 @@ fn deserialize_cached_redis_value<V: serde::de::DeserializeOwned>(
 
 impl<K, V> ConcurrentCacheBase for RedisCache<K, V> {
     type Error = RedisCacheError;
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    /// Answered according to the cache's [`RedisSizeMode`]: `Ok(None)` by default, a
+    /// `SCAN` count capped at the mode's limit, or the exact count of the size index.
+    fn cache_size(&self) -> Result<Option<usize>, RedisCacheError> {
+        let limit = match self.size_mode {
+            RedisSizeMode::Unknown => return Ok(None),
+            RedisSizeMode::Exact => {
+                let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
+                return SIZE_INDEX_COUNT
+                    .key(size_index_key(&self.namespace, &self.prefix))
+                    .invoke(&mut *conn)
+                    .map(Some)
+                    .map_err(RedisCacheError::redis);
+            }
+            RedisSizeMode::Scan { limit } => limit,
+        };
+        let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
+        let pattern = clear_match_pattern(&self.namespace, &self.prefix);
+        let mut counted = 0;
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, keys): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query(&mut *conn)
+                .map_err(RedisCacheError::redis)?;
+            counted += keys.iter().filter(|k| is_entry_key(k)).count();
+            if next == 0 || counted >= limit {
+                break;
+            }
+            cursor = next;
+        }
+        Ok(Some(counted.min(limit)))
+    }
 }
 
 impl<K, V> ConcurrentCacheTtl for RedisCache<K, V> {
@@ -1857,72 +2443,66 @@ where
     K: Display + Clone,
     V: Serialize + DeserializeOwned,
 {
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.get", level = "debug", skip_all, fields(namespace = %self.namespace, prefix = %self.prefix)))]
     fn cache_get(&self, key: &K) -> Result<Option<V>, RedisCacheError> {
-        let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
+        let mut conn = traced!("redis.pool", self.pool.get()).map_err(RedisCacheError::pool_err)?;
         let mut pipe = redis::pipe();
         let key_str = self.generate_key(key);
 
         pipe.get(&key_str);
+        let mut refreshed = false;
         if self.refresh.load(Ordering::Relaxed) {
             let ttl = *self.ttl.lock();
             // A zero (disabled) TTL means entries are stored without expiry; skip the
             // refresh `PEXPIRE` so the key stays persistent (no TTL to renew).
             if !ttl.is_zero() {
                 pipe.pexpire(&key_str, ttl_millis_i64(ttl)?).ignore();
+                refreshed = true;
             }
         }
         // ugh: https://github.com/mitsuhiko/redis-rs/pull/388#issuecomment-910919137
-        let res: (Option<Vec<u8>>,) = pipe.query(&mut *conn).map_err(RedisCacheError::redis)?;
-        match res.0 {
-            None => Ok(None),
-            Some(bytes) => match deserialize_cached_redis_value(&bytes) {
-                Ok(v) => Ok(Some(v.value)),
-                Err(e) if !self.strict_deserialization => {
-                    // Self-heal: the stored bytes are corrupt or incompatible with V.
-                    // Delete the entry so the caller can recompute on the next call.
-                    // Use a conditional Lua delete (C6) that only removes the key
-                    // if its current value still equals the corrupt `bytes` we
-                    // read; a concurrent valid `SET`/`PSETEX` in between is left
-                    // untouched instead of being clobbered by an unconditional DEL.
-                    let _: i64 = SELF_HEAL_CONDITIONAL_DEL
+        let res: (Option<Vec<u8>>,) =
+            traced!("redis.query", pipe.query(&mut *conn)).map_err(RedisCacheError::redis)?;
+        let Some(bytes) = res.0 else {
+            super::record_lookup(&self.metrics_exporter, false);
+            return Ok(None);
+        };
+        let found = match traced!("cached.deserialize", deserialize_cached_redis_value(&bytes)) {
+            Ok(v) => Ok(Some(v.value)),
+            Err(e) if !self.strict_deserialization => {
+                // Self-heal: the stored bytes are corrupt or incompatible with V.
+                // Delete the entry so the caller can recompute on the next call.
+                // Use a conditional Lua delete (C6) that only removes the key
+                // if its current value still equals the corrupt `bytes` we
+                // read; a concurrent valid `SET`/`PSETEX` in between is left
+                // untouched instead of being clobbered by an unconditional DEL.
+                let _: i64 = traced!(
+                    "redis.self_heal",
+                    SELF_HEAL_CONDITIONAL_DEL
                         .key(&key_str)
                         .arg(&bytes)
                         .invoke(&mut *conn)
-                        .map_err(RedisCacheError::redis)?;
-                    let _ = e;
-                    Ok(None)
-                }
-                Err(e) => Err(e),
-            },
+                )
+                .map_err(RedisCacheError::redis)?;
+                let _ = e;
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        };
+        // The refresh moved the key's expiry and a self-heal deleted it; either way the size
+        // index is out of date.
+        if refreshed || matches!(found, Ok(None)) {
+            self.sync_size_index(&mut conn, &[&key_str])?;
         }
+        if let Ok(found) = &found {
+            super::record_lookup(&self.metrics_exporter, found.is_some());
+        }
+        found
     }
 
     fn cache_set(&self, key: K, val: V) -> Result<Option<V>, RedisCacheError> {
-        let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
-        let mut pipe = redis::pipe();
-        let key_str = self.generate_key(&key);
-
         let ttl = *self.ttl.lock();
-
-        let val = CachedRedisValue::new(val);
-        let serialized = rmp_serde::to_vec(&val).map_err(RedisCacheError::serialization)?;
-        pipe.get(&key_str);
-        if ttl.is_zero() {
-            // Disabled TTL: write the key without expiry (plain `SET`).
-            pipe.set::<String, Vec<u8>>(key_str, serialized).ignore();
-        } else {
-            pipe.pset_ex::<String, Vec<u8>>(key_str, serialized, ttl_millis(ttl)?)
-                .ignore();
-        }
-
-        let res: (Option<Vec<u8>>,) = pipe.query(&mut *conn).map_err(RedisCacheError::redis)?;
-        // REDIS-10: if the displaced previous value fails to decode, the new write
-        // succeeded — return Ok(None) (garbage old value) rather than surfacing an error.
-        Ok(res.0.and_then(|bytes| {
-            deserialize_cached_redis_value::<V>(&bytes)
-                .ok()
-                .map(|v| v.value)
-        }))
+        self.set_with_ttl(key, val, ttl)
     }
 
     /// Remove a cached value.
@@ -1937,21 +2517,26 @@ where
     ///   `Ok(None)` (the undecodable previous value is discarded).
     /// - **Strict (`strict_deserialization(true)`):** the corrupt entry is still removed
     ///   and the method returns `Err(RedisCacheError::CacheDeserialization { .. })`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.remove", level = "debug", skip_all, fields(namespace = %self.namespace, prefix = %self.prefix)))]
     fn cache_remove(&self, key: &K) -> Result<Option<V>, RedisCacheError> {
-        let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
+        let mut conn = traced!("redis.pool", self.pool.get()).map_err(RedisCacheError::pool_err)?;
         let mut pipe = redis::pipe();
         let key_str = self.generate_key(key);
 
         pipe.get(&key_str);
-        pipe.del::<String>(key_str).ignore();
-        let res: (Option<Vec<u8>>,) = pipe.query(&mut *conn).map_err(RedisCacheError::redis)?;
+        pipe.del::<&str>(&key_str).ignore();
+        let res: (Option<Vec<u8>>,) =
+            traced!("redis.query", pipe.query(&mut *conn)).map_err(RedisCacheError::redis)?;
+        self.sync_size_index(&mut conn, &[&key_str])?;
         match res.0 {
             None => Ok(None),
-            Some(bytes) => match deserialize_cached_redis_value(&bytes) {
-                Ok(v) => Ok(Some(v.value)),
-                Err(_) if !self.strict_deserialization => Ok(None),
-                Err(e) => Err(e),
-            },
+            Some(bytes) => {
+                match traced!("cached.deserialize", deserialize_cached_redis_value(&bytes)) {
+                    Ok(v) => Ok(Some(v.value)),
+                    Err(_) if !self.strict_deserialization => Ok(None),
+                    Err(e) => Err(e),
+                }
+            }
         }
     }
 
@@ -1970,9 +2555,10 @@ where
         let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
         let key_str = self.generate_key(key);
         let removed: usize = redis::cmd("DEL")
-            .arg(key_str)
+            .arg(&key_str)
             .query(&mut *conn)
             .map_err(RedisCacheError::redis)?;
+        self.sync_size_index(&mut conn, &[&key_str])?;
         Ok(removed > 0)
     }
 
@@ -2048,6 +2634,94 @@ where
     }
 }
 
+/// Each tag is a Redis set at `{namespace}:{prefix}:tag:{tag}` whose members are the full
+/// data keys carrying it. `cache_clear` deletes the sets along with the entries.
+///
+/// Unlike the in-memory index, re-tagging a key does not remove it from the sets of its old
+/// tags, which only ever makes invalidation remove more, never less. Members whose entries
+/// expired or were removed are pruned as the set is written: each `set_with_tags` checks
+/// two random members of every set it adds to and drops those whose entry is gone, so a set
+/// stays within about twice its live members. The sets carry no TTL, since one could lapse
+/// before a member renewed by `refresh_on_hit`.
+///
+/// Both scripts are passed every key they touch through `KEYS`. On Redis Cluster a script's
+/// keys must also share a hash slot, so tagging there needs a namespace and prefix that put
+/// the entries and tag sets in one slot (a `{hash tag}`).
+impl<K, V> ConcurrentCachedTags<K, V> for RedisCache<K, V>
+where
+    K: Display + Clone,
+    V: Serialize + DeserializeOwned,
+{
+    /// Writes the entry and adds its key to every tag set in one `MULTI`/`EXEC`
+    /// transaction, so an entry is never visible without its tags.
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        key: K,
+        val: V,
+        tags: &[T],
+    ) -> Result<Option<V>, RedisCacheError> {
+        let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        let key_str = self.generate_key(&key);
+
+        let ttl = *self.ttl.lock();
+
+        let serialized = encode_value(self.key_encoder, &key, &val)?;
+        pipe.get(&key_str);
+        if ttl.is_zero() {
+            pipe.set::<&str, Vec<u8>>(&key_str, serialized).ignore();
+        } else {
+            pipe.pset_ex::<&str, Vec<u8>>(&key_str, serialized, ttl_millis(ttl)?)
+                .ignore();
+        }
+        for tag in tags {
+            pipe.sadd(self.generate_tag_key(tag.as_ref()), &key_str)
+                .ignore();
+        }
+
+        let res: (Option<Vec<u8>>,) = pipe.query(&mut *conn).map_err(RedisCacheError::redis)?;
+        self.sync_size_index(&mut conn, &[&key_str])?;
+        self.prune_tag_sets(&mut conn, tags)?;
+        // As in `cache_set`: an undecodable previous value is reported as absent.
+        Ok(res.0.and_then(|bytes| {
+            deserialize_cached_redis_value::<V>(&bytes)
+                .ok()
+                .map(|v| v.value)
+        }))
+    }
+
+    /// Returns the number of tagged entries that still existed, as counted by `DEL`.
+    ///
+    /// Reads the set, then deletes its members in chunks of 500, each chunk in one script
+    /// that also removes them from the set. A member added after the read is left in place.
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, RedisCacheError> {
+        let mut conn = self.pool.get().map_err(RedisCacheError::pool_err)?;
+        let set = self.generate_tag_key(tag);
+        let members: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(&set)
+            .query(&mut *conn)
+            .map_err(RedisCacheError::redis)?;
+        let index = self.size_index();
+        let mut removed = 0;
+        for chunk in members.chunks(INVALIDATE_TAG_CHUNK) {
+            let mut invocation = INVALIDATE_TAG.prepare_invoke();
+            invocation.key(&set);
+            if let Some(index) = &index {
+                invocation.key(index);
+            }
+            for member in chunk {
+                invocation.key(member);
+            }
+            invocation.arg(u8::from(index.is_some()));
+            removed += invocation
+                .invoke::<usize>(&mut *conn)
+                .map_err(RedisCacheError::redis)?;
+        }
+        Ok(removed)
+    }
+}
+
 impl<K, V> crate::SerializeCached<K, V> for RedisCache<K, V>
 where
     K: Display + Clone,
@@ -2063,8 +2737,7 @@ where
 
         let ttl = *self.ttl.lock();
 
-        let val = CachedRedisValueRef::new(val);
-        let serialized = rmp_serde::to_vec(&val).map_err(RedisCacheError::serialization)?;
+        let serialized = encode_value(self.key_encoder, key, val)?;
 
         if ttl.is_zero() {
             // Disabled TTL: write the key without expiry (plain `SET`).
@@ -2081,10 +2754,183 @@ where
                 .query(&mut *conn)
                 .map_err(RedisCacheError::redis)?;
         }
+        self.sync_size_index(&mut conn, &[&key_str])
+    }
+}
+
+impl<K, V> RedisCache<K, V>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    /// Iterate over this cache's live entries with a cursored `SCAN`, yielding each decoded
+    /// value with its remaining TTL.
+    ///
+    /// [`StoredEntry::key`] is the key's `Display` form, recovered from the Redis key;
+    /// [`StoredEntry::original_key`] is `Some` for entries written while
+    /// [`store_keys`](RedisCacheBuilder::store_keys) was on. Batches are fetched lazily,
+    /// about 100 keys per round trip, each batch's values and TTLs read by one script, and
+    /// the iterator holds a pooled connection until it is dropped.
+    ///
+    /// `SCAN` guarantees only that an entry present for the whole iteration is returned; an
+    /// entry written or removed meanwhile may or may not be, and one that is rewritten may be
+    /// returned twice. Tag sets are skipped. An undecodable entry is skipped in the default
+    /// mode (it is left for `cache_get` to heal) and yielded as
+    /// [`RedisCacheError::CacheDeserialization`] in strict mode, after which iteration
+    /// continues. A connection error ends the iteration after being yielded.
+    pub fn scan(&self) -> Result<RedisScan<K, V>, RedisCacheError> {
+        Ok(RedisScan {
+            conn: self.pool.get().map_err(RedisCacheError::pool_err)?,
+            cursor: ScanCursor::new(
+                clear_match_pattern(&self.namespace, &self.prefix),
+                self.strict_deserialization,
+            ),
+            pending: std::collections::VecDeque::new(),
+        })
+    }
+}
+
+/// State of a `scan`, shared by [`RedisScan`] and the async stream: the `SCAN` cursor and how
+/// to turn each fetched batch into entries.
+struct ScanCursor {
+    pattern: String,
+    cursor: u64,
+    done: bool,
+    strict: bool,
+}
+
+impl ScanCursor {
+    fn new(pattern: String, strict: bool) -> Self {
+        Self {
+            pattern,
+            cursor: 0,
+            done: false,
+            strict,
+        }
+    }
+
+    fn scan_cmd(&self) -> redis::Cmd {
+        let mut cmd = redis::cmd("SCAN");
+        cmd.arg(self.cursor)
+            .arg("MATCH")
+            .arg(&self.pattern)
+            .arg("COUNT")
+            .arg(100);
+        cmd
+    }
+
+    /// Record a `SCAN` reply and return the entry keys to fetch.
+    fn advance(&mut self, next: u64, mut keys: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+        self.cursor = next;
+        self.done = next == 0;
+        keys.retain(|k| is_entry_key(k));
+        keys
+    }
+
+    /// The [`SCAN_FETCH`] invocation for `keys`.
+    fn fetch_script(keys: &[Vec<u8>]) -> redis::ScriptInvocation<'static> {
+        let mut fetch = SCAN_FETCH.prepare_invoke();
+        for key in keys {
+            fetch.key(key.as_slice());
+        }
+        fetch
+    }
+
+    /// Decode a [`SCAN_FETCH`] reply for `keys`, dropping entries that vanished or expired
+    /// since the `SCAN` and, outside strict mode, entries that do not decode.
+    fn decode<K, V>(
+        &self,
+        keys: Vec<Vec<u8>>,
+        fetched: Vec<(Option<Vec<u8>>, i64)>,
+    ) -> impl Iterator<Item = Result<StoredEntry<K, V>, RedisCacheError>> + use<K, V>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let strict = self.strict;
+        keys.into_iter()
+            .zip(fetched)
+            .filter_map(move |(raw_key, (bytes, pttl))| {
+                let bytes = bytes?;
+                // PTTL is -1 for a key without expiry and -2 for one that is gone.
+                let ttl = match pttl {
+                    -1 => None,
+                    ms if ms > 0 => Some(Duration::from_millis(ms as u64)),
+                    _ => return None,
+                };
+                let key = entry_key_field(&raw_key)?;
+                let decoded = deserialize_cached_redis_value::<V>(&bytes).and_then(|envelope| {
+                    let original_key = envelope.key.as_ref().map(EncodedKey::decode).transpose();
+                    match original_key {
+                        Ok(original_key) => Ok((original_key, envelope.value)),
+                        Err(e) => Err(RedisCacheError::deserialization(e, bytes)),
+                    }
+                });
+                match decoded {
+                    Ok((original_key, value)) => {
+                        Some(Ok(StoredEntry::new(key, original_key, value, ttl)))
+                    }
+                    Err(e) if strict => Some(Err(e)),
+                    Err(_) => None,
+                }
+            })
+    }
+}
+
+/// Iterator over the live entries of a [`RedisCache`]; see [`RedisCache::scan`].
+pub struct RedisScan<K, V> {
+    conn: r2d2::PooledConnection<redis::Client>,
+    cursor: ScanCursor,
+    pending: std::collections::VecDeque<Result<StoredEntry<K, V>, RedisCacheError>>,
+}
+
+impl<K: DeserializeOwned, V: DeserializeOwned> RedisScan<K, V> {
+    fn fetch(&mut self) -> Result<(), RedisCacheError> {
+        let (next, keys): (u64, Vec<Vec<u8>>) = self
+            .cursor
+            .scan_cmd()
+            .query(&mut *self.conn)
+            .map_err(RedisCacheError::redis)?;
+        let keys = self.cursor.advance(next, keys);
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let fetched = ScanCursor::fetch_script(&keys)
+            .invoke(&mut *self.conn)
+            .map_err(RedisCacheError::redis)?;
+        self.pending.extend(self.cursor.decode(keys, fetched));
         Ok(())
     }
 }
 
+impl<K: DeserializeOwned, V: DeserializeOwned> Iterator for RedisScan<K, V> {
+    type Item = Result<StoredEntry<K, V>, RedisCacheError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item);
+            }
+            if self.cursor.done {
+                return None;
+            }
+            if let Err(e) = self.fetch() {
+                self.cursor.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl<K, V> std::fmt::Debug for RedisScan<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisScan")
+            .field("cursor", &self.cursor.cursor)
+            .field("done", &self.cursor.done)
+            .finish_non_exhaustive()
+    }
+}
+
 // Canonical `AsyncRedisCache` availability gate (kept in sync with src/lib.rs and
 // src/stores/mod.rs): a redis async runtime feature must be enabled. The six runtime features
 // each imply `redis_store` + `async`; the capability-only features are excluded because they
@@ -2103,9 +2949,9 @@ mod async_redis {
     use std::sync::atomic::{AtomicBool, Ordering};
 
     use super::{
-        CachedRedisValue, CachedRedisValueRef, ConnectionString, DEFAULT_NAMESPACE,
-        DeserializeOwned, Display, ENV_KEY, PhantomData, RedisCacheBuildError, RedisCacheError,
-        Serialize,
+        ConnectionString, DEFAULT_NAMESPACE, DeserializeOwned, Display, ENV_KEY, KeyEncoder,
+        PhantomData, RedisCacheBuildError, RedisCacheError, RedisSizeMode, ScanCursor, Serialize,
+        StoredEntry, encode_key,
     };
     use crate::{
         ConcurrentCacheBase, ConcurrentCacheRefreshOnHit, ConcurrentCacheTtl, ConcurrentCachedAsync,
@@ -2113,6 +2959,20 @@ mod async_redis {
     #[cfg(feature = "redis_async_cache")]
     use redis::IntoConnectionInfo;
 
+    /// Await `$fut` inside a `$name` span; the async counterpart of `traced!`.
+    #[cfg(feature = "tracing")]
+    macro_rules! traced_await {
+        ($name:literal, $fut:expr) => {
+            tracing::Instrument::instrument($fut, tracing::debug_span!($name)).await
+        };
+    }
+    #[cfg(not(feature = "tracing"))]
+    macro_rules! traced_await {
+        ($name:literal, $fut:expr) => {
+            $fut.await
+        };
+    }
+
     /// The async redis connection held by an [`AsyncRedisCache`].
     ///
     /// The `Multiplexed` variant is always compiled; the `Manager`
@@ -2210,6 +3070,9 @@ mod async_redis {
         // 2.x multiplexed behavior even when the feature is enabled transitively.
         #[cfg(feature = "redis_connection_manager")]
         connection_manager: bool,
+        size_mode: RedisSizeMode,
+        key_encoder: Option<KeyEncoder<K>>,
+        metrics_exporter: crate::stores::ExporterSlot,
         // fn-pointer phantom — see the rationale on `RedisCache::_phantom`.
         _phantom: PhantomData<fn() -> (K, V)>,
     }
@@ -2250,6 +3113,9 @@ mod async_redis {
                 client_side_caching: false,
                 #[cfg(feature = "redis_connection_manager")]
                 connection_manager: false,
+                size_mode: RedisSizeMode::Unknown,
+                key_encoder: None,
+                metrics_exporter: crate::stores::ExporterSlot::default(),
                 _phantom: PhantomData,
             }
         }
@@ -2389,6 +3255,26 @@ mod async_redis {
             self
         }
 
+        /// Set how [`AsyncRedisCache::async_cache_size`] is answered (default
+        /// [`RedisSizeMode::Unknown`]); see
+        /// [`RedisCacheBuilder::size_mode`](super::RedisCacheBuilder::size_mode).
+        #[must_use]
+        pub fn size_mode(mut self, mode: RedisSizeMode) -> Self {
+            self.size_mode = mode;
+            self
+        }
+
+        /// Publish this cache's metrics through the `metrics` facade, labelled
+        /// `cache = name`. Every `async_cache_get` counts a hit or a miss as it happens;
+        /// [`AsyncRedisCache::async_publish_metrics`] adds the entry count.
+        #[cfg(feature = "metrics")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+        #[must_use]
+        pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+            self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+            self
+        }
+
         /// Return the current connection string or load from the env var: `CACHED_REDIS_CONNECTION_STRING`.
         ///
         /// The value is wrapped in a redacting [`ConnectionString`](super::ConnectionString):
@@ -2445,7 +3331,13 @@ mod async_redis {
         /// [`redis::aio::ConnectionManager`]; otherwise build a plain
         /// [`redis::aio::MultiplexedConnection`]. The default is multiplexed, so
         /// enabling the feature transitively never changes an existing cache.
+        #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.connect", level = "debug", skip_all, fields(connection = tracing::field::Empty)))]
         async fn create_connection(&self) -> Result<AsyncRedisConnection, RedisCacheBuildError> {
+            // `ConnectionString` displays redacted, so the span never carries credentials.
+            #[cfg(feature = "tracing")]
+            if let Ok(s) = self.resolve_connection_string() {
+                tracing::Span::current().record("connection", tracing::field::display(&s));
+            }
             #[cfg(feature = "redis_connection_manager")]
             if self.connection_manager {
                 return Ok(AsyncRedisConnection::Manager(
@@ -2646,6 +3538,7 @@ mod async_redis {
                 }
                 .into());
             }
+            super::validate_size_mode(self.size_mode)?;
             let connection_string = self.resolve_connection_string()?;
             let connection = self.create_connection().await?;
             Ok(AsyncRedisCache {
@@ -2656,11 +3549,26 @@ mod async_redis {
                 namespace: self.namespace,
                 prefix: self.prefix.unwrap_or_default(),
                 strict_deserialization: self.strict_deserialization,
+                size_mode: self.size_mode,
+                key_encoder: self.key_encoder,
+                metrics_exporter: self.metrics_exporter,
                 _phantom: PhantomData,
             })
         }
     }
 
+    impl<K: Serialize, V> AsyncRedisCacheBuilder<K, V> {
+        /// Set whether each write also stores the original key, so that
+        /// [`AsyncRedisCache::scan`] can hand it back through
+        /// [`StoredEntry::original_key`] (default `false`). Same layout and caveats as
+        /// [`RedisCacheBuilder::store_keys`](super::RedisCacheBuilder::store_keys).
+        #[must_use]
+        pub fn store_keys(mut self, store: bool) -> Self {
+            self.key_encoder = store.then_some(encode_key::<K> as KeyEncoder<K>);
+            self
+        }
+    }
+
     /// Async cache store backed by redis.
     ///
     /// The TTL is optional and enforced by Redis itself: entries built with a
@@ -2703,6 +3611,10 @@ mod async_redis {
         // `connection_manager` flag; defaults to `Multiplexed` (2.x behavior).
         connection: AsyncRedisConnection,
         strict_deserialization: bool,
+        size_mode: RedisSizeMode,
+        /// Set by [`AsyncRedisCacheBuilder::store_keys`]; see `RedisCache::key_encoder`.
+        key_encoder: Option<KeyEncoder<K>>,
+        metrics_exporter: crate::stores::ExporterSlot,
         // `AsyncRedisCache` owns no live `K`/`V` — see the rationale on
         // `RedisCache::_phantom`. Same fn-pointer phantom so a `Send`-but-`!Sync`
         // `V` (e.g. one containing a `Cell`) is usable, and the macro-emitted
@@ -2736,6 +3648,9 @@ mod async_redis {
                 connection_string: self.connection_string.clone(),
                 connection: self.connection.clone(),
                 strict_deserialization: self.strict_deserialization,
+                size_mode: self.size_mode,
+                key_encoder: self.key_encoder,
+                metrics_exporter: self.metrics_exporter.clone(),
                 _phantom: PhantomData,
             }
         }
@@ -2777,6 +3692,167 @@ mod async_redis {
             super::clear_match_pattern(&self.namespace, &self.prefix)
         }
 
+        /// Key of the size index, if this cache keeps one; see `RedisCache::size_index`.
+        fn size_index(&self) -> Option<String> {
+            (self.size_mode == RedisSizeMode::Exact)
+                .then(|| super::size_index_key(&self.namespace, &self.prefix))
+        }
+
+        /// Async counterpart of `RedisCache::sync_size_index`.
+        async fn sync_size_index<T: redis::ToRedisArgs>(
+            &self,
+            conn: &mut AsyncRedisConnection,
+            keys: &[T],
+        ) -> Result<(), RedisCacheError> {
+            let Some(index) = self.size_index() else {
+                return Ok(());
+            };
+            super::size_index_sync(&index, keys)
+                .invoke_async(conn)
+                .await
+                .map_err(RedisCacheError::redis)
+        }
+
+        /// The number of entries in this cache, answered according to its
+        /// [`RedisSizeMode`] exactly as
+        /// [`RedisCache::cache_size`](crate::ConcurrentCacheBase::cache_size) does.
+        ///
+        /// [`ConcurrentCacheBase::cache_size`] is synchronous, so this store cannot answer it
+        /// without blocking and leaves it at `Ok(None)`; call this instead.
+        pub async fn async_cache_size(&self) -> Result<Option<usize>, RedisCacheError> {
+            let mut conn = self.connection.clone();
+            let limit = match self.size_mode {
+                RedisSizeMode::Unknown => return Ok(None),
+                RedisSizeMode::Exact => {
+                    return super::SIZE_INDEX_COUNT
+                        .key(super::size_index_key(&self.namespace, &self.prefix))
+                        .invoke_async(&mut conn)
+                        .await
+                        .map(Some)
+                        .map_err(RedisCacheError::redis);
+                }
+                RedisSizeMode::Scan { limit } => limit,
+            };
+            let pattern = self.clear_match_pattern();
+            let mut counted = 0;
+            let mut cursor: u64 = 0;
+            loop {
+                let (next, keys): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(100)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(RedisCacheError::redis)?;
+                counted += keys.iter().filter(|k| super::is_entry_key(k)).count();
+                if next == 0 || counted >= limit {
+                    break;
+                }
+                cursor = next;
+            }
+            Ok(Some(counted.min(limit)))
+        }
+
+        /// Async counterpart of
+        /// [`ConcurrentCacheBase::publish_metrics`](crate::ConcurrentCacheBase::publish_metrics):
+        /// publish the entry count from [`async_cache_size`](Self::async_cache_size) through the
+        /// exporter set by [`metrics_name`](AsyncRedisCacheBuilder::metrics_name). Does nothing
+        /// for a cache built without one.
+        ///
+        /// # Errors
+        ///
+        /// Returns the error of `async_cache_size`.
+        #[cfg(feature = "metrics")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+        pub async fn async_publish_metrics(&self) -> Result<(), RedisCacheError> {
+            if let Some(exporter) = &self.metrics_exporter {
+                let mut metrics = ConcurrentCacheBase::metrics(self);
+                metrics.entry_count = self.async_cache_size().await?;
+                exporter.publish(|| metrics);
+            }
+            Ok(())
+        }
+
+        /// Async counterpart of
+        /// [`RedisCache::remove_matching`](super::RedisCache::remove_matching): remove every
+        /// entry whose key matches the glob `pattern` within this cache's scope, `UNLINK`ing
+        /// each `SCAN` batch, and return the number removed.
+        pub async fn remove_matching(&self, pattern: &str) -> Result<usize, RedisCacheError> {
+            let mut conn = self.connection.clone();
+            let pattern = super::key_match_pattern(&self.namespace, &self.prefix, pattern);
+            let mut removed = 0;
+            let mut cursor: u64 = 0;
+            loop {
+                let (next, mut keys): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(100)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(RedisCacheError::redis)?;
+                keys.retain(|k| super::is_entry_key(k));
+                if !keys.is_empty() {
+                    let unlinked: usize = redis::cmd("UNLINK")
+                        .arg(&keys)
+                        .query_async(&mut conn)
+                        .await
+                        .map_err(RedisCacheError::redis)?;
+                    removed += unlinked;
+                    self.sync_size_index(&mut conn, &keys).await?;
+                }
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+            Ok(removed)
+        }
+
+        /// Async counterpart of [`RedisCache::set_with_ttl`](super::RedisCache::set_with_ttl):
+        /// insert `key`/`val` with its own server-side TTL instead of the cache's, a zero
+        /// `ttl` meaning no expiry.
+        #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.set", level = "debug", skip_all, fields(namespace = %self.namespace, prefix = %self.prefix)))]
+        pub async fn set_with_ttl(
+            &self,
+            key: K,
+            val: V,
+            ttl: Duration,
+        ) -> Result<Option<V>, RedisCacheError> {
+            let mut conn = self.connection.clone();
+            let mut pipe = redis::pipe();
+            let key_str = self.generate_key(&key);
+
+            let serialized = traced!(
+                "cached.serialize",
+                super::encode_value(self.key_encoder, &key, &val)
+            )?;
+            pipe.get(&key_str);
+            if ttl.is_zero() {
+                // Disabled TTL: write the key without expiry (plain `SET`).
+                pipe.set::<&str, Vec<u8>>(&key_str, serialized).ignore();
+            } else {
+                pipe.pset_ex::<&str, Vec<u8>>(&key_str, serialized, super::ttl_millis(ttl)?)
+                    .ignore();
+            }
+
+            let res: (Option<Vec<u8>>,) = traced_await!("redis.query", pipe.query_async(&mut conn))
+                .map_err(RedisCacheError::redis)?;
+            self.sync_size_index(&mut conn, &[&key_str]).await?;
+            // REDIS-10: if the displaced previous value fails to decode, return Ok(None).
+            Ok(res.0.and_then(|bytes| {
+                traced!(
+                    "cached.deserialize",
+                    super::deserialize_cached_redis_value::<V>(&bytes)
+                )
+                .ok()
+                .map(|v| v.value)
+            }))
+        }
+
         /// Return the redis connection string as a [`ConnectionString`].
         ///
         /// `ConnectionString`'s `Debug`/`Display` render `[REDACTED connection string]`,
@@ -2789,8 +3865,70 @@ mod async_redis {
         }
     }
 
+    impl<K, V> AsyncRedisCache<K, V>
+    where
+        K: DeserializeOwned + Send + 'static,
+        V: DeserializeOwned + Send + 'static,
+    {
+        /// Async counterpart of [`RedisCache::scan`](super::RedisCache::scan): a `Stream` over
+        /// this cache's live entries, driven by a cursored `SCAN` on a clone of the cache's
+        /// connection. Nothing is sent until the stream is first polled. Same batching,
+        /// consistency and error behavior as the sync iterator.
+        pub fn scan(
+            &self,
+        ) -> impl futures_util::Stream<Item = Result<StoredEntry<K, V>, RedisCacheError>>
+        + Send
+        + use<K, V> {
+            let state = (
+                self.connection.clone(),
+                ScanCursor::new(
+                    super::clear_match_pattern(&self.namespace, &self.prefix),
+                    self.strict_deserialization,
+                ),
+                std::collections::VecDeque::new(),
+            );
+            futures_util::stream::unfold(state, |(mut conn, mut cursor, mut pending)| async move {
+                loop {
+                    if let Some(item) = pending.pop_front() {
+                        return Some((item, (conn, cursor, pending)));
+                    }
+                    if cursor.done {
+                        return None;
+                    }
+                    let fetched = async {
+                        let (next, keys): (u64, Vec<Vec<u8>>) = cursor
+                            .scan_cmd()
+                            .query_async(&mut conn)
+                            .await
+                            .map_err(RedisCacheError::redis)?;
+                        let keys = cursor.advance(next, keys);
+                        if keys.is_empty() {
+                            return Ok(());
+                        }
+                        let fetched = ScanCursor::fetch_script(&keys)
+                            .invoke_async(&mut conn)
+                            .await
+                            .map_err(RedisCacheError::redis)?;
+                        pending.extend(cursor.decode(keys, fetched));
+                        Ok(())
+                    }
+                    .await;
+                    if let Err(e) = fetched {
+                        cursor.done = true;
+                        return Some((Err(e), (conn, cursor, pending)));
+                    }
+                }
+            })
+        }
+    }
+
     impl<K, V> ConcurrentCacheBase for AsyncRedisCache<K, V> {
         type Error = RedisCacheError;
+
+        #[cfg(feature = "metrics")]
+        fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+            self.metrics_exporter.as_ref()
+        }
     }
 
     impl<K, V> ConcurrentCacheTtl for AsyncRedisCache<K, V> {
@@ -2848,75 +3986,66 @@ mod async_redis {
         V: Serialize + DeserializeOwned + Send,
     {
         /// Get a cached value
+        #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.get", level = "debug", skip_all, fields(namespace = %self.namespace, prefix = %self.prefix)))]
         async fn async_cache_get(&self, key: &K) -> Result<Option<V>, Self::Error> {
             let mut conn = self.connection.clone();
             let mut pipe = redis::pipe();
             let key_str = self.generate_key(key);
 
             pipe.get(&key_str);
+            let mut refreshed = false;
             if self.refresh.load(Ordering::Relaxed) {
                 let ttl = *self.ttl.lock();
                 // A zero (disabled) TTL means entries are stored without expiry; skip the
                 // refresh `PEXPIRE` so the key stays persistent (no TTL to renew).
                 if !ttl.is_zero() {
                     pipe.pexpire(&key_str, super::ttl_millis_i64(ttl)?).ignore();
+                    refreshed = true;
                 }
             }
-            let res: (Option<Vec<u8>>,) = pipe
-                .query_async(&mut conn)
-                .await
+            let res: (Option<Vec<u8>>,) = traced_await!("redis.query", pipe.query_async(&mut conn))
                 .map_err(RedisCacheError::redis)?;
-            match res.0 {
-                None => Ok(None),
-                Some(bytes) => match super::deserialize_cached_redis_value(&bytes) {
-                    Ok(v) => Ok(Some(v.value)),
-                    Err(e) if !self.strict_deserialization => {
-                        // Conditional self-heal delete (C6): only remove the key
-                        // if its current value still equals the corrupt `bytes`
-                        // we read, so a concurrent valid write is never clobbered.
-                        let _: i64 = super::SELF_HEAL_CONDITIONAL_DEL
+            let Some(bytes) = res.0 else {
+                crate::stores::record_lookup(&self.metrics_exporter, false);
+                return Ok(None);
+            };
+            let found = match traced!(
+                "cached.deserialize",
+                super::deserialize_cached_redis_value(&bytes)
+            ) {
+                Ok(v) => Ok(Some(v.value)),
+                Err(e) if !self.strict_deserialization => {
+                    // Conditional self-heal delete (C6): only remove the key
+                    // if its current value still equals the corrupt `bytes`
+                    // we read, so a concurrent valid write is never clobbered.
+                    let _: i64 = traced_await!(
+                        "redis.self_heal",
+                        super::SELF_HEAL_CONDITIONAL_DEL
                             .key(&key_str)
                             .arg(&bytes)
                             .invoke_async(&mut conn)
-                            .await
-                            .map_err(RedisCacheError::redis)?;
-                        let _ = e;
-                        Ok(None)
-                    }
-                    Err(e) => Err(e),
-                },
+                    )
+                    .map_err(RedisCacheError::redis)?;
+                    let _ = e;
+                    Ok(None)
+                }
+                Err(e) => Err(e),
+            };
+            // As in the sync `cache_get`: a refresh or a self-heal changes what the size
+            // index should record.
+            if refreshed || matches!(found, Ok(None)) {
+                self.sync_size_index(&mut conn, &[&key_str]).await?;
             }
+            if let Ok(found) = &found {
+                crate::stores::record_lookup(&self.metrics_exporter, found.is_some());
+            }
+            found
         }
 
         /// Set a cached value
         async fn async_cache_set(&self, key: K, val: V) -> Result<Option<V>, Self::Error> {
-            let mut conn = self.connection.clone();
-            let mut pipe = redis::pipe();
-            let key_str = self.generate_key(&key);
-
             let ttl = *self.ttl.lock();
-
-            let val = CachedRedisValue::new(val);
-            let serialized = rmp_serde::to_vec(&val).map_err(RedisCacheError::serialization)?;
-            pipe.get(&key_str);
-            if ttl.is_zero() {
-                // Disabled TTL: write the key without expiry (plain `SET`).
-                pipe.set::<String, Vec<u8>>(key_str, serialized).ignore();
-            } else {
-                pipe.pset_ex::<String, Vec<u8>>(key_str, serialized, super::ttl_millis(ttl)?)
-                    .ignore();
-            }
-
-            let res: (Option<Vec<u8>>,) = pipe
-                .query_async(&mut conn)
-                .await
-                .map_err(RedisCacheError::redis)?;
-            // REDIS-10: if the displaced previous value fails to decode, return Ok(None).
-            Ok(res.0.and_then(|bytes| {
-                super::deserialize_cached_redis_value::<V>(&bytes)
-                    .ok()
-                    .map(|v| v.value)
-            }))
+            self.set_with_ttl(key, val, ttl).await
         }
 
         /// Remove a cached value.
@@ -2931,20 +4060,23 @@ mod async_redis {
         ///   `Ok(None)` (the undecodable previous value is discarded).
         /// - **Strict (`strict_deserialization(true)`):** the corrupt entry is still removed
         ///   and the method returns `Err(RedisCacheError::CacheDeserialization { .. })`.
+        #[cfg_attr(feature = "tracing", tracing::instrument(name = "redis.remove", level = "debug", skip_all, fields(namespace = %self.namespace, prefix = %self.prefix)))]
         async fn async_cache_remove(&self, key: &K) -> Result<Option<V>, Self::Error> {
             let mut conn = self.connection.clone();
             let mut pipe = redis::pipe();
             let key_str = self.generate_key(key);
 
             pipe.get(&key_str);
-            pipe.del::<String>(key_str).ignore();
-            let res: (Option<Vec<u8>>,) = pipe
-                .query_async(&mut conn)
-                .await
+            pipe.del::<&str>(&key_str).ignore();
+            let res: (Option<Vec<u8>>,) = traced_await!("redis.query", pipe.query_async(&mut conn))
                 .map_err(RedisCacheError::redis)?;
+            self.sync_size_index(&mut conn, &[&key_str]).await?;
             match res.0 {
                 None => Ok(None),
-                Some(bytes) => match super::deserialize_cached_redis_value(&bytes) {
+                Some(bytes) => match traced!(
+                    "cached.deserialize",
+                    super::deserialize_cached_redis_value(&bytes)
+                ) {
                     Ok(v) => Ok(Some(v.value)),
                     Err(_) if !self.strict_deserialization => Ok(None),
                     Err(e) => Err(e),
@@ -2968,10 +4100,11 @@ mod async_redis {
             let mut conn = self.connection.clone();
             let key_str = self.generate_key(key);
             let removed: usize = redis::cmd("DEL")
-                .arg(key_str)
+                .arg(&key_str)
                 .query_async(&mut conn)
                 .await
                 .map_err(RedisCacheError::redis)?;
+            self.sync_size_index(&mut conn, &[&key_str]).await?;
             Ok(removed > 0)
         }
 
@@ -3069,7 +4202,9 @@ mod async_redis {
             val: &V,
         ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
             let mut conn = self.connection.clone();
+            let serialized = super::encode_value(self.key_encoder, key, val);
             let key = self.generate_key(key);
+            let size_index = self.size_index();
             let ttl = *self.ttl.lock();
             // Compute the milliseconds eagerly (only for a real, non-zero TTL) so any
             // error is surfaced before the future is awaited, matching the eager
@@ -3079,8 +4214,6 @@ mod async_redis {
             } else {
                 super::ttl_millis(ttl).map(Some)
             };
-            let serialized = rmp_serde::to_vec(&CachedRedisValueRef::new(val))
-                .map_err(RedisCacheError::serialization);
             async move {
                 let serialized: Vec<u8> = serialized?;
                 let ttl_ms = ttl_ms?;
@@ -3104,6 +4237,13 @@ mod async_redis {
                             .map_err(RedisCacheError::redis)?;
                     }
                 }
+                // `self` is not captured by the future, so this inlines `sync_size_index`.
+                if let Some(index) = size_index {
+                    super::size_index_sync(&index, &[&key])
+                        .invoke_async::<()>(&mut conn)
+                        .await
+                        .map_err(RedisCacheError::redis)?;
+                }
                 Ok(())
             }
         }
@@ -3957,6 +5097,9 @@ mod async_redis {
 )]
 pub use async_redis::{AsyncRedisCache, AsyncRedisCacheBuilder};
 
+mod mock;
+pub use mock::{MockRedisCache, MockRedisCacheBuilder, MockRedisFault, MockRedisServer};
+
 #[cfg(test)]
 mod error_source_tests {
     use std::error::Error;
@@ -4160,7 +5303,8 @@ mod error_source_tests {
         // which requires it to keep the same fields in the same order.
         let borrowed = "hello".to_string();
         assert_eq!(
-            rmp_serde::to_vec(&CachedRedisValueRef::new(&borrowed)).expect("serialize borrowed"),
+            rmp_serde::to_vec(&CachedRedisValueRef::new(&borrowed, None))
+                .expect("serialize borrowed"),
             bytes,
             "`cache_set_ref` must write the same positional layout as `cache_set`"
         );
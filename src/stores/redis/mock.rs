@@ -0,0 +1,1010 @@
+//! [`MockRedisCache`]: an in-process [`RedisCache`](super::RedisCache) stand-in for tests.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::{Mutex, MutexGuard};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use super::{
+    DEFAULT_NAMESPACE, RedisCacheBuildError, RedisCacheError, RedisSizeMode, ScanCursor,
+    clear_match_pattern, deserialize_cached_redis_value, encode_value, generate_redis_key,
+    is_entry_key, key_match_pattern, tag_set_key, ttl_millis, validate_size_mode,
+};
+use crate::stores::StoredEntry;
+use crate::stores::stored::{KeyEncoder, encode_key};
+use crate::time::{Duration, Instant};
+use crate::{
+    ConcurrentCacheBase, ConcurrentCacheRefreshOnHit, ConcurrentCacheTtl, ConcurrentCached,
+    ConcurrentCachedTags,
+};
+
+/// What [`MockRedisCache::scan`] yields: a snapshot of the entries, decoded.
+type MockScan<K, V> = std::vec::IntoIter<Result<StoredEntry<K, V>, RedisCacheError>>;
+
+/// Bytes [`MockRedisCache::corrupt`] writes: neither MessagePack nor the legacy JSON envelope.
+const CORRUPT: &[u8] = b"\xc1 corrupt cached value";
+
+/// A failure [`MockRedisServer`] injects into the next operation, or every operation during
+/// an [outage](MockRedisServer::set_outage). It fires before the operation touches the
+/// keyspace, so a failed write writes nothing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MockRedisFault {
+    /// [`RedisCacheError::Redis`] whose source is a `redis::RedisError` reporting
+    /// `is_connection_refusal()`.
+    ConnectionRefused,
+    /// [`RedisCacheError::Redis`] whose source is a `redis::RedisError` reporting
+    /// `is_timeout()`.
+    Timeout,
+    /// [`RedisCacheError::Pool`], as when no pooled connection frees up in time.
+    PoolExhausted,
+}
+
+impl MockRedisFault {
+    fn error(self) -> RedisCacheError {
+        use std::io::{Error, ErrorKind};
+        match self {
+            Self::ConnectionRefused => RedisCacheError::redis(
+                Error::new(ErrorKind::ConnectionRefused, "connection refused (mock)").into(),
+            ),
+            Self::Timeout => RedisCacheError::redis(
+                Error::new(ErrorKind::TimedOut, "command timed out (mock)").into(),
+            ),
+            Self::PoolExhausted => RedisCacheError::Pool {
+                source: Box::new(redis::RedisError::from((
+                    redis::ErrorKind::Io,
+                    "timed out waiting for a pooled connection (mock)",
+                ))),
+            },
+        }
+    }
+}
+
+/// The keyspace behind one or more [`MockRedisCache`]s, standing in for a Redis server.
+///
+/// Clones share the keyspace, so caches built on clones of one server see each other's keys
+/// exactly as caches on one Redis server do. Besides failure injection it gives raw access to
+/// the stored bytes, for asserting the key layout or planting payloads.
+#[derive(Clone, Default)]
+pub struct MockRedisServer {
+    inner: Arc<Mutex<Keyspace>>,
+}
+
+#[derive(Default)]
+struct Keyspace {
+    keys: HashMap<String, Slot>,
+    faults: VecDeque<MockRedisFault>,
+    outage: Option<MockRedisFault>,
+}
+
+struct Slot {
+    value: Value,
+    expires_at: Option<Instant>,
+}
+
+enum Value {
+    Bytes(Vec<u8>),
+    Set(BTreeSet<String>),
+}
+
+impl Keyspace {
+    /// The live slot at `key`, dropping it first if it has expired.
+    fn slot(&mut self, key: &str) -> Option<&mut Slot> {
+        if self
+            .keys
+            .get(key)
+            .and_then(|slot| slot.expires_at)
+            .is_some_and(|at| at <= Instant::now())
+        {
+            self.keys.remove(key);
+        }
+        self.keys.get_mut(key)
+    }
+
+    fn get(&mut self, key: &str) -> Option<Vec<u8>> {
+        match &self.slot(key)?.value {
+            Value::Bytes(bytes) => Some(bytes.clone()),
+            Value::Set(_) => None,
+        }
+    }
+
+    /// `SET`, or `PSETEX` with the millisecond TTL Redis would store.
+    fn set(&mut self, key: &str, bytes: Vec<u8>, ttl: Duration) -> Result<(), RedisCacheError> {
+        let expires_at = if ttl.is_zero() {
+            None
+        } else {
+            Some(Instant::now() + Duration::from_millis(ttl_millis(ttl)?))
+        };
+        self.keys.insert(
+            key.to_owned(),
+            Slot {
+                value: Value::Bytes(bytes),
+                expires_at,
+            },
+        );
+        Ok(())
+    }
+
+    fn expire(&mut self, key: &str, ttl: Duration) -> Result<(), RedisCacheError> {
+        let at = Instant::now() + Duration::from_millis(ttl_millis(ttl)?);
+        if let Some(slot) = self.slot(key) {
+            slot.expires_at = Some(at);
+        }
+        Ok(())
+    }
+
+    fn del(&mut self, key: &str) -> bool {
+        self.slot(key).is_some() && self.keys.remove(key).is_some()
+    }
+
+    fn sadd(&mut self, key: &str, member: &str) {
+        let slot = self.keys.entry(key.to_owned()).or_insert_with(|| Slot {
+            value: Value::Set(BTreeSet::new()),
+            expires_at: None,
+        });
+        if let Value::Set(members) = &mut slot.value {
+            members.insert(member.to_owned());
+        }
+    }
+
+    /// Drop the members of the set at `key` whose data keys are gone. The server checks a
+    /// sample of two per write; the mock checks them all.
+    fn prune(&mut self, key: &str) {
+        let now = Instant::now();
+        let Some(Slot {
+            value: Value::Set(members),
+            ..
+        }) = self.keys.get(key)
+        else {
+            return;
+        };
+        let gone: Vec<String> = members
+            .iter()
+            .filter(|member| {
+                self.keys
+                    .get(member.as_str())
+                    .is_none_or(|slot| slot.expires_at.is_some_and(|at| at <= now))
+            })
+            .cloned()
+            .collect();
+        if let Some(Slot {
+            value: Value::Set(members),
+            ..
+        }) = self.keys.get_mut(key)
+        {
+            for member in &gone {
+                members.remove(member);
+            }
+        }
+    }
+
+    /// Every live key matching the Redis glob `pattern`, as a full `SCAN` would return them.
+    fn scan(&mut self, pattern: &str) -> Vec<String> {
+        let now = Instant::now();
+        self.keys
+            .retain(|_, slot| slot.expires_at.is_none_or(|at| at > now));
+        let mut keys: Vec<String> = self
+            .keys
+            .keys()
+            .filter(|key| glob_match(pattern.as_bytes(), key.as_bytes()))
+            .cloned()
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// `PTTL`: -1 for a key without expiry, otherwise the milliseconds left.
+    fn pttl(&self, key: &str) -> i64 {
+        match self.keys.get(key).and_then(|slot| slot.expires_at) {
+            None => -1,
+            Some(at) => at.saturating_duration_since(Instant::now()).as_millis() as i64,
+        }
+    }
+}
+
+impl MockRedisServer {
+    /// An empty keyspace with no faults scheduled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the next operation of any cache on this server with `fault`. Calls queue up, one
+    /// fault per operation, and queued faults fire before an [outage](Self::set_outage).
+    pub fn fail_next(&self, fault: MockRedisFault) {
+        self.inner.lock().faults.push_back(fault);
+    }
+
+    /// Fail every operation with `fault` until called again with `None`.
+    pub fn set_outage(&self, fault: Option<MockRedisFault>) {
+        self.inner.lock().outage = fault;
+    }
+
+    /// Drop queued faults and end any outage.
+    pub fn clear_faults(&self) {
+        let mut keyspace = self.inner.lock();
+        keyspace.faults.clear();
+        keyspace.outage = None;
+    }
+
+    /// Every live key, entries and tag sets alike, sorted.
+    #[must_use]
+    pub fn keys(&self) -> Vec<String> {
+        self.inner.lock().scan("*")
+    }
+
+    /// The raw bytes stored at `key`, or `None` if it is absent, expired or a tag set.
+    #[must_use]
+    pub fn get_raw(&self, key: &str) -> Option<Vec<u8>> {
+        self.inner.lock().get(key)
+    }
+
+    /// The members of the set at `key`, sorted, or empty if it is absent or not a set.
+    #[must_use]
+    pub fn members(&self, key: &str) -> Vec<String> {
+        match self.inner.lock().slot(key).map(|slot| &slot.value) {
+            Some(Value::Set(members)) => members.iter().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Store `bytes` at `key` without expiry, like a plain `SET`.
+    pub fn set_raw(&self, key: &str, bytes: impl Into<Vec<u8>>) {
+        self.inner.lock().keys.insert(
+            key.to_owned(),
+            Slot {
+                value: Value::Bytes(bytes.into()),
+                expires_at: None,
+            },
+        );
+    }
+
+    /// Lock the keyspace for one operation, or fail it with the next scheduled fault.
+    fn connect(&self) -> Result<MutexGuard<'_, Keyspace>, RedisCacheError> {
+        let mut keyspace = self.inner.lock();
+        match keyspace.faults.pop_front().or(keyspace.outage) {
+            Some(fault) => Err(fault.error()),
+            None => Ok(keyspace),
+        }
+    }
+}
+
+impl std::fmt::Debug for MockRedisServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keyspace = self.inner.lock();
+        f.debug_struct("MockRedisServer")
+            .field("keys", &keyspace.keys.len())
+            .field("queued_faults", &keyspace.faults.len())
+            .field("outage", &keyspace.outage)
+            .finish()
+    }
+}
+
+/// Redis `stringmatchlen` glob: `*`, `?`, `[...]` classes with `^` and ranges, `\` escapes.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', rest @ ..] => (0..=text.len()).any(|at| glob_match(rest, &text[at..])),
+        [b'?', rest @ ..] => !text.is_empty() && glob_match(rest, &text[1..]),
+        [b'[', rest @ ..] => {
+            let Some((&c, text)) = text.split_first() else {
+                return false;
+            };
+            let (negate, mut class) = match rest {
+                [b'^', class @ ..] => (true, class),
+                class => (false, class),
+            };
+            let mut matched = false;
+            loop {
+                match class {
+                    [] => break,
+                    [b']', tail @ ..] => {
+                        class = tail;
+                        break;
+                    }
+                    [b'\\', literal, tail @ ..] => {
+                        matched |= *literal == c;
+                        class = tail;
+                    }
+                    [lo, b'-', hi, tail @ ..] if *hi != b']' => {
+                        matched |= (*lo.min(hi)..=*lo.max(hi)).contains(&c);
+                        class = tail;
+                    }
+                    [literal, tail @ ..] => {
+                        matched |= *literal == c;
+                        class = tail;
+                    }
+                }
+            }
+            matched != negate && glob_match(class, text)
+        }
+        [b'\\', literal, rest @ ..] | [literal, rest @ ..] => {
+            text.first() == Some(literal) && glob_match(rest, &text[1..])
+        }
+    }
+}
+
+/// Builder for [`MockRedisCache`], with the surface of [`RedisCacheBuilder`](super::RedisCacheBuilder).
+///
+/// The connection and pool settings are accepted and ignored, so a builder chain written for
+/// `RedisCache` compiles against the mock unchanged. [`server`](Self::server) picks the
+/// keyspace; without it the cache gets one of its own.
+pub struct MockRedisCacheBuilder<K, V> {
+    ttl: Option<Duration>,
+    refresh: bool,
+    namespace: String,
+    prefix: Option<String>,
+    strict_deserialization: bool,
+    size_mode: RedisSizeMode,
+    key_encoder: Option<KeyEncoder<K>>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    server: Option<MockRedisServer>,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> Default for MockRedisCacheBuilder<K, V>
+where
+    K: Display,
+    V: Serialize + DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> MockRedisCacheBuilder<K, V>
+where
+    K: Display,
+    V: Serialize + DeserializeOwned,
+{
+    /// Initialize a `MockRedisCacheBuilder`. As with `RedisCacheBuilder::new`, the prefix
+    /// must be set before [`build`](Self::build).
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            ttl: None,
+            refresh: false,
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            prefix: None,
+            strict_deserialization: false,
+            size_mode: RedisSizeMode::Unknown,
+            key_encoder: None,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            server: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Share `server`'s keyspace instead of a private one.
+    #[must_use]
+    pub fn server(mut self, server: &MockRedisServer) -> Self {
+        self.server = Some(server.clone());
+        self
+    }
+
+    /// See [`RedisCacheBuilder::ttl`](super::RedisCacheBuilder::ttl).
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// See [`RedisCacheBuilder::ttl_secs`](super::RedisCacheBuilder::ttl_secs).
+    #[must_use]
+    pub fn ttl_secs(self, secs: u64) -> Self {
+        self.ttl(Duration::from_secs(secs))
+    }
+
+    /// See [`RedisCacheBuilder::ttl_millis`](super::RedisCacheBuilder::ttl_millis).
+    #[must_use]
+    pub fn ttl_millis(self, millis: u64) -> Self {
+        self.ttl(Duration::from_millis(millis))
+    }
+
+    /// See [`RedisCacheBuilder::refresh_on_hit`](super::RedisCacheBuilder::refresh_on_hit).
+    #[must_use]
+    pub fn refresh_on_hit(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// See [`RedisCacheBuilder::namespace`](super::RedisCacheBuilder::namespace).
+    #[must_use]
+    pub fn namespace<S: AsRef<str>>(mut self, namespace: S) -> Self {
+        self.namespace = namespace.as_ref().to_string();
+        self
+    }
+
+    /// See [`RedisCacheBuilder::prefix`](super::RedisCacheBuilder::prefix).
+    #[must_use]
+    pub fn prefix<S: AsRef<str>>(mut self, prefix: S) -> Self {
+        self.prefix = Some(prefix.as_ref().to_string());
+        self
+    }
+
+    /// Ignored: the mock connects to nothing.
+    #[must_use]
+    pub fn connection_string(self, _cs: &str) -> Self {
+        self
+    }
+
+    /// Ignored: the mock has no connection pool.
+    #[must_use]
+    pub fn connection_pool_max_size(self, _max_size: u32) -> Self {
+        self
+    }
+
+    /// Ignored: the mock has no connection pool.
+    #[must_use]
+    pub fn connection_pool_min_idle(self, _min_idle: u32) -> Self {
+        self
+    }
+
+    /// Ignored: the mock has no connection pool.
+    #[must_use]
+    pub fn connection_pool_max_lifetime(self, _max_lifetime: Duration) -> Self {
+        self
+    }
+
+    /// Ignored: the mock has no connection pool.
+    #[must_use]
+    pub fn connection_pool_idle_timeout(self, _idle_timeout: Duration) -> Self {
+        self
+    }
+
+    /// Ignored: the mock has no connection pool. Use
+    /// [`MockRedisFault::PoolExhausted`] to exercise a pool timeout.
+    #[must_use]
+    pub fn connection_pool_connection_timeout(self, _connection_timeout: Duration) -> Self {
+        self
+    }
+
+    /// See [`RedisCacheBuilder::strict_deserialization`](super::RedisCacheBuilder::strict_deserialization).
+    #[must_use]
+    pub fn strict_deserialization(mut self, strict: bool) -> Self {
+        self.strict_deserialization = strict;
+        self
+    }
+
+    /// See [`RedisCacheBuilder::size_mode`](super::RedisCacheBuilder::size_mode). The mock
+    /// keeps no index key, so `Exact` counts the live entries directly and never drifts.
+    #[must_use]
+    pub fn size_mode(mut self, mode: RedisSizeMode) -> Self {
+        self.size_mode = mode;
+        self
+    }
+
+    /// See [`RedisCacheBuilder::metrics_name`](super::RedisCacheBuilder::metrics_name).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Build the cache, rejecting the same configurations as
+    /// [`RedisCacheBuilder::build`](super::RedisCacheBuilder::build).
+    ///
+    /// # Errors
+    ///
+    /// - `Build(BuildError::MissingRequired("prefix"))`: no key prefix was set.
+    /// - `Build(BuildError::InvalidValue { .. })`: a zero `ttl`, an empty `prefix`, or a
+    ///   `Scan` size mode with a zero limit.
+    pub fn build(self) -> Result<MockRedisCache<K, V>, RedisCacheBuildError> {
+        let Some(prefix) = self.prefix else {
+            return Err(crate::stores::BuildError::MissingRequired("prefix").into());
+        };
+        let ttl = match self.ttl {
+            Some(ttl) => {
+                crate::stores::validate_ttl(ttl)?;
+                ttl
+            }
+            None => Duration::ZERO,
+        };
+        if prefix.is_empty() {
+            return Err(crate::stores::BuildError::InvalidValue {
+                field: "prefix",
+                reason: "prefix must be non-empty: it is what scopes cache_clear to this \
+                         cache; with an empty prefix cache_clear would delete every key \
+                         under the namespace",
+            }
+            .into());
+        }
+        validate_size_mode(self.size_mode)?;
+        Ok(MockRedisCache {
+            server: self.server.unwrap_or_default(),
+            ttl: Mutex::new(ttl),
+            refresh: AtomicBool::new(self.refresh),
+            namespace: self.namespace,
+            prefix,
+            strict_deserialization: self.strict_deserialization,
+            size_mode: self.size_mode,
+            key_encoder: self.key_encoder,
+            metrics_exporter: self.metrics_exporter,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<K: Serialize, V> MockRedisCacheBuilder<K, V> {
+    /// See [`RedisCacheBuilder::store_keys`](super::RedisCacheBuilder::store_keys).
+    #[must_use]
+    pub fn store_keys(mut self, store: bool) -> Self {
+        self.key_encoder = store.then_some(encode_key::<K> as KeyEncoder<K>);
+        self
+    }
+}
+
+/// In-process stand-in for [`RedisCache`](super::RedisCache), for testing code that uses it
+/// without a Redis server.
+///
+/// It implements the same traits with the same error type ([`RedisCacheError`]) and mirrors
+/// the store's observable behavior: keys are laid out as `{namespace}:{prefix}:{key}` with the
+/// same escaping, values are written in the same MessagePack envelope (legacy JSON entries
+/// included on read), TTLs are kept to the millisecond and renewed by `refresh_on_hit`, an
+/// undecodable entry is self-healed or reported according to `strict_deserialization`, and
+/// tags are sets at `{namespace}:{prefix}:tag:{tag}` cleared along with the entries. The
+/// bytes at a key are those `RedisCache` would write there, so they can be planted in or
+/// read from the [`MockRedisServer`] directly.
+///
+/// Failures are injected through the server: [`fail_next`](MockRedisServer::fail_next) and
+/// [`set_outage`](MockRedisServer::set_outage) make operations return the connection,
+/// timeout and pool errors a real server produces, and [`corrupt`](Self::corrupt) plants an
+/// undecodable payload.
+///
+/// ```rust
+/// use cached::ConcurrentCached;
+/// use cached::stores::{MockRedisCache, MockRedisFault};
+///
+/// let cache: MockRedisCache<u32, String> = MockRedisCache::builder("users")
+///     .ttl_secs(60)
+///     .build()
+///     .unwrap();
+/// cache.cache_set(1, "ada".to_string()).unwrap();
+/// assert_eq!(cache.server().keys(), ["cached-redis-store:users:1"]);
+///
+/// cache.server().fail_next(MockRedisFault::Timeout);
+/// assert!(cache.cache_get(&1).is_err());
+///
+/// cache.corrupt(&1);
+/// assert_eq!(cache.cache_get(&1).unwrap(), None); // self-healed
+/// assert!(cache.server().keys().is_empty());
+/// ```
+pub struct MockRedisCache<K, V> {
+    server: MockRedisServer,
+    ttl: Mutex<Duration>,
+    refresh: AtomicBool,
+    namespace: String,
+    prefix: String,
+    strict_deserialization: bool,
+    size_mode: RedisSizeMode,
+    key_encoder: Option<KeyEncoder<K>>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    // Fn-pointer phantom, as on `RedisCache`: no live `K`/`V` is held.
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> std::fmt::Debug for MockRedisCache<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockRedisCache")
+            .field("namespace", &self.namespace)
+            .field("prefix", &self.prefix)
+            .field("ttl", &*self.ttl.lock())
+            .field("refresh", &self.refresh.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> Clone for MockRedisCache<K, V> {
+    /// Shares the server, like `RedisCache` shares its pool, and snapshots the TTL.
+    fn clone(&self) -> Self {
+        Self {
+            server: self.server.clone(),
+            ttl: Mutex::new(*self.ttl.lock()),
+            refresh: AtomicBool::new(self.refresh.load(Ordering::Relaxed)),
+            namespace: self.namespace.clone(),
+            prefix: self.prefix.clone(),
+            strict_deserialization: self.strict_deserialization,
+            size_mode: self.size_mode,
+            key_encoder: self.key_encoder,
+            metrics_exporter: self.metrics_exporter.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K, V> MockRedisCache<K, V>
+where
+    K: Display,
+    V: Serialize + DeserializeOwned,
+{
+    /// Initialize a [`MockRedisCacheBuilder`] with the required key `prefix`.
+    #[must_use]
+    pub fn builder(prefix: impl Into<String>) -> MockRedisCacheBuilder<K, V> {
+        MockRedisCacheBuilder::new().prefix(prefix.into())
+    }
+
+    /// The server holding this cache's keys, for injecting faults and inspecting the keyspace.
+    #[must_use]
+    pub fn server(&self) -> &MockRedisServer {
+        &self.server
+    }
+
+    /// The Redis key `key` is stored at: `{namespace}:{prefix}:{key}`, escaped.
+    #[must_use]
+    pub fn redis_key(&self, key: &K) -> String {
+        generate_redis_key(&self.namespace, &self.prefix, &key.to_string())
+    }
+
+    /// Replace the payload at `key` with bytes that decode as neither envelope, keeping its
+    /// expiry (or, for an absent key, writing with the cache's TTL). The next read self-heals
+    /// or, in strict mode, fails with [`RedisCacheError::CacheDeserialization`].
+    pub fn corrupt(&self, key: &K) {
+        let key_str = self.redis_key(key);
+        let mut keyspace = self.server.inner.lock();
+        match keyspace.slot(&key_str) {
+            Some(slot) => slot.value = Value::Bytes(CORRUPT.to_vec()),
+            None => {
+                let ttl = *self.ttl.lock();
+                // A zero TTL means no expiry, so `set` cannot fail here.
+                let _ = keyspace.set(&key_str, CORRUPT.to_vec(), ttl);
+            }
+        }
+    }
+
+    /// See [`RedisCache::remove_matching`](super::RedisCache::remove_matching).
+    ///
+    /// # Errors
+    ///
+    /// Returns the scheduled [`MockRedisFault`], if any.
+    pub fn remove_matching(&self, pattern: &str) -> Result<usize, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let pattern = key_match_pattern(&self.namespace, &self.prefix, pattern);
+        let keys = keyspace.scan(&pattern);
+        Ok(keys
+            .iter()
+            .filter(|key| is_entry_key(key.as_bytes()))
+            .filter(|key| keyspace.del(key))
+            .count())
+    }
+
+    /// See [`RedisCache::set_with_ttl`](super::RedisCache::set_with_ttl).
+    ///
+    /// # Errors
+    ///
+    /// Returns the scheduled [`MockRedisFault`], if any.
+    pub fn set_with_ttl(
+        &self,
+        key: K,
+        val: V,
+        ttl: Duration,
+    ) -> Result<Option<V>, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let previous = self.write(&mut keyspace, &key, &val, ttl, &[])?;
+        Ok(previous.and_then(|bytes| {
+            deserialize_cached_redis_value::<V>(&bytes)
+                .ok()
+                .map(|v| v.value)
+        }))
+    }
+
+    /// Write `val` at `key_str` with `ttl` and add it to `tags`, returning the raw bytes it
+    /// displaced.
+    fn write(
+        &self,
+        keyspace: &mut Keyspace,
+        key: &K,
+        val: &V,
+        ttl: Duration,
+        tags: &[&str],
+    ) -> Result<Option<Vec<u8>>, RedisCacheError> {
+        let key_str = self.redis_key(key);
+        let serialized = encode_value(self.key_encoder, key, val)?;
+        let previous = keyspace.get(&key_str);
+        keyspace.set(&key_str, serialized, ttl)?;
+        for tag in tags {
+            let set = tag_set_key(&self.namespace, &self.prefix, tag);
+            keyspace.sadd(&set, &key_str);
+            keyspace.prune(&set);
+        }
+        Ok(previous)
+    }
+}
+
+impl<K, V> MockRedisCache<K, V>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+{
+    /// See [`RedisCache::scan`](super::RedisCache::scan). The mock takes one snapshot of the
+    /// keyspace up front rather than walking it in batches.
+    ///
+    /// # Errors
+    ///
+    /// Returns the scheduled [`MockRedisFault`], if any.
+    pub fn scan(&self) -> Result<MockScan<K, V>, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let cursor = ScanCursor::new(
+            clear_match_pattern(&self.namespace, &self.prefix),
+            self.strict_deserialization,
+        );
+        let keys: Vec<Vec<u8>> = keyspace
+            .scan(&cursor.pattern)
+            .into_iter()
+            .filter(|key| is_entry_key(key.as_bytes()))
+            .map(String::into_bytes)
+            .collect();
+        let fetched = keys
+            .iter()
+            .map(|key| {
+                let key = std::str::from_utf8(key).unwrap_or_default();
+                (keyspace.get(key), keyspace.pttl(key))
+            })
+            .collect();
+        Ok(cursor.decode(keys, fetched).collect::<Vec<_>>().into_iter())
+    }
+}
+
+impl<K, V> ConcurrentCacheBase for MockRedisCache<K, V> {
+    type Error = RedisCacheError;
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    /// Answered according to the cache's [`RedisSizeMode`]. Both counting modes count the
+    /// live entries exactly; `Scan` caps the count at its limit.
+    fn cache_size(&self) -> Result<Option<usize>, RedisCacheError> {
+        let limit = match self.size_mode {
+            RedisSizeMode::Unknown => return Ok(None),
+            RedisSizeMode::Exact => usize::MAX,
+            RedisSizeMode::Scan { limit } => limit,
+        };
+        let mut keyspace = self.server.connect()?;
+        let pattern = clear_match_pattern(&self.namespace, &self.prefix);
+        let entries = keyspace
+            .scan(&pattern)
+            .iter()
+            .filter(|key| is_entry_key(key.as_bytes()))
+            .count();
+        Ok(Some(entries.min(limit)))
+    }
+}
+
+impl<K, V> ConcurrentCacheTtl for MockRedisCache<K, V> {
+    fn ttl(&self) -> Option<Duration> {
+        let ttl = *self.ttl.lock();
+        if ttl.is_zero() { None } else { Some(ttl) }
+    }
+
+    /// As on `RedisCache`: applies to later writes (and to keys renewed by `refresh_on_hit`),
+    /// and a zero `ttl` disables expiry.
+    fn set_ttl(&self, ttl: Duration) -> Option<Duration> {
+        let old = std::mem::replace(&mut *self.ttl.lock(), ttl);
+        if old.is_zero() { None } else { Some(old) }
+    }
+
+    fn unset_ttl(&self) -> Option<Duration> {
+        self.set_ttl(Duration::ZERO)
+    }
+}
+
+impl<K, V> ConcurrentCacheRefreshOnHit for MockRedisCache<K, V> {
+    fn refresh_on_hit(&self) -> bool {
+        self.refresh.load(Ordering::Relaxed)
+    }
+
+    fn set_refresh_on_hit(&self, refresh: bool) -> bool {
+        self.refresh.swap(refresh, Ordering::Relaxed)
+    }
+}
+
+impl<K, V> ConcurrentCached<K, V> for MockRedisCache<K, V>
+where
+    K: Display + Clone,
+    V: Serialize + DeserializeOwned,
+{
+    fn cache_get(&self, key: &K) -> Result<Option<V>, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let key_str = self.redis_key(key);
+        let bytes = keyspace.get(&key_str);
+        if self.refresh.load(Ordering::Relaxed) {
+            let ttl = *self.ttl.lock();
+            if !ttl.is_zero() {
+                keyspace.expire(&key_str, ttl)?;
+            }
+        }
+        let Some(bytes) = bytes else {
+            crate::stores::record_lookup(&self.metrics_exporter, false);
+            return Ok(None);
+        };
+        let found = match deserialize_cached_redis_value(&bytes) {
+            Ok(v) => Some(v.value),
+            Err(_) if !self.strict_deserialization => {
+                keyspace.del(&key_str);
+                None
+            }
+            Err(e) => return Err(e),
+        };
+        crate::stores::record_lookup(&self.metrics_exporter, found.is_some());
+        Ok(found)
+    }
+
+    fn cache_set(&self, key: K, val: V) -> Result<Option<V>, RedisCacheError> {
+        let ttl = *self.ttl.lock();
+        self.set_with_ttl(key, val, ttl)
+    }
+
+    /// See [`RedisCache`](super::RedisCache)'s `cache_remove`: the entry is always removed,
+    /// and an undecodable previous value is `Ok(None)`, or an error in strict mode.
+    fn cache_remove(&self, key: &K) -> Result<Option<V>, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let key_str = self.redis_key(key);
+        let Some(bytes) = keyspace.get(&key_str) else {
+            return Ok(None);
+        };
+        keyspace.del(&key_str);
+        match deserialize_cached_redis_value(&bytes) {
+            Ok(v) => Ok(Some(v.value)),
+            Err(_) if !self.strict_deserialization => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn cache_remove_entry(&self, key: &K) -> Result<Option<(K, V)>, Self::Error> {
+        self.cache_remove(key)
+            .map(|opt| opt.map(|v| (key.clone(), v)))
+    }
+
+    fn cache_delete(&self, key: &K) -> Result<bool, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        Ok(keyspace.del(&self.redis_key(key)))
+    }
+
+    /// Removes every key in this cache's `{namespace}:{prefix}:` scope, tag sets included.
+    fn cache_clear(&self) -> Result<(), RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        for key in keyspace.scan(&clear_match_pattern(&self.namespace, &self.prefix)) {
+            keyspace.keys.remove(&key);
+        }
+        Ok(())
+    }
+
+    fn cache_reset(&self) -> Result<(), RedisCacheError> {
+        self.cache_clear()
+    }
+
+    fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
+        self.cache_get(k).map(|v| v.is_some())
+    }
+}
+
+/// Tag sets behave as on [`RedisCache`](super::RedisCache): only `set_with_tags` adds to them,
+/// and it prunes the members whose entries are gone from each set it writes to.
+impl<K, V> ConcurrentCachedTags<K, V> for MockRedisCache<K, V>
+where
+    K: Display + Clone,
+    V: Serialize + DeserializeOwned,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        key: K,
+        val: V,
+        tags: &[T],
+    ) -> Result<Option<V>, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let tags: Vec<&str> = tags.iter().map(AsRef::as_ref).collect();
+        let ttl = *self.ttl.lock();
+        let previous = self.write(&mut keyspace, &key, &val, ttl, &tags)?;
+        Ok(previous.and_then(|bytes| {
+            deserialize_cached_redis_value::<V>(&bytes)
+                .ok()
+                .map(|v| v.value)
+        }))
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let members = match keyspace
+            .keys
+            .remove(&tag_set_key(&self.namespace, &self.prefix, tag))
+        {
+            Some(Slot {
+                value: Value::Set(members),
+                ..
+            }) => members,
+            _ => return Ok(0),
+        };
+        Ok(members.iter().filter(|key| keyspace.del(key)).count())
+    }
+}
+
+impl<K, V> crate::SerializeCached<K, V> for MockRedisCache<K, V>
+where
+    K: Display + Clone,
+    V: Serialize + DeserializeOwned,
+{
+    fn cache_set_ref(&self, key: &K, val: &V) -> Result<(), RedisCacheError> {
+        let mut keyspace = self.server.connect()?;
+        let ttl = *self.ttl.lock();
+        self.write(&mut keyspace, key, val, ttl, &[]).map(|_| ())
+    }
+}
+
+/// Runs the synchronous operations, so the mock also stands in for `AsyncRedisCache`.
+#[cfg(feature = "async_core")]
+impl<K, V> crate::ConcurrentCachedAsync<K, V> for MockRedisCache<K, V>
+where
+    K: Display + Clone + Send + Sync,
+    V: Serialize + DeserializeOwned + Send,
+{
+    async fn async_cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_get(self, k)
+    }
+
+    async fn async_cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    async fn async_cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_remove(self, k)
+    }
+
+    async fn async_cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
+        ConcurrentCached::cache_remove_entry(self, k)
+    }
+
+    async fn async_cache_delete(&self, k: &K) -> Result<bool, Self::Error> {
+        ConcurrentCached::cache_delete(self, k)
+    }
+
+    async fn async_cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
+        ConcurrentCached::cache_contains(self, k)
+    }
+
+    async fn async_cache_clear(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_clear(self)
+    }
+
+    async fn async_cache_reset(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_reset(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn glob_matches_like_redis() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("*", "", true),
+            ("a*c", "abbbc", true),
+            ("a?c", "abc", true),
+            ("a?c", "ac", false),
+            ("[abc]x", "bx", true),
+            ("[^abc]x", "bx", false),
+            ("[a-c]x", "cx", true),
+            ("[c-a]x", "bx", true),
+            ("\\*", "*", true),
+            ("\\*", "a", false),
+            ("[\\]]", "]", true),
+            ("ns:p:*", "ns:p:k", true),
+            ("ns:p:*", "ns:q:k", false),
+        ];
+        for &(pattern, text, expected) in cases {
+            assert_eq!(
+                glob_match(pattern.as_bytes(), text.as_bytes()),
+                expected,
+                "{pattern:?} against {text:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,1222 @@
+use super::{Cached, CachedRead, DefaultHashBuilder, StripedCounter};
+use crate::lru_list::LRUList;
+use crate::{CachedIter, CachedPeek};
+use hashbrown::HashTable;
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+#[cfg(feature = "async_core")]
+use {super::CachedGetOrSetAsync, std::future::Future};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+/// Default share of the capacity given to the small queue, as in the S3-FIFO paper.
+pub(super) const DEFAULT_SMALL_RATIO: f64 = 0.1;
+
+/// Saturation point of the per-entry access counter.
+const MAX_FREQ: u8 = 3;
+
+/// The three S3-FIFO queues. `Small` and `Main` hold resident entries, `Ghost` only the keys
+/// of entries recently evicted from `Small`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Queue {
+    Small,
+    Main,
+    Ghost,
+}
+
+/// Where a key lives: its queue and its slot in that queue's slab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Slot {
+    queue: Queue,
+    index: usize,
+}
+
+impl Slot {
+    fn is_resident(self) -> bool {
+        self.queue != Queue::Ghost
+    }
+}
+
+/// A resident entry. `freq` is bumped through `&self`, so a hit needs no exclusive access.
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    freq: AtomicU8,
+}
+
+impl<K, V> Entry<K, V> {
+    fn new(key: K, value: V) -> Self {
+        Self {
+            key,
+            value,
+            freq: AtomicU8::new(0),
+        }
+    }
+
+    /// Count an access, saturating at [`MAX_FREQ`]. Concurrent hits may lose an increment,
+    /// which only makes the entry look slightly colder.
+    fn touch(&self) {
+        let freq = self.freq.load(Ordering::Relaxed);
+        if freq < MAX_FREQ {
+            self.freq.store(freq + 1, Ordering::Relaxed);
+        }
+    }
+
+    fn into_pair(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for Entry<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            freq: AtomicU8::new(self.freq.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// The queues and their lengths, kept apart from the hash table so a table probe can borrow
+/// the queues while the table is borrowed mutably.
+#[derive(Clone)]
+struct Queues<K, V> {
+    small: LRUList<Entry<K, V>>,
+    main: LRUList<Entry<K, V>>,
+    ghost: LRUList<K>,
+    /// Lengths of `[small, main, ghost]`; `LRUList` does not track its own.
+    lens: [usize; 3],
+}
+
+impl<K, V> Queues<K, V> {
+    fn with_capacity(capacity: usize) -> Result<Self, super::BuildError> {
+        Ok(Self {
+            small: LRUList::with_capacity(0),
+            main: LRUList::try_with_capacity(capacity)?,
+            ghost: LRUList::with_capacity(0),
+            lens: [0; 3],
+        })
+    }
+
+    fn len(&self, queue: Queue) -> usize {
+        self.lens[queue as usize]
+    }
+
+    fn resident(&self) -> usize {
+        self.lens[Queue::Small as usize] + self.lens[Queue::Main as usize]
+    }
+
+    fn key(&self, slot: Slot) -> &K {
+        match slot.queue {
+            Queue::Small => &self.small.get(slot.index).key,
+            Queue::Main => &self.main.get(slot.index).key,
+            Queue::Ghost => self.ghost.get(slot.index),
+        }
+    }
+
+    fn resident_queue(&self, queue: Queue) -> &LRUList<Entry<K, V>> {
+        match queue {
+            Queue::Small => &self.small,
+            Queue::Main => &self.main,
+            Queue::Ghost => unreachable!("the ghost queue holds no values"),
+        }
+    }
+
+    fn resident_queue_mut(&mut self, queue: Queue) -> &mut LRUList<Entry<K, V>> {
+        match queue {
+            Queue::Small => &mut self.small,
+            Queue::Main => &mut self.main,
+            Queue::Ghost => unreachable!("the ghost queue holds no values"),
+        }
+    }
+
+    fn entry(&self, slot: Slot) -> &Entry<K, V> {
+        self.resident_queue(slot.queue).get(slot.index)
+    }
+
+    fn entry_mut(&mut self, slot: Slot) -> &mut Entry<K, V> {
+        self.resident_queue_mut(slot.queue).get_mut(slot.index)
+    }
+
+    fn tail(&self, queue: Queue) -> Slot {
+        let index = match queue {
+            Queue::Small => self.small.back(),
+            Queue::Main => self.main.back(),
+            Queue::Ghost => self.ghost.back(),
+        };
+        Slot { queue, index }
+    }
+
+    fn push_resident(&mut self, queue: Queue, entry: Entry<K, V>) -> Slot {
+        self.lens[queue as usize] += 1;
+        let index = self.resident_queue_mut(queue).push_front(entry);
+        Slot { queue, index }
+    }
+
+    fn remove_resident(&mut self, slot: Slot) -> Entry<K, V> {
+        self.lens[slot.queue as usize] -= 1;
+        self.resident_queue_mut(slot.queue).remove(slot.index)
+    }
+
+    fn push_ghost(&mut self, key: K) -> Slot {
+        self.lens[Queue::Ghost as usize] += 1;
+        Slot {
+            queue: Queue::Ghost,
+            index: self.ghost.push_front(key),
+        }
+    }
+
+    fn remove_ghost(&mut self, slot: Slot) -> K {
+        self.lens[Queue::Ghost as usize] -= 1;
+        self.ghost.remove(slot.index)
+    }
+
+    fn clear(&mut self) {
+        self.small.clear();
+        self.main.clear();
+        self.ghost.clear();
+        self.lens = [0; 3];
+    }
+
+    fn allocation_size(&self) -> usize {
+        self.small.allocation_size() + self.main.allocation_size() + self.ghost.allocation_size()
+    }
+
+    /// Resident entries: `Small` newest first, then `Main` newest first.
+    fn iter(&self) -> impl Iterator<Item = &Entry<K, V>> {
+        self.small.iter().chain(self.main.iter())
+    }
+}
+
+/// Split `capacity` into the small-queue and ghost-queue bounds for `small_ratio`.
+fn queue_caps(capacity: usize, small_ratio: f64) -> (usize, usize) {
+    let small = ((capacity as f64 * small_ratio) as usize).clamp(1, capacity);
+    (small, (capacity - small).max(1))
+}
+
+/// S3-FIFO Cache
+///
+/// Holds up to `max_size` entries in two FIFO queues: a small queue that every new key enters,
+/// and a main queue for keys that proved useful. A hit only bumps a 2-bit access counter on
+/// the entry; nothing is relinked. When the small queue's oldest entry comes up for eviction
+/// it moves to the main queue if it was read since it arrived, and is evicted otherwise, with
+/// its key remembered on a ghost queue. Writing a key that is on the ghost queue admits it
+/// straight to the main queue. The main queue evicts like CLOCK: an entry with a non-zero
+/// counter has it decremented and goes back to the head. One-hit wonders therefore leave
+/// through the small queue without disturbing the main queue.
+///
+/// Because a hit only touches an atomic, `S3FifoCache` implements [`CachedRead`]: lookups
+/// through `&self` still feed the eviction policy. [`ShardedS3FifoCache`](crate::ShardedS3FifoCache)
+/// serves `cache_get` under the shard's read lock for the same reason.
+///
+/// Eviction, `on_evict` and the counters follow [`LruCache`](crate::LruCache). Ghost keys are
+/// not entries: they are invisible to lookups, `cache_size` and iteration, and hold at most
+/// `max_size` further clones of `K`.
+///
+/// Note: This cache is in-memory only
+///
+/// The optional type parameter `S` selects the hash builder, as on `LruCache`. Supply a custom
+/// `S` via [`S3FifoCacheBuilder::hasher`].
+///
+/// # Example
+///
+/// ```rust
+/// use cached::{Cached, S3FifoCache};
+///
+/// let mut cache = S3FifoCache::builder()
+///     .max_size(4)
+///     .small_ratio(0.25)
+///     .build()
+///     .unwrap();
+/// cache.cache_set(1, "one");
+/// cache.cache_get(&1); // read while in the small queue: promoted when it reaches the tail
+/// for k in 2..=5 {
+///     cache.cache_set(k, "once");
+/// }
+/// assert_eq!(cache.main_key_order(), [1]);
+/// assert_eq!(cache.cache_get(&1), Some(&"one"));
+/// assert_eq!(cache.ghost_key_order(), [2]);
+/// ```
+pub struct S3FifoCache<K, V, S = DefaultHashBuilder> {
+    // `store` maps a hash of K -> the key's slot on one of the three queues
+    store: HashTable<Slot>,
+    hash_builder: S,
+    queues: Queues<K, V>,
+    capacity: usize,
+    small_ratio: f64,
+    /// Bound of the small queue; it may run over while the main queue is empty.
+    small_cap: usize,
+    ghost_cap: usize,
+    hits: StripedCounter,
+    misses: StripedCounter,
+    evictions: AtomicU64,
+    pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// When false, lookups skip incrementing `hits` and `misses`. The sharded store keeps its
+    /// own per-shard counters.
+    pub(crate) track_hit_miss: bool,
+    tags: super::TagIndex<K>,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeys<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
+}
+
+impl<K, V, S> Clone for S3FifoCache<K, V, S>
+where
+    K: Clone + Hash + Eq,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            hash_builder: self.hash_builder.clone(),
+            queues: self.queues.clone(),
+            capacity: self.capacity,
+            small_ratio: self.small_ratio,
+            small_cap: self.small_cap,
+            ghost_cap: self.ghost_cap,
+            hits: self.hits.snapshot(),
+            misses: self.misses.snapshot(),
+            evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
+            on_evict: self.on_evict.clone(),
+            track_hit_miss: self.track_hit_miss,
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for S3FifoCache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3FifoCache")
+            .field("capacity", &self.capacity)
+            .field("small", &self.queues.len(Queue::Small))
+            .field("main", &self.queues.len(Queue::Main))
+            .field("ghost", &self.queues.len(Queue::Ghost))
+            .field("hits", &self.hits.load())
+            .field("misses", &self.misses.load())
+            .field("evictions", &self.evictions.load(Ordering::Relaxed))
+            .field("on_evict", &self.on_evict.as_ref().map(|_| "on_evict"))
+            .finish()
+    }
+}
+
+/// Builder for [`S3FifoCache`].
+pub struct S3FifoCacheBuilder<K, V, S = DefaultHashBuilder> {
+    size: Option<usize>,
+    small_ratio: f64,
+    on_evict: Option<super::OnEvict<K, V>>,
+    hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
+}
+
+impl<K, V> Default for S3FifoCacheBuilder<K, V, DefaultHashBuilder> {
+    fn default() -> Self {
+        Self {
+            size: None,
+            small_ratio: DEFAULT_SMALL_RATIO,
+            on_evict: None,
+            hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+        }
+    }
+}
+
+impl<K, V> S3FifoCacheBuilder<K, V> {
+    /// Create a builder with default settings. Equivalent to [`S3FifoCache::builder`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, S> S3FifoCacheBuilder<K, V, S> {
+    /// Set the maximum number of entries. Required -- `build` returns `Err` if not set.
+    #[doc(alias = "size")]
+    #[doc(alias = "capacity")]
+    #[must_use]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.size = Some(max_size);
+        self
+    }
+
+    /// Set the share of `max_size` given to the small queue, strictly between `0` and `1`.
+    /// Defaults to `0.1`. The small queue always holds at least one entry.
+    #[must_use]
+    pub fn small_ratio(mut self, small_ratio: f64) -> Self {
+        self.small_ratio = small_ratio;
+        self
+    }
+
+    /// Set a callback to be invoked when an entry is evicted or removed.
+    ///
+    /// Use [`cache_clear_with_on_evict`](S3FifoCache::cache_clear_with_on_evict)
+    /// instead of [`cache_clear`](crate::Cached::cache_clear) to opt into callback
+    /// firing and eviction counter increments when clearing all entries.
+    #[must_use]
+    pub fn on_evict(mut self, on_evict: impl Fn(&K, &V) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(on_evict));
+        self
+    }
+
+    /// Switch to a custom hash builder `S2`, returning a builder parameterized on `S2`.
+    #[doc(alias = "with_hasher")]
+    #[must_use]
+    pub fn hasher<S2: BuildHasher>(self, hasher: S2) -> S3FifoCacheBuilder<K, V, S2> {
+        S3FifoCacheBuilder {
+            size: self.size,
+            small_ratio: self.small_ratio,
+            on_evict: self.on_evict,
+            hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
+        }
+    }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Ghost keys are not counted.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
+    /// Build the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError::MissingRequired`](super::BuildError::MissingRequired) if `max_size`
+    /// was not set, or [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `max_size` is `0`, `small_ratio` is not strictly between `0` and `1`, or capacity
+    /// pre-allocation fails.
+    pub fn build(self) -> Result<S3FifoCache<K, V, S>, super::BuildError>
+    where
+        K: Hash + Eq + Clone,
+        S: BuildHasher,
+    {
+        let size = self
+            .size
+            .ok_or(super::BuildError::MissingRequired("max_size"))?;
+        if size == 0 {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "must be greater than zero",
+            });
+        }
+        if !(self.small_ratio > 0.0 && self.small_ratio < 1.0) {
+            return Err(super::BuildError::InvalidValue {
+                field: "small_ratio",
+                reason: "must be strictly between 0 and 1",
+            });
+        }
+        let mut store = HashTable::new();
+        if store.try_reserve(size, |_: &Slot| 0).is_err() {
+            return Err(super::BuildError::InvalidValue {
+                field: "max_size",
+                reason: "allocation failed",
+            });
+        }
+        let (small_cap, ghost_cap) = queue_caps(size, self.small_ratio);
+        Ok(S3FifoCache {
+            store,
+            hash_builder: self.hasher,
+            queues: Queues::with_capacity(size)?,
+            capacity: size,
+            small_ratio: self.small_ratio,
+            small_cap,
+            ghost_cap,
+            hits: StripedCounter::new(),
+            misses: StripedCounter::new(),
+            evictions: AtomicU64::new(0),
+            on_evict: self.on_evict,
+            track_hit_miss: true,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
+        })
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> S3FifoCache<K, V> {
+    /// Construct a ready-to-use [`S3FifoCache`] holding up to `max_size` entries, with the
+    /// default small-queue ratio.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is `0`, or if pre-allocating the backing store fails. Use
+    /// [`builder`](Self::builder) with [`build`](S3FifoCacheBuilder::build) to handle those
+    /// cases without panicking.
+    #[must_use]
+    pub fn new(max_size: usize) -> Self {
+        Self::builder()
+            .max_size(max_size)
+            .build()
+            .expect("S3FifoCache::new requires a non-zero max_size with a valid allocation")
+    }
+
+    /// Return a builder for constructing an [`S3FifoCache`].
+    #[must_use]
+    pub fn builder() -> S3FifoCacheBuilder<K, V> {
+        S3FifoCacheBuilder::default()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> S3FifoCache<K, V, S> {
+    /// Returns the maximum number of entries this cache will hold before evicting.
+    #[doc(alias = "size")]
+    #[doc(alias = "max_size")]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Resident keys in queue order: the small queue, then the main queue, each newest first.
+    /// Meant for debugging and tests.
+    #[must_use]
+    pub fn key_order(&self) -> Vec<K> {
+        let mut out = Vec::with_capacity(self.queues.resident());
+        out.extend(self.queues.iter().map(|e| e.key.clone()));
+        out
+    }
+
+    /// Keys on the small queue, newest first.
+    #[must_use]
+    pub fn small_key_order(&self) -> Vec<K> {
+        self.queues.small.iter().map(|e| e.key.clone()).collect()
+    }
+
+    /// Keys on the main queue, newest first.
+    #[must_use]
+    pub fn main_key_order(&self) -> Vec<K> {
+        self.queues.main.iter().map(|e| e.key.clone()).collect()
+    }
+
+    /// Remembered ghost keys, most recently evicted first.
+    #[must_use]
+    pub fn ghost_key_order(&self) -> Vec<K> {
+        self.queues.ghost.iter().cloned().collect()
+    }
+
+    /// Change the maximum number of entries, returning the previous bound as
+    /// `Some(prev_capacity)`, like [`LruCache::set_max_size`](crate::LruCache::set_max_size).
+    ///
+    /// Shrinking evicts entries as an insert would (firing `on_evict`), re-splits the queue
+    /// bounds with the configured ratio and forgets ghost keys beyond the new bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0. Use [`try_set_max_size`](S3FifoCache::try_set_max_size)
+    /// to validate first and avoid the panic.
+    pub fn set_max_size(&mut self, max_size: usize) -> Option<usize> {
+        assert!(max_size > 0, "max_size must be greater than zero");
+        let prev = self.capacity;
+        self.capacity = max_size;
+        (self.small_cap, self.ghost_cap) = queue_caps(max_size, self.small_ratio);
+        while self.queues.resident() > max_size {
+            self.evict();
+        }
+        while self.queues.len(Queue::Ghost) > self.ghost_cap {
+            self.forget_oldest_ghost();
+        }
+        Some(prev)
+    }
+
+    /// Fallible counterpart of [`set_max_size`](S3FifoCache::set_max_size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SetMaxSizeError::ZeroMaxSize`](super::SetMaxSizeError) if `max_size` is 0.
+    pub fn try_set_max_size(
+        &mut self,
+        max_size: usize,
+    ) -> Result<Option<usize>, super::SetMaxSizeError> {
+        if max_size == 0 {
+            return Err(super::SetMaxSizeError::ZeroMaxSize);
+        }
+        Ok(self.set_max_size(max_size))
+    }
+
+    /// Removes entries for which `keep` returns `false`, firing `on_evict` and counting an
+    /// eviction for each, like [`LruCache::retain`](crate::LruCache::retain). Removed keys
+    /// do not become ghosts. Returns the number of entries removed.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&mut self, mut keep: F) -> usize {
+        let mut doomed = Vec::new();
+        for queue in [Queue::Small, Queue::Main] {
+            let entries = self.queues.resident_queue(queue);
+            doomed.extend(
+                entries
+                    .iter_indices()
+                    .filter(|&index| {
+                        let e = entries.get(index);
+                        !keep(&e.key, &e.value)
+                    })
+                    .map(|index| Slot { queue, index }),
+            );
+        }
+        let removed = doomed.len();
+        for slot in doomed {
+            let (key, value) = self.remove_resident(slot);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(&key, &value);
+            }
+        }
+        removed
+    }
+
+    /// Remove all entries and fire the `on_evict` callback for each one, incrementing the
+    /// evictions counter. Ghost keys are forgotten too.
+    pub fn cache_clear_with_on_evict(&mut self) {
+        let removed = self.drain_all();
+        if !removed.is_empty() {
+            self.evictions
+                .fetch_add(removed.len() as u64, Ordering::Relaxed);
+        }
+        if let Some(on_evict) = &self.on_evict {
+            for (k, v) in &removed {
+                on_evict(k, v);
+            }
+        }
+    }
+
+    /// Remove every entry and ghost key, returning the resident pairs. Fires no callback and
+    /// touches no counters.
+    pub(super) fn drain_all(&mut self) -> Vec<(K, V)> {
+        let mut entries = Vec::with_capacity(self.queues.resident());
+        self.queues.small.drain_into(&mut entries);
+        self.queues.main.drain_into(&mut entries);
+        self.queues.clear();
+        self.store.clear();
+        entries.into_iter().map(Entry::into_pair).collect()
+    }
+
+    /// Remove `k` if it is resident, returning the stored pair. Fires no callback and touches
+    /// no counters; a ghost key is left in place.
+    pub(super) fn pop_raw<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash(k), k).filter(|s| s.is_resident())?;
+        Some(self.remove_resident(slot))
+    }
+
+    /// The resident entries, small queue first. Used by the sharded store's `retain`.
+    pub(super) fn iter_raw(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.queues.iter().map(|e| (&e.key, &e.value))
+    }
+
+    /// Look `k` up and count the access on the entry, without recording a hit or a miss.
+    /// Only needs `&self`, so the sharded store calls it under a shard read lock.
+    pub(super) fn touch<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash(k), k).filter(|s| s.is_resident())?;
+        let entry = self.queues.entry(slot);
+        entry.touch();
+        Some(&entry.value)
+    }
+
+    pub(super) fn add_evictions(&self, n: u64) {
+        self.evictions.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Bytes allocated for the hash table and the three queues, by capacity.
+    pub(super) fn allocated_bytes(&self) -> usize {
+        self.store.allocation_size() + self.queues.allocation_size()
+    }
+
+    /// Sum `heap` over every resident entry.
+    pub(super) fn entries_heap_size(&self, heap: impl Fn(&K, &V) -> usize) -> usize {
+        self.queues.iter().map(|e| heap(&e.key, &e.value)).sum()
+    }
+
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.hash_builder.hash_one(key)
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<Slot>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store
+            .find(hash, |&slot| key == self.queues.key(slot).borrow())
+            .copied()
+    }
+
+    fn insert_slot(&mut self, hash: u64, slot: Slot) {
+        let queues = &self.queues;
+        let hash_builder = &self.hash_builder;
+        self.store
+            .insert_unique(hash, slot, |&s| hash_builder.hash_one(queues.key(s)));
+    }
+
+    /// Point the table entry for `from` at `to`. `hash` is the key's hash.
+    fn relink(&mut self, hash: u64, from: Slot, to: Slot) {
+        match self.store.find_mut(hash, |&s| s == from) {
+            Some(slot) => *slot = to,
+            None => unreachable!(
+                "S3FifoCache internal invariant violated: queues and table out of sync"
+            ),
+        }
+    }
+
+    fn unlink(&mut self, hash: u64, slot: Slot) {
+        match self.store.find_entry(hash, |&s| s == slot) {
+            Ok(entry) => {
+                entry.remove();
+            }
+            Err(_) => unreachable!(
+                "S3FifoCache internal invariant violated: queues and table out of sync"
+            ),
+        }
+    }
+
+    fn remove_resident(&mut self, slot: Slot) -> (K, V) {
+        let hash = self.hash(self.queues.key(slot));
+        self.unlink(hash, slot);
+        self.queues.remove_resident(slot).into_pair()
+    }
+
+    fn forget_oldest_ghost(&mut self) {
+        let slot = self.queues.tail(Queue::Ghost);
+        let hash = self.hash(self.queues.key(slot));
+        self.unlink(hash, slot);
+        self.queues.remove_ghost(slot);
+    }
+
+    /// Evict one entry: from the small queue while it is over its bound (or the main queue is
+    /// empty), otherwise, or if every small entry earned promotion, from the main queue.
+    fn evict(&mut self) {
+        let small_full = self.queues.len(Queue::Small) >= self.small_cap;
+        if (small_full || self.queues.len(Queue::Main) == 0) && self.evict_small() {
+            return;
+        }
+        self.evict_main();
+    }
+
+    /// Walk the small queue from its tail, moving entries read since admission to the main
+    /// queue, until one unread entry is evicted and its key becomes a ghost. Returns `false`
+    /// if the small queue emptied without an eviction.
+    fn evict_small(&mut self) -> bool {
+        while self.queues.len(Queue::Small) > 0 {
+            let slot = self.queues.tail(Queue::Small);
+            let mut entry = self.queues.remove_resident(slot);
+            let hash = self.hash(&entry.key);
+            if *entry.freq.get_mut() > 0 {
+                *entry.freq.get_mut() = 0;
+                let to = self.queues.push_resident(Queue::Main, entry);
+                self.relink(hash, slot, to);
+                continue;
+            }
+            let (key, value) = entry.into_pair();
+            if self.queues.len(Queue::Ghost) >= self.ghost_cap {
+                self.forget_oldest_ghost();
+            }
+            let to = self.queues.push_ghost(key);
+            self.relink(hash, slot, to);
+            // The entry is already off the resident queues, so a panicking callback leaves
+            // the cache within capacity.
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(self.queues.key(to), &value);
+            }
+            return true;
+        }
+        false
+    }
+
+    /// CLOCK over the main queue: give each tail entry with a non-zero counter another pass
+    /// with the counter decremented, and evict the first one whose counter is zero.
+    fn evict_main(&mut self) {
+        loop {
+            let slot = self.queues.tail(Queue::Main);
+            let freq = self.queues.entry(slot).freq.load(Ordering::Relaxed);
+            if freq > 0 {
+                self.queues
+                    .entry(slot)
+                    .freq
+                    .store(freq - 1, Ordering::Relaxed);
+                self.queues.main.move_to_front(slot.index);
+                continue;
+            }
+            let (key, value) = self.remove_resident(slot);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(&key, &value);
+            }
+            return;
+        }
+    }
+
+    /// Insert or overwrite `key`, returning the displaced value and the entry's slot.
+    fn put(&mut self, hash: u64, key: K, val: V) -> (Option<V>, Slot) {
+        match self.find(hash, &key) {
+            Some(slot) if slot.is_resident() => {
+                let entry = self.queues.entry_mut(slot);
+                entry.touch();
+                (Some(std::mem::replace(&mut entry.value, val)), slot)
+            }
+            found => {
+                let queue = match found {
+                    Some(ghost) => {
+                        self.unlink(hash, ghost);
+                        self.queues.remove_ghost(ghost);
+                        Queue::Main
+                    }
+                    None => Queue::Small,
+                };
+                if self.queues.resident() >= self.capacity {
+                    self.evict();
+                }
+                let slot = self.queues.push_resident(queue, Entry::new(key, val));
+                self.insert_slot(hash, slot);
+                (None, slot)
+            }
+        }
+    }
+
+    /// The resident slot for `key`, counting the access and a hit or a miss.
+    fn lookup<Q>(&mut self, key: &Q) -> Option<Slot>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash(key), key).filter(|s| s.is_resident());
+        if let Some(slot) = slot {
+            self.queues.entry(slot).touch();
+        }
+        if self.track_hit_miss {
+            if slot.is_some() {
+                self.hits.increment_mut();
+            } else {
+                self.misses.increment_mut();
+            }
+        }
+        slot
+    }
+
+    fn get_or_set_slot<F: FnOnce() -> V>(&mut self, key: K, f: F) -> Slot {
+        match self.lookup(&key) {
+            Some(slot) => slot,
+            None => {
+                let hash = self.hash(&key);
+                self.put(hash, key, f()).1
+            }
+        }
+    }
+
+    fn try_get_or_set_slot<E, F: FnOnce() -> Result<V, E>>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<Slot, E> {
+        match self.lookup(&key) {
+            Some(slot) => Ok(slot),
+            None => {
+                let value = f()?;
+                let hash = self.hash(&key);
+                Ok(self.put(hash, key, value).1)
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> Cached<K, V> for S3FifoCache<K, V, S> {
+    type Error = std::convert::Infallible;
+
+    fn cache_get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(hot) = &self.hot_keys {
+            let stored = self
+                .find(self.hash(key), key)
+                .filter(|s| s.is_resident())
+                .map(|s| self.queues.key(s));
+            hot.record(hot.hash(key), || stored);
+        }
+        let slot = self.lookup(key)?;
+        Some(&self.queues.entry(slot).value)
+    }
+
+    fn cache_get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.lookup(key)?;
+        Some(&mut self.queues.entry_mut(slot).value)
+    }
+
+    /// Insert or replace a cache entry, returning the previous value.
+    ///
+    /// Overwriting a resident key counts as an access and leaves it on its queue. Writing a
+    /// key that is on the ghost queue admits it straight to the main queue.
+    fn cache_set(&mut self, key: K, val: V) -> Option<V> {
+        let hash = self.hash(&key);
+        self.put(hash, key, val).0
+    }
+
+    fn cache_get_or_set_with_mut<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        let slot = self.get_or_set_slot(key, f);
+        &mut self.queues.entry_mut(slot).value
+    }
+
+    fn cache_try_get_or_set_with_mut<F: FnOnce() -> Result<V, E>, E>(
+        &mut self,
+        key: K,
+        f: F,
+    ) -> Result<&mut V, E> {
+        let slot = self.try_get_or_set_slot(key, f)?;
+        Ok(&mut self.queues.entry_mut(slot).value)
+    }
+
+    fn cache_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        <Self as Cached<K, V>>::cache_remove_entry(self, k).map(|(_, v)| v)
+    }
+
+    fn cache_remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = self.pop_raw(k);
+        if let Some((ref key, ref value)) = removed {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            if let Some(on_evict) = &self.on_evict {
+                on_evict(key, value);
+            }
+        }
+        removed
+    }
+
+    fn cache_clear(&mut self) {
+        self.store.clear();
+        self.queues.clear();
+        self.tags.clear();
+    }
+
+    fn cache_reset(&mut self) {
+        self.cache_clear();
+        self.cache_reset_metrics();
+    }
+
+    fn cache_reset_metrics(&mut self) {
+        self.misses.reset();
+        self.hits.reset();
+        self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
+    }
+
+    fn cache_size(&self) -> usize {
+        self.queues.resident()
+    }
+
+    fn cache_hits(&self) -> Option<u64> {
+        Some(self.hits.load())
+    }
+
+    fn cache_misses(&self) -> Option<u64> {
+        Some(self.misses.load())
+    }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self
+            .deep_size
+            .map_or(0, |heap| self.entries_heap_size(|k, v| heap.entry(k, v)));
+        Some(self.allocated_bytes() + entries)
+    }
+
+    fn cache_evictions(&self) -> Option<u64> {
+        Some(self.evictions.load(Ordering::Relaxed))
+    }
+
+    fn cache_capacity(&self) -> Option<usize> {
+        Some(self.capacity)
+    }
+
+    /// Peek-based: records no hit/miss metrics and leaves the access counter alone.
+    fn cache_contains<Q>(&mut self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        CachedPeek::cache_peek(self, k).is_some()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedIter<K, V> for S3FifoCache<K, V, S> {
+    /// Yields the small queue, then the main queue, each newest first.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)> + 'a
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter_raw()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedPeek<K, V> for S3FifoCache<K, V, S> {
+    fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash(k), k).filter(|s| s.is_resident())?;
+        Some(&self.queues.entry(slot).value)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> CachedRead<K, V> for S3FifoCache<K, V, S> {
+    /// Unlike a peek, the read counts as an access for eviction and records a hit or miss.
+    fn cache_get_read<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self.touch(k);
+        if self.track_hit_miss {
+            if value.is_some() {
+                self.hits.increment();
+            } else {
+                self.misses.increment();
+            }
+        }
+        value
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> super::CachedTags<K, V> for S3FifoCache<K, V, S> {
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.queues.resident()) {
+            let mut index = std::mem::replace(&mut self.tags, super::TagIndex::new());
+            index.retain_keys(|k| CachedPeek::cache_peek(self, k).is_some());
+            self.tags = index;
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, S> CachedGetOrSetAsync<K, V> for S3FifoCache<K, V, S>
+where
+    K: Hash + Eq + Clone + Send,
+    S: BuildHasher + Send,
+{
+    fn async_cache_get_or_set_with_mut<'a, F, Fut>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = &'a mut V> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = V> + Send + 'a,
+    {
+        async move {
+            let slot = match self.lookup(&k) {
+                Some(slot) => slot,
+                None => {
+                    let value = f().await;
+                    let hash = self.hash(&k);
+                    self.put(hash, k, value).1
+                }
+            };
+            &mut self.queues.entry_mut(slot).value
+        }
+    }
+
+    fn async_cache_try_get_or_set_with_mut<'a, F, Fut, E>(
+        &'a mut self,
+        k: K,
+        f: F,
+    ) -> impl Future<Output = Result<&'a mut V, E>> + Send + 'a
+    where
+        K: 'a,
+        V: Send + 'a,
+        E: 'a,
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<V, E>> + Send + 'a,
+    {
+        async move {
+            let slot = match self.lookup(&k) {
+                Some(slot) => slot,
+                None => {
+                    let value = f().await?;
+                    let hash = self.hash(&k);
+                    self.put(hash, k, value).1
+                }
+            };
+            Ok(&mut self.queues.entry_mut(slot).value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resident entries never exceed `capacity`, the ghost queue never exceeds its bound, and
+    /// every queue entry has exactly one table entry.
+    fn assert_invariants<K: Hash + Eq + Clone, V>(c: &S3FifoCache<K, V>) {
+        let [small, main, ghost] = c.queues.lens;
+        assert!(small + main <= c.capacity);
+        assert!(ghost <= c.ghost_cap);
+        assert_eq!(c.store.len(), small + main + ghost);
+        assert_eq!(c.queues.small.iter().count(), small);
+        assert_eq!(c.queues.main.iter().count(), main);
+        assert_eq!(c.queues.ghost.iter().count(), ghost);
+        assert!(
+            c.queues
+                .iter()
+                .all(|e| e.freq.load(Ordering::Relaxed) <= MAX_FREQ)
+        );
+    }
+
+    #[test]
+    fn read_entries_are_promoted_and_unread_ones_become_ghosts() {
+        let mut c = S3FifoCache::builder()
+            .max_size(4)
+            .small_ratio(0.5)
+            .build()
+            .unwrap();
+        c.cache_set(1, 1);
+        c.cache_set(2, 2);
+        c.cache_get(&1);
+        c.cache_set(3, 3);
+        c.cache_set(4, 4);
+        c.cache_set(5, 5); // small is over its bound: 1 moves to main, 2 is evicted
+        assert_eq!(c.main_key_order(), [1]);
+        assert_eq!(c.ghost_key_order(), [2]);
+        assert_eq!(c.small_key_order(), [5, 4, 3]);
+        c.cache_set(2, 2); // ghost hit: straight to main
+        assert_eq!(c.main_key_order(), [2, 1]);
+        assert_eq!(c.ghost_key_order(), [3]);
+        assert_eq!(c.small_key_order(), [5, 4]);
+        assert_invariants(&c);
+    }
+
+    #[test]
+    fn reads_through_a_shared_reference_feed_the_policy() {
+        let mut c = S3FifoCache::builder()
+            .max_size(2)
+            .small_ratio(0.5)
+            .build()
+            .unwrap();
+        c.cache_set(1, 1);
+        assert_eq!(c.cache_get_read(&1), Some(&1));
+        assert_eq!(c.cache_get_read(&9), None);
+        c.cache_set(2, 2);
+        c.cache_set(3, 3);
+        assert_eq!(c.main_key_order(), [1]);
+        assert_eq!((c.cache_hits(), c.cache_misses()), (Some(1), Some(1)));
+        assert_invariants(&c);
+    }
+
+    #[test]
+    fn random_workload_keeps_the_invariants() {
+        let mut c = S3FifoCache::new(8);
+        let mut x: u64 = 7;
+        for _ in 0..5000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            let key = x % 24;
+            match x % 5 {
+                0 => {
+                    let _ = c.cache_remove(&key);
+                }
+                1 | 2 => {
+                    c.cache_get(&key);
+                }
+                _ => {
+                    c.cache_set(key, key);
+                }
+            }
+            assert_invariants(&c);
+        }
+        c.set_max_size(3);
+        assert_invariants(&c);
+    }
+}
@@ -0,0 +1,855 @@
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    CacheMetrics, Cached, CachedPeek, ConcurrentCacheBase, ConcurrentCachePeek, ConcurrentCached,
+};
+#[cfg(feature = "async_core")]
+use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
+#[cfg(feature = "async_core")]
+use core::future::Future;
+
+use super::{
+    CachePadded, DefaultShardHasher, Shard, ShardHasher, ShardRead, ShardSet, ShardStats,
+    ShardWrite, checked_per_shard_cap_from_total, checked_shard_count,
+    default_shard_count_for_capacity, per_shard_cap_from_total,
+};
+use crate::stores::{ArcCache, BuildError, ConcurrentCachedTags, TagIndex};
+
+type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
+
+struct ArcInner<K, V, H> {
+    shards: ShardSet<ArcCache<K, V>>,
+    hasher: H,
+    on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
+    /// Total logical capacity (sum of per-shard caps).
+    total_capacity: AtomicUsize,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+}
+
+/// A fully-concurrent, partitioned [`ArcCache`]: each shard runs its own adaptive replacement
+/// over its share of the capacity.
+///
+/// Wraps an `Arc` — `clone()` is an Arc-share (shared state), not a deep copy.
+///
+/// Capacity, shard count and the shard hasher `H` are configured exactly as on
+/// [`ShardedLruCache`](crate::ShardedLruCache), including the 16-per-shard capacity floor.
+/// Like it, `cache_get` takes the shard's **write** lock, because a hit moves the entry
+/// between lists, and `K` and `V` must be `Clone`. Each shard adapts its target on its own
+/// keys; a workload skewed across shards can leave shards leaning different ways.
+///
+/// **Note**: the inherent `get`, `set`, `remove`, ... return unwrapped values and take
+/// call-site priority over the same-named [`ConcurrentCached`] trait methods, as on
+/// `ShardedLruCache`.
+pub struct ShardedArcCache<K, V, H = DefaultShardHasher> {
+    inner: Arc<ArcInner<K, V, H>>,
+}
+
+impl<K, V, H> Clone for ShardedArcCache<K, V, H> {
+    /// Arc-share clone — both handles point to the same underlying cache.
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<K, V, H> std::fmt::Debug for ShardedArcCache<K, V, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardedArcCache")
+            .field("shards", &self.inner.shards.len())
+            .field(
+                "capacity",
+                &self.inner.total_capacity.load(Ordering::Relaxed),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> ShardedArcCache<K, V, DefaultShardHasher>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Construct a ready-to-use [`ShardedArcCache`] holding up to roughly `max_size` entries
+    /// total, with the default hasher and shard count. See
+    /// [`ShardedLruCache::new`](crate::ShardedLruCache::new) for how the effective capacity
+    /// can exceed `max_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is `0`, or if the effective sharded capacity overflows `usize` /
+    /// a per-shard allocation fails. Use [`builder`](Self::builder) to handle those cases.
+    #[must_use]
+    pub fn new(max_size: usize) -> ShardedArcCache<K, V> {
+        Self::builder()
+            .max_size(max_size)
+            .build()
+            .expect("ShardedArcCache::new requires a non-zero max_size with a valid allocation")
+    }
+
+    /// Return a builder for constructing a [`ShardedArcCache`].
+    #[must_use]
+    pub fn builder() -> ShardedArcCacheBuilder<K, V, DefaultShardHasher> {
+        ShardedArcCacheBuilder::default()
+    }
+}
+
+impl<K, V, H> ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    H: ShardHasher<K>,
+{
+    #[inline]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, ArcCache<K, V>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, ArcCache<K, V>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
+    }
+
+    /// Fire `on_evict` for pairs removed under a shard lock that has since been released.
+    fn notify(&self, removed: &[(K, V)]) {
+        if let Some(on_evict) = &self.inner.on_evict {
+            for (k, v) in removed {
+                on_evict(k, v);
+            }
+        }
+    }
+}
+
+impl<K: Clone + Hash + Eq, V: Clone, H: ShardHasher<K>> ShardedArcCache<K, V, H> {
+    /// Return an independent deep copy of this cache — entries, ghost lists, adaptive targets
+    /// and metrics are duplicated, not shared. In most cases [`Clone::clone`] (Arc-share) is
+    /// what you want.
+    #[must_use]
+    pub fn deep_clone(&self) -> Self {
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let guard = shard.read();
+                let store_copy = guard.clone();
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
+                drop(guard);
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
+                CachePadded(shard)
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            inner: Arc::new(ArcInner {
+                shards: ShardSet::new(shards),
+                hasher: self.inner.hasher.clone(),
+                on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
+                total_capacity: AtomicUsize::new(self.inner.total_capacity.load(Ordering::Relaxed)),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                deep_size: self.inner.deep_size,
+            }),
+        }
+    }
+}
+
+impl<K, V, H: ShardHasher<K>> ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// Retrieve a cached value, returning `None` on a miss.
+    ///
+    /// This is the infallible ergonomic API for the concrete type.
+    #[must_use]
+    pub fn get(&self, k: &K) -> Option<V> {
+        ConcurrentCached::cache_get(self, k).unwrap()
+    }
+
+    /// Insert a key-value pair and return the previous value, if any.
+    ///
+    /// This is the infallible ergonomic API for the concrete type; `.set(k, v).unwrap()`
+    /// panics on a fresh insert.
+    pub fn set(&self, k: K, v: V) -> Option<V> {
+        ConcurrentCached::cache_set(self, k, v).unwrap()
+    }
+
+    /// Return the cached value for `k`, or compute `f()`, store it, and return it.
+    ///
+    /// Non-atomic get-then-set, like
+    /// [`ConcurrentCached::cache_get_or_set_with`](crate::ConcurrentCached::cache_get_or_set_with).
+    pub fn get_or_set_with<F: FnOnce() -> V>(&self, k: K, f: F) -> V {
+        ConcurrentCached::cache_get_or_set_with(self, k, f).unwrap()
+    }
+
+    /// Remove a cached value and return it if the entry was present.
+    pub fn remove(&self, k: &K) -> Option<V> {
+        ConcurrentCached::cache_remove(self, k).unwrap()
+    }
+
+    /// Remove a cached entry and return the stored key and value, if present.
+    pub fn remove_entry(&self, k: &K) -> Option<(K, V)> {
+        ConcurrentCached::cache_remove_entry(self, k).unwrap()
+    }
+
+    /// Delete a cached entry without returning the value. Returns `true` if an entry was removed.
+    pub fn delete(&self, k: &K) -> bool {
+        ConcurrentCached::cache_delete(self, k).unwrap()
+    }
+
+    /// Remove all entries from every shard and reset metrics.
+    pub fn reset(&self) {
+        ConcurrentCached::cache_reset(self).unwrap()
+    }
+
+    /// Return true if a value is stored for `k`. Peek-based: no list movement, no hit/miss
+    /// metrics.
+    #[must_use]
+    pub fn contains(&self, k: &K) -> bool {
+        ConcurrentCached::cache_contains(self, k).unwrap()
+    }
+
+    /// Return a clone of the value stored for `k` under the shard's read lock, without moving
+    /// it between lists or recording a hit or miss.
+    #[must_use]
+    pub fn peek(&self, k: &K) -> Option<V> {
+        self.read_shard(k).1.cache_peek(k).cloned()
+    }
+}
+
+impl<K, V, H: ShardHasher<K>> ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Return aggregate metrics across all shards. Approximate under concurrent mutation.
+    #[must_use]
+    pub fn metrics(&self) -> CacheMetrics {
+        let mut hits = 0u64;
+        let mut misses = 0u64;
+        let mut evictions = 0u64;
+        let mut size = 0usize;
+        for shard in self.inner.shards.table().iter() {
+            hits += shard.hits.load(Ordering::Relaxed);
+            misses += shard.misses.load(Ordering::Relaxed);
+            let guard = shard.read();
+            evictions += guard.cache_evictions().unwrap_or(0);
+            size += guard.cache_size();
+        }
+        CacheMetrics {
+            hits: Some(hits),
+            misses: Some(misses),
+            evictions: Some(evictions),
+            entry_count: Some(size),
+            capacity: Some(self.inner.total_capacity.load(Ordering::Acquire)),
+            mem_bytes: Some(self.shard_memory_usage().into_iter().sum()),
+        }
+    }
+
+    /// Number of shards.
+    #[must_use]
+    pub fn shards(&self) -> usize {
+        self.inner.shards.len()
+    }
+
+    /// Per-shard live entry counts.
+    #[must_use]
+    pub fn shard_sizes(&self) -> Vec<usize> {
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|s| s.read().cache_size())
+            .collect()
+    }
+
+    /// Per-shard adaptive targets (see [`ArcCache::target`]), showing which shards lean
+    /// toward recency and which toward frequency.
+    #[must_use]
+    pub fn shard_targets(&self) -> Vec<usize> {
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|s| s.read().target())
+            .collect()
+    }
+
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage).
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        let deep_size = self.inner.deep_size;
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let store = shard.read();
+                let entries =
+                    deep_size.map_or(0, |heap| store.entries_heap_size(|k, v| heap.entry(k, v)));
+                size_of_val(shard) + store.allocated_bytes() + entries
+            })
+            .collect()
+    }
+
+    /// Per-shard entry counts, hit/miss counters and lock-wait figures; see [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(|store| store.cache_size())
+    }
+
+    /// Total number of entries across all shards. Approximate under concurrent mutation.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shard_sizes().into_iter().sum()
+    }
+
+    /// `true` if no entries are present. Approximate under concurrent mutation.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .all(|s| s.read().cache_size() == 0)
+    }
+
+    /// Remove all entries and ghost keys from every shard. Does **not** fire `on_evict`.
+    pub fn clear(&self) {
+        for shard in self.inner.shards.table().iter() {
+            shard.write().cache_clear();
+        }
+    }
+
+    /// Remove all entries from every shard, counting each as an eviction and firing
+    /// `on_evict` for it after the shard's lock is released.
+    pub fn cache_clear_with_on_evict(&self) {
+        for shard in self.inner.shards.table().iter() {
+            let removed = {
+                let mut guard = shard.write();
+                let removed = guard.drain_all();
+                guard.add_evictions(removed.len() as u64);
+                removed
+            };
+            self.notify(&removed);
+        }
+    }
+
+    /// Remove every entry for which `keep` returns `false`, shard by shard, with the
+    /// semantics of [`ShardedLruCache::retain`](crate::ShardedLruCache::retain): `keep` runs
+    /// under the shard's write lock and `on_evict` fires after it is released. Returns the
+    /// number of entries removed.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
+        let mut total_removed = 0;
+        for shard in self.inner.shards.table().iter() {
+            let removed: Vec<(K, V)> = {
+                let mut guard = shard.write();
+                let doomed: Vec<K> = guard
+                    .iter_raw()
+                    .filter(|(k, v)| !keep(k, v))
+                    .map(|(k, _)| k.clone())
+                    .collect();
+                let removed: Vec<(K, V)> = doomed.iter().filter_map(|k| guard.pop_raw(k)).collect();
+                guard.add_evictions(removed.len() as u64);
+                removed
+            };
+            total_removed += removed.len();
+            self.notify(&removed);
+        }
+        total_removed
+    }
+
+    /// Effective total capacity across all shards.
+    #[doc(alias = "max_size")]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.inner.total_capacity.load(Ordering::Acquire)
+    }
+
+    /// Resize the cache to hold up to `max_size` entries in total, returning the previous
+    /// total capacity as `Some(prev)`. The total is re-split across shards with the
+    /// builder's policy and each shard shrinks as [`ArcCache::set_max_size`] does. Not atomic
+    /// across shards, like [`ShardedLruCache::set_max_size`](crate::ShardedLruCache::set_max_size).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0 or the re-split capacity overflows `usize`. Use
+    /// [`try_set_max_size`](Self::try_set_max_size) to avoid either panic.
+    pub fn set_max_size(&self, max_size: usize) -> Option<usize> {
+        assert!(max_size > 0, "max_size must be greater than zero");
+        let table = self.inner.shards.table();
+        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, table.len());
+        for shard in table.iter() {
+            shard.write().set_max_size(per_shard_cap);
+        }
+        Some(self.inner.total_capacity.swap(total_cap, Ordering::Release))
+    }
+
+    /// Fallible counterpart of [`set_max_size`](Self::set_max_size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SetMaxSizeError::ZeroMaxSize`](crate::SetMaxSizeError) if `max_size` is 0, or
+    /// [`SetMaxSizeError::CapacityOverflow`](crate::SetMaxSizeError) if the re-split capacity
+    /// overflows `usize`.
+    pub fn try_set_max_size(
+        &self,
+        max_size: usize,
+    ) -> Result<Option<usize>, crate::SetMaxSizeError> {
+        if max_size == 0 {
+            return Err(crate::SetMaxSizeError::ZeroMaxSize);
+        }
+        checked_per_shard_cap_from_total(max_size, self.inner.shards.len())?;
+        Ok(self.set_max_size(max_size))
+    }
+}
+
+impl<K, V, H> ConcurrentCacheBase for ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    type Error = std::convert::Infallible;
+
+    fn cache_size(&self) -> Result<Option<usize>, Self::Error> {
+        Ok(Some(self.len()))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum())
+    }
+
+    fn cache_hits(&self) -> Option<u64> {
+        Some(
+            self.inner
+                .shards
+                .table()
+                .iter()
+                .map(|s| s.hits.load(Ordering::Relaxed))
+                .sum(),
+        )
+    }
+
+    fn cache_misses(&self) -> Option<u64> {
+        Some(
+            self.inner
+                .shards
+                .table()
+                .iter()
+                .map(|s| s.misses.load(Ordering::Relaxed))
+                .sum(),
+        )
+    }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
+
+    fn cache_capacity(&self) -> Option<usize> {
+        Some(self.capacity())
+    }
+
+    fn cache_evictions(&self) -> Option<u64> {
+        Some(
+            self.inner
+                .shards
+                .table()
+                .iter()
+                .map(|s| s.read().cache_evictions().unwrap_or(0))
+                .sum(),
+        )
+    }
+}
+
+impl<K, V, H> ConcurrentCached<K, V> for ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.record(self.inner.hasher.shard_hash(k), || Some(k));
+        }
+        let (shard, mut guard) = self.write_shard(k);
+        let value = guard.cache_get(k).cloned();
+        drop(guard);
+        let counter = if value.is_some() {
+            &shard.hits
+        } else {
+            &shard.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        Ok(value)
+    }
+
+    fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
+        Ok(self.write_shard(&k).1.cache_set(k, v))
+    }
+
+    fn cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_remove_entry(self, k).map(|r| r.map(|(_, v)| v))
+    }
+
+    fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
+        let removed = {
+            let (_, mut guard) = self.write_shard(k);
+            let removed = guard.pop_raw(k);
+            if removed.is_some() {
+                guard.add_evictions(1);
+            }
+            removed
+        };
+        if let Some(pair) = &removed {
+            self.notify(std::slice::from_ref(pair));
+        }
+        Ok(removed)
+    }
+
+    fn cache_clear(&self) -> Result<(), Self::Error> {
+        self.clear();
+        Ok(())
+    }
+
+    fn cache_reset(&self) -> Result<(), Self::Error> {
+        self.clear();
+        ConcurrentCached::cache_reset_metrics(self)
+    }
+
+    fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
+            shard.write().cache_reset_metrics();
+        }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
+        }
+        Ok(())
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    /// Peek-based: read lock only, no clone, no list movement, no hit/miss metrics.
+    fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
+        Ok(self.read_shard(k).1.cache_peek(k).is_some())
+    }
+}
+
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| self.contains(k));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+impl<K, V, H> ConcurrentCachePeek<K, V> for ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn cache_peek(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        Ok(self.peek(k))
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, H> ConcurrentCachePeekAsync<K, V> for ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+    H: ShardHasher<K>,
+{
+    async fn async_cache_peek(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCachePeek::cache_peek(self, k)
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, H> ConcurrentCachedAsync<K, V> for ShardedArcCache<K, V, H>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+    H: ShardHasher<K>,
+{
+    async fn async_cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_get(self, k)
+    }
+
+    async fn async_cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    async fn async_cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_remove(self, k)
+    }
+
+    async fn async_cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
+        ConcurrentCached::cache_remove_entry(self, k)
+    }
+
+    async fn async_cache_clear(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_clear(self)
+    }
+
+    async fn async_cache_reset(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_reset(self)
+    }
+
+    async fn async_cache_reset_metrics(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_reset_metrics(self)
+    }
+
+    fn async_cache_contains(&self, k: &K) -> impl Future<Output = Result<bool, Self::Error>> + Send
+    where
+        Self: Sized + Sync,
+        K: Sync,
+    {
+        let result = ConcurrentCached::cache_contains(self, k);
+        async move { result }
+    }
+}
+
+/// Builder for [`ShardedArcCache`].
+pub struct ShardedArcCacheBuilder<K, V, H = DefaultShardHasher> {
+    shards: Option<usize>,
+    max_size: Option<usize>,
+    per_shard_max_size: Option<usize>,
+    hasher: H,
+    on_evict: Option<OnEvict<K, V>>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+}
+
+impl<K, V> Default for ShardedArcCacheBuilder<K, V, DefaultShardHasher> {
+    fn default() -> Self {
+        Self {
+            shards: None,
+            max_size: None,
+            per_shard_max_size: None,
+            hasher: DefaultShardHasher::default(),
+            on_evict: None,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+        }
+    }
+}
+
+impl<K, V> ShardedArcCacheBuilder<K, V> {
+    /// Create a builder with default settings. Equivalent to [`ShardedArcCache::builder`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, H> ShardedArcCacheBuilder<K, V, H> {
+    /// Set the requested total capacity, divided across shards as
+    /// [`ShardedLruCacheBuilder::max_size`](crate::ShardedLruCacheBuilder::max_size) does.
+    /// Mutually exclusive with [`per_shard_max_size`](Self::per_shard_max_size).
+    #[doc(alias = "size")]
+    #[doc(alias = "capacity")]
+    #[must_use]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Set per-shard capacity directly. Mutually exclusive with [`max_size`](Self::max_size).
+    #[must_use]
+    pub fn per_shard_max_size(mut self, per_shard_max_size: usize) -> Self {
+        self.per_shard_max_size = Some(per_shard_max_size);
+        self
+    }
+
+    /// Set the number of shards (rounded up to the next power of two).
+    #[must_use]
+    pub fn shards(mut self, shards: usize) -> Self {
+        self.shards = Some(shards);
+        self
+    }
+
+    /// Set a custom shard-selection hasher, changing the type parameter. See
+    /// [`ShardedLruCacheBuilder::hasher`](crate::ShardedLruCacheBuilder::hasher).
+    #[doc(alias = "with_hasher")]
+    #[must_use]
+    pub fn hasher<H2: ShardHasher<K>>(self, hasher: H2) -> ShardedArcCacheBuilder<K, V, H2> {
+        ShardedArcCacheBuilder {
+            shards: self.shards,
+            max_size: self.max_size,
+            per_shard_max_size: self.per_shard_max_size,
+            hasher,
+            on_evict: self.on_evict,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
+        }
+    }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to the memory estimates, measured through
+    /// their [`DeepSize`](crate::DeepSize) impls.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
+    /// Set a callback invoked when an entry is evicted by capacity pressure, removed, or
+    /// cleared through [`cache_clear_with_on_evict`](ShardedArcCache::cache_clear_with_on_evict).
+    ///
+    /// Capacity-eviction callbacks run while the affected shard's write lock is held; do not
+    /// call back into the same cache from the callback.
+    #[must_use]
+    pub fn on_evict(mut self, on_evict: impl Fn(&K, &V) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(on_evict));
+        self
+    }
+
+    fn resolve_per_shard_cap(&self, n_shards: usize) -> Result<usize, BuildError> {
+        match (self.max_size, self.per_shard_max_size) {
+            (Some(_), Some(_)) => Err(BuildError::InvalidValue {
+                field: "max_size / per_shard_max_size",
+                reason: "`max_size` and `per_shard_max_size` are mutually exclusive",
+            }),
+            (None, None) => Err(BuildError::MissingRequired("max_size")),
+            (Some(0), None) => Err(BuildError::InvalidValue {
+                field: "max_size",
+                reason: "must be greater than zero",
+            }),
+            (None, Some(0)) => Err(BuildError::InvalidValue {
+                field: "per_shard_max_size",
+                reason: "must be greater than zero",
+            }),
+            (Some(total), None) => checked_per_shard_cap_from_total(total, n_shards)
+                .map(|(per_shard, _)| per_shard)
+                .map_err(|_| BuildError::InvalidValue {
+                    field: "max_size",
+                    reason: "effective sharded capacity overflows usize",
+                }),
+            (None, Some(per_shard)) => Ok(per_shard),
+        }
+    }
+
+    /// Build the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError`] if neither or both of `max_size` and `per_shard_max_size` are
+    /// set, either is `0`, `shards` is `0`, or the effective capacity overflows `usize`.
+    #[must_use = "the Result from build() must be used"]
+    pub fn build(self) -> Result<ShardedArcCache<K, V, H>, BuildError>
+    where
+        K: Hash + Eq + Clone,
+        H: ShardHasher<K>,
+    {
+        let n = match self.shards {
+            Some(_) => checked_shard_count(self.shards)?,
+            None => default_shard_count_for_capacity(self.max_size),
+        };
+        let per_shard_cap = self.resolve_per_shard_cap(n)?;
+        let total_cap = n
+            .checked_mul(per_shard_cap)
+            .ok_or(BuildError::InvalidValue {
+                field: "per_shard_max_size",
+                reason: "effective sharded capacity overflows usize",
+            })?;
+        let shards = (0..n)
+            .map(|_| {
+                let mut arc = ArcCache::builder().max_size(per_shard_cap).build()?;
+                arc.on_evict = self.on_evict.clone();
+                arc.track_hit_miss = false;
+                Ok(CachePadded(Shard::new(arc)))
+            })
+            .collect::<Result<Vec<_>, BuildError>>()?
+            .into_boxed_slice();
+        Ok(ShardedArcCache {
+            inner: Arc::new(ArcInner {
+                shards: ShardSet::new(shards),
+                hasher: self.hasher,
+                on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
+                total_capacity: AtomicUsize::new(total_cap),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
+            }),
+        })
+    }
+}
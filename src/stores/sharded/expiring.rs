@@ -1,13 +1,13 @@
 use std::hash::Hash;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::Ordering;
 
 #[cfg(feature = "ahash")]
 use ahash::RandomState;
 #[cfg(not(feature = "ahash"))]
 use std::collections::hash_map::RandomState;
 
-use std::collections::HashMap;
+use crate::stores::bucket_map::BucketMap;
 
 use crate::{
     CacheMetrics, ConcurrentCacheBase, ConcurrentCachePeek, ConcurrentCached,
@@ -19,19 +19,39 @@ use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
 use core::future::Future;
 
 use super::{
-    CachePadded, DefaultShardHasher, Shard, ShardHasher, checked_shard_count, shard_index,
+    CachePadded, DefaultShardHasher, EvictCursor, Shard, ShardHasher, ShardRead, ShardSet,
+    ShardStats, ShardWrite, checked_reshard_count, checked_shard_count,
 };
 use crate::ConcurrentCacheEvict;
-use crate::stores::BuildError;
+use crate::stores::{
+    BuildError, ConcurrentCachedTags, EvictProgress, ReshardError, TagIndex, TimerWheel,
+};
+#[cfg(feature = "persist")]
+use crate::stores::{CacheSnapshot, ConcurrentCachedSnapshot, SnapshotClock};
+use crate::time::{Duration, Instant};
 
 type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
 
 #[allow(clippy::type_complexity)]
 struct ExpiringInner<K, V, H> {
-    shards: Box<[CachePadded<Shard<HashMap<K, V, RandomState>>>]>,
-    shard_mask: usize,
+    shards: ShardSet<BucketMap<K, V, RandomState>>,
     hasher: H,
     on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+    /// One timing wheel per shard the cache was built with, picked by shard hash as the hot-key
+    /// summaries are. Locked after the key's shard, never before.
+    wheels: Option<Box<[parking_lot::Mutex<TimerWheel<K>>]>>,
+    /// Where the next [`evict_budgeted`](ShardedExpiringCache::evict_budgeted) step starts
+    /// when there are no wheels, or the next scan step when there are; the position is in the
+    /// shard map's iteration order.
+    evict_cursor: parking_lot::Mutex<EvictCursor>,
+    /// With wheels, whether a scan for values without a deadline is under way, and if so
+    /// whether it has met a live one yet. Locked before `evict_cursor` and any wheel.
+    scan: parking_lot::Mutex<Option<bool>>,
 }
 
 /// A fully-concurrent, partitioned, unbounded in-memory cache with per-value expiry.
@@ -88,12 +108,19 @@ impl<K, V, H> std::fmt::Debug for ShardedExpiringCache<K, V, H> {
         let evictions: u64 = self
             .inner
             .shards
+            .table()
             .iter()
             .map(|s| s.evictions.load(Ordering::Relaxed))
             .sum();
+        let timer_wheel = self
+            .inner
+            .wheels
+            .as_ref()
+            .map(|wheels| wheels.iter().map(|w| w.lock().len()).sum::<usize>());
         f.debug_struct("ShardedExpiringCache")
             .field("shards", &self.inner.shards.len())
             .field("evictions", &evictions)
+            .field("timer_wheel", &timer_wheel)
             .finish_non_exhaustive()
     }
 }
@@ -136,9 +163,19 @@ where
     H: ShardHasher<K>,
 {
     #[inline]
-    fn shard_of(&self, k: &K) -> &CachePadded<Shard<HashMap<K, V, RandomState>>> {
-        let h = self.inner.hasher.shard_hash(k);
-        &self.inner.shards[shard_index(h, self.inner.shard_mask)]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, BucketMap<K, V, RandomState>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, BucketMap<K, V, RandomState>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
+    }
+
+    /// The timing wheel for keys with shard hash `hash`, locked; `None` without `timer_wheel`.
+    fn wheel(&self, hash: u64) -> Option<parking_lot::MutexGuard<'_, TimerWheel<K>>> {
+        let wheels = self.inner.wheels.as_ref()?;
+        Some(wheels[super::shard_index(hash, wheels.len() - 1)].lock())
     }
 }
 
@@ -160,34 +197,48 @@ impl<K: Clone + Hash + Eq, V: Clone + Expires, H: ShardHasher<K>> ShardedExpirin
     /// what you want.
     #[must_use]
     pub fn deep_clone(&self) -> Self {
-        let n = self.inner.shards.len();
-        let shards = (0..n)
-            .map(|i| {
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
                 // Load the hit/miss/eviction counters under the read lock so the metrics
                 // snapshot is consistent with the entry snapshot (B4: loading after
                 // drop(guard) could yield counters newer than the cloned entries).
-                let guard = self.inner.shards[i].lock.read();
+                let guard = shard.read();
                 let store_copy = guard.clone();
-                let hits = self.inner.shards[i].hits.load(Ordering::Relaxed);
-                let misses = self.inner.shards[i].misses.load(Ordering::Relaxed);
-                let evictions = self.inner.shards[i].evictions.load(Ordering::Relaxed);
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
+                let evictions = shard.evictions.load(Ordering::Relaxed);
                 drop(guard);
-                let shard = Shard {
-                    lock: parking_lot::RwLock::new(store_copy),
-                    hits: AtomicU64::new(hits),
-                    misses: AtomicU64::new(misses),
-                    evictions: AtomicU64::new(evictions),
-                };
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
+                shard.evictions.store(evictions, Ordering::Relaxed);
                 CachePadded(shard)
             })
             .collect::<Vec<_>>()
             .into_boxed_slice();
         Self {
             inner: Arc::new(ExpiringInner {
-                shards,
-                shard_mask: self.inner.shard_mask,
+                shards: ShardSet::new(shards),
                 hasher: self.inner.hasher.clone(),
                 on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                deep_size: self.inner.deep_size,
+                wheels: self.inner.wheels.as_ref().map(|wheels| {
+                    wheels
+                        .iter()
+                        .map(|wheel| parking_lot::Mutex::new(wheel.lock().clone()))
+                        .collect()
+                }),
+                evict_cursor: parking_lot::Mutex::default(),
+                scan: parking_lot::Mutex::default(),
             }),
         }
     }
@@ -275,8 +326,7 @@ where
     /// per-shard lock.
     #[must_use]
     pub fn peek(&self, k: &K) -> Option<V> {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         guard.get(k).filter(|v| !v.is_expired()).cloned()
     }
 }
@@ -296,11 +346,11 @@ where
         let mut misses = 0u64;
         let mut evictions = 0u64;
         let mut size = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             hits += shard.hits.load(Ordering::Relaxed);
             misses += shard.misses.load(Ordering::Relaxed);
             evictions += shard.evictions.load(Ordering::Relaxed);
-            size += shard.lock.read().len();
+            size += shard.read().len();
         }
         CacheMetrics {
             hits: Some(hits),
@@ -308,6 +358,9 @@ where
             evictions: Some(evictions),
             entry_count: Some(size),
             capacity: None,
+            mem_bytes: Some(
+                self.shard_memory_usage().into_iter().sum::<usize>() + self.wheel_bytes(),
+            ),
         }
     }
 
@@ -322,28 +375,103 @@ where
     pub fn shard_sizes(&self) -> Vec<usize> {
         self.inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().len())
+            .map(|s| s.read().len())
             .collect()
     }
 
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage). A shard whose share
+    /// of the bytes runs well ahead of its share of the entries holds unusually large values.
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        let deep_size = self.inner.deep_size;
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let store = shard.read();
+                let entries =
+                    deep_size.map_or(0, |heap| store.iter().map(|(k, v)| heap.entry(k, v)).sum());
+                size_of_val(shard) + store.allocation_bytes() + entries
+            })
+            .collect()
+    }
+
+    /// Per-shard entry counts (expired-but-unswept entries included, as in
+    /// [`shard_sizes`](Self::shard_sizes)), hit/miss counters and lock-wait figures; see
+    /// [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(BucketMap::len)
+    }
+
+    /// Migrate every entry into a new array of `shards` shards (rounded up to a power of two)
+    /// while the cache keeps serving requests.
+    ///
+    /// Entries move as they are, expired ones included: expiry is the value's own
+    /// [`Expires`] state, so it is unaffected by the move, and sweeping stays with
+    /// [`evict`](Self::evict). Only the shard being drained blocks its keys, and only while
+    /// its entries move; whole-cache calls wait for the full migration. Hit, miss and
+    /// eviction counts carry over. A no-op when the cache already has that many shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReshardError::ZeroShards`] or [`ReshardError::ShardCountOverflow`] if
+    /// `shards` is zero or overflows when rounded up. Returns [`ReshardError::Reentrant`],
+    /// rather than deadlocking, when called from a callback (such as `on_evict`) that a
+    /// whole-cache call or reshard on this same cache is running.
+    pub fn reshard(&self, shards: usize) -> Result<(), ReshardError>
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+    {
+        let n = checked_reshard_count(shards)?;
+        let hasher = &self.inner.hasher;
+        self.inner.shards.reshard(
+            n,
+            || BucketMap::with_hasher(RandomState::new()),
+            |map| {
+                std::mem::take(map)
+                    .into_iter()
+                    .map(|(k, v)| (hasher.shard_hash(&k), (k, v)))
+                    .collect()
+            },
+            |map, (k, v)| {
+                map.insert(k, v);
+            },
+        )
+    }
+
     /// Total number of entries across all shards (including not-yet-swept expired entries).
     #[must_use]
     pub fn len(&self) -> usize {
-        self.inner.shards.iter().map(|s| s.lock.read().len()).sum()
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|s| s.read().len())
+            .sum()
     }
 
     /// `true` if no entries are present.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.inner.shards.iter().all(|s| s.lock.read().is_empty())
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .all(|s| s.read().is_empty())
     }
 
     /// Remove all entries from every shard. Does **not** fire `on_evict`.
     /// Use [`cache_clear_with_on_evict`](Self::cache_clear_with_on_evict) to opt into callback firing.
     pub fn clear(&self) {
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().clear();
+        self.clear_wheels();
+        for shard in self.inner.shards.table().iter() {
+            shard.write().clear();
         }
     }
 
@@ -354,9 +482,10 @@ where
     /// (`metrics().evictions`) whether or not an `on_evict` callback is configured; the callback
     /// fires only when one is set.
     pub fn cache_clear_with_on_evict(&self) {
+        self.clear_wheels();
         if self.inner.on_evict.is_none() {
-            for shard in self.inner.shards.iter() {
-                let mut guard = shard.lock.write();
+            for shard in self.inner.shards.table().iter() {
+                let mut guard = shard.write();
                 let n = guard.len();
                 guard.clear();
                 drop(guard);
@@ -366,8 +495,8 @@ where
             }
             return;
         }
-        for shard in self.inner.shards.iter() {
-            let removed: Vec<(K, V)> = shard.lock.write().drain().collect();
+        for shard in self.inner.shards.table().iter() {
+            let removed: Vec<(K, V)> = shard.write().drain().collect();
             if !removed.is_empty() {
                 shard
                     .evictions
@@ -393,6 +522,10 @@ where
     /// counted and notified before the panic. This holds whether or not an `on_evict` callback
     /// is configured.
     ///
+    /// With a [`timer_wheel`](ShardedExpiringCacheBuilder::timer_wheel) this visits the entries
+    /// whose deadline has passed, and scans the rest only while the cache may hold values
+    /// without a deadline, as [`evict_some`](Self::evict_some) with no budget does.
+    ///
     /// The `K: Clone` bound is not needed by this implementation; it predates the two-phase
     /// sweep and is kept because removing it from the inherent method alone would leave it
     /// stricter on the [`ConcurrentCacheEvict`](crate::ConcurrentCacheEvict) impl.
@@ -401,8 +534,11 @@ where
     where
         K: Clone,
     {
+        if self.inner.wheels.is_some() {
+            return self.evict_some(usize::MAX);
+        }
         let mut total = 0;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             // Collect under the write lock, fire callbacks after releasing it. Two phases: the
             // first calls `is_expired` (user code) and only selects, the second removes and
             // runs nothing that can panic. See `stores::take_doomed`. The no-callback path used
@@ -410,8 +546,8 @@ where
             // a panicking `is_expired` skipped entirely; both paths now remove and count
             // exactly the same entries.
             let removed: Vec<(K, V)> = {
-                let mut guard = shard.lock.write();
-                crate::stores::take_doomed(&mut guard, |_, v| v.is_expired())
+                let mut guard = shard.write();
+                guard.take_doomed(|_, v| v.is_expired())
             };
 
             total += removed.len();
@@ -429,6 +565,187 @@ where
         total
     }
 
+    /// Evict expired entries, checking at most `budget` of them, and return how many were
+    /// removed.
+    ///
+    /// This is an [`evict_budgeted`](Self::evict_budgeted) step without the progress report,
+    /// so the budget counts entries examined, whether or not they turn out to be expired. With
+    /// a [`timer_wheel`](ShardedExpiringCacheBuilder::timer_wheel) it goes first to the keys
+    /// the wheels have due, each checked under its shard's write lock, so the cost follows the
+    /// number of due entries rather than the size of the cache; keys past the budget stay due
+    /// for the next call. A key whose value is still live is filed again at its current
+    /// deadline, and a key no longer stored is dropped; both count. Without a wheel the step
+    /// walks the shards from where the last one stopped.
+    ///
+    /// `on_evict` fires after the shard lock is released, once per removed entry. A panicking
+    /// [`Expires::is_expired`] leaves the entry it panicked on in place; entries already
+    /// removed by this call were counted and notified first.
+    pub fn evict_some(&self, budget: usize) -> usize {
+        self.evict_budgeted(budget).reclaimed
+    }
+
+    /// Take one step of an incremental [`evict`](Self::evict): check at most `max_entries`
+    /// entries, remove the expired ones, and report how many were removed and whether the
+    /// sweep got past the last shard.
+    ///
+    /// With a [`timer_wheel`](ShardedExpiringCacheBuilder::timer_wheel) a step checks the keys
+    /// the wheels have due, then spends what is left of the budget scanning the shards for
+    /// values without a deadline, while the cache may hold any. The sweep is finished once a
+    /// step runs out of due keys and, if it scanned, gets past the last shard. Without one,
+    /// steps walk the shards from where the last one stopped, holding one shard's write lock
+    /// at a time; a walk spends the whole budget once it has probed eight buckets of a
+    /// shard's map per entry of it. See [`ConcurrentCacheEvict::evict_budgeted`] for how steps add up to a
+    /// sweep.
+    #[must_use]
+    pub fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        if let Some(wheels) = &self.inner.wheels {
+            return self.evict_due(wheels, max_entries);
+        }
+        self.evict_window(max_entries, |_, v| v.is_expired())
+    }
+
+    /// Remove what `doomed` selects from the next `budget` entries, walking the shards from
+    /// where the last window stopped.
+    fn evict_window(&self, budget: usize, mut doomed: impl FnMut(&K, &V) -> bool) -> EvictProgress {
+        super::evict_budgeted(
+            &self.inner.shards,
+            &self.inner.evict_cursor,
+            budget,
+            |store, position, budget| store.take_window(position, budget, &mut doomed),
+            |shard, removed| {
+                if removed.is_empty() {
+                    return;
+                }
+                shard
+                    .evictions
+                    .fetch_add(removed.len() as u64, Ordering::Relaxed);
+                if let Some(on_evict) = &self.inner.on_evict {
+                    for (k, v) in &removed {
+                        on_evict(k, v);
+                    }
+                }
+            },
+        )
+    }
+
+    /// Check up to `budget` entries for expiry, for the values without a deadline that the
+    /// wheels cannot file. As on [`ExpiringCache`](crate::ExpiringCache), a scan starts only
+    /// once a wheel has noted such a value, resumes where the last step's stopped, and notes
+    /// again at its end if it met a live one. `finished` reports that no scan is left under
+    /// way.
+    fn scan_unscheduled(
+        &self,
+        wheels: &[parking_lot::Mutex<TimerWheel<K>>],
+        budget: usize,
+    ) -> EvictProgress {
+        let mut scan = self.inner.scan.lock();
+        let met = match *scan {
+            Some(met) => met,
+            None => {
+                // Every wheel's note is taken, not just the first one set.
+                let noted = wheels.iter().fold(false, |noted, wheel| {
+                    wheel.lock().take_unscheduled() | noted
+                });
+                if !noted {
+                    return EvictProgress {
+                        reclaimed: 0,
+                        finished: true,
+                    };
+                }
+                false
+            }
+        };
+        let mut met_now = false;
+        let progress = self.evict_window(budget, |_, v| {
+            v.is_expired() || {
+                met_now |= v.expires_at().is_none();
+                false
+            }
+        });
+        let met = met || met_now;
+        if progress.finished {
+            *scan = None;
+            if met {
+                wheels[0].lock().mark_unscheduled();
+            }
+        } else {
+            *scan = Some(met);
+        }
+        progress
+    }
+
+    /// One sweep step with wheels: check up to `budget` keys the wheels have due, then hand
+    /// the rest of the budget to [`scan_unscheduled`](Self::scan_unscheduled).
+    fn evict_due(
+        &self,
+        wheels: &[parking_lot::Mutex<TimerWheel<K>>],
+        budget: usize,
+    ) -> EvictProgress {
+        let now = Instant::now();
+        let mut left = budget;
+        let mut total = 0;
+        for wheel in wheels.iter() {
+            if left == 0 {
+                break;
+            }
+            // The wheel is unlocked before any shard is taken, then relocked under a shard
+            // lock to refile a live key, which keeps the shard-then-wheel order of `cache_set`.
+            let keys = wheel.lock().poll(now, left);
+            left -= keys.len();
+            for key in keys {
+                let (shard, mut guard) = self.write_shard(&key);
+                let removed = match guard.get(&key) {
+                    Some(v) if v.is_expired() => guard.remove_entry(&key),
+                    Some(v) => {
+                        match v.expires_at() {
+                            Some(deadline) => wheel.lock().reschedule(key, deadline),
+                            // Its deadline was taken away in place: only a scan will see it now.
+                            None => wheel.lock().mark_unscheduled(),
+                        }
+                        None
+                    }
+                    None => None,
+                };
+                drop(guard);
+                if let Some((k, v)) = removed {
+                    // Count BEFORE notifying: a panicking callback must never leave an
+                    // entry removed-but-uncounted.
+                    shard.evictions.fetch_add(1, Ordering::Relaxed);
+                    total += 1;
+                    if let Some(on_evict) = &self.inner.on_evict {
+                        on_evict(&k, &v);
+                    }
+                }
+            }
+        }
+        if left == 0 {
+            return EvictProgress {
+                reclaimed: total,
+                finished: false,
+            };
+        }
+        let scanned = self.scan_unscheduled(wheels, left);
+        EvictProgress {
+            reclaimed: total + scanned.reclaimed,
+            finished: scanned.finished,
+        }
+    }
+
+    fn clear_wheels(&self) {
+        for wheel in self.inner.wheels.iter().flat_map(|wheels| wheels.iter()) {
+            wheel.lock().clear();
+        }
+    }
+
+    fn wheel_bytes(&self) -> usize {
+        self.inner
+            .wheels
+            .iter()
+            .flat_map(|wheels| wheels.iter())
+            .map(|wheel| wheel.lock().heap_bytes())
+            .sum()
+    }
+
     /// Retain only entries that are unexpired and satisfy `keep`.
     ///
     /// Removes every entry whose value reports [`is_expired`](Expires::is_expired) **or** for
@@ -466,14 +783,14 @@ where
     /// not an `on_evict` callback is configured.
     pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
         let mut total_removed = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             // Collect under the write lock, fire callbacks after releasing it. Two phases for
             // the same reason as `evict`; see `stores::take_doomed`. The no-callback path used
             // to take a `before - guard.len()` delta that a panicking predicate skipped
             // entirely; it now shares this structure.
             let removed: Vec<(K, V)> = {
-                let mut guard = shard.lock.write();
-                crate::stores::take_doomed(&mut guard, |k, v| v.is_expired() || !keep(k, v))
+                let mut guard = shard.write();
+                guard.take_doomed(|k, v| v.is_expired() || !keep(k, v))
             };
             total_removed += removed.len();
             if !removed.is_empty() {
@@ -503,10 +820,15 @@ where
         Ok(Some(self.len()))
     }
 
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum::<usize>() + self.wheel_bytes())
+    }
+
     fn cache_hits(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.hits.load(Ordering::Relaxed))
                 .sum(),
@@ -517,16 +839,23 @@ where
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.misses.load(Ordering::Relaxed))
                 .sum(),
         )
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
+
     fn cache_evictions(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.evictions.load(Ordering::Relaxed))
                 .sum(),
@@ -541,27 +870,30 @@ where
     H: ShardHasher<K>,
 {
     fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
+        if let Some(hot) = &self.inner.hot_keys {
+            let hash = self.inner.hasher.shard_hash(k);
+            hot.record(hash, || Some(k));
+        }
         // Expiry check — try with a read lock first to allow read concurrency on hits.
-        let (expired, value) = {
-            let guard = shard.lock.read();
-            match guard.get(k) {
-                Some(v) => {
-                    let expired = v.is_expired();
-                    let val = if !expired { Some(v.clone()) } else { None };
-                    (expired, val)
-                }
-                None => {
-                    drop(guard);
-                    shard.misses.fetch_add(1, Ordering::Relaxed);
-                    return Ok(None);
-                }
+        let (shard, guard) = self.read_shard(k);
+        let (expired, value) = match guard.get(k) {
+            Some(v) => {
+                let expired = v.is_expired();
+                let val = if !expired { Some(v.clone()) } else { None };
+                (expired, val)
+            }
+            None => {
+                drop(guard);
+                shard.misses.fetch_add(1, Ordering::Relaxed);
+                return Ok(None);
             }
         };
+        drop(guard);
 
         if expired {
-            // Upgrade to write lock to remove the expired entry.
-            let mut guard = shard.lock.write();
+            // Upgrade to write lock to remove the expired entry. Re-resolved rather than
+            // re-locking `shard`: a reshard may have moved the key in between.
+            let (shard, mut guard) = self.write_shard(k);
             // Re-check under write lock — another thread may have replaced the entry
             // with a fresh value in the meantime; clone it out in the same lookup.
             let fresh_val = match guard.get(k) {
@@ -591,7 +923,6 @@ where
     }
 
     fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(&k);
         // Capture the displaced value and evaluate is_expired() while the write lock is still
         // held (B2: avoids a TOCTOU where an entry crosses the expiry threshold between unlock
         // and the check). An owned key is kept in hand so `on_evict` can fire after the lock is
@@ -606,20 +937,26 @@ where
         // the caller's key `k` owned here; `on_evict` therefore receives the caller's key -- the
         // same key the LRU-backed sharded stores hand it when the stored key is kept. The two
         // compare `Eq`.
-        let old: Option<(K, V, bool)> = {
-            let mut guard = shard.lock.write();
-            match guard.get_mut(&k) {
-                Some(slot) => {
-                    let old_v = std::mem::replace(slot, v);
-                    let expired = old_v.is_expired();
-                    Some((k, old_v, expired))
+        let hash = self.inner.hasher.shard_hash(&k);
+        let (shard, mut guard) = self.inner.shards.write(hash);
+        let old: Option<(K, V, bool)> = match guard.get_mut(&k) {
+            Some(slot) => {
+                if let Some(mut wheel) = self.wheel(hash) {
+                    wheel.schedule_replacing(&k, slot.expires_at(), v.expires_at());
                 }
-                None => {
-                    guard.insert(k, v);
-                    None
+                let old_v = std::mem::replace(slot, v);
+                let expired = old_v.is_expired();
+                Some((k, old_v, expired))
+            }
+            None => {
+                if let Some(mut wheel) = self.wheel(hash) {
+                    wheel.schedule(&k, v.expires_at());
                 }
+                guard.insert(k, v);
+                None
             }
         };
+        drop(guard);
         match old {
             // A displaced expired value is filtered from the return (matching cache_remove and
             // the single-owner expiring stores); fire on_evict and count an eviction for it.
@@ -642,8 +979,9 @@ where
     /// [`cache_remove_entry`](ConcurrentCached::cache_remove_entry) to
     /// receive the value regardless of expiry.
     fn cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
-        let removed = shard.lock.write().remove_entry(k);
+        let (shard, mut guard) = self.write_shard(k);
+        let removed = guard.remove_entry(k);
+        drop(guard);
         if let Some((stored_k, v)) = removed {
             shard.evictions.fetch_add(1, Ordering::Relaxed);
             if let Some(on_evict) = &self.inner.on_evict {
@@ -663,8 +1001,9 @@ where
     /// [`cache_remove`](ConcurrentCached::cache_remove), which filters
     /// expired values).
     fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
-        let shard = self.shard_of(k);
-        let removed = shard.lock.write().remove_entry(k);
+        let (shard, mut guard) = self.write_shard(k);
+        let removed = guard.remove_entry(k);
+        drop(guard);
         if let Some((ref stored_k, ref v)) = removed {
             shard.evictions.fetch_add(1, Ordering::Relaxed);
             if let Some(on_evict) = &self.inner.on_evict {
@@ -685,19 +1024,56 @@ where
     }
 
     fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
-        for shard in self.inner.shards.iter() {
-            shard.hits.store(0, Ordering::Relaxed);
-            shard.misses.store(0, Ordering::Relaxed);
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
             shard.evictions.store(0, Ordering::Relaxed);
         }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
+        }
         Ok(())
     }
 
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
     /// Efficient peek-based contains: acquires a read lock, does not clone the value,
     /// and does not record hit/miss metrics. Returns `true` only for live (not expired) entries.
     fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
-        let shard = self.shard_of(k);
-        Ok(shard.lock.read().get(k).is_some_and(|v| !v.is_expired()))
+        Ok(self.read_shard(k).1.get(k).is_some_and(|v| !v.is_expired()))
+    }
+}
+
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedExpiringCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone + Expires,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| matches!(ConcurrentCached::cache_contains(self, k), Ok(true)));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
     }
 }
 
@@ -784,6 +1160,10 @@ where
     fn evict(&self) -> usize {
         ShardedExpiringCache::evict(self)
     }
+
+    fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        ShardedExpiringCache::evict_budgeted(self, max_entries)
+    }
 }
 
 /// Builder for [`ShardedExpiringCache`].
@@ -801,6 +1181,10 @@ pub struct ShardedExpiringCacheBuilder<K, V, H = DefaultShardHasher> {
     on_evict: Option<OnEvict<K, V>>,
     _k: std::marker::PhantomData<K>,
     _v: std::marker::PhantomData<V>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+    timer_wheel: Option<crate::stores::TimerWheelConfig<K>>,
 }
 
 impl<K, V> Default for ShardedExpiringCacheBuilder<K, V, DefaultShardHasher> {
@@ -812,6 +1196,10 @@ impl<K, V> Default for ShardedExpiringCacheBuilder<K, V, DefaultShardHasher> {
             on_evict: None,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+            timer_wheel: None,
         }
     }
 }
@@ -862,9 +1250,84 @@ impl<K, V, H> ShardedExpiringCacheBuilder<K, V, H> {
             on_evict: self.on_evict,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
+            timer_wheel: self.timer_wheel,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key. Read them with
+    /// [`top_keys`](crate::ConcurrentCachedExt::top_keys), which merges the shards' summaries.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](crate::stores::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::ConcurrentCachedExt::memory_usage) and `shard_memory_usage`,
+    /// measured through their [`DeepSize`](crate::DeepSize) impls. Each estimate then visits
+    /// every entry under its shard's read lock.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
+    /// Keep a hierarchical timing wheel of value deadlines, so
+    /// [`evict`](ShardedExpiringCache::evict) and
+    /// [`evict_some`](ShardedExpiringCache::evict_some) visit only entries whose deadline has
+    /// passed instead of sweeping every shard. There is one wheel per shard the cache is built
+    /// with, each under its own lock, and a [`reshard`](ShardedExpiringCache::reshard) keeps
+    /// them.
+    ///
+    /// The rules for what the wheel sees are those of
+    /// [`ExpiringCacheBuilder::timer_wheel`](crate::ExpiringCacheBuilder::timer_wheel): deadlines
+    /// come from [`expires_at`](Expires::expires_at) rounded up to `resolution`, and while the
+    /// cache may hold a value without one, each sweep also scans the shards in order, checking
+    /// every entry's [`is_expired`](Expires::is_expired) under one shard's write lock at a
+    /// time.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`] if `resolution` is zero.
+    #[must_use]
+    pub fn timer_wheel(mut self, resolution: Duration) -> Self
+    where
+        K: Clone,
+    {
+        self.timer_wheel = Some(crate::stores::TimerWheelConfig::new(resolution));
+        self
+    }
+
     /// Set a callback invoked when an entry is evicted. Fires in five situations:
     /// on expired-entry removal during [`cache_get`](ConcurrentCached::cache_get);
     /// explicitly via [`evict`](ShardedExpiringCache::evict); on explicit
@@ -909,9 +1372,9 @@ impl<K, V, H> ShardedExpiringCacheBuilder<K, V, H> {
         H: ShardHasher<K>,
     {
         let new_cache = self.build()?;
-        for shard in existing.inner.shards.iter() {
+        for shard in existing.inner.shards.table().iter() {
             let entries: Vec<(K, V)> = {
-                let guard = shard.lock.read();
+                let guard = shard.read();
                 guard
                     .iter()
                     .filter(|(_, v)| !v.is_expired())
@@ -935,7 +1398,8 @@ impl<K, V, H> ShardedExpiringCacheBuilder<K, V, H> {
     /// # Errors
     ///
     /// Returns [`BuildError`] if the `shards` count is zero or overflows when rounded
-    /// up to the next power of two.
+    /// up to the next power of two, or if the `hot_keys` capacity or `timer_wheel` resolution
+    /// is zero.
     #[must_use = "the Result from build() must be used"]
     pub fn build(self) -> Result<ShardedExpiringCache<K, V, H>, BuildError>
     where
@@ -943,11 +1407,10 @@ impl<K, V, H> ShardedExpiringCacheBuilder<K, V, H> {
         H: ShardHasher<K>,
     {
         let n = checked_shard_count(self.shards)?;
-        let mask = n - 1;
         let per_shard_capacity = self.per_shard_initial_capacity.unwrap_or(0);
         let shards = (0..n)
             .map(|_| {
-                CachePadded(Shard::new(HashMap::with_capacity_and_hasher(
+                CachePadded(Shard::new(BucketMap::with_capacity_and_hasher(
                     per_shard_capacity,
                     RandomState::new(),
                 )))
@@ -956,12 +1419,25 @@ impl<K, V, H> ShardedExpiringCacheBuilder<K, V, H> {
             .into_boxed_slice();
         Ok(ShardedExpiringCache {
             inner: Arc::new(ExpiringInner {
-                shards,
-                shard_mask: mask,
+                shards: ShardSet::new(shards),
                 hasher: self
                     .hasher
                     .expect("hasher is always initialized via Default or .hasher()"),
                 on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
+                wheels: self
+                    .timer_wheel
+                    .map(|config| {
+                        (0..n)
+                            .map(|_| config.build().map(parking_lot::Mutex::new))
+                            .collect::<Result<_, _>>()
+                    })
+                    .transpose()?,
+                evict_cursor: parking_lot::Mutex::default(),
+                scan: parking_lot::Mutex::default(),
             }),
         })
     }
@@ -976,8 +1452,7 @@ where
     /// Returns `(Some(v), false)` for a live entry (hit), `(Some(v), true)` for an expired
     /// entry (miss, **no removal**, no eviction counter), or `(None, false)` when absent (miss).
     fn cache_get_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (shard, guard) = self.read_shard(k);
         match guard.get(k) {
             None => {
                 drop(guard);
@@ -1003,8 +1478,7 @@ where
     /// removes the entry. Returns `(Some(v), expired)` for a present entry (expired or not) or
     /// `(None, false)` when absent.
     fn cache_peek_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         match guard.get(k) {
             None => (None, false),
             Some(v) => {
@@ -1015,6 +1489,46 @@ where
     }
 }
 
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K, V, H> ConcurrentCachedSnapshot<K, V> for ShardedExpiringCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone + Expires,
+    H: ShardHasher<K>,
+{
+    fn snapshot(&self) -> CacheSnapshot<K, V> {
+        let clock = SnapshotClock::now();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let guard = shard.read();
+                guard
+                    .iter()
+                    .filter(|(_, v)| !v.is_expired())
+                    .filter_map(|(k, v)| clock.entry(k.clone(), v.clone(), v.expires_at()))
+                    .collect()
+            })
+            .collect();
+        CacheSnapshot::from_shards(shards)
+    }
+
+    fn restore(&self, snapshot: CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(SnapshotClock::now()) {
+            if v.is_expired() {
+                continue;
+            }
+            let _ = ConcurrentCached::cache_set(self, k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1179,6 +1693,7 @@ mod tests {
                     cache
                         .inner
                         .shards
+                        .table()
                         .iter()
                         .all(|s| s.lock.try_write().is_some()),
                     "on_evict must fire after the shard write lock is released"
@@ -1230,8 +1745,9 @@ mod tests {
         let expected: Vec<usize> = c
             .inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().keys().filter(|k| *k % 2 == 0).count())
+            .map(|s| s.read().iter().filter(|(k, _)| *k % 2 == 0).count())
             .collect();
         c.retain(|k, _v| k % 2 == 0);
         assert_eq!(c.shard_sizes(), expected);
@@ -2090,6 +2606,7 @@ mod tests {
         let nonzero_shards = c
             .inner
             .shards
+            .table()
             .iter()
             .filter(|s| s.evictions.load(Ordering::Relaxed) > 0)
             .count();
@@ -2133,7 +2650,13 @@ mod tests {
         );
         // Per-shard carry-over: every shard's evictions counter in the clone must match
         // the corresponding source shard, not just the aggregate.
-        for (src, cloned) in c.inner.shards.iter().zip(clone.inner.shards.iter()) {
+        for (src, cloned) in c
+            .inner
+            .shards
+            .table()
+            .iter()
+            .zip(clone.inner.shards.table().iter())
+        {
             assert_eq!(
                 src.evictions.load(Ordering::Relaxed),
                 cloned.evictions.load(Ordering::Relaxed),
@@ -2371,6 +2894,7 @@ mod tests {
     fn shard_eviction_counters<K, V, H>(c: &ShardedExpiringCache<K, V, H>) -> Vec<u64> {
         c.inner
             .shards
+            .table()
             .iter()
             .map(|s| s.evictions.load(Ordering::Relaxed))
             .collect()
@@ -2378,7 +2902,7 @@ mod tests {
 
     /// Index of the shard that owns `k`.
     fn owning_shard<K, V, H: ShardHasher<K>>(c: &ShardedExpiringCache<K, V, H>, k: &K) -> usize {
-        shard_index(c.inner.hasher.shard_hash(k), c.inner.shard_mask)
+        crate::stores::sharded::shard_index(c.inner.hasher.shard_hash(k), c.inner.shards.len() - 1)
     }
 
     /// Deterministic shard placement for cross-cache comparisons. `DefaultShardHasher` is
@@ -2400,8 +2924,8 @@ mod tests {
         c: &ShardedExpiringCache<u32, Val, H>,
     ) -> Vec<(u32, u32, bool)> {
         let mut out: Vec<(u32, u32, bool)> = Vec::new();
-        for shard in c.inner.shards.iter() {
-            let guard = shard.lock.read();
+        for shard in c.inner.shards.table().iter() {
+            let guard = shard.read();
             for (k, v) in guard.iter() {
                 out.push((*k, v.v, v.expired));
             }
@@ -2715,6 +3239,7 @@ mod tests {
         // movement, (3) the raw per-shard sum agrees with metrics(), and (4) no shard is
         // left in a torn state (shard_sizes sums to len()).
         use std::collections::HashSet;
+        use std::sync::atomic::AtomicU64;
         use std::sync::{Barrier, Mutex};
 
         const SHARDS: usize = 8;
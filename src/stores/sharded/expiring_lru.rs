@@ -1,6 +1,6 @@
 use std::hash::Hash;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::{
     CacheMetrics, CachedIter, CachedPeek, ConcurrentCacheBase, ConcurrentCachePeek,
@@ -12,24 +12,41 @@ use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
 use core::future::Future;
 
 use super::{
-    CachePadded, DefaultShardHasher, Shard, ShardHasher, checked_per_shard_cap_from_total,
-    checked_shard_count, default_shard_count_for_capacity, per_shard_cap_from_total, shard_index,
+    CachePadded, DefaultShardHasher, EvictCursor, Shard, ShardHasher, ShardRead, ShardSet,
+    ShardStats, ShardWrite, checked_per_shard_cap_from_total, checked_reshard_count,
+    checked_shard_count, default_shard_count_for_capacity, per_shard_cap_from_total, pin_shard,
+    pinned_len, reshard_lru,
 };
 use crate::Cached;
 use crate::ConcurrentCacheEvict;
-use crate::stores::{BuildError, LruCache};
+use crate::stores::{
+    BuildError, ConcurrentCachedTags, EvictProgress, LruCache, PinError, ReshardError, TagIndex,
+};
+#[cfg(feature = "persist")]
+use crate::stores::{CacheSnapshot, ConcurrentCachedSnapshot, SnapshotClock};
 
 type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
 
 #[allow(clippy::type_complexity)]
 struct ExpiringLruInner<K, V, H> {
-    shards: Box<[CachePadded<Shard<LruCache<K, V>>>]>,
-    shard_mask: usize,
+    shards: ShardSet<LruCache<K, V>>,
     hasher: H,
     on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
     /// Total logical capacity (sum of per-shard caps). Stored as `AtomicUsize` so
     /// [`set_max_size`](ShardedExpiringLruCache::set_max_size) can update it from `&self`.
     total_capacity: AtomicUsize,
+    /// Store-wide cap on pinned entries; the shards carry none of their own.
+    max_pinned: Option<usize>,
+    /// Serializes pins against `max_pinned`; taken only when a cap is set.
+    pin_lock: parking_lot::Mutex<()>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+    /// Where the next [`evict_budgeted`](ShardedExpiringLruCache::evict_budgeted) step
+    /// starts; the position is a slot of the shard's entry list.
+    evict_cursor: parking_lot::Mutex<EvictCursor>,
 }
 
 /// A fully-concurrent, partitioned, LRU size-bounded in-memory cache with per-value expiry.
@@ -95,6 +112,7 @@ impl<K, V, H> ShardedExpiringLruCache<K, V, H> {
     fn non_capacity_evictions(&self) -> u64 {
         self.inner
             .shards
+            .table()
             .iter()
             .map(|s| s.evictions.load(Ordering::Relaxed))
             .sum()
@@ -165,9 +183,13 @@ where
     H: ShardHasher<K>,
 {
     #[inline]
-    fn shard_of(&self, k: &K) -> &CachePadded<Shard<LruCache<K, V>>> {
-        let h = self.inner.hasher.shard_hash(k);
-        &self.inner.shards[shard_index(h, self.inner.shard_mask)]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, LruCache<K, V>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, LruCache<K, V>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
     }
 }
 
@@ -177,38 +199,47 @@ impl<K: Clone + Hash + Eq, V: Clone + Expires, H: ShardHasher<K>> ShardedExpirin
     /// what you want.
     #[must_use]
     pub fn deep_clone(&self) -> Self {
-        let n = self.inner.shards.len();
-        let shards = (0..n)
-            .map(|i| {
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
                 // Load the hit/miss counters under the read lock so the metrics snapshot is
                 // consistent with the entry snapshot (B4: loading after drop(guard) could yield
                 // counters newer than the cloned entries).
-                let guard = self.inner.shards[i].lock.read();
+                let guard = shard.read();
                 let store_copy = guard.clone();
-                let hits = self.inner.shards[i].hits.load(Ordering::Relaxed);
-                let misses = self.inner.shards[i].misses.load(Ordering::Relaxed);
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
                 // Carry the shard's non-capacity eviction count across too (it used to live
                 // in a single process-wide counter that `deep_clone` copied wholesale), so
                 // the clone's `metrics().evictions` matches the source's.
-                let evictions = self.inner.shards[i].evictions.load(Ordering::Relaxed);
+                let evictions = shard.evictions.load(Ordering::Relaxed);
                 drop(guard);
-                let shard = Shard {
-                    lock: parking_lot::RwLock::new(store_copy),
-                    hits: AtomicU64::new(hits),
-                    misses: AtomicU64::new(misses),
-                    evictions: AtomicU64::new(evictions),
-                };
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
+                shard.evictions.store(evictions, Ordering::Relaxed);
                 CachePadded(shard)
             })
             .collect::<Vec<_>>()
             .into_boxed_slice();
         Self {
             inner: Arc::new(ExpiringLruInner {
-                shards,
-                shard_mask: self.inner.shard_mask,
+                shards: ShardSet::new(shards),
                 hasher: self.inner.hasher.clone(),
                 on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
                 total_capacity: AtomicUsize::new(self.inner.total_capacity.load(Ordering::Relaxed)),
+                max_pinned: self.inner.max_pinned,
+                pin_lock: parking_lot::Mutex::new(()),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                deep_size: self.inner.deep_size,
+                evict_cursor: parking_lot::Mutex::default(),
             }),
         }
     }
@@ -297,10 +328,98 @@ where
     #[must_use]
     pub fn peek(&self, k: &K) -> Option<V> {
         use crate::CachedPeek;
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         guard.cache_peek(k).filter(|v| !v.is_expired()).cloned()
     }
+
+    /// Pin the live entry for `k` so LRU eviction never selects it, returning `Ok(false)`
+    /// when `k` is absent or expired. A pinned entry does not count toward its shard's
+    /// capacity and keeps its pin across a [`reshard`](Self::reshard), but it still expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`] when the entry is not pinned yet and the store
+    /// already holds [`max_pinned`](ShardedExpiringLruCacheBuilder::max_pinned) pinned
+    /// entries.
+    pub fn pin(&self, k: &K) -> Result<bool, PinError> {
+        let mut pin = self.pin_shard(k);
+        let hash = pin.store.hash(k);
+        let Some(index) = pin.store.get_index(hash, k) else {
+            return Ok(false);
+        };
+        if pin.store.order.get(index).1.is_expired() {
+            return Ok(false);
+        }
+        pin.check_room(pin.store.is_pinned_index(index))?;
+        pin.store.pin_index(index)?;
+        Ok(true)
+    }
+
+    /// Unpin the entry for `k`, returning whether it was pinned. The entry becomes its
+    /// shard's most recently used one and may evict that shard's least recently used entry.
+    pub fn unpin(&self, k: &K) -> bool {
+        self.write_shard(k).1.unpin(k)
+    }
+
+    /// Insert or replace the entry for `k` and pin it, returning the previous value only if
+    /// it had not yet expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`] when `k` is not pinned yet and the store already
+    /// holds [`max_pinned`](ShardedExpiringLruCacheBuilder::max_pinned) pinned entries.
+    /// Nothing is stored.
+    pub fn set_pinned(&self, k: K, v: V) -> Result<Option<V>, PinError> {
+        let old = {
+            let mut pin = self.pin_shard(&k);
+            pin.check_room(pin.store.is_pinned(&k))?;
+            let old = pin.store.set_pinned_returning_entry(k, v)?.map(|(ok, ov)| {
+                let expired = ov.is_expired();
+                (Some(ok), ov, expired)
+            });
+            if matches!(&old, Some((_, _, true))) {
+                pin.store.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+            old
+        };
+        Ok(self.notify_displaced(old))
+    }
+
+    /// Whether the entry for `k` is pinned, expired or not.
+    #[must_use]
+    pub fn is_pinned(&self, k: &K) -> bool {
+        self.read_shard(k).1.is_pinned(k)
+    }
+
+    /// Number of pinned entries across all shards, including expired ones not yet removed.
+    #[must_use]
+    pub fn pinned_len(&self) -> usize {
+        pinned_len(&self.inner.shards)
+    }
+
+    fn pin_shard(&self, k: &K) -> super::PinShard<'_, LruCache<K, V>> {
+        pin_shard(
+            &self.inner.shards,
+            &self.inner.pin_lock,
+            self.inner.max_pinned,
+            self.inner.hasher.shard_hash(k),
+        )
+    }
+
+    /// Finish a write after its shard lock is released: a live displaced value is returned,
+    /// an expired one (already counted under the lock) is handed to `on_evict`.
+    fn notify_displaced(&self, old: Option<(Option<K>, V, bool)>) -> Option<V> {
+        match old {
+            Some((key, ov, true)) => {
+                if let (Some(on_evict), Some(key)) = (&self.inner.on_evict, &key) {
+                    on_evict(key, &ov);
+                }
+                None
+            }
+            Some((_, ov, false)) => Some(ov),
+            None => None,
+        }
+    }
 }
 
 impl<K, V, H: ShardHasher<K>> ShardedExpiringLruCache<K, V, H>
@@ -330,14 +449,14 @@ where
         let mut inner_evictions = 0u64;
         let mut non_capacity_evictions = 0u64;
         let mut size = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             hits += shard.hits.load(Ordering::Relaxed);
             misses += shard.misses.load(Ordering::Relaxed);
             // Per-shard non-capacity evictions (lazy expiry / evict / retain); the
             // inner `LruCache` counter below holds this shard's capacity evictions and its
             // explicit removes. The two families are disjoint, so summing cannot double-count.
             non_capacity_evictions += shard.evictions.load(Ordering::Relaxed);
-            let guard = shard.lock.read();
+            let guard = shard.read();
             if let Some(e) = guard.cache_evictions() {
                 inner_evictions += e;
             }
@@ -351,6 +470,7 @@ where
             // Acquire, like `capacity()`: a caller that just resized on this thread sees
             // the new total here too, not a stale value alongside a fresh `capacity()`.
             capacity: Some(self.inner.total_capacity.load(Ordering::Acquire)),
+            mem_bytes: Some(self.shard_memory_usage().into_iter().sum()),
         }
     }
 
@@ -365,18 +485,82 @@ where
     pub fn shard_sizes(&self) -> Vec<usize> {
         self.inner
             .shards
+            .table()
+            .iter()
+            .map(|s| s.read().cache_size())
+            .collect()
+    }
+
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage). A shard whose share
+    /// of the bytes runs well ahead of its share of the entries holds unusually large values.
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        let deep_size = self.inner.deep_size;
+        self.inner
+            .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().cache_size())
+            .map(|shard| {
+                let store = shard.read();
+                let entries =
+                    deep_size.map_or(0, |heap| store.entries_heap_size(|k, v| heap.entry(k, v)));
+                size_of_val(shard) + store.allocated_bytes() + entries
+            })
             .collect()
     }
 
+    /// Per-shard entry counts (expired-but-unswept entries included), hit/miss counters and
+    /// lock-wait figures; see [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(|store| store.cache_size())
+    }
+
+    /// Migrate every entry into a new array of `shards` shards (rounded up to a power of two)
+    /// while the cache keeps serving requests.
+    ///
+    /// As on [`ShardedLruCache::reshard`](crate::ShardedLruCache::reshard), the total
+    /// capacity is re-split across the new shards and each old shard's entries are
+    /// re-inserted least recently used first. Expired entries move with the rest; the values
+    /// decide their own expiry, so nothing about it changes. Hit, miss and eviction counts
+    /// carry over. A no-op when the cache already has that many shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReshardError::ZeroShards`] or [`ReshardError::ShardCountOverflow`] if
+    /// `shards` is zero or overflows when rounded up, and [`ReshardError::CapacityOverflow`]
+    /// if the re-split capacity overflows `usize`. Returns [`ReshardError::Reentrant`],
+    /// rather than deadlocking, when called from a callback (such as `on_evict`) that a
+    /// whole-cache call or reshard on this same cache is running.
+    pub fn reshard(&self, shards: usize) -> Result<(), ReshardError>
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+    {
+        let n = checked_reshard_count(shards)?;
+        reshard_lru(
+            &self.inner.shards,
+            &self.inner.hasher,
+            &self.inner.total_capacity,
+            n,
+            |cap| {
+                let mut lru = LruCache::new(cap);
+                lru.on_evict = self.inner.on_evict.clone();
+                lru.disable_hit_miss_tracking();
+                lru
+            },
+        )
+    }
+
     /// Total number of entries across all shards (including not-yet-swept expired entries).
     #[must_use]
     pub fn len(&self) -> usize {
         self.inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().cache_size())
+            .map(|s| s.read().cache_size())
             .sum()
     }
 
@@ -385,15 +569,16 @@ where
     pub fn is_empty(&self) -> bool {
         self.inner
             .shards
+            .table()
             .iter()
-            .all(|s| s.lock.read().cache_size() == 0)
+            .all(|s| s.read().cache_size() == 0)
     }
 
     /// Remove all entries from every shard. Does **not** fire `on_evict`.
     /// Use [`cache_clear_with_on_evict`](Self::cache_clear_with_on_evict) to opt into callback firing.
     pub fn clear(&self) {
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().cache_clear();
+        for shard in self.inner.shards.table().iter() {
+            shard.write().cache_clear();
         }
     }
 
@@ -404,9 +589,9 @@ where
     /// (`metrics().evictions`) whether or not an `on_evict` callback is configured; the callback
     /// fires only when one is set.
     pub fn cache_clear_with_on_evict(&self) {
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed: Vec<(K, V)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 // `drain_all` walks each shard's LRU chain once taking owned pairs in
                 // MRU -> LRU order -- the same order the old "clone every key, then
                 // `pop_raw` each one" drain fired in, but with zero key clones and zero
@@ -450,9 +635,9 @@ where
     /// legitimate and common use.
     pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
         let mut total_removed = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed: Vec<(K, V)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 let doomed: Vec<K> = guard
                     .iter()
                     .filter_map(|(k, v)| {
@@ -556,10 +741,10 @@ where
     /// are the parallel methods on the other sharded LRU-bounded stores.
     pub fn set_max_size(&self, max_size: usize) -> Option<usize> {
         assert!(max_size > 0, "max_size must be greater than zero");
-        let n_shards = self.inner.shards.len();
-        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, n_shards);
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().set_max_size(per_shard_cap);
+        let table = self.inner.shards.table();
+        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, table.len());
+        for shard in table.iter() {
+            shard.write().set_max_size(per_shard_cap);
         }
         // Publish the new total only after every shard has adopted its new cap;
         // Release pairs with the Acquire load in `capacity()`.
@@ -601,10 +786,15 @@ where
         Ok(Some(self.len()))
     }
 
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum())
+    }
+
     fn cache_hits(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.hits.load(Ordering::Relaxed))
                 .sum(),
@@ -615,12 +805,18 @@ where
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.misses.load(Ordering::Relaxed))
                 .sum(),
         )
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
+
     fn cache_capacity(&self) -> Option<usize> {
         // Acquire: see `capacity()`.
         Some(self.inner.total_capacity.load(Ordering::Acquire))
@@ -628,8 +824,8 @@ where
 
     fn cache_evictions(&self) -> Option<u64> {
         let mut inner_evictions = 0u64;
-        for shard in self.inner.shards.iter() {
-            let guard = shard.lock.read();
+        for shard in self.inner.shards.table().iter() {
+            let guard = shard.read();
             if let Some(e) = Cached::cache_evictions(&*guard) {
                 inner_evictions += e;
             }
@@ -645,8 +841,11 @@ where
     H: ShardHasher<K>,
 {
     fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
-        let mut guard = shard.lock.write();
+        if let Some(hot) = &self.inner.hot_keys {
+            let hash = self.inner.hasher.shard_hash(k);
+            hot.record(hash, || Some(k));
+        }
+        let (shard, mut guard) = self.write_shard(k);
         // The common case (a live hit) resolves in a SINGLE hash + probe: `get_if` promotes
         // LRU recency only when the predicate reports the value live, so an expired entry is
         // neither promoted nor removed here -- exactly the intent of the old peek-then-get
@@ -679,7 +878,6 @@ where
     }
 
     fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(&k);
         // With a callback we need the *stored* key to hand to it, so the write goes through
         // `cache_set_returning_entry`; otherwise a plain set. Both promote an overwritten key to
         // MRU, so the two branches agree on eviction order. A displaced
@@ -690,7 +888,7 @@ where
         // crossing the expiry threshold between two evaluations would otherwise fire `on_evict`
         // without counting the eviction.
         let old: Option<(Option<K>, V, bool)> = {
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = self.write_shard(&k);
             let old = if self.inner.on_evict.is_some() {
                 guard.cache_set_returning_entry(k, v).map(|(ok, ov)| {
                     let expired = ov.is_expired();
@@ -710,16 +908,7 @@ where
             }
             old
         };
-        match old {
-            Some((key, ov, true)) => {
-                if let (Some(on_evict), Some(key)) = (&self.inner.on_evict, &key) {
-                    on_evict(key, &ov);
-                }
-                Ok(None)
-            }
-            Some((_, ov, false)) => Ok(Some(ov)),
-            None => Ok(None),
-        }
+        Ok(self.notify_displaced(old))
     }
 
     /// Removes the entry and returns the value only if it is still live;
@@ -727,9 +916,8 @@ where
     /// [`cache_remove_entry`](ConcurrentCached::cache_remove_entry) to
     /// receive the value regardless of expiry.
     fn cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
         let removed = {
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = self.write_shard(k);
             let removed = guard.pop_raw(k);
             if removed.is_some() {
                 guard.evictions.fetch_add(1, Ordering::Relaxed);
@@ -753,9 +941,8 @@ where
     /// [`cache_remove`](ConcurrentCached::cache_remove), which filters
     /// expired values).
     fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
-        let shard = self.shard_of(k);
         let removed = {
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = self.write_shard(k);
             let removed = guard.pop_raw(k);
             if removed.is_some() {
                 guard.evictions.fetch_add(1, Ordering::Relaxed);
@@ -782,31 +969,68 @@ where
     }
 
     fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
-        for shard in self.inner.shards.iter() {
-            shard.hits.store(0, Ordering::Relaxed);
-            shard.misses.store(0, Ordering::Relaxed);
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
             // The shard's non-capacity eviction counter (lazy expiry / evict / retain / clear).
             shard.evictions.store(0, Ordering::Relaxed);
             // Zero the per-shard inner store's metrics, including its LRU capacity-eviction counter.
-            shard.lock.write().cache_reset_metrics();
+            shard.write().cache_reset_metrics();
+        }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
         }
         Ok(())
     }
 
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
     /// Efficient peek-based contains: acquires a read lock, does not clone the value, does not
     /// update LRU recency, and does not record hit/miss metrics. Returns `true` only for live
     /// (not expired) entries.
     fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
         use crate::CachedPeek;
-        let shard = self.shard_of(k);
-        Ok(shard
-            .lock
-            .read()
+        Ok(self
+            .read_shard(k)
+            .1
             .cache_peek(k)
             .is_some_and(|v| !v.is_expired()))
     }
 }
 
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedExpiringLruCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone + Expires,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| matches!(ConcurrentCached::cache_contains(self, k), Ok(true)));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
 impl<K, V, H> ConcurrentCachePeek<K, V> for ShardedExpiringLruCache<K, V, H>
 where
     K: Hash + Eq + Clone,
@@ -892,9 +1116,9 @@ where
     #[must_use]
     pub fn evict(&self) -> usize {
         let mut total = 0;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 let expired_keys: Vec<K> = guard
                     .iter()
                     .filter(|(_, v)| v.is_expired())
@@ -923,6 +1147,44 @@ where
         }
         total
     }
+
+    /// Take one step of an incremental [`evict`](Self::evict): check at most `max_entries`
+    /// values from where the last step stopped, remove the expired ones, and report how many
+    /// were removed and whether the sweep got past the last shard.
+    ///
+    /// Within a shard, steps walk the entries in storage order rather than recency order, so
+    /// a hit mid-sweep does not move an entry past the cursor. See
+    /// [`ConcurrentCacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        super::evict_budgeted(
+            &self.inner.shards,
+            &self.inner.evict_cursor,
+            max_entries,
+            |store, position, budget| {
+                let (doomed, visited, next) =
+                    store.doomed_slots(position, budget, |_, v| v.is_expired());
+                let removed: Vec<(K, V)> = doomed
+                    .into_iter()
+                    .map(|index| store.remove_index(index))
+                    .collect();
+                (removed, visited, next)
+            },
+            |shard, removed| {
+                if removed.is_empty() {
+                    return;
+                }
+                shard
+                    .evictions
+                    .fetch_add(removed.len() as u64, Ordering::Relaxed);
+                if let Some(on_evict) = &self.inner.on_evict {
+                    for (k, v) in &removed {
+                        on_evict(k, v);
+                    }
+                }
+            },
+        )
+    }
 }
 
 impl<K, V, H> ConcurrentCacheEvict for ShardedExpiringLruCache<K, V, H>
@@ -934,6 +1196,10 @@ where
     fn evict(&self) -> usize {
         ShardedExpiringLruCache::evict(self)
     }
+
+    fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        ShardedExpiringLruCache::evict_budgeted(self, max_entries)
+    }
 }
 
 /// Builder for [`ShardedExpiringLruCache`].
@@ -947,10 +1213,14 @@ pub struct ShardedExpiringLruCacheBuilder<K, V, H = DefaultShardHasher> {
     shards: Option<usize>,
     max_size: Option<usize>,
     per_shard_max_size: Option<usize>,
+    max_pinned: Option<usize>,
     hasher: Option<H>,
     on_evict: Option<OnEvict<K, V>>,
     _k: std::marker::PhantomData<K>,
     _v: std::marker::PhantomData<V>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for ShardedExpiringLruCacheBuilder<K, V, DefaultShardHasher> {
@@ -959,10 +1229,14 @@ impl<K, V> Default for ShardedExpiringLruCacheBuilder<K, V, DefaultShardHasher>
             shards: None,
             max_size: None,
             per_shard_max_size: None,
+            max_pinned: None,
             hasher: Some(DefaultShardHasher::default()),
             on_evict: None,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -1021,6 +1295,14 @@ impl<K, V, H> ShardedExpiringLruCacheBuilder<K, V, H> {
         self
     }
 
+    /// Cap the number of pinned entries across all shards. Pinned entries do not count
+    /// toward any shard's capacity; see [`ShardedExpiringLruCache::pin`].
+    #[must_use]
+    pub fn max_pinned(mut self, max_pinned: usize) -> Self {
+        self.max_pinned = Some(max_pinned);
+        self
+    }
+
     /// Set a custom shard-selection hasher, changing the type parameter.
     ///
     /// The hasher decides only which shard a key maps to — it does **not** replace the
@@ -1039,13 +1321,64 @@ impl<K, V, H> ShardedExpiringLruCacheBuilder<K, V, H> {
             shards: self.shards,
             max_size: self.max_size,
             per_shard_max_size: self.per_shard_max_size,
+            max_pinned: self.max_pinned,
             hasher: Some(hasher),
             on_evict: self.on_evict,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key. Read them with
+    /// [`top_keys`](crate::ConcurrentCachedExt::top_keys), which merges the shards' summaries.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](crate::stores::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::ConcurrentCachedExt::memory_usage) and `shard_memory_usage`,
+    /// measured through their [`DeepSize`](crate::DeepSize) impls. Each estimate then visits
+    /// every entry under its shard's read lock.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
     /// Set a callback invoked when an entry is evicted. Fires in six situations:
     /// for LRU capacity evictions; expired-entry removal during
     /// [`cache_get`](ConcurrentCached::cache_get); explicitly via
@@ -1170,11 +1503,11 @@ impl<K, V, H> ShardedExpiringLruCacheBuilder<K, V, H> {
         H: ShardHasher<K>,
     {
         let new_cache = self.build()?;
-        for shard in existing.inner.shards.iter() {
+        for shard in existing.inner.shards.table().iter() {
             // iter_order returns MRU-first; insert in reverse (LRU-first) so
             // that MRU entries land at the head of the new cache.
             let entries: Vec<(K, V)> = {
-                let guard = shard.lock.read();
+                let guard = shard.read();
                 guard.iter_order_raw()
             };
             for (k, v) in entries.into_iter().rev() {
@@ -1195,15 +1528,15 @@ impl<K, V, H> ShardedExpiringLruCacheBuilder<K, V, H> {
     ///
     /// Returns [`BuildError`] if `size` (or `per_shard_max_size`) was not set, is `0`,
     /// or if both `max_size` and `per_shard_max_size` are set simultaneously,
-    /// or if the shard count overflows.
+    /// if the shard count overflows, or if `max_pinned` is `0`.
     #[must_use = "the Result from build() must be used"]
     pub fn build(self) -> Result<ShardedExpiringLruCache<K, V, H>, BuildError>
     where
         K: Hash + Eq + Clone,
         H: ShardHasher<K>,
     {
+        crate::stores::check_max_pinned(self.max_pinned)?;
         let n = self.resolve_shard_count()?;
-        let mask = n - 1;
         let per_shard_cap = self.resolve_per_shard_cap(n)?;
         let total_cap = self.total_capacity(n, per_shard_cap)?;
         let on_evict = self.on_evict.clone();
@@ -1218,13 +1551,19 @@ impl<K, V, H> ShardedExpiringLruCacheBuilder<K, V, H> {
             .into_boxed_slice();
         Ok(ShardedExpiringLruCache {
             inner: Arc::new(ExpiringLruInner {
-                shards,
-                shard_mask: mask,
+                shards: ShardSet::new(shards),
                 hasher: self
                     .hasher
                     .expect("hasher is always initialized via Default or .hasher()"),
                 on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
                 total_capacity: AtomicUsize::new(total_cap),
+                max_pinned: self.max_pinned,
+                pin_lock: parking_lot::Mutex::new(()),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
+                evict_cursor: parking_lot::Mutex::default(),
             }),
         })
     }
@@ -1240,8 +1579,7 @@ where
     /// expired entry (miss, **no removal**, no LRU promotion, no eviction counter), or
     /// `(None, false)` when absent (miss).
     fn cache_get_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
-        let mut guard = shard.lock.write();
+        let (shard, mut guard) = self.write_shard(k);
         // Single peek captures both expiry status and value; the expired path
         // can then return without a second lookup.
         let (expired, peeked) = match guard.cache_peek(k) {
@@ -1271,8 +1609,7 @@ where
     /// the hits/misses counters, and does not remove the entry. Returns `(Some(v), expired)` for
     /// a present entry (expired or not) or `(None, false)` when absent.
     fn cache_peek_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         match guard.cache_peek(k) {
             None => (None, false),
             Some(v) => {
@@ -1283,6 +1620,51 @@ where
     }
 }
 
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K, V, H> ConcurrentCachedSnapshot<K, V> for ShardedExpiringLruCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone + Expires,
+    H: ShardHasher<K>,
+{
+    /// Each shard's entries are recorded least-recently-used first.
+    fn snapshot(&self) -> CacheSnapshot<K, V> {
+        let clock = SnapshotClock::now();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let entries = shard.read().iter_order_raw();
+                entries
+                    .into_iter()
+                    .rev()
+                    .filter(|(_, v)| !v.is_expired())
+                    .filter_map(|(k, v)| {
+                        let expires_at = v.expires_at();
+                        clock.entry(k, v, expires_at)
+                    })
+                    .collect()
+            })
+            .collect();
+        CacheSnapshot::from_shards(shards)
+    }
+
+    fn restore(&self, snapshot: CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(SnapshotClock::now()) {
+            if v.is_expired() {
+                continue;
+            }
+            let _ = ConcurrentCached::cache_set(self, k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2404,8 +2786,7 @@ mod tests {
             )
             .expect("insert must succeed");
         }
-        let before: Vec<u32> = c.inner.shards[0]
-            .lock
+        let before: Vec<u32> = c.inner.shards.table()[0]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -2415,8 +2796,7 @@ mod tests {
 
         c.retain(|k, _| k % 2 == 0);
 
-        let after: Vec<u32> = c.inner.shards[0]
-            .lock
+        let after: Vec<u32> = c.inner.shards.table()[0]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -2449,8 +2829,7 @@ mod tests {
             )
             .expect("insert must succeed");
         }
-        let inner_before = c.inner.shards[0]
-            .lock
+        let inner_before = c.inner.shards.table()[0]
             .read()
             .evictions
             .load(Ordering::Relaxed);
@@ -2458,8 +2837,7 @@ mod tests {
 
         c.retain(|k, _| k % 2 == 0);
 
-        let inner_after = c.inner.shards[0]
-            .lock
+        let inner_after = c.inner.shards.table()[0]
             .read()
             .evictions
             .load(Ordering::Relaxed);
@@ -2490,6 +2868,7 @@ mod tests {
     fn shard_eviction_counters<K, V, H>(c: &ShardedExpiringLruCache<K, V, H>) -> Vec<u64> {
         c.inner
             .shards
+            .table()
             .iter()
             .map(|s| s.evictions.load(Ordering::Relaxed))
             .collect()
@@ -2497,7 +2876,7 @@ mod tests {
 
     /// Index of the shard that owns `k`.
     fn owning_shard<K, V, H: ShardHasher<K>>(c: &ShardedExpiringLruCache<K, V, H>, k: &K) -> usize {
-        shard_index(c.inner.hasher.shard_hash(k), c.inner.shard_mask)
+        crate::stores::sharded::shard_index(c.inner.hasher.shard_hash(k), c.inner.shards.len() - 1)
     }
 
     /// Keys of one shard in MRU -> LRU order.
@@ -2505,8 +2884,7 @@ mod tests {
         c: &ShardedExpiringLruCache<K, V, H>,
         shard: usize,
     ) -> Vec<K> {
-        c.inner.shards[shard]
-            .lock
+        c.inner.shards.table()[shard]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -1,6 +1,6 @@
 use std::hash::Hash;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::{CacheMetrics, CachedIter, ConcurrentCacheBase, ConcurrentCachePeek, ConcurrentCached};
 #[cfg(feature = "async_core")]
@@ -9,22 +9,33 @@ use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
 use core::future::Future;
 
 use super::{
-    CachePadded, DefaultShardHasher, Shard, ShardHasher, checked_per_shard_cap_from_total,
-    checked_shard_count, default_shard_count_for_capacity, per_shard_cap_from_total, shard_index,
+    CachePadded, DefaultShardHasher, Shard, ShardHasher, ShardRead, ShardSet, ShardStats,
+    ShardWrite, checked_per_shard_cap_from_total, checked_reshard_count, checked_shard_count,
+    default_shard_count_for_capacity, per_shard_cap_from_total, pin_shard, pinned_len, reshard_lru,
 };
-use crate::stores::{BuildError, LruCache};
+use crate::stores::{BuildError, ConcurrentCachedTags, LruCache, PinError, ReshardError, TagIndex};
+#[cfg(feature = "persist")]
+use crate::stores::{CacheSnapshot, ConcurrentCachedSnapshot, SnapshotClock};
 
 type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
 
 #[allow(clippy::type_complexity)]
 struct LruInner<K, V, H> {
-    shards: Box<[CachePadded<Shard<LruCache<K, V>>>]>,
-    shard_mask: usize,
+    shards: ShardSet<LruCache<K, V>>,
     hasher: H,
     on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
     /// Total logical capacity (sum of per-shard caps). Stored as `AtomicUsize` so
     /// [`set_max_size`](ShardedLruCache::set_max_size) can update it from `&self`.
     total_capacity: AtomicUsize,
+    /// Store-wide cap on pinned entries; the shards carry none of their own.
+    max_pinned: Option<usize>,
+    /// Serializes pins against `max_pinned`; taken only when a cap is set.
+    pin_lock: parking_lot::Mutex<()>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
 }
 
 /// A fully-concurrent, partitioned, LRU-bounded in-memory cache.
@@ -135,9 +146,13 @@ where
     H: ShardHasher<K>,
 {
     #[inline]
-    fn shard_of(&self, k: &K) -> &CachePadded<Shard<LruCache<K, V>>> {
-        let h = self.inner.hasher.shard_hash(k);
-        &self.inner.shards[shard_index(h, self.inner.shard_mask)]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, LruCache<K, V>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, LruCache<K, V>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
     }
 }
 
@@ -147,31 +162,38 @@ impl<K: Clone + Hash + Eq, V: Clone, H: ShardHasher<K>> ShardedLruCache<K, V, H>
     /// what you want.
     #[must_use]
     pub fn deep_clone(&self) -> Self {
-        let n = self.inner.shards.len();
-        let shards = (0..n)
-            .map(|i| {
-                let guard = self.inner.shards[i].lock.read();
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let guard = shard.read();
                 let store_copy = guard.clone();
-                let hits = self.inner.shards[i].hits.load(Ordering::Relaxed);
-                let misses = self.inner.shards[i].misses.load(Ordering::Relaxed);
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
                 drop(guard);
-                let shard = Shard {
-                    lock: parking_lot::RwLock::new(store_copy),
-                    hits: AtomicU64::new(hits),
-                    misses: AtomicU64::new(misses),
-                    evictions: AtomicU64::new(0),
-                };
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
                 CachePadded(shard)
             })
             .collect::<Vec<_>>()
             .into_boxed_slice();
         Self {
             inner: Arc::new(LruInner {
-                shards,
-                shard_mask: self.inner.shard_mask,
+                shards: ShardSet::new(shards),
                 hasher: self.inner.hasher.clone(),
                 on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
                 total_capacity: AtomicUsize::new(self.inner.total_capacity.load(Ordering::Relaxed)),
+                max_pinned: self.inner.max_pinned,
+                pin_lock: parking_lot::Mutex::new(()),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                deep_size: self.inner.deep_size,
             }),
         }
     }
@@ -259,7 +281,66 @@ where
     #[must_use]
     pub fn peek(&self, k: &K) -> Option<V> {
         use crate::CachedPeek;
-        self.shard_of(k).lock.read().cache_peek(k).cloned()
+        self.read_shard(k).1.cache_peek(k).cloned()
+    }
+
+    /// Pin the entry for `k` so LRU eviction never selects it, returning `Ok(false)` when `k`
+    /// is absent. A pinned entry does not count toward its shard's capacity and keeps its pin
+    /// across a [`reshard`](Self::reshard). See [`LruCache::pin`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`] when the entry is not pinned yet and the store
+    /// already holds [`max_pinned`](ShardedLruCacheBuilder::max_pinned) pinned entries.
+    pub fn pin(&self, k: &K) -> Result<bool, PinError> {
+        let mut pin = self.pin_shard(k);
+        let hash = pin.store.hash(k);
+        let Some(index) = pin.store.get_index(hash, k) else {
+            return Ok(false);
+        };
+        pin.check_room(pin.store.is_pinned_index(index))?;
+        pin.store.pin_index(index)?;
+        Ok(true)
+    }
+
+    /// Unpin the entry for `k`, returning whether it was pinned. The entry becomes its
+    /// shard's most recently used one and may evict that shard's least recently used entry.
+    pub fn unpin(&self, k: &K) -> bool {
+        self.write_shard(k).1.unpin(k)
+    }
+
+    /// Insert or replace the entry for `k` and pin it, returning the previous value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`] when `k` is not pinned yet and the store already
+    /// holds [`max_pinned`](ShardedLruCacheBuilder::max_pinned) pinned entries. Nothing is
+    /// stored.
+    pub fn set_pinned(&self, k: K, v: V) -> Result<Option<V>, PinError> {
+        let mut pin = self.pin_shard(&k);
+        pin.check_room(pin.store.is_pinned(&k))?;
+        pin.store.set_pinned(k, v)
+    }
+
+    /// Whether the entry for `k` is pinned.
+    #[must_use]
+    pub fn is_pinned(&self, k: &K) -> bool {
+        self.read_shard(k).1.is_pinned(k)
+    }
+
+    /// Number of pinned entries across all shards.
+    #[must_use]
+    pub fn pinned_len(&self) -> usize {
+        pinned_len(&self.inner.shards)
+    }
+
+    fn pin_shard(&self, k: &K) -> super::PinShard<'_, LruCache<K, V>> {
+        pin_shard(
+            &self.inner.shards,
+            &self.inner.pin_lock,
+            self.inner.max_pinned,
+            self.inner.hasher.shard_hash(k),
+        )
     }
 }
 
@@ -282,10 +363,10 @@ where
         let mut misses = 0u64;
         let mut evictions = 0u64;
         let mut size = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             hits += shard.hits.load(Ordering::Relaxed);
             misses += shard.misses.load(Ordering::Relaxed);
-            let guard = shard.lock.read();
+            let guard = shard.read();
             if let Some(e) = guard.cache_evictions() {
                 evictions += e;
             }
@@ -300,6 +381,7 @@ where
             // Acquire, like `capacity()`: a caller that just resized on this thread sees
             // the new total here too, not a stale value alongside a fresh `capacity()`.
             capacity: Some(self.inner.total_capacity.load(Ordering::Acquire)),
+            mem_bytes: Some(self.shard_memory_usage().into_iter().sum()),
         }
     }
 
@@ -314,11 +396,79 @@ where
     pub fn shard_sizes(&self) -> Vec<usize> {
         self.inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().cache_size())
+            .map(|s| s.read().cache_size())
             .collect()
     }
 
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage). A shard whose share
+    /// of the bytes runs well ahead of its share of the entries holds unusually large values.
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        let deep_size = self.inner.deep_size;
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let store = shard.read();
+                let entries =
+                    deep_size.map_or(0, |heap| store.entries_heap_size(|k, v| heap.entry(k, v)));
+                size_of_val(shard) + store.allocated_bytes() + entries
+            })
+            .collect()
+    }
+
+    /// Per-shard entry counts, hit/miss counters and lock-wait figures; see [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(|store| store.cache_size())
+    }
+
+    /// Migrate every entry into a new array of `shards` shards (rounded up to a power of two)
+    /// while the cache keeps serving requests.
+    ///
+    /// The total [`capacity`](Self::capacity) is re-split across the new shards with the same
+    /// 16-per-shard floor the builder applies, so growing the shard count can raise it. Each
+    /// old shard's entries are re-inserted least recently used first, which keeps their
+    /// relative recency in whichever new shard they land in; a new shard that receives more
+    /// than its cap evicts its least recent entries (firing `on_evict`), as a `cache_set`
+    /// would.
+    ///
+    /// Old shards are drained one at a time, so a lookup waits only while its own shard's
+    /// entries move; whole-cache calls (`len`, `clear`, `set_max_size`, ...) wait for the
+    /// full migration. Hit, miss and eviction counts carry over. A no-op when the cache
+    /// already has that many shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReshardError::ZeroShards`] or [`ReshardError::ShardCountOverflow`] if
+    /// `shards` is zero or overflows when rounded up, and [`ReshardError::CapacityOverflow`]
+    /// if the re-split capacity overflows `usize`. Returns [`ReshardError::Reentrant`],
+    /// rather than deadlocking, when called from a callback (such as `on_evict`) that a
+    /// whole-cache call or reshard on this same cache is running.
+    pub fn reshard(&self, shards: usize) -> Result<(), ReshardError>
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+    {
+        let n = checked_reshard_count(shards)?;
+        reshard_lru(
+            &self.inner.shards,
+            &self.inner.hasher,
+            &self.inner.total_capacity,
+            n,
+            |cap| {
+                let mut lru = LruCache::new(cap);
+                lru.on_evict = self.inner.on_evict.clone();
+                lru.disable_hit_miss_tracking();
+                lru
+            },
+        )
+    }
+
     /// Total number of live entries across all shards.
     ///
     /// Approximate under concurrent mutation: no global lock is held across shards; each shard is
@@ -327,8 +477,9 @@ where
     pub fn len(&self) -> usize {
         self.inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().cache_size())
+            .map(|s| s.read().cache_size())
             .sum()
     }
 
@@ -340,15 +491,16 @@ where
     pub fn is_empty(&self) -> bool {
         self.inner
             .shards
+            .table()
             .iter()
-            .all(|s| s.lock.read().cache_size() == 0)
+            .all(|s| s.read().cache_size() == 0)
     }
 
     /// Remove all entries from every shard. Does **not** fire `on_evict`.
     /// Use [`cache_clear_with_on_evict`](Self::cache_clear_with_on_evict) to opt into callback firing.
     pub fn clear(&self) {
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().cache_clear();
+        for shard in self.inner.shards.table().iter() {
+            shard.write().cache_clear();
         }
     }
 
@@ -359,9 +511,9 @@ where
     /// (`metrics().evictions`) whether or not an `on_evict` callback is configured; the callback
     /// fires only when one is set.
     pub fn cache_clear_with_on_evict(&self) {
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed: Vec<(K, V)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 // `drain_all` walks each shard's LRU chain once taking owned pairs in
                 // MRU -> LRU order -- the same order the old "clone every key, then
                 // `pop_raw` each one" drain fired in, but with zero key clones and zero
@@ -400,9 +552,9 @@ where
     /// call has not yet visited will have that entry filtered by the same in-flight `retain`.
     pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
         let mut total_removed = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed: Vec<(K, V)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 let doomed: Vec<K> = guard
                     .iter()
                     .filter_map(|(k, v)| if keep(k, v) { None } else { Some(k.clone()) })
@@ -496,10 +648,10 @@ where
     /// are the parallel methods on the other sharded LRU-bounded stores.
     pub fn set_max_size(&self, max_size: usize) -> Option<usize> {
         assert!(max_size > 0, "max_size must be greater than zero");
-        let n_shards = self.inner.shards.len();
-        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, n_shards);
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().set_max_size(per_shard_cap);
+        let table = self.inner.shards.table();
+        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, table.len());
+        for shard in table.iter() {
+            shard.write().set_max_size(per_shard_cap);
         }
         // Publish the new total only after every shard has adopted its new cap;
         // Release pairs with the Acquire load in `capacity()`.
@@ -543,10 +695,15 @@ where
         Ok(Some(self.len()))
     }
 
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum())
+    }
+
     fn cache_hits(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.hits.load(Ordering::Relaxed))
                 .sum(),
@@ -557,12 +714,18 @@ where
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.misses.load(Ordering::Relaxed))
                 .sum(),
         )
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
+
     fn cache_capacity(&self) -> Option<usize> {
         // Acquire: see `capacity()`.
         Some(self.inner.total_capacity.load(Ordering::Acquire))
@@ -570,8 +733,8 @@ where
 
     fn cache_evictions(&self) -> Option<u64> {
         let mut evictions = 0u64;
-        for shard in self.inner.shards.iter() {
-            let guard = shard.lock.read();
+        for shard in self.inner.shards.table().iter() {
+            let guard = shard.read();
             if let Some(e) = guard.cache_evictions() {
                 evictions += e;
             }
@@ -587,8 +750,11 @@ where
     H: ShardHasher<K>,
 {
     fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
-        let mut guard = shard.lock.write();
+        if let Some(hot) = &self.inner.hot_keys {
+            let hash = self.inner.hasher.shard_hash(k);
+            hot.record(hash, || Some(k));
+        }
+        let (shard, mut guard) = self.write_shard(k);
         let value = guard.cache_get(k).cloned();
         // Release the shard lock before touching the counters: the atomics are
         // shard-local but there is no reason to hold the write lock across them
@@ -607,8 +773,7 @@ where
     }
 
     fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(&k);
-        Ok(shard.lock.write().cache_set(k, v))
+        Ok(self.write_shard(&k).1.cache_set(k, v))
     }
 
     fn cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
@@ -616,9 +781,8 @@ where
     }
 
     fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
-        let shard = self.shard_of(k);
         let removed = {
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = self.write_shard(k);
             let removed = guard.pop_raw(k);
             if removed.is_some() {
                 guard.evictions.fetch_add(1, Ordering::Relaxed);
@@ -644,21 +808,58 @@ where
     }
 
     fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
-        for shard in self.inner.shards.iter() {
-            shard.hits.store(0, Ordering::Relaxed);
-            shard.misses.store(0, Ordering::Relaxed);
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
             // Zero the per-shard inner store's metrics, including its eviction counter.
-            shard.lock.write().cache_reset_metrics();
+            shard.write().cache_reset_metrics();
+        }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
         }
         Ok(())
     }
 
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
     /// Efficient peek-based contains: acquires a read lock, does not clone the value,
     /// does not update LRU recency, and does not record hit/miss metrics.
     fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
         use crate::CachedPeek;
-        let shard = self.shard_of(k);
-        Ok(shard.lock.read().cache_peek(k).is_some())
+        Ok(self.read_shard(k).1.cache_peek(k).is_some())
+    }
+}
+
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedLruCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| matches!(ConcurrentCached::cache_contains(self, k), Ok(true)));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
     }
 }
 
@@ -741,10 +942,14 @@ pub struct ShardedLruCacheBuilder<K, V, H = DefaultShardHasher> {
     shards: Option<usize>,
     max_size: Option<usize>,
     per_shard_max_size: Option<usize>,
+    max_pinned: Option<usize>,
     hasher: Option<H>,
     on_evict: Option<OnEvict<K, V>>,
     _k: std::marker::PhantomData<K>,
     _v: std::marker::PhantomData<V>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for ShardedLruCacheBuilder<K, V, DefaultShardHasher> {
@@ -753,10 +958,14 @@ impl<K, V> Default for ShardedLruCacheBuilder<K, V, DefaultShardHasher> {
             shards: None,
             max_size: None,
             per_shard_max_size: None,
+            max_pinned: None,
             hasher: Some(DefaultShardHasher::default()),
             on_evict: None,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -815,6 +1024,14 @@ impl<K, V, H> ShardedLruCacheBuilder<K, V, H> {
         self
     }
 
+    /// Cap the number of pinned entries across all shards. Pinned entries do not count
+    /// toward any shard's capacity; see [`ShardedLruCache::pin`].
+    #[must_use]
+    pub fn max_pinned(mut self, max_pinned: usize) -> Self {
+        self.max_pinned = Some(max_pinned);
+        self
+    }
+
     /// Set a custom shard-selection hasher, changing the type parameter.
     ///
     /// The hasher decides only which shard a key maps to — it does **not** replace the
@@ -830,13 +1047,64 @@ impl<K, V, H> ShardedLruCacheBuilder<K, V, H> {
             shards: self.shards,
             max_size: self.max_size,
             per_shard_max_size: self.per_shard_max_size,
+            max_pinned: self.max_pinned,
             hasher: Some(hasher),
             on_evict: self.on_evict,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key. Read them with
+    /// [`top_keys`](crate::ConcurrentCachedExt::top_keys), which merges the shards' summaries.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](crate::stores::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::ConcurrentCachedExt::memory_usage) and `shard_memory_usage`,
+    /// measured through their [`DeepSize`](crate::DeepSize) impls. Each estimate then visits
+    /// every entry under its shard's read lock.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
     /// Set a callback invoked when an entry is evicted. Fires in four situations:
     /// on LRU capacity pressure; on explicit
     /// [`cache_remove`](ConcurrentCached::cache_remove); on
@@ -933,16 +1201,16 @@ impl<K, V, H> ShardedLruCacheBuilder<K, V, H> {
     /// # Errors
     ///
     /// Returns [`BuildError`] if `max_size` (or `per_shard_max_size`) was not set, is `0`,
-    /// or if both `max_size` and `per_shard_max_size` are set simultaneously, or if the
-    /// effective sharded capacity overflows `usize`.
+    /// or if both `max_size` and `per_shard_max_size` are set simultaneously, if the
+    /// effective sharded capacity overflows `usize`, or if `max_pinned` is `0`.
     #[must_use = "the Result from build() must be used"]
     pub fn build(self) -> Result<ShardedLruCache<K, V, H>, BuildError>
     where
         K: Hash + Eq + Clone,
         H: ShardHasher<K>,
     {
+        crate::stores::check_max_pinned(self.max_pinned)?;
         let n = self.resolve_shard_count()?;
-        let mask = n - 1;
         let per_shard_cap = self.resolve_per_shard_cap(n)?;
         let total_cap = self.total_capacity(n, per_shard_cap)?;
         let on_evict = self.on_evict.clone();
@@ -957,13 +1225,18 @@ impl<K, V, H> ShardedLruCacheBuilder<K, V, H> {
             .into_boxed_slice();
         Ok(ShardedLruCache {
             inner: Arc::new(LruInner {
-                shards,
-                shard_mask: mask,
+                shards: ShardSet::new(shards),
                 hasher: self
                     .hasher
                     .expect("hasher is always initialized via Default or .hasher()"),
                 on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
                 total_capacity: AtomicUsize::new(total_cap),
+                max_pinned: self.max_pinned,
+                pin_lock: parking_lot::Mutex::new(()),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
             }),
         })
     }
@@ -997,11 +1270,11 @@ impl<K, V, H> ShardedLruCacheBuilder<K, V, H> {
         H: ShardHasher<K>,
     {
         let new_cache = self.build()?;
-        for shard in existing.inner.shards.iter() {
+        for shard in existing.inner.shards.table().iter() {
             // iter_order returns MRU-first; insert in reverse (LRU-first)
             // so that the MRU entries are pushed in last and land at the head.
             let entries: Vec<(K, V)> = {
-                let guard = shard.lock.read();
+                let guard = shard.read();
                 guard.iter_order_raw()
             };
             for (k, v) in entries.into_iter().rev() {
@@ -1012,6 +1285,44 @@ impl<K, V, H> ShardedLruCacheBuilder<K, V, H> {
     }
 }
 
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K, V, H> ConcurrentCachedSnapshot<K, V> for ShardedLruCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    /// Each shard's entries are recorded least-recently-used first.
+    fn snapshot(&self) -> CacheSnapshot<K, V> {
+        let clock = SnapshotClock::now();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let entries = shard.read().iter_order_raw();
+                entries
+                    .into_iter()
+                    .rev()
+                    .filter_map(|(k, v)| clock.entry(k, v, None))
+                    .collect()
+            })
+            .collect();
+        CacheSnapshot::from_shards(shards)
+    }
+
+    fn restore(&self, snapshot: CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(SnapshotClock::now()) {
+            let _ = ConcurrentCached::cache_set(self, k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1695,8 +2006,7 @@ mod tests {
         for i in 0..10u32 {
             SyncConcurrentCached::cache_set(&c, i, i).expect("insert must succeed");
         }
-        let before: Vec<u32> = c.inner.shards[0]
-            .lock
+        let before: Vec<u32> = c.inner.shards.table()[0]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -1706,8 +2016,7 @@ mod tests {
 
         c.retain(|k, _| k % 2 == 0);
 
-        let after: Vec<u32> = c.inner.shards[0]
-            .lock
+        let after: Vec<u32> = c.inner.shards.table()[0]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -1740,8 +2049,7 @@ mod tests {
         // Re-read 0 and 2 so the recency chain is not simply insertion order reversed.
         assert_eq!(SyncConcurrentCached::cache_get(&c, &0).unwrap(), Some(0));
         assert_eq!(SyncConcurrentCached::cache_get(&c, &2).unwrap(), Some(2));
-        let expected: Vec<u32> = c.inner.shards[0]
-            .lock
+        let expected: Vec<u32> = c.inner.shards.table()[0]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -1806,14 +2114,12 @@ mod tests {
         for i in 0..10u32 {
             SyncConcurrentCached::cache_set(&c, i, i).expect("insert must succeed");
         }
-        let before = c.inner.shards[0]
-            .lock
+        let before = c.inner.shards.table()[0]
             .read()
             .evictions
             .load(Ordering::Relaxed);
         c.retain(|k, _| k % 2 == 0);
-        let after = c.inner.shards[0]
-            .lock
+        let after = c.inner.shards.table()[0]
             .read()
             .evictions
             .load(Ordering::Relaxed);
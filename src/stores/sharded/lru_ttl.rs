@@ -14,21 +14,28 @@ use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
 use core::future::Future;
 
 use super::{
-    CachePadded, DefaultShardHasher, Shard, ShardHasher, checked_per_shard_cap_from_total,
+    CachePadded, DefaultShardHasher, EvictCursor, Shard, ShardHasher, ShardRead, ShardSet,
+    ShardStats, ShardWrite, checked_per_shard_cap_from_total, checked_reshard_count,
     checked_shard_count, decode_ttl, default_shard_count_for_capacity, encode_ttl,
-    per_shard_cap_from_total, shard_index,
+    per_shard_cap_from_total, pin_shard, pinned_len, reshard_lru,
 };
-use crate::stores::{BuildError, HasEvict, LruCache, NoEvict, TimedEntry};
+use crate::stores::{
+    BuildError, ConcurrentCachedTags, EvictProgress, HasEvict, LruCache, NoEvict, PinError,
+    ReshardError, TagIndex, TimedEntry,
+};
+#[cfg(feature = "persist")]
+use crate::stores::{CacheSnapshot, ConcurrentCachedSnapshot, SnapshotClock};
 use crate::{Cached, CachedIter, CachedPeek};
 
 type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
 
 #[allow(clippy::type_complexity)]
 struct LruTtlInner<K, V, H> {
-    shards: Box<[CachePadded<Shard<LruCache<K, TimedEntry<V>>>>]>,
-    shard_mask: usize,
+    shards: ShardSet<LruCache<K, TimedEntry<V>>>,
     hasher: H,
     on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
     /// TTL in nanoseconds, or `0` to mean expiry is disabled (entries never expire).
     /// A zero stored value is the single sentinel for "no expiry"; there is no separate
     /// `ttl_set` flag. `unset_ttl`/`set_ttl(0)` store `0`; `set_ttl(nonzero)` stores the ttl.
@@ -37,6 +44,16 @@ struct LruTtlInner<K, V, H> {
     /// Total logical capacity (sum of per-shard caps). Stored as `AtomicUsize` so
     /// [`set_max_size`](ShardedLruTtlCache::set_max_size) can update it from `&self`.
     total_capacity: AtomicUsize,
+    /// Store-wide cap on pinned entries; the shards carry none of their own.
+    max_pinned: Option<usize>,
+    /// Serializes pins against `max_pinned`; taken only when a cap is set.
+    pin_lock: parking_lot::Mutex<()>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+    /// Where the next [`evict_budgeted`](ShardedLruTtlCache::evict_budgeted) step starts; the
+    /// position is a slot of the shard's entry list.
+    evict_cursor: parking_lot::Mutex<EvictCursor>,
 }
 
 /// A fully-concurrent, partitioned, LRU-bounded, TTL-expiring in-memory cache.
@@ -124,6 +141,7 @@ impl<K, V, H> ShardedLruTtlCache<K, V, H> {
     fn non_capacity_evictions(&self) -> u64 {
         self.inner
             .shards
+            .table()
             .iter()
             .map(|s| s.evictions.load(Ordering::Relaxed))
             .sum()
@@ -194,9 +212,13 @@ where
     H: ShardHasher<K>,
 {
     #[inline]
-    fn shard_of(&self, k: &K) -> &CachePadded<Shard<LruCache<K, TimedEntry<V>>>> {
-        let h = self.inner.hasher.shard_hash(k);
-        &self.inner.shards[shard_index(h, self.inner.shard_mask)]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, LruCache<K, TimedEntry<V>>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, LruCache<K, TimedEntry<V>>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
     }
 
     #[inline]
@@ -216,6 +238,61 @@ where
             now.checked_add(ttl)
         }
     }
+
+    /// Store `new_entry` for `k`, returning the displaced value only if it was still live at
+    /// `now`. Shared by `cache_set` and snapshot restore, which carries its own deadline.
+    fn set_entry(&self, k: K, new_entry: TimedEntry<V>, now: Instant) -> Option<V> {
+        // Capture the displaced entry and evaluate expiry against the caller's `now` for
+        // this operation (B2: a single sample, taken before the lock, cannot see the entry
+        // cross the expiry threshold part-way through the op). When an `on_evict` callback is
+        // configured we need the *stored* key to hand to it, so the write goes through
+        // `cache_set_returning_entry`; otherwise a plain set. Both promote an overwritten key
+        // to MRU, so the two branches agree on eviction order. The entry count is unchanged,
+        // no capacity eviction is triggered.
+        let (shard, mut guard) = self.write_shard(&k);
+        let old = if self.inner.on_evict.is_some() {
+            guard
+                .cache_set_returning_entry(k, new_entry)
+                .map(|(ok, e)| (Some(ok), e))
+        } else {
+            guard.cache_set(k, new_entry).map(|e| (None, e))
+        };
+        drop(guard);
+        self.settle_displaced(&shard, old, now)
+    }
+
+    /// Report the entry displaced from `shard` by a write at `now`: a live value is returned,
+    /// an expired one is filtered from the return (matching cache_remove and the single-owner
+    /// TTL stores), counted as an eviction and handed to `on_evict` with its stored key.
+    fn settle_displaced(
+        &self,
+        shard: &Shard<LruCache<K, TimedEntry<V>>>,
+        displaced: Option<(Option<K>, TimedEntry<V>)>,
+        now: Instant,
+    ) -> Option<V> {
+        match displaced {
+            Some((key, entry)) if entry.expires_at.is_some_and(|t| now >= t) => {
+                // Count BEFORE notifying: a panicking callback must never leave an
+                // entry removed-but-uncounted.
+                shard.evictions.fetch_add(1, Ordering::Relaxed);
+                if let (Some(on_evict), Some(key)) = (&self.inner.on_evict, &key) {
+                    on_evict(key, &entry.value);
+                }
+                None
+            }
+            Some((_, entry)) => Some(entry.value),
+            None => None,
+        }
+    }
+
+    fn pin_shard(&self, k: &K) -> super::PinShard<'_, LruCache<K, TimedEntry<V>>> {
+        pin_shard(
+            &self.inner.shards,
+            &self.inner.pin_lock,
+            self.inner.max_pinned,
+            self.inner.hasher.shard_hash(k),
+        )
+    }
 }
 
 impl<K: Clone + Hash + Eq, V: Clone, H: ShardHasher<K>> ShardedLruTtlCache<K, V, H> {
@@ -224,40 +301,49 @@ impl<K: Clone + Hash + Eq, V: Clone, H: ShardHasher<K>> ShardedLruTtlCache<K, V,
     /// what you want.
     #[must_use]
     pub fn deep_clone(&self) -> Self {
-        let n = self.inner.shards.len();
-        let shards = (0..n)
-            .map(|i| {
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
                 // Load the hit/miss counters under the read lock so the metrics snapshot is
                 // consistent with the entry snapshot (B4: loading after drop(guard) could yield
                 // counters newer than the cloned entries).
-                let guard = self.inner.shards[i].lock.read();
+                let guard = shard.read();
                 let store_copy = guard.clone();
-                let hits = self.inner.shards[i].hits.load(Ordering::Relaxed);
-                let misses = self.inner.shards[i].misses.load(Ordering::Relaxed);
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
                 // Carry the shard's non-capacity eviction count across too (it used to live
                 // in a single process-wide counter that `deep_clone` copied wholesale), so
                 // the clone's `metrics().evictions` matches the source's.
-                let evictions = self.inner.shards[i].evictions.load(Ordering::Relaxed);
+                let evictions = shard.evictions.load(Ordering::Relaxed);
                 drop(guard);
-                let shard = Shard {
-                    lock: parking_lot::RwLock::new(store_copy),
-                    hits: AtomicU64::new(hits),
-                    misses: AtomicU64::new(misses),
-                    evictions: AtomicU64::new(evictions),
-                };
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
+                shard.evictions.store(evictions, Ordering::Relaxed);
                 CachePadded(shard)
             })
             .collect::<Vec<_>>()
             .into_boxed_slice();
         Self {
             inner: Arc::new(LruTtlInner {
-                shards,
-                shard_mask: self.inner.shard_mask,
+                shards: ShardSet::new(shards),
                 hasher: self.inner.hasher.clone(),
                 on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
                 ttl_nanos: AtomicU64::new(self.inner.ttl_nanos.load(Ordering::Relaxed)),
                 refresh: AtomicBool::new(self.inner.refresh.load(Ordering::Relaxed)),
                 total_capacity: AtomicUsize::new(self.inner.total_capacity.load(Ordering::Relaxed)),
+                max_pinned: self.inner.max_pinned,
+                pin_lock: parking_lot::Mutex::new(()),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                deep_size: self.inner.deep_size,
+                evict_cursor: parking_lot::Mutex::default(),
             }),
         }
     }
@@ -289,6 +375,39 @@ where
         ConcurrentCached::cache_set(self, k, v).unwrap()
     }
 
+    /// Insert a key-value pair that expires `ttl` from now instead of after the cache's TTL,
+    /// returning the previous value only if it had not yet expired. A zero `ttl` stores an
+    /// entry that never expires. The entry can evict its shard's least recently used entry,
+    /// exactly like [`set`](Self::set).
+    ///
+    /// The override sets the entry's first deadline only: with
+    /// [`refresh_on_hit`](ShardedLruTtlCacheBuilder::refresh_on_hit) a hit re-arms it with the
+    /// cache's TTL, as it does every other entry.
+    pub fn set_with_ttl(&self, k: K, v: V, ttl: Duration) -> Option<V> {
+        let now = Instant::now();
+        let expires_at = if ttl.is_zero() {
+            None
+        } else {
+            now.checked_add(ttl)
+        };
+        let new_entry = TimedEntry {
+            expires_at,
+            value: v,
+        };
+        self.set_entry(k, new_entry, now)
+    }
+
+    /// Insert a key-value pair that expires at `deadline`, returning the previous value only
+    /// if it had not yet expired. A `deadline` that has already passed stores an entry that
+    /// is already expired. Otherwise behaves as [`set_with_ttl`](Self::set_with_ttl).
+    pub fn set_until(&self, k: K, v: V, deadline: Instant) -> Option<V> {
+        let new_entry = TimedEntry {
+            expires_at: Some(deadline),
+            value: v,
+        };
+        self.set_entry(k, new_entry, Instant::now())
+    }
+
     /// Return the cached value for `k`, or compute `f()`, store it, and return it.
     ///
     /// Infallible ergonomic API for the concrete type. As an inherent method it takes
@@ -346,13 +465,84 @@ where
     #[must_use]
     pub fn peek(&self, k: &K) -> Option<V> {
         use crate::CachedPeek;
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         guard
             .cache_peek(k)
             .filter(|entry| entry.expires_at.is_none_or(|t| Instant::now() < t))
             .map(|entry| entry.value.clone())
     }
+
+    /// Pin the live entry for `k` so LRU eviction never selects it, returning `Ok(false)`
+    /// when `k` is absent or expired. A pinned entry does not count toward its shard's
+    /// capacity and keeps its pin across a [`reshard`](Self::reshard), but it still expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`] when the entry is not pinned yet and the store
+    /// already holds [`max_pinned`](ShardedLruTtlCacheBuilder::max_pinned) pinned entries.
+    pub fn pin(&self, k: &K) -> Result<bool, PinError> {
+        let now = Instant::now();
+        let mut pin = self.pin_shard(k);
+        let hash = pin.store.hash(k);
+        let Some(index) = pin.store.get_index(hash, k) else {
+            return Ok(false);
+        };
+        if pin
+            .store
+            .order
+            .get(index)
+            .1
+            .expires_at
+            .is_some_and(|t| now >= t)
+        {
+            return Ok(false);
+        }
+        pin.check_room(pin.store.is_pinned_index(index))?;
+        pin.store.pin_index(index)?;
+        Ok(true)
+    }
+
+    /// Unpin the entry for `k`, returning whether it was pinned. The entry becomes its
+    /// shard's most recently used one and may evict that shard's least recently used entry.
+    pub fn unpin(&self, k: &K) -> bool {
+        self.write_shard(k).1.unpin(k)
+    }
+
+    /// Insert or replace the entry for `k` with the cache's TTL and pin it, returning the
+    /// previous value only if it had not yet expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PinError::LimitReached`] when `k` is not pinned yet and the store already
+    /// holds [`max_pinned`](ShardedLruTtlCacheBuilder::max_pinned) pinned entries. Nothing is
+    /// stored.
+    pub fn set_pinned(&self, k: K, v: V) -> Result<Option<V>, PinError> {
+        let now = Instant::now();
+        let new_entry = TimedEntry {
+            expires_at: self.compute_expires_at(now),
+            value: v,
+        };
+        let (shard, displaced) = {
+            let mut pin = self.pin_shard(&k);
+            pin.check_room(pin.store.is_pinned(&k))?;
+            let displaced = pin.store.set_pinned_returning_entry(k, new_entry)?;
+            (pin.shard, displaced)
+        };
+        let displaced = displaced.map(|(key, entry)| (Some(key), entry));
+        Ok(self.settle_displaced(&shard, displaced, now))
+    }
+
+    /// Whether the entry for `k` is pinned, expired or not.
+    #[must_use]
+    pub fn is_pinned(&self, k: &K) -> bool {
+        self.read_shard(k).1.is_pinned(k)
+    }
+
+    /// Number of pinned entries across all shards, including expired ones not yet removed.
+    #[must_use]
+    pub fn pinned_len(&self) -> usize {
+        pinned_len(&self.inner.shards)
+    }
 }
 
 impl<K, V, H: ShardHasher<K>> ShardedLruTtlCache<K, V, H>
@@ -374,14 +564,14 @@ where
         let mut lru_evictions = 0u64;
         let mut non_capacity_evictions = 0u64;
         let mut size = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             hits += shard.hits.load(Ordering::Relaxed);
             misses += shard.misses.load(Ordering::Relaxed);
             // Per-shard non-capacity evictions (expiry / removes / retain / clear); the
             // inner `LruCache` counter below holds this shard's capacity evictions. The two
             // families are disjoint, so summing them cannot double-count.
             non_capacity_evictions += shard.evictions.load(Ordering::Relaxed);
-            let guard = shard.lock.read();
+            let guard = shard.read();
             if let Some(e) = guard.cache_evictions() {
                 lru_evictions += e;
             }
@@ -395,6 +585,7 @@ where
             // Acquire, like `capacity()`: a caller that just resized on this thread sees
             // the new total here too, not a stale value alongside a fresh `capacity()`.
             capacity: Some(self.inner.total_capacity.load(Ordering::Acquire)),
+            mem_bytes: Some(self.shard_memory_usage().into_iter().sum()),
         }
     }
 
@@ -409,18 +600,86 @@ where
     pub fn shard_sizes(&self) -> Vec<usize> {
         self.inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().cache_size())
+            .map(|s| s.read().cache_size())
             .collect()
     }
 
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage). A shard whose share
+    /// of the bytes runs well ahead of its share of the entries holds unusually large values.
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        let deep_size = self.inner.deep_size;
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let store = shard.read();
+                let entries = deep_size.map_or(0, |heap| {
+                    store.entries_heap_size(|k, e| heap.entry(k, &e.value))
+                });
+                size_of_val(shard) + store.allocated_bytes() + entries
+            })
+            .collect()
+    }
+
+    /// Per-shard entry counts (expired-but-unswept entries included), hit/miss counters and
+    /// lock-wait figures; see [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(|store| store.cache_size())
+    }
+
+    /// Migrate every entry into a new array of `shards` shards (rounded up to a power of two)
+    /// while the cache keeps serving requests.
+    ///
+    /// As on [`ShardedLruCache::reshard`](crate::ShardedLruCache::reshard), the total
+    /// capacity is re-split across the new shards and each old shard's entries are
+    /// re-inserted least recently used first. Entries keep the `expires_at` they were
+    /// written (or last refreshed) with, so a move never extends or shortens a TTL. Hit, miss
+    /// and eviction counts carry over. A no-op when the cache already has that many shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReshardError::ZeroShards`] or [`ReshardError::ShardCountOverflow`] if
+    /// `shards` is zero or overflows when rounded up, and [`ReshardError::CapacityOverflow`]
+    /// if the re-split capacity overflows `usize`. Returns [`ReshardError::Reentrant`],
+    /// rather than deadlocking, when called from a callback (such as `on_evict`) that a
+    /// whole-cache call or reshard on this same cache is running.
+    pub fn reshard(&self, shards: usize) -> Result<(), ReshardError>
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+    {
+        let n = checked_reshard_count(shards)?;
+        // The shards' callback adapts the store's `on_evict` to `TimedEntry`; every shard
+        // holds the same one, so the new shards take it from an existing shard.
+        let lru_on_evict = self.inner.shards.table()[0].read().on_evict.clone();
+        reshard_lru(
+            &self.inner.shards,
+            &self.inner.hasher,
+            &self.inner.total_capacity,
+            n,
+            |cap| {
+                let mut lru = LruCache::new(cap);
+                lru.on_evict = lru_on_evict.clone();
+                lru.disable_hit_miss_tracking();
+                lru
+            },
+        )
+    }
+
     /// Total number of entries across all shards (including not-yet-swept expired entries).
     #[must_use]
     pub fn len(&self) -> usize {
         self.inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().cache_size())
+            .map(|s| s.read().cache_size())
             .sum()
     }
 
@@ -429,15 +688,16 @@ where
     pub fn is_empty(&self) -> bool {
         self.inner
             .shards
+            .table()
             .iter()
-            .all(|s| s.lock.read().cache_size() == 0)
+            .all(|s| s.read().cache_size() == 0)
     }
 
     /// Remove all entries from every shard. Does **not** fire `on_evict`.
     /// Use [`cache_clear_with_on_evict`](Self::cache_clear_with_on_evict) to opt into callback firing.
     pub fn clear(&self) {
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().cache_clear();
+        for shard in self.inner.shards.table().iter() {
+            shard.write().cache_clear();
         }
     }
 
@@ -448,9 +708,9 @@ where
     /// (`metrics().evictions`) whether or not an `on_evict` callback is configured; the callback
     /// fires only when one is set.
     pub fn cache_clear_with_on_evict(&self) {
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed: Vec<(K, TimedEntry<V>)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 // `drain_all` walks each shard's LRU chain once taking owned pairs in
                 // MRU -> LRU order -- the same order the old "clone every key, then
                 // `pop_raw` each one" drain fired in, but with zero key clones and zero
@@ -493,9 +753,9 @@ where
     pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
         let now = Instant::now();
         let mut total_removed = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed: Vec<(K, TimedEntry<V>)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 let doomed: Vec<K> = guard
                     .iter()
                     .filter_map(|(k, entry)| {
@@ -601,10 +861,10 @@ where
     /// are the parallel methods on the other sharded LRU-bounded stores.
     pub fn set_max_size(&self, max_size: usize) -> Option<usize> {
         assert!(max_size > 0, "max_size must be greater than zero");
-        let n_shards = self.inner.shards.len();
-        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, n_shards);
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().set_max_size(per_shard_cap);
+        let table = self.inner.shards.table();
+        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, table.len());
+        for shard in table.iter() {
+            shard.write().set_max_size(per_shard_cap);
         }
         // Publish the new total only after every shard has adopted its new cap;
         // Release pairs with the Acquire load in `capacity()`.
@@ -639,9 +899,9 @@ where
     pub fn evict(&self) -> usize {
         let mut total = 0;
         let now = Instant::now();
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             let removed = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 let expired: Vec<K> = guard
                     .iter()
                     // An entry is expired when expires_at is Some(t) and now >= t.
@@ -674,6 +934,46 @@ where
         }
         total
     }
+
+    /// Take one step of an incremental [`evict`](Self::evict): examine at most `max_entries`
+    /// entries from where the last step stopped, remove the expired ones, and report how
+    /// many were removed and whether the sweep got past the last shard.
+    ///
+    /// Within a shard, steps walk the entries in storage order rather than recency order, so
+    /// a hit mid-sweep does not move an entry past the cursor. See
+    /// [`ConcurrentCacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        let now = Instant::now();
+        super::evict_budgeted(
+            &self.inner.shards,
+            &self.inner.evict_cursor,
+            max_entries,
+            |store, position, budget| {
+                let (doomed, visited, next) = store.doomed_slots(position, budget, |_, e| {
+                    e.expires_at.is_some_and(|t| now >= t)
+                });
+                let removed: Vec<(K, TimedEntry<V>)> = doomed
+                    .into_iter()
+                    .map(|index| store.remove_index(index))
+                    .collect();
+                (removed, visited, next)
+            },
+            |shard, removed| {
+                if removed.is_empty() {
+                    return;
+                }
+                shard
+                    .evictions
+                    .fetch_add(removed.len() as u64, Ordering::Relaxed);
+                if let Some(cb) = &self.inner.on_evict {
+                    for (k, entry) in &removed {
+                        cb(k, &entry.value);
+                    }
+                }
+            },
+        )
+    }
 }
 
 impl<K, V, H> ConcurrentCacheEvict for ShardedLruTtlCache<K, V, H>
@@ -684,6 +984,10 @@ where
     fn evict(&self) -> usize {
         ShardedLruTtlCache::evict(self)
     }
+
+    fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        ShardedLruTtlCache::evict_budgeted(self, max_entries)
+    }
 }
 
 impl<K, V, H> ConcurrentCacheBase for ShardedLruTtlCache<K, V, H>
@@ -698,10 +1002,15 @@ where
         Ok(Some(self.len()))
     }
 
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum())
+    }
+
     fn cache_hits(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.hits.load(Ordering::Relaxed))
                 .sum(),
@@ -712,12 +1021,18 @@ where
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.misses.load(Ordering::Relaxed))
                 .sum(),
         )
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
+
     fn cache_capacity(&self) -> Option<usize> {
         // Acquire: see `capacity()`.
         Some(self.inner.total_capacity.load(Ordering::Acquire))
@@ -725,8 +1040,8 @@ where
 
     fn cache_evictions(&self) -> Option<u64> {
         let mut lru_evictions = 0u64;
-        for shard in self.inner.shards.iter() {
-            let guard = shard.lock.read();
+        for shard in self.inner.shards.table().iter() {
+            let guard = shard.read();
             if let Some(e) = Cached::cache_evictions(&*guard) {
                 lru_evictions += e;
             }
@@ -781,14 +1096,17 @@ where
     H: ShardHasher<K>,
 {
     fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
+        if let Some(hot) = &self.inner.hot_keys {
+            let hash = self.inner.hasher.shard_hash(k);
+            hot.record(hash, || Some(k));
+        }
         let refresh = self.inner.refresh.load(Ordering::Relaxed);
         // One clock sample per operation, taken before the lock: it decides expiry and (when
         // refreshing) seeds the new `expires_at`, so the critical section contains no
         // `Instant::now()` syscall at all.
         let now = Instant::now();
 
-        let mut guard = shard.lock.write();
+        let (shard, mut guard) = self.write_shard(k);
 
         // The common case (a live hit) resolves in a SINGLE hash + probe:
         // `get_if`/`get_mut_if` promote LRU recency only when the predicate reports the entry
@@ -834,54 +1152,19 @@ where
     }
 
     fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(&k);
         let now = Instant::now();
         let expires_at = self.compute_expires_at(now);
         let new_entry = TimedEntry {
             expires_at,
             value: v,
         };
-        // Capture the displaced entry and evaluate expiry against the `now` sampled above for
-        // this operation (B2: a single sample, taken before the lock, cannot see the entry
-        // cross the expiry threshold part-way through the op). When an `on_evict` callback is
-        // configured we need the *stored* key to hand to it, so the write goes through
-        // `cache_set_returning_entry`; otherwise a plain set. Both promote an overwritten key
-        // to MRU, so the two branches agree on eviction order. The entry count is unchanged,
-        // no capacity eviction is triggered.
-        let old: Option<(Option<K>, TimedEntry<V>, bool)> = if self.inner.on_evict.is_some() {
-            let mut guard = shard.lock.write();
-            guard
-                .cache_set_returning_entry(k, new_entry)
-                .map(|(ok, e)| {
-                    let expired = e.expires_at.is_some_and(|t| now >= t);
-                    (Some(ok), e, expired)
-                })
-        } else {
-            shard.lock.write().cache_set(k, new_entry).map(|e| {
-                let expired = e.expires_at.is_some_and(|t| now >= t);
-                (None, e, expired)
-            })
-        };
-        match old {
-            // A displaced expired value is filtered from the return (matching cache_remove and
-            // the single-owner TTL stores); fire on_evict and count an eviction for it.
-            Some((key, entry, true)) => {
-                // Count BEFORE notifying: a panicking callback must never leave an
-                // entry removed-but-uncounted.
-                shard.evictions.fetch_add(1, Ordering::Relaxed);
-                if let (Some(on_evict), Some(key)) = (&self.inner.on_evict, &key) {
-                    on_evict(key, &entry.value);
-                }
-                Ok(None)
-            }
-            Some((_, entry, false)) => Ok(Some(entry.value)),
-            None => Ok(None),
-        }
+        Ok(self.set_entry(k, new_entry, now))
     }
 
     fn cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
-        let removed = shard.lock.write().pop_raw(k);
+        let (shard, mut guard) = self.write_shard(k);
+        let removed = guard.pop_raw(k);
+        drop(guard);
         if let Some((key, entry)) = removed {
             shard.evictions.fetch_add(1, Ordering::Relaxed);
             if let Some(on_evict) = &self.inner.on_evict {
@@ -898,8 +1181,9 @@ where
     }
 
     fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
-        let shard = self.shard_of(k);
-        let removed = shard.lock.write().pop_raw(k);
+        let (shard, mut guard) = self.write_shard(k);
+        let removed = guard.pop_raw(k);
+        drop(guard);
         if let Some((ref stored_k, ref entry)) = removed {
             shard.evictions.fetch_add(1, Ordering::Relaxed);
             if let Some(on_evict) = &self.inner.on_evict {
@@ -920,29 +1204,66 @@ where
     }
 
     fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
-        for shard in self.inner.shards.iter() {
-            shard.hits.store(0, Ordering::Relaxed);
-            shard.misses.store(0, Ordering::Relaxed);
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
             // The shard's non-capacity eviction counter (expiry / removes / retain / clear).
             shard.evictions.store(0, Ordering::Relaxed);
             // Zero the per-shard inner store's metrics, including its LRU capacity-eviction counter.
-            shard.lock.write().cache_reset_metrics();
+            shard.write().cache_reset_metrics();
+        }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
         }
         Ok(())
     }
 
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
     /// Efficient peek-based contains: acquires a read lock, does not clone the value, does not
     /// update LRU recency, and does not record hit/miss metrics. Returns `true` only for live
     /// (not expired) entries.
     fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         Ok(guard
             .cache_peek(k)
             .is_some_and(|entry| entry.expires_at.is_none_or(|t| Instant::now() < t)))
     }
 }
 
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedLruTtlCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| matches!(ConcurrentCached::cache_contains(self, k), Ok(true)));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
 impl<K, V, H> ConcurrentCachePeek<K, V> for ShardedLruTtlCache<K, V, H>
 where
     K: Hash + Eq + Clone,
@@ -1035,11 +1356,15 @@ pub struct ShardedLruTtlCacheBuilder<K, V, H = DefaultShardHasher, E = NoEvict>
     shards: Option<usize>,
     max_size: Option<usize>,
     per_shard_max_size: Option<usize>,
+    max_pinned: Option<usize>,
     ttl: Option<Duration>,
     refresh: bool,
     hasher: Option<H>,
     on_evict: Option<OnEvict<K, V>>,
     _evict: PhantomData<E>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for ShardedLruTtlCacheBuilder<K, V> {
@@ -1048,11 +1373,15 @@ impl<K, V> Default for ShardedLruTtlCacheBuilder<K, V> {
             shards: None,
             max_size: None,
             per_shard_max_size: None,
+            max_pinned: None,
             ttl: None,
             refresh: false,
             hasher: Some(DefaultShardHasher::default()),
             on_evict: None,
             _evict: PhantomData,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -1140,6 +1469,14 @@ impl<K, V, H, E> ShardedLruTtlCacheBuilder<K, V, H, E> {
         self
     }
 
+    /// Cap the number of pinned entries across all shards. Pinned entries do not count
+    /// toward any shard's capacity; see [`ShardedLruTtlCache::pin`].
+    #[must_use]
+    pub fn max_pinned(mut self, max_pinned: usize) -> Self {
+        self.max_pinned = Some(max_pinned);
+        self
+    }
+
     /// Set whether cache hits refresh the TTL.
     #[must_use]
     pub fn refresh_on_hit(mut self, refresh: bool) -> Self {
@@ -1162,14 +1499,65 @@ impl<K, V, H, E> ShardedLruTtlCacheBuilder<K, V, H, E> {
             shards: self.shards,
             max_size: self.max_size,
             per_shard_max_size: self.per_shard_max_size,
+            max_pinned: self.max_pinned,
             ttl: self.ttl,
             refresh: self.refresh,
             hasher: Some(hasher),
             on_evict: self.on_evict,
             _evict: PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key. Read them with
+    /// [`top_keys`](crate::ConcurrentCachedExt::top_keys), which merges the shards' summaries.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](crate::stores::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::ConcurrentCachedExt::memory_usage) and `shard_memory_usage`,
+    /// measured through their [`DeepSize`](crate::DeepSize) impls. Each estimate then visits
+    /// every entry under its shard's read lock.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
     fn resolve_per_shard_cap(&self, n_shards: usize) -> Result<usize, BuildError> {
         match (self.max_size, self.per_shard_max_size) {
             (Some(_), Some(_)) => Err(BuildError::InvalidValue {
@@ -1240,6 +1628,7 @@ impl<K, V, H, E> ShardedLruTtlCacheBuilder<K, V, H, E> {
     fn validated_parts(&self) -> Result<(Duration, usize, usize, usize), BuildError> {
         let ttl = self.ttl.ok_or(BuildError::MissingRequired("ttl"))?;
         crate::stores::validate_ttl(ttl)?;
+        crate::stores::check_max_pinned(self.max_pinned)?;
         let n = self.resolve_shard_count()?;
         let mask = n - 1;
         let per_shard_cap = self.resolve_per_shard_cap(n)?;
@@ -1279,11 +1668,15 @@ impl<K, V, H> ShardedLruTtlCacheBuilder<K, V, H, NoEvict> {
             shards: self.shards,
             max_size: self.max_size,
             per_shard_max_size: self.per_shard_max_size,
+            max_pinned: self.max_pinned,
             ttl: self.ttl,
             refresh: self.refresh,
             hasher: self.hasher,
             on_evict: Some(Arc::new(on_evict)),
             _evict: PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
@@ -1294,8 +1687,9 @@ impl<K, V, H> ShardedLruTtlCacheBuilder<K, V, H, NoEvict> {
     ///
     /// # Errors
     ///
-    /// Returns [`BuildError`] if `size` (or `per_shard_max_size`) or `ttl` was not set, is `0`,
-    /// or if both `max_size` and `per_shard_max_size` are set simultaneously. May also return
+    /// Returns [`BuildError`] if `size` (or `per_shard_max_size`) or `ttl` was not set or is
+    /// `0`, if `max_pinned` is `0`, or if both `max_size` and `per_shard_max_size` are set
+    /// simultaneously. May also return
     /// [`BuildError::InvalidValue`] if the effective sharded capacity overflows `usize` or a
     /// per-shard allocation fails.
     #[must_use = "the Result from build() must be used"]
@@ -1319,15 +1713,21 @@ impl<K, V, H> ShardedLruTtlCacheBuilder<K, V, H, NoEvict> {
 
         Ok(ShardedLruTtlCache {
             inner: Arc::new(LruTtlInner {
-                shards,
-                shard_mask: mask,
+                shards: ShardSet::new(shards),
                 hasher: self
                     .hasher
                     .expect("hasher is always initialized via Default or .hasher()"),
                 on_evict: None,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
                 ttl_nanos: AtomicU64::new(encode_ttl(ttl)),
                 refresh: AtomicBool::new(self.refresh),
                 total_capacity: AtomicUsize::new(total_cap),
+                max_pinned: self.max_pinned,
+                pin_lock: parking_lot::Mutex::new(()),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
+                evict_cursor: parking_lot::Mutex::default(),
             }),
         })
     }
@@ -1376,8 +1776,9 @@ impl<K, V, H> ShardedLruTtlCacheBuilder<K, V, H, HasEvict> {
     ///
     /// # Errors
     ///
-    /// Returns [`BuildError`] if `size` (or `per_shard_max_size`) or `ttl` was not set, is `0`,
-    /// or if both `max_size` and `per_shard_max_size` are set simultaneously. May also return
+    /// Returns [`BuildError`] if `size` (or `per_shard_max_size`) or `ttl` was not set or is
+    /// `0`, if `max_pinned` is `0`, or if both `max_size` and `per_shard_max_size` are set
+    /// simultaneously. May also return
     /// [`BuildError::InvalidValue`] if the effective sharded capacity overflows `usize` or a
     /// per-shard allocation fails.
     #[must_use = "the Result from build() must be used"]
@@ -1412,15 +1813,21 @@ impl<K, V, H> ShardedLruTtlCacheBuilder<K, V, H, HasEvict> {
 
         Ok(ShardedLruTtlCache {
             inner: Arc::new(LruTtlInner {
-                shards,
-                shard_mask: mask,
+                shards: ShardSet::new(shards),
                 hasher: self
                     .hasher
                     .expect("hasher is always initialized via Default or .hasher()"),
                 on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
                 ttl_nanos: AtomicU64::new(encode_ttl(ttl)),
                 refresh: AtomicBool::new(self.refresh),
                 total_capacity: AtomicUsize::new(total_cap),
+                max_pinned: self.max_pinned,
+                pin_lock: parking_lot::Mutex::new(()),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
+                evict_cursor: parking_lot::Mutex::default(),
             }),
         })
     }
@@ -1472,9 +1879,9 @@ where
     H2: ShardHasher<K>,
 {
     let now = Instant::now();
-    for shard in existing.inner.shards.iter() {
+    for shard in existing.inner.shards.table().iter() {
         let entries: Vec<(K, TimedEntry<V>)> = {
-            let guard = shard.lock.read();
+            let guard = shard.read();
             guard.iter_order_raw()
         };
         for (k, entry) in entries.into_iter().rev() {
@@ -1482,8 +1889,7 @@ where
             if entry.expires_at.is_some_and(|t| now >= t) {
                 continue;
             }
-            let new_shard = new_cache.shard_of(&k);
-            new_shard.lock.write().cache_set(k, entry);
+            new_cache.write_shard(&k).1.cache_set(k, entry);
         }
     }
     new_cache
@@ -1499,11 +1905,10 @@ where
     /// expired entry (miss, **no removal**, no LRU promotion, no eviction counter), or
     /// `(None, false)` when absent (miss).
     fn cache_get_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
         let refresh = self.inner.refresh.load(Ordering::Relaxed);
         // One clock sample per operation, taken before the lock (see `cache_get`).
         let now = Instant::now();
-        let mut guard = shard.lock.write();
+        let (shard, mut guard) = self.write_shard(k);
         // Common case (live hit) in a single lookup: `get_if`/`get_mut_if` promote LRU
         // recency only when the predicate reports the entry live, and leave it in place
         // (no removal, no promotion) when it reports expired. The rarer expired/absent
@@ -1541,8 +1946,7 @@ where
     /// Returns `(Some(v), expired)` for a present entry (expired or not) or `(None, false)` when
     /// absent.
     fn cache_peek_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         match guard.cache_peek(k) {
             None => (None, false),
             Some(entry) => {
@@ -1553,6 +1957,46 @@ where
     }
 }
 
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K, V, H> ConcurrentCachedSnapshot<K, V> for ShardedLruTtlCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    /// Each shard's entries are recorded least-recently-used first.
+    fn snapshot(&self) -> CacheSnapshot<K, V> {
+        let clock = SnapshotClock::now();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let entries = shard.read().iter_order_raw();
+                entries
+                    .into_iter()
+                    .rev()
+                    .filter_map(|(k, e)| clock.entry(k, e.value, e.expires_at))
+                    .collect()
+            })
+            .collect();
+        CacheSnapshot::from_shards(shards)
+    }
+
+    /// Restored entries keep their recorded deadline rather than starting a fresh TTL.
+    fn restore(&self, snapshot: CacheSnapshot<K, V>) -> usize {
+        let clock = SnapshotClock::now();
+        let mut restored = 0;
+        for (key, value, expires_at) in snapshot.into_live(clock) {
+            let _ = self.set_entry(key, TimedEntry { expires_at, value }, clock.instant());
+            restored += 1;
+        }
+        restored
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2713,8 +3157,7 @@ mod tests {
         for i in 0..10u32 {
             SyncConcurrentCached::cache_set(&c, i, i).expect("insert must succeed");
         }
-        let before: Vec<u32> = c.inner.shards[0]
-            .lock
+        let before: Vec<u32> = c.inner.shards.table()[0]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -2724,8 +3167,7 @@ mod tests {
 
         c.retain(|k, _| k % 2 == 0);
 
-        let after: Vec<u32> = c.inner.shards[0]
-            .lock
+        let after: Vec<u32> = c.inner.shards.table()[0]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -2752,8 +3194,7 @@ mod tests {
         for i in 0..10u32 {
             SyncConcurrentCached::cache_set(&c, i, i).expect("insert must succeed");
         }
-        let inner_before = c.inner.shards[0]
-            .lock
+        let inner_before = c.inner.shards.table()[0]
             .read()
             .evictions
             .load(Ordering::Relaxed);
@@ -2761,8 +3202,7 @@ mod tests {
 
         c.retain(|k, _| k % 2 == 0);
 
-        let inner_after = c.inner.shards[0]
-            .lock
+        let inner_after = c.inner.shards.table()[0]
             .read()
             .evictions
             .load(Ordering::Relaxed);
@@ -2806,8 +3246,7 @@ mod tests {
 
         // evict() path: pin key 1's expiry to "now".
         {
-            let shard = c.shard_of(&1);
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = c.write_shard(&1);
             let entry = guard.get_mut_if(&1, |_| true).expect("key 1 stored");
             entry.expires_at = Some(Instant::now());
         }
@@ -2821,8 +3260,7 @@ mod tests {
 
         // retain() path: symmetric case with the surviving entry pinned the same way.
         {
-            let shard = c.shard_of(&2);
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = c.write_shard(&2);
             let entry = guard.get_mut_if(&2, |_| true).expect("key 2 stored");
             entry.expires_at = Some(Instant::now());
         }
@@ -2841,6 +3279,7 @@ mod tests {
     fn shard_eviction_counters<K, V, H>(c: &ShardedLruTtlCache<K, V, H>) -> Vec<u64> {
         c.inner
             .shards
+            .table()
             .iter()
             .map(|s| s.evictions.load(Ordering::Relaxed))
             .collect()
@@ -2848,7 +3287,7 @@ mod tests {
 
     /// Index of the shard that owns `k`.
     fn owning_shard<K, V, H: ShardHasher<K>>(c: &ShardedLruTtlCache<K, V, H>, k: &K) -> usize {
-        shard_index(c.inner.hasher.shard_hash(k), c.inner.shard_mask)
+        crate::stores::sharded::shard_index(c.inner.hasher.shard_hash(k), c.inner.shards.len() - 1)
     }
 
     /// Keys of one shard in MRU -> LRU order.
@@ -2856,8 +3295,7 @@ mod tests {
         c: &ShardedLruTtlCache<K, V, H>,
         shard: usize,
     ) -> Vec<K> {
-        c.inner.shards[shard]
-            .lock
+        c.inner.shards.table()[shard]
             .read()
             .iter_order_raw()
             .into_iter()
@@ -1,7 +1,15 @@
+use std::cell::RefCell;
 use std::sync::OnceLock;
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
-use crate::stores::{BuildError, SetMaxSizeError};
+use crossbeam_epoch::{self as epoch, Atomic, Owned};
+use parking_lot::{RwLockReadGuard, RwLockWriteGuard};
+
+use std::hash::Hash;
+use std::sync::atomic::AtomicUsize;
+
+use crate::Cached;
+use crate::stores::{BuildError, LruCache, PinError, ReshardError, SetMaxSizeError};
 
 /// Cache-line size used for padding. Covers both x86_64 (64 B + Intel adjacent-line prefetch)
 /// and Apple Silicon (128 B L1 line). Matches the `repr(align)` on `CachePadded`.
@@ -54,6 +62,15 @@ pub(crate) struct Shard<S> {
     /// intentionally left unused. Keeping one shared field (rather than a type-level split
     /// of `Shard`) is a deliberate simplification.
     pub evictions: AtomicU64,
+    /// Acquisitions through [`read`](Self::read) / [`write`](Self::write) that found the lock
+    /// held and had to block, and the nanoseconds they spent blocked. An uncontended
+    /// acquisition takes the `try_` fast path and never reads the clock.
+    pub lock_waits: AtomicU64,
+    pub lock_wait_nanos: AtomicU64,
+    /// Set (under the write lock) once a reshard has drained this shard into the table's
+    /// successor. A per-key operation that locks a moved shard drops the guard and follows
+    /// [`ShardTable::next`] instead; see [`ShardSet`].
+    pub moved: AtomicBool,
 }
 
 impl<S> Shard<S> {
@@ -63,6 +80,641 @@ impl<S> Shard<S> {
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
             evictions: AtomicU64::new(0),
+            lock_waits: AtomicU64::new(0),
+            lock_wait_nanos: AtomicU64::new(0),
+            moved: AtomicBool::new(false),
+        }
+    }
+
+    /// Shared-lock the shard, counting the wait if the lock was contended.
+    pub fn read(&self) -> RwLockReadGuard<'_, S> {
+        if let Some(guard) = self.lock.try_read() {
+            return guard;
+        }
+        let start = crate::time::Instant::now();
+        let guard = self.lock.read();
+        self.record_wait(start);
+        guard
+    }
+
+    /// Exclusively lock the shard, counting the wait if the lock was contended.
+    pub fn write(&self) -> RwLockWriteGuard<'_, S> {
+        if let Some(guard) = self.lock.try_write() {
+            return guard;
+        }
+        let start = crate::time::Instant::now();
+        let guard = self.lock.write();
+        self.record_wait(start);
+        guard
+    }
+
+    fn record_wait(&self, start: crate::time::Instant) {
+        let waited = start.elapsed().as_nanos().min(u64::MAX as u128) as u64;
+        self.lock_waits.fetch_add(1, Ordering::Relaxed);
+        self.lock_wait_nanos.fetch_add(waited, Ordering::Relaxed);
+    }
+
+    /// Zero the hit/miss and lock-wait counters, as `cache_reset_metrics` does.
+    pub fn reset_counters(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.lock_waits.store(0, Ordering::Relaxed);
+        self.lock_wait_nanos.store(0, Ordering::Relaxed);
+    }
+
+    /// Add `other`'s counters to this shard's, for a reshard folding a drained shard into its
+    /// successor so the store-wide totals survive the move.
+    fn absorb_counters(&self, other: &Self) {
+        for (into, from) in [
+            (&self.hits, &other.hits),
+            (&self.misses, &other.misses),
+            (&self.evictions, &other.evictions),
+            (&self.lock_waits, &other.lock_waits),
+            (&self.lock_wait_nanos, &other.lock_wait_nanos),
+        ] {
+            into.fetch_add(from.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+        }
+    }
+
+    /// This shard's [`ShardStats`], with `entries` supplied by the store.
+    pub fn stats(&self, entries: usize) -> ShardStats {
+        ShardStats {
+            entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            lock_waits: self.lock_waits.load(Ordering::Relaxed),
+            lock_wait_time: crate::time::Duration::from_nanos(
+                self.lock_wait_nanos.load(Ordering::Relaxed),
+            ),
+        }
+    }
+}
+
+/// One shard's counters, as returned by `shard_stats()` on the sharded stores.
+///
+/// `hits`, `misses` and the lock-wait figures count from the store's build or its last
+/// `cache_reset_metrics`. A lock wait is an acquisition of the shard's lock that found it held
+/// and had to block; uncontended acquisitions are not counted, so a shard with many waits or a
+/// long `lock_wait_time` is one whose keys are hot enough to serialize callers. After a
+/// `reshard` each new shard carries the counters of the old shards that folded into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct ShardStats {
+    /// Entries currently held by the shard, counted as the store's `shard_sizes` counts them.
+    pub entries: usize,
+    /// Lookups that found a live entry.
+    pub hits: u64,
+    /// Lookups that found nothing or an expired entry.
+    pub misses: u64,
+    /// Lock acquisitions that had to wait for another holder.
+    pub lock_waits: u64,
+    /// Total time spent in those waits.
+    pub lock_wait_time: crate::time::Duration,
+}
+
+/// One generation of a sharded store's shards: the padded shard array and its index mask.
+pub(crate) struct ShardTable<S> {
+    shards: Box<[CachePadded<Shard<S>>]>,
+    mask: usize,
+    /// The table a running (or finished) reshard migrates this one into. Set before the
+    /// first shard is marked [`moved`](Shard::moved), and never changed afterward. Not owned:
+    /// the successor is freed as the [`ShardSet`]'s current table or by the reshard that
+    /// replaces it in turn.
+    next: Atomic<ShardTable<S>>,
+}
+
+impl<S> ShardTable<S> {
+    fn new(shards: Box<[CachePadded<Shard<S>>]>) -> Self {
+        debug_assert!(shards.len().is_power_of_two());
+        Self {
+            mask: shards.len() - 1,
+            shards,
+            next: Atomic::null(),
+        }
+    }
+
+    /// The shard `hash` routes to in this table.
+    #[inline]
+    pub fn shard(&self, hash: u64) -> &CachePadded<Shard<S>> {
+        &self.shards[shard_index(hash, self.mask)]
+    }
+}
+
+impl<S> std::ops::Deref for ShardTable<S> {
+    type Target = [CachePadded<Shard<S>>];
+    fn deref(&self) -> &Self::Target {
+        &self.shards
+    }
+}
+
+/// A located shard together with its held read lock.
+pub(crate) type ShardRead<'a, S> = (ShardRef<'a, S>, ShardGuard<RwLockReadGuard<'a, S>>);
+
+/// A located shard together with its held write lock.
+pub(crate) type ShardWrite<'a, S> = (ShardRef<'a, S>, ShardGuard<RwLockWriteGuard<'a, S>>);
+
+thread_local! {
+    /// The [`ShardSet`]s, by address, this thread holds a [`TableGuard`] on or is resharding.
+    static WHOLE_TABLE_HELD: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a [`ShardSet`] in [`WHOLE_TABLE_HELD`] for as long as it lives.
+struct WholeTableMark(usize);
+
+impl WholeTableMark {
+    fn new<S>(set: &ShardSet<S>) -> Self {
+        let addr = std::ptr::from_ref(set) as usize;
+        WHOLE_TABLE_HELD.with_borrow_mut(|held| held.push(addr));
+        Self(addr)
+    }
+
+    fn held<S>(set: &ShardSet<S>) -> bool {
+        let addr = std::ptr::from_ref(set) as usize;
+        WHOLE_TABLE_HELD.with_borrow(|held| held.contains(&addr))
+    }
+}
+
+impl Drop for WholeTableMark {
+    fn drop(&mut self) {
+        WHOLE_TABLE_HELD.with_borrow_mut(|held| {
+            if let Some(i) = held.iter().rposition(|&addr| addr == self.0) {
+                held.swap_remove(i);
+            }
+        });
+    }
+}
+
+/// The shard array of a sharded store, replaceable at runtime by [`reshard`](Self::reshard).
+///
+/// Per-key operations stay off any store-wide lock: they load the current table, lock the
+/// key's shard, and only if that shard has been [`moved`](Shard::moved) drop the guard and
+/// follow [`ShardTable::next`] to where the key lives now. Since a shard is drained and marked
+/// moved under its own write lock, a guard on a shard that is not moved always sees the key's
+/// only copy. Every per-key lock must therefore go through [`read`](Self::read) /
+/// [`write`](Self::write), which do that check; a `&Shard` kept across a release and
+/// re-acquired directly could land in a drained shard.
+///
+/// Whole-table operations (`len`, `clear`, `retain`, metrics, snapshots) go through
+/// [`table`](Self::table), which holds the `resize` lock shared so they never observe a table
+/// half-migrated. A reshard holds it exclusively for its whole run, so those operations wait
+/// for it to finish while per-key traffic continues. A reshard called on the same thread from
+/// inside a whole-table operation on the same store (an `on_evict` fired by `retain`, say), or
+/// from inside another reshard, would wait on itself; it is refused with an error instead.
+///
+/// A table a reshard replaces is not freed at once, since a per-key operation may still hold a
+/// reference into it. Per-key operations pin the thread with `crossbeam-epoch` for as long as
+/// the [`ShardRef`] or lock guard they return lives, and the reshard hands the replaced table
+/// to the same collector, which frees it once every thread pinned before the replacement has
+/// unpinned. The table the set was built with is the exception: it is only freed with the set,
+/// so an operation that finds it current skips the pin, and a store that is never resharded
+/// pays nothing for the indirection beyond a pointer load and the `moved` check.
+pub(crate) struct ShardSet<S> {
+    /// The table the set was built with, current until the first reshard.
+    initial: Box<ShardTable<S>>,
+    /// The current table: `initial`, or one a reshard allocated.
+    current: Atomic<ShardTable<S>>,
+    resize: parking_lot::RwLock<()>,
+}
+
+// SAFETY: the set owns its initial and current tables outright, and hands replaced ones to the
+// epoch collector only when `S: Send`, so it is `Send`/`Sync` exactly when a `Box<[Shard<S>]>`
+// shared between threads would be. (`Atomic` on its own would demand `S: Sync` for `Send` too.)
+unsafe impl<S: Send> Send for ShardSet<S> {}
+unsafe impl<S: Send + Sync> Sync for ShardSet<S> {}
+
+/// The current table, with the `resize` lock held shared; see [`ShardSet::table`].
+pub(crate) struct TableGuard<'a, S> {
+    _resize: parking_lot::RwLockReadGuard<'a, ()>,
+    _mark: WholeTableMark,
+    table: &'a ShardTable<S>,
+}
+
+impl<S> std::ops::Deref for TableGuard<'_, S> {
+    type Target = ShardTable<S>;
+    fn deref(&self) -> &ShardTable<S> {
+        self.table
+    }
+}
+
+/// The shard a per-key operation located, pinned so its table outlives the reference even if
+/// a reshard replaces it; the [`ShardRead`] / [`ShardWrite`] lock guard alone keeps it current.
+pub(crate) struct ShardRef<'a, S> {
+    shard: &'a CachePadded<Shard<S>>,
+    /// `None` for a shard of the set's initial table, which needs no pin.
+    _pin: Option<epoch::Guard>,
+}
+
+impl<S> std::ops::Deref for ShardRef<'_, S> {
+    type Target = CachePadded<Shard<S>>;
+    fn deref(&self) -> &Self::Target {
+        self.shard
+    }
+}
+
+/// A located shard's lock guard, with a pin of its own: either half of a [`ShardRead`] /
+/// [`ShardWrite`] may be dropped first, or alone, without the other's table being freed
+/// under it. The guard is declared first so it unlocks before the pin is released.
+pub(crate) struct ShardGuard<G> {
+    guard: G,
+    _pin: Option<epoch::Guard>,
+}
+
+impl<G: std::ops::Deref> std::ops::Deref for ShardGuard<G> {
+    type Target = G::Target;
+    fn deref(&self) -> &G::Target {
+        &self.guard
+    }
+}
+
+impl<G: std::ops::DerefMut> std::ops::DerefMut for ShardGuard<G> {
+    fn deref_mut(&mut self) -> &mut G::Target {
+        &mut self.guard
+    }
+}
+
+impl<S> ShardSet<S> {
+    pub fn new(shards: Box<[CachePadded<Shard<S>>]>) -> Self {
+        let initial = Box::new(ShardTable::new(shards));
+        Self {
+            current: Atomic::from(std::ptr::from_ref(&*initial)),
+            initial,
+            resize: parking_lot::RwLock::new(()),
+        }
+    }
+
+    fn is_initial(&self, table: epoch::Shared<'_, ShardTable<S>>) -> bool {
+        std::ptr::eq(table.as_raw(), &*self.initial)
+    }
+
+    /// The number of shards in the current table.
+    pub fn len(&self) -> usize {
+        let pin = epoch::pin();
+        // SAFETY: `current` always points at a live table, and a replaced one is not freed
+        // while `pin` is held.
+        unsafe { self.current.load(Ordering::Acquire, &pin).deref() }.len()
+    }
+
+    /// The current table for a whole-table operation. Holding the guard keeps a reshard from
+    /// starting (or waits for a running one to finish); it is recursive, so a whole-table
+    /// operation may call another.
+    pub fn table(&self) -> TableGuard<'_, S> {
+        let resize = self.resize.read_recursive();
+        // SAFETY: only a table a reshard has replaced is ever freed, and the `resize` lock
+        // keeps the current one from being replaced for as long as the guard holds it.
+        let table = unsafe {
+            self.current
+                .load(Ordering::Acquire, epoch::unprotected())
+                .deref()
+        };
+        TableGuard {
+            _resize: resize,
+            _mark: WholeTableMark::new(self),
+            table,
+        }
+    }
+
+    fn locate<'a, G>(
+        &'a self,
+        hash: u64,
+        lock: impl Fn(&'a Shard<S>) -> G,
+    ) -> (ShardRef<'a, S>, ShardGuard<G>) {
+        // SAFETY: the pointer is only compared, not dereferenced.
+        let current = self
+            .current
+            .load(Ordering::Acquire, unsafe { epoch::unprotected() });
+        if self.is_initial(current) {
+            let shard = self.initial.shard(hash);
+            let guard = lock(shard);
+            if !shard.moved.load(Ordering::Acquire) {
+                return (
+                    ShardRef { shard, _pin: None },
+                    ShardGuard { guard, _pin: None },
+                );
+            }
+            // A reshard has moved this shard: start over from whichever table is current now.
+            drop(guard);
+        }
+        let pin = epoch::pin();
+        let mut table = self.current.load(Ordering::Acquire, &pin);
+        loop {
+            // SAFETY: `table` is `current`, or the `next` of a shard found moved (set before
+            // any shard is marked). Every table reachable that way stays allocated until all
+            // pins taken before its replacement are released, and the returned reference is
+            // handed out with two such pins: `pin` and the one nested in it below.
+            let table_ref: &'a ShardTable<S> = unsafe { &*table.as_raw() };
+            let shard = table_ref.shard(hash);
+            let guard = lock(shard);
+            if !shard.moved.load(Ordering::Acquire) {
+                let shard = ShardRef {
+                    shard,
+                    _pin: Some(epoch::pin()),
+                };
+                return (
+                    shard,
+                    ShardGuard {
+                        guard,
+                        _pin: Some(pin),
+                    },
+                );
+            }
+            drop(guard);
+            table = table_ref.next.load(Ordering::Acquire, &pin);
+        }
+    }
+
+    /// Shared-lock the shard that holds `hash` now, returning it with its guard.
+    pub fn read(&self, hash: u64) -> ShardRead<'_, S> {
+        self.locate(hash, Shard::read)
+    }
+
+    /// Exclusively lock the shard that holds `hash` now, returning it with its guard.
+    pub fn write(&self, hash: u64) -> ShardWrite<'_, S> {
+        self.locate(hash, Shard::write)
+    }
+
+    /// Per-shard [`ShardStats`] for the current table, counting entries with `entries`.
+    pub fn stats(&self, entries: impl Fn(&S) -> usize) -> Vec<ShardStats> {
+        self.table()
+            .iter()
+            .map(|shard| {
+                let len = entries(&shard.read());
+                shard.stats(len)
+            })
+            .collect()
+    }
+
+    /// Migrate the store into a new table of `n` shards (`n` a power of two), while per-key
+    /// operations keep running against it. A no-op if the current table already has `n`.
+    ///
+    /// Old shards are migrated one at a time: each is write-locked, `drain` empties it into
+    /// `(shard hash, entry)` pairs in the order they should be re-inserted, `fill` inserts
+    /// them into the new shards in that order, and the old shard is marked moved before its
+    /// lock is released. Only the shard being migrated is unavailable, and only while its own
+    /// entries move; its counters fold into the new shard at the same index (masked), so the
+    /// store-wide totals are unchanged. `make` builds each new shard's empty store. The old
+    /// table is handed to the epoch collector, which frees it once no per-key operation can
+    /// reach it; that may happen on another thread, hence `S: Send + 'static`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReshardError::Reentrant`] if this thread is inside a whole-table operation
+    /// or another reshard on the same set, which would otherwise deadlock.
+    pub fn reshard<E>(
+        &self,
+        n: usize,
+        make: impl FnMut() -> S,
+        mut drain: impl FnMut(&mut S) -> Vec<(u64, E)>,
+        mut fill: impl FnMut(&mut S, E),
+    ) -> Result<(), ReshardError>
+    where
+        S: Send + 'static,
+    {
+        debug_assert!(n.is_power_of_two());
+        if WholeTableMark::held(self) {
+            return Err(ReshardError::Reentrant);
+        }
+        let _resize = self.resize.write();
+        let _mark = WholeTableMark::new(self);
+        let pin = epoch::pin();
+        let old_ptr = self.current.load(Ordering::Acquire, &pin);
+        // SAFETY: the current table, kept current by the `resize` lock.
+        let old = unsafe { old_ptr.deref() };
+        if old.len() == n {
+            return Ok(());
+        }
+        let shards = std::iter::repeat_with(make)
+            .take(n)
+            .map(|store| CachePadded(Shard::new(store)))
+            .collect();
+        let new_ptr = Owned::new(ShardTable::new(shards)).into_shared(&pin);
+        // SAFETY: just allocated; published below and owned by the set from here on.
+        let new = unsafe { new_ptr.deref() };
+        old.next.store(new_ptr, Ordering::Release);
+
+        let mut batches: Vec<Vec<E>> = std::iter::repeat_with(Vec::new).take(n).collect();
+        for (i, shard) in old.iter().enumerate() {
+            let mut guard = shard.write();
+            for (hash, entry) in drain(&mut guard) {
+                batches[shard_index(hash, new.mask)].push(entry);
+            }
+            for (target, batch) in new.iter().zip(batches.iter_mut()) {
+                if !batch.is_empty() {
+                    let mut store = target.write();
+                    for entry in batch.drain(..) {
+                        fill(&mut store, entry);
+                    }
+                }
+            }
+            new[i & new.mask].absorb_counters(shard);
+            shard.moved.store(true, Ordering::Release);
+        }
+        self.current.store(new_ptr, Ordering::Release);
+        if !self.is_initial(old_ptr) {
+            // SAFETY: `old` is no longer current, so only an operation pinned before the store
+            // above can reach it (directly, or through an older table's `next`); the collector
+            // waits those out. Nothing else frees it, and `S: Send + 'static` lets the
+            // collector drop it on whichever thread gets there, whenever that is.
+            unsafe { pin.defer_destroy(old_ptr) };
+            pin.flush();
+        }
+        Ok(())
+    }
+}
+
+/// [`ShardSet::reshard`] for the stores whose shards are [`LruCache`]s: the sharded LRU,
+/// expiring-LRU and LRU-TTL caches.
+///
+/// The store's total capacity is re-split across `n` shards with the builders' 16-per-shard
+/// floor and published to `total_capacity`; `make` builds a shard of the given capacity with
+/// the store's callback wiring. Each old shard is re-inserted least recently used first, so
+/// its entries keep their relative recency, and pinned entries are re-inserted pinned (after
+/// the unpinned ones, so they never count toward a new shard's capacity while it fills). The
+/// inner caches' eviction counters are summed into the first new shard.
+pub(crate) fn reshard_lru<K, V, H>(
+    shards: &ShardSet<LruCache<K, V>>,
+    hasher: &H,
+    total_capacity: &AtomicUsize,
+    n: usize,
+    make: impl Fn(usize) -> LruCache<K, V>,
+) -> Result<(), ReshardError>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Send + 'static,
+    H: ShardHasher<K>,
+{
+    let total = total_capacity.load(Ordering::Acquire);
+    let (per_shard_cap, total_cap) =
+        checked_per_shard_cap_from_total(total, n).map_err(|_| ReshardError::CapacityOverflow)?;
+    let mut evictions = 0;
+    shards.reshard(
+        n,
+        || make(per_shard_cap),
+        |lru| {
+            evictions += lru.evictions.swap(0, Ordering::Relaxed);
+            // MRU-first with the pinned entries leading; reversed so the least recent entries
+            // go in first.
+            let pinned = lru.pinned_len();
+            let drained = std::mem::replace(lru, make(1)).drain_all();
+            drained
+                .into_iter()
+                .enumerate()
+                .rev()
+                .map(|(i, (k, v))| (hasher.shard_hash(&k), (k, v, i < pinned)))
+                .collect()
+        },
+        |lru, (k, v, pinned)| {
+            if pinned {
+                // The shards carry no pin cap of their own (the store enforces its total),
+                // so this cannot fail.
+                let _ = lru.set_pinned_returning_entry(k, v);
+            } else {
+                lru.cache_set(k, v);
+            }
+        },
+    )?;
+    total_capacity.store(total_cap, Ordering::Release);
+    if let Some(shard) = shards.table().first() {
+        shard
+            .read()
+            .evictions
+            .fetch_add(evictions, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// A key's shard write-locked for a pin or `set_pinned` on a sharded LRU-family store, with
+/// the store-wide `max_pinned` cap counted over every shard.
+///
+/// When a cap is set the store's pin lock and the table guard stay held until this is
+/// dropped, so no other pin can slip in between the count and the pin, and no reshard can
+/// move entries while they are counted. Without a cap neither is taken.
+pub(crate) struct PinShard<'a, S> {
+    /// Read only by the LRU-TTL store, which counts displaced expired entries on the shard.
+    #[cfg_attr(not(feature = "time_stores"), allow(dead_code))]
+    pub shard: ShardRef<'a, S>,
+    pub store: ShardGuard<RwLockWriteGuard<'a, S>>,
+    /// `Some(max_pinned)` when the store already holds that many pinned entries.
+    full: Option<usize>,
+    _table: Option<TableGuard<'a, S>>,
+    _pin: Option<parking_lot::MutexGuard<'a, ()>>,
+}
+
+impl<S> PinShard<'_, S> {
+    /// Whether one more entry may be pinned; an entry that is `already_pinned` always may.
+    pub fn check_room(&self, already_pinned: bool) -> Result<(), PinError> {
+        match self.full {
+            Some(max_pinned) if !already_pinned => Err(PinError::LimitReached { max_pinned }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Lock the shard holding `hash` for a pin, counting the store's pinned entries against
+/// `max_pinned` first. See [`PinShard`].
+pub(crate) fn pin_shard<'a, K, V>(
+    shards: &'a ShardSet<LruCache<K, V>>,
+    pin_lock: &'a parking_lot::Mutex<()>,
+    max_pinned: Option<usize>,
+    hash: u64,
+) -> PinShard<'a, LruCache<K, V>>
+where
+    K: Hash + Eq + Clone,
+{
+    let (pin, table, full) = match max_pinned {
+        Some(max_pinned) => {
+            let pin = pin_lock.lock();
+            let table = shards.table();
+            let pinned: usize = table.iter().map(|s| s.read().pinned_len()).sum();
+            let full = (pinned >= max_pinned).then_some(max_pinned);
+            (Some(pin), Some(table), full)
+        }
+        None => (None, None, None),
+    };
+    let (shard, store) = shards.write(hash);
+    PinShard {
+        shard,
+        store,
+        full,
+        _table: table,
+        _pin: pin,
+    }
+}
+
+/// Total pinned entries across a sharded LRU-family store's shards.
+pub(crate) fn pinned_len<K, V>(shards: &ShardSet<LruCache<K, V>>) -> usize
+where
+    K: Hash + Eq + Clone,
+{
+    shards.table().iter().map(|s| s.read().pinned_len()).sum()
+}
+
+/// Where a sharded store's budgeted sweep resumes: a shard of the current table and a position
+/// within it, in whatever order the store walks a shard.
+#[derive(Debug, Default)]
+pub(crate) struct EvictCursor {
+    shard: usize,
+    position: usize,
+}
+
+/// One [`evict_budgeted`](crate::ConcurrentCacheEvict::evict_budgeted) call over `shards`,
+/// resuming from `cursor`.
+///
+/// `sweep` gets a shard's write guard, the position to start at and the entries left in the
+/// budget. It returns what it removed, how much of the budget it spent, and the position the
+/// next step in that shard starts at; a shard that had fewer entries left than it was
+/// allowed is done, and the cursor moves on to the next. `settle` counts and notifies each
+/// step's removals after the shard lock is released. The cursor is locked around each step
+/// (before the shard) but not across `settle`, so an `on_evict` may call back into the store.
+pub(crate) fn evict_budgeted<S, T>(
+    shards: &ShardSet<S>,
+    cursor: &parking_lot::Mutex<EvictCursor>,
+    max_entries: usize,
+    mut sweep: impl FnMut(&mut S, usize, usize) -> (Vec<T>, usize, usize),
+    mut settle: impl FnMut(&Shard<S>, Vec<T>),
+) -> crate::stores::EvictProgress {
+    let table = shards.table();
+    let mut left = max_entries;
+    let mut reclaimed = 0;
+    loop {
+        let mut at = cursor.lock();
+        let Some(shard) = table.get(at.shard) else {
+            // Past the last shard, or the table shrank under a reshard: start over next time.
+            *at = EvictCursor::default();
+            return crate::stores::EvictProgress {
+                reclaimed,
+                finished: true,
+            };
+        };
+        if left == 0 {
+            return crate::stores::EvictProgress {
+                reclaimed,
+                finished: false,
+            };
+        }
+        let (removed, visited, next) = sweep(&mut shard.write(), at.position, left);
+        if visited < left {
+            *at = EvictCursor {
+                shard: at.shard + 1,
+                position: 0,
+            };
+        } else {
+            at.position = next;
+        }
+        left -= visited;
+        drop(at);
+        reclaimed += removed.len();
+        settle(shard, removed);
+    }
+}
+
+impl<S> Drop for ShardSet<S> {
+    fn drop(&mut self) {
+        // SAFETY: `&mut self` rules out any operation in flight, so nothing else can reach the
+        // current table; every table replaced before it, bar the initial one (dropped with
+        // `self`), was handed to the collector instead.
+        let current = unsafe { self.current.load(Ordering::Relaxed, epoch::unprotected()) };
+        if !self.is_initial(current) {
+            drop(unsafe { current.into_owned() });
         }
     }
 }
@@ -170,6 +822,16 @@ pub(crate) fn checked_shard_count(shards: Option<usize>) -> Result<usize, BuildE
         })
 }
 
+/// The shard count a `reshard(shards)` call migrates to: `shards` rounded up to a power of two.
+pub(crate) fn checked_reshard_count(shards: usize) -> Result<usize, ReshardError> {
+    if shards == 0 {
+        return Err(ReshardError::ZeroShards);
+    }
+    shards
+        .checked_next_power_of_two()
+        .ok_or(ReshardError::ShardCountOverflow)
+}
+
 #[inline]
 pub(crate) fn shard_index(hash: u64, mask: usize) -> usize {
     (hash >> 32) as usize & mask
@@ -357,19 +1019,29 @@ impl std::hash::BuildHasher for DefaultShardHasher {
     }
 }
 
+mod arc;
 mod expiring;
 mod expiring_lru;
+mod lfu;
 mod lru;
+mod policy;
+mod s3fifo;
 mod unbound;
 
 #[cfg(feature = "time_stores")]
 mod lru_ttl;
 #[cfg(feature = "time_stores")]
 mod ttl;
+#[cfg(feature = "time_stores")]
+mod ttl_sorted;
 
+pub use arc::{ShardedArcCache, ShardedArcCacheBuilder};
 pub use expiring::{ShardedExpiringCache, ShardedExpiringCacheBuilder};
 pub use expiring_lru::{ShardedExpiringLruCache, ShardedExpiringLruCacheBuilder};
+pub use lfu::{ShardedLfuCache, ShardedLfuCacheBuilder};
 pub use lru::{ShardedLruCache, ShardedLruCacheBuilder};
+pub use policy::{ShardedPolicyCache, ShardedPolicyCacheBuilder};
+pub use s3fifo::{ShardedS3FifoCache, ShardedS3FifoCacheBuilder};
 pub use unbound::{ShardedUnboundCache, ShardedUnboundCacheBuilder};
 
 #[cfg(feature = "time_stores")]
@@ -380,6 +1052,12 @@ pub use ttl::{ShardedTtlCache, ShardedTtlCacheBuilder};
 #[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
 pub use lru_ttl::{ShardedLruTtlCache, ShardedLruTtlCacheBuilder};
 
+#[cfg(feature = "time_stores")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
+pub use ttl_sorted::{
+    ShardedTtlSortedCache, ShardedTtlSortedCacheBuilder, ShardedTtlSortedSetBuilder,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -497,6 +1175,151 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shard_counts_only_contended_acquisitions() {
+        let shard = std::sync::Arc::new(Shard::new(0u32));
+        drop(shard.read());
+        drop(shard.write());
+        assert_eq!(shard.stats(0).lock_waits, 0);
+
+        let guard = shard.write();
+        let waiter = {
+            let shard = shard.clone();
+            std::thread::spawn(move || *shard.read())
+        };
+        // Give the reader time to fail its `try_read` and block.
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        drop(guard);
+        waiter.join().unwrap();
+
+        let stats = shard.stats(0);
+        assert_eq!(stats.lock_waits, 1);
+        assert!(stats.lock_wait_time > crate::time::Duration::ZERO);
+        shard.reset_counters();
+        assert_eq!(shard.stats(0), ShardStats::default());
+    }
+
+    fn shard_set(n: usize) -> ShardSet<Vec<u64>> {
+        ShardSet::new(
+            std::iter::repeat_with(|| CachePadded(Shard::new(Vec::new())))
+                .take(n)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn shard_set_reshard_reroutes_and_keeps_counters() {
+        let set = shard_set(1);
+        for hash in 0..8u64 {
+            set.write(hash << 32).1.push(hash << 32);
+        }
+        set.table()[0].hits.store(5, Ordering::Relaxed);
+
+        set.reshard(
+            4,
+            Vec::new,
+            |v| v.drain(..).map(|h| (h, h)).collect(),
+            Vec::push,
+        )
+        .unwrap();
+        assert_eq!(set.len(), 4);
+        for hash in 0..8u64 {
+            let (_, guard) = set.read(hash << 32);
+            assert!(
+                guard.contains(&(hash << 32)),
+                "hash {hash} not in its new shard"
+            );
+        }
+        let stats = set.stats(Vec::len);
+        assert_eq!(stats.iter().map(|s| s.entries).sum::<usize>(), 8);
+        assert_eq!(stats.iter().map(|s| s.hits).sum::<u64>(), 5);
+
+        // Shrinking back follows the chain from the first table to the last.
+        set.reshard(
+            1,
+            Vec::new,
+            |v| v.drain(..).map(|h| (h, h)).collect(),
+            Vec::push,
+        )
+        .unwrap();
+        assert_eq!(set.read(3 << 32).1.len(), 8);
+    }
+
+    fn reshard_vec(set: &ShardSet<Vec<u64>>, n: usize) -> Result<(), ReshardError> {
+        set.reshard(
+            n,
+            Vec::new,
+            |v| v.drain(..).map(|h| (h, h)).collect(),
+            Vec::push,
+        )
+    }
+
+    #[test]
+    fn shard_set_keeps_replaced_tables_alive_while_pinned() {
+        let set = shard_set(1);
+        reshard_vec(&set, 2).unwrap();
+
+        // A located shard outlives its lock guard; its table must outlive both reshards.
+        let (held, guard) = set.write(1 << 32);
+        drop(guard);
+        reshard_vec(&set, 4).unwrap();
+        reshard_vec(&set, 8).unwrap();
+        held.hits.fetch_add(1, Ordering::Relaxed);
+        drop(held);
+        assert_eq!(set.len(), 8);
+
+        // And a lock guard outlives the shard it was returned with, across a reshard on
+        // another thread that has to wait for it.
+        let set = std::sync::Arc::new(shard_set(1));
+        let mut guard = set.write(1 << 32).1;
+        let worker = {
+            let set = set.clone();
+            std::thread::spawn(move || reshard_vec(&set, 4))
+        };
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        guard.push(1 << 32);
+        drop(guard);
+        worker.join().unwrap().unwrap();
+        assert_eq!(*set.read(1 << 32).1, [1 << 32]);
+    }
+
+    #[test]
+    fn shard_set_refuses_reshard_inside_a_whole_table_op() {
+        let set = shard_set(2);
+        let table = set.table();
+        assert_eq!(reshard_vec(&set, 4), Err(ReshardError::Reentrant));
+        assert_eq!(table.len(), 2);
+        drop(table);
+        reshard_vec(&set, 4).unwrap();
+
+        // So is one from inside a running reshard.
+        let mut nested = None;
+        set.reshard(
+            8,
+            Vec::new,
+            |v| {
+                nested.get_or_insert_with(|| reshard_vec(&set, 16));
+                v.drain(..).map(|h| (h, h)).collect()
+            },
+            Vec::push,
+        )
+        .unwrap();
+        assert_eq!(nested, Some(Err(ReshardError::Reentrant)));
+        assert_eq!(set.len(), 8);
+
+        // Another thread's whole-table op only delays it.
+        let set = std::sync::Arc::new(shard_set(2));
+        let table = set.table();
+        let worker = {
+            let set = set.clone();
+            std::thread::spawn(move || reshard_vec(&set, 4))
+        };
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        drop(table);
+        worker.join().unwrap().unwrap();
+        assert_eq!(set.len(), 4);
+    }
+
     #[test]
     fn default_shard_count_is_stable_across_calls() {
         // `default_shard_count` caches its result in a `OnceLock`; repeated calls
@@ -7,7 +7,7 @@ use ahash::RandomState;
 #[cfg(not(feature = "ahash"))]
 use std::collections::hash_map::RandomState;
 
-use std::collections::HashMap;
+use crate::stores::bucket_map::BucketMap;
 
 use crate::time::{Duration, Instant};
 use crate::{
@@ -20,24 +20,35 @@ use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
 use core::future::Future;
 
 use super::{
-    CachePadded, DefaultShardHasher, Shard, ShardHasher, checked_shard_count, decode_ttl,
-    encode_ttl, shard_index,
+    CachePadded, DefaultShardHasher, EvictCursor, Shard, ShardHasher, ShardRead, ShardSet,
+    ShardStats, ShardWrite, checked_reshard_count, checked_shard_count, decode_ttl, encode_ttl,
 };
-use crate::stores::{BuildError, TimedEntry};
+use crate::stores::{
+    BuildError, ConcurrentCachedTags, EvictProgress, ReshardError, TagIndex, TimedEntry,
+};
+#[cfg(feature = "persist")]
+use crate::stores::{CacheSnapshot, ConcurrentCachedSnapshot, SnapshotClock};
 
 type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
 
 #[allow(clippy::type_complexity)]
 struct TtlInner<K, V, H> {
-    shards: Box<[CachePadded<Shard<HashMap<K, TimedEntry<V>, RandomState>>>]>,
-    shard_mask: usize,
+    shards: ShardSet<BucketMap<K, TimedEntry<V>, RandomState>>,
     hasher: H,
     on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
     /// TTL in nanoseconds, or `0` to mean expiry is disabled (entries never expire).
     /// A zero stored value is the single sentinel for "no expiry"; there is no separate
     /// `ttl_set` flag. `unset_ttl`/`set_ttl(0)` store `0`; `set_ttl(nonzero)` stores the ttl.
     ttl_nanos: AtomicU64,
     refresh: AtomicBool,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+    /// Where the next [`evict_budgeted`](ShardedTtlCache::evict_budgeted) step starts; the
+    /// position is in the shard map's iteration order.
+    evict_cursor: parking_lot::Mutex<EvictCursor>,
 }
 
 /// Judge a stored entry's expiry against an already-sampled instant.
@@ -165,9 +176,13 @@ where
     H: ShardHasher<K>,
 {
     #[inline]
-    fn shard_of(&self, k: &K) -> &CachePadded<Shard<HashMap<K, TimedEntry<V>, RandomState>>> {
-        let h = self.inner.hasher.shard_hash(k);
-        &self.inner.shards[shard_index(h, self.inner.shard_mask)]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, BucketMap<K, TimedEntry<V>, RandomState>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, BucketMap<K, TimedEntry<V>, RandomState>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
     }
 
     #[inline]
@@ -192,6 +207,55 @@ where
             now.checked_add(ttl)
         }
     }
+
+    /// Store `new_entry` for `k`, returning the displaced value only if it was still live at
+    /// `now`. Shared by `cache_set` and snapshot restore, which carries its own deadline.
+    fn set_entry(&self, k: K, new_entry: TimedEntry<V>, now: Instant) -> Option<V> {
+        // Capture the displaced entry and evaluate expiry while the write lock is still held
+        // (B2: avoids a TOCTOU where the entry crosses the expiry threshold between unlock and
+        // the check). Expiry is judged against the same `now` that stamps the replacement
+        // entry — one clock read per call, and the displaced entry is judged against exactly
+        // the instant the new entry claims to start at. An owned key is kept in hand so
+        // `on_evict` can fire after the lock is released (on_evict-after-unlock).
+        //
+        // There is exactly ONE write shape here, taken whether or not an `on_evict` callback is
+        // configured: an overwrite keeps the STORED key and drops the caller's, matching
+        // `HashMap::insert` and the single-owner `TtlCache`. This used to branch on
+        // `on_evict.is_some()` and take a `remove_entry` + `insert` (key-rebinding) path when a
+        // callback was present, so attaching a purely observational callback changed which key
+        // was physically stored. The value is swapped in place through `get_mut`, which leaves
+        // the caller's key `k` owned here; `on_evict` therefore receives the caller's key -- the
+        // same key the LRU-backed sharded stores hand it when the stored key is kept. The two
+        // compare `Eq`.
+        let (shard, mut guard) = self.write_shard(&k);
+        let old: Option<(K, TimedEntry<V>, bool)> = match guard.get_mut(&k) {
+            Some(slot) => {
+                let e = std::mem::replace(slot, new_entry);
+                let expired = expired_at(&e, now);
+                Some((k, e, expired))
+            }
+            None => {
+                guard.insert(k, new_entry);
+                None
+            }
+        };
+        drop(guard);
+        match old {
+            // A displaced expired value is filtered from the return (matching cache_remove and
+            // the single-owner TTL stores); fire on_evict and count an eviction for it.
+            Some((key, entry, true)) => {
+                // Count BEFORE notifying: a panicking callback must never leave an
+                // entry removed-but-uncounted.
+                shard.evictions.fetch_add(1, Ordering::Relaxed);
+                if let Some(cb) = &self.inner.on_evict {
+                    cb(&key, &entry.value);
+                }
+                None
+            }
+            Some((_, entry, false)) => Some(entry.value),
+            None => None,
+        }
+    }
 }
 
 impl<K: Clone + Hash + Eq, V: Clone, H: ShardHasher<K>> ShardedTtlCache<K, V, H> {
@@ -200,36 +264,43 @@ impl<K: Clone + Hash + Eq, V: Clone, H: ShardHasher<K>> ShardedTtlCache<K, V, H>
     /// what you want.
     #[must_use]
     pub fn deep_clone(&self) -> Self {
-        let n = self.inner.shards.len();
-        let shards = (0..n)
-            .map(|i| {
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
                 // Load the hit/miss counters under the read lock so the metrics snapshot is
                 // consistent with the entry snapshot (B4: loading after drop(guard) could yield
                 // counters newer than the cloned entries).
-                let guard = self.inner.shards[i].lock.read();
+                let guard = shard.read();
                 let store_copy = guard.clone();
-                let hits = self.inner.shards[i].hits.load(Ordering::Relaxed);
-                let misses = self.inner.shards[i].misses.load(Ordering::Relaxed);
-                let evictions = self.inner.shards[i].evictions.load(Ordering::Relaxed);
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
+                let evictions = shard.evictions.load(Ordering::Relaxed);
                 drop(guard);
-                let shard = Shard {
-                    lock: parking_lot::RwLock::new(store_copy),
-                    hits: AtomicU64::new(hits),
-                    misses: AtomicU64::new(misses),
-                    evictions: AtomicU64::new(evictions),
-                };
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
+                shard.evictions.store(evictions, Ordering::Relaxed);
                 CachePadded(shard)
             })
             .collect::<Vec<_>>()
             .into_boxed_slice();
         Self {
             inner: Arc::new(TtlInner {
-                shards,
-                shard_mask: self.inner.shard_mask,
+                shards: ShardSet::new(shards),
                 hasher: self.inner.hasher.clone(),
                 on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
                 ttl_nanos: AtomicU64::new(self.inner.ttl_nanos.load(Ordering::Relaxed)),
                 refresh: AtomicBool::new(self.inner.refresh.load(Ordering::Relaxed)),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                deep_size: self.inner.deep_size,
+                evict_cursor: parking_lot::Mutex::default(),
             }),
         }
     }
@@ -261,6 +332,38 @@ where
         ConcurrentCached::cache_set(self, k, v).unwrap()
     }
 
+    /// Insert a key-value pair that expires `ttl` from now instead of after the cache's TTL,
+    /// returning the previous value only if it had not yet expired. A zero `ttl` stores an
+    /// entry that never expires.
+    ///
+    /// The override sets the entry's first deadline only: with
+    /// [`refresh_on_hit`](ShardedTtlCacheBuilder::refresh_on_hit) a hit re-arms it with the
+    /// cache's TTL, as it does every other entry.
+    pub fn set_with_ttl(&self, k: K, v: V, ttl: Duration) -> Option<V> {
+        let now = Instant::now();
+        let expires_at = if ttl.is_zero() {
+            None
+        } else {
+            now.checked_add(ttl)
+        };
+        let new_entry = TimedEntry {
+            expires_at,
+            value: v,
+        };
+        self.set_entry(k, new_entry, now)
+    }
+
+    /// Insert a key-value pair that expires at `deadline`, returning the previous value only
+    /// if it had not yet expired. A `deadline` that has already passed stores an entry that
+    /// is already expired. Otherwise behaves as [`set_with_ttl`](Self::set_with_ttl).
+    pub fn set_until(&self, k: K, v: V, deadline: Instant) -> Option<V> {
+        let new_entry = TimedEntry {
+            expires_at: Some(deadline),
+            value: v,
+        };
+        self.set_entry(k, new_entry, Instant::now())
+    }
+
     /// Return the cached value for `k`, or compute `f()`, store it, and return it.
     ///
     /// Infallible ergonomic API for the concrete type. As an inherent method it takes
@@ -317,8 +420,7 @@ where
     /// per-shard lock.
     #[must_use]
     pub fn peek(&self, k: &K) -> Option<V> {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         guard
             .get(k)
             .filter(|entry| !self.is_expired(entry))
@@ -340,13 +442,13 @@ where
         let mut misses = 0u64;
         let mut evictions = 0u64;
         let mut size = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             hits += shard.hits.load(Ordering::Relaxed);
             misses += shard.misses.load(Ordering::Relaxed);
             // Evictions are counted per shard (the counter lives on the same cache line the
             // evicting thread already owns) and summed here, exactly like hits/misses.
             evictions += shard.evictions.load(Ordering::Relaxed);
-            size += shard.lock.read().len();
+            size += shard.read().len();
         }
         CacheMetrics {
             hits: Some(hits),
@@ -354,6 +456,7 @@ where
             evictions: Some(evictions),
             entry_count: Some(size),
             capacity: None,
+            mem_bytes: Some(self.shard_memory_usage().into_iter().sum()),
         }
     }
 
@@ -368,28 +471,103 @@ where
     pub fn shard_sizes(&self) -> Vec<usize> {
         self.inner
             .shards
+            .table()
+            .iter()
+            .map(|s| s.read().len())
+            .collect()
+    }
+
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage). A shard whose share
+    /// of the bytes runs well ahead of its share of the entries holds unusually large values.
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        let deep_size = self.inner.deep_size;
+        self.inner
+            .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().len())
+            .map(|shard| {
+                let store = shard.read();
+                let entries = deep_size.map_or(0, |heap| {
+                    store.iter().map(|(k, e)| heap.entry(k, &e.value)).sum()
+                });
+                size_of_val(shard) + store.allocation_bytes() + entries
+            })
             .collect()
     }
 
+    /// Per-shard entry counts (expired-but-unswept entries included), hit/miss counters and
+    /// lock-wait figures; see [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(BucketMap::len)
+    }
+
+    /// Migrate every entry into a new array of `shards` shards (rounded up to a power of two)
+    /// while the cache keeps serving requests.
+    ///
+    /// Entries move with their deadlines: an entry's `expires_at` was fixed when it was
+    /// written (or last refreshed), so it expires at the same instant after the move as it
+    /// would have before. Entries already past their deadline move too and are swept as
+    /// usual. Only the shard being drained blocks its keys, and only while its entries move;
+    /// whole-cache calls wait for the full migration. Hit, miss and eviction counts carry
+    /// over. A no-op when the cache already has that many shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReshardError::ZeroShards`] or [`ReshardError::ShardCountOverflow`] if
+    /// `shards` is zero or overflows when rounded up. Returns [`ReshardError::Reentrant`],
+    /// rather than deadlocking, when called from a callback (such as `on_evict`) that a
+    /// whole-cache call or reshard on this same cache is running.
+    pub fn reshard(&self, shards: usize) -> Result<(), ReshardError>
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+    {
+        let n = checked_reshard_count(shards)?;
+        let hasher = &self.inner.hasher;
+        self.inner.shards.reshard(
+            n,
+            || BucketMap::with_hasher(RandomState::new()),
+            |map| {
+                std::mem::take(map)
+                    .into_iter()
+                    .map(|(k, entry)| (hasher.shard_hash(&k), (k, entry)))
+                    .collect()
+            },
+            |map, (k, entry)| {
+                map.insert(k, entry);
+            },
+        )
+    }
+
     /// Total number of entries across all shards (including not-yet-swept expired entries).
     #[must_use]
     pub fn len(&self) -> usize {
-        self.inner.shards.iter().map(|s| s.lock.read().len()).sum()
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|s| s.read().len())
+            .sum()
     }
 
     /// `true` if no entries are present.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.inner.shards.iter().all(|s| s.lock.read().is_empty())
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .all(|s| s.read().is_empty())
     }
 
     /// Remove all entries from every shard. Does **not** fire `on_evict`.
     /// Use [`cache_clear_with_on_evict`](Self::cache_clear_with_on_evict) to opt into callback firing.
     pub fn clear(&self) {
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().clear();
+        for shard in self.inner.shards.table().iter() {
+            shard.write().clear();
         }
     }
 
@@ -403,9 +581,9 @@ where
         let Some(on_evict) = &self.inner.on_evict else {
             // No callback: only the removed *count* is observable, so clear each shard in
             // place instead of draining every entry into a `Vec` just to drop it.
-            for shard in self.inner.shards.iter() {
+            for shard in self.inner.shards.table().iter() {
                 let removed = {
-                    let mut guard = shard.lock.write();
+                    let mut guard = shard.write();
                     let n = guard.len();
                     guard.clear();
                     n
@@ -416,8 +594,8 @@ where
             }
             return;
         };
-        for shard in self.inner.shards.iter() {
-            let removed: Vec<(K, TimedEntry<V>)> = shard.lock.write().drain().collect();
+        for shard in self.inner.shards.table().iter() {
+            let removed: Vec<(K, TimedEntry<V>)> = shard.write().drain().collect();
             if !removed.is_empty() {
                 shard
                     .evictions
@@ -443,9 +621,9 @@ where
         let Some(cb) = &self.inner.on_evict else {
             // No callback: only the removed *count* is observable, so drop the expired entries
             // in place via `retain` and take the length delta -- no key clones, no `Vec`.
-            for shard in self.inner.shards.iter() {
+            for shard in self.inner.shards.table().iter() {
                 let removed = {
-                    let mut guard = shard.lock.write();
+                    let mut guard = shard.write();
                     let before = guard.len();
                     guard.retain(|_, e| !expired_at(e, now));
                     before - guard.len()
@@ -457,12 +635,12 @@ where
             }
             return total;
         };
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             // Single pass: `extract_if` removes and yields the expired entries as it walks the
             // table, so no key is cloned and no key is re-hashed for a second lookup.
             // Collect under the write lock, fire callbacks after releasing it.
             let removed: Vec<(K, TimedEntry<V>)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 guard.extract_if(|_, e| expired_at(e, now)).collect()
             };
 
@@ -479,6 +657,40 @@ where
         total
     }
 
+    /// Take one step of an incremental [`evict`](Self::evict): examine at most `max_entries`
+    /// entries from where the last step stopped, remove the expired ones, and report how
+    /// many were removed and whether the sweep got past the last shard.
+    ///
+    /// A step holds one shard's write lock at a time, for at most `max_entries` entries (or
+    /// eight times as many buckets of a shard's map, if removals left it sparse), so a large
+    /// store can be swept without the pause a full `evict` takes on each shard. See
+    /// [`ConcurrentCacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        let now = Instant::now();
+        super::evict_budgeted(
+            &self.inner.shards,
+            &self.inner.evict_cursor,
+            max_entries,
+            |store, position, budget| {
+                store.take_window(position, budget, |_, entry| expired_at(entry, now))
+            },
+            |shard, removed| {
+                if removed.is_empty() {
+                    return;
+                }
+                shard
+                    .evictions
+                    .fetch_add(removed.len() as u64, Ordering::Relaxed);
+                if let Some(cb) = &self.inner.on_evict {
+                    for (k, entry) in &removed {
+                        cb(k, &entry.value);
+                    }
+                }
+            },
+        )
+    }
+
     /// Retain only entries that are unexpired and satisfy `keep`.
     ///
     /// Removes every entry that is already TTL-expired **or** for which `keep` returns
@@ -517,7 +729,7 @@ where
     pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
         let now = Instant::now();
         let mut total_removed = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             // Collect under the write lock, fire callbacks after releasing it. Two phases: the
             // first runs `keep` (user code) and only selects, the second removes and runs
             // nothing that can panic. See `stores::take_doomed`. The no-callback path used to
@@ -525,10 +737,8 @@ where
             // panicking predicate skipped entirely; both paths now remove, count, and (where
             // configured) notify exactly the same entries.
             let removed: Vec<(K, TimedEntry<V>)> = {
-                let mut guard = shard.lock.write();
-                crate::stores::take_doomed(&mut guard, |k, entry| {
-                    expired_at(entry, now) || !keep(k, &entry.value)
-                })
+                let mut guard = shard.write();
+                guard.take_doomed(|k, entry| expired_at(entry, now) || !keep(k, &entry.value))
             };
             total_removed += removed.len();
             if !removed.is_empty() {
@@ -554,6 +764,10 @@ where
     fn evict(&self) -> usize {
         ShardedTtlCache::evict(self)
     }
+
+    fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        ShardedTtlCache::evict_budgeted(self, max_entries)
+    }
 }
 
 impl<K, V, H> ConcurrentCacheBase for ShardedTtlCache<K, V, H>
@@ -568,10 +782,15 @@ where
         Ok(Some(self.len()))
     }
 
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum())
+    }
+
     fn cache_hits(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.hits.load(Ordering::Relaxed))
                 .sum(),
@@ -582,16 +801,23 @@ where
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.misses.load(Ordering::Relaxed))
                 .sum(),
         )
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
+
     fn cache_evictions(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.evictions.load(Ordering::Relaxed))
                 .sum(),
@@ -645,9 +871,12 @@ where
     H: ShardHasher<K>,
 {
     fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
+        if let Some(hot) = &self.inner.hot_keys {
+            let hash = self.inner.hasher.shard_hash(k);
+            hot.record(hash, || Some(k));
+        }
         if self.inner.refresh.load(Ordering::Relaxed) {
-            let mut guard = shard.lock.write();
+            let (shard, mut guard) = self.write_shard(k);
             // The clock is read once, and only when the key is actually present: a lookup for
             // an absent key never touches it. The same instant decides expiry and stamps the
             // renewed `expires_at` (the previous code read the clock twice on this path).
@@ -692,33 +921,33 @@ where
         }
 
         // Check for expiry — try with a read lock.
-        let (expired, value, now) = {
-            let guard = shard.lock.read();
-            match guard.get(k) {
-                None => {
-                    // Release the shard lock before touching the counter, like every other
-                    // path in this file. A miss on an absent key never reads the clock.
-                    drop(guard);
-                    shard.misses.fetch_add(1, Ordering::Relaxed);
-                    return Ok(None);
-                }
-                Some(entry) => {
-                    // One clock read per call: this instant is reused by the write-lock
-                    // re-check below, which previously sampled the clock a second time.
-                    let now = Instant::now();
-                    let expired = expired_at(entry, now);
-                    let value = if !expired {
-                        Some(entry.value.clone())
-                    } else {
-                        None
-                    };
-                    (expired, value, now)
-                }
+        let (shard, guard) = self.read_shard(k);
+        let (expired, value, now) = match guard.get(k) {
+            None => {
+                // Release the shard lock before touching the counter, like every other
+                // path in this file. A miss on an absent key never reads the clock.
+                drop(guard);
+                shard.misses.fetch_add(1, Ordering::Relaxed);
+                return Ok(None);
+            }
+            Some(entry) => {
+                // One clock read per call: this instant is reused by the write-lock
+                // re-check below, which previously sampled the clock a second time.
+                let now = Instant::now();
+                let expired = expired_at(entry, now);
+                let value = if !expired {
+                    Some(entry.value.clone())
+                } else {
+                    None
+                };
+                (expired, value, now)
             }
         };
+        drop(guard);
         if expired {
-            // Upgrade to write lock to remove the expired entry.
-            let mut guard = shard.lock.write();
+            // Upgrade to write lock to remove the expired entry. Re-resolved rather than
+            // re-locking `shard`: a reshard may have moved the key in between.
+            let (shard, mut guard) = self.write_shard(k);
             // Re-check under write lock — another thread may have replaced the entry
             // with a fresh value in the meantime; clone it out in the same lookup.
             let fresh_value = match guard.get(k) {
@@ -747,63 +976,19 @@ where
     }
 
     fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(&k);
         let now = Instant::now();
         let expires_at = self.compute_expires_at(now);
         let new_entry = TimedEntry {
             expires_at,
             value: v,
         };
-        // Capture the displaced entry and evaluate expiry while the write lock is still held
-        // (B2: avoids a TOCTOU where the entry crosses the expiry threshold between unlock and
-        // the check). Expiry is judged against the same `now` that stamps the replacement
-        // entry — one clock read per call, and the displaced entry is judged against exactly
-        // the instant the new entry claims to start at. An owned key is kept in hand so
-        // `on_evict` can fire after the lock is released (on_evict-after-unlock).
-        //
-        // There is exactly ONE write shape here, taken whether or not an `on_evict` callback is
-        // configured: an overwrite keeps the STORED key and drops the caller's, matching
-        // `HashMap::insert` and the single-owner `TtlCache`. This used to branch on
-        // `on_evict.is_some()` and take a `remove_entry` + `insert` (key-rebinding) path when a
-        // callback was present, so attaching a purely observational callback changed which key
-        // was physically stored. The value is swapped in place through `get_mut`, which leaves
-        // the caller's key `k` owned here; `on_evict` therefore receives the caller's key -- the
-        // same key the LRU-backed sharded stores hand it when the stored key is kept. The two
-        // compare `Eq`.
-        let old: Option<(K, TimedEntry<V>, bool)> = {
-            let mut guard = shard.lock.write();
-            match guard.get_mut(&k) {
-                Some(slot) => {
-                    let e = std::mem::replace(slot, new_entry);
-                    let expired = expired_at(&e, now);
-                    Some((k, e, expired))
-                }
-                None => {
-                    guard.insert(k, new_entry);
-                    None
-                }
-            }
-        };
-        match old {
-            // A displaced expired value is filtered from the return (matching cache_remove and
-            // the single-owner TTL stores); fire on_evict and count an eviction for it.
-            Some((key, entry, true)) => {
-                // Count BEFORE notifying: a panicking callback must never leave an
-                // entry removed-but-uncounted.
-                shard.evictions.fetch_add(1, Ordering::Relaxed);
-                if let Some(cb) = &self.inner.on_evict {
-                    cb(&key, &entry.value);
-                }
-                Ok(None)
-            }
-            Some((_, entry, false)) => Ok(Some(entry.value)),
-            None => Ok(None),
-        }
+        Ok(self.set_entry(k, new_entry, now))
     }
 
     fn cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
-        let removed = shard.lock.write().remove_entry(k);
+        let (shard, mut guard) = self.write_shard(k);
+        let removed = guard.remove_entry(k);
+        drop(guard);
         if let Some((stored_k, entry)) = removed {
             shard.evictions.fetch_add(1, Ordering::Relaxed);
             if let Some(cb) = &self.inner.on_evict {
@@ -821,8 +1006,9 @@ where
     }
 
     fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
-        let shard = self.shard_of(k);
-        let removed = shard.lock.write().remove_entry(k);
+        let (shard, mut guard) = self.write_shard(k);
+        let removed = guard.remove_entry(k);
+        drop(guard);
         if let Some((ref stored_k, ref entry)) = removed {
             shard.evictions.fetch_add(1, Ordering::Relaxed);
             if let Some(cb) = &self.inner.on_evict {
@@ -843,23 +1029,60 @@ where
     }
 
     fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
-        for shard in self.inner.shards.iter() {
-            shard.hits.store(0, Ordering::Relaxed);
-            shard.misses.store(0, Ordering::Relaxed);
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
             shard.evictions.store(0, Ordering::Relaxed);
         }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
+        }
         Ok(())
     }
 
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
     /// Efficient peek-based contains: acquires a read lock, does not clone the value,
     /// and does not record hit/miss metrics. Returns `true` only for live (not expired) entries.
     fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         Ok(guard.get(k).is_some_and(|entry| !self.is_expired(entry)))
     }
 }
 
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedTtlCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| matches!(ConcurrentCached::cache_contains(self, k), Ok(true)));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
 impl<K, V, H> ConcurrentCachePeek<K, V> for ShardedTtlCache<K, V, H>
 where
     K: Hash + Eq,
@@ -947,6 +1170,9 @@ pub struct ShardedTtlCacheBuilder<K, V, H = DefaultShardHasher> {
     on_evict: Option<OnEvict<K, V>>,
     _k: std::marker::PhantomData<K>,
     _v: std::marker::PhantomData<V>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for ShardedTtlCacheBuilder<K, V, DefaultShardHasher> {
@@ -960,6 +1186,9 @@ impl<K, V> Default for ShardedTtlCacheBuilder<K, V, DefaultShardHasher> {
             on_evict: None,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -1046,9 +1275,59 @@ impl<K, V, H> ShardedTtlCacheBuilder<K, V, H> {
             on_evict: self.on_evict,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key. Read them with
+    /// [`top_keys`](crate::ConcurrentCachedExt::top_keys), which merges the shards' summaries.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](crate::stores::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::ConcurrentCachedExt::memory_usage) and `shard_memory_usage`,
+    /// measured through their [`DeepSize`](crate::DeepSize) impls. Each estimate then visits
+    /// every entry under its shard's read lock.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
     /// Set a callback invoked when an entry is evicted. Fires in five situations:
     /// lazily during [`cache_get`](ConcurrentCached::cache_get) when a TTL-expired entry is
     /// found and removed; explicitly via [`evict`](ShardedTtlCache::evict); on
@@ -1087,11 +1366,10 @@ impl<K, V, H> ShardedTtlCacheBuilder<K, V, H> {
         let ttl = self.ttl.ok_or(BuildError::MissingRequired("ttl"))?;
         crate::stores::validate_ttl(ttl)?;
         let n = checked_shard_count(self.shards)?;
-        let mask = n - 1;
         let per_shard_capacity = self.per_shard_initial_capacity.unwrap_or(0);
         let shards = (0..n)
             .map(|_| {
-                CachePadded(Shard::new(HashMap::with_capacity_and_hasher(
+                CachePadded(Shard::new(BucketMap::with_capacity_and_hasher(
                     per_shard_capacity,
                     RandomState::new(),
                 )))
@@ -1100,14 +1378,18 @@ impl<K, V, H> ShardedTtlCacheBuilder<K, V, H> {
             .into_boxed_slice();
         Ok(ShardedTtlCache {
             inner: Arc::new(TtlInner {
-                shards,
-                shard_mask: mask,
+                shards: ShardSet::new(shards),
                 hasher: self
                     .hasher
                     .expect("hasher is always initialized via Default or .hasher()"),
                 on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
                 ttl_nanos: AtomicU64::new(encode_ttl(ttl)),
                 refresh: AtomicBool::new(self.refresh),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
+                evict_cursor: parking_lot::Mutex::default(),
             }),
         })
     }
@@ -1144,9 +1426,9 @@ impl<K, V, H> ShardedTtlCacheBuilder<K, V, H> {
         H: ShardHasher<K>,
     {
         let new_cache = self.build()?;
-        for shard in existing.inner.shards.iter() {
+        for shard in existing.inner.shards.table().iter() {
             let entries: Vec<(K, TimedEntry<V>)> = {
-                let guard = shard.lock.read();
+                let guard = shard.read();
                 let now = Instant::now();
                 guard
                     .iter()
@@ -1159,8 +1441,7 @@ impl<K, V, H> ShardedTtlCacheBuilder<K, V, H> {
             };
             // Insert preserving original timestamps.
             for (k, entry) in entries {
-                let new_shard = new_cache.shard_of(&k);
-                new_shard.lock.write().insert(k, entry);
+                new_cache.write_shard(&k).1.insert(k, entry);
             }
         }
         Ok(new_cache)
@@ -1176,10 +1457,9 @@ where
     /// Returns `(Some(v), false)` for a live entry (hit), `(Some(v), true)` for an expired
     /// entry (miss, **no removal**, no eviction counter), or `(None, false)` when absent (miss).
     fn cache_get_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
         if self.inner.refresh.load(Ordering::Relaxed) {
             // Refresh-on-hit path: write lock needed to update the entry's expires_at.
-            let mut guard = shard.lock.write();
+            let (shard, mut guard) = self.write_shard(k);
             match guard.get_mut(k) {
                 None => {
                     drop(guard);
@@ -1207,7 +1487,7 @@ where
             }
         } else {
             // Default path: read lock sufficient; no modification needed.
-            let guard = shard.lock.read();
+            let (shard, guard) = self.read_shard(k);
             match guard.get(k) {
                 None => {
                     drop(guard);
@@ -1234,8 +1514,7 @@ where
     /// hits/misses counters, or removes the entry. Returns `(Some(v), expired)` for a present
     /// entry (expired or not) or `(None, false)` when absent.
     fn cache_peek_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        let (_, guard) = self.read_shard(k);
         match guard.get(k) {
             None => (None, false),
             Some(entry) => {
@@ -1246,6 +1525,44 @@ where
     }
 }
 
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K, V, H> ConcurrentCachedSnapshot<K, V> for ShardedTtlCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn snapshot(&self) -> CacheSnapshot<K, V> {
+        let clock = SnapshotClock::now();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let guard = shard.read();
+                guard
+                    .iter()
+                    .filter_map(|(k, e)| clock.entry(k.clone(), e.value.clone(), e.expires_at))
+                    .collect()
+            })
+            .collect();
+        CacheSnapshot::from_shards(shards)
+    }
+
+    /// Restored entries keep their recorded deadline rather than starting a fresh TTL.
+    fn restore(&self, snapshot: CacheSnapshot<K, V>) -> usize {
+        let clock = SnapshotClock::now();
+        let mut restored = 0;
+        for (key, value, expires_at) in snapshot.into_live(clock) {
+            let _ = self.set_entry(key, TimedEntry { expires_at, value }, clock.instant());
+            restored += 1;
+        }
+        restored
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1614,6 +1931,7 @@ mod tests {
                     cache
                         .inner
                         .shards
+                        .table()
                         .iter()
                         .all(|s| s.lock.try_write().is_some()),
                     "on_evict must fire after the shard write lock is released"
@@ -1650,8 +1968,7 @@ mod tests {
         // Backdate key 1's expiry directly in its shard so it is expired without sleeping:
         // `retain` samples its own `now` after this line, and `now >= expires_at` is expired.
         {
-            let shard = c.shard_of(&1);
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = c.write_shard(&1);
             let entry = guard.get_mut(&1).expect("key 1 stored");
             entry.expires_at = Some(Instant::now());
         }
@@ -1689,8 +2006,7 @@ mod tests {
 
         // evict() path: pin key 1's expiry to "now".
         {
-            let shard = c.shard_of(&1);
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = c.write_shard(&1);
             let entry = guard.get_mut(&1).expect("key 1 stored");
             entry.expires_at = Some(Instant::now());
         }
@@ -1704,8 +2020,7 @@ mod tests {
 
         // retain() path: symmetric case with a fresh entry pinned the same way.
         {
-            let shard = c.shard_of(&2);
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = c.write_shard(&2);
             let entry = guard.get_mut(&2).expect("key 2 stored");
             entry.expires_at = Some(Instant::now());
         }
@@ -2186,6 +2501,7 @@ mod tests {
     fn shard_eviction_counters<K, V, H>(c: &ShardedTtlCache<K, V, H>) -> Vec<u64> {
         c.inner
             .shards
+            .table()
             .iter()
             .map(|s| s.evictions.load(Ordering::Relaxed))
             .collect()
@@ -2193,7 +2509,7 @@ mod tests {
 
     /// Index of the shard that owns `k`.
     fn owning_shard<K, V, H: ShardHasher<K>>(c: &ShardedTtlCache<K, V, H>, k: &K) -> usize {
-        shard_index(c.inner.hasher.shard_hash(k), c.inner.shard_mask)
+        crate::stores::sharded::shard_index(c.inner.hasher.shard_hash(k), c.inner.shards.len() - 1)
     }
 
     #[test]
@@ -2535,8 +2851,7 @@ mod tests {
             // Backdate the even keys so exactly half the entries are expired, without sleeping.
             let past = Instant::now();
             for i in (0..8u32).step_by(2) {
-                let shard = c.shard_of(&i);
-                let mut guard = shard.lock.write();
+                let (_, mut guard) = c.write_shard(&i);
                 guard.get_mut(&i).expect("key stored").expires_at = Some(past);
             }
             assert_eq!(c.evict(), 4, "(with_callback={with_callback})");
@@ -2603,8 +2918,7 @@ mod tests {
         SyncConcurrentCached::cache_set(&c, 1, 10).expect("insert must succeed");
         SyncConcurrentCached::cache_set(&c, 2, 20).expect("insert must succeed");
         {
-            let shard = c.shard_of(&1);
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = c.write_shard(&1);
             guard.get_mut(&1).expect("key 1 stored").expires_at = Some(Instant::now());
         }
         assert_eq!(
@@ -2636,8 +2950,7 @@ mod tests {
         SyncConcurrentCached::cache_set(&c, 1, 10).expect("insert must succeed");
         SyncConcurrentCached::cache_set(&c, 2, 20).expect("insert must succeed");
         {
-            let shard = c.shard_of(&1);
-            let mut guard = shard.lock.write();
+            let (_, mut guard) = c.write_shard(&1);
             guard.get_mut(&1).expect("key 1 stored").expires_at = Some(Instant::now());
         }
         assert_eq!(
@@ -2649,14 +2962,12 @@ mod tests {
 
         // The live entry is renewed: its expires_at moves forward on the hit.
         let before = {
-            let shard = c.shard_of(&2);
-            let guard = shard.lock.read();
+            let (_, guard) = c.read_shard(&2);
             guard.get(&2).expect("key 2 stored").expires_at
         };
         assert_eq!(SyncConcurrentCached::cache_get(&c, &2).unwrap(), Some(20));
         let after = {
-            let shard = c.shard_of(&2);
-            let guard = shard.lock.read();
+            let (_, guard) = c.read_shard(&2);
             guard.get(&2).expect("key 2 stored").expires_at
         };
         assert!(
@@ -2681,8 +2992,7 @@ mod tests {
                 .unwrap();
             SyncConcurrentCached::cache_set(&c, 1, 10).expect("insert must succeed");
             {
-                let shard = c.shard_of(&1);
-                let mut guard = shard.lock.write();
+                let (_, mut guard) = c.write_shard(&1);
                 guard.get_mut(&1).expect("key 1 stored").expires_at = Some(Instant::now());
             }
             assert_eq!(
@@ -2726,8 +3036,8 @@ mod tests {
         c: &ShardedTtlCache<u32, u32, H>,
     ) -> Vec<(u32, u32, bool)> {
         let mut out: Vec<(u32, u32, bool)> = Vec::new();
-        for shard in c.inner.shards.iter() {
-            let guard = shard.lock.read();
+        for shard in c.inner.shards.table().iter() {
+            let guard = shard.read();
             for (k, e) in guard.iter() {
                 out.push((*k, e.value, e.expires_at.is_some()));
             }
@@ -2742,8 +3052,7 @@ mod tests {
         k: u32,
         expires_at: Option<Instant>,
     ) {
-        let shard = c.shard_of(&k);
-        let mut guard = shard.lock.write();
+        let (_, mut guard) = c.write_shard(&k);
         guard.get_mut(&k).expect("key stored").expires_at = expires_at;
     }
 
@@ -2752,8 +3061,7 @@ mod tests {
         c: &ShardedTtlCache<u32, u32, H>,
         k: u32,
     ) -> Option<Option<Instant>> {
-        let shard = c.shard_of(&k);
-        let guard = shard.lock.read();
+        let (_, guard) = c.read_shard(&k);
         guard.get(&k).map(|e| e.expires_at)
     }
 
@@ -0,0 +1,1155 @@
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::time::{Duration, Instant};
+use crate::{
+    CacheMetrics, Cached, CachedPeek, ConcurrentCacheBase, ConcurrentCacheEvict,
+    ConcurrentCachePeek, ConcurrentCacheTtl, ConcurrentCached, ConcurrentCloneCached,
+};
+#[cfg(feature = "async_core")]
+use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
+#[cfg(feature = "async_core")]
+use core::future::Future;
+
+use super::{
+    CachePadded, DefaultShardHasher, EvictCursor, Shard, ShardHasher, ShardRead, ShardSet,
+    ShardStats, ShardWrite, checked_per_shard_cap_from_total, checked_shard_count, decode_ttl,
+    default_shard_count_for_capacity, encode_ttl, per_shard_cap_from_total,
+};
+use crate::stores::{BuildError, ConcurrentCachedTags, EvictProgress, TagIndex, TtlSortedCache};
+
+type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
+
+struct TtlSortedInner<K, V, H> {
+    shards: ShardSet<TtlSortedCache<K, V>>,
+    hasher: H,
+    on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
+    /// Default TTL in nanoseconds, `0` when expiry is disabled. Every insert passes it to its
+    /// shard explicitly, so the shards' own `ttl` fields are never consulted.
+    ttl_nanos: AtomicU64,
+    /// Total logical capacity (sum of per-shard caps), `0` when the cache is unbounded.
+    total_capacity: AtomicUsize,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    /// Where the next [`evict_budgeted`](ShardedTtlSortedCache::evict_budgeted) step starts.
+    /// Only the shard is used: a shard's expired entries are always the front of its index.
+    evict_cursor: parking_lot::Mutex<EvictCursor>,
+}
+
+/// A fully-concurrent, partitioned [`TtlSortedCache`]: each shard keeps its own
+/// deadline-ordered index, so entries can carry their own TTL through
+/// [`set_with`](Self::set_with) and a full shard drops its next entry to expire.
+///
+/// Wraps an `Arc` — `clone()` is an Arc-share (shared state), not a deep copy.
+///
+/// Read hits take only the shard's **read** lock; an expired entry found by
+/// [`cache_get`](ConcurrentCached::cache_get) is removed under the write lock, like
+/// [`ShardedTtlCache`](crate::ShardedTtlCache). [`evict`](Self::evict) reads each shard's
+/// soonest deadline first and write-locks only the shards that have something to drop, and
+/// then pops just the expired front of the index.
+///
+/// `max_size` is optional, as on `TtlSortedCache`; when set it is split across shards like
+/// [`ShardedLruCacheBuilder::max_size`](crate::ShardedLruCacheBuilder::max_size), including
+/// the 16-per-shard floor. `K` must be `Ord + Clone` and `V` must be `Clone`.
+///
+/// **Note**: the inherent `get`, `set`, `remove`, ... return unwrapped values and take
+/// call-site priority over the same-named [`ConcurrentCached`] trait methods, as on
+/// `ShardedTtlCache`.
+#[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
+pub struct ShardedTtlSortedCache<K, V, H = DefaultShardHasher> {
+    inner: Arc<TtlSortedInner<K, V, H>>,
+}
+
+impl<K, V, H> Clone for ShardedTtlSortedCache<K, V, H> {
+    /// Arc-share clone — both handles point to the same underlying cache.
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<K, V, H> std::fmt::Debug for ShardedTtlSortedCache<K, V, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardedTtlSortedCache")
+            .field("shards", &self.inner.shards.len())
+            .field(
+                "ttl",
+                &decode_ttl(self.inner.ttl_nanos.load(Ordering::Relaxed)),
+            )
+            .field(
+                "capacity",
+                &self.inner.total_capacity.load(Ordering::Relaxed),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> ShardedTtlSortedCache<K, V, DefaultShardHasher>
+where
+    K: Hash + Eq + Ord + Clone,
+{
+    /// Construct a ready-to-use [`ShardedTtlSortedCache`] with the given default `ttl`, no
+    /// size bound, and the default hasher and shard count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ttl` is zero. Use [`builder`](Self::builder) to handle that case.
+    #[must_use]
+    pub fn new(ttl: Duration) -> ShardedTtlSortedCache<K, V> {
+        Self::builder()
+            .ttl(ttl)
+            .build()
+            .expect("ShardedTtlSortedCache::new requires a non-zero ttl")
+    }
+
+    /// Return a builder for constructing a [`ShardedTtlSortedCache`].
+    #[must_use]
+    pub fn builder() -> ShardedTtlSortedCacheBuilder<K, V, DefaultShardHasher> {
+        ShardedTtlSortedCacheBuilder::default()
+    }
+}
+
+impl<K, V, H> ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    H: ShardHasher<K>,
+{
+    #[inline]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, TtlSortedCache<K, V>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, TtlSortedCache<K, V>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
+    }
+
+    /// The TTL handed to a shard for an insert without an override; zero means never expires.
+    #[inline]
+    fn default_ttl(&self) -> Duration {
+        Duration::from_nanos(self.inner.ttl_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Fire `on_evict` for pairs removed under a shard lock that has since been released.
+    fn notify(&self, removed: &[(K, V)]) {
+        if let Some(on_evict) = &self.inner.on_evict {
+            for (k, v) in removed {
+                on_evict(k, v);
+            }
+        }
+    }
+
+    /// Start an insert with a per-entry TTL override and/or an expiry sweep of the key's
+    /// shard, e.g. `cache.set_with(k, v).ttl(Duration::from_secs(5)).set()`.
+    ///
+    /// Works through `&self` like every other method here; the shard's write lock is taken
+    /// only by the terminal [`.set()`](ShardedTtlSortedSetBuilder::set).
+    #[must_use = "set_with does nothing until .set() is called"]
+    pub fn set_with(&self, key: K, value: V) -> ShardedTtlSortedSetBuilder<'_, K, V, H> {
+        ShardedTtlSortedSetBuilder {
+            cache: self,
+            key,
+            value,
+            ttl: None,
+            evict: false,
+        }
+    }
+}
+
+impl<K, V, H> ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    /// Return an independent deep copy of this cache — entries, deadlines and metrics are
+    /// duplicated, not shared. In most cases [`Clone::clone`] (Arc-share) is what you want.
+    #[must_use]
+    pub fn deep_clone(&self) -> Self {
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let guard = shard.read();
+                let store_copy = guard.clone();
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
+                drop(guard);
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
+                CachePadded(shard)
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            inner: Arc::new(TtlSortedInner {
+                shards: ShardSet::new(shards),
+                hasher: self.inner.hasher.clone(),
+                on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
+                ttl_nanos: AtomicU64::new(self.inner.ttl_nanos.load(Ordering::Relaxed)),
+                total_capacity: AtomicUsize::new(self.inner.total_capacity.load(Ordering::Relaxed)),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                evict_cursor: parking_lot::Mutex::default(),
+            }),
+        }
+    }
+
+    /// Retrieve a live cached value, returning `None` on a miss.
+    ///
+    /// This is the infallible ergonomic API for the concrete type.
+    #[must_use]
+    pub fn get(&self, k: &K) -> Option<V> {
+        ConcurrentCached::cache_get(self, k).unwrap()
+    }
+
+    /// Insert a key-value pair with the default TTL and return the previous live value, if
+    /// any.
+    ///
+    /// This is the infallible ergonomic API for the concrete type; `.set(k, v).unwrap()`
+    /// panics on a fresh insert.
+    pub fn set(&self, k: K, v: V) -> Option<V> {
+        ConcurrentCached::cache_set(self, k, v).unwrap()
+    }
+
+    /// Return the cached value for `k`, or compute `f()`, store it, and return it.
+    ///
+    /// Non-atomic get-then-set, like
+    /// [`ConcurrentCached::cache_get_or_set_with`](crate::ConcurrentCached::cache_get_or_set_with).
+    pub fn get_or_set_with<F: FnOnce() -> V>(&self, k: K, f: F) -> V {
+        ConcurrentCached::cache_get_or_set_with(self, k, f).unwrap()
+    }
+
+    /// Remove a cached value and return it if the entry was present and live.
+    pub fn remove(&self, k: &K) -> Option<V> {
+        ConcurrentCached::cache_remove(self, k).unwrap()
+    }
+
+    /// Remove a cached entry and return the stored key and value, expired or not.
+    pub fn remove_entry(&self, k: &K) -> Option<(K, V)> {
+        ConcurrentCached::cache_remove_entry(self, k).unwrap()
+    }
+
+    /// Delete a cached entry without returning the value. Returns `true` if an entry was removed.
+    pub fn delete(&self, k: &K) -> bool {
+        ConcurrentCached::cache_delete(self, k).unwrap()
+    }
+
+    /// Remove all entries from every shard and reset metrics.
+    pub fn reset(&self) {
+        ConcurrentCached::cache_reset(self).unwrap()
+    }
+
+    /// Return true if a live value is stored for `k`. Peek-based: no hit/miss metrics.
+    #[must_use]
+    pub fn contains(&self, k: &K) -> bool {
+        ConcurrentCached::cache_contains(self, k).unwrap()
+    }
+
+    /// Return a clone of the live value stored for `k` under the shard's read lock, without
+    /// recording a hit or miss.
+    #[must_use]
+    pub fn peek(&self, k: &K) -> Option<V> {
+        self.read_shard(k).1.cache_peek(k).cloned()
+    }
+}
+
+impl<K, V, H> ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    H: ShardHasher<K>,
+{
+    /// Return aggregate metrics across all shards. Approximate under concurrent mutation.
+    #[must_use]
+    pub fn metrics(&self) -> CacheMetrics {
+        let mut hits = 0u64;
+        let mut misses = 0u64;
+        let mut evictions = 0u64;
+        let mut size = 0usize;
+        for shard in self.inner.shards.table().iter() {
+            hits += shard.hits.load(Ordering::Relaxed);
+            misses += shard.misses.load(Ordering::Relaxed);
+            let guard = shard.read();
+            evictions += guard.cache_evictions().unwrap_or(0);
+            size += guard.cache_size();
+        }
+        CacheMetrics {
+            hits: Some(hits),
+            misses: Some(misses),
+            evictions: Some(evictions),
+            entry_count: Some(size),
+            capacity: self.capacity(),
+            mem_bytes: Some(self.shard_memory_usage().into_iter().sum()),
+        }
+    }
+
+    /// Number of shards.
+    #[must_use]
+    pub fn shards(&self) -> usize {
+        self.inner.shards.len()
+    }
+
+    /// Per-shard stored entry counts, which may include expired entries not yet swept.
+    #[must_use]
+    pub fn shard_sizes(&self) -> Vec<usize> {
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|s| s.read().cache_size())
+            .collect()
+    }
+
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage).
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| size_of_val(shard) + shard.read().cache_memory_usage().unwrap_or(0))
+            .collect()
+    }
+
+    /// Per-shard entry counts, hit/miss counters and lock-wait figures; see [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(|store| store.cache_size())
+    }
+
+    /// Total number of stored entries across all shards, including expired entries not yet
+    /// swept by [`evict`](Self::evict). Approximate under concurrent mutation.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shard_sizes().into_iter().sum()
+    }
+
+    /// `true` if no entries are present. Approximate under concurrent mutation.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .all(|s| s.read().cache_size() == 0)
+    }
+
+    /// Remove all entries from every shard. Does **not** fire `on_evict`.
+    pub fn clear(&self) {
+        for shard in self.inner.shards.table().iter() {
+            shard.write().cache_clear();
+        }
+    }
+
+    /// Remove all entries from every shard, counting each as an eviction and firing
+    /// `on_evict` for it after the shard's lock is released.
+    pub fn cache_clear_with_on_evict(&self) {
+        for shard in self.inner.shards.table().iter() {
+            let removed = {
+                let mut guard = shard.write();
+                let removed = guard.drain_all();
+                guard.add_evictions(removed.len() as u64);
+                removed
+            };
+            self.notify(&removed);
+        }
+    }
+
+    /// Remove expired entries from every shard, firing `on_evict` for each after the shard's
+    /// lock is released, and return how many were removed.
+    ///
+    /// A shard whose soonest deadline is still ahead costs one read-locked comparison and is
+    /// never write-locked; otherwise only the expired front of its index is detached, so the
+    /// live entries behind it are not visited. Expiry is judged against one instant sampled at
+    /// the start of the call.
+    #[must_use]
+    pub fn evict(&self) -> usize {
+        let now = Instant::now();
+        let mut total = 0;
+        for shard in self.inner.shards.table().iter() {
+            if !shard.read().has_expired(now) {
+                continue;
+            }
+            let removed = {
+                let mut guard = shard.write();
+                let removed = guard.take_expired(now);
+                guard.add_evictions(removed.len() as u64);
+                removed
+            };
+            total += removed.len();
+            self.notify(&removed);
+        }
+        total
+    }
+
+    /// Take one step of an incremental [`evict`](Self::evict): remove at most `max_entries`
+    /// expired entries, taking each shard's in turn, and report how many were removed and
+    /// whether the sweep got past the last shard.
+    ///
+    /// As with `evict`, only the expired front of each shard's index is visited, so the
+    /// budget is spent on expired entries alone. See
+    /// [`ConcurrentCacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        let now = Instant::now();
+        super::evict_budgeted(
+            &self.inner.shards,
+            &self.inner.evict_cursor,
+            max_entries,
+            |store, _, budget| {
+                let (removed, taken) = store.take_expired_some(now, budget);
+                store.add_evictions(removed.len() as u64);
+                (removed, taken, 0)
+            },
+            |_, removed| self.notify(&removed),
+        )
+    }
+
+    /// Remove expired entries and every entry for which `keep` returns `false`, shard by
+    /// shard, with the semantics of
+    /// [`ShardedTtlCache::retain`](crate::ShardedTtlCache::retain): `keep` runs under the
+    /// shard's write lock and `on_evict` fires after it is released. Returns the number of
+    /// entries removed.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
+        let now = Instant::now();
+        let mut total_removed = 0;
+        for shard in self.inner.shards.table().iter() {
+            let removed: Vec<(K, V)> = {
+                let mut guard = shard.write();
+                let removed = guard.take_rejected(now, &mut keep);
+                guard.add_evictions(removed.len() as u64);
+                removed
+            };
+            total_removed += removed.len();
+            self.notify(&removed);
+        }
+        total_removed
+    }
+
+    /// Effective total capacity across all shards, or `None` if no size bound is configured.
+    #[doc(alias = "max_size")]
+    #[must_use]
+    pub fn capacity(&self) -> Option<usize> {
+        match self.inner.total_capacity.load(Ordering::Acquire) {
+            0 => None,
+            total => Some(total),
+        }
+    }
+
+    /// Bound the cache to `max_size` entries in total, returning the previous total capacity
+    /// (`None` if it was unbounded). The total is re-split across shards as the builder does
+    /// and each shard shrinks as [`TtlSortedCache::set_max_size`] does, dropping its next
+    /// entries to expire. Not atomic across shards, like
+    /// [`ShardedLruCache::set_max_size`](crate::ShardedLruCache::set_max_size).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0 or the re-split capacity overflows `usize`. Use
+    /// [`try_set_max_size`](Self::try_set_max_size) to avoid either panic.
+    pub fn set_max_size(&self, max_size: usize) -> Option<usize> {
+        assert!(max_size > 0, "max_size must be greater than zero");
+        let table = self.inner.shards.table();
+        let (per_shard_cap, total_cap) = per_shard_cap_from_total(max_size, table.len());
+        for shard in table.iter() {
+            shard.write().set_max_size(per_shard_cap);
+        }
+        match self.inner.total_capacity.swap(total_cap, Ordering::Release) {
+            0 => None,
+            prev => Some(prev),
+        }
+    }
+
+    /// Fallible counterpart of [`set_max_size`](Self::set_max_size).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SetMaxSizeError::ZeroMaxSize`](crate::SetMaxSizeError) if `max_size` is 0, or
+    /// [`SetMaxSizeError::CapacityOverflow`](crate::SetMaxSizeError) if the re-split capacity
+    /// overflows `usize`.
+    pub fn try_set_max_size(
+        &self,
+        max_size: usize,
+    ) -> Result<Option<usize>, crate::SetMaxSizeError> {
+        if max_size == 0 {
+            return Err(crate::SetMaxSizeError::ZeroMaxSize);
+        }
+        checked_per_shard_cap_from_total(max_size, self.inner.shards.len())?;
+        Ok(self.set_max_size(max_size))
+    }
+}
+
+/// Builder returned by [`ShardedTtlSortedCache::set_with`] for chaining a per-entry TTL
+/// override and/or an expiry sweep before performing the insertion.
+///
+/// Nothing is inserted until the terminal [`.set()`](Self::set) is called.
+#[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
+#[must_use = "set_with does nothing until .set() is called"]
+pub struct ShardedTtlSortedSetBuilder<'a, K, V, H = DefaultShardHasher> {
+    cache: &'a ShardedTtlSortedCache<K, V, H>,
+    key: K,
+    value: V,
+    ttl: Option<Duration>,
+    evict: bool,
+}
+
+impl<K, V, H> ShardedTtlSortedSetBuilder<'_, K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    H: ShardHasher<K>,
+{
+    /// Override the cache's default TTL for this entry only; see
+    /// [`TtlSortedSetBuilder::ttl`](crate::TtlSortedSetBuilder::ttl). `Duration::ZERO` stores
+    /// the entry with no expiry.
+    ///
+    /// Overrides any previously set ttl/ttl_secs/ttl_millis on this builder.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Override the cache's default TTL for this entry only, in whole seconds.
+    /// Equivalent to `ttl(Duration::from_secs(secs))`.
+    pub fn ttl_secs(self, secs: u64) -> Self {
+        self.ttl(Duration::from_secs(secs))
+    }
+
+    /// Override the cache's default TTL for this entry only, in milliseconds.
+    /// Equivalent to `ttl(Duration::from_millis(millis))`.
+    pub fn ttl_millis(self, millis: u64) -> Self {
+        self.ttl(Duration::from_millis(millis))
+    }
+
+    /// Also sweep the expired entries of the shard this key lands in, under the same write
+    /// lock as the insert. Their `on_evict` calls run after the lock is released. Other
+    /// shards are left for [`ShardedTtlSortedCache::evict`].
+    pub fn evict(mut self) -> Self {
+        self.evict = true;
+        self
+    }
+
+    /// Perform the insertion. Returns the value that was replaced if it was still live, or
+    /// `None` if the key was absent or its entry had already expired.
+    pub fn set(self) -> Option<V> {
+        let cache = self.cache;
+        let ttl = self.ttl.unwrap_or_else(|| cache.default_ttl());
+        let (displaced, swept) = {
+            let (_, mut guard) = cache.write_shard(&self.key);
+            let displaced = guard.set_for(self.key, self.value, ttl);
+            let swept = if self.evict {
+                let swept = guard.take_expired(Instant::now());
+                guard.add_evictions(swept.len() as u64);
+                swept
+            } else {
+                Vec::new()
+            };
+            (displaced, swept)
+        };
+        cache.notify(&swept);
+        displaced
+    }
+}
+
+impl<K, V, H> ConcurrentCacheBase for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    type Error = std::convert::Infallible;
+
+    fn cache_size(&self) -> Result<Option<usize>, Self::Error> {
+        Ok(Some(self.len()))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum())
+    }
+
+    fn cache_hits(&self) -> Option<u64> {
+        Some(
+            self.inner
+                .shards
+                .table()
+                .iter()
+                .map(|s| s.hits.load(Ordering::Relaxed))
+                .sum(),
+        )
+    }
+
+    fn cache_misses(&self) -> Option<u64> {
+        Some(
+            self.inner
+                .shards
+                .table()
+                .iter()
+                .map(|s| s.misses.load(Ordering::Relaxed))
+                .sum(),
+        )
+    }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
+
+    fn cache_capacity(&self) -> Option<usize> {
+        self.capacity()
+    }
+
+    fn cache_evictions(&self) -> Option<u64> {
+        Some(
+            self.inner
+                .shards
+                .table()
+                .iter()
+                .map(|s| s.read().cache_evictions().unwrap_or(0))
+                .sum(),
+        )
+    }
+}
+
+impl<K, V, H> ConcurrentCacheTtl for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn ttl(&self) -> Option<Duration> {
+        decode_ttl(self.inner.ttl_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Set the default TTL for future inserts. Stored entries keep their deadlines, so the
+    /// shard indexes stay sorted.
+    fn set_ttl(&self, ttl: Duration) -> Option<Duration> {
+        decode_ttl(
+            self.inner
+                .ttl_nanos
+                .swap(encode_ttl(ttl), Ordering::Relaxed),
+        )
+    }
+
+    fn unset_ttl(&self) -> Option<Duration> {
+        decode_ttl(self.inner.ttl_nanos.swap(0, Ordering::Relaxed))
+    }
+}
+
+impl<K, V, H> ConcurrentCached<K, V> for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.record(self.inner.hasher.shard_hash(k), || Some(k));
+        }
+        let now = Instant::now();
+        let (shard, guard) = self.read_shard(k);
+        let (value, expired) = match guard.peek_raw(k, now) {
+            Some((value, false)) => (Some(value.clone()), false),
+            Some((_, true)) => (None, true),
+            None => (None, false),
+        };
+        drop(guard);
+        if !expired {
+            let counter = if value.is_some() {
+                &shard.hits
+            } else {
+                &shard.misses
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+        // Expired under the read lock: re-check under the write lock, since another thread
+        // may have replaced the entry in between, and remove it only if it is still expired.
+        let (shard, mut guard) = self.write_shard(k);
+        if let Some((value, false)) = guard.peek_raw(k, now) {
+            let value = value.clone();
+            drop(guard);
+            shard.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(value));
+        }
+        let removed = guard.pop_raw(k, now).map(|(k, v, _)| (k, v));
+        if removed.is_some() {
+            guard.add_evictions(1);
+        }
+        drop(guard);
+        if let Some(pair) = &removed {
+            self.notify(std::slice::from_ref(pair));
+        }
+        shard.misses.fetch_add(1, Ordering::Relaxed);
+        Ok(None)
+    }
+
+    fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
+        let ttl = self.default_ttl();
+        Ok(self.write_shard(&k).1.set_for(k, v, ttl))
+    }
+
+    fn cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        let removed = {
+            let (_, mut guard) = self.write_shard(k);
+            let removed = guard.pop_raw(k, Instant::now());
+            if removed.is_some() {
+                guard.add_evictions(1);
+            }
+            removed
+        };
+        let Some((key, value, expired)) = removed else {
+            return Ok(None);
+        };
+        if let Some(on_evict) = &self.inner.on_evict {
+            on_evict(&key, &value);
+        }
+        Ok((!expired).then_some(value))
+    }
+
+    fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
+        let removed = {
+            let (_, mut guard) = self.write_shard(k);
+            let removed = guard.pop_raw(k, Instant::now()).map(|(k, v, _)| (k, v));
+            if removed.is_some() {
+                guard.add_evictions(1);
+            }
+            removed
+        };
+        if let Some(pair) = &removed {
+            self.notify(std::slice::from_ref(pair));
+        }
+        Ok(removed)
+    }
+
+    fn cache_clear(&self) -> Result<(), Self::Error> {
+        self.clear();
+        Ok(())
+    }
+
+    fn cache_reset(&self) -> Result<(), Self::Error> {
+        self.clear();
+        ConcurrentCached::cache_reset_metrics(self)
+    }
+
+    fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
+            shard.write().cache_reset_metrics();
+        }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
+        }
+        Ok(())
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    /// Peek-based: read lock only, no clone, no hit/miss metrics. Expired entries report
+    /// `false`.
+    fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
+        Ok(self.read_shard(k).1.cache_peek(k).is_some())
+    }
+}
+
+impl<K, V, H> ConcurrentCacheEvict for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    H: ShardHasher<K>,
+{
+    fn evict(&self) -> usize {
+        ShardedTtlSortedCache::evict(self)
+    }
+
+    fn evict_budgeted(&self, max_entries: usize) -> EvictProgress {
+        ShardedTtlSortedCache::evict_budgeted(self, max_entries)
+    }
+}
+
+impl<K, V, H> ConcurrentCloneCached<K, V> for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    /// Returns `(Some(v), false)` for a live entry (hit), `(Some(v), true)` for an expired
+    /// entry (miss, **no removal**, no eviction counter), or `(None, false)` when absent (miss).
+    /// Takes only the shard's read lock.
+    fn cache_get_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
+        let (shard, guard) = self.read_shard(k);
+        let found = guard
+            .peek_raw(k, Instant::now())
+            .map(|(value, expired)| (value.clone(), expired));
+        drop(guard);
+        let counter = match found {
+            Some((_, false)) => &shard.hits,
+            _ => &shard.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        match found {
+            Some((value, expired)) => (Some(value), expired),
+            None => (None, false),
+        }
+    }
+
+    /// Read-locked peek with no side effects: returns `(Some(v), expired)` for a present
+    /// entry or `(None, false)` when absent.
+    fn cache_peek_with_expiry_status(&self, k: &K) -> (Option<V>, bool) {
+        match self.read_shard(k).1.peek_raw(k, Instant::now()) {
+            Some((value, expired)) => (Some(value.clone()), expired),
+            None => (None, false),
+        }
+    }
+}
+
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| self.contains(k));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+impl<K, V, H> ConcurrentCachePeek<K, V> for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn cache_peek(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        Ok(self.peek(k))
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, H> ConcurrentCachePeekAsync<K, V> for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+    H: ShardHasher<K>,
+{
+    async fn async_cache_peek(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCachePeek::cache_peek(self, k)
+    }
+}
+
+#[cfg(feature = "async_core")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_core")))]
+impl<K, V, H> ConcurrentCachedAsync<K, V> for ShardedTtlSortedCache<K, V, H>
+where
+    K: Hash + Eq + Ord + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+    H: ShardHasher<K>,
+{
+    async fn async_cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_get(self, k)
+    }
+
+    async fn async_cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    async fn async_cache_remove(&self, k: &K) -> Result<Option<V>, Self::Error> {
+        ConcurrentCached::cache_remove(self, k)
+    }
+
+    async fn async_cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
+        ConcurrentCached::cache_remove_entry(self, k)
+    }
+
+    async fn async_cache_clear(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_clear(self)
+    }
+
+    async fn async_cache_reset(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_reset(self)
+    }
+
+    async fn async_cache_reset_metrics(&self) -> Result<(), Self::Error> {
+        ConcurrentCached::cache_reset_metrics(self)
+    }
+
+    fn async_cache_contains(&self, k: &K) -> impl Future<Output = Result<bool, Self::Error>> + Send
+    where
+        Self: Sized + Sync,
+        K: Sync,
+    {
+        let result = ConcurrentCached::cache_contains(self, k);
+        async move { result }
+    }
+}
+
+/// Builder for [`ShardedTtlSortedCache`].
+#[cfg_attr(docsrs, doc(cfg(feature = "time_stores")))]
+pub struct ShardedTtlSortedCacheBuilder<K, V, H = DefaultShardHasher> {
+    shards: Option<usize>,
+    max_size: Option<usize>,
+    per_shard_max_size: Option<usize>,
+    ttl: Option<Duration>,
+    hasher: H,
+    on_evict: Option<OnEvict<K, V>>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
+}
+
+impl<K, V> Default for ShardedTtlSortedCacheBuilder<K, V, DefaultShardHasher> {
+    fn default() -> Self {
+        Self {
+            shards: None,
+            max_size: None,
+            per_shard_max_size: None,
+            ttl: None,
+            hasher: DefaultShardHasher::default(),
+            on_evict: None,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
+        }
+    }
+}
+
+impl<K, V> ShardedTtlSortedCacheBuilder<K, V> {
+    /// Create a builder with default settings. Equivalent to
+    /// [`ShardedTtlSortedCache::builder`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, H> ShardedTtlSortedCacheBuilder<K, V, H> {
+    /// Set the default TTL for cache entries. Required.
+    ///
+    /// Overrides any previously set ttl/ttl_secs/ttl_millis on this builder.
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set the default TTL in whole seconds. Equivalent to `ttl(Duration::from_secs(secs))`.
+    #[must_use]
+    pub fn ttl_secs(self, secs: u64) -> Self {
+        self.ttl(Duration::from_secs(secs))
+    }
+
+    /// Set the default TTL in milliseconds. Equivalent to
+    /// `ttl(Duration::from_millis(millis))`.
+    #[must_use]
+    pub fn ttl_millis(self, millis: u64) -> Self {
+        self.ttl(Duration::from_millis(millis))
+    }
+
+    /// Bound the total number of entries, divided across shards as
+    /// [`ShardedLruCacheBuilder::max_size`](crate::ShardedLruCacheBuilder::max_size) does. A
+    /// full shard drops its next entry to expire. Optional; mutually exclusive with
+    /// [`per_shard_max_size`](Self::per_shard_max_size).
+    #[doc(alias = "size")]
+    #[doc(alias = "capacity")]
+    #[must_use]
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Set per-shard capacity directly. Mutually exclusive with [`max_size`](Self::max_size).
+    #[must_use]
+    pub fn per_shard_max_size(mut self, per_shard_max_size: usize) -> Self {
+        self.per_shard_max_size = Some(per_shard_max_size);
+        self
+    }
+
+    /// Set the number of shards (rounded up to the next power of two).
+    #[must_use]
+    pub fn shards(mut self, shards: usize) -> Self {
+        self.shards = Some(shards);
+        self
+    }
+
+    /// Set a custom shard-selection hasher, changing the type parameter. See
+    /// [`ShardedLruCacheBuilder::hasher`](crate::ShardedLruCacheBuilder::hasher).
+    #[doc(alias = "with_hasher")]
+    #[must_use]
+    pub fn hasher<H2: ShardHasher<K>>(self, hasher: H2) -> ShardedTtlSortedCacheBuilder<K, V, H2> {
+        ShardedTtlSortedCacheBuilder {
+            shards: self.shards,
+            max_size: self.max_size,
+            per_shard_max_size: self.per_shard_max_size,
+            ttl: self.ttl,
+            hasher,
+            on_evict: self.on_evict,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
+        }
+    }
+
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to the memory estimates, measured through
+    /// their [`DeepSize`](crate::DeepSize) impls.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
+    /// Set a callback invoked when an entry expires, is evicted by capacity pressure, is
+    /// removed, is displaced after expiring, or is cleared through
+    /// [`cache_clear_with_on_evict`](ShardedTtlSortedCache::cache_clear_with_on_evict).
+    ///
+    /// Callbacks for capacity evictions and displaced expired entries run while the affected
+    /// shard's write lock is held; do not call back into the same cache from the callback.
+    /// The others run after the lock is released.
+    #[must_use]
+    pub fn on_evict(mut self, on_evict: impl Fn(&K, &V) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(on_evict));
+        self
+    }
+
+    fn resolve_per_shard_cap(&self, n_shards: usize) -> Result<Option<usize>, BuildError> {
+        match (self.max_size, self.per_shard_max_size) {
+            (Some(_), Some(_)) => Err(BuildError::InvalidValue {
+                field: "max_size / per_shard_max_size",
+                reason: "`max_size` and `per_shard_max_size` are mutually exclusive",
+            }),
+            (None, None) => Ok(None),
+            (Some(0), None) => Err(BuildError::InvalidValue {
+                field: "max_size",
+                reason: "must be greater than zero",
+            }),
+            (None, Some(0)) => Err(BuildError::InvalidValue {
+                field: "per_shard_max_size",
+                reason: "must be greater than zero",
+            }),
+            (Some(total), None) => checked_per_shard_cap_from_total(total, n_shards)
+                .map(|(per_shard, _)| Some(per_shard))
+                .map_err(|_| BuildError::InvalidValue {
+                    field: "max_size",
+                    reason: "effective sharded capacity overflows usize",
+                }),
+            (None, Some(per_shard)) => Ok(Some(per_shard)),
+        }
+    }
+
+    /// Build the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError`] if `ttl` is not set or is zero, both `max_size` and
+    /// `per_shard_max_size` are set, either of them is `0`, `shards` is `0`, or the effective
+    /// capacity overflows `usize`.
+    #[must_use = "the Result from build() must be used"]
+    pub fn build(self) -> Result<ShardedTtlSortedCache<K, V, H>, BuildError>
+    where
+        K: Hash + Eq + Ord + Clone,
+        H: ShardHasher<K>,
+    {
+        let ttl = self.ttl.ok_or(BuildError::MissingRequired("ttl"))?;
+        crate::stores::validate_ttl(ttl)?;
+        let n = match self.shards {
+            Some(_) => checked_shard_count(self.shards)?,
+            None => default_shard_count_for_capacity(self.max_size),
+        };
+        let per_shard_cap = self.resolve_per_shard_cap(n)?;
+        let total_cap = match per_shard_cap {
+            Some(per_shard) => n.checked_mul(per_shard).ok_or(BuildError::InvalidValue {
+                field: "per_shard_max_size",
+                reason: "effective sharded capacity overflows usize",
+            })?,
+            None => 0,
+        };
+        let shards = (0..n)
+            .map(|_| {
+                let mut builder = TtlSortedCache::builder().ttl(ttl);
+                if let Some(cap) = per_shard_cap {
+                    builder = builder.max_size(cap);
+                }
+                let mut store = builder.build()?;
+                store.on_evict = self.on_evict.clone();
+                store.deep_size = self.deep_size;
+                Ok(CachePadded(Shard::new(store)))
+            })
+            .collect::<Result<Vec<_>, BuildError>>()?
+            .into_boxed_slice();
+        Ok(ShardedTtlSortedCache {
+            inner: Arc::new(TtlSortedInner {
+                shards: ShardSet::new(shards),
+                hasher: self.hasher,
+                on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
+                ttl_nanos: AtomicU64::new(encode_ttl(ttl)),
+                total_capacity: AtomicUsize::new(total_cap),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                evict_cursor: parking_lot::Mutex::default(),
+            }),
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use std::hash::Hash;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::Ordering;
 
 #[cfg(feature = "ahash")]
 use ahash::RandomState;
@@ -16,18 +16,25 @@ use crate::{ConcurrentCachePeekAsync, ConcurrentCachedAsync};
 use core::future::Future;
 
 use super::{
-    CachePadded, DefaultShardHasher, Shard, ShardHasher, checked_shard_count, shard_index,
+    CachePadded, DefaultShardHasher, Shard, ShardHasher, ShardRead, ShardSet, ShardStats,
+    ShardWrite, checked_reshard_count, checked_shard_count,
 };
-use crate::stores::BuildError;
+use crate::stores::{BuildError, ConcurrentCachedTags, ReshardError, TagIndex};
+#[cfg(feature = "persist")]
+use crate::stores::{CacheSnapshot, ConcurrentCachedSnapshot, SnapshotClock};
 
 type OnEvict<K, V> = Arc<dyn Fn(&K, &V) + Send + Sync>;
 
 #[allow(clippy::type_complexity)]
 struct UnboundInner<K, V, H> {
-    shards: Box<[CachePadded<Shard<HashMap<K, V, RandomState>>>]>,
-    shard_mask: usize,
+    shards: ShardSet<HashMap<K, V, RandomState>>,
     hasher: H,
     on_evict: Option<OnEvict<K, V>>,
+    /// Tag index for [`ConcurrentCachedTags`]; locked before any shard, never after.
+    tags: parking_lot::Mutex<TagIndex<K>>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeys<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
 }
 
 /// A fully-concurrent, partitioned, unbounded in-memory cache.
@@ -110,9 +117,13 @@ where
     H: ShardHasher<K>,
 {
     #[inline]
-    fn shard_of(&self, k: &K) -> &CachePadded<Shard<HashMap<K, V, RandomState>>> {
-        let h = self.inner.hasher.shard_hash(k);
-        &self.inner.shards[shard_index(h, self.inner.shard_mask)]
+    fn read_shard(&self, k: &K) -> ShardRead<'_, HashMap<K, V, RandomState>> {
+        self.inner.shards.read(self.inner.hasher.shard_hash(k))
+    }
+
+    #[inline]
+    fn write_shard(&self, k: &K) -> ShardWrite<'_, HashMap<K, V, RandomState>> {
+        self.inner.shards.write(self.inner.hasher.shard_hash(k))
     }
 }
 
@@ -147,35 +158,40 @@ impl<K: Clone + Hash + Eq, V: Clone, H: ShardHasher<K>> ShardedUnboundCache<K, V
     /// ```
     #[must_use]
     pub fn deep_clone(&self) -> Self {
-        let n = self.inner.shards.len();
-        let shards = (0..n)
-            .map(|i| {
-                let guard = self.inner.shards[i].lock.read();
+        // Held across the shard copies so the copied index covers every copied entry.
+        let tags = self.inner.tags.lock();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let guard = shard.read();
                 let store_copy = guard.clone();
                 // Load the hit/miss atomics while still holding the shard read
                 // lock, matching ShardedLruCache::deep_clone (src/stores/sharded/
                 // lru.rs): dropping the guard first would let a concurrent writer
                 // mutate the entries and bump the counters in between, pairing a
                 // stale entry snapshot with newer metrics (C7).
-                let hits = self.inner.shards[i].hits.load(Ordering::Relaxed);
-                let misses = self.inner.shards[i].misses.load(Ordering::Relaxed);
+                let hits = shard.hits.load(Ordering::Relaxed);
+                let misses = shard.misses.load(Ordering::Relaxed);
                 drop(guard);
-                let shard = Shard {
-                    lock: parking_lot::RwLock::new(store_copy),
-                    hits: AtomicU64::new(hits),
-                    misses: AtomicU64::new(misses),
-                    evictions: AtomicU64::new(0),
-                };
+                let shard = Shard::new(store_copy);
+                shard.hits.store(hits, Ordering::Relaxed);
+                shard.misses.store(misses, Ordering::Relaxed);
                 CachePadded(shard)
             })
             .collect::<Vec<_>>()
             .into_boxed_slice();
         Self {
             inner: Arc::new(UnboundInner {
-                shards,
-                shard_mask: self.inner.shard_mask,
+                shards: ShardSet::new(shards),
                 hasher: self.inner.hasher.clone(),
                 on_evict: self.inner.on_evict.clone(),
+                tags: parking_lot::Mutex::new(tags.clone()),
+                metrics_exporter: self.inner.metrics_exporter.clone(),
+                hot_keys: self.inner.hot_keys.clone(),
+                deep_size: self.inner.deep_size,
             }),
         }
     }
@@ -262,7 +278,7 @@ where
     /// per-shard lock.
     #[must_use]
     pub fn peek(&self, k: &K) -> Option<V> {
-        self.shard_of(k).lock.read().get(k).cloned()
+        self.read_shard(k).1.get(k).cloned()
     }
 }
 
@@ -279,10 +295,10 @@ where
         let mut hits = 0u64;
         let mut misses = 0u64;
         let mut size = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             hits += shard.hits.load(Ordering::Relaxed);
             misses += shard.misses.load(Ordering::Relaxed);
-            size += shard.lock.read().len();
+            size += shard.read().len();
         }
         CacheMetrics {
             hits: Some(hits),
@@ -290,6 +306,7 @@ where
             evictions: None,
             entry_count: Some(size),
             capacity: None,
+            mem_bytes: Some(self.shard_memory_usage().into_iter().sum()),
         }
     }
 
@@ -304,18 +321,85 @@ where
     pub fn shard_sizes(&self) -> Vec<usize> {
         self.inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().len())
+            .map(|s| s.read().len())
             .collect()
     }
 
+    /// Per-shard estimated memory usage in bytes; see
+    /// [`cache_memory_usage`](ConcurrentCacheBase::cache_memory_usage). A shard whose share
+    /// of the bytes runs well ahead of its share of the entries holds unusually large values.
+    #[must_use]
+    pub fn shard_memory_usage(&self) -> Vec<usize> {
+        let deep_size = self.inner.deep_size;
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let store = shard.read();
+                let entries =
+                    deep_size.map_or(0, |heap| store.iter().map(|(k, v)| heap.entry(k, v)).sum());
+                size_of_val(shard) + crate::stores::hash_map_bytes(&store) + entries
+            })
+            .collect()
+    }
+
+    /// Per-shard entry counts, hit/miss counters and lock-wait figures; see [`ShardStats`].
+    #[must_use]
+    pub fn shard_stats(&self) -> Vec<ShardStats> {
+        self.inner.shards.stats(HashMap::len)
+    }
+
+    /// Migrate every entry into a new array of `shards` shards (rounded up to a power of two)
+    /// while the cache keeps serving requests.
+    ///
+    /// Old shards are drained one at a time, so a lookup waits only while its own shard's
+    /// entries move; whole-cache calls (`len`, `clear`, `metrics`, ...) wait for the full
+    /// migration. The per-shard hit/miss counters fold into the new shards, so the cache-wide
+    /// totals are unchanged. A no-op when the cache already has that many shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReshardError::ZeroShards`] or [`ReshardError::ShardCountOverflow`] if
+    /// `shards` is zero or overflows when rounded up. Returns [`ReshardError::Reentrant`],
+    /// rather than deadlocking, when called from a callback (such as `on_evict`) that a
+    /// whole-cache call or reshard on this same cache is running.
+    pub fn reshard(&self, shards: usize) -> Result<(), ReshardError>
+    where
+        K: Send + 'static,
+        V: Send + 'static,
+    {
+        let n = checked_reshard_count(shards)?;
+        let hasher = &self.inner.hasher;
+        self.inner.shards.reshard(
+            n,
+            || HashMap::with_hasher(RandomState::new()),
+            |map| {
+                std::mem::take(map)
+                    .into_iter()
+                    .map(|(k, v)| (hasher.shard_hash(&k), (k, v)))
+                    .collect()
+            },
+            |map, (k, v)| {
+                map.insert(k, v);
+            },
+        )
+    }
+
     /// Total number of live entries across all shards.
     ///
     /// Note: the returned value is approximate under concurrent mutation — no global lock is held
     /// across shards; each shard is locked and read one at a time.
     #[must_use]
     pub fn len(&self) -> usize {
-        self.inner.shards.iter().map(|s| s.lock.read().len()).sum()
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .map(|s| s.read().len())
+            .sum()
     }
 
     /// `true` if no entries are present.
@@ -324,14 +408,18 @@ where
     /// across shards; each shard is locked and read one at a time.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.inner.shards.iter().all(|s| s.lock.read().is_empty())
+        self.inner
+            .shards
+            .table()
+            .iter()
+            .all(|s| s.read().is_empty())
     }
 
     /// Remove all entries from every shard. Does **not** fire `on_evict`.
     /// Use [`cache_clear_with_on_evict`](Self::cache_clear_with_on_evict) to opt into callback firing.
     pub fn clear(&self) {
-        for shard in self.inner.shards.iter() {
-            shard.lock.write().clear();
+        for shard in self.inner.shards.table().iter() {
+            shard.write().clear();
         }
     }
 
@@ -348,8 +436,8 @@ where
         if self.inner.on_evict.is_none() {
             return self.clear();
         }
-        for shard in self.inner.shards.iter() {
-            let entries: Vec<(K, V)> = shard.lock.write().drain().collect();
+        for shard in self.inner.shards.table().iter() {
+            let entries: Vec<(K, V)> = shard.write().drain().collect();
             if let Some(on_evict) = &self.inner.on_evict {
                 for (k, v) in &entries {
                     on_evict(k, v);
@@ -393,12 +481,12 @@ where
     /// the panic.
     pub fn retain<F: FnMut(&K, &V) -> bool>(&self, mut keep: F) -> usize {
         let mut total_removed = 0usize;
-        for shard in self.inner.shards.iter() {
+        for shard in self.inner.shards.table().iter() {
             // Collect under the write lock, fire callbacks after releasing it. Two phases: the
             // first runs `keep` (user code) and only selects, the second removes and runs
             // nothing that can panic. See `stores::take_doomed`.
             let removed: Vec<(K, V)> = {
-                let mut guard = shard.lock.write();
+                let mut guard = shard.write();
                 crate::stores::take_doomed(&mut guard, |k, v| !keep(k, v))
             };
             total_removed += removed.len();
@@ -424,10 +512,15 @@ where
         Ok(Some(self.len()))
     }
 
+    fn cache_memory_usage(&self) -> Option<usize> {
+        Some(self.shard_memory_usage().into_iter().sum())
+    }
+
     fn cache_hits(&self) -> Option<u64> {
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.hits.load(Ordering::Relaxed))
                 .sum(),
@@ -438,11 +531,17 @@ where
         Some(
             self.inner
                 .shards
+                .table()
                 .iter()
                 .map(|s| s.misses.load(Ordering::Relaxed))
                 .sum(),
         )
     }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&crate::stores::MetricsExporter> {
+        self.inner.metrics_exporter.as_ref()
+    }
 }
 
 impl<K, V, H> ConcurrentCached<K, V> for ShardedUnboundCache<K, V, H>
@@ -452,8 +551,11 @@ where
     H: ShardHasher<K>,
 {
     fn cache_get(&self, k: &K) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(k);
-        let guard = shard.lock.read();
+        if let Some(hot) = &self.inner.hot_keys {
+            let hash = self.inner.hasher.shard_hash(k);
+            hot.record(hash, || Some(k));
+        }
+        let (shard, guard) = self.read_shard(k);
         let found = guard.get(k).cloned();
         drop(guard);
         match found {
@@ -469,8 +571,7 @@ where
     }
 
     fn cache_set(&self, k: K, v: V) -> Result<Option<V>, Self::Error> {
-        let shard = self.shard_of(&k);
-        let mut guard = shard.lock.write();
+        let (_, mut guard) = self.write_shard(&k);
         // `HashMap::insert` keeps the stored key and drops the caller's.
         Ok(guard.insert(k, v))
     }
@@ -480,8 +581,7 @@ where
     }
 
     fn cache_remove_entry(&self, k: &K) -> Result<Option<(K, V)>, Self::Error> {
-        let shard = self.shard_of(k);
-        let removed = shard.lock.write().remove_entry(k);
+        let removed = self.write_shard(k).1.remove_entry(k);
         if let Some((ref stored_k, ref v)) = removed
             && let Some(on_evict) = &self.inner.on_evict
         {
@@ -501,18 +601,55 @@ where
     }
 
     fn cache_reset_metrics(&self) -> Result<(), Self::Error> {
-        for shard in self.inner.shards.iter() {
-            shard.hits.store(0, Ordering::Relaxed);
-            shard.misses.store(0, Ordering::Relaxed);
+        for shard in self.inner.shards.table().iter() {
+            shard.reset_counters();
+        }
+        if let Some(hot) = &self.inner.hot_keys {
+            hot.reset();
         }
         Ok(())
     }
 
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<crate::stores::HotKey<K>>> {
+        self.inner.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
     /// Efficient peek-based contains: acquires a read lock, does not clone the value,
     /// and does not record hit/miss metrics.
     fn cache_contains(&self, k: &K) -> Result<bool, Self::Error> {
-        let shard = self.shard_of(k);
-        Ok(shard.lock.read().contains_key(k))
+        Ok(self.read_shard(k).1.contains_key(k))
+    }
+}
+
+impl<K, V, H> ConcurrentCachedTags<K, V> for ShardedUnboundCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        if index.is_oversized(self.len()) {
+            index.retain_keys(|k| matches!(ConcurrentCached::cache_contains(self, k), Ok(true)));
+        }
+        index.tag(&k, tags);
+        ConcurrentCached::cache_set(self, k, v)
+    }
+
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error> {
+        let mut index = self.inner.tags.lock();
+        let mut removed = 0;
+        for k in index.take(tag) {
+            if ConcurrentCached::cache_remove(self, &k)?.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
     }
 }
 
@@ -598,6 +735,9 @@ pub struct ShardedUnboundCacheBuilder<K, V, H = DefaultShardHasher> {
     on_evict: Option<OnEvict<K, V>>,
     _k: std::marker::PhantomData<K>,
     _v: std::marker::PhantomData<V>,
+    metrics_exporter: crate::stores::ExporterSlot,
+    hot_keys: Option<crate::stores::HotKeyConfig<K>>,
+    deep_size: Option<crate::stores::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for ShardedUnboundCacheBuilder<K, V, DefaultShardHasher> {
@@ -609,6 +749,9 @@ impl<K, V> Default for ShardedUnboundCacheBuilder<K, V, DefaultShardHasher> {
             on_evict: None,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: crate::stores::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -659,9 +802,59 @@ impl<K, V, H> ShardedUnboundCacheBuilder<K, V, H> {
             on_evict: self.on_evict,
             _k: std::marker::PhantomData,
             _v: std::marker::PhantomData,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`ConcurrentCacheBase::publish_metrics`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(crate::stores::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots per shard,
+    /// keeping a clone of each tracked key. Read them with
+    /// [`top_keys`](crate::ConcurrentCachedExt::top_keys), which merges the shards' summaries.
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](crate::stores::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(crate::stores::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::ConcurrentCachedExt::memory_usage) and `shard_memory_usage`,
+    /// measured through their [`DeepSize`](crate::DeepSize) impls. Each estimate then visits
+    /// every entry under its shard's read lock.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: crate::DeepSize,
+        V: crate::DeepSize,
+    {
+        self.deep_size = Some(crate::stores::HeapSize::of());
+        self
+    }
+
     /// Set a callback invoked when an entry is explicitly removed via
     /// [`cache_remove`](ConcurrentCached::cache_remove) or
     /// [`cache_remove_entry`](ConcurrentCached::cache_remove_entry).
@@ -700,7 +893,6 @@ impl<K, V, H> ShardedUnboundCacheBuilder<K, V, H> {
         H: ShardHasher<K>,
     {
         let n = checked_shard_count(self.shards)?;
-        let mask = n - 1;
         let per_shard_capacity = self.per_shard_initial_capacity.unwrap_or(0);
         let shards = (0..n)
             .map(|_| {
@@ -713,12 +905,15 @@ impl<K, V, H> ShardedUnboundCacheBuilder<K, V, H> {
             .into_boxed_slice();
         Ok(ShardedUnboundCache {
             inner: Arc::new(UnboundInner {
-                shards,
-                shard_mask: mask,
+                shards: ShardSet::new(shards),
                 hasher: self
                     .hasher
                     .expect("hasher is always initialized via Default or .hasher()"),
                 on_evict: self.on_evict,
+                tags: parking_lot::Mutex::new(TagIndex::new()),
+                metrics_exporter: self.metrics_exporter,
+                hot_keys: crate::stores::HotKeys::build(self.hot_keys, n)?,
+                deep_size: self.deep_size,
             }),
         })
     }
@@ -754,9 +949,9 @@ impl<K, V, H> ShardedUnboundCacheBuilder<K, V, H> {
         H: ShardHasher<K>,
     {
         let new_cache = self.build()?;
-        for shard in existing.inner.shards.iter() {
+        for shard in existing.inner.shards.table().iter() {
             let entries: Vec<(K, V)> = {
-                let guard = shard.lock.read();
+                let guard = shard.read();
                 guard.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
             };
             for (k, v) in entries {
@@ -767,6 +962,42 @@ impl<K, V, H> ShardedUnboundCacheBuilder<K, V, H> {
     }
 }
 
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K, V, H> ConcurrentCachedSnapshot<K, V> for ShardedUnboundCache<K, V, H>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    H: ShardHasher<K>,
+{
+    fn snapshot(&self) -> CacheSnapshot<K, V> {
+        let clock = SnapshotClock::now();
+        let shards = self
+            .inner
+            .shards
+            .table()
+            .iter()
+            .map(|shard| {
+                let guard = shard.read();
+                guard
+                    .iter()
+                    .filter_map(|(k, v)| clock.entry(k.clone(), v.clone(), None))
+                    .collect()
+            })
+            .collect();
+        CacheSnapshot::from_shards(shards)
+    }
+
+    fn restore(&self, snapshot: CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(SnapshotClock::now()) {
+            let _ = ConcurrentCached::cache_set(self, k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1058,6 +1289,7 @@ mod tests {
                     cache
                         .inner
                         .shards
+                        .table()
                         .iter()
                         .all(|s| s.lock.try_write().is_some()),
                     "on_evict must fire after the shard write lock is released"
@@ -1094,8 +1326,9 @@ mod tests {
         let expected: Vec<usize> = c
             .inner
             .shards
+            .table()
             .iter()
-            .map(|s| s.lock.read().keys().filter(|k| *k % 2 == 0).count())
+            .map(|s| s.read().keys().filter(|k| *k % 2 == 0).count())
             .collect();
         c.retain(|k, _v| k % 2 == 0);
         assert_eq!(c.shard_sizes(), expected);
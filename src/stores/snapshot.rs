@@ -0,0 +1,375 @@
+//! Owned, serializable snapshots of the in-memory stores (the `persist` feature).
+//!
+//! A [`CacheSnapshot`] is a plain-data copy of a store's live entries that survives a process
+//! restart: it is `serde`-serializable, and [`save`](CacheSnapshot::save) /
+//! [`load`](CacheSnapshot::load) write and read it as a MessagePack file. Stores produce one
+//! through [`CachedSnapshot::snapshot`] (single-owner) or [`ConcurrentCachedSnapshot::snapshot`]
+//! (sharded), and accept one back through the matching `restore`.
+//!
+//! What a snapshot preserves:
+//!
+//! - **Recency order.** Entries are recorded least-recently-used first, so restoring them in
+//!   order into an LRU-family store rebuilds the same recency chain. Unordered stores record
+//!   their entries in an arbitrary order.
+//! - **Remaining TTL, as a wall-clock deadline.** `Instant`s are process-local, so every
+//!   deadline is converted to a point on the system clock when the snapshot is taken and back
+//!   to an `Instant` when it is restored. The time the process spent down counts against the
+//!   entry: an entry whose deadline passed before `restore` is dropped.
+//! - **Per-shard layout.** A sharded snapshot keeps one entry list per shard. Restoring routes
+//!   every key through the target's shard hasher, so a target with the same shard count and a
+//!   deterministic hasher gets exactly the original layout back; any other target still gets
+//!   every entry, each shard's recency order intact.
+//!
+//! Hit/miss/eviction counters, capacities, TTL settings, and `on_evict` callbacks are store
+//! configuration, not entries, and are not part of a snapshot.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever the snapshot layout changes, so [`CacheSnapshot::load`] rejects a file
+/// written by an incompatible release instead of misreading it.
+const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// One cache entry inside a [`CacheSnapshot`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry<K, V> {
+    key: K,
+    value: V,
+    /// Wall-clock deadline as time since the Unix epoch; `None` when the entry never expires.
+    expires_at: Option<Duration>,
+}
+
+impl<K, V> SnapshotEntry<K, V> {
+    fn new(key: K, value: V, expires_at: Option<Duration>) -> Self {
+        Self {
+            key,
+            value,
+            expires_at,
+        }
+    }
+
+    /// The entry's key.
+    #[must_use]
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// The entry's value.
+    #[must_use]
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// The wall-clock instant at which the entry expires, or `None` if it never does.
+    #[must_use]
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at.and_then(|d| UNIX_EPOCH.checked_add(d))
+    }
+
+    /// Consume the entry, returning its key and value.
+    #[must_use]
+    pub fn into_parts(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+/// An owned, serializable copy of a store's live entries.
+///
+/// Produced by [`CachedSnapshot::snapshot`] / [`ConcurrentCachedSnapshot::snapshot`] and
+/// consumed by the matching `restore`. See the [module docs](self) for what is preserved.
+///
+/// A single-owner store produces a snapshot with one shard.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheSnapshot<K, V> {
+    format: u32,
+    shards: Vec<Vec<SnapshotEntry<K, V>>>,
+}
+
+impl<K, V> CacheSnapshot<K, V> {
+    pub(crate) fn from_shards(shards: Vec<Vec<SnapshotEntry<K, V>>>) -> Self {
+        Self {
+            format: SNAPSHOT_FORMAT_VERSION,
+            shards,
+        }
+    }
+
+    /// Number of entries across all shards.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(Vec::len).sum()
+    }
+
+    /// `true` if the snapshot holds no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(Vec::is_empty)
+    }
+
+    /// Number of shards the snapshot was taken from (`1` for single-owner stores).
+    #[must_use]
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The entries of shard `index`, least-recently-used first, or `None` if out of range.
+    #[must_use]
+    pub fn shard(&self, index: usize) -> Option<&[SnapshotEntry<K, V>]> {
+        self.shards.get(index).map(Vec::as_slice)
+    }
+
+    /// Iterate every entry, shard by shard, each shard least-recently-used first.
+    pub fn entries(&self) -> impl Iterator<Item = &SnapshotEntry<K, V>> + '_ {
+        self.shards.iter().flatten()
+    }
+
+    /// Drain the snapshot shard by shard, each shard least-recently-used first, dropping the
+    /// entries whose deadline has passed by `clock` and translating the rest back to in-memory
+    /// expiry instants.
+    pub(crate) fn into_live(
+        self,
+        clock: SnapshotClock,
+    ) -> impl Iterator<Item = (K, V, Option<Instant>)> {
+        self.shards.into_iter().flatten().filter_map(move |entry| {
+            let expires_at = clock.expires_at(entry.expires_at)?;
+            Some((entry.key, entry.value, expires_at))
+        })
+    }
+
+    /// Write the snapshot to `path` as MessagePack.
+    ///
+    /// The file is written to a sibling temporary file and renamed into place, so a crash
+    /// mid-write leaves the previous snapshot intact. Missing parent directories are created.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PersistError::Io`] if the file cannot be written and
+    /// [`PersistError::Encode`] if a key or value fails to serialize.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PersistError>
+    where
+        K: Serialize,
+        V: Serialize,
+    {
+        let path = path.as_ref();
+        let bytes = rmp_serde::to_vec(self).map_err(PersistError::encode)?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Read a snapshot previously written by [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PersistError::Io`] if the file cannot be read, [`PersistError::Decode`] if it
+    /// is not a snapshot of this `K`/`V`, and [`PersistError::UnsupportedFormat`] if it was
+    /// written by an incompatible release.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PersistError>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let bytes = fs::read(path)?;
+        let snapshot: Self = rmp_serde::from_slice(&bytes).map_err(PersistError::decode)?;
+        if snapshot.format != SNAPSHOT_FORMAT_VERSION {
+            return Err(PersistError::UnsupportedFormat {
+                found: snapshot.format,
+            });
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Error returned by [`CacheSnapshot::save`] and [`CacheSnapshot::load`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum PersistError {
+    /// The snapshot file could not be read or written.
+    #[error("snapshot io error: {0}")]
+    Io(#[from] io::Error),
+    /// A key or value failed to serialize.
+    ///
+    /// **Semver note:** the concrete source type is NOT part of the public API.
+    #[error("error serializing snapshot: {source}")]
+    Encode {
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    /// The file is not a snapshot of the expected key and value types.
+    ///
+    /// **Semver note:** the concrete source type is NOT part of the public API.
+    #[error("error deserializing snapshot: {source}")]
+    Decode {
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    /// The file was written with a snapshot format this release does not read.
+    #[error("unsupported snapshot format version {found}")]
+    UnsupportedFormat {
+        /// The format version recorded in the file.
+        found: u32,
+    },
+}
+
+impl PersistError {
+    fn encode(e: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::Encode {
+            source: Box::new(e),
+        }
+    }
+
+    fn decode(e: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self::Decode {
+            source: Box::new(e),
+        }
+    }
+}
+
+/// Snapshot and restore for single-owner in-memory stores.
+///
+/// Implemented by every built-in single-owner in-memory store. Custom stores used with
+/// `#[cached(persist_path = "...")]` implement it to opt into persistence.
+pub trait CachedSnapshot<K, V> {
+    /// Copy every live entry into an owned [`CacheSnapshot`].
+    ///
+    /// Expired entries are skipped. No hit/miss metrics are recorded and recency is not
+    /// changed.
+    #[must_use]
+    fn snapshot(&self) -> CacheSnapshot<K, V>;
+
+    /// Insert the still-live entries of `snapshot`, returning how many were restored.
+    ///
+    /// Entries go through the store's normal insert path, in snapshot order: an existing
+    /// value for the same key is replaced, and a bounded store evicts (firing `on_evict`)
+    /// if the snapshot holds more entries than it has room for. Entries whose wall-clock
+    /// deadline has passed are dropped.
+    fn restore(&mut self, snapshot: CacheSnapshot<K, V>) -> usize;
+}
+
+/// Snapshot and restore for internally-synchronized (sharded) in-memory stores.
+///
+/// `snapshot` locks one shard at a time, so it is consistent per shard but not a
+/// point-in-time view across shards (see design record 0047). It allocates a copy of every
+/// live entry.
+pub trait ConcurrentCachedSnapshot<K, V> {
+    /// Copy every live entry into an owned [`CacheSnapshot`] with one entry list per shard.
+    #[must_use]
+    fn snapshot(&self) -> CacheSnapshot<K, V>;
+
+    /// Insert the still-live entries of `snapshot`, returning how many were restored.
+    ///
+    /// Each key is routed through this store's shard hasher; see
+    /// [`CachedSnapshot::restore`] for the insert semantics.
+    fn restore(&self, snapshot: CacheSnapshot<K, V>) -> usize;
+}
+
+/// One paired reading of the monotonic and the wall clock, used to translate deadlines
+/// between `Instant` (in memory) and time-since-epoch (in a snapshot).
+#[derive(Clone, Copy)]
+pub(crate) struct SnapshotClock {
+    now: Instant,
+    since_epoch: Duration,
+}
+
+impl SnapshotClock {
+    pub(crate) fn now() -> Self {
+        Self {
+            now: Instant::now(),
+            since_epoch: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO),
+        }
+    }
+
+    /// The monotonic reading this clock was sampled at. Only the TTL stores restore through
+    /// it; the others go through `cache_set`.
+    #[cfg(any(feature = "time_stores", test))]
+    pub(crate) fn instant(&self) -> Instant {
+        self.now
+    }
+
+    /// Wall-clock deadline for an in-memory expiry instant. Returns `None` when the entry has
+    /// already expired and must be left out of the snapshot; `Some(None)` means it never
+    /// expires.
+    fn deadline(&self, expires_at: Option<Instant>) -> Option<Option<Duration>> {
+        match expires_at {
+            None => Some(None),
+            Some(t) if t <= self.now => None,
+            Some(t) => Some(self.since_epoch.checked_add(t - self.now)),
+        }
+    }
+
+    /// Snapshot entry for a live in-memory entry, or `None` if it has already expired.
+    pub(crate) fn entry<K, V>(
+        &self,
+        key: K,
+        value: V,
+        expires_at: Option<Instant>,
+    ) -> Option<SnapshotEntry<K, V>> {
+        let deadline = self.deadline(expires_at)?;
+        Some(SnapshotEntry::new(key, value, deadline))
+    }
+
+    /// Time left before a snapshot deadline, or `None` once it has passed.
+    fn remaining(&self, deadline: Duration) -> Option<Duration> {
+        deadline
+            .checked_sub(self.since_epoch)
+            .filter(|d| !d.is_zero())
+    }
+
+    /// In-memory expiry instant for a snapshot deadline. Returns `None` when the entry has
+    /// expired and must not be restored; `Some(None)` means it never expires (including a
+    /// deadline too far out for `Instant`, matching the stores' overflow handling).
+    fn expires_at(&self, deadline: Option<Duration>) -> Option<Option<Instant>> {
+        match deadline {
+            None => Some(None),
+            Some(d) => self.remaining(d).map(|left| self.now.checked_add(left)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_round_trips_a_future_deadline() {
+        let clock = SnapshotClock::now();
+        let expires = clock.instant() + Duration::from_secs(30);
+        let deadline = clock.deadline(Some(expires)).unwrap().unwrap();
+        assert_eq!(clock.expires_at(Some(deadline)), Some(Some(expires)));
+        assert_eq!(clock.remaining(deadline), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn clock_drops_past_deadlines() {
+        let clock = SnapshotClock::now();
+        assert_eq!(clock.deadline(Some(clock.instant())), None);
+        assert_eq!(clock.expires_at(Some(Duration::ZERO)), None);
+        assert_eq!(clock.expires_at(None), Some(None));
+    }
+
+    #[test]
+    fn load_rejects_unknown_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snap.msgpack");
+        let mut snap = CacheSnapshot::<u32, u32>::from_shards(vec![vec![]]);
+        snap.format = SNAPSHOT_FORMAT_VERSION + 1;
+        snap.save(&path).unwrap();
+        assert!(matches!(
+            CacheSnapshot::<u32, u32>::load(&path),
+            Err(PersistError::UnsupportedFormat { found }) if found == SNAPSHOT_FORMAT_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+//! Entries read back from the IO stores by [`RedbCache::iter`](crate::RedbCache::iter) and
+//! [`RedisCache::scan`](crate::RedisCache::scan), and the optional serialized original key
+//! those stores write alongside a value.
+//!
+//! Both stores address an entry by its key's `to_string()` form, which is not in general
+//! reversible. A store built with `store_keys(true)` also writes the key itself, MessagePack-
+//! encoded, as a trailing field of the stored envelope; [`StoredEntry::original_key`] decodes
+//! it. The field is omitted when unset, so the default layout is byte-for-byte the frozen 3.x
+//! one and envelopes written without it still decode.
+
+use serde::de::{DeserializeOwned, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::time::Duration;
+
+/// One live entry read back from a [`RedbCache`](crate::RedbCache) or `RedisCache`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredEntry<K, V> {
+    key: String,
+    original_key: Option<K>,
+    value: V,
+    ttl: Option<Duration>,
+}
+
+impl<K, V> StoredEntry<K, V> {
+    pub(super) fn new(
+        key: String,
+        original_key: Option<K>,
+        value: V,
+        ttl: Option<Duration>,
+    ) -> Self {
+        Self {
+            key,
+            original_key,
+            value,
+            ttl,
+        }
+    }
+
+    /// The key as the store addresses it: the `to_string()` form of the original key.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The original key, when the entry was written by a store built with `store_keys(true)`.
+    #[must_use]
+    pub fn original_key(&self) -> Option<&K> {
+        self.original_key.as_ref()
+    }
+
+    /// The decoded value.
+    #[must_use]
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Time left before the entry expires, or `None` if it never does.
+    #[must_use]
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Consume the entry, returning its value.
+    #[must_use]
+    pub fn into_value(self) -> V {
+        self.value
+    }
+}
+
+/// A MessagePack-encoded original key, stored as a `bin` field of the value envelope.
+///
+/// Kept as bytes rather than as a `K` so the envelope types, and every read and write path
+/// through them, stay generic over `V` only; `K` is decoded just where a caller asks for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct EncodedKey(Vec<u8>);
+
+impl EncodedKey {
+    pub(super) fn decode<K: DeserializeOwned>(&self) -> Result<K, rmp_serde::decode::Error> {
+        rmp_serde::from_slice(&self.0)
+    }
+}
+
+/// Encodes a key for a store built with `store_keys(true)`. Captured as a fn pointer by the
+/// builders, whose `store_keys` is the only place that requires `K: Serialize`.
+pub(super) type KeyEncoder<K> = fn(&K) -> Result<EncodedKey, rmp_serde::encode::Error>;
+
+pub(super) fn encode_key<K: Serialize>(key: &K) -> Result<EncodedKey, rmp_serde::encode::Error> {
+    rmp_serde::to_vec(key).map(EncodedKey)
+}
+
+impl Serialize for EncodedKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for EncodedKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = EncodedKey;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("an encoded key")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<EncodedKey, E> {
+                Ok(EncodedKey(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<EncodedKey, E> {
+                Ok(EncodedKey(v))
+            }
+
+            // The legacy JSON read path of the Redis store has no `bin` type.
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<EncodedKey, A::Error> {
+                let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    out.push(b);
+                }
+                Ok(EncodedKey(out))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Before {
+        value: u32,
+        stamp: u64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct After {
+        value: u32,
+        stamp: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key: Option<EncodedKey>,
+    }
+
+    #[test]
+    fn absent_key_keeps_the_old_layout_in_both_directions() {
+        let before = rmp_serde::to_vec(&Before { value: 1, stamp: 2 }).unwrap();
+        let after = rmp_serde::to_vec(&After {
+            value: 1,
+            stamp: 2,
+            key: None,
+        })
+        .unwrap();
+        assert_eq!(before, after);
+        let read: After = rmp_serde::from_slice(&before).unwrap();
+        assert!(read.key.is_none());
+    }
+
+    #[test]
+    fn present_key_round_trips() {
+        let key = (7u32, "seven".to_string());
+        let bytes = rmp_serde::to_vec(&After {
+            value: 1,
+            stamp: 2,
+            key: Some(encode_key(&key).unwrap()),
+        })
+        .unwrap();
+        let read: After = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(read.key.unwrap().decode::<(u32, String)>().unwrap(), key);
+    }
+}
@@ -0,0 +1,248 @@
+//! Tag-based group invalidation: [`CachedTags`] for the single-owner stores and
+//! [`ConcurrentCachedTags`] for the sharded and Redis stores.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::Arc;
+
+use super::{DefaultHashBuilder, new_default_hash_builder};
+
+/// Minimum number of tagged keys before the index bothers pruning, so a small cache does not
+/// walk its index on every tagged write.
+const PRUNE_FLOOR: usize = 1024;
+
+/// Tag-based invalidation for single-owner stores.
+///
+/// A tag is a string attached to a key when it is written with `set_with_tags`, and
+/// `invalidate_tag` removes every entry that currently carries it while leaving the rest of the
+/// cache alone (`"tenant:42"` for all of one tenant's derived views, say). Implemented by every
+/// in-memory store (`UnboundCache`, `LruCache`, `TtlCache`, `LruTtlCache`, `TtlSortedCache`,
+/// `ExpiringCache`, `ExpiringLruCache`).
+///
+/// # Keys removed by other means
+///
+/// The tag index is maintained by the two methods here and by `cache_clear`/`cache_reset`.
+/// A key that leaves the store any other way (capacity eviction, expiry, `cache_remove`,
+/// `retain`) stays in the index until an `invalidate_tag` for one of its tags, or until the
+/// index is pruned. Invalidation therefore errs on the side of removing too much, never too
+/// little: if such a key is written again without tags, invalidating one of its old tags still
+/// removes it. A removal is only ever a cache miss, so that is the safe direction. The index
+/// drops keys the store no longer holds once it grows past twice the store's size, which bounds
+/// its memory by the size of the cache rather than by the number of keys ever tagged.
+///
+/// ```rust
+/// use cached::{Cached, CachedTags, UnboundCache};
+///
+/// let mut cache = UnboundCache::new();
+/// cache.set_with_tags("a", 1, &["tenant:1"]);
+/// cache.set_with_tags("b", 2, &["tenant:1", "report"]);
+/// cache.set_with_tags("c", 3, &["tenant:2"]);
+///
+/// assert_eq!(cache.invalidate_tag("tenant:1"), 2);
+/// assert_eq!(cache.cache_get(&"a"), None);
+/// assert_eq!(cache.cache_get(&"c"), Some(&3));
+/// ```
+pub trait CachedTags<K, V> {
+    /// Insert `k -> v` and attach `tags` to `k`, replacing whatever tags the key carried.
+    /// An empty `tags` slice writes the entry untagged. Returns the previous value, as
+    /// [`cache_set`](crate::Cached::cache_set) does.
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V>;
+
+    /// Remove every entry tagged `tag` and return the number of live entries removed.
+    ///
+    /// Each entry is removed with [`cache_remove`](crate::Cached::cache_remove), so the
+    /// store's `on_evict` callback and eviction counter see exactly what an explicit removal
+    /// would. An unknown tag removes nothing.
+    fn invalidate_tag(&mut self, tag: &str) -> usize;
+}
+
+/// Tag-based invalidation for internally synchronized stores.
+///
+/// The sharded stores keep one tag index per store behind its own mutex, taken only by
+/// these two methods: untagged reads and writes never touch it. Both methods hold the index
+/// lock across their shard writes, so a concurrent `invalidate_tag` sees either both a tagged
+/// entry and its tags or neither. An `on_evict` callback that fires during either call runs
+/// with that lock held and must not call back into the tag API of the same store.
+///
+/// The sharded `clear`/`reset` leave the index alone. The stale keys it keeps are harmless
+/// for the reason given under [`CachedTags`], and are pruned the same way.
+///
+/// `RedisCache` keeps one Redis set per tag under the cache's namespace and prefix; see its
+/// implementation for the details.
+pub trait ConcurrentCachedTags<K, V>: crate::ConcurrentCacheBase {
+    /// Insert `k -> v` and attach `tags` to `k`. Returns the previous value, as
+    /// [`cache_set`](crate::ConcurrentCached::cache_set) does.
+    ///
+    /// # Errors
+    ///
+    /// Returns the store's error if the write fails.
+    fn set_with_tags<T: AsRef<str>>(
+        &self,
+        k: K,
+        v: V,
+        tags: &[T],
+    ) -> Result<Option<V>, Self::Error>;
+
+    /// Remove every entry tagged `tag` and return the number of live entries removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the store's error if a removal fails.
+    fn invalidate_tag(&self, tag: &str) -> Result<usize, Self::Error>;
+}
+
+/// Key/tag index backing the in-memory [`CachedTags`] and [`ConcurrentCachedTags`] impls.
+///
+/// Both directions are kept: `by_tag` answers `invalidate_tag`, and `by_key` lets a re-tagged
+/// key drop out of the sets of the tags it no longer carries.
+pub(crate) struct TagIndex<K> {
+    by_tag: HashMap<Arc<str>, HashSet<K, DefaultHashBuilder>, DefaultHashBuilder>,
+    by_key: HashMap<K, Box<[Arc<str>]>, DefaultHashBuilder>,
+}
+
+impl<K> TagIndex<K> {
+    pub(crate) fn new() -> Self {
+        Self {
+            by_tag: HashMap::with_hasher(new_default_hash_builder()),
+            by_key: HashMap::with_hasher(new_default_hash_builder()),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.by_tag = HashMap::with_hasher(new_default_hash_builder());
+        self.by_key = HashMap::with_hasher(new_default_hash_builder());
+    }
+
+    /// Number of keys the index currently holds tags for.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.by_key.len()
+    }
+}
+
+impl<K: Clone> Clone for TagIndex<K> {
+    fn clone(&self) -> Self {
+        Self {
+            by_tag: self.by_tag.clone(),
+            by_key: self.by_key.clone(),
+        }
+    }
+}
+
+impl<K> std::fmt::Debug for TagIndex<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TagIndex")
+            .field("tags", &self.by_tag.len())
+            .field("keys", &self.by_key.len())
+            .finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone> TagIndex<K> {
+    /// Attach `tags` to `k`, replacing its previous tags.
+    pub(crate) fn tag<T: AsRef<str>>(&mut self, k: &K, tags: &[T]) {
+        self.forget(k);
+        if tags.is_empty() {
+            return;
+        }
+        let mut owned: Vec<Arc<str>> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = tag.as_ref();
+            if owned.iter().any(|t| &**t == tag) {
+                continue;
+            }
+            let tag = match self.by_tag.get_key_value(tag) {
+                Some((shared, _)) => Arc::clone(shared),
+                None => Arc::from(tag),
+            };
+            self.by_tag
+                .entry(Arc::clone(&tag))
+                .or_insert_with(|| HashSet::with_hasher(new_default_hash_builder()))
+                .insert(k.clone());
+            owned.push(tag);
+        }
+        self.by_key.insert(k.clone(), owned.into_boxed_slice());
+    }
+
+    /// Drop `k` from the index entirely.
+    pub(crate) fn forget(&mut self, k: &K) {
+        let Some(tags) = self.by_key.remove(k) else {
+            return;
+        };
+        for tag in tags.iter() {
+            if let Some(keys) = self.by_tag.get_mut(tag) {
+                keys.remove(k);
+                if keys.is_empty() {
+                    self.by_tag.remove(tag);
+                }
+            }
+        }
+    }
+
+    /// Remove `tag` and return the keys that carried it. Each returned key is forgotten
+    /// entirely, since the caller is about to remove it from the store.
+    pub(crate) fn take(&mut self, tag: &str) -> Vec<K> {
+        let Some(keys) = self.by_tag.remove(tag) else {
+            return Vec::new();
+        };
+        let keys: Vec<K> = keys.into_iter().collect();
+        for k in &keys {
+            self.forget(k);
+        }
+        keys
+    }
+
+    /// Whether the index has outgrown a store holding `store_len` entries enough to be worth
+    /// a [`retain_keys`](Self::retain_keys) pass.
+    pub(crate) fn is_oversized(&self, store_len: usize) -> bool {
+        self.by_key.len() > PRUNE_FLOOR.max(store_len.saturating_mul(2))
+    }
+
+    /// Forget every key for which `live` returns `false`.
+    pub(crate) fn retain_keys(&mut self, mut live: impl FnMut(&K) -> bool) {
+        let dead: Vec<K> = self.by_key.keys().filter(|k| !live(k)).cloned().collect();
+        for k in &dead {
+            self.forget(k);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retagging_moves_a_key_between_tags() {
+        let mut index = TagIndex::new();
+        index.tag(&1u32, &["a", "b"]);
+        index.tag(&1, &["b", "c"]);
+        assert!(index.take("a").is_empty());
+        assert_eq!(index.take("c"), vec![1]);
+        // Taking `c` forgot the key, so `b` no longer lists it either.
+        assert!(index.take("b").is_empty());
+        assert_eq!(index.len(), 0);
+    }
+
+    #[test]
+    fn duplicate_tags_are_recorded_once() {
+        let mut index = TagIndex::new();
+        index.tag(&"k", &["t", "t"]);
+        assert_eq!(index.take("t"), vec!["k"]);
+    }
+
+    #[test]
+    fn retain_keys_drops_only_dead_keys() {
+        let mut index = TagIndex::new();
+        for k in 0..(PRUNE_FLOOR as u32 + 10) {
+            index.tag(&k, &["t"]);
+        }
+        assert!(!index.is_oversized(PRUNE_FLOOR));
+        assert!(index.is_oversized(5));
+
+        index.retain_keys(|k| *k < 5);
+        assert_eq!(index.len(), 5);
+        let mut left = index.take("t");
+        left.sort_unstable();
+        assert_eq!(left, vec![0, 1, 2, 3, 4]);
+    }
+}
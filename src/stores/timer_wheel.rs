@@ -0,0 +1,366 @@
+//! Hierarchical timing wheel for the per-value-expiry stores (`timer_wheel` on the builders).
+//!
+//! The wheel files a clone of each key under the tick its value's
+//! [`Expires::expires_at`](super::Expires::expires_at) deadline falls in. Ticks are
+//! `resolution` wide and counted from the instant the wheel was built; a deadline is rounded
+//! *up* to a tick, so a key only comes due once its deadline has passed.
+//!
+//! There are [`LEVELS`] levels of 64 slots. A level-0 slot holds one tick, and each level's
+//! slot spans all 64 slots of the level below, so level `n` reaches `64^(n + 1)` ticks ahead.
+//! A key goes in the lowest level whose slot still separates its tick from the current one.
+//! Advancing to an occupied slot above level 0 re-files its keys a level or more further down
+//! (a cascade), so every key is moved at most [`LEVELS`] times before it comes due. Each level
+//! keeps a bitmap of its occupied slots, so finding the next due slot does not walk the empty
+//! ticks in between, however long the store went without a sweep. A deadline past the top
+//! level's reach goes in the top-level slot its tick maps to and is re-filed each time that
+//! slot comes round, until it is in reach.
+//!
+//! The wheel only hands out keys. It knows nothing of the entries, so the store decides what a
+//! due key means: a key that was removed or overwritten since it was filed is a stale hint,
+//! and a value whose deadline moved later is filed again at the new one.
+//!
+//! A value with no deadline has nothing to be filed under, but its `is_expired` still decides.
+//! The wheel only remembers that the store may hold such values, and the store scans its map
+//! for them while it does.
+
+use super::BuildError;
+use crate::time::{Duration, Instant};
+
+/// Number of levels; with 64 slots each the wheel reaches `64^LEVELS` ticks ahead.
+const LEVELS: usize = 6;
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+/// Ticks from the current one to the end of the top level's reach.
+const MAX_SPAN: u64 = 1 << (SLOT_BITS as usize * LEVELS);
+
+/// What a builder's `timer_wheel` asked for; turned into [`TimerWheel`]s by `build`.
+pub(crate) struct TimerWheelConfig<K> {
+    resolution: Duration,
+    clone_key: fn(&K) -> K,
+}
+
+impl<K> TimerWheelConfig<K> {
+    pub(super) fn new(resolution: Duration) -> Self
+    where
+        K: Clone,
+    {
+        Self {
+            resolution,
+            clone_key: K::clone,
+        }
+    }
+
+    /// Build one wheel, starting its ticks now.
+    ///
+    /// Returns [`BuildError::InvalidValue`] if the resolution is zero.
+    pub(super) fn build(&self) -> Result<TimerWheel<K>, BuildError> {
+        if self.resolution.is_zero() {
+            return Err(BuildError::InvalidValue {
+                field: "timer_wheel",
+                reason: "resolution must be greater than zero",
+            });
+        }
+        Ok(TimerWheel {
+            origin: Instant::now(),
+            resolution: self.resolution.as_nanos(),
+            elapsed: 0,
+            levels: (0..LEVELS).map(|_| Level::new()).collect(),
+            due: Vec::new(),
+            len: 0,
+            unscheduled: false,
+            clone_key: self.clone_key,
+        })
+    }
+}
+
+/// Keys filed by the tick their value expires in.
+pub(crate) struct TimerWheel<K> {
+    origin: Instant,
+    /// Tick width in nanoseconds; never zero.
+    resolution: u128,
+    /// The tick the wheel has advanced to. Every slot before it has been emptied.
+    elapsed: u64,
+    levels: Box<[Level<K>]>,
+    /// Keys that have come due but were not handed out yet, because a budgeted poll stopped
+    /// short or the key was filed at or before `elapsed`.
+    due: Vec<K>,
+    /// Keys filed, including those in `due`.
+    len: usize,
+    /// Set when a key was scheduled without a deadline, or handed out and found live without
+    /// one, since the store last took it.
+    unscheduled: bool,
+    clone_key: fn(&K) -> K,
+}
+
+struct Level<K> {
+    /// Bit `i` is set when `slots[i]` is not empty.
+    occupied: u64,
+    slots: Box<[Vec<(u64, K)>]>,
+}
+
+impl<K> Level<K> {
+    fn new() -> Self {
+        Self {
+            occupied: 0,
+            slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+        }
+    }
+}
+
+impl<K> TimerWheel<K> {
+    /// File a clone of `key` under `deadline`. A `None` deadline is not filed, only noted.
+    pub(super) fn schedule(&mut self, key: &K, deadline: Option<Instant>) {
+        match deadline {
+            Some(deadline) => {
+                let key = (self.clone_key)(key);
+                self.reschedule(key, deadline);
+            }
+            None => self.unscheduled = true,
+        }
+    }
+
+    /// Note a value without a deadline, for a key the wheel handed out and the store found
+    /// still live.
+    pub(super) fn mark_unscheduled(&mut self) {
+        self.unscheduled = true;
+    }
+
+    /// Whether a value without a deadline was noted since the last call, clearing the note. A
+    /// store calls this when it starts scanning for such values, and marks again any it finds.
+    pub(super) fn take_unscheduled(&mut self) -> bool {
+        std::mem::take(&mut self.unscheduled)
+    }
+
+    /// File a new value's deadline unless the key is already filed no later than it.
+    ///
+    /// `previous` is the deadline of the value the new one replaced. Its filing is still in the
+    /// wheel and comes due first, at which point the store finds the new value live and files
+    /// its deadline then; filing it now as well would leave the key in the wheel twice.
+    pub(super) fn schedule_replacing(
+        &mut self,
+        key: &K,
+        previous: Option<Instant>,
+        deadline: Option<Instant>,
+    ) {
+        match (previous, deadline) {
+            (Some(previous), Some(deadline)) if previous <= deadline => {}
+            _ => self.schedule(key, deadline),
+        }
+    }
+
+    /// File `key` itself under `deadline`, for a key the wheel handed out and the store found
+    /// still live.
+    pub(super) fn reschedule(&mut self, key: K, deadline: Instant) {
+        let tick = self.tick_ceil(deadline);
+        self.len += 1;
+        if tick <= self.elapsed {
+            self.due.push(key);
+        } else {
+            self.file(tick, key);
+        }
+    }
+
+    /// Advance to `now` and hand out up to `budget` keys that have come due.
+    ///
+    /// Stops advancing once the budget is spent, so the next poll picks up where this one
+    /// stopped. The keys are hints: the store must check each against its entry.
+    pub(super) fn poll(&mut self, now: Instant, budget: usize) -> Vec<K> {
+        let now = self.tick_floor(now);
+        let mut out = Vec::new();
+        loop {
+            let take = (budget - out.len()).min(self.due.len());
+            out.extend(self.due.drain(self.due.len() - take..));
+            if out.len() == budget {
+                break;
+            }
+            match self.next_slot() {
+                Some((level, slot, start)) if start <= now => {
+                    self.elapsed = start;
+                    let level = &mut self.levels[level];
+                    level.occupied &= !(1 << slot);
+                    let keys = std::mem::take(&mut level.slots[slot]);
+                    for (tick, key) in keys {
+                        if tick <= self.elapsed {
+                            self.due.push(key);
+                        } else {
+                            self.file(tick, key);
+                        }
+                    }
+                }
+                _ => {
+                    self.elapsed = self.elapsed.max(now);
+                    break;
+                }
+            }
+        }
+        self.len -= out.len();
+        out
+    }
+
+    /// Drop every filed key.
+    pub(super) fn clear(&mut self) {
+        for level in self.levels.iter_mut() {
+            level.occupied = 0;
+            level.slots.iter_mut().for_each(Vec::clear);
+        }
+        self.due.clear();
+        self.len = 0;
+        self.unscheduled = false;
+    }
+
+    /// Number of keys filed, counting stale ones until they come due.
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Approximate heap bytes held by the slots, for `memory_usage`. Filed keys' own heap
+    /// allocations are not counted.
+    pub(super) fn heap_bytes(&self) -> usize {
+        let filed: usize = self
+            .levels
+            .iter()
+            .flat_map(|level| level.slots.iter())
+            .map(Vec::capacity)
+            .sum();
+        self.levels.len() * SLOTS * size_of::<Vec<(u64, K)>>()
+            + filed * size_of::<(u64, K)>()
+            + self.due.capacity() * size_of::<K>()
+    }
+
+    /// Put `key` in the slot for `tick`, which must be after `elapsed`.
+    fn file(&mut self, tick: u64, key: K) {
+        let differing = ((self.elapsed ^ tick) | (SLOTS as u64 - 1)).min(MAX_SPAN - 1);
+        let level = ((u64::BITS - 1 - differing.leading_zeros()) / SLOT_BITS) as usize;
+        let slot = ((tick >> (level as u32 * SLOT_BITS)) as usize) & (SLOTS - 1);
+        let level = &mut self.levels[level];
+        level.occupied |= 1 << slot;
+        level.slots[slot].push((tick, key));
+    }
+
+    /// The next occupied slot as `(level, slot, first tick)`. The lowest occupied level always
+    /// holds it: its keys share every higher level's current slot with `elapsed`.
+    fn next_slot(&self) -> Option<(usize, usize, u64)> {
+        self.levels.iter().enumerate().find_map(|(n, level)| {
+            if level.occupied == 0 {
+                return None;
+            }
+            let shift = n as u32 * SLOT_BITS;
+            let current = ((self.elapsed >> shift) as usize) & (SLOTS - 1);
+            let ahead = level.occupied.rotate_right(current as u32).trailing_zeros() as usize;
+            let slot = (current + ahead) & (SLOTS - 1);
+            let span = 1u64 << (shift + SLOT_BITS);
+            let mut start = (self.elapsed & !(span - 1)) + ((slot as u64) << shift);
+            if start <= self.elapsed {
+                // Only the top level wraps: a slot behind the current one is its next round.
+                start += span;
+            }
+            Some((n, slot, start))
+        })
+    }
+
+    fn ticks(&self, at: Instant) -> u128 {
+        at.saturating_duration_since(self.origin).as_nanos()
+    }
+
+    fn tick_floor(&self, at: Instant) -> u64 {
+        u64::try_from(self.ticks(at) / self.resolution).unwrap_or(u64::MAX)
+    }
+
+    fn tick_ceil(&self, at: Instant) -> u64 {
+        u64::try_from(self.ticks(at).div_ceil(self.resolution)).unwrap_or(u64::MAX)
+    }
+}
+
+impl<K> Clone for TimerWheel<K> {
+    fn clone(&self) -> Self {
+        let clone_key = self.clone_key;
+        Self {
+            origin: self.origin,
+            resolution: self.resolution,
+            elapsed: self.elapsed,
+            levels: self
+                .levels
+                .iter()
+                .map(|level| Level {
+                    occupied: level.occupied,
+                    slots: level
+                        .slots
+                        .iter()
+                        .map(|slot| slot.iter().map(|(t, k)| (*t, clone_key(k))).collect())
+                        .collect(),
+                })
+                .collect(),
+            due: self.due.iter().map(clone_key).collect(),
+            len: self.len,
+            unscheduled: self.unscheduled,
+            clone_key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wheel(resolution: Duration) -> TimerWheel<u64> {
+        TimerWheelConfig::new(resolution).build().unwrap()
+    }
+
+    #[test]
+    fn keys_come_due_in_deadline_order_across_every_level() {
+        let mut w = wheel(Duration::from_millis(1));
+        let at = |t: u64| w.origin + Duration::from_millis(t);
+        // One key per level, plus one past the top level's reach.
+        let ticks: Vec<u64> = (0..=LEVELS as u32).map(|n| 3 * 64u64.pow(n)).collect();
+        let deadlines: Vec<Instant> = ticks.iter().map(|&t| at(t)).collect();
+        for (t, &deadline) in ticks.iter().zip(&deadlines).rev() {
+            w.schedule(t, Some(deadline));
+        }
+        assert_eq!(w.len(), ticks.len());
+        for (&t, &deadline) in ticks.iter().zip(&deadlines) {
+            let early = deadline - Duration::from_millis(1);
+            assert!(w.poll(early, usize::MAX).is_empty(), "tick {t} came early");
+            assert_eq!(w.poll(deadline, usize::MAX), vec![t]);
+        }
+        assert_eq!(w.len(), 0);
+    }
+
+    #[test]
+    fn a_budgeted_poll_resumes_where_it_stopped() {
+        let tick = Duration::from_millis(10);
+        let mut w = wheel(tick);
+        let origin = w.origin;
+        for k in 0..100u64 {
+            w.schedule(&k, Some(origin + tick * (1 + (k % 5) as u32)));
+        }
+        // Already due: filed straight into the due list.
+        w.schedule(&500, Some(origin));
+        let now = origin + tick * 3;
+        let mut seen = Vec::new();
+        loop {
+            let batch = w.poll(now, 7);
+            assert!(batch.len() <= 7);
+            if batch.is_empty() {
+                break;
+            }
+            seen.extend(batch);
+        }
+        seen.sort_unstable();
+        let expected: Vec<u64> = (0..100).filter(|k| k % 5 < 3).chain([500]).collect();
+        assert_eq!(seen, expected);
+        assert_eq!(w.len(), 40);
+        w.clear();
+        assert!(w.poll(origin + tick * 10, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn zero_resolution_is_rejected() {
+        assert!(matches!(
+            TimerWheelConfig::<u64>::new(Duration::ZERO).build(),
+            Err(BuildError::InvalidValue {
+                field: "timer_wheel",
+                ..
+            })
+        ));
+    }
+}
@@ -3,14 +3,14 @@ use crate::time::Instant;
 use std::cmp::Eq;
 use std::hash::{BuildHasher, Hash};
 
-use std::collections::{HashMap, hash_map::Entry};
+use super::bucket_map::{BucketMap, Entry};
 
 #[cfg(feature = "async_core")]
 use {super::CachedGetOrSetAsync, std::future::Future};
 
 use crate::{CachedIter, CachedPeek, CloneCached};
 
-use super::{CacheEvict, Cached, DefaultHashBuilder, TimedEntry};
+use super::{CacheEvict, Cached, DefaultHashBuilder, EvictProgress, TimedEntry};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
@@ -32,7 +32,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 /// [`TtlCacheBuilder::hasher`] to use a different hasher.
 #[doc(alias = "TimedCache")]
 pub struct TtlCache<K, V, S = DefaultHashBuilder> {
-    pub(super) store: HashMap<K, TimedEntry<V>, S>,
+    pub(super) store: BucketMap<K, TimedEntry<V>, S>,
     pub(super) ttl: Duration,
     pub(super) hits: AtomicU64,
     pub(super) misses: AtomicU64,
@@ -40,6 +40,14 @@ pub struct TtlCache<K, V, S = DefaultHashBuilder> {
     pub(super) initial_capacity: Option<usize>,
     pub(super) refresh: bool,
     pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// Keys written through [`CachedTags::set_with_tags`](super::CachedTags), by tag.
+    pub(super) tags: super::TagIndex<K>,
+    pub(super) metrics_exporter: super::ExporterSlot,
+    pub(super) hot_keys: Option<super::HotKeys<K>>,
+    pub(super) deep_size: Option<super::HeapSize<K, V>>,
+    /// Where the next [`evict_budgeted`](Self::evict_budgeted) step starts, as a bucket index
+    /// into the map tagged with the table size it was taken against.
+    pub(super) evict_cursor: usize,
 }
 
 impl<K, V, S> std::fmt::Debug for TtlCache<K, V, S> {
@@ -72,6 +80,11 @@ where
             initial_capacity: self.initial_capacity,
             refresh: self.refresh,
             on_evict: self.on_evict.clone(),
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
+            evict_cursor: self.evict_cursor,
         }
     }
 }
@@ -83,6 +96,9 @@ pub struct TtlCacheBuilder<K, V, S = DefaultHashBuilder> {
     refresh: bool,
     on_evict: Option<super::OnEvict<K, V>>,
     hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for TtlCacheBuilder<K, V, DefaultHashBuilder> {
@@ -93,6 +109,9 @@ impl<K, V> Default for TtlCacheBuilder<K, V, DefaultHashBuilder> {
             refresh: false,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -172,7 +191,7 @@ impl<K, V, S> TtlCacheBuilder<K, V, S> {
 
     /// Switch to a custom hash builder `S2`, returning a builder parameterized on `S2`.
     ///
-    /// The hasher is used to hash keys in the internal `HashMap`. Calling this method
+    /// The hasher is used to hash keys in the internal hash table. Calling this method
     /// changes the builder's type parameter so `build()` returns a `TtlCache<K, V, S2>`.
     ///
     /// # Example
@@ -198,9 +217,57 @@ impl<K, V, S> TtlCacheBuilder<K, V, S> {
             refresh: self.refresh,
             on_evict: self.on_evict,
             hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Each estimate then visits every entry.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
     /// Build the cache.
     ///
     /// # Errors
@@ -216,8 +283,8 @@ impl<K, V, S> TtlCacheBuilder<K, V, S> {
         let ttl = self.ttl.ok_or(super::BuildError::MissingRequired("ttl"))?;
         super::validate_ttl(ttl)?;
         let store = match self.capacity {
-            Some(cap) => HashMap::with_capacity_and_hasher(cap, self.hasher),
-            None => HashMap::with_hasher(self.hasher),
+            Some(cap) => BucketMap::with_capacity_and_hasher(cap, self.hasher),
+            None => BucketMap::with_hasher(self.hasher),
         };
         Ok(TtlCache {
             store,
@@ -228,6 +295,11 @@ impl<K, V, S> TtlCacheBuilder<K, V, S> {
             initial_capacity: self.capacity,
             refresh: self.refresh,
             on_evict: self.on_evict,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
+            evict_cursor: 0,
         })
     }
 }
@@ -283,7 +355,6 @@ impl<K: Hash + Eq, V, S: BuildHasher> TtlCache<K, V, S> {
     /// `now` is the caller's already-sampled clock reading, used to decide whether the
     /// displaced entry was still live -- avoids a second `Instant::now()` call here.
     fn set_entry(&mut self, key: K, entry: TimedEntry<V>, now: Instant) -> Option<V> {
-        use std::collections::hash_map::Entry;
         match self.store.entry(key) {
             Entry::Occupied(mut occupied) => {
                 let old = occupied.insert(entry);
@@ -345,13 +416,12 @@ impl<K: Hash + Eq, V, S: BuildHasher> TtlCache<K, V, S> {
     /// Phase 1 of a two-phase sweep: run `doomed` over every entry and hand back the
     /// entries it selected, removed from the store.
     ///
-    /// See [`take_doomed`](crate::stores::take_doomed) for why the sweep is split in two and
-    /// what ties the passes together.
+    /// See [`take_doomed`](crate::stores::take_doomed) for why the sweep is split in two.
     fn take_doomed<F: FnMut(&K, &TimedEntry<V>) -> bool>(
         &mut self,
         doomed: F,
     ) -> Vec<(K, TimedEntry<V>)> {
-        crate::stores::take_doomed(&mut self.store, doomed)
+        self.store.take_doomed(doomed)
     }
 
     /// Phase 2 of a two-phase sweep: count `removed` as evictions and then notify
@@ -398,7 +468,7 @@ impl<K: Hash + Eq, V, S: BuildHasher> TtlCache<K, V, S> {
     pub fn evict(&mut self) -> usize {
         let now = Instant::now();
         // Two-phase: select, then remove, then count, then notify. Counting or notifying
-        // from inside a `HashMap::retain` predicate would fire the side effects *before*
+        // from inside a `retain` predicate would fire the side effects *before*
         // the map drops the entry, so a panicking `on_evict` would leave an entry counted
         // (and cleaned up) while still stored and served.
         // None means never-expires; Some(t) expires when now >= t.
@@ -406,6 +476,29 @@ impl<K: Hash + Eq, V, S: BuildHasher> TtlCache<K, V, S> {
         self.notify_evicted(&removed)
     }
 
+    /// Take one step of an incremental [`evict`](Self::evict): examine at most `max_entries`
+    /// entries from where the last step stopped, remove the expired ones, and report how
+    /// many were removed and whether the sweep reached the end of the map.
+    ///
+    /// A step also stops after probing eight buckets of the map per entry of `max_entries`,
+    /// so one over a table that removals left sparse stays short. See
+    /// [`CacheEvict::evict_budgeted`] for how steps add up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        let now = Instant::now();
+        let (removed, visited, next) =
+            self.store
+                .take_window(self.evict_cursor, max_entries, |_key, entry| {
+                    !Self::entry_live_at(entry.expires_at, now)
+                });
+        let finished =
+            crate::stores::advance_evict_cursor(&mut self.evict_cursor, visited, max_entries, next);
+        EvictProgress {
+            reclaimed: self.notify_evicted(&removed),
+            finished,
+        }
+    }
+
     /// Retain only entries that are unexpired and satisfy `keep`.
     ///
     /// Removes every entry that is already TTL-expired **or** for which `keep`
@@ -433,6 +526,30 @@ impl<K: Hash + Eq, V, S: BuildHasher> TtlCache<K, V, S> {
         });
         self.notify_evicted(&removed)
     }
+
+    /// Insert a key-value pair that expires `ttl` from now instead of after the cache's TTL,
+    /// returning the previous value only if it had not yet expired. A zero `ttl` stores an
+    /// entry that never expires.
+    ///
+    /// The override sets the entry's first deadline only: with
+    /// [`refresh_on_hit`](TtlCacheBuilder::refresh_on_hit) a hit re-arms it with the cache's
+    /// TTL, as it does every other entry.
+    pub fn set_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = Instant::now();
+        let expires_at = Self::compute_expires_at(ttl, now);
+        self.set_entry(key, TimedEntry { expires_at, value }, now)
+    }
+
+    /// Insert a key-value pair that expires at `deadline`, returning the previous value only
+    /// if it had not yet expired. A `deadline` that has already passed stores an entry that
+    /// is already expired. Refreshes on hit as [`set_with_ttl`](Self::set_with_ttl) describes.
+    pub fn set_until(&mut self, key: K, value: V, deadline: Instant) -> Option<V> {
+        let entry = TimedEntry {
+            expires_at: Some(deadline),
+            value,
+        };
+        self.set_entry(key, entry, Instant::now())
+    }
 }
 
 impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for TtlCache<K, V, S> {
@@ -443,6 +560,11 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for TtlCache<K, V, S> {
         K: std::borrow::Borrow<Q>,
         Q: std::hash::Hash + Eq + ?Sized,
     {
+        if let Some(hot) = &self.hot_keys {
+            hot.record(hot.hash(key), || {
+                self.store.get_key_value(key).map(|(k, _)| k)
+            });
+        }
         // Resolve hit / expired / absent from a SINGLE lookup: an absent key
         // (the common miss) must not pay a second `remove_entry` probe (CORE-7).
         let now = Instant::now();
@@ -452,7 +574,7 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for TtlCache<K, V, S> {
                 if self.refresh {
                     entry.expires_at = Self::refreshed_expires_at(self.ttl, now, entry.expires_at);
                 }
-                // SAFETY: `ptr` points into a HashMap entry obtained from
+                // SAFETY: `ptr` points into a map entry obtained from
                 // `get_mut`. We return immediately without modifying the map, so
                 // the entry is not moved while the returned reference is live.
                 // The raw pointer is needed because the borrow checker cannot see
@@ -676,17 +798,22 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for TtlCache<K, V, S> {
 
     fn cache_clear(&mut self) {
         self.store.clear();
+        self.tags.clear();
     }
     fn cache_reset_metrics(&mut self) {
         self.misses.store(0, Ordering::Relaxed);
         self.hits.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
     fn cache_reset(&mut self) {
         // Entries are dropped in-place; `on_evict` is NOT called for cleared entries.
         // We use clear + shrink_to rather than rebuilding so we don't need S: Clone.
         self.store.clear();
         self.store.shrink_to(self.initial_capacity.unwrap_or(0));
+        self.tags.clear();
         self.cache_reset_metrics();
     }
     fn cache_size(&self) -> usize {
@@ -698,6 +825,25 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for TtlCache<K, V, S> {
     fn cache_misses(&self) -> Option<u64> {
         Some(self.misses.load(Ordering::Relaxed))
     }
+
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self.deep_size.map_or(0, |heap| {
+            self.store
+                .iter()
+                .map(|(k, e)| heap.entry(k, &e.value))
+                .sum()
+        });
+        Some(self.store.allocation_bytes() + entries)
+    }
     fn cache_evictions(&self) -> Option<u64> {
         Some(self.evictions.load(Ordering::Relaxed))
     }
@@ -737,6 +883,51 @@ impl<K: Hash + Eq, V, S: BuildHasher> CachedIter<K, V> for TtlCache<K, V, S> {
     }
 }
 
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> super::CachedTags<K, V> for TtlCache<K, V, S> {
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.store.len()) {
+            let store = &self.store;
+            self.tags.retain_keys(|k| store.contains_key(k));
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> super::CachedSnapshot<K, V>
+    for TtlCache<K, V, S>
+{
+    fn snapshot(&self) -> super::CacheSnapshot<K, V> {
+        let clock = super::SnapshotClock::now();
+        let entries = self
+            .store
+            .iter()
+            .filter_map(|(k, e)| clock.entry(k.clone(), e.value.clone(), e.expires_at))
+            .collect();
+        super::CacheSnapshot::from_shards(vec![entries])
+    }
+
+    /// Restored entries keep their recorded deadline rather than starting a fresh TTL.
+    fn restore(&mut self, snapshot: super::CacheSnapshot<K, V>) -> usize {
+        let clock = super::SnapshotClock::now();
+        let mut restored = 0;
+        for (key, value, expires_at) in snapshot.into_live(clock) {
+            self.set_entry(key, TimedEntry { expires_at, value }, clock.instant());
+            restored += 1;
+        }
+        restored
+    }
+}
+
 impl<K: Hash + Eq, V, S: BuildHasher> CachedPeek<K, V> for TtlCache<K, V, S> {
     fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
     where
@@ -981,6 +1172,10 @@ impl<K: std::hash::Hash + Eq, V, S: BuildHasher> CacheEvict for TtlCache<K, V, S
     fn evict(&mut self) -> usize {
         TtlCache::evict(self)
     }
+
+    fn evict_budgeted(&mut self, max_entries: usize) -> EvictProgress {
+        TtlCache::evict_budgeted(self, max_entries)
+    }
 }
 
 #[cfg(test)]
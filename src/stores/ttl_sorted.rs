@@ -218,6 +218,11 @@ pub struct TtlSortedCache<K, V, S = DefaultHashBuilder> {
     pub(super) misses: StripedCounter,
     pub(super) evictions: AtomicU64,
     pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// Keys written through [`CachedTags::set_with_tags`](super::CachedTags), by tag.
+    pub(super) tags: super::TagIndex<K>,
+    pub(super) metrics_exporter: super::ExporterSlot,
+    pub(super) hot_keys: Option<super::HotKeys<K>>,
+    pub(super) deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V, S> std::fmt::Debug for TtlSortedCache<K, V, S> {
@@ -251,6 +256,10 @@ where
             misses: self.misses.snapshot(),
             evictions: AtomicU64::new(self.evictions.load(AtomicOrdering::Relaxed)),
             on_evict: self.on_evict.clone(),
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
         }
     }
 }
@@ -263,6 +272,9 @@ pub struct TtlSortedCacheBuilder<K, V, S = DefaultHashBuilder> {
     ttl: Option<Duration>,
     on_evict: Option<super::OnEvict<K, V>>,
     hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for TtlSortedCacheBuilder<K, V, DefaultHashBuilder> {
@@ -273,6 +285,9 @@ impl<K, V> Default for TtlSortedCacheBuilder<K, V, DefaultHashBuilder> {
             ttl: None,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -391,9 +406,57 @@ impl<K, V, S> TtlSortedCacheBuilder<K, V, S> {
             ttl: self.ttl,
             on_evict: self.on_evict,
             hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Each estimate then visits every entry.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
     /// Build the cache.
     ///
     /// # Errors
@@ -423,6 +486,10 @@ impl<K, V, S> TtlSortedCacheBuilder<K, V, S> {
             misses: StripedCounter::new(),
             evictions: AtomicU64::new(0),
             on_evict: self.on_evict,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
         };
         // Decide the single preallocation amount once all options are known.
         // An explicit `capacity` is the preallocation hint and takes precedence,
@@ -559,6 +626,32 @@ impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> TtlSortedCache<K, V, S> {
         self.evict_at(Instant::now())
     }
 
+    /// Take one step of an incremental [`evict`](Self::evict): remove at most `max_entries`
+    /// expired entries and report how many were removed and whether any expired ones remain.
+    ///
+    /// The expired entries are always the front of the expiry index, so no cursor is kept
+    /// between steps: each takes up to `max_entries` from the front, and the live entries
+    /// behind them are never visited. See [`CacheEvict::evict_budgeted`] for how steps add
+    /// up to a sweep.
+    #[must_use]
+    pub fn evict_budgeted(&mut self, max_entries: usize) -> super::EvictProgress {
+        let now = Instant::now();
+        let (removed, _) = self.take_expired_some(now, max_entries);
+        if !removed.is_empty() {
+            self.evictions
+                .fetch_add(removed.len() as u64, AtomicOrdering::Relaxed);
+        }
+        if let Some(on_evict) = &self.on_evict {
+            for (key, value) in &removed {
+                on_evict(key, value);
+            }
+        }
+        super::EvictProgress {
+            reclaimed: removed.len(),
+            finished: !self.has_expired(now),
+        }
+    }
+
     /// [`evict`](Self::evict) against an explicit `cutoff`, so a caller that already sampled
     /// the clock (e.g. [`set_inner`](Self::set_inner) on an evicting insert) does not pay for a
     /// second `Instant::now()`.
@@ -1060,6 +1153,132 @@ impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> TtlSortedCache<K, V, S> {
             }
         }
     }
+
+    /// Insert with an explicit TTL (zero: never expires) and no expiry sweep. Used by the
+    /// sharded store, which keeps its TTL outside the shards.
+    pub(super) fn set_for(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.set_inner(key, value, Some(ttl), false, false).0
+    }
+
+    /// The value stored for `key`, live or not, and whether it had expired by `now`.
+    pub(super) fn peek_raw<Q>(&self, key: &Q, now: Instant) -> Option<(&V, bool)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map
+            .get(key)
+            .map(|entry| (&entry.value, entry.is_expired_at(now)))
+    }
+
+    /// `true` if the soonest deadline in the expiry index is before `now`.
+    pub(super) fn has_expired(&self, now: Instant) -> bool {
+        self.keys
+            .first()
+            .is_some_and(|first| first.expiry.is_some_and(|expiry| expiry < now))
+    }
+
+    /// Detach the expired front of the expiry index and return the removed pairs. Entries
+    /// behind the first live deadline are never visited. Fires no callback and touches no
+    /// counters.
+    pub(super) fn take_expired(&mut self, now: Instant) -> Vec<(K, V)> {
+        if !self.has_expired(now) {
+            return Vec::new();
+        }
+        let live = self.keys.split_off(&Stamped::bound(now));
+        let expired = std::mem::replace(&mut self.keys, live);
+        expired
+            .into_iter()
+            .filter_map(|stamped| {
+                let key = stamped
+                    .key
+                    .expect("evicting: only artificial bounds are none");
+                self.map
+                    .remove_entry(key.0.as_ref())
+                    .map(|(k, entry)| (k, entry.value))
+            })
+            .collect()
+    }
+
+    /// Detach up to `budget` entries from the expired front of the expiry index and return the
+    /// removed pairs with how many index entries were taken. Fires no callback and touches
+    /// no counters.
+    pub(super) fn take_expired_some(
+        &mut self,
+        now: Instant,
+        budget: usize,
+    ) -> (Vec<(K, V)>, usize) {
+        let mut removed = Vec::new();
+        let mut taken = 0;
+        while taken < budget && self.has_expired(now) {
+            let Some(stamped) = self.keys.pop_first() else {
+                break;
+            };
+            taken += 1;
+            let key = stamped
+                .key
+                .expect("evicting: only artificial bounds are none");
+            if let Some((k, entry)) = self.map.remove_entry(key.0.as_ref()) {
+                removed.push((k, entry.value));
+            }
+        }
+        (removed, taken)
+    }
+
+    /// Remove every entry expired by `now` or rejected by `keep` and return the removed
+    /// pairs. `keep` sees live entries only and runs before anything is removed. Fires no
+    /// callback and touches no counters.
+    pub(super) fn take_rejected<F: FnMut(&K, &V) -> bool>(
+        &mut self,
+        now: Instant,
+        keep: &mut F,
+    ) -> Vec<(K, V)> {
+        let doomed: Vec<Stamped<K>> = self
+            .map
+            .iter()
+            .filter(|(key, entry)| entry.is_expired_at(now) || !keep(key, &entry.value))
+            .map(|(_key, entry)| entry.as_stamped())
+            .collect();
+        doomed
+            .into_iter()
+            .filter_map(|stamped| {
+                self.keys.remove(&stamped);
+                let key = stamped
+                    .key
+                    .expect("retaining: only artificial bounds are none");
+                self.map
+                    .remove_entry(key.0.as_ref())
+                    .map(|(k, entry)| (k, entry.value))
+            })
+            .collect()
+    }
+
+    /// Remove `key` if present, returning the stored pair and whether it had expired by
+    /// `now`. Fires no callback and touches no counters.
+    pub(super) fn pop_raw<Q>(&mut self, key: &Q, now: Instant) -> Option<(K, V, bool)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (k, entry) = self.map.remove_entry(key)?;
+        self.keys.remove(&entry.as_stamped());
+        let expired = entry.is_expired_at(now);
+        Some((k, entry.value, expired))
+    }
+
+    /// Remove every entry, returning the stored pairs. Fires no callback and touches no
+    /// counters.
+    pub(super) fn drain_all(&mut self) -> Vec<(K, V)> {
+        self.keys.clear();
+        self.map
+            .drain()
+            .map(|(k, entry)| (k, entry.value))
+            .collect()
+    }
+
+    pub(super) fn add_evictions(&self, n: u64) {
+        self.evictions.fetch_add(n, AtomicOrdering::Relaxed);
+    }
 }
 
 /// Builder returned by [`TtlSortedCache::set_with`] for chaining a per-entry TTL override
@@ -1139,6 +1358,11 @@ impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> Cached<K, V> for TtlSortedCa
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
+        if let Some(hot) = &self.hot_keys {
+            hot.record(hot.hash(key), || {
+                self.map.get_key_value(key).map(|(k, _)| k)
+            });
+        }
         // Two lookups on the hit path: the first checks expiry (releasing the borrow via
         // `.map`), the second returns the reference. A single-lookup approach is not possible
         // SAFELY in stable Rust because returning `&'1 V` from inside an `if let` block ties
@@ -1290,6 +1514,7 @@ impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> Cached<K, V> for TtlSortedCa
         // would be circular.
         self.map.clear();
         self.keys.clear();
+        self.tags.clear();
     }
 
     fn cache_reset(&mut self) {
@@ -1300,6 +1525,7 @@ impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> Cached<K, V> for TtlSortedCa
         self.map.clear();
         self.map.shrink_to(self.initial_capacity.unwrap_or(0));
         self.keys = BTreeSet::new();
+        self.tags.clear();
         self.min_instant = Instant::now();
         self.cache_reset_metrics();
     }
@@ -1308,6 +1534,9 @@ impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> Cached<K, V> for TtlSortedCa
         self.misses.reset();
         self.hits.reset();
         self.evictions.store(0, AtomicOrdering::Relaxed);
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
 
     /// Reports raw entry count without sweeping; the count may include
@@ -1326,6 +1555,32 @@ impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> Cached<K, V> for TtlSortedCa
         Some(self.misses.load())
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        // Each key is held twice: as the map key and behind the `Arc` the expiry index shares.
+        let entries = self.deep_size.map_or(0, |heap| {
+            self.map
+                .iter()
+                .map(|(k, e)| 2 * (heap.key)(k) + (heap.value)(&e.value))
+                .sum()
+        });
+        let arcs = self.map.len() * (2 * size_of::<usize>() + size_of::<K>());
+        Some(
+            super::hash_map_bytes(&self.map)
+                + super::btree_set_bytes::<Stamped<K>>(self.keys.len())
+                + arcs
+                + entries,
+        )
+    }
+
     fn cache_evictions(&self) -> Option<u64> {
         Some(self.evictions.load(AtomicOrdering::Relaxed))
     }
@@ -1366,6 +1621,59 @@ impl<K: Hash + Eq + Ord, V, S: BuildHasher> CachedIter<K, V> for TtlSortedCache<
     }
 }
 
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher> super::CachedTags<K, V>
+    for TtlSortedCache<K, V, S>
+{
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.map.len()) {
+            let map = &self.map;
+            self.tags.retain_keys(|k| map.contains_key(k));
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K: Hash + Eq + Ord + Clone, V: Clone, S: BuildHasher> super::CachedSnapshot<K, V>
+    for TtlSortedCache<K, V, S>
+{
+    /// Entries are recorded in ascending expiry order, never-expiring entries last.
+    fn snapshot(&self) -> super::CacheSnapshot<K, V> {
+        let clock = super::SnapshotClock::now();
+        let entries = self
+            .keys
+            .iter()
+            .filter_map(|stamped| stamped.key.as_ref())
+            .filter_map(|key| self.map.get(key.0.as_ref()))
+            .filter_map(|e| clock.entry(e.key.0.as_ref().clone(), e.value.clone(), e.expiry))
+            .collect();
+        super::CacheSnapshot::from_shards(vec![entries])
+    }
+
+    /// Each entry is inserted with its remaining time as a per-entry TTL, so it keeps its
+    /// recorded deadline rather than starting a fresh one.
+    fn restore(&mut self, snapshot: super::CacheSnapshot<K, V>) -> usize {
+        let clock = super::SnapshotClock::now();
+        let mut restored = 0;
+        for (key, value, expires_at) in snapshot.into_live(clock) {
+            // A zero per-entry TTL stores the entry with no expiry.
+            let ttl = expires_at.map_or(Duration::ZERO, |t| t - clock.instant());
+            self.set_inner(key, value, Some(ttl), false, false);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 impl<K: Hash + Eq + Ord, V, S: BuildHasher> CacheTtl for TtlSortedCache<K, V, S> {
     /// Returns the currently configured TTL, or `None` when expiry is disabled.
     ///
@@ -1575,6 +1883,10 @@ impl<K: std::hash::Hash + Eq + Ord + Clone, V, S: BuildHasher> CacheEvict
     fn evict(&mut self) -> usize {
         TtlSortedCache::evict(self)
     }
+
+    fn evict_budgeted(&mut self, max_entries: usize) -> super::EvictProgress {
+        TtlSortedCache::evict_budgeted(self, max_entries)
+    }
 }
 
 #[cfg(test)]
@@ -28,6 +28,11 @@ pub struct UnboundCache<K, V, S = DefaultHashBuilder> {
     pub(super) misses: StripedCounter,
     pub(super) initial_capacity: Option<usize>,
     pub(super) on_evict: Option<super::OnEvict<K, V>>,
+    /// Keys written through [`CachedTags::set_with_tags`](super::CachedTags), by tag.
+    pub(super) tags: super::TagIndex<K>,
+    pub(super) metrics_exporter: super::ExporterSlot,
+    pub(super) hot_keys: Option<super::HotKeys<K>>,
+    pub(super) deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V, S> std::fmt::Debug for UnboundCache<K, V, S> {
@@ -53,6 +58,10 @@ where
             misses: self.misses.snapshot(),
             initial_capacity: self.initial_capacity,
             on_evict: self.on_evict.clone(),
+            tags: self.tags.clone(),
+            metrics_exporter: self.metrics_exporter.clone(),
+            hot_keys: self.hot_keys.clone(),
+            deep_size: self.deep_size,
         }
     }
 }
@@ -81,6 +90,9 @@ pub struct UnboundCacheBuilder<K, V, S = DefaultHashBuilder> {
     capacity: Option<usize>,
     on_evict: Option<super::OnEvict<K, V>>,
     hasher: S,
+    metrics_exporter: super::ExporterSlot,
+    hot_keys: Option<super::HotKeyConfig<K>>,
+    deep_size: Option<super::HeapSize<K, V>>,
 }
 
 impl<K, V> Default for UnboundCacheBuilder<K, V, DefaultHashBuilder> {
@@ -89,6 +101,9 @@ impl<K, V> Default for UnboundCacheBuilder<K, V, DefaultHashBuilder> {
             capacity: None,
             on_evict: None,
             hasher: super::new_default_hash_builder(),
+            metrics_exporter: super::ExporterSlot::default(),
+            hot_keys: None,
+            deep_size: None,
         }
     }
 }
@@ -123,6 +138,51 @@ impl<K, V, S> UnboundCacheBuilder<K, V, S> {
         self
     }
 
+    /// Publish this cache's metrics through the `metrics` facade, labelled `cache = name`.
+    /// See [`CachedExt::publish_metrics`](crate::CachedExt::publish_metrics).
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics_name(mut self, name: impl Into<std::sync::Arc<str>>) -> Self {
+        self.metrics_exporter = Some(super::MetricsExporter::new(name));
+        self
+    }
+
+    /// Track the most looked-up keys in a Space-Saving summary of `capacity` slots, keeping a
+    /// clone of each tracked key. Read them with [`top_keys`](crate::CachedExt::top_keys).
+    ///
+    /// `build` returns [`BuildError::InvalidValue`](super::BuildError::InvalidValue) if
+    /// `capacity` is zero.
+    #[must_use]
+    pub fn hot_keys(mut self, capacity: usize) -> Self
+    where
+        K: Clone,
+    {
+        self.hot_keys = Some(super::HotKeyConfig::keys(capacity));
+        self
+    }
+
+    /// Like [`hot_keys`](Self::hot_keys), but record key hashes only, for a `K` that is not
+    /// `Clone` or too large to copy.
+    #[must_use]
+    pub fn hot_key_hashes(mut self, capacity: usize) -> Self {
+        self.hot_keys = Some(super::HotKeyConfig::hashes(capacity));
+        self
+    }
+
+    /// Add the heap memory owned by keys and values to
+    /// [`memory_usage`](crate::CachedExt::memory_usage), measured through their
+    /// [`DeepSize`](super::DeepSize) impls. Each estimate then visits every entry.
+    #[must_use]
+    pub fn deep_size(mut self) -> Self
+    where
+        K: super::DeepSize,
+        V: super::DeepSize,
+    {
+        self.deep_size = Some(super::HeapSize::of());
+        self
+    }
+
     /// Switch to a custom hash builder `S2`, returning a builder parameterized on `S2`.
     ///
     /// The hasher is used for the backing `HashMap`. Calling this method changes the
@@ -148,6 +208,9 @@ impl<K, V, S> UnboundCacheBuilder<K, V, S> {
             capacity: self.capacity,
             on_evict: self.on_evict,
             hasher,
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: self.hot_keys,
+            deep_size: self.deep_size,
         }
     }
 
@@ -173,6 +236,10 @@ impl<K, V, S> UnboundCacheBuilder<K, V, S> {
             misses: StripedCounter::new(),
             initial_capacity: self.capacity,
             on_evict: self.on_evict,
+            tags: super::TagIndex::new(),
+            metrics_exporter: self.metrics_exporter,
+            hot_keys: super::HotKeys::build(self.hot_keys, 1)?,
+            deep_size: self.deep_size,
         })
     }
 }
@@ -264,6 +331,11 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for UnboundCache<K, V, S> {
         K: std::borrow::Borrow<Q>,
         Q: std::hash::Hash + Eq + ?Sized,
     {
+        if let Some(hot) = &self.hot_keys {
+            hot.record(hot.hash(key), || {
+                self.store.get_key_value(key).map(|(k, _)| k)
+            });
+        }
         if let Some(v) = self.store.get(key) {
             self.hits.increment_mut();
             Some(v)
@@ -342,6 +414,7 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for UnboundCache<K, V, S> {
 
     fn cache_clear(&mut self) {
         self.store.clear();
+        self.tags.clear();
     }
     fn cache_reset(&mut self) {
         // Clear all entries and shrink capacity back toward the initial hint.
@@ -350,11 +423,15 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for UnboundCache<K, V, S> {
         // Entries are dropped in-place; `on_evict` is NOT called for cleared entries.
         self.store.clear();
         self.store.shrink_to(self.initial_capacity.unwrap_or(0));
+        self.tags.clear();
         self.cache_reset_metrics();
     }
     fn cache_reset_metrics(&mut self) {
         self.misses.reset();
         self.hits.reset();
+        if let Some(hot) = &self.hot_keys {
+            hot.reset();
+        }
     }
     fn cache_size(&self) -> usize {
         self.store.len()
@@ -366,6 +443,22 @@ impl<K: Hash + Eq, V, S: BuildHasher> Cached<K, V> for UnboundCache<K, V, S> {
         Some(self.misses.load())
     }
 
+    #[cfg(feature = "metrics")]
+    fn cache_metrics_exporter(&self) -> Option<&super::MetricsExporter> {
+        self.metrics_exporter.as_ref()
+    }
+
+    fn cache_top_keys(&self, n: usize) -> Option<Vec<super::HotKey<K>>> {
+        self.hot_keys.as_ref().map(|hot| hot.top(n))
+    }
+
+    fn cache_memory_usage(&self) -> Option<usize> {
+        let entries = self.deep_size.map_or(0, |heap| {
+            self.store.iter().map(|(k, v)| heap.entry(k, v)).sum()
+        });
+        Some(super::hash_map_bytes(&self.store) + entries)
+    }
+
     /// Check whether the cache contains a live entry for `k`.
     ///
     /// Delegates to [`CachedPeek::cache_peek`], so it records no hit/miss
@@ -390,6 +483,49 @@ impl<K: Hash + Eq, V, S: BuildHasher> CachedIter<K, V> for UnboundCache<K, V, S>
     }
 }
 
+impl<K: Hash + Eq + Clone, V, S: BuildHasher> super::CachedTags<K, V> for UnboundCache<K, V, S> {
+    fn set_with_tags<T: AsRef<str>>(&mut self, k: K, v: V, tags: &[T]) -> Option<V> {
+        if self.tags.is_oversized(self.store.len()) {
+            let store = &self.store;
+            self.tags.retain_keys(|k| store.contains_key(k));
+        }
+        self.tags.tag(&k, tags);
+        self.cache_set(k, v)
+    }
+
+    fn invalidate_tag(&mut self, tag: &str) -> usize {
+        let keys = self.tags.take(tag);
+        keys.iter()
+            .filter(|k| self.cache_remove(*k).is_some())
+            .count()
+    }
+}
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> super::CachedSnapshot<K, V>
+    for UnboundCache<K, V, S>
+{
+    fn snapshot(&self) -> super::CacheSnapshot<K, V> {
+        let clock = super::SnapshotClock::now();
+        let entries = self
+            .store
+            .iter()
+            .filter_map(|(k, v)| clock.entry(k.clone(), v.clone(), None))
+            .collect();
+        super::CacheSnapshot::from_shards(vec![entries])
+    }
+
+    fn restore(&mut self, snapshot: super::CacheSnapshot<K, V>) -> usize {
+        let mut restored = 0;
+        for (k, v, _) in snapshot.into_live(super::SnapshotClock::now()) {
+            self.cache_set(k, v);
+            restored += 1;
+        }
+        restored
+    }
+}
+
 impl<K: Hash + Eq, V, S: BuildHasher> CachedPeek<K, V> for UnboundCache<K, V, S> {
     fn cache_peek<Q>(&self, k: &Q) -> Option<&V>
     where
@@ -0,0 +1,723 @@
+/*!
+HTTP response caching as `tower` middleware.
+
+[`CacheLayer`] wraps an HTTP service and caches its responses in any
+[`ConcurrentCachedAsync`] store, a [`ShardedExpiringLruCache`] unless another is supplied.
+Responses are keyed by method, URI and the request headers chosen with
+[`key_header`](CacheLayer::key_header), and the upstream's `Cache-Control` decides whether and
+for how long one is kept: a [`CachedResponse`] implements [`Expires`] with the deadline that
+`s-maxage` or `max-age` gave it, so the store drops it on its own.
+
+```rust,no_run
+use cached::tower::CacheLayer;
+use http::header::ACCEPT_LANGUAGE;
+use std::time::Duration;
+
+let layer = CacheLayer::new(10_000)
+    .key_header(ACCEPT_LANGUAGE)
+    .default_ttl(Duration::from_secs(30));
+// let app = axum::Router::new().route("/", get(handler)).layer(layer);
+```
+
+Only `GET` and `HEAD` requests without an `Authorization` header are cached. A response is
+stored when its status is cacheable by default (RFC 9110 section 15.1, except `206`), its
+`Cache-Control` has none of `no-store`, `no-cache` or `private`, it sets no cookie, its `Vary`
+is not `*`, and it has a positive lifetime from `s-maxage`, `max-age` or the layer's
+[`default_ttl`](CacheLayer::default_ttl). The lifetime is capped at 2^31 seconds (RFC 9111
+section 1.2.2) and counts the upstream's `Age` against it. The request headers named by `Vary` are recorded with
+the entry; a later request that differs in any of them is treated as a miss and its response
+replaces the entry. A request with `Cache-Control: no-store` bypasses the cache, and one with
+`no-cache` or `max-age=0` skips the lookup but stores the fresh response.
+
+Conditional requests are answered by the layer: `If-None-Match` and `If-Modified-Since` are
+removed from requests it may cache, so the upstream always returns a full response. An
+`If-None-Match` that matches the response's `ETag` gets a `304 Not Modified` instead, as does an
+`If-Modified-Since` no earlier than its `Last-Modified` when the request has no `If-None-Match`
+(RFC 9110 section 13.1.3). Hits carry an `Age` header: the upstream's `Age` plus the time since
+the layer stored the response.
+
+Cacheable bodies are buffered up to [`max_body_size`](CacheLayer::max_body_size); a larger one is
+streamed through uncached, as is every response the layer will not store. Trailers are not
+cached. Store errors are ignored: a failed lookup is a miss and a failed write leaves the
+response uncached.
+*/
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::{Buf, Bytes, BytesMut};
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::{Method, Request, Response, StatusCode, Uri, Version};
+use http_body::{Body, Frame, SizeHint};
+use http_body_util::BodyExt;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::ConcurrentCachedAsync;
+use crate::http::{age, cache_control, parse_http_date, seconds};
+use crate::stores::{Expires, ShardedExpiringLruCache};
+use crate::time::{Duration, Instant};
+
+/// The store a [`CacheLayer::new`] layer caches in.
+pub type DefaultHttpStore = ShardedExpiringLruCache<HttpCacheKey, CachedResponse>;
+
+/// Bodies at most this large are buffered for caching unless
+/// [`max_body_size`](CacheLayer::max_body_size) says otherwise.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1 << 20;
+
+/// The longest lifetime the layer gives a response. RFC 9111 section 1.2.2 has caches treat a
+/// larger delta-seconds value as this one.
+const MAX_LIFETIME: Duration = Duration::from_secs(1 << 31);
+
+/// Statuses a response may be stored with: RFC 9110's heuristically cacheable set, minus `206`,
+/// whose partial body the layer would serve as if it were whole.
+const CACHEABLE_STATUSES: [u16; 11] = [200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+/// Headers a `304 Not Modified` repeats from the response it stands for (RFC 9110 section
+/// 15.4.5).
+const NOT_MODIFIED_HEADERS: [HeaderName; 6] = [
+    header::CACHE_CONTROL,
+    header::CONTENT_LOCATION,
+    header::DATE,
+    header::ETAG,
+    header::EXPIRES,
+    header::VARY,
+];
+
+/// The request headers a key or a `Vary` check compares: each name with all of its values, in
+/// order.
+type HeaderValues = Vec<(HeaderName, Vec<HeaderValue>)>;
+
+fn header_values(headers: &HeaderMap, names: impl IntoIterator<Item = HeaderName>) -> HeaderValues {
+    names
+        .into_iter()
+        .map(|name| {
+            let values = headers.get_all(&name).iter().cloned().collect();
+            (name, values)
+        })
+        .collect()
+}
+
+/// The cache key of a request: its method, URI and the values of the layer's
+/// [`key_header`](CacheLayer::key_header)s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpCacheKey {
+    method: Method,
+    uri: Uri,
+    headers: HeaderValues,
+}
+
+impl HttpCacheKey {
+    /// The request method.
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The request URI, as the service received it.
+    #[must_use]
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// The key headers with their values; an absent header has no values.
+    #[must_use]
+    pub fn headers(&self) -> &[(HeaderName, Vec<HeaderValue>)] {
+        &self.headers
+    }
+}
+
+/// A buffered response as the layer stores it.
+///
+/// Expires at the instant its `s-maxage`, `max-age` or the layer's default TTL ran out, counted
+/// from when the layer received it less the `Age` it arrived with.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+    stored_at: Instant,
+    /// The `Age` the upstream sent with it.
+    upstream_age: Duration,
+    expires_at: Instant,
+    /// The request headers named by the response's `Vary`, as the storing request sent them.
+    vary: HeaderValues,
+}
+
+impl CachedResponse {
+    /// The response status.
+    #[must_use]
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The response headers, as the upstream sent them.
+    #[must_use]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The buffered body.
+    #[must_use]
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The response's age: the `Age` the upstream sent plus the time since it was stored.
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        self.upstream_age.saturating_add(self.stored_at.elapsed())
+    }
+
+    fn vary_matches(&self, headers: &HeaderMap) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, values)| headers.get_all(name).iter().eq(values.iter()))
+    }
+
+    fn respond<B: Body>(&self, conditions: &Conditions) -> Response<CacheBody<B>> {
+        let age = HeaderValue::from(self.age().as_secs());
+        let not_modified = if self.status == StatusCode::OK {
+            not_modified(&self.headers, conditions)
+        } else {
+            None
+        };
+        let mut response = match not_modified {
+            Some(response) => response,
+            None => {
+                let mut response = Response::new(CacheBody::full(self.body.clone()));
+                *response.status_mut() = self.status;
+                *response.headers_mut() = self.headers.clone();
+                response
+            }
+        };
+        *response.version_mut() = self.version;
+        response.headers_mut().insert(header::AGE, age);
+        response
+    }
+}
+
+impl Expires for CachedResponse {
+    fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    fn expires_at(&self) -> Option<Instant> {
+        Some(self.expires_at)
+    }
+}
+
+/// The names listed by `Vary`, or `None` if it is `*` or names something that is not a header.
+fn vary_names(headers: &HeaderMap) -> Option<Vec<HeaderName>> {
+    let mut names = Vec::new();
+    for value in headers.get_all(header::VARY) {
+        for name in value.to_str().ok()?.split(',') {
+            let name = name.trim();
+            if name == "*" {
+                return None;
+            }
+            if !name.is_empty() {
+                names.push(HeaderName::from_bytes(name.as_bytes()).ok()?);
+            }
+        }
+    }
+    Some(names)
+}
+
+/// Whether `if_none_match` lists `etag` under the weak comparison RFC 9110 prescribes for it.
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let (Ok(list), Ok(etag)) = (if_none_match.to_str(), etag.to_str()) else {
+        return false;
+    };
+    let weak = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+    let etag = weak(etag);
+    list.split(',')
+        .any(|tag| tag.trim() == "*" || weak(tag) == etag)
+}
+
+/// The conditional headers the layer withholds from the upstream and evaluates itself.
+#[derive(Debug, Default)]
+struct Conditions {
+    if_none_match: Option<HeaderValue>,
+    if_modified_since: Option<HeaderValue>,
+}
+
+impl Conditions {
+    fn take(headers: &mut HeaderMap) -> Self {
+        Self {
+            if_none_match: headers.remove(header::IF_NONE_MATCH),
+            if_modified_since: headers.remove(header::IF_MODIFIED_SINCE),
+        }
+    }
+
+    /// Whether a response with `headers` is unchanged under these conditions. As RFC 9110
+    /// section 13.1.3 prescribes, `If-Modified-Since` only counts without `If-None-Match`.
+    fn unchanged(&self, headers: &HeaderMap) -> Option<bool> {
+        Some(match &self.if_none_match {
+            Some(if_none_match) => etag_matches(if_none_match, headers.get(header::ETAG)?),
+            None => {
+                let since = parse_http_date(self.if_modified_since.as_ref()?)?;
+                parse_http_date(headers.get(header::LAST_MODIFIED)?)? <= since
+            }
+        })
+    }
+}
+
+/// A `304 Not Modified` for a response with `headers`, if `conditions` find it unchanged.
+fn not_modified<B: Body>(
+    headers: &HeaderMap,
+    conditions: &Conditions,
+) -> Option<Response<CacheBody<B>>> {
+    if !conditions.unchanged(headers)? {
+        return None;
+    }
+    let mut response = Response::new(CacheBody::full(Bytes::new()));
+    *response.status_mut() = StatusCode::NOT_MODIFIED;
+    for name in NOT_MODIFIED_HEADERS {
+        for value in headers.get_all(&name) {
+            response.headers_mut().append(name.clone(), value.clone());
+        }
+    }
+    Some(response)
+}
+
+/// What the layer does with a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestPolicy {
+    /// Forward it untouched and store nothing.
+    Bypass,
+    /// Skip the lookup but store the response.
+    Refresh,
+    /// Serve a hit, or fetch and store.
+    Lookup,
+}
+
+fn request_policy(method: &Method, headers: &HeaderMap) -> RequestPolicy {
+    if (method != Method::GET && method != Method::HEAD)
+        || headers.contains_key(header::AUTHORIZATION)
+    {
+        return RequestPolicy::Bypass;
+    }
+    let mut policy = RequestPolicy::Lookup;
+    for (name, value) in cache_control(headers) {
+        match name.as_str() {
+            "no-store" => return RequestPolicy::Bypass,
+            "no-cache" => policy = RequestPolicy::Refresh,
+            "max-age" if seconds(value.as_ref()) == Some(Duration::ZERO) => {
+                policy = RequestPolicy::Refresh;
+            }
+            _ => {}
+        }
+    }
+    policy
+}
+
+/// Caches the responses of the wrapped HTTP service; see the [module docs](self).
+///
+/// Cloning a layer shares its store, so every service it wraps reads and writes the same
+/// entries.
+pub struct CacheLayer<S = DefaultHttpStore> {
+    store: Arc<S>,
+    key_headers: Vec<HeaderName>,
+    default_ttl: Option<Duration>,
+    max_body_size: usize,
+}
+
+impl<S> Clone for CacheLayer<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            key_headers: self.key_headers.clone(),
+            default_ttl: self.default_ttl,
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+impl<S> std::fmt::Debug for CacheLayer<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheLayer")
+            .field("key_headers", &self.key_headers)
+            .field("default_ttl", &self.default_ttl)
+            .field("max_body_size", &self.max_body_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CacheLayer {
+    /// A layer caching up to `max_size` responses in a [`DefaultHttpStore`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is zero, as [`ShardedExpiringLruCache::new`] does.
+    #[must_use]
+    pub fn new(max_size: usize) -> Self {
+        Self::with_store(ShardedExpiringLruCache::new(max_size))
+    }
+}
+
+impl<S> CacheLayer<S> {
+    /// A layer caching in `store`. Its values must honor [`Expires`] for entries to expire.
+    #[must_use]
+    pub fn with_store(store: S) -> Self {
+        Self::with_shared_store(Arc::new(store))
+    }
+
+    /// A layer caching in a store the caller keeps a handle to, to invalidate entries with.
+    #[must_use]
+    pub fn with_shared_store(store: Arc<S>) -> Self {
+        Self {
+            store,
+            key_headers: Vec::new(),
+            default_ttl: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Add a request header to the cache key, so requests that differ in it are cached
+    /// separately whether or not the upstream lists it in `Vary`.
+    #[must_use]
+    pub fn key_header(mut self, name: HeaderName) -> Self {
+        if !self.key_headers.contains(&name) {
+            self.key_headers.push(name);
+        }
+        self
+    }
+
+    /// Cache responses that carry neither `s-maxage` nor `max-age` for `ttl`. Without it such
+    /// responses are not cached.
+    #[must_use]
+    pub fn default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Buffer and cache bodies of at most `bytes` bytes; larger ones are streamed through
+    /// uncached. Defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    #[must_use]
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// The store responses are cached in.
+    #[must_use]
+    pub fn store(&self) -> &Arc<S> {
+        &self.store
+    }
+
+    /// The key `request` is cached under.
+    #[must_use]
+    pub fn key<B>(&self, request: &Request<B>) -> HttpCacheKey {
+        HttpCacheKey {
+            method: request.method().clone(),
+            uri: request.uri().clone(),
+            headers: header_values(request.headers(), self.key_headers.iter().cloned()),
+        }
+    }
+
+    /// How long a response with `status` and `headers` may be cached, or `None` if it may not.
+    ///
+    /// A response that sets a cookie is not cached: replaying it would hand one client's
+    /// cookie to every other.
+    fn ttl(&self, status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+        if !CACHEABLE_STATUSES.contains(&status.as_u16())
+            || headers.contains_key(header::SET_COOKIE)
+        {
+            return None;
+        }
+        let (mut max_age, mut s_maxage) = (None, None);
+        for (name, value) in cache_control(headers) {
+            match name.as_str() {
+                "no-store" | "no-cache" | "private" => return None,
+                "max-age" => max_age = seconds(value.as_ref()),
+                "s-maxage" => s_maxage = seconds(value.as_ref()),
+                _ => {}
+            }
+        }
+        s_maxage
+            .or(max_age)
+            .or(self.default_ttl)?
+            .min(MAX_LIFETIME)
+            .checked_sub(age(headers))
+            .filter(|ttl| !ttl.is_zero())
+    }
+}
+
+impl<I, S> Layer<I> for CacheLayer<S> {
+    type Service = CacheService<I, S>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        CacheService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service a [`CacheLayer`] wraps around `I`.
+pub struct CacheService<I, S = DefaultHttpStore> {
+    inner: I,
+    layer: CacheLayer<S>,
+}
+
+impl<I: Clone, S> Clone for CacheService<I, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+impl<I: std::fmt::Debug, S> std::fmt::Debug for CacheService<I, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CacheService")
+            .field("inner", &self.inner)
+            .field("layer", &self.layer)
+            .finish()
+    }
+}
+
+impl<I, S> CacheService<I, S> {
+    /// The store responses are cached in.
+    #[must_use]
+    pub fn store(&self) -> &Arc<S> {
+        &self.layer.store
+    }
+}
+
+type BoxFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send>>;
+
+impl<I, S, ReqB, ResB> Service<Request<ReqB>> for CacheService<I, S>
+where
+    I: Service<Request<ReqB>, Response = Response<ResB>> + Clone + Send + 'static,
+    I::Future: Send,
+    I::Error: Send,
+    S: ConcurrentCachedAsync<HttpCacheKey, CachedResponse> + Send + Sync + 'static,
+    ReqB: Send + 'static,
+    ResB: Body + Send + 'static,
+    ResB::Data: Send,
+    ResB::Error: Send,
+{
+    type Response = Response<CacheBody<ResB>>;
+    type Error = I::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqB>) -> Self::Future {
+        // The clone is not necessarily ready; call the one `poll_ready` was called on.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        let policy = request_policy(request.method(), request.headers());
+        if policy == RequestPolicy::Bypass {
+            let future = inner.call(request);
+            return Box::pin(async move {
+                let (parts, body) = future.await?.into_parts();
+                Ok(Response::from_parts(parts, CacheBody::upstream(body)))
+            });
+        }
+
+        let key = layer.key(&request);
+        let conditions = Conditions::take(request.headers_mut());
+        let request_headers = request.headers().clone();
+        Box::pin(async move {
+            if policy == RequestPolicy::Lookup
+                && let Ok(Some(hit)) = layer.store.async_cache_get(&key).await
+                && hit.vary_matches(&request_headers)
+            {
+                return Ok(hit.respond(&conditions));
+            }
+
+            let (parts, body) = inner.call(request).await?.into_parts();
+            let lifetime = vary_names(&parts.headers)
+                .and_then(|vary| Some((layer.ttl(parts.status, &parts.headers)?, vary)));
+            let response = match lifetime {
+                None => Response::from_parts(parts, CacheBody::upstream(body)),
+                Some((ttl, vary)) => match buffer(body, layer.max_body_size).await {
+                    Err(body) => Response::from_parts(parts, body),
+                    Ok(body) => {
+                        let stored_at = Instant::now();
+                        // An instant past what the platform can represent is not cached.
+                        if let Some(expires_at) = stored_at.checked_add(ttl) {
+                            let entry = CachedResponse {
+                                status: parts.status,
+                                version: parts.version,
+                                headers: parts.headers.clone(),
+                                body: body.clone(),
+                                stored_at,
+                                upstream_age: age(&parts.headers),
+                                expires_at,
+                                vary: header_values(&request_headers, vary),
+                            };
+                            let _ = layer.store.async_cache_set(key, entry).await;
+                        }
+                        Response::from_parts(parts, CacheBody::full(body))
+                    }
+                },
+            };
+            // The conditional headers were withheld from the upstream, so answer them here.
+            if response.status() == StatusCode::OK
+                && let Some(mut not_modified) = not_modified(response.headers(), &conditions)
+            {
+                *not_modified.version_mut() = response.version();
+                return Ok(not_modified);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Read `body` to the end if it fits in `limit` bytes. Otherwise, or if it fails, return a
+/// body that replays what was read and then continues with the rest (or the error).
+async fn buffer<B: Body>(body: B, limit: usize) -> Result<Bytes, CacheBody<B>> {
+    let mut body = Box::pin(body);
+    if body.size_hint().lower() > limit as u64 {
+        return Err(CacheBody::resume(Bytes::new(), body));
+    }
+    let mut buffered = BytesMut::new();
+    while let Some(frame) = body.frame().await {
+        match frame {
+            Ok(frame) => {
+                let Ok(mut data) = frame.into_data() else {
+                    // Trailers are not cached.
+                    continue;
+                };
+                if buffered.len() + data.remaining() > limit {
+                    buffered.extend_from_slice(&data.copy_to_bytes(data.remaining()));
+                    return Err(CacheBody::resume(buffered.freeze(), body));
+                }
+                buffered.extend_from_slice(&data.copy_to_bytes(data.remaining()));
+            }
+            Err(error) => {
+                return Err(CacheBody {
+                    kind: Kind::Failed {
+                        prefix: non_empty(buffered.freeze()),
+                        error: Some(Box::new(error)),
+                    },
+                });
+            }
+        }
+    }
+    Ok(buffered.freeze())
+}
+
+fn non_empty(bytes: Bytes) -> Option<Bytes> {
+    (!bytes.is_empty()).then_some(bytes)
+}
+
+/// The body of a response from a [`CacheService`]: a cached or buffered body, or the upstream's
+/// own body streamed through.
+pub struct CacheBody<B: Body> {
+    kind: Kind<B>,
+}
+
+enum Kind<B: Body> {
+    Full(Option<Bytes>),
+    Upstream {
+        prefix: Option<Bytes>,
+        body: Pin<Box<B>>,
+    },
+    Failed {
+        prefix: Option<Bytes>,
+        error: Option<Box<B::Error>>,
+    },
+}
+
+impl<B: Body> CacheBody<B> {
+    fn full(bytes: Bytes) -> Self {
+        Self {
+            kind: Kind::Full(non_empty(bytes)),
+        }
+    }
+
+    fn upstream(body: B) -> Self {
+        Self::resume(Bytes::new(), Box::pin(body))
+    }
+
+    fn resume(prefix: Bytes, body: Pin<Box<B>>) -> Self {
+        Self {
+            kind: Kind::Upstream {
+                prefix: non_empty(prefix),
+                body,
+            },
+        }
+    }
+}
+
+impl<B: Body> std::fmt::Debug for CacheBody<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match &self.kind {
+            Kind::Full(_) => "Full",
+            Kind::Upstream { .. } => "Upstream",
+            Kind::Failed { .. } => "Failed",
+        };
+        f.debug_struct("CacheBody")
+            .field("kind", &kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: Body> Body for CacheBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, B::Error>>> {
+        match &mut self.get_mut().kind {
+            Kind::Full(bytes) => Poll::Ready(bytes.take().map(|bytes| Ok(Frame::data(bytes)))),
+            Kind::Upstream { prefix, body } => {
+                if let Some(prefix) = prefix.take() {
+                    return Poll::Ready(Some(Ok(Frame::data(prefix))));
+                }
+                body.as_mut().poll_frame(cx).map(|frame| {
+                    frame.map(|frame| {
+                        frame.map(|frame| {
+                            frame.map_data(|mut data| data.copy_to_bytes(data.remaining()))
+                        })
+                    })
+                })
+            }
+            Kind::Failed { prefix, error } => {
+                if let Some(prefix) = prefix.take() {
+                    return Poll::Ready(Some(Ok(Frame::data(prefix))));
+                }
+                Poll::Ready(error.take().map(|error| Err(*error)))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.kind {
+            Kind::Full(bytes) => bytes.is_none(),
+            Kind::Upstream { prefix, body } => prefix.is_none() && body.is_end_stream(),
+            Kind::Failed { prefix, error } => prefix.is_none() && error.is_none(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let prefix_len = |prefix: &Option<Bytes>| prefix.as_ref().map_or(0, |p| p.len() as u64);
+        match &self.kind {
+            Kind::Full(bytes) => SizeHint::with_exact(prefix_len(bytes)),
+            Kind::Upstream { prefix, body } => {
+                let rest = body.size_hint();
+                let mut hint = SizeHint::new();
+                hint.set_lower(rest.lower() + prefix_len(prefix));
+                if let Some(upper) = rest.upper() {
+                    hint.set_upper(upper + prefix_len(prefix));
+                }
+                hint
+            }
+            Kind::Failed { prefix, .. } => SizeHint::with_exact(prefix_len(prefix)),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use cached::macros::cached;
+
+#[cached(ttl_secs = 60, none_ttl_secs = 5)]
+fn my_fn(x: u64) -> Option<u64> {
+    Some(x)
+}
+
+fn main() {}
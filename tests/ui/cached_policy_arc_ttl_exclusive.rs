@@ -0,0 +1,8 @@
+use cached::macros::cached;
+
+#[cached(max_size = 100, ttl_secs = 60, policy = "arc")]
+fn my_fn(x: u32) -> u32 {
+    x
+}
+
+fn main() {}
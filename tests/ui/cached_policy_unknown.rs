@@ -0,0 +1,8 @@
+use cached::macros::cached;
+
+#[cached(max_size = 100, policy = "mru")]
+fn my_fn(x: u32) -> u32 {
+    x
+}
+
+fn main() {}
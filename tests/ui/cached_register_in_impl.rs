@@ -0,0 +1,12 @@
+use cached::macros::cached;
+
+struct Service;
+
+impl Service {
+    #[cached(in_impl = true, register = true)]
+    fn lookup(&self, x: u32) -> u32 {
+        x * 2
+    }
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use cached::macros::cached;
+
+#[cached(max_size = 100, ttl_fn = { std::time::Duration::from_secs(*result) })]
+fn my_fn(x: u64) -> u64 {
+    x
+}
+
+fn main() {}
@@ -1,6 +1,7 @@
 use cached::macros::concurrent_cached;
 
-// Option<T> + cache_none=true on redis should say "Option<T> return types", not "plain".
+// cache_none=true on redis needs `Result<Option<T>, E>`: a bare `Option<T>` has no room for
+// store errors.
 #[concurrent_cached(map_error = "|e| e", redis = true, ttl_secs = 60, cache_none = true)]
 fn my_fn(k: i32) -> Option<i32> {
     Some(k)
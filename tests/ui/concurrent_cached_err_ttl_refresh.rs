@@ -0,0 +1,8 @@
+use cached::macros::concurrent_cached;
+
+#[concurrent_cached(ttl_secs = 60, refresh = true, cache_err = true, err_ttl_millis = 500)]
+fn my_fn(x: u64) -> Result<u64, String> {
+    Ok(x)
+}
+
+fn main() {}
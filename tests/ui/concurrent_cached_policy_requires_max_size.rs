@@ -0,0 +1,8 @@
+use cached::macros::concurrent_cached;
+
+#[concurrent_cached(policy = "arc")]
+fn my_fn(x: u32) -> u32 {
+    x
+}
+
+fn main() {}
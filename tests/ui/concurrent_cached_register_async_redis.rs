@@ -0,0 +1,8 @@
+use cached::macros::concurrent_cached;
+
+#[concurrent_cached(redis = true, ttl_secs = 60, register = true)]
+async fn lookup(x: u32) -> Result<u32, cached::RedisCacheError> {
+    Ok(x * 2)
+}
+
+fn main() {}
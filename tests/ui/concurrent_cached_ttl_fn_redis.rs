@@ -0,0 +1,8 @@
+use cached::macros::concurrent_cached;
+
+#[concurrent_cached(redis = true, ttl_secs = 60, ttl_fn = { std::time::Duration::from_secs(*result) })]
+fn my_fn(x: u64) -> Result<u64, String> {
+    Ok(x)
+}
+
+fn main() {}
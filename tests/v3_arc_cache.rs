@@ -0,0 +1,212 @@
+//! `ArcCache` / `ShardedArcCache`: adaptive replacement, builder validation, `on_evict`, and
+//! the `policy = "arc"` macro selection.
+
+use std::sync::{Arc, Mutex};
+
+use cached::{
+    ArcCache, BuildError, Cached, CachedIter, ConcurrentCacheBase, ConcurrentCached, LruCache,
+    ShardedArcCache,
+};
+
+/// Use `hot` twice, then write `scan` one-off keys: the access pattern LRU handles worst.
+fn hot_then_scan<C: Cached<u32, u32>>(cache: &mut C, hot: u32, scan: u32) {
+    for k in 0..hot {
+        cache.cache_set(k, k);
+        let _ = cache.cache_get(&k);
+    }
+    for k in 1000..1000 + scan {
+        cache.cache_set(k, k);
+    }
+}
+
+#[test]
+fn build_requires_a_positive_max_size() {
+    assert!(matches!(
+        ArcCache::<u32, u32>::builder().build(),
+        Err(BuildError::MissingRequired("max_size"))
+    ));
+    assert!(matches!(
+        ArcCache::<u32, u32>::builder().max_size(0).build(),
+        Err(BuildError::InvalidValue {
+            field: "max_size",
+            ..
+        })
+    ));
+    assert!(ShardedArcCache::<u32, u32>::builder().build().is_err());
+    assert!(
+        ShardedArcCache::<u32, u32>::builder()
+            .max_size(64)
+            .per_shard_max_size(16)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn a_scan_does_not_flush_the_frequent_entries() {
+    let mut lru = LruCache::new(10);
+    hot_then_scan(&mut lru, 5, 100);
+    assert!((0..5).all(|k| lru.cache_get(&k).is_none()));
+
+    let mut arc = ArcCache::new(10);
+    hot_then_scan(&mut arc, 5, 100);
+    assert!((0..5).all(|k| arc.cache_get(&k).is_some()));
+    assert_eq!(arc.cache_size(), 10);
+    assert_eq!(arc.frequent_keys().len(), 5);
+}
+
+#[test]
+fn the_target_follows_the_workload() {
+    let mut cache = ArcCache::new(4);
+    for k in [0, 1] {
+        cache.cache_set(k, k);
+        let _ = cache.cache_get(&k);
+    }
+    // Once-seen keys pushed out by newer ones are remembered in B1 ...
+    for k in 10..14 {
+        cache.cache_set(k, k);
+    }
+    assert_eq!(cache.target(), 0);
+    assert_eq!(cache.ghost_len(), (2, 0));
+    // ... and coming back to them shows recency pays, so the recent list's target grows.
+    cache.cache_set(10, 10);
+    cache.cache_set(11, 11);
+    assert_eq!(cache.target(), 2);
+    let (b1, b2) = cache.ghost_len();
+    assert!(b1 + b2 + cache.cache_size() <= 2 * cache.capacity());
+}
+
+#[test]
+fn evictions_and_removals_fire_on_evict() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let mut cache = ArcCache::builder()
+        .max_size(2)
+        .on_evict(move |k: &u32, v: &u32| sink.lock().unwrap().push((*k, *v)))
+        .build()
+        .unwrap();
+    cache.cache_set(1, 10);
+    cache.cache_set(2, 20);
+    cache.cache_set(3, 30);
+    assert_eq!(cache.cache_remove(&3), Some(30));
+    assert_eq!(*evicted.lock().unwrap(), [(1, 10), (3, 30)]);
+    assert_eq!(cache.cache_evictions(), Some(2));
+
+    assert_eq!(cache.retain(|_, v| *v > 100), 1);
+    assert_eq!(evicted.lock().unwrap().last(), Some(&(2, 20)));
+    assert_eq!(cache.cache_size(), 0);
+}
+
+#[test]
+fn shrinking_evicts_down_to_the_new_bound() {
+    let mut cache = ArcCache::new(8);
+    for k in 0..8 {
+        cache.cache_set(k, k);
+    }
+    assert_eq!(cache.set_max_size(3), Some(8));
+    assert_eq!(cache.cache_size(), 3);
+    assert!(cache.target() <= 3);
+    assert!(cache.try_set_max_size(0).is_err());
+    // The survivors are the most recently written keys.
+    let mut keys: Vec<u32> = cache.keys().copied().collect();
+    keys.sort_unstable();
+    assert_eq!(keys, [5, 6, 7]);
+}
+
+#[test]
+fn sharded_cache_keeps_frequent_entries_per_shard() {
+    let cache: ShardedArcCache<u32, u32> = ShardedArcCache::builder()
+        .shards(1)
+        .max_size(16)
+        .build()
+        .unwrap();
+    for k in 0..8 {
+        cache.set(k, k);
+        assert_eq!(cache.get(&k), Some(k));
+    }
+    for k in 1000..1200 {
+        cache.set(k, k);
+    }
+    assert!((0..8).all(|k| cache.peek(&k).is_some()));
+    assert_eq!(cache.len(), 16);
+    assert_eq!(cache.cache_hits(), Some(8));
+    assert_eq!(cache.cache_capacity(), Some(16));
+
+    let copy = cache.deep_clone();
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(copy.len(), 16);
+}
+
+#[test]
+fn sharded_on_evict_sees_every_removal() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedArcCache<u32, u32> = ShardedArcCache::builder()
+        .shards(4)
+        .per_shard_max_size(16)
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    for k in 0..200 {
+        cache.cache_set(k, k).unwrap();
+    }
+    assert!(cache.len() <= 64);
+    assert_eq!(evicted.lock().unwrap().len(), 200 - cache.len());
+
+    let before = evicted.lock().unwrap().len();
+    let odd = (0..200).filter(|k| k % 2 == 1 && cache.contains(k)).count();
+    assert_eq!(cache.retain(|k, _| k % 2 == 0), odd);
+    assert_eq!(evicted.lock().unwrap().len(), before + odd);
+
+    let left = cache.len();
+    cache.cache_clear_with_on_evict();
+    assert!(cache.is_empty());
+    assert_eq!(evicted.lock().unwrap().len(), before + odd + left);
+}
+
+#[cfg(feature = "proc_macro")]
+mod macros {
+    use super::*;
+    use cached::macros::{cached, concurrent_cached};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ARC_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static SHARDED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[cached(max_size = 4, policy = "arc")]
+    fn arc_square(x: u32) -> u32 {
+        ARC_CALLS.fetch_add(1, Ordering::SeqCst);
+        x * x
+    }
+
+    #[concurrent_cached(max_size = 64, shards = 2, policy = "arc")]
+    fn sharded_square(x: u32) -> u32 {
+        SHARDED_CALLS.fetch_add(1, Ordering::SeqCst);
+        x * x
+    }
+
+    #[cached(max_size = 4, policy = "lru")]
+    fn lru_square(x: u32) -> u32 {
+        x * x
+    }
+
+    #[test]
+    fn policy_arc_selects_the_arc_stores() {
+        assert_eq!(arc_square(3), 9);
+        assert_eq!(arc_square(3), 9);
+        assert_eq!(ARC_CALLS.load(Ordering::SeqCst), 1);
+        let cache: &ArcCache<u32, u32> = &ARC_SQUARE.read();
+        assert_eq!(cache.frequent_keys(), [3]);
+
+        assert_eq!(sharded_square(5), 25);
+        assert_eq!(sharded_square(5), 25);
+        assert_eq!(SHARDED_CALLS.load(Ordering::SeqCst), 1);
+        let cache: &ShardedArcCache<u32, u32> = &SHARDED_SQUARE;
+        assert_eq!(cache.shards(), 2);
+
+        assert_eq!(lru_square(2), 4);
+        let cache: &LruCache<u32, u32> = &LRU_SQUARE.read();
+        assert_eq!(cache.cache_size(), 1);
+    }
+}
@@ -0,0 +1,409 @@
+//! Budgeted eviction: `evict_budgeted` examines a bounded number of entries per call and
+//! resumes from where the last call stopped, across shards on the sharded stores, until the
+//! sweep finishes; `evict_for` takes such steps until it finishes or its time is up.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use cached::stores::{ShardedExpiringCache, ShardedExpiringLruCache};
+use cached::time::{Duration, Instant};
+use cached::{
+    CacheEvict, Cached, ConcurrentCacheEvict, EvictProgress, Expires, ExpiringCache,
+    ExpiringLruCache, LruPolicy, PolicyCache, ShardedPolicyCache,
+};
+
+/// A value that expires at its deadline, if it has one.
+#[derive(Clone, Debug)]
+struct Deadline(Option<Instant>);
+
+impl Deadline {
+    /// Expired from the moment it is stored.
+    fn due() -> Self {
+        Self(Some(Instant::now()))
+    }
+
+    fn live() -> Self {
+        Self(Some(Instant::now() + Duration::from_secs(3600)))
+    }
+
+    /// `Deadline::due()` for even keys, `Deadline::live()` for odd ones.
+    fn for_key(k: u32) -> Self {
+        if k.is_multiple_of(2) {
+            Self::due()
+        } else {
+            Self::live()
+        }
+    }
+}
+
+impl Expires for Deadline {
+    fn is_expired(&self) -> bool {
+        self.0.is_some_and(|d| Instant::now() >= d)
+    }
+
+    fn expires_at(&self) -> Option<Instant> {
+        self.0
+    }
+}
+
+/// Call `step` until it reports the sweep finished, checking that no call reclaims more than
+/// `max_entries`. Returns the total reclaimed and the number of calls.
+fn sweep(max_entries: usize, mut step: impl FnMut(usize) -> EvictProgress) -> (usize, usize) {
+    let mut reclaimed = 0;
+    for calls in 1.. {
+        let progress = step(max_entries);
+        assert!(progress.reclaimed <= max_entries, "{progress:?}");
+        reclaimed += progress.reclaimed;
+        if progress.finished {
+            return (reclaimed, calls);
+        }
+        assert!(calls < 10_000, "sweep never finished");
+    }
+    unreachable!()
+}
+
+fn counter() -> (
+    Arc<AtomicUsize>,
+    impl Fn(&u32, &Deadline) + Send + Sync + 'static,
+) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let sink = Arc::clone(&count);
+    (count, move |_: &u32, _: &Deadline| {
+        sink.fetch_add(1, Ordering::Relaxed);
+    })
+}
+
+#[test]
+fn expiring_steps_resume_until_the_map_is_walked() {
+    let (evicted, on_evict) = counter();
+    let mut cache: ExpiringCache<u32, Deadline> =
+        ExpiringCache::builder().on_evict(on_evict).build().unwrap();
+    for k in 0..1000 {
+        cache.cache_set(k, Deadline::for_key(k));
+    }
+    let first = cache.evict_budgeted(100);
+    assert!(!first.finished);
+    let (rest, calls) = sweep(100, |n| cache.evict_budgeted(n));
+    // 1000 entries at 100 a step, plus the step that finds the end.
+    assert_eq!((first.reclaimed + rest, calls + 1), (500, 11));
+    assert_eq!(evicted.load(Ordering::Relaxed), 500);
+    assert_eq!(cache.cache_evictions(), Some(500));
+    assert_eq!(cache.cache_size(), 500);
+    // A finished sweep starts the next one from the beginning.
+    cache.cache_set(0, Deadline::due());
+    assert_eq!(
+        cache.evict_budgeted(usize::MAX),
+        EvictProgress {
+            reclaimed: 1,
+            finished: true
+        }
+    );
+}
+
+#[test]
+fn expiring_steps_survive_the_map_growing_between_them() {
+    let mut cache: ExpiringCache<u32, Deadline> = ExpiringCache::new();
+    for k in 0..1000 {
+        cache.cache_set(k, Deadline::for_key(k));
+    }
+    // Enough live inserts between the first steps to double the table a few times mid-sweep.
+    let mut next = 1000..8000;
+    let (reclaimed, _) = sweep(50, |n| {
+        let progress = cache.evict_budgeted(n);
+        for k in next.by_ref().take(300) {
+            cache.cache_set(k, Deadline::live());
+        }
+        progress
+    });
+    assert_eq!(reclaimed, 500);
+    assert_eq!(cache.cache_size(), 500 + next.start as usize - 1000);
+
+    let sharded: ShardedExpiringCache<u32, Deadline> =
+        ShardedExpiringCache::builder().shards(4).build().unwrap();
+    for k in 0..1000 {
+        sharded.set(k, Deadline::for_key(k));
+    }
+    let mut next = 1000..8000;
+    let (reclaimed, _) = sweep(50, |n| {
+        let progress = sharded.evict_budgeted(n);
+        for k in next.by_ref().take(300) {
+            sharded.set(k, Deadline::live());
+        }
+        progress
+    });
+    assert_eq!(reclaimed, 500);
+    assert_eq!(sharded.len(), 500 + next.start as usize - 1000);
+}
+
+#[test]
+fn expiring_with_a_wheel_steps_through_due_keys() {
+    let mut cache: ExpiringCache<u32, Deadline> = ExpiringCache::builder()
+        .timer_wheel(Duration::from_millis(1))
+        .build()
+        .unwrap();
+    for k in 0..1000 {
+        cache.cache_set(k, Deadline::for_key(k));
+    }
+    std::thread::sleep(Duration::from_millis(5));
+    // 500 due keys at 100 a step, plus the step that finds none left.
+    assert_eq!(sweep(100, |n| cache.evict_budgeted(n)), (500, 6));
+    assert_eq!(cache.cache_size(), 500);
+}
+
+#[test]
+fn expiring_lru_and_policy_steps_reclaim_every_expired_entry() {
+    let mut lru: ExpiringLruCache<u32, Deadline> =
+        ExpiringLruCache::builder().max_size(2000).build().unwrap();
+    for k in 0..1000 {
+        lru.cache_set(k, Deadline::for_key(k));
+    }
+    assert_eq!(sweep(64, |n| lru.evict_budgeted(n)).0, 500);
+    assert_eq!(lru.cache_size(), 500);
+
+    let ttl = Duration::from_millis(100);
+    let mut policy = PolicyCache::builder()
+        .max_size(2000)
+        .policy(LruPolicy::new())
+        .ttl(ttl)
+        .build()
+        .unwrap();
+    for k in 0..500u32 {
+        policy.cache_set(k, k);
+    }
+    std::thread::sleep(ttl + Duration::from_millis(50));
+    for k in 500..1000 {
+        policy.cache_set(k, k);
+    }
+    assert_eq!(sweep(64, |n| policy.evict_budgeted(n)).0, 500);
+    assert_eq!(policy.cache_size(), 500);
+    assert_eq!(CacheEvict::evict(&mut policy), 0);
+}
+
+#[test]
+fn evict_for_finishes_a_sweep_within_a_generous_budget() {
+    let mut cache: ExpiringCache<u32, Deadline> = ExpiringCache::new();
+    for k in 0..10_000 {
+        cache.cache_set(k, Deadline::for_key(k));
+    }
+    let progress = CacheEvict::evict_for(&mut cache, Duration::from_secs(60));
+    assert_eq!(
+        progress,
+        EvictProgress {
+            reclaimed: 5000,
+            finished: true
+        }
+    );
+    // A zero budget still takes one step.
+    for k in 0..10_000 {
+        cache.cache_set(k, Deadline::due());
+    }
+    let progress = CacheEvict::evict_for(&mut cache, Duration::ZERO);
+    assert!(progress.reclaimed > 0 && !progress.finished, "{progress:?}");
+}
+
+#[test]
+fn sharded_expiring_steps_cover_every_shard() {
+    let (evicted, on_evict) = counter();
+    let cache: ShardedExpiringCache<u32, Deadline> = ShardedExpiringCache::builder()
+        .shards(8)
+        .on_evict(on_evict)
+        .build()
+        .unwrap();
+    for k in 0..1000 {
+        cache.set(k, Deadline::for_key(k));
+    }
+    // Steps smaller than a shard resume inside it...
+    let (reclaimed, calls) = sweep(10, |n| cache.evict_budgeted(n));
+    assert_eq!(reclaimed, 500);
+    assert!(calls > 100, "{calls}");
+    // ...and larger ones run on into the next.
+    let (reclaimed, calls) = sweep(300, |n| cache.evict_budgeted(n));
+    assert_eq!(reclaimed, 0);
+    assert!(calls <= 3, "{calls}");
+    assert_eq!(evicted.load(Ordering::Relaxed), 500);
+    assert_eq!(cache.len(), 500);
+
+    let wheeled: ShardedExpiringCache<u32, Deadline> = ShardedExpiringCache::builder()
+        .shards(8)
+        .timer_wheel(Duration::from_millis(1))
+        .build()
+        .unwrap();
+    for k in 0..1000 {
+        wheeled.set(k, Deadline::for_key(k));
+    }
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(sweep(100, |n| wheeled.evict_budgeted(n)), (500, 6));
+}
+
+#[test]
+fn sharded_steps_survive_a_reshard_mid_sweep() {
+    let cache: ShardedExpiringLruCache<u32, Deadline> = ShardedExpiringLruCache::builder()
+        .shards(8)
+        .max_size(4096)
+        .build()
+        .unwrap();
+    for k in 0..1000 {
+        cache.set(k, Deadline::for_key(k));
+    }
+    let mut reclaimed = cache.evict_budgeted(100).reclaimed;
+    cache.reshard(2).unwrap();
+    // The cursor outlives the reshard; whatever the interrupted sweep misses, the next gets.
+    reclaimed += sweep(100, |n| cache.evict_budgeted(n)).0;
+    reclaimed += sweep(100, |n| cache.evict_budgeted(n)).0;
+    assert_eq!(reclaimed, 500);
+    assert_eq!(cache.len(), 500);
+
+    let ttl = Duration::from_millis(20);
+    let policy: ShardedPolicyCache<u32, u32, LruPolicy<u32>> = ShardedPolicyCache::builder()
+        .shards(4)
+        .per_shard_max_size(1024)
+        .policy(LruPolicy::new())
+        .ttl(ttl)
+        .build()
+        .unwrap();
+    for k in 0..1000 {
+        policy.set(k, k);
+    }
+    std::thread::sleep(ttl * 2);
+    let progress = ConcurrentCacheEvict::evict_for(&policy, Duration::from_secs(60));
+    assert_eq!(
+        progress,
+        EvictProgress {
+            reclaimed: 1000,
+            finished: true
+        }
+    );
+    assert_eq!(policy.len(), 0);
+}
+
+/// A store that implements only `evict`.
+struct Bag(Vec<bool>);
+
+impl CacheEvict for Bag {
+    fn evict(&mut self) -> usize {
+        let before = self.0.len();
+        self.0.retain(|expired| !expired);
+        before - self.0.len()
+    }
+}
+
+impl ConcurrentCacheEvict for Bag {
+    fn evict(&self) -> usize {
+        self.0.iter().filter(|expired| **expired).count()
+    }
+}
+
+#[test]
+fn the_default_step_is_a_full_evict() {
+    let mut bag = Bag(vec![true, false, true, true]);
+    let done = EvictProgress {
+        reclaimed: 3,
+        finished: true,
+    };
+    assert_eq!(ConcurrentCacheEvict::evict_budgeted(&bag, 1), done);
+    assert_eq!(CacheEvict::evict_budgeted(&mut bag, 1), done);
+    assert_eq!(CacheEvict::evict_for(&mut bag, Duration::ZERO).reclaimed, 0);
+}
+
+#[cfg(feature = "time_stores")]
+mod ttl {
+    use super::*;
+    use cached::{
+        LruTtlCache, ShardedLruTtlCache, ShardedTtlCache, ShardedTtlSortedCache, TtlCache,
+        TtlSortedCache,
+    };
+
+    const SHORT: Duration = Duration::from_millis(20);
+    const LONG: Duration = Duration::from_secs(3600);
+
+    fn sleep_past_short() {
+        std::thread::sleep(Duration::from_millis(40));
+    }
+
+    #[test]
+    fn ttl_and_lru_ttl_steps_resume_until_the_store_is_walked() {
+        let mut ttl: TtlCache<u32, u32> = TtlCache::builder().ttl(SHORT).build().unwrap();
+        let mut lru: LruTtlCache<u32, u32> = LruTtlCache::builder()
+            .max_size(2000)
+            .ttl(SHORT)
+            .build()
+            .unwrap();
+        for k in 0..1000u32 {
+            if k.is_multiple_of(2) {
+                ttl.cache_set(k, k);
+                lru.cache_set(k, k);
+            } else {
+                ttl.set_with_ttl(k, k, LONG);
+                lru.set_with_ttl(k, k, LONG);
+            }
+        }
+        sleep_past_short();
+        assert_eq!(sweep(100, |n| ttl.evict_budgeted(n)), (500, 11));
+        assert_eq!(sweep(100, |n| lru.evict_budgeted(n)), (500, 11));
+        assert_eq!((ttl.cache_size(), lru.cache_size()), (500, 500));
+        assert_eq!(ttl.cache_evictions(), Some(500));
+    }
+
+    #[test]
+    fn ttl_sorted_steps_take_the_expired_front() {
+        let mut cache: TtlSortedCache<u32, u32> =
+            TtlSortedCache::builder().ttl(SHORT).build().unwrap();
+        for k in 0..1000u32 {
+            if k.is_multiple_of(2) {
+                cache.cache_set(k, k);
+            } else {
+                cache.set_with(k, k).ttl(LONG).set();
+            }
+        }
+        sleep_past_short();
+        // Only expired entries are examined, so the last full step finishes the sweep.
+        assert_eq!(sweep(100, |n| cache.evict_budgeted(n)), (500, 5));
+        assert_eq!(cache.cache_size(), 500);
+    }
+
+    #[test]
+    fn sharded_ttl_stores_sweep_every_shard() {
+        let evicted = Arc::new(AtomicUsize::new(0));
+        let sink = Arc::clone(&evicted);
+        let ttl: ShardedTtlCache<u32, u32> = ShardedTtlCache::builder()
+            .shards(16)
+            .ttl(SHORT)
+            .on_evict(move |_, _| {
+                sink.fetch_add(1, Ordering::Relaxed);
+            })
+            .build()
+            .unwrap();
+        let lru: ShardedLruTtlCache<u32, u32> = ShardedLruTtlCache::builder()
+            .shards(4)
+            .max_size(4096)
+            .ttl(SHORT)
+            .build()
+            .unwrap();
+        let sorted: ShardedTtlSortedCache<u32, u32> = ShardedTtlSortedCache::builder()
+            .shards(4)
+            .ttl(SHORT)
+            .build()
+            .unwrap();
+        for k in 0..1000u32 {
+            if k.is_multiple_of(2) {
+                ttl.set(k, k);
+                lru.set(k, k);
+                sorted.set(k, k);
+            } else {
+                ttl.set_with_ttl(k, k, LONG);
+                lru.set_with_ttl(k, k, LONG);
+                sorted.set_with(k, k).ttl(LONG).set();
+            }
+        }
+        sleep_past_short();
+        let (reclaimed, calls) = sweep(50, |n| ttl.evict_budgeted(n));
+        assert_eq!(reclaimed, 500);
+        assert!(calls > 20, "{calls}");
+        assert_eq!(evicted.load(Ordering::Relaxed), 500);
+        assert_eq!(sweep(50, |n| lru.evict_budgeted(n)).0, 500);
+        assert_eq!(sweep(50, |n| sorted.evict_budgeted(n)).0, 500);
+        for len in [ttl.len(), lru.len(), sorted.len()] {
+            assert_eq!(len, 500);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! `FaultyCache`: seeded failures, latency, dropped writes and stale reads around a store.
+
+use std::time::Instant;
+
+use cached::stores::{FaultyCache, InjectedFault};
+use cached::time::Duration;
+use cached::{BuildError, ConcurrentCacheBase, ConcurrentCached, ShardedUnboundCache};
+
+fn store() -> ShardedUnboundCache<u32, u32> {
+    ShardedUnboundCache::builder().build().unwrap()
+}
+
+/// Which of `n` sets fail, for a cache failing half its operations with `seed`.
+fn failures(seed: u64, n: u32) -> Vec<bool> {
+    let cache = FaultyCache::builder(store())
+        .fail_rate(0.5)
+        .seed(seed)
+        .build()
+        .unwrap();
+    (0..n).map(|k| cache.cache_set(k, k).is_err()).collect()
+}
+
+#[test]
+fn build_rejects_rates_outside_zero_to_one() {
+    for rate in [-0.1, 1.5, f64::NAN] {
+        assert!(matches!(
+            FaultyCache::builder(store()).fail_rate(rate).build(),
+            Err(BuildError::InvalidValue {
+                field: "fail_rate",
+                ..
+            })
+        ));
+    }
+    assert!(
+        FaultyCache::builder(store())
+            .drop_write_rate(2.0)
+            .build()
+            .is_err()
+    );
+    assert!(
+        FaultyCache::builder(store())
+            .stale_rate(-1.0)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn without_faults_it_is_the_store() {
+    let cache = FaultyCache::builder(store()).build().unwrap();
+    assert_eq!(cache.cache_set(1, 10).unwrap(), None);
+    assert_eq!(cache.cache_set(1, 11).unwrap(), Some(10));
+    assert_eq!(cache.cache_get(&1).unwrap(), Some(11));
+    assert!(cache.cache_contains(&1).unwrap());
+    assert_eq!(cache.cache_remove(&1).unwrap(), Some(11));
+    assert_eq!(cache.cache_size().unwrap(), Some(0));
+}
+
+#[test]
+fn failures_are_injected_before_the_store_is_touched() {
+    let cache = FaultyCache::builder(store())
+        .fail_rate(1.0)
+        .build()
+        .unwrap();
+    assert_eq!(cache.cache_set(1, 1), Err(InjectedFault));
+    assert_eq!(cache.cache_get(&1), Err(InjectedFault));
+    assert_eq!(cache.cache_clear(), Err(InjectedFault));
+    assert_eq!(cache.inner().get(&1), None);
+
+    assert!(cache.set_enabled(false));
+    assert_eq!(cache.cache_set(1, 1).unwrap(), None);
+    cache.set_enabled(true);
+    assert_eq!(cache.cache_get(&1), Err(InjectedFault));
+}
+
+#[test]
+fn the_seed_fixes_the_fault_sequence() {
+    let first = failures(42, 64);
+    assert_eq!(first, failures(42, 64));
+    assert_ne!(first, failures(43, 64));
+    let failed = first.iter().filter(|&&failed| failed).count();
+    assert!((16..=48).contains(&failed), "{failed} of 64 failed");
+}
+
+#[derive(Debug, PartialEq)]
+enum AppError {
+    CacheDown,
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("cache down")
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<std::convert::Infallible> for AppError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+#[test]
+fn the_supplied_error_is_returned() {
+    let cache = FaultyCache::builder(store())
+        .fail_rate(1.0)
+        .error(|| AppError::CacheDown)
+        .build()
+        .unwrap();
+    assert_eq!(cache.cache_get(&1), Err(AppError::CacheDown));
+}
+
+#[test]
+fn dropped_writes_report_success() {
+    let cache = FaultyCache::builder(store())
+        .drop_write_rate(1.0)
+        .build()
+        .unwrap();
+    assert_eq!(cache.cache_set(1, 1).unwrap(), None);
+    assert_eq!(cache.cache_get(&1).unwrap(), None);
+    assert_eq!(cache.inner().len(), 0);
+}
+
+#[test]
+fn stale_reads_return_the_previous_value() {
+    let cache = FaultyCache::builder(store())
+        .stale_rate(1.0)
+        .build()
+        .unwrap();
+    cache.cache_set(1, 10).unwrap();
+    // No previous value yet: the read goes to the store.
+    assert_eq!(cache.cache_get(&1).unwrap(), Some(10));
+    cache.cache_set(1, 11).unwrap();
+    assert_eq!(cache.cache_get(&1).unwrap(), Some(10));
+    cache.cache_remove(&1).unwrap();
+    assert_eq!(cache.cache_get(&1).unwrap(), Some(11));
+    assert_eq!(cache.inner().get(&1), None);
+}
+
+#[test]
+fn latency_delays_every_operation() {
+    let cache = FaultyCache::builder(store())
+        .latency(Duration::from_millis(20))
+        .latency_jitter(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    let start = Instant::now();
+    cache.cache_set(1, 1).unwrap();
+    cache.cache_get(&1).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_operations_are_faulted_too() {
+    use cached::ConcurrentCachedAsync;
+
+    futures::executor::block_on(async {
+        let cache = FaultyCache::builder(store())
+            .latency(Duration::from_millis(20))
+            .fail_rate(1.0)
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        assert_eq!(cache.async_cache_set(1, 1).await, Err(InjectedFault));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        cache.set_enabled(false);
+        cache.async_cache_set(1, 1).await.unwrap();
+        assert_eq!(cache.async_cache_get(&1).await.unwrap(), Some(1));
+    });
+}
+
+#[cfg(feature = "proc_macro")]
+mod concurrent_cached {
+    use super::*;
+    use cached::macros::concurrent_cached;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[concurrent_cached(
+        ty = "FaultyCache<ShardedUnboundCache<u32, u32>>",
+        create = r#"{ FaultyCache::builder(ShardedUnboundCache::builder().build().unwrap()).fail_rate(0.5).seed(3).build().unwrap() }"#,
+        map_error = r#"|_e: InjectedFault| "cache unavailable".to_string()"#
+    )]
+    fn double(x: u32) -> Result<u32, String> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        Ok(x * 2)
+    }
+
+    #[test]
+    fn store_failures_surface_through_map_error() {
+        let results: Vec<_> = (0..32).map(|_| double(1)).collect();
+        assert!(results.contains(&Ok(2)));
+        assert!(results.contains(&Err("cache unavailable".to_string())));
+        assert!(CALLS.load(Ordering::SeqCst) < 32);
+    }
+}
@@ -0,0 +1,260 @@
+//! Hot-key tracking (`hot_keys` / `hot_key_hashes` on the in-memory builders): the top-K
+//! summary counts `cache_get` lookups, attaches key clones, and merges per-shard summaries on
+//! the sharded stores.
+
+use cached::stores::{
+    ExpiringLruCache, LruCache, ShardedLruCache, ShardedUnboundCache, UnboundCache,
+};
+use cached::{
+    BuildError, Cached, CachedExt, ConcurrentCached, ConcurrentCachedExt, Expires, HotKey,
+};
+
+/// Look `key` up `times` times.
+fn read<C: Cached<u32, u32>>(cache: &mut C, key: u32, times: usize) {
+    for _ in 0..times {
+        let _ = cache.cache_get(&key);
+    }
+}
+
+fn keys(top: &[HotKey<u32>]) -> Vec<Option<u32>> {
+    top.iter().map(|h| h.key).collect()
+}
+
+#[test]
+fn untracked_stores_report_none() {
+    let mut cache: UnboundCache<u32, u32> = UnboundCache::new();
+    read(&mut cache, 1, 3);
+    assert_eq!(cache.top_keys(10), None);
+
+    let sharded: ShardedUnboundCache<u32, u32> = ShardedUnboundCache::new();
+    assert_eq!(ConcurrentCachedExt::top_keys(&sharded, 10), None);
+}
+
+#[test]
+fn unbound_counts_lookups_and_attaches_keys() {
+    let mut cache: UnboundCache<u32, u32> = UnboundCache::builder().hot_keys(8).build().unwrap();
+    for k in 0..4 {
+        cache.cache_set(k, k * 10);
+    }
+    read(&mut cache, 2, 5);
+    read(&mut cache, 0, 3);
+    read(&mut cache, 3, 1);
+
+    let top = cache.top_keys(2).unwrap();
+    assert_eq!(keys(&top), [Some(2), Some(0)]);
+    assert_eq!((top[0].count, top[0].error), (5, 0));
+    assert_eq!(top[1].guaranteed_count(), 3);
+    // Keys are attached from the store on the first lookup, even for entries set earlier.
+    assert!(cache.top_keys(10).unwrap().iter().all(|h| h.key.is_some()));
+}
+
+#[test]
+fn misses_are_counted_without_a_key_until_the_key_is_stored() {
+    let mut cache: LruCache<u32, u32> =
+        LruCache::builder().max_size(4).hot_keys(4).build().unwrap();
+    read(&mut cache, 7, 2);
+    assert_eq!(keys(&cache.top_keys(1).unwrap()), [None]);
+
+    cache.cache_set(7, 70);
+    read(&mut cache, 7, 1);
+    let top = cache.top_keys(1).unwrap();
+    assert_eq!(top[0].key, Some(7));
+    assert_eq!(top[0].count, 3);
+}
+
+#[test]
+fn a_full_summary_keeps_the_heavy_hitters() {
+    let mut cache: UnboundCache<u32, u32> = UnboundCache::builder().hot_keys(4).build().unwrap();
+    for k in 0..100 {
+        cache.cache_set(k, k);
+    }
+    // Two hot keys among a long tail of one-off lookups.
+    for round in 0..50 {
+        read(&mut cache, 1, 3);
+        read(&mut cache, 2, 2);
+        read(&mut cache, 10 + round, 1);
+    }
+    let top = cache.top_keys(2).unwrap();
+    assert_eq!(keys(&top), [Some(1), Some(2)]);
+    for hot in &top {
+        let actual = if hot.key == Some(1) { 150 } else { 100 };
+        assert!(hot.count >= actual, "{hot:?}");
+        assert!(hot.guaranteed_count() <= actual, "{hot:?}");
+    }
+}
+
+#[test]
+fn hash_only_tracking_works_for_keys_that_are_not_clone() {
+    #[derive(Hash, PartialEq, Eq)]
+    struct Opaque(u32);
+
+    let mut cache: UnboundCache<Opaque, u32> =
+        UnboundCache::builder().hot_key_hashes(4).build().unwrap();
+    cache.cache_set(Opaque(1), 1);
+    let _ = cache.cache_get(&Opaque(1));
+    let _ = cache.cache_get(&Opaque(1));
+    let _ = cache.cache_get(&Opaque(2));
+
+    let top = cache.top_keys(10).unwrap();
+    assert_eq!(top.len(), 2);
+    assert!(top.iter().all(|h| h.key.is_none()));
+    assert_eq!(top[0].count, 2);
+    assert_ne!(top[0].hash, top[1].hash);
+    // The hash is stable, so it identifies the key across calls.
+    let _ = cache.cache_get(&Opaque(1));
+    assert_eq!(cache.top_keys(1).unwrap()[0].hash, top[0].hash);
+}
+
+#[test]
+fn reset_metrics_clears_the_summary_but_clear_does_not() {
+    let mut cache: UnboundCache<u32, u32> = UnboundCache::builder().hot_keys(4).build().unwrap();
+    cache.cache_set(1, 1);
+    read(&mut cache, 1, 2);
+    cache.cache_clear();
+    assert_eq!(cache.top_keys(1).unwrap()[0].count, 2);
+    cache.cache_reset_metrics();
+    assert_eq!(cache.top_keys(1), Some(Vec::new()));
+}
+
+#[test]
+fn cloned_stores_track_independently() {
+    let mut cache: UnboundCache<u32, u32> = UnboundCache::builder().hot_keys(4).build().unwrap();
+    cache.cache_set(1, 1);
+    read(&mut cache, 1, 2);
+    let mut copy = cache.clone();
+    read(&mut copy, 1, 3);
+    assert_eq!(cache.top_keys(1).unwrap()[0].count, 2);
+    assert_eq!(copy.top_keys(1).unwrap()[0].count, 5);
+    assert_eq!(copy.top_keys(1).unwrap()[0].key, Some(1));
+}
+
+#[test]
+fn expiring_lru_tracks_through_the_wrapper() {
+    #[derive(Clone)]
+    struct Fresh;
+    impl Expires for Fresh {
+        fn is_expired(&self) -> bool {
+            false
+        }
+    }
+
+    let mut cache: ExpiringLruCache<u32, Fresh> = ExpiringLruCache::builder()
+        .max_size(4)
+        .hot_keys(4)
+        .build()
+        .unwrap();
+    cache.cache_set(3, Fresh);
+    let _ = cache.cache_get(&3);
+    let _ = cache.cache_get(&3);
+    let top = cache.top_keys(1).unwrap();
+    assert_eq!((top[0].key, top[0].count), (Some(3), 2));
+}
+
+#[test]
+fn zero_capacity_is_rejected() {
+    let err = UnboundCache::<u32, u32>::builder()
+        .hot_keys(0)
+        .build()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        BuildError::InvalidValue {
+            field: "hot_keys",
+            ..
+        }
+    ));
+    assert!(
+        ShardedUnboundCache::<u32, u32>::builder()
+            .hot_key_hashes(0)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn sharded_summaries_are_merged() {
+    let cache: ShardedUnboundCache<u32, u32> = ShardedUnboundCache::builder()
+        .shards(8)
+        .hot_keys(16)
+        .build()
+        .unwrap();
+    // Key `k` is read `k + 1` times; the keys spread across the shards' summaries.
+    for k in 0..16 {
+        cache.cache_set(k, k).unwrap();
+        for _ in 0..=k {
+            let _ = ConcurrentCached::cache_get(&cache, &k);
+        }
+    }
+    let top = ConcurrentCachedExt::top_keys(&cache, 3).unwrap();
+    assert_eq!(keys(&top), [Some(15), Some(14), Some(13)]);
+    assert_eq!(top[0].count, 16);
+    assert_eq!(
+        ConcurrentCachedExt::top_keys(&cache, 100).unwrap().len(),
+        16
+    );
+
+    ConcurrentCached::cache_reset_metrics(&cache).unwrap();
+    assert_eq!(ConcurrentCachedExt::top_keys(&cache, 3), Some(Vec::new()));
+}
+
+#[test]
+fn sharded_lru_counts_concurrent_lookups() {
+    let cache: ShardedLruCache<u32, u32> = ShardedLruCache::builder()
+        .max_size(64)
+        .shards(4)
+        .hot_keys(8)
+        .build()
+        .unwrap();
+    cache.cache_set(5, 50).unwrap();
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    let _ = ConcurrentCached::cache_get(&cache, &5);
+                }
+            });
+        }
+    });
+    let top = ConcurrentCachedExt::top_keys(&cache, 1).unwrap();
+    assert_eq!((top[0].key, top[0].count, top[0].error), (Some(5), 400, 0));
+}
+
+#[cfg(feature = "time_stores")]
+mod timed {
+    use super::{keys, read};
+    use cached::stores::{ShardedTtlCache, TtlCache, TtlSortedCache};
+    use cached::time::Duration;
+    use cached::{CachedExt, ConcurrentCached, ConcurrentCachedExt};
+
+    #[test]
+    fn ttl_stores_track_lookups() {
+        let mut ttl: TtlCache<u32, u32> = TtlCache::builder()
+            .ttl(Duration::from_secs(60))
+            .hot_keys(4)
+            .build()
+            .unwrap();
+        ttl.set(1, 1);
+        read(&mut ttl, 1, 2);
+        assert_eq!(keys(&ttl.top_keys(1).unwrap()), [Some(1)]);
+
+        let mut sorted: TtlSortedCache<u32, u32> = TtlSortedCache::builder()
+            .ttl(Duration::from_secs(60))
+            .hot_keys(4)
+            .build()
+            .unwrap();
+        sorted.set(2, 2);
+        read(&mut sorted, 2, 2);
+        assert_eq!(keys(&sorted.top_keys(1).unwrap()), [Some(2)]);
+
+        let sharded: ShardedTtlCache<u32, u32> = ShardedTtlCache::builder()
+            .ttl(Duration::from_secs(60))
+            .hot_keys(4)
+            .build()
+            .unwrap();
+        let _ = ConcurrentCached::cache_get(&sharded, &3);
+        assert_eq!(
+            keys(&ConcurrentCachedExt::top_keys(&sharded, 1).unwrap()),
+            [Some(3)]
+        );
+    }
+}
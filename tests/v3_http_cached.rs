@@ -0,0 +1,266 @@
+//! `HttpCached`: freshness read from `Cache-Control`, `Expires`/`Date` and `Age`, and the
+//! `stale-if-error` window that bounds `result_fallback`.
+
+#![cfg(feature = "http")]
+
+use std::time::{Duration, SystemTime};
+
+use cached::Expires;
+use cached::http::HttpCached;
+use cached::time::Instant;
+use http::HeaderMap;
+use http::header::{AGE, CACHE_CONTROL, DATE, EXPIRES, HeaderName};
+
+fn headers(pairs: &[(HeaderName, &str)]) -> HeaderMap {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.clone(), value.parse().unwrap()))
+        .collect()
+}
+
+/// Time from now until `value` expires, or zero if it already has.
+fn remaining<T>(value: &HttpCached<T>) -> Duration {
+    value
+        .expires_at()
+        .unwrap()
+        .saturating_duration_since(Instant::now())
+}
+
+#[test]
+fn max_age_sets_the_deadline_and_s_maxage_wins() {
+    let value = HttpCached::from_headers("v", &headers(&[(CACHE_CONTROL, "public, max-age=60")]));
+    assert!(!value.is_expired());
+    assert!(remaining(&value) > Duration::from_secs(59));
+    assert_eq!(*value.value(), "v");
+
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[(CACHE_CONTROL, "max-age=600, s-maxage=\"5\"")]),
+    );
+    assert!(remaining(&value) <= Duration::from_secs(5));
+    assert!(remaining(&value) > Duration::from_secs(4));
+}
+
+#[test]
+fn no_cache_no_store_and_missing_freshness_expire_immediately() {
+    for cache_control in ["no-cache", "max-age=60, no-store", "public"] {
+        let value = HttpCached::from_headers((), &headers(&[(CACHE_CONTROL, cache_control)]));
+        assert!(value.is_expired(), "{cache_control}");
+    }
+    assert!(HttpCached::from_headers((), &HeaderMap::new()).is_expired());
+}
+
+#[test]
+fn age_is_counted_against_the_lifetime() {
+    let value =
+        HttpCached::from_headers((), &headers(&[(CACHE_CONTROL, "max-age=60"), (AGE, "50")]));
+    assert!(remaining(&value) <= Duration::from_secs(10));
+    assert!(remaining(&value) > Duration::from_secs(9));
+    assert!(value.age() >= Duration::from_secs(50));
+
+    let value =
+        HttpCached::from_headers((), &headers(&[(CACHE_CONTROL, "max-age=60"), (AGE, "90")]));
+    assert!(value.is_expired());
+}
+
+#[test]
+fn an_age_past_the_lifetime_expires_and_shortens_the_stale_windows() {
+    // Far longer than this process has run, so it cannot be taken off an `Instant`.
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[
+            (CACHE_CONTROL, "max-age=60, stale-if-error=3600"),
+            (AGE, "1000000000"),
+        ]),
+    );
+    assert!(value.is_expired());
+    assert!(!value.serve_stale_on_error());
+    assert!(value.age() >= Duration::from_secs(1_000_000_000));
+
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[(CACHE_CONTROL, "max-age=1000003600"), (AGE, "1000000000")]),
+    );
+    assert!(remaining(&value) <= Duration::from_secs(3600));
+    assert!(remaining(&value) > Duration::from_secs(3599));
+
+    // 30 seconds past its lifetime, so 30 of the 60 seconds of `stale-if-error` are left.
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[
+            (CACHE_CONTROL, "max-age=60, stale-if-error=60"),
+            (AGE, "90"),
+        ]),
+    );
+    assert!(value.is_expired());
+    assert_eq!(
+        value.stale_if_error_until(),
+        Some(value.expires_at().unwrap() + Duration::from_secs(30))
+    );
+    assert!(value.serve_stale_on_error());
+}
+
+#[test]
+fn expires_is_measured_from_date() {
+    let date = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[
+            (DATE, &httpdate::fmt_http_date(date)),
+            (
+                EXPIRES,
+                &httpdate::fmt_http_date(date + Duration::from_secs(30)),
+            ),
+        ]),
+    );
+    assert!(remaining(&value) <= Duration::from_secs(30));
+    assert!(remaining(&value) > Duration::from_secs(29));
+
+    // Without `Date` the lifetime runs from now, so a past `Expires` is already stale.
+    let value =
+        HttpCached::from_headers((), &headers(&[(EXPIRES, &httpdate::fmt_http_date(date))]));
+    assert!(value.is_expired());
+
+    // `max-age` overrides `Expires`; an invalid `Expires` means already expired.
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[(CACHE_CONTROL, "max-age=60"), (EXPIRES, "0")]),
+    );
+    assert!(!value.is_expired());
+    assert!(HttpCached::from_headers((), &headers(&[(EXPIRES, "0")])).is_expired());
+}
+
+#[test]
+fn stale_windows_start_at_expiry() {
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[(
+            CACHE_CONTROL,
+            "max-age=10, stale-while-revalidate=20, stale-if-error=3600",
+        )]),
+    );
+    let expires_at = value.expires_at().unwrap();
+    assert_eq!(
+        value.stale_while_revalidate_until(),
+        Some(expires_at + Duration::from_secs(20))
+    );
+    assert_eq!(
+        value.stale_if_error_until(),
+        Some(expires_at + Duration::from_secs(3600))
+    );
+    assert!(value.serve_stale_on_error());
+
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[(CACHE_CONTROL, "max-age=0, stale-if-error=0")]),
+    );
+    assert!(!value.serve_stale_on_error());
+
+    // Without the directive the fallback is not bounded.
+    let value = HttpCached::from_headers((), &headers(&[(CACHE_CONTROL, "max-age=0")]));
+    assert_eq!(value.stale_if_error_until(), None);
+    assert!(value.serve_stale_on_error());
+}
+
+#[test]
+fn lifetimes_past_the_clock_never_expire() {
+    let max = u64::MAX.to_string();
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[(
+            CACHE_CONTROL,
+            &format!("max-age={max}, stale-while-revalidate={max}, stale-if-error={max}"),
+        )]),
+    );
+    assert_eq!(value.expires_at(), None);
+    assert!(!value.is_expired());
+    assert_eq!(value.stale_while_revalidate_until(), None);
+    assert_eq!(value.stale_if_error_until(), None);
+    assert!(value.serve_stale_on_error());
+
+    // A representable deadline with a window that is not.
+    let value = HttpCached::from_headers(
+        (),
+        &headers(&[(CACHE_CONTROL, &format!("max-age=60, stale-if-error={max}"))]),
+    );
+    assert!(value.expires_at().is_some());
+    assert_eq!(value.stale_if_error_until(), None);
+    assert!(value.serve_stale_on_error());
+}
+
+#[cfg(feature = "proc_macro")]
+mod result_fallback {
+    use super::headers;
+    use cached::http::HttpCached;
+    use cached::macros::{cached, concurrent_cached};
+    use http::header::CACHE_CONTROL;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The first call for a key stores an already-expired response with `cache_control`; every
+    /// later call fails.
+    fn fetch(calls: &AtomicUsize, cache_control: &str) -> Result<HttpCached<usize>, String> {
+        match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => Ok(HttpCached::from_headers(
+                1,
+                &headers(&[(CACHE_CONTROL, cache_control)]),
+            )),
+            _ => Err("upstream down".to_string()),
+        }
+    }
+
+    static INSIDE_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static OUTSIDE_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static UNBOUNDED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[cached(expires = true, result_fallback = true)]
+    fn inside_window() -> Result<HttpCached<usize>, String> {
+        fetch(&INSIDE_CALLS, "max-age=0, stale-if-error=3600")
+    }
+
+    #[cached(expires = true, result_fallback = true)]
+    fn outside_window() -> Result<HttpCached<usize>, String> {
+        fetch(&OUTSIDE_CALLS, "max-age=0, stale-if-error=0")
+    }
+
+    #[cached(expires = true, result_fallback = true, max_size = 4)]
+    fn unbounded() -> Result<HttpCached<usize>, String> {
+        fetch(&UNBOUNDED_CALLS, "max-age=0")
+    }
+
+    #[test]
+    fn cached_respects_stale_if_error() {
+        assert_eq!(*inside_window().unwrap().value(), 1);
+        assert_eq!(*inside_window().unwrap().value(), 1);
+        assert_eq!(INSIDE_CALLS.load(Ordering::SeqCst), 2);
+
+        assert_eq!(*outside_window().unwrap().value(), 1);
+        assert_eq!(outside_window().unwrap_err(), "upstream down");
+
+        assert_eq!(*unbounded().unwrap().value(), 1);
+        assert_eq!(*unbounded().unwrap().value(), 1);
+    }
+
+    static CONCURRENT_INSIDE_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static CONCURRENT_OUTSIDE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[concurrent_cached(expires = true, result_fallback = true)]
+    fn concurrent_inside(key: u32) -> Result<HttpCached<usize>, String> {
+        let _ = key;
+        fetch(&CONCURRENT_INSIDE_CALLS, "max-age=0, stale-if-error=3600")
+    }
+
+    #[concurrent_cached(expires = true, result_fallback = true, max_size = 4)]
+    fn concurrent_outside(key: u32) -> Result<HttpCached<usize>, String> {
+        let _ = key;
+        fetch(&CONCURRENT_OUTSIDE_CALLS, "max-age=0, stale-if-error=0")
+    }
+
+    #[test]
+    fn concurrent_cached_respects_stale_if_error() {
+        assert_eq!(*concurrent_inside(1).unwrap().value(), 1);
+        assert_eq!(*concurrent_inside(1).unwrap().value(), 1);
+
+        assert_eq!(*concurrent_outside(1).unwrap().value(), 1);
+        assert!(concurrent_outside(1).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! `LfuCache` / `ShardedLfuCache`: frequency-ordered eviction, count halving, builder
+//! validation, `on_evict`, and peek/iteration leaving counts alone.
+
+use std::sync::{Arc, Mutex};
+
+use cached::{
+    BuildError, Cached, CachedIter, CachedPeek, ConcurrentCacheBase, ConcurrentCached, LfuCache,
+    ShardedLfuCache,
+};
+
+#[test]
+fn build_validates_size_and_halving_interval() {
+    assert!(matches!(
+        LfuCache::<u32, u32>::builder().build(),
+        Err(BuildError::MissingRequired("max_size"))
+    ));
+    assert!(matches!(
+        LfuCache::<u32, u32>::builder()
+            .max_size(8)
+            .halve_every(0)
+            .build(),
+        Err(BuildError::InvalidValue {
+            field: "halve_every",
+            ..
+        })
+    ));
+    assert!(
+        ShardedLfuCache::<u32, u32>::builder()
+            .max_size(64)
+            .halve_every(0)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn the_least_used_entry_is_evicted_oldest_first_on_ties() {
+    let mut cache = LfuCache::new(3);
+    cache.cache_set(1, 1);
+    cache.cache_set(2, 2);
+    cache.cache_set(3, 3);
+    let _ = cache.cache_get(&1);
+    let _ = cache.cache_get(&3);
+    // 2 is the only entry used once.
+    cache.cache_set(4, 4);
+    assert_eq!(cache.cache_peek(&2), None);
+    // 4 is now the only entry used once; among the rest, 1 and 3 tie and 1 is older.
+    let _ = cache.cache_get(&4);
+    cache.cache_set(5, 5);
+    assert_eq!(cache.cache_peek(&1), None);
+    let keys: Vec<u32> = cache.keys().copied().collect();
+    assert_eq!(keys, [4, 3, 5]);
+}
+
+#[test]
+fn frequency_counts_writes_and_hits_but_not_peeks() {
+    let mut cache = LfuCache::new(4);
+    cache.cache_set("a", 1);
+    assert_eq!(cache.frequency(&"a"), Some(1));
+    let _ = cache.cache_get(&"a");
+    cache.cache_set("a", 2);
+    assert_eq!(cache.frequency(&"a"), Some(3));
+    let _ = cache.cache_peek(&"a");
+    assert!(cache.cache_contains(&"a"));
+    assert_eq!(cache.frequency(&"a"), Some(3));
+    assert_eq!(cache.frequency(&"b"), None);
+    assert_eq!(cache.cache_hits(), Some(1));
+}
+
+#[test]
+fn halving_lets_new_favourites_overtake_old_ones() {
+    let run = |halve: Option<u64>| {
+        let mut builder = LfuCache::builder().max_size(2);
+        if let Some(uses) = halve {
+            builder = builder.halve_every(uses);
+        }
+        let mut cache = builder.build().unwrap();
+        cache.cache_set("old", 0);
+        for _ in 0..20 {
+            let _ = cache.cache_get(&"old");
+        }
+        cache.cache_set("new", 0);
+        for _ in 0..6 {
+            let _ = cache.cache_get(&"new");
+        }
+        cache.cache_set("scan", 0);
+        cache
+    };
+    // Without halving, "old" keeps its lead and "scan" pushes out "new".
+    let plain = run(None);
+    assert_eq!(plain.frequency(&"old"), Some(21));
+    assert_eq!(plain.frequency(&"new"), None);
+    // Halving every 8 uses decays "old" below "new", so "scan" pushes out "old".
+    let aged = run(Some(8));
+    assert_eq!(aged.frequency(&"old"), None);
+    assert!(aged.frequency(&"new").is_some());
+}
+
+#[test]
+fn manual_halving_rounds_down() {
+    let mut cache = LfuCache::new(4);
+    cache.cache_set(1, 1);
+    cache.cache_set(2, 2);
+    for _ in 0..4 {
+        let _ = cache.cache_get(&2);
+    }
+    cache.halve_frequencies();
+    assert_eq!(cache.frequency(&1), Some(0));
+    assert_eq!(cache.frequency(&2), Some(2));
+    cache.cache_set(3, 3);
+    let keys: Vec<u32> = cache.keys().copied().collect();
+    assert_eq!(keys, [2, 3, 1]);
+}
+
+#[test]
+fn evictions_and_removals_fire_on_evict() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let mut cache = LfuCache::builder()
+        .max_size(2)
+        .on_evict(move |k: &u32, v: &u32| sink.lock().unwrap().push((*k, *v)))
+        .build()
+        .unwrap();
+    cache.cache_set(1, 10);
+    cache.cache_set(2, 20);
+    cache.cache_set(3, 30);
+    assert_eq!(cache.cache_remove(&3), Some(30));
+    // Overwriting is a use, not a removal.
+    assert_eq!(cache.cache_set(2, 21), Some(20));
+    assert_eq!(*evicted.lock().unwrap(), [(1, 10), (3, 30)]);
+    assert_eq!(cache.cache_evictions(), Some(2));
+
+    assert_eq!(cache.retain(|_, v| *v > 100), 1);
+    assert_eq!(evicted.lock().unwrap().last(), Some(&(2, 21)));
+    assert_eq!(cache.cache_size(), 0);
+}
+
+#[test]
+fn shrinking_evicts_the_least_used() {
+    let mut cache = LfuCache::new(8);
+    for k in 0..8 {
+        cache.cache_set(k, k);
+        for _ in 0..k {
+            let _ = cache.cache_get(&k);
+        }
+    }
+    assert_eq!(cache.set_max_size(3), Some(8));
+    assert!(cache.try_set_max_size(0).is_err());
+    let keys: Vec<u32> = cache.keys().copied().collect();
+    assert_eq!(keys, [7, 6, 5]);
+}
+
+#[test]
+fn sharded_cache_keeps_frequent_entries_per_shard() {
+    let cache: ShardedLfuCache<u32, u32> = ShardedLfuCache::builder()
+        .shards(1)
+        .max_size(16)
+        .build()
+        .unwrap();
+    for k in 0..8 {
+        cache.set(k, k);
+        assert_eq!(cache.get(&k), Some(k));
+    }
+    for k in 1000..1200 {
+        cache.set(k, k);
+    }
+    assert!((0..8).all(|k| cache.frequency(&k) == Some(2)));
+    assert_eq!(cache.len(), 16);
+    assert_eq!(cache.cache_hits(), Some(8));
+    assert_eq!(cache.cache_capacity(), Some(16));
+
+    cache.halve_frequencies();
+    assert_eq!(cache.frequency(&0), Some(1));
+    let copy = cache.deep_clone();
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(copy.len(), 16);
+}
+
+#[test]
+fn sharded_on_evict_sees_every_removal() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedLfuCache<u32, u32> = ShardedLfuCache::builder()
+        .shards(4)
+        .per_shard_max_size(16)
+        .halve_every(32)
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    for k in 0..200 {
+        cache.cache_set(k, k).unwrap();
+    }
+    assert!(cache.len() <= 64);
+    assert_eq!(evicted.lock().unwrap().len(), 200 - cache.len());
+
+    let before = evicted.lock().unwrap().len();
+    let odd = (0..200).filter(|k| k % 2 == 1 && cache.contains(k)).count();
+    assert_eq!(cache.retain(|k, _| k % 2 == 0), odd);
+    assert_eq!(evicted.lock().unwrap().len(), before + odd);
+
+    let left = cache.len();
+    cache.cache_clear_with_on_evict();
+    assert!(cache.is_empty());
+    assert_eq!(evicted.lock().unwrap().len(), before + odd + left);
+}
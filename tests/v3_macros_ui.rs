@@ -20,6 +20,10 @@ These cover the new attribute validations:
   `in_impl`-requires-self rejection) on all three macros.
 - a `create` block combined with `ttl_millis` (the create-conflict rejection,
   #149) on both `#[cached]` and `#[concurrent_cached]`.
+- `policy`: an unknown value, `policy = "arc"` with a TTL, and `policy` without
+  `max_size`.
+- `ttl_fn` without a TTL store, and with `redis = true`.
+- `none_ttl` without `cache_none`, and `err_ttl` with `refresh`.
 
 All of the compile-fail cases fire during macro expansion before any
 feature-gated store type is emitted, so `proc_macro` alone is sufficient (no
@@ -129,6 +133,17 @@ fn compile_fail_v3_macros() {
     // immediately visible.
     t.compile_fail("tests/ui/once_force_refresh_bare_bool.rs");
     t.compile_fail("tests/ui/concurrent_cached_force_refresh_bare_bool.rs");
+    // `policy` picks how the `max_size` bound evicts: it needs `max_size`, and only
+    // `"lru"` has TTL/`expires` stores.
+    t.compile_fail("tests/ui/cached_policy_unknown.rs");
+    t.compile_fail("tests/ui/cached_policy_arc_ttl_exclusive.rs");
+    t.compile_fail("tests/ui/concurrent_cached_policy_requires_max_size.rs");
+    // `ttl_fn` stores through an inherent `set_with_ttl`, which only the TTL stores have.
+    t.compile_fail("tests/ui/cached_ttl_fn_requires_ttl.rs");
+    t.compile_fail("tests/ui/concurrent_cached_ttl_fn_redis.rs");
+    // `none_ttl` / `err_ttl` need their opt-in, and a refreshing hit would outlive them.
+    t.compile_fail("tests/ui/cached_none_ttl_requires_cache_none.rs");
+    t.compile_fail("tests/ui/concurrent_cached_err_ttl_refresh.rs");
     // T1: `result_fallback = true` with `sync_writes = false` (Disabled) must compile.
     // Unlike the compile-fail cases above, this one expands fully and uses
     // `ttl_secs` (result_fallback requires a ttl/expires/ty), so it needs the
@@ -0,0 +1,208 @@
+//! Memory usage estimates (`memory_usage` / `CacheMetrics::mem_bytes`): the shallow estimate
+//! from the stores' own allocations, the `deep_size` builder option that adds what keys and
+//! values own through `DeepSize`, and the per-shard breakdown on the sharded stores.
+
+use cached::stores::{
+    ExpiringLruCache, LruCache, ShardedLruCache, ShardedUnboundCache, UnboundCache,
+};
+use cached::{
+    Cached, CachedExt, CachedIter, ConcurrentCacheBase, ConcurrentCached, ConcurrentCachedExt,
+    DeepSize, Expires,
+};
+
+#[test]
+fn unbound_grows_with_its_table_and_shrinks_on_reset() {
+    let mut cache: UnboundCache<u64, u64> = UnboundCache::new();
+    let empty = cache.memory_usage().unwrap();
+    for k in 0..1_000 {
+        cache.cache_set(k, k);
+    }
+    let full = cache.memory_usage().unwrap();
+    // At least the inline size of every entry.
+    assert!(full >= empty + 1_000 * 16, "{empty} -> {full}");
+
+    // Clearing keeps the allocation; a reset shrinks it back.
+    cache.cache_clear();
+    assert_eq!(cache.memory_usage(), Some(full));
+    cache.cache_reset();
+    assert_eq!(cache.memory_usage(), Some(empty));
+}
+
+#[test]
+fn metrics_carry_the_estimate() {
+    let mut cache: LruCache<u32, u32> = LruCache::new(64);
+    cache.cache_set(1, 1);
+    let metrics = cache.metrics();
+    assert_eq!(metrics.mem_bytes, cache.memory_usage());
+    assert!(metrics.mem_bytes.unwrap() > 0);
+}
+
+#[test]
+fn an_lru_counts_its_preallocated_slab() {
+    let small: LruCache<u64, u64> = LruCache::new(8);
+    let large: LruCache<u64, u64> = LruCache::new(8_000);
+    // The list slab is allocated for `max_size` entries up front.
+    assert!(large.memory_usage().unwrap() >= small.memory_usage().unwrap() + 7_992 * 16);
+}
+
+#[test]
+fn deep_size_adds_what_keys_and_values_own() {
+    let mut shallow: UnboundCache<String, Vec<u8>> = UnboundCache::new();
+    let mut deep: UnboundCache<String, Vec<u8>> =
+        UnboundCache::builder().deep_size().build().unwrap();
+    for cache in [&mut shallow, &mut deep] {
+        for k in 0..10 {
+            cache.cache_set(format!("key-{k}"), vec![0; 100]);
+        }
+    }
+    let owned: usize = shallow
+        .iter()
+        .map(|(k, v)| k.heap_size() + v.heap_size())
+        .sum();
+    assert!(owned >= 10 * 105);
+    assert_eq!(
+        deep.memory_usage().unwrap(),
+        shallow.memory_usage().unwrap() + owned
+    );
+}
+
+#[test]
+fn deep_size_follows_values_resized_in_place() {
+    let mut cache: LruCache<u32, Vec<u64>> =
+        LruCache::builder().max_size(4).deep_size().build().unwrap();
+    cache.cache_set(1, Vec::new());
+    let before = cache.memory_usage().unwrap();
+    cache.cache_get_mut(&1).unwrap().reserve_exact(1_000);
+    assert!(cache.memory_usage().unwrap() >= before + 8_000);
+}
+
+#[derive(Clone)]
+struct User {
+    name: String,
+    tags: Vec<String>,
+}
+
+impl DeepSize for User {
+    fn heap_size(&self) -> usize {
+        self.name.heap_size() + self.tags.heap_size()
+    }
+}
+
+#[test]
+fn user_types_plug_in_through_deep_size() {
+    #[derive(Clone)]
+    struct Fresh(User);
+    impl Expires for Fresh {
+        fn is_expired(&self) -> bool {
+            false
+        }
+    }
+    impl DeepSize for Fresh {
+        fn heap_size(&self) -> usize {
+            self.0.heap_size()
+        }
+    }
+    let mut cache: ExpiringLruCache<u32, Fresh> = ExpiringLruCache::builder()
+        .max_size(4)
+        .deep_size()
+        .build()
+        .unwrap();
+    let empty = cache.memory_usage().unwrap();
+    let user = User {
+        name: String::with_capacity(64),
+        tags: Vec::with_capacity(4),
+    };
+    let owned = user.heap_size();
+    assert_eq!(owned, 64 + 4 * size_of::<String>());
+    cache.cache_set(1, Fresh(user));
+    assert_eq!(cache.memory_usage().unwrap(), empty + owned);
+}
+
+#[test]
+fn sharded_stores_report_per_shard_and_in_total() {
+    let cache: ShardedUnboundCache<u64, String> = ShardedUnboundCache::builder()
+        .shards(4)
+        .deep_size()
+        .build()
+        .unwrap();
+    let empty = cache.shard_memory_usage();
+    assert_eq!(empty.len(), 4);
+
+    // One large value makes its shard stand out without changing the entry counts much.
+    for k in 0..40 {
+        cache.cache_set(k, String::new()).unwrap();
+    }
+    cache.cache_set(0, "x".repeat(10_000)).unwrap();
+    let per_shard = cache.shard_memory_usage();
+    let heavy = per_shard
+        .iter()
+        .zip(&empty)
+        .map(|(now, before)| now - before)
+        .max()
+        .unwrap();
+    assert!(heavy >= 10_000);
+
+    let total: usize = per_shard.iter().sum();
+    assert_eq!(ConcurrentCachedExt::memory_usage(&cache), Some(total));
+    assert_eq!(cache.metrics().mem_bytes, Some(total));
+    assert_eq!(ConcurrentCacheBase::metrics(&cache).mem_bytes, Some(total));
+}
+
+#[test]
+fn sharded_lru_counts_every_shard_slab() {
+    let cache: ShardedLruCache<u64, u64> = ShardedLruCache::builder()
+        .max_size(4_096)
+        .shards(8)
+        .build()
+        .unwrap();
+    let per_shard = cache.shard_memory_usage();
+    assert_eq!(per_shard.len(), 8);
+    // Each shard preallocates its share of the capacity.
+    assert!(per_shard.iter().all(|&b| b >= 512 * 16), "{per_shard:?}");
+    let _ = ConcurrentCached::cache_get(&cache, &1);
+    assert_eq!(
+        ConcurrentCacheBase::cache_memory_usage(&cache),
+        Some(per_shard.iter().sum())
+    );
+}
+
+#[cfg(feature = "time_stores")]
+mod timed {
+    use cached::stores::{ShardedTtlCache, TtlCache, TtlSortedCache};
+    use cached::time::Duration;
+    use cached::{CachedExt, ConcurrentCachedExt};
+
+    #[test]
+    fn ttl_stores_count_values_inside_their_timed_entries() {
+        let mut ttl: TtlCache<u32, String> = TtlCache::builder()
+            .ttl(Duration::from_secs(60))
+            .deep_size()
+            .build()
+            .unwrap();
+        let empty = ttl.memory_usage().unwrap();
+        ttl.set(1, String::with_capacity(1_000));
+        assert!(ttl.memory_usage().unwrap() >= empty + 1_000);
+
+        let sharded: ShardedTtlCache<u32, String> = ShardedTtlCache::builder()
+            .ttl(Duration::from_secs(60))
+            .deep_size()
+            .build()
+            .unwrap();
+        let before = ConcurrentCachedExt::memory_usage(&sharded).unwrap();
+        ConcurrentCachedExt::set(&sharded, 1, String::with_capacity(1_000)).unwrap();
+        assert!(ConcurrentCachedExt::memory_usage(&sharded).unwrap() >= before + 1_000);
+    }
+
+    #[test]
+    fn ttl_sorted_counts_keys_held_by_the_expiry_index() {
+        let mut sorted: TtlSortedCache<String, u32> = TtlSortedCache::builder()
+            .ttl(Duration::from_secs(60))
+            .deep_size()
+            .build()
+            .unwrap();
+        let empty = sorted.memory_usage().unwrap();
+        sorted.set("k".repeat(500), 1);
+        // The key buffer is held by the map and again by the expiry index.
+        assert!(sorted.memory_usage().unwrap() >= empty + 2 * 500);
+    }
+}
@@ -0,0 +1,251 @@
+//! Store statistics published through the `metrics` facade: a hand-built store's
+//! `publish_metrics`, and the per-call counting of the `metrics_name` macro attribute.
+//!
+//! Each test installs its own `DebuggingRecorder` with `with_local_recorder`, so the
+//! tests do not share a global recorder and can run in parallel.
+//!
+//! Gated on `metrics`. Run with `cargo test --features metrics`.
+
+#![cfg(all(feature = "metrics", feature = "proc_macro"))]
+
+use cached::macros::{cached, concurrent_cached, once};
+use cached::stores::{ShardedUnboundCache, UnboundCache};
+use cached::{Cached, CachedExt, ConcurrentCacheBase, ConcurrentCached};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+/// One drained snapshot of the recorder. `Snapshotter::snapshot` resets what it reads, so
+/// each test takes a single one after exercising the cache and asserts against it.
+struct Recorded(Vec<(String, String, DebugValue)>);
+
+impl Recorded {
+    fn take(snapshotter: &Snapshotter) -> Self {
+        Self(
+            snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .map(|(key, _, _, value)| {
+                    let cache = key
+                        .key()
+                        .labels()
+                        .find(|l| l.key() == "cache")
+                        .map(|l| l.value().to_string())
+                        .unwrap_or_default();
+                    (key.key().name().to_string(), cache, value)
+                })
+                .collect(),
+        )
+    }
+
+    fn value(&self, name: &str, cache: &str) -> Option<&DebugValue> {
+        self.0
+            .iter()
+            .find(|(n, c, _)| n == name && c == cache)
+            .map(|(_, _, v)| v)
+    }
+
+    fn counter(&self, name: &str, cache: &str) -> u64 {
+        match self.value(name, cache) {
+            Some(DebugValue::Counter(n)) => *n,
+            _ => 0,
+        }
+    }
+
+    fn gauge(&self, name: &str, cache: &str) -> Option<f64> {
+        match self.value(name, cache) {
+            Some(DebugValue::Gauge(v)) => Some(v.into_inner()),
+            _ => None,
+        }
+    }
+
+    fn samples(&self, name: &str, cache: &str) -> usize {
+        match self.value(name, cache) {
+            Some(DebugValue::Histogram(samples)) => samples.len(),
+            _ => 0,
+        }
+    }
+}
+
+#[test]
+fn unbound_store_publishes_counters_and_gauges() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let mut cache = UnboundCache::<u32, u32>::builder()
+            .metrics_name("unbound")
+            .build()
+            .unwrap();
+        cache.cache_set(1, 10);
+        cache.cache_set(2, 20);
+        assert_eq!(cache.cache_get(&1), Some(&10));
+        assert_eq!(cache.cache_get(&3), None);
+        cache.publish_metrics();
+
+        let r = Recorded::take(&snapshotter);
+        assert_eq!(r.counter("cached_hits_total", "unbound"), 1);
+        assert_eq!(r.counter("cached_misses_total", "unbound"), 1);
+        assert_eq!(r.gauge("cached_entries", "unbound"), Some(2.0));
+        assert_eq!(
+            r.gauge("cached_memory_bytes", "unbound"),
+            cache.memory_usage().map(|b| b as f64)
+        );
+
+        // Publishing again adds only what happened since.
+        assert_eq!(cache.cache_get(&2), Some(&20));
+        cache.publish_metrics();
+        let r = Recorded::take(&snapshotter);
+        assert_eq!(r.counter("cached_hits_total", "unbound"), 1);
+        assert_eq!(r.counter("cached_misses_total", "unbound"), 0);
+    });
+}
+
+#[test]
+fn store_without_a_name_publishes_nothing() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let mut cache = UnboundCache::<u32, u32>::new();
+        cache.cache_set(1, 10);
+        assert!(cache.cache_metrics_exporter().is_none());
+        cache.publish_metrics();
+        assert!(snapshotter.snapshot().into_vec().is_empty());
+    });
+}
+
+#[test]
+fn sharded_store_publishes_through_the_concurrent_trait() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let cache = ShardedUnboundCache::<u32, u32>::builder()
+            .shards(2)
+            .metrics_name("sharded")
+            .build()
+            .unwrap();
+        cache.cache_set(1, 10).unwrap();
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(10));
+        assert_eq!(cache.cache_get(&2).unwrap(), None);
+        ConcurrentCacheBase::publish_metrics(&cache);
+
+        let r = Recorded::take(&snapshotter);
+        assert_eq!(r.counter("cached_hits_total", "sharded"), 1);
+        assert_eq!(r.counter("cached_misses_total", "sharded"), 1);
+        assert_eq!(r.gauge("cached_entries", "sharded"), Some(1.0));
+        assert!(r.gauge("cached_memory_bytes", "sharded").is_some());
+    });
+}
+
+#[cached(metrics_name = "macro_cached")]
+fn square(n: u64) -> u64 {
+    n * n
+}
+
+#[test]
+fn cached_macro_counts_calls_and_times_misses() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        assert_eq!(square(3), 9);
+        assert_eq!(square(3), 9);
+        assert_eq!(square(4), 16);
+        // Priming is neither a lookup nor a miss.
+        assert_eq!(square_prime_cache(5), 25);
+
+        let r = Recorded::take(&snapshotter);
+        assert_eq!(r.counter("cached_hits_total", "macro_cached"), 1);
+        assert_eq!(r.counter("cached_misses_total", "macro_cached"), 2);
+        assert_eq!(r.samples("cached_miss_duration_seconds", "macro_cached"), 2);
+        // Published after the second miss, before the prime.
+        assert_eq!(r.gauge("cached_entries", "macro_cached"), Some(2.0));
+    });
+}
+
+#[concurrent_cached(metrics_name = "macro_concurrent")]
+fn cube(n: u64) -> u64 {
+    n * n * n
+}
+
+#[test]
+fn concurrent_cached_macro_counts_calls_and_times_misses() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        assert_eq!(cube(2), 8);
+        assert_eq!(cube(2), 8);
+        assert_eq!(cube(2), 8);
+        assert_eq!(cube_prime_cache(3), 27);
+
+        let r = Recorded::take(&snapshotter);
+        assert_eq!(r.counter("cached_hits_total", "macro_concurrent"), 2);
+        assert_eq!(r.counter("cached_misses_total", "macro_concurrent"), 1);
+        assert_eq!(
+            r.samples("cached_miss_duration_seconds", "macro_concurrent"),
+            1
+        );
+        assert_eq!(r.gauge("cached_entries", "macro_concurrent"), Some(1.0));
+    });
+}
+
+#[once(metrics_name = "macro_once")]
+fn answer() -> u32 {
+    42
+}
+
+#[test]
+fn once_macro_counts_calls_and_times_misses() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        assert_eq!(answer(), 42);
+        assert_eq!(answer(), 42);
+
+        let r = Recorded::take(&snapshotter);
+        assert_eq!(r.counter("cached_hits_total", "macro_once"), 1);
+        assert_eq!(r.counter("cached_misses_total", "macro_once"), 1);
+        assert_eq!(r.samples("cached_miss_duration_seconds", "macro_once"), 1);
+    });
+}
+
+#[cfg(feature = "async")]
+mod async_macros {
+    use super::Recorded;
+    use cached::macros::{cached, concurrent_cached};
+    use metrics_util::debugging::DebuggingRecorder;
+
+    #[cached(metrics_name = "async_cached")]
+    async fn double(n: u64) -> u64 {
+        n * 2
+    }
+
+    #[concurrent_cached(metrics_name = "async_concurrent")]
+    async fn triple(n: u64) -> u64 {
+        n * 3
+    }
+
+    // The local recorder is per thread, so drive the futures on a current-thread runtime
+    // inside it.
+    #[test]
+    fn async_macros_count_calls_and_time_misses() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(async {
+                assert_eq!(double(1).await, 2);
+                assert_eq!(double(1).await, 2);
+                assert_eq!(triple(1).await, 3);
+                assert_eq!(triple(1).await, 3);
+            });
+        });
+
+        let r = Recorded::take(&snapshotter);
+        for cache in ["async_cached", "async_concurrent"] {
+            assert_eq!(r.counter("cached_hits_total", cache), 1);
+            assert_eq!(r.counter("cached_misses_total", cache), 1);
+            assert_eq!(r.samples("cached_miss_duration_seconds", cache), 1);
+            assert_eq!(r.gauge("cached_entries", cache), Some(1.0));
+        }
+    }
+}
@@ -2,14 +2,14 @@
 //!
 //! Before the 3.0 breaking window, `entry_count` was a plain `usize` and the
 //! `ConcurrentCacheBase::metrics` default masked an unknown size (`cache_size()`
-//! returning `Ok(None)`, as `RedisCache`/`RedbCache` do) as a false `0`. These tests
+//! returning `Ok(None)`, as `RedisCache` does by default) as a false `0`. These tests
 //! prove that "unknown" now propagates as `None` while stores that report an exact
 //! size still surface `Some(n)`.
 
 use cached::{CacheMetrics, ConcurrentCacheBase};
 
 /// Minimal `ConcurrentCacheBase` impl that leaves `cache_size()` at its default,
-/// which returns `Ok(None)` — mirroring redis/redb.
+/// which returns `Ok(None)` — mirroring redis in its default size mode.
 #[derive(Default)]
 struct UnknownSizeStore;
 
@@ -30,6 +30,13 @@ fn concurrent_base_metrics_propagates_unknown_entry_count() {
     );
 }
 
+/// `cache_is_empty` maps an unknown size through to `None` rather than fabricating a bool.
+#[test]
+fn concurrent_base_is_empty_propagates_unknown_size() {
+    assert_eq!(UnknownSizeStore.cache_size(), Ok(None));
+    assert_eq!(UnknownSizeStore.cache_is_empty(), Ok(None));
+}
+
 /// A store that reports an exact size still surfaces `Some(n)`.
 #[test]
 fn concurrent_base_metrics_reports_known_entry_count() {
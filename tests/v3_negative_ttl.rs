@@ -0,0 +1,432 @@
+//! Negative-result TTLs: `none_ttl` / `err_ttl` on the macros keep a cached `None` or `Err`
+//! for a shorter TTL than a real value, and `set_with_ttl` on `RedbCache` and the Redis
+//! stores gives one entry its own TTL in place of the cache TTL.
+#![cfg(feature = "time_stores")]
+
+use cached::time::Duration;
+
+#[cfg(feature = "proc_macro")]
+mod macros {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use cached::macros::{cached, concurrent_cached};
+
+    static CACHED_NONE_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static CACHED_ERR_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static CONCURRENT_NONE_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static CONCURRENT_ERR_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[cached(ttl_secs = 60, cache_none = true, none_ttl_millis = 20)]
+    fn cached_lookup(found: bool) -> Option<u32> {
+        CACHED_NONE_CALLS.fetch_add(1, Ordering::SeqCst);
+        found.then_some(1)
+    }
+
+    #[cached(
+        max_size = 8,
+        ttl_secs = 60,
+        cache_err = true,
+        err_ttl = "Duration::from_millis(20)"
+    )]
+    fn cached_fetch(ok: bool) -> Result<u32, String> {
+        CACHED_ERR_CALLS.fetch_add(1, Ordering::SeqCst);
+        if ok { Ok(1) } else { Err("down".into()) }
+    }
+
+    #[concurrent_cached(ttl_secs = 60, cache_none = true, none_ttl_millis = 20)]
+    fn concurrent_lookup(found: bool) -> Option<u32> {
+        CONCURRENT_NONE_CALLS.fetch_add(1, Ordering::SeqCst);
+        found.then_some(1)
+    }
+
+    #[concurrent_cached(max_size = 64, ttl_secs = 60, cache_err = true, err_ttl_millis = 20)]
+    fn concurrent_fetch(ok: bool) -> Result<u32, String> {
+        CONCURRENT_ERR_CALLS.fetch_add(1, Ordering::SeqCst);
+        if ok { Ok(1) } else { Err("down".into()) }
+    }
+
+    /// Calls `f` twice for each input, sleeps past the negative TTL, then once more: only
+    /// the negative input is recomputed after the sleep.
+    fn assert_only_negatives_expire(calls: &AtomicUsize, f: impl Fn(bool)) {
+        for input in [true, false, true, false] {
+            f(input);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        std::thread::sleep(Duration::from_millis(40));
+        f(true);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        f(false);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn cached_none_uses_none_ttl() {
+        assert_only_negatives_expire(&CACHED_NONE_CALLS, |found| {
+            assert_eq!(cached_lookup(found), found.then_some(1));
+        });
+    }
+
+    #[test]
+    fn cached_err_uses_err_ttl() {
+        assert_only_negatives_expire(&CACHED_ERR_CALLS, |ok| {
+            assert_eq!(cached_fetch(ok).is_ok(), ok);
+        });
+    }
+
+    #[test]
+    fn concurrent_none_uses_none_ttl() {
+        assert_only_negatives_expire(&CONCURRENT_NONE_CALLS, |found| {
+            assert_eq!(concurrent_lookup(found), found.then_some(1));
+        });
+    }
+
+    #[test]
+    fn concurrent_err_uses_err_ttl() {
+        assert_only_negatives_expire(&CONCURRENT_ERR_CALLS, |ok| {
+            assert_eq!(concurrent_fetch(ok).is_ok(), ok);
+        });
+    }
+
+    #[cfg(feature = "redb_store")]
+    mod disk {
+        use super::*;
+
+        use cached::ConcurrentCached;
+
+        static DISK_NONE_CALLS: AtomicUsize = AtomicUsize::new(0);
+        static DISK_ERR_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[concurrent_cached(
+            disk = true,
+            durable = false,
+            ttl_secs = 60,
+            cache_none = true,
+            none_ttl_millis = 20,
+            map_error = r##"|e| format!("{e}")"##
+        )]
+        fn disk_lookup(found: bool) -> Result<Option<u32>, String> {
+            DISK_NONE_CALLS.fetch_add(1, Ordering::SeqCst);
+            Ok(found.then_some(1))
+        }
+
+        #[concurrent_cached(
+            disk = true,
+            durable = false,
+            ttl_secs = 60,
+            cache_err = true,
+            err_ttl_millis = 20,
+            map_error = r##"|e| format!("{e}")"##
+        )]
+        fn disk_fetch(ok: bool) -> Result<u32, String> {
+            DISK_ERR_CALLS.fetch_add(1, Ordering::SeqCst);
+            if ok { Ok(1) } else { Err("down".into()) }
+        }
+
+        static DISK_BOTH_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        // `cache_err` stores the whole `Result`, so `none_ttl` applies to its `Ok(None)`s and
+        // an `Err` keeps the cache TTL.
+        #[concurrent_cached(
+            disk = true,
+            durable = false,
+            ttl_secs = 60,
+            cache_none = true,
+            cache_err = true,
+            none_ttl_millis = 20,
+            map_error = r##"|e| format!("{e}")"##
+        )]
+        fn disk_lookup_or_fail(input: u8) -> Result<Option<u32>, String> {
+            DISK_BOTH_CALLS.fetch_add(1, Ordering::SeqCst);
+            match input {
+                0 => Ok(None),
+                1 => Ok(Some(1)),
+                _ => Err("down".into()),
+            }
+        }
+
+        static DISK_TWO_TTLS_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        // `Ok(None)` and `Err(_)` never match the same value, so each family keeps its own TTL.
+        #[concurrent_cached(
+            disk = true,
+            durable = false,
+            ttl_secs = 60,
+            cache_none = true,
+            cache_err = true,
+            none_ttl_millis = 20,
+            err_ttl_millis = 300,
+            map_error = r##"|e| format!("{e}")"##
+        )]
+        fn disk_lookup_with_two_ttls(input: u8) -> Result<Option<u32>, String> {
+            DISK_TWO_TTLS_CALLS.fetch_add(1, Ordering::SeqCst);
+            match input {
+                0 => Ok(None),
+                1 => Ok(Some(1)),
+                _ => Err("down".into()),
+            }
+        }
+
+        // The redb file outlives the test run, so each test starts from an empty table.
+        #[test]
+        fn disk_none_uses_none_ttl() {
+            DISK_LOOKUP.cache_clear().unwrap();
+            assert_only_negatives_expire(&DISK_NONE_CALLS, |found| {
+                assert_eq!(disk_lookup(found), Ok(found.then_some(1)));
+            });
+        }
+
+        #[test]
+        fn disk_err_uses_err_ttl() {
+            DISK_FETCH.cache_clear().unwrap();
+            assert_only_negatives_expire(&DISK_ERR_CALLS, |ok| {
+                assert_eq!(disk_fetch(ok).is_ok(), ok);
+            });
+        }
+
+        #[test]
+        fn disk_none_ttl_with_cache_err_skips_errors() {
+            DISK_LOOKUP_OR_FAIL.cache_clear().unwrap();
+            for input in [0, 1, 2, 0, 1, 2] {
+                disk_lookup_or_fail(input).ok();
+            }
+            assert_eq!(DISK_BOTH_CALLS.load(Ordering::SeqCst), 3);
+            std::thread::sleep(Duration::from_millis(40));
+            assert_eq!(disk_lookup_or_fail(2), Err("down".into()));
+            assert_eq!(disk_lookup_or_fail(1), Ok(Some(1)));
+            assert_eq!(DISK_BOTH_CALLS.load(Ordering::SeqCst), 3);
+            assert_eq!(disk_lookup_or_fail(0), Ok(None));
+            assert_eq!(DISK_BOTH_CALLS.load(Ordering::SeqCst), 4);
+        }
+
+        #[test]
+        fn disk_none_ttl_and_err_ttl_both_apply() {
+            DISK_LOOKUP_WITH_TWO_TTLS.cache_clear().unwrap();
+            let calls = || DISK_TWO_TTLS_CALLS.load(Ordering::SeqCst);
+            for input in [0, 1, 2, 0, 1, 2] {
+                disk_lookup_with_two_ttls(input).ok();
+            }
+            assert_eq!(calls(), 3);
+            // Past `none_ttl` only: the `Ok(None)` is recomputed, the `Err` is still cached.
+            std::thread::sleep(Duration::from_millis(40));
+            assert_eq!(disk_lookup_with_two_ttls(0), Ok(None));
+            assert_eq!(calls(), 4);
+            assert_eq!(disk_lookup_with_two_ttls(2), Err("down".into()));
+            assert_eq!(calls(), 4);
+            // Past `err_ttl` too: the `Err` is recomputed, the value is still cached.
+            std::thread::sleep(Duration::from_millis(300));
+            assert_eq!(disk_lookup_with_two_ttls(2), Err("down".into()));
+            assert_eq!(calls(), 5);
+            assert_eq!(disk_lookup_with_two_ttls(1), Ok(Some(1)));
+            assert_eq!(calls(), 5);
+        }
+
+        #[cfg(feature = "async")]
+        mod async_disk {
+            use super::*;
+
+            static ASYNC_DISK_ERR_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+            #[concurrent_cached(
+                disk = true,
+                durable = false,
+                ttl_secs = 60,
+                cache_err = true,
+                err_ttl_millis = 20,
+                map_error = r##"|e| format!("{e}")"##
+            )]
+            async fn async_disk_fetch(ok: bool) -> Result<u32, String> {
+                ASYNC_DISK_ERR_CALLS.fetch_add(1, Ordering::SeqCst);
+                if ok { Ok(1) } else { Err("down".into()) }
+            }
+
+            #[tokio::test]
+            async fn async_disk_err_uses_err_ttl() {
+                async_disk_fetch(true).await.ok();
+                ASYNC_DISK_FETCH.get().unwrap().cache_clear().unwrap();
+                let before = ASYNC_DISK_ERR_CALLS.load(Ordering::SeqCst);
+                for ok in [true, false, true, false] {
+                    async_disk_fetch(ok).await.ok();
+                }
+                assert_eq!(ASYNC_DISK_ERR_CALLS.load(Ordering::SeqCst), before + 2);
+                tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+                async_disk_fetch(true).await.ok();
+                assert_eq!(ASYNC_DISK_ERR_CALLS.load(Ordering::SeqCst), before + 2);
+                async_disk_fetch(false).await.ok();
+                assert_eq!(ASYNC_DISK_ERR_CALLS.load(Ordering::SeqCst), before + 3);
+            }
+        }
+    }
+
+    // Needs a redis server (`CACHED_REDIS_CONNECTION_STRING`), like the other macro redis
+    // tests.
+    #[cfg(feature = "redis_store")]
+    mod redis {
+        use super::*;
+
+        static REDIS_NONE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[concurrent_cached(
+            redis = true,
+            ttl_secs = 60,
+            cache_prefix_block = "{ \"__cached_redis_proc_macro_test_fn_redis_lookup\" }",
+            cache_none = true,
+            none_ttl_millis = 20,
+            map_error = r##"|e| format!("{e}")"##
+        )]
+        fn redis_lookup(found: bool) -> Result<Option<u32>, String> {
+            REDIS_NONE_CALLS.fetch_add(1, Ordering::SeqCst);
+            Ok(found.then_some(1))
+        }
+
+        #[test]
+        fn redis_none_uses_none_ttl() {
+            cached::ConcurrentCached::cache_clear(&*REDIS_LOOKUP).unwrap();
+            assert_only_negatives_expire(&REDIS_NONE_CALLS, |found| {
+                assert_eq!(redis_lookup(found), Ok(found.then_some(1)));
+            });
+        }
+
+        #[cfg(any(feature = "redis_smol", feature = "redis_tokio"))]
+        mod async_redis {
+            use super::*;
+
+            static ASYNC_REDIS_ERR_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+            #[concurrent_cached(
+                redis = true,
+                ttl_secs = 60,
+                cache_prefix_block = "{ \"__cached_redis_proc_macro_test_fn_async_redis_fetch\" }",
+                cache_err = true,
+                err_ttl_millis = 20,
+                map_error = r##"|e| format!("{e}")"##
+            )]
+            async fn async_redis_fetch(ok: bool) -> Result<u32, String> {
+                ASYNC_REDIS_ERR_CALLS.fetch_add(1, Ordering::SeqCst);
+                if ok { Ok(1) } else { Err("down".into()) }
+            }
+
+            #[tokio::test]
+            async fn async_redis_err_uses_err_ttl() {
+                async_redis_fetch(true).await.ok();
+                cached::ConcurrentCachedAsync::async_cache_clear(ASYNC_REDIS_FETCH.get().unwrap())
+                    .await
+                    .unwrap();
+                let before = ASYNC_REDIS_ERR_CALLS.load(Ordering::SeqCst);
+                for ok in [true, false, true, false] {
+                    async_redis_fetch(ok).await.ok();
+                }
+                assert_eq!(ASYNC_REDIS_ERR_CALLS.load(Ordering::SeqCst), before + 2);
+                tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+                async_redis_fetch(true).await.ok();
+                assert_eq!(ASYNC_REDIS_ERR_CALLS.load(Ordering::SeqCst), before + 2);
+                async_redis_fetch(false).await.ok();
+                assert_eq!(ASYNC_REDIS_ERR_CALLS.load(Ordering::SeqCst), before + 3);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "redb_store")]
+mod redb {
+    use super::*;
+    use std::path::Path;
+
+    use cached::{ConcurrentCacheTtl, ConcurrentCached, RedbCache};
+    use tempfile::TempDir;
+
+    fn scratch_dir() -> TempDir {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("local");
+        std::fs::create_dir_all(&root).expect("create local/ scratch root");
+        TempDir::new_in(root).expect("create scratch dir")
+    }
+
+    fn build(name: &str, dir: &TempDir, ttl: Option<Duration>) -> RedbCache<u32, u32> {
+        let mut b = RedbCache::<u32, u32>::builder(name)
+            .disk_dir(dir.path())
+            .durable(false);
+        if let Some(ttl) = ttl {
+            b = b.ttl(ttl);
+        }
+        b.build().expect("cache build")
+    }
+
+    #[test]
+    fn own_ttl_replaces_the_cache_ttl() {
+        let dir = scratch_dir();
+        let cache = build("own-ttl", &dir, Some(Duration::from_millis(100)));
+        cache.cache_set(1, 1).unwrap();
+        assert_eq!(
+            cache.set_with_ttl(2, 2, Duration::from_millis(20)).unwrap(),
+            None
+        );
+        cache.set_with_ttl(3, 3, Duration::ZERO).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(1));
+        assert_eq!(cache.cache_get(&2).unwrap(), None);
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(cache.cache_get(&1).unwrap(), None);
+        assert_eq!(cache.cache_get(&3).unwrap(), Some(3));
+    }
+
+    #[test]
+    fn set_ttl_leaves_own_ttls_alone() {
+        let dir = scratch_dir();
+        let cache = build("own-ttl-retro", &dir, None);
+        cache.set_with_ttl(1, 1, Duration::from_secs(60)).unwrap();
+        cache.cache_set(2, 2).unwrap();
+        cache.set_ttl(Duration::from_millis(20));
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(1));
+        assert_eq!(cache.cache_get(&2).unwrap(), None);
+    }
+
+    #[test]
+    fn remove_expired_entries_sweeps_own_ttls_without_a_cache_ttl() {
+        let dir = scratch_dir();
+        let cache = build("own-ttl-sweep", &dir, None);
+        cache.cache_set(1, 1).unwrap();
+        cache.set_with_ttl(2, 2, Duration::from_millis(20)).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.remove_expired_entries().unwrap(), 1);
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn own_ttl_survives_a_reopen() {
+        let dir = scratch_dir();
+        {
+            let cache = build("own-ttl-reopen", &dir, Some(Duration::from_millis(20)));
+            cache.set_with_ttl(1, 1, Duration::from_secs(60)).unwrap();
+        }
+        let cache = build("own-ttl-reopen", &dir, Some(Duration::from_millis(20)));
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(1));
+    }
+}
+
+#[cfg(feature = "redis_store")]
+mod redis_mock {
+    use super::*;
+
+    use cached::ConcurrentCached;
+    use cached::stores::MockRedisCache;
+
+    #[test]
+    fn own_ttl_replaces_the_cache_ttl() {
+        let cache: MockRedisCache<u32, u32> = MockRedisCache::builder("own-ttl")
+            .ttl_secs(60)
+            .build()
+            .unwrap();
+        cache.cache_set(1, 1).unwrap();
+        assert_eq!(
+            cache.set_with_ttl(2, 2, Duration::from_millis(20)).unwrap(),
+            None
+        );
+        assert_eq!(cache.set_with_ttl(1, 10, Duration::ZERO).unwrap(), Some(1));
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(10));
+        assert_eq!(cache.cache_get(&2).unwrap(), None);
+        assert_eq!(cache.server().keys().len(), 1);
+    }
+}
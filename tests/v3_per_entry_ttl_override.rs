@@ -0,0 +1,184 @@
+//! `set_with_ttl` / `set_until` on the TTL stores and the `ttl_fn` macro attribute built on
+//! them: the override replaces the cache TTL for one entry, is reported through
+//! `CacheValue::expires_at`, and a hit with `refresh_on_hit` re-arms it with the cache TTL.
+#![cfg(feature = "time_stores")]
+
+use std::sync::{Arc, Mutex};
+
+use cached::time::{Duration, Instant};
+use cached::{
+    Cached, ConcurrentCacheBase, LruTtlCache, ShardedLruTtlCache, ShardedTtlCache, TtlCache,
+};
+
+#[test]
+fn ttl_cache_entries_expire_on_their_own_ttl() {
+    let mut cache = TtlCache::new(Duration::from_secs(60));
+    cache.cache_set(1, 1);
+    assert_eq!(cache.set_with_ttl(2, 2, Duration::from_millis(20)), None);
+    cache.set_with_ttl(3, 3, Duration::ZERO);
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(cache.cache_get(&1), Some(&1));
+    assert_eq!(cache.cache_get(&2), None);
+    assert_eq!(cache.cache_get(&3), Some(&3));
+}
+
+#[test]
+fn set_until_uses_the_given_deadline() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let mut cache = TtlCache::builder()
+        .ttl(Duration::from_secs(60))
+        .on_evict(move |k: &u32, _: &u32| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    cache.set_until(1, 1, Instant::now() + Duration::from_millis(20));
+    cache.set_until(2, 2, Instant::now() - Duration::from_millis(1));
+    assert_eq!(cache.cache_get(&2), None);
+    // Overwriting an entry that already expired drops it as an eviction, as `cache_set` does.
+    assert_eq!(
+        cache.set_until(2, 20, Instant::now() + Duration::from_secs(60)),
+        None
+    );
+    assert_eq!(*evicted.lock().unwrap(), [2]);
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(cache.cache_get(&1), None);
+    assert_eq!(cache.cache_get(&2), Some(&20));
+}
+
+#[test]
+fn lru_ttl_cache_reports_the_override_in_expires_at() {
+    let mut cache = LruTtlCache::new(2, Duration::from_secs(60));
+    let deadline = Instant::now() + Duration::from_secs(5);
+    cache.set_until(1, 1, deadline);
+    cache.set_with_ttl(2, 2, Duration::ZERO);
+    let order = cache.iter_order();
+    assert_eq!(order[0].1.expires_at(), None);
+    assert_eq!(order[1].1.expires_at(), Some(deadline));
+
+    // The override does not exempt the entry from LRU eviction.
+    cache.set_with_ttl(3, 3, Duration::from_secs(1));
+    assert_eq!(cache.key_order(), [3, 2]);
+}
+
+#[test]
+fn refresh_on_hit_re_arms_an_override_with_the_cache_ttl() {
+    let mut cache = LruTtlCache::builder()
+        .max_size(4)
+        .ttl(Duration::from_secs(60))
+        .refresh_on_hit(true)
+        .build()
+        .unwrap();
+    cache.set_with_ttl(1, 1, Duration::from_millis(50));
+    assert_eq!(cache.cache_get(&1), Some(&1));
+    std::thread::sleep(Duration::from_millis(80));
+    assert_eq!(cache.cache_get(&1), Some(&1));
+}
+
+#[test]
+fn sharded_stores_accept_per_entry_ttls() {
+    let ttl: ShardedTtlCache<u32, u32> = ShardedTtlCache::new(Duration::from_secs(60));
+    let lru: ShardedLruTtlCache<u32, u32> = ShardedLruTtlCache::new(64, Duration::from_secs(60));
+    ttl.set_with_ttl(1, 1, Duration::from_millis(20));
+    lru.set_with_ttl(1, 1, Duration::from_millis(20));
+    ttl.set_until(2, 2, Instant::now() + Duration::from_secs(60));
+    lru.set_until(2, 2, Instant::now() + Duration::from_secs(60));
+    assert_eq!(ttl.set_with_ttl(2, 20, Duration::ZERO), Some(2));
+    assert_eq!(lru.set_with_ttl(2, 20, Duration::ZERO), Some(2));
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(ttl.get(&1), None);
+    assert_eq!(lru.get(&1), None);
+    assert_eq!(ttl.get(&2), Some(20));
+    assert_eq!(lru.get(&2), Some(20));
+    assert_eq!(ttl.cache_evictions(), Some(1));
+    assert_eq!(lru.cache_evictions(), Some(1));
+}
+
+#[cfg(feature = "proc_macro")]
+mod macros {
+    use super::*;
+    use cached::macros::{cached, concurrent_cached};
+
+    fn ttl_for(value: &u64) -> Duration {
+        Duration::from_millis(*value)
+    }
+
+    #[cached(ttl_secs = 60, ttl_fn = { ttl_for(&result) })]
+    fn cached_with_ttl_fn(millis: u64) -> u64 {
+        millis
+    }
+
+    #[cached(max_size = 8, ttl_secs = 60, ttl_fn = { ttl_for(&result) })]
+    fn cached_result_with_ttl_fn(millis: u64) -> Result<u64, String> {
+        Ok(millis)
+    }
+
+    #[concurrent_cached(ttl_secs = 60, ttl_fn = { ttl_for(&result) })]
+    fn concurrent_with_ttl_fn(millis: u64) -> u64 {
+        millis
+    }
+
+    #[concurrent_cached(max_size = 64, ttl_secs = 60, ttl_fn = { Duration::from_secs(1) })]
+    fn concurrent_with_constant_ttl_fn(millis: u64) -> Option<u64> {
+        Some(millis)
+    }
+
+    #[test]
+    fn ttl_fn_sets_each_entry_ttl_from_its_value() {
+        cached_with_ttl_fn(20);
+        cached_with_ttl_fn(60_000);
+        cached_result_with_ttl_fn(20).unwrap();
+        concurrent_with_ttl_fn(20);
+        concurrent_with_ttl_fn(60_000);
+        concurrent_with_constant_ttl_fn(20);
+        std::thread::sleep(Duration::from_millis(40));
+
+        let mut cache = CACHED_WITH_TTL_FN.write();
+        assert_eq!(cache.cache_get(&20), None);
+        assert_eq!(cache.cache_get(&60_000), Some(&60_000));
+        drop(cache);
+        assert_eq!(CACHED_RESULT_WITH_TTL_FN.write().cache_get(&20), None);
+        assert_eq!(CONCURRENT_WITH_TTL_FN.get(&20), None);
+        assert_eq!(CONCURRENT_WITH_TTL_FN.get(&60_000), Some(60_000));
+        assert_eq!(CONCURRENT_WITH_CONSTANT_TTL_FN.get(&20), Some(20));
+    }
+
+    #[cfg(feature = "async")]
+    mod async_tests {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CACHED_CALLS: AtomicUsize = AtomicUsize::new(0);
+        static CONCURRENT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[cached(ttl_secs = 60, ttl_fn = { ttl_for(&result) })]
+        async fn cached_async(millis: u64) -> u64 {
+            CACHED_CALLS.fetch_add(1, Ordering::SeqCst);
+            millis
+        }
+
+        #[concurrent_cached(ttl_secs = 60, ttl_fn = { ttl_for(&result) })]
+        async fn concurrent_async(millis: u64) -> Result<u64, String> {
+            CONCURRENT_CALLS.fetch_add(1, Ordering::SeqCst);
+            Ok(millis)
+        }
+
+        #[tokio::test]
+        async fn async_ttl_fn_expires_short_lived_values() {
+            for _ in 0..2 {
+                cached_async(20).await;
+                cached_async(60_000).await;
+                concurrent_async(20).await.unwrap();
+                concurrent_async(60_000).await.unwrap();
+            }
+            assert_eq!(CACHED_CALLS.load(Ordering::SeqCst), 2);
+            assert_eq!(CONCURRENT_CALLS.load(Ordering::SeqCst), 2);
+            tokio::time::sleep(Duration::from_millis(40)).await;
+            cached_async(20).await;
+            cached_async(60_000).await;
+            concurrent_async(20).await.unwrap();
+            concurrent_async(60_000).await.unwrap();
+            assert_eq!(CACHED_CALLS.load(Ordering::SeqCst), 3);
+            assert_eq!(CONCURRENT_CALLS.load(Ordering::SeqCst), 3);
+        }
+    }
+}
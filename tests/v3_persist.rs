@@ -0,0 +1,208 @@
+//! Snapshot/restore of the in-memory stores and the `persist_path` macro attribute,
+//! exercised through the public API only.
+//!
+//! Covers the three properties the snapshot format promises: recency order survives a
+//! round trip through a file (so a bounded store keeps its most-recent entries), a TTL
+//! deadline is carried as wall-clock time rather than restarted, and the sharded stores
+//! restore into a store with a different shard count. The macro tests save through
+//! `{fn}_save_cache` and load through a second function pointed at the same file.
+//!
+//! Gated on `persist`. Run with `cargo test --features persist`.
+
+#![cfg(all(feature = "persist", feature = "time_stores"))]
+
+use cached::stores::{
+    LruCache, ShardedLruCache, ShardedUnboundCache, TtlCache, TtlSortedCache, UnboundCache,
+};
+use cached::time::Duration;
+use cached::{
+    CacheSnapshot, Cached, CachedIter, CachedSnapshot, ConcurrentCached, ConcurrentCachedSnapshot,
+    PersistError,
+};
+use std::thread::sleep;
+
+#[test]
+fn lru_order_survives_a_file_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("lru.snapshot");
+
+    let mut cache = LruCache::new(3);
+    cache.cache_set(1u32, "one".to_string());
+    cache.cache_set(2, "two".to_string());
+    cache.cache_set(3, "three".to_string());
+    // Touch 1 so 2 is now least recently used.
+    assert!(cache.cache_get(&1).is_some());
+
+    let snapshot = cache.snapshot();
+    assert_eq!(snapshot.len(), 3);
+    snapshot.save(&path).unwrap();
+
+    let loaded = CacheSnapshot::<u32, String>::load(&path).unwrap();
+    assert_eq!(loaded, snapshot);
+
+    // Restoring into a smaller store keeps the two most recently used entries.
+    let mut smaller = LruCache::new(2);
+    assert_eq!(smaller.restore(loaded), 3);
+    let keys: Vec<u32> = smaller.keys().copied().collect();
+    assert_eq!(smaller.cache_size(), 2);
+    assert!(keys.contains(&1) && keys.contains(&3), "{keys:?}");
+}
+
+#[test]
+fn ttl_deadline_is_not_restarted_by_restore() {
+    let mut cache = TtlCache::new(Duration::from_millis(300));
+    cache.cache_set("k", 1u8);
+    let snapshot = cache.snapshot();
+    assert!(snapshot.entries().next().unwrap().expires_at().is_some());
+
+    sleep(Duration::from_millis(150));
+    // A store with a much longer TTL still honours the snapshotted deadline.
+    let mut restored = TtlCache::new(Duration::from_secs(60));
+    assert_eq!(restored.restore(snapshot.clone()), 1);
+    assert_eq!(restored.cache_get(&"k"), Some(&1));
+
+    sleep(Duration::from_millis(250));
+    assert_eq!(restored.cache_get(&"k"), None);
+
+    // Once the deadline has passed the entry is dropped at restore time.
+    let mut late = TtlSortedCache::new(Duration::from_secs(60));
+    assert_eq!(late.restore(snapshot), 0);
+    assert_eq!(late.cache_size(), 0);
+}
+
+#[test]
+fn unbound_snapshot_has_no_deadlines() {
+    let mut cache = UnboundCache::new();
+    cache.cache_set(7u64, vec![1u8, 2, 3]);
+    let snapshot = cache.snapshot();
+    let entry = snapshot.entries().next().unwrap();
+    assert_eq!((entry.key(), entry.value()), (&7, &vec![1, 2, 3]));
+    assert_eq!(entry.expires_at(), None);
+}
+
+#[test]
+fn sharded_restore_accepts_a_different_shard_count() {
+    let source = ShardedUnboundCache::builder().shards(8).build().unwrap();
+    for i in 0..100u32 {
+        source.cache_set(i, i * 2).unwrap();
+    }
+    let snapshot = source.snapshot();
+    assert_eq!(snapshot.shard_count(), 8);
+    assert_eq!(snapshot.len(), 100);
+
+    let target = ShardedLruCache::builder()
+        .max_size(200)
+        .shards(2)
+        .build()
+        .unwrap();
+    assert_eq!(target.restore(snapshot), 100);
+    for i in 0..100u32 {
+        assert_eq!(target.cache_get(&i).unwrap(), Some(i * 2));
+    }
+}
+
+#[test]
+fn load_reports_a_missing_file_as_io() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = CacheSnapshot::<u32, u32>::load(dir.path().join("absent")).unwrap_err();
+    assert!(matches!(err, PersistError::Io(_)), "{err:?}");
+}
+
+#[cfg(feature = "proc_macro")]
+mod macros {
+    use cached::PersistError;
+    use cached::macros::{cached, concurrent_cached};
+    use std::path::PathBuf;
+    use std::sync::LazyLock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// One scratch directory for the whole test binary, removed when it exits.
+    static SCRATCH: LazyLock<tempfile::TempDir> = LazyLock::new(|| tempfile::tempdir().unwrap());
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        SCRATCH.path().join(name)
+    }
+
+    static WARM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[cached(max_size = 16, persist_path = { snapshot_path("cached.snapshot") })]
+    fn cold(n: u32) -> u32 {
+        n * 10
+    }
+
+    #[cached(max_size = 16, persist_path = { snapshot_path("cached.snapshot") })]
+    fn warm(n: u32) -> u32 {
+        WARM_CALLS.fetch_add(1, Ordering::SeqCst);
+        n * 10
+    }
+
+    #[test]
+    fn cached_save_then_load_on_first_use() {
+        assert_eq!(cold(1), 10);
+        assert!(matches!(cold_restore_result(), Some(Ok(0))));
+        assert_eq!(cold(2), 20);
+        assert_eq!(cold_save_cache().unwrap(), 2);
+
+        // `warm`'s static initializes on this first call and restores `cold`'s entries.
+        assert!(warm_restore_result().is_none());
+        assert_eq!(warm(1), 10);
+        assert!(matches!(warm_restore_result(), Some(Ok(2))));
+        assert_eq!(warm(2), 20);
+        assert_eq!(WARM_CALLS.load(Ordering::SeqCst), 0);
+        assert_eq!(warm(3), 30);
+        assert_eq!(WARM_CALLS.load(Ordering::SeqCst), 1);
+    }
+
+    static SHARED_WARM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[concurrent_cached(persist_path = { snapshot_path("concurrent.snapshot") })]
+    fn shared_cold(n: u32) -> String {
+        n.to_string()
+    }
+
+    #[concurrent_cached(persist_path = { snapshot_path("concurrent.snapshot") })]
+    fn shared_warm(n: u32) -> String {
+        SHARED_WARM_CALLS.fetch_add(1, Ordering::SeqCst);
+        n.to_string()
+    }
+
+    #[test]
+    fn concurrent_cached_save_then_load_on_first_use() {
+        assert_eq!(shared_cold(4), "4");
+        assert_eq!(shared_cold_save_cache().unwrap(), 1);
+
+        assert_eq!(shared_warm(4), "4");
+        assert!(matches!(shared_warm_restore_result(), Some(Ok(1))));
+        assert_eq!(SHARED_WARM_CALLS.load(Ordering::SeqCst), 0);
+    }
+
+    #[concurrent_cached(persist_path = { snapshot_path("corrupt.snapshot") })]
+    fn corrupt(n: u32) -> u32 {
+        n
+    }
+
+    /// A file that is not a snapshot still lets the cache come up, cold, and says why.
+    #[test]
+    fn unreadable_snapshot_is_reported() {
+        std::fs::write(snapshot_path("corrupt.snapshot"), b"not a snapshot").unwrap();
+        assert_eq!(corrupt(1), 1);
+        assert!(
+            matches!(
+                corrupt_restore_result(),
+                Some(Err(PersistError::Decode { .. }))
+            ),
+            "{:?}",
+            corrupt_restore_result()
+        );
+    }
+}
+
+/// `persist_path` needs a module-level static over an in-memory store; the macros reject the
+/// attribute where either is missing instead of emitting a companion that cannot work.
+#[cfg(feature = "proc_macro")]
+#[test]
+fn persist_path_rejections() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/cached_persist_path_in_impl.rs");
+    t.compile_fail("tests/ui/concurrent_cached_persist_path_redis.rs");
+}
@@ -0,0 +1,179 @@
+//! Pinned entries on the LRU-family stores: a pinned entry is never chosen for capacity
+//! eviction, does not count toward `max_size`, keeps its pin across a reshard, and still
+//! expires on the TTL and per-value-expiry stores. `max_pinned` caps the pins store-wide.
+
+use cached::stores::{ShardedExpiringLruCache, ShardedLruCache};
+use cached::{Cached, Expires, ExpiringLruCache, PinError};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Val {
+    id: u32,
+    expired: bool,
+}
+
+impl Val {
+    fn live(id: u32) -> Self {
+        Self { id, expired: false }
+    }
+
+    fn dead(id: u32) -> Self {
+        Self { id, expired: true }
+    }
+}
+
+impl Expires for Val {
+    fn is_expired(&self) -> bool {
+        self.expired
+    }
+}
+
+#[test]
+fn expiring_lru_pins_only_live_entries() {
+    let mut c: ExpiringLruCache<u32, Val> = ExpiringLruCache::builder()
+        .max_size(2)
+        .max_pinned(2)
+        .build()
+        .unwrap();
+    c.cache_set(1, Val::live(1));
+    c.cache_set(2, Val::dead(2));
+    assert_eq!(c.pin(&1), Ok(true));
+    assert_eq!(c.pin(&2), Ok(false));
+    assert_eq!(c.set_pinned(2, Val::live(20)), Ok(None));
+    for k in 10..20 {
+        c.cache_set(k, Val::live(k));
+    }
+    assert_eq!(c.key_order(), vec![2, 1, 19, 18]);
+    let pinned: Vec<bool> = c.value_order().iter().map(|v| v.is_pinned()).collect();
+    assert_eq!(pinned, vec![true, true, false, false]);
+    assert_eq!(
+        c.set_pinned(3, Val::live(3)),
+        Err(PinError::LimitReached { max_pinned: 2 })
+    );
+
+    // A pinned entry still expires: replacing it with an expired value hides it.
+    c.set_pinned(1, Val::dead(1)).unwrap();
+    assert!(c.cache_get(&1).is_none());
+    assert_eq!(c.pinned_len(), 1);
+}
+
+#[test]
+fn sharded_lru_pins_survive_eviction_and_reshard() {
+    let cache: ShardedLruCache<u32, u32> = ShardedLruCache::builder()
+        .shards(2)
+        .per_shard_max_size(2)
+        .build()
+        .unwrap();
+    for k in 0..4 {
+        cache.set(k, k);
+        assert_eq!(cache.pin(&k), Ok(true));
+    }
+    for k in 100..200 {
+        cache.set(k, k);
+    }
+    for n in [8, 1, 4] {
+        cache.reshard(n).unwrap();
+        for k in 100..200 {
+            cache.set(k, k);
+        }
+        assert_eq!(cache.pinned_len(), 4, "after reshard({n})");
+        for k in 0..4 {
+            assert!(cache.is_pinned(&k), "key {k} after reshard({n})");
+            assert_eq!(cache.get(&k), Some(k), "key {k} after reshard({n})");
+        }
+    }
+    assert!(cache.unpin(&0));
+    for k in 200..1200 {
+        cache.set(k, k);
+    }
+    assert_eq!(cache.get(&0), None);
+}
+
+#[test]
+fn sharded_max_pinned_counts_every_shard() {
+    let cache: ShardedLruCache<u32, u32> = ShardedLruCache::builder()
+        .shards(8)
+        .max_size(128)
+        .max_pinned(2)
+        .build()
+        .unwrap();
+    assert_eq!(cache.pin(&1), Ok(false));
+    assert_eq!(cache.set_pinned(1, 1), Ok(None));
+    assert_eq!(cache.set_pinned(2, 2), Ok(None));
+    let full = PinError::LimitReached { max_pinned: 2 };
+    assert_eq!(cache.set_pinned(3, 3), Err(full.clone()));
+    cache.set(3, 3);
+    assert_eq!(cache.pin(&3), Err(full));
+    // Re-pinning an already pinned entry is not a new pin.
+    assert_eq!(cache.set_pinned(1, 10), Ok(Some(1)));
+    assert_eq!(cache.remove(&2), Some(2));
+    assert_eq!(cache.pin(&3), Ok(true));
+    assert_eq!(cache.pinned_len(), 2);
+}
+
+#[test]
+fn sharded_expiring_lru_pins_only_live_entries() {
+    let cache: ShardedExpiringLruCache<u32, Val> = ShardedExpiringLruCache::builder()
+        .shards(1)
+        .max_size(1)
+        .build()
+        .unwrap();
+    cache.set(1, Val::dead(1));
+    assert_eq!(cache.pin(&1), Ok(false));
+    assert_eq!(cache.set_pinned(1, Val::live(1)), Ok(None));
+    cache.set(2, Val::live(2));
+    cache.set(3, Val::live(3));
+    assert_eq!(cache.get(&1), Some(Val::live(1)));
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.set_pinned(1, Val::dead(1)), Ok(Some(Val::live(1))));
+    assert_eq!(cache.get(&1), None);
+}
+
+#[cfg(feature = "time_stores")]
+mod ttl {
+    use super::*;
+    use cached::time::Duration;
+    use cached::{LruTtlCache, ShardedLruTtlCache};
+
+    #[test]
+    fn lru_ttl_pinned_entries_still_expire() {
+        let mut c: LruTtlCache<u32, u32> = LruTtlCache::builder()
+            .max_size(1)
+            .ttl(Duration::from_millis(30))
+            .build()
+            .unwrap();
+        c.set_pinned(1, 1).unwrap();
+        c.cache_set(2, 2);
+        c.cache_set(3, 3);
+        assert_eq!(c.cache_get(&1), Some(&1));
+        assert_eq!(c.cache_get(&2), None);
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(c.cache_get(&1), None);
+        assert_eq!(c.pin(&3), Ok(false));
+        assert_eq!(c.pinned_len(), 0);
+    }
+
+    #[test]
+    fn sharded_lru_ttl_pins_live_entries_across_a_reshard() {
+        let cache: ShardedLruTtlCache<u32, u32> = ShardedLruTtlCache::builder()
+            .shards(2)
+            .per_shard_max_size(1)
+            .ttl(Duration::from_secs(60))
+            .max_pinned(1)
+            .build()
+            .unwrap();
+        cache.set(1, 1);
+        assert_eq!(cache.pin(&1), Ok(true));
+        assert_eq!(
+            cache.set_pinned(2, 2),
+            Err(PinError::LimitReached { max_pinned: 1 })
+        );
+        cache.reshard(4).unwrap();
+        for k in 10..50 {
+            cache.set(k, k);
+        }
+        assert!(cache.is_pinned(&1));
+        assert_eq!(cache.get(&1), Some(1));
+        assert!(cache.unpin(&1));
+        assert_eq!(cache.set_pinned(2, 2), Ok(None));
+    }
+}
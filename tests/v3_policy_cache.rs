@@ -0,0 +1,244 @@
+//! `PolicyCache` / `ShardedPolicyCache`: the `EvictionPolicy` call protocol, the `LruPolicy`
+//! reference implementation, TTL handling, builder validation and `on_evict`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cached::{
+    BuildError, CacheEvict, Cached, CachedIter, ConcurrentCacheBase, ConcurrentCacheEvict,
+    ConcurrentCached, EvictionPolicy, LruCache, LruPolicy, PolicyCache, ShardedPolicyCache,
+};
+
+/// Records every call, and evicts the oldest inserted key still tracked.
+#[derive(Clone, Default)]
+struct Recorder {
+    calls: Vec<String>,
+    tracked: Vec<u32>,
+}
+
+impl EvictionPolicy<u32> for Recorder {
+    fn record_insert(&mut self, key: &u32) {
+        self.calls.push(format!("insert {key}"));
+        self.tracked.push(*key);
+    }
+    fn record_access(&mut self, key: &u32) {
+        self.calls.push(format!("access {key}"));
+    }
+    fn record_remove(&mut self, key: &u32) {
+        self.calls.push(format!("remove {key}"));
+        self.tracked.retain(|k| k != key);
+    }
+    fn choose_victim(&mut self) -> Option<u32> {
+        let victim = (!self.tracked.is_empty()).then(|| self.tracked.remove(0));
+        self.calls.push(format!("victim {victim:?}"));
+        victim
+    }
+}
+
+/// Evicts the lowest-priority key; priority is the key's tens digit, so 5 < 12 < 31.
+#[derive(Clone, Default)]
+struct PriorityClasses(std::collections::BTreeSet<(u32, u32)>);
+
+impl EvictionPolicy<u32> for PriorityClasses {
+    fn record_insert(&mut self, key: &u32) {
+        self.0.insert((key / 10, *key));
+    }
+    fn record_access(&mut self, _key: &u32) {}
+    fn record_remove(&mut self, key: &u32) {
+        self.0.remove(&(key / 10, *key));
+    }
+    fn choose_victim(&mut self) -> Option<u32> {
+        self.0.pop_first().map(|(_, key)| key)
+    }
+}
+
+#[test]
+fn build_requires_size_and_policy() {
+    assert!(matches!(
+        PolicyCache::<u32, u32, LruPolicy<u32>>::builder()
+            .policy(LruPolicy::new())
+            .build(),
+        Err(BuildError::MissingRequired("max_size"))
+    ));
+    assert!(matches!(
+        PolicyCache::<u32, u32, LruPolicy<u32>>::builder()
+            .max_size(4)
+            .build(),
+        Err(BuildError::MissingRequired("policy"))
+    ));
+    assert!(
+        ShardedPolicyCache::<u32, u32, LruPolicy<u32>>::builder()
+            .max_size(64)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn the_policy_sees_every_change_in_order() {
+    let mut cache = PolicyCache::new(2, Recorder::default());
+    cache.cache_set(1, 1);
+    cache.cache_set(2, 2);
+    let _ = cache.cache_get(&1);
+    let _ = cache.cache_get(&9);
+    cache.cache_set(2, 20);
+    cache.cache_set(3, 3);
+    let _ = cache.cache_remove(&2);
+    cache.cache_clear();
+    assert_eq!(
+        cache.policy().calls,
+        [
+            "insert 1",
+            "insert 2",
+            "access 1",
+            "access 2",
+            "victim Some(1)",
+            "insert 3",
+            "remove 2",
+            "remove 3",
+        ]
+    );
+}
+
+#[test]
+fn lru_policy_matches_lru_cache() {
+    let mut lru = LruCache::new(3);
+    let mut policy = PolicyCache::new(3, LruPolicy::new());
+    for (k, read) in [
+        (1, false),
+        (2, false),
+        (3, false),
+        (1, true),
+        (4, false),
+        (2, true),
+    ] {
+        if read {
+            assert_eq!(lru.cache_get(&k), policy.cache_get(&k));
+        } else {
+            assert_eq!(lru.cache_set(k, k), policy.cache_set(k, k));
+        }
+    }
+    let lru_keys: Vec<u32> = lru.keys().copied().collect();
+    let policy_keys: Vec<u32> = policy.policy().keys().copied().collect();
+    assert_eq!(lru_keys, [4, 1, 3]);
+    assert_eq!(policy_keys, lru_keys);
+}
+
+#[test]
+fn a_custom_policy_picks_the_victims() {
+    let mut cache = PolicyCache::new(3, PriorityClasses::default());
+    for k in [31, 5, 12] {
+        cache.cache_set(k, k);
+    }
+    cache.cache_set(40, 40);
+    assert_eq!(cache.cache_get(&5), None);
+    cache.cache_set(41, 41);
+    let mut keys: Vec<u32> = cache.keys().copied().collect();
+    keys.sort_unstable();
+    assert_eq!(keys, [31, 40, 41]);
+}
+
+#[test]
+fn expired_entries_are_misses_and_leave_through_on_evict() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let mut cache = PolicyCache::builder()
+        .max_size(4)
+        .policy(Recorder::default())
+        .ttl(Duration::from_millis(20))
+        .on_evict(move |k: &u32, _: &u32| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    cache.cache_set(1, 1);
+    cache.cache_set(2, 2);
+    assert_eq!(cache.ttl(), Some(Duration::from_millis(20)));
+    std::thread::sleep(Duration::from_millis(40));
+    cache.cache_set(3, 3);
+    assert_eq!(cache.cache_get(&1), None);
+    assert_eq!(CacheEvict::evict(&mut cache), 1);
+    assert_eq!(*evicted.lock().unwrap(), [1, 2]);
+    assert_eq!(cache.cache_size(), 1);
+    assert_eq!(cache.cache_misses(), Some(1));
+    assert!(cache.policy().calls.ends_with(&[
+        "insert 3".to_string(),
+        "remove 1".to_string(),
+        "remove 2".to_string()
+    ]));
+}
+
+#[test]
+fn evictions_and_removals_fire_on_evict() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let mut cache = PolicyCache::builder()
+        .max_size(2)
+        .policy(LruPolicy::new())
+        .on_evict(move |k: &u32, v: &u32| sink.lock().unwrap().push((*k, *v)))
+        .build()
+        .unwrap();
+    cache.cache_set(1, 10);
+    cache.cache_set(2, 20);
+    cache.cache_set(3, 30);
+    assert_eq!(cache.cache_remove(&3), Some(30));
+    assert_eq!(*evicted.lock().unwrap(), [(1, 10), (3, 30)]);
+    assert_eq!(cache.cache_evictions(), Some(2));
+
+    assert_eq!(cache.retain(|_, v| *v > 100), 1);
+    assert_eq!(evicted.lock().unwrap().last(), Some(&(2, 20)));
+    assert_eq!(cache.cache_size(), 0);
+    assert_eq!(cache.policy().keys().count(), 0);
+}
+
+#[test]
+fn sharded_cache_gives_each_shard_its_own_policy() {
+    let cache: ShardedPolicyCache<u32, u32, LruPolicy<u32>> = ShardedPolicyCache::builder()
+        .shards(1)
+        .max_size(16)
+        .policy(LruPolicy::new())
+        .build()
+        .unwrap();
+    for k in 0..20 {
+        cache.set(k, k);
+    }
+    assert_eq!(cache.len(), 16);
+    assert_eq!(cache.get(&4), Some(4));
+    assert_eq!(cache.get(&0), None);
+    assert_eq!(cache.with_policy(&4, |p| p.keys().next().copied()), Some(4));
+    assert_eq!(cache.cache_hits(), Some(1));
+    assert_eq!(cache.cache_misses(), Some(1));
+
+    let copy = cache.deep_clone();
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(copy.len(), 16);
+}
+
+#[test]
+fn sharded_evict_and_on_evict_see_every_removal() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedPolicyCache<u32, u32, LruPolicy<u32>> = ShardedPolicyCache::builder()
+        .shards(4)
+        .per_shard_max_size(16)
+        .policy(LruPolicy::new())
+        .ttl(Duration::from_millis(300))
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    for k in 0..200 {
+        cache.cache_set(k, k).unwrap();
+    }
+    assert!(cache.len() <= 64);
+    assert_eq!(evicted.lock().unwrap().len(), 200 - cache.len());
+
+    let before = evicted.lock().unwrap().len();
+    let odd = (0..200).filter(|k| k % 2 == 1 && cache.contains(k)).count();
+    assert_eq!(cache.retain(|k, _| k % 2 == 0), odd);
+    assert_eq!(evicted.lock().unwrap().len(), before + odd);
+
+    let left = cache.len();
+    std::thread::sleep(Duration::from_millis(400));
+    assert_eq!(ConcurrentCacheEvict::evict(&cache), left);
+    assert!(cache.is_empty());
+    assert_eq!(evicted.lock().unwrap().len(), before + odd + left);
+}
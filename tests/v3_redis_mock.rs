@@ -0,0 +1,323 @@
+//! `MockRedisCache`: the Redis store's key layout, TTLs, envelope and self-heal, run in
+//! process, plus the faults `MockRedisServer` injects. No server is needed.
+
+#![cfg(feature = "redis_store")]
+
+use std::thread::sleep;
+
+use cached::stores::{MockRedisCache, MockRedisFault, MockRedisServer, RedisSizeMode};
+use cached::time::Duration;
+use cached::{
+    ConcurrentCacheBase, ConcurrentCacheTtl, ConcurrentCached, ConcurrentCachedTags,
+    RedisCacheError,
+};
+
+fn build(prefix: &str) -> MockRedisCache<String, String> {
+    MockRedisCache::builder(prefix).build().unwrap()
+}
+
+#[test]
+fn build_rejects_what_the_redis_builder_rejects() {
+    let missing = cached::stores::MockRedisCacheBuilder::<u32, u32>::new().build();
+    assert!(missing.is_err());
+    assert!(MockRedisCache::<u32, u32>::builder("").build().is_err());
+    assert!(
+        MockRedisCache::<u32, u32>::builder("p")
+            .ttl(Duration::ZERO)
+            .build()
+            .is_err()
+    );
+    assert!(
+        MockRedisCache::<u32, u32>::builder("p")
+            .size_mode(RedisSizeMode::Scan { limit: 0 })
+            .build()
+            .is_err()
+    );
+    // Connection settings are accepted and ignored.
+    MockRedisCache::<u32, u32>::builder("p")
+        .connection_string("redis://unreachable:1")
+        .connection_pool_max_size(1)
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn keys_follow_the_redis_layout() {
+    let cache: MockRedisCache<String, String> = MockRedisCache::builder("users")
+        .namespace("app")
+        .build()
+        .unwrap();
+    cache.cache_set("a:b".into(), "v".into()).unwrap();
+    assert_eq!(cache.redis_key(&"a:b".to_string()), "app:users:a%3Ab");
+    assert_eq!(cache.server().keys(), ["app:users:a%3Ab"]);
+
+    let default = build("users");
+    default.cache_set("k".into(), "v".into()).unwrap();
+    assert_eq!(default.server().keys(), ["cached-redis-store:users:k"]);
+}
+
+#[test]
+fn get_set_remove_delete() {
+    let cache = build("basic");
+    assert_eq!(cache.cache_set("k".into(), "1".into()).unwrap(), None);
+    assert_eq!(
+        cache.cache_set("k".into(), "2".into()).unwrap(),
+        Some("1".into())
+    );
+    assert_eq!(cache.cache_get(&"k".into()).unwrap(), Some("2".into()));
+    assert!(cache.cache_contains(&"k".into()).unwrap());
+    assert_eq!(cache.cache_remove(&"k".into()).unwrap(), Some("2".into()));
+    assert_eq!(cache.cache_get(&"k".into()).unwrap(), None);
+
+    cache.cache_set("k".into(), "3".into()).unwrap();
+    assert!(cache.cache_delete(&"k".into()).unwrap());
+    assert!(!cache.cache_delete(&"k".into()).unwrap());
+}
+
+#[test]
+fn ttl_expires_and_refresh_on_hit_renews() {
+    let cache: MockRedisCache<u32, u32> = MockRedisCache::builder("ttl")
+        .ttl_millis(150)
+        .build()
+        .unwrap();
+    cache.cache_set(1, 1).unwrap();
+    sleep(Duration::from_millis(200));
+    assert_eq!(cache.cache_get(&1).unwrap(), None);
+    assert!(cache.server().keys().is_empty());
+
+    let cache: MockRedisCache<u32, u32> = MockRedisCache::builder("refresh")
+        .ttl_millis(300)
+        .refresh_on_hit(true)
+        .build()
+        .unwrap();
+    cache.cache_set(1, 1).unwrap();
+    for _ in 0..4 {
+        sleep(Duration::from_millis(100));
+        assert_eq!(cache.cache_get(&1).unwrap(), Some(1));
+    }
+
+    // Unsetting the TTL applies to later writes.
+    assert_eq!(cache.unset_ttl(), Some(Duration::from_millis(300)));
+    cache.cache_set(2, 2).unwrap();
+    sleep(Duration::from_millis(350));
+    assert_eq!(cache.cache_get(&2).unwrap(), Some(2));
+}
+
+#[test]
+fn legacy_json_entries_are_read() {
+    let cache = build("legacy");
+    let key = cache.redis_key(&"hello".to_string());
+    cache
+        .server()
+        .set_raw(&key, r#"{"value":"world","version":1}"#);
+    assert_eq!(
+        cache.cache_get(&"hello".into()).unwrap(),
+        Some("world".into())
+    );
+}
+
+#[test]
+fn payloads_are_shared_between_caches_on_one_server() {
+    let server = MockRedisServer::new();
+    let writer: MockRedisCache<u32, String> = MockRedisCache::builder("shared")
+        .server(&server)
+        .build()
+        .unwrap();
+    let reader: MockRedisCache<u32, String> = MockRedisCache::builder("shared")
+        .server(&server)
+        .build()
+        .unwrap();
+    writer.cache_set(7, "seven".into()).unwrap();
+    assert_eq!(reader.cache_get(&7).unwrap(), Some("seven".into()));
+
+    // Copying the raw bytes elsewhere copies the entry.
+    let bytes = server.get_raw(&writer.redis_key(&7)).unwrap();
+    server.set_raw(&writer.redis_key(&8), bytes);
+    assert_eq!(reader.cache_get(&8).unwrap(), Some("seven".into()));
+}
+
+#[test]
+fn corrupt_payloads_self_heal_or_fail_in_strict_mode() {
+    let cache = build("heal");
+    cache.cache_set("k".into(), "v".into()).unwrap();
+    cache.corrupt(&"k".into());
+    assert_eq!(cache.cache_get(&"k".into()).unwrap(), None);
+    assert!(cache.server().keys().is_empty());
+
+    let strict: MockRedisCache<String, String> = MockRedisCache::builder("strict")
+        .strict_deserialization(true)
+        .build()
+        .unwrap();
+    strict.corrupt(&"k".into());
+    assert!(matches!(
+        strict.cache_get(&"k".into()),
+        Err(RedisCacheError::CacheDeserialization { .. })
+    ));
+    // Strict reads leave the entry for inspection; remove still drops it.
+    assert_eq!(strict.server().keys().len(), 1);
+    assert!(strict.cache_remove(&"k".into()).is_err());
+    assert!(strict.server().keys().is_empty());
+}
+
+fn redis_error<'a>(source: &'a (dyn std::error::Error + 'static)) -> &'a redis::RedisError {
+    source.downcast_ref().expect("a redis::RedisError source")
+}
+
+#[test]
+fn faults_fail_one_operation_each_before_it_runs() {
+    let cache = build("faults");
+    let server = cache.server();
+    server.fail_next(MockRedisFault::ConnectionRefused);
+    server.fail_next(MockRedisFault::Timeout);
+    server.fail_next(MockRedisFault::PoolExhausted);
+
+    let refused = cache.cache_set("k".into(), "v".into()).unwrap_err();
+    match &refused {
+        RedisCacheError::Redis { source } => {
+            assert!(redis_error(source.as_ref()).is_connection_refusal())
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(server.keys().is_empty(), "a failed write writes nothing");
+
+    match cache.cache_get(&"k".into()).unwrap_err() {
+        RedisCacheError::Redis { source } => assert!(redis_error(source.as_ref()).is_timeout()),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(matches!(
+        cache.cache_clear().unwrap_err(),
+        RedisCacheError::Pool { .. }
+    ));
+
+    cache.cache_set("k".into(), "v".into()).unwrap();
+    server.set_outage(Some(MockRedisFault::Timeout));
+    assert!(cache.cache_get(&"k".into()).is_err());
+    assert!(cache.cache_get(&"k".into()).is_err());
+    server.set_outage(None);
+    assert_eq!(cache.cache_get(&"k".into()).unwrap(), Some("v".into()));
+}
+
+#[test]
+fn tags_and_clear_stay_in_scope() {
+    let server = MockRedisServer::new();
+    let cache: MockRedisCache<u32, u32> = MockRedisCache::builder("tags")
+        .server(&server)
+        .build()
+        .unwrap();
+    let other: MockRedisCache<u32, u32> = MockRedisCache::builder("other")
+        .server(&server)
+        .build()
+        .unwrap();
+    cache.set_with_tags(1, 1, &["even", "all"]).unwrap();
+    cache.set_with_tags(2, 2, &["all"]).unwrap();
+    other.cache_set(1, 1).unwrap();
+
+    assert_eq!(cache.invalidate_tag("even").unwrap(), 1);
+    assert_eq!(cache.cache_get(&1).unwrap(), None);
+    assert_eq!(cache.invalidate_tag("missing").unwrap(), 0);
+
+    cache.cache_clear().unwrap();
+    assert_eq!(cache.cache_get(&2).unwrap(), None);
+    assert_eq!(server.keys(), ["cached-redis-store:other:1"]);
+}
+
+#[test]
+fn tag_sets_drop_expired_members_on_write() {
+    let cache: MockRedisCache<u32, u32> = MockRedisCache::builder("prune")
+        .ttl_millis(20)
+        .build()
+        .unwrap();
+    let set = "cached-redis-store:prune:tag:t";
+    for i in 0..3 {
+        cache.set_with_tags(i, i, &["t"]).unwrap();
+    }
+    assert_eq!(cache.server().members(set).len(), 3);
+
+    sleep(Duration::from_millis(40));
+    cache.set_with_tags(9, 9, &["t"]).unwrap();
+    assert_eq!(cache.server().members(set), ["cached-redis-store:prune:9"]);
+    assert_eq!(cache.invalidate_tag("t").unwrap(), 1);
+    assert!(cache.server().members(set).is_empty());
+}
+
+#[test]
+fn remove_matching_and_scan() {
+    let cache: MockRedisCache<String, u32> = MockRedisCache::builder("scan")
+        .store_keys(true)
+        .ttl_secs(60)
+        .build()
+        .unwrap();
+    for key in ["user:1", "user:2", "order:1"] {
+        cache.cache_set(key.into(), 1).unwrap();
+    }
+    cache.set_with_tags("user:3".into(), 1, &["t"]).unwrap();
+    assert_eq!(cache.remove_matching("user:[12]").unwrap(), 2);
+
+    let mut keys: Vec<String> = cache
+        .scan()
+        .unwrap()
+        .map(|entry| entry.unwrap().original_key().unwrap().clone())
+        .collect();
+    keys.sort();
+    assert_eq!(keys, ["order:1", "user:3"]);
+    assert!(
+        cache
+            .scan()
+            .unwrap()
+            .all(|entry| entry.unwrap().ttl().is_some_and(|ttl| ttl > Duration::ZERO))
+    );
+}
+
+#[test]
+fn cache_size_follows_the_size_mode() {
+    assert_eq!(build("unknown").cache_size().unwrap(), None);
+
+    let exact: MockRedisCache<u32, u32> = MockRedisCache::builder("exact")
+        .size_mode(RedisSizeMode::Exact)
+        .build()
+        .unwrap();
+    let capped: MockRedisCache<u32, u32> = MockRedisCache::builder("capped")
+        .size_mode(RedisSizeMode::Scan { limit: 2 })
+        .build()
+        .unwrap();
+    for i in 0..3 {
+        exact.set_with_tags(i, i, &["t"]).unwrap();
+        capped.cache_set(i, i).unwrap();
+    }
+    assert_eq!(exact.cache_size().unwrap(), Some(3));
+    assert_eq!(capped.cache_size().unwrap(), Some(2));
+}
+
+/// Code written against the Redis store's trait surface runs unchanged on the mock.
+fn get_or_default<C>(cache: &C, key: u32) -> Result<String, RedisCacheError>
+where
+    C: ConcurrentCached<u32, String, Error = RedisCacheError>,
+{
+    if let Some(value) = cache.cache_get(&key)? {
+        return Ok(value);
+    }
+    let value = format!("default-{key}");
+    cache.cache_set(key, value.clone())?;
+    Ok(value)
+}
+
+#[test]
+fn generic_code_sees_the_store_errors() {
+    let cache: MockRedisCache<u32, String> = MockRedisCache::builder("generic").build().unwrap();
+    assert_eq!(get_or_default(&cache, 1).unwrap(), "default-1");
+    cache.server().fail_next(MockRedisFault::Timeout);
+    assert!(get_or_default(&cache, 1).is_err());
+    assert_eq!(get_or_default(&cache, 1).unwrap(), "default-1");
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_trait_runs_the_same_operations() {
+    use cached::ConcurrentCachedAsync;
+
+    let cache: MockRedisCache<u32, u32> = MockRedisCache::builder("async").build().unwrap();
+    cache.async_cache_set(1, 1).await.unwrap();
+    assert_eq!(cache.async_cache_get(&1).await.unwrap(), Some(1));
+    cache.server().fail_next(MockRedisFault::ConnectionRefused);
+    assert!(cache.async_cache_get(&1).await.is_err());
+}
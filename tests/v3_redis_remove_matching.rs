@@ -0,0 +1,97 @@
+//! `RedisCache::remove_matching` / `AsyncRedisCache::remove_matching`: glob removal scoped to
+//! the cache's namespace and prefix.
+//!
+//! Requires a live redis; every test skips (returns early) when
+//! `CACHED_REDIS_CONNECTION_STRING` is unset, matching the existing live redis tests.
+
+#![cfg(feature = "redis_store")]
+
+macro_rules! skip_without_redis {
+    () => {
+        if std::env::var("CACHED_REDIS_CONNECTION_STRING").is_err() {
+            return;
+        }
+    };
+}
+
+mod sync_tests {
+    use cached::{ConcurrentCached, ConcurrentCachedTags, RedisCache};
+    use std::time::Duration;
+
+    fn build(prefix: &str) -> RedisCache<String, u32> {
+        RedisCache::<String, u32>::builder(prefix)
+            .ttl(Duration::from_secs(60))
+            .build()
+            .expect("build RedisCache")
+    }
+
+    #[test]
+    fn removes_only_matching_keys_of_this_cache() {
+        skip_without_redis!();
+        let cache = build("v3_remove_matching_sync");
+        let other = build("v3_remove_matching_sync_other");
+        cache.cache_clear().unwrap();
+        other.cache_clear().unwrap();
+
+        cache.cache_set("user:42:profile".into(), 1).unwrap();
+        cache.cache_set("user:42:feed".into(), 2).unwrap();
+        cache.cache_set("user:43:profile".into(), 3).unwrap();
+        other.cache_set("user:42:profile".into(), 4).unwrap();
+
+        assert_eq!(cache.remove_matching("user:42:*").unwrap(), 2);
+        assert_eq!(cache.cache_get(&"user:42:profile".into()).unwrap(), None);
+        assert_eq!(cache.cache_get(&"user:43:profile".into()).unwrap(), Some(3));
+        assert_eq!(other.cache_get(&"user:42:profile".into()).unwrap(), Some(4));
+        assert_eq!(cache.remove_matching("user:42:*").unwrap(), 0);
+
+        cache.cache_clear().unwrap();
+        other.cache_clear().unwrap();
+    }
+
+    #[test]
+    fn percent_in_pattern_matches_literally_and_tag_sets_survive() {
+        skip_without_redis!();
+        let cache = build("v3_remove_matching_sync_pct");
+        cache.cache_clear().unwrap();
+
+        cache.set_with_tags("50%".into(), 1, &["t"]).unwrap();
+        cache.cache_set("500".into(), 2).unwrap();
+        assert_eq!(cache.remove_matching("50%").unwrap(), 1);
+        assert_eq!(cache.cache_get(&"500".into()).unwrap(), Some(2));
+
+        // `*` never reaches the tag set, so invalidating still works afterwards.
+        cache.set_with_tags("k".into(), 3, &["t"]).unwrap();
+        assert_eq!(cache.remove_matching("5*").unwrap(), 1);
+        assert_eq!(cache.invalidate_tag("t").unwrap(), 1);
+
+        cache.cache_clear().unwrap();
+    }
+}
+
+#[cfg(feature = "redis_tokio")]
+mod async_tests {
+    use cached::time::Duration;
+    use cached::{AsyncRedisCache, ConcurrentCachedAsync};
+
+    #[tokio::test]
+    async fn removes_only_matching_keys_of_this_cache() {
+        skip_without_redis!();
+        let cache = AsyncRedisCache::<String, u32>::builder("v3_remove_matching_async")
+            .ttl(Duration::from_secs(60))
+            .build()
+            .await
+            .expect("build AsyncRedisCache");
+        cache.async_cache_clear().await.unwrap();
+
+        for i in 0..250u32 {
+            cache.async_cache_set(format!("a{i}"), i).await.unwrap();
+        }
+        cache.async_cache_set("b0".into(), 0).await.unwrap();
+
+        assert_eq!(cache.remove_matching("a*").await.unwrap(), 250);
+        assert_eq!(cache.async_cache_get(&"a0".into()).await.unwrap(), None);
+        assert_eq!(cache.async_cache_get(&"b0".into()).await.unwrap(), Some(0));
+
+        cache.async_cache_clear().await.unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+//! `RedisCache::scan` / `AsyncRedisCache::scan`: cursored iteration over the cache's live
+//! entries, with the original key when the cache was built with `store_keys(true)`.
+//!
+//! Requires a live redis; every test skips (returns early) when
+//! `CACHED_REDIS_CONNECTION_STRING` is unset, matching the existing live redis tests.
+
+#![cfg(feature = "redis_store")]
+
+macro_rules! skip_without_redis {
+    () => {
+        if std::env::var("CACHED_REDIS_CONNECTION_STRING").is_err() {
+            return;
+        }
+    };
+}
+
+mod sync_tests {
+    use cached::{ConcurrentCached, ConcurrentCachedTags, RedisCache};
+    use std::time::Duration;
+
+    #[test]
+    fn yields_every_entry_once_and_skips_tag_sets() {
+        skip_without_redis!();
+        let cache = RedisCache::<String, u32>::builder("v3_scan_sync")
+            .ttl(Duration::from_secs(60))
+            .build()
+            .expect("build RedisCache");
+        cache.cache_clear().unwrap();
+
+        for i in 0..250u32 {
+            cache.cache_set(format!("k:{i}"), i).unwrap();
+        }
+        cache.set_with_tags("tagged".into(), 999, &["t"]).unwrap();
+
+        let mut seen: Vec<(String, u32)> = cache
+            .scan()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                assert!(
+                    entry
+                        .ttl()
+                        .is_some_and(|ttl| ttl <= Duration::from_secs(60))
+                );
+                assert!(entry.original_key().is_none());
+                (entry.key().to_string(), entry.into_value())
+            })
+            .collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 251);
+        assert!(seen.contains(&("k:7".to_string(), 7)));
+        assert!(seen.contains(&("tagged".to_string(), 999)));
+
+        cache.cache_clear().unwrap();
+    }
+
+    #[test]
+    fn store_keys_returns_the_original_key() {
+        skip_without_redis!();
+        let cache = RedisCache::<String, u32>::builder("v3_scan_sync_keys")
+            .store_keys(true)
+            .build()
+            .expect("build RedisCache");
+        cache.cache_clear().unwrap();
+
+        cache.cache_set("a:b%c".into(), 1).unwrap();
+        assert_eq!(cache.cache_get(&"a:b%c".into()).unwrap(), Some(1));
+
+        let entries: Vec<_> = cache.scan().unwrap().map(Result::unwrap).collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key(), "a:b%c");
+        assert_eq!(entries[0].original_key(), Some(&"a:b%c".to_string()));
+        assert_eq!(entries[0].ttl(), None);
+
+        cache.cache_clear().unwrap();
+    }
+}
+
+#[cfg(feature = "redis_tokio")]
+mod async_tests {
+    use cached::time::Duration;
+    use cached::{AsyncRedisCache, ConcurrentCachedAsync};
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn streams_entries_with_original_keys() {
+        skip_without_redis!();
+        let cache = AsyncRedisCache::<String, u32>::builder("v3_scan_async")
+            .ttl(Duration::from_secs(60))
+            .store_keys(true)
+            .build()
+            .await
+            .expect("build AsyncRedisCache");
+        cache.async_cache_clear().await.unwrap();
+
+        for i in 0..150u32 {
+            cache.async_cache_set(format!("k{i}"), i).await.unwrap();
+        }
+
+        let mut seen: Vec<(String, u32)> = cache
+            .scan()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                assert_eq!(entry.original_key().map(String::as_str), Some(entry.key()));
+                (entry.key().to_string(), *entry.value())
+            })
+            .collect()
+            .await;
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 150);
+
+        cache.async_cache_clear().await.unwrap();
+    }
+}
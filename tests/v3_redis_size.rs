@@ -0,0 +1,125 @@
+//! `RedisSizeMode`: `cache_size` for `RedisCache` by `SCAN` or by an exact index, and
+//! `AsyncRedisCache::async_cache_size`.
+//!
+//! Requires a live redis; every test skips (returns early) when
+//! `CACHED_REDIS_CONNECTION_STRING` is unset, matching the existing live redis tests.
+
+#![cfg(feature = "redis_store")]
+
+macro_rules! skip_without_redis {
+    () => {
+        if std::env::var("CACHED_REDIS_CONNECTION_STRING").is_err() {
+            return;
+        }
+    };
+}
+
+mod sync_tests {
+    use cached::{
+        ConcurrentCacheBase, ConcurrentCached, ConcurrentCachedTags, RedisCache, RedisSizeMode,
+        SerializeCached,
+    };
+    use std::time::Duration;
+
+    fn build(prefix: &str, mode: RedisSizeMode) -> RedisCache<String, u32> {
+        RedisCache::<String, u32>::builder(prefix)
+            .ttl(Duration::from_secs(60))
+            .size_mode(mode)
+            .build()
+            .expect("build RedisCache")
+    }
+
+    #[test]
+    fn scan_mode_counts_entries_up_to_the_limit() {
+        skip_without_redis!();
+        let cache = build("v3_size_scan", RedisSizeMode::Scan { limit: 150 });
+        cache.cache_clear().unwrap();
+        assert_eq!(cache.cache_size().unwrap(), Some(0));
+
+        for i in 0..120u32 {
+            cache.cache_set(format!("k{i}"), i).unwrap();
+        }
+        cache.set_with_tags("tagged".into(), 0, &["t"]).unwrap();
+        assert_eq!(cache.cache_size().unwrap(), Some(121));
+
+        for i in 120..200u32 {
+            cache.cache_set(format!("k{i}"), i).unwrap();
+        }
+        assert_eq!(cache.cache_size().unwrap(), Some(150));
+
+        cache.cache_clear().unwrap();
+    }
+
+    #[test]
+    fn exact_mode_follows_every_write_path() {
+        skip_without_redis!();
+        let cache = build("v3_size_exact", RedisSizeMode::Exact);
+        cache.cache_clear().unwrap();
+        assert_eq!(cache.cache_size().unwrap(), Some(0));
+
+        cache.cache_set("a".into(), 1).unwrap();
+        cache.cache_set("a".into(), 2).unwrap();
+        cache.cache_set_ref(&"b".into(), &3).unwrap();
+        cache.set_with_tags("c".into(), 4, &["t"]).unwrap();
+        cache.set_with_tags("d".into(), 5, &["t"]).unwrap();
+        cache.cache_set("e:1".into(), 6).unwrap();
+        cache.cache_set("e:2".into(), 7).unwrap();
+        assert_eq!(cache.metrics().entry_count, Some(6));
+
+        assert_eq!(cache.cache_remove(&"a".into()).unwrap(), Some(2));
+        assert!(cache.cache_delete(&"b".into()).unwrap());
+        assert_eq!(cache.cache_size().unwrap(), Some(4));
+        assert_eq!(cache.invalidate_tag("t").unwrap(), 2);
+        assert_eq!(cache.cache_size().unwrap(), Some(2));
+        assert_eq!(cache.remove_matching("e:*").unwrap(), 2);
+        assert_eq!(cache.cache_size().unwrap(), Some(0));
+
+        cache.cache_set("f".into(), 8).unwrap();
+        cache.cache_clear().unwrap();
+        assert_eq!(cache.cache_size().unwrap(), Some(0));
+    }
+
+    #[test]
+    fn exact_mode_drops_expired_entries() {
+        skip_without_redis!();
+        let cache = RedisCache::<String, u32>::builder("v3_size_exact_ttl")
+            .ttl(Duration::from_millis(200))
+            .size_mode(RedisSizeMode::Exact)
+            .build()
+            .expect("build RedisCache");
+        cache.cache_clear().unwrap();
+
+        cache.cache_set("a".into(), 1).unwrap();
+        assert_eq!(cache.cache_size().unwrap(), Some(1));
+        std::thread::sleep(Duration::from_millis(400));
+        assert_eq!(cache.cache_size().unwrap(), Some(0));
+
+        cache.cache_clear().unwrap();
+    }
+}
+
+#[cfg(feature = "redis_tokio")]
+mod async_tests {
+    use cached::time::Duration;
+    use cached::{AsyncRedisCache, ConcurrentCachedAsync, RedisSizeMode};
+
+    #[tokio::test]
+    async fn exact_mode_counts_async_writes() {
+        skip_without_redis!();
+        let cache = AsyncRedisCache::<String, u32>::builder("v3_size_exact_async")
+            .ttl(Duration::from_secs(60))
+            .size_mode(RedisSizeMode::Exact)
+            .build()
+            .await
+            .expect("build AsyncRedisCache");
+        cache.async_cache_clear().await.unwrap();
+
+        for i in 0..10u32 {
+            cache.async_cache_set(format!("k{i}"), i).await.unwrap();
+        }
+        cache.async_cache_remove(&"k0".into()).await.unwrap();
+        assert_eq!(cache.async_cache_size().await.unwrap(), Some(9));
+
+        cache.async_cache_clear().await.unwrap();
+    }
+}
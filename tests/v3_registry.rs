@@ -0,0 +1,188 @@
+//! The `register = true` macro attribute and `cached::registry`: a cache registers under its
+//! function path on first use, and the type-erased accessors reach the live static.
+
+#![cfg(feature = "proc_macro")]
+
+use cached::macros::{cached, concurrent_cached, once};
+use cached::registry::{self, CacheKind, RegisteredCache};
+
+fn registered(name: &str) -> Option<RegisteredCache> {
+    registry::get(&format!("{}::{name}", module_path!()))
+}
+
+#[cached(register = true, max_size = 8)]
+fn registered_square(n: u64) -> u64 {
+    n * n
+}
+
+#[test]
+fn cached_registers_on_first_call() {
+    assert!(registered("registered_square").is_none());
+    assert_eq!(registered_square(2), 4);
+    assert_eq!(registered_square(3), 9);
+    assert_eq!(registered_square(3), 9);
+
+    let cache = registered("registered_square").expect("registered after the first call");
+    assert_eq!(cache.kind(), CacheKind::Locked);
+    assert!(
+        cache.store_type().contains("LruCache"),
+        "{}",
+        cache.store_type()
+    );
+    assert_eq!(cache.len(), Some(2));
+    let metrics = cache.metrics();
+    assert_eq!(metrics.hits, Some(1));
+    assert_eq!(metrics.misses, Some(2));
+    assert_eq!(metrics.capacity, Some(8));
+
+    cache.clear().unwrap();
+    assert_eq!(cache.is_empty(), Some(true));
+    // Clearing keeps the counters, as `cache_clear` does.
+    assert_eq!(cache.metrics().hits, Some(1));
+    assert_eq!(
+        registry::all().filter(|c| c.path() == cache.path()).count(),
+        1,
+        "registered once, not once per call"
+    );
+}
+
+#[concurrent_cached(register = true)]
+fn registered_cube(n: u64) -> u64 {
+    n * n * n
+}
+
+#[test]
+fn concurrent_cached_registers_and_clears() {
+    assert_eq!(registered_cube(2), 8);
+    let cache = registered("registered_cube").unwrap();
+    assert_eq!(cache.kind(), CacheKind::Concurrent);
+    assert_eq!(cache.len(), Some(1));
+    assert_eq!(cache.metrics().entry_count, Some(1));
+    cache.clear().unwrap();
+    assert_eq!(cache.len(), Some(0));
+}
+
+#[once(register = true)]
+fn registered_answer() -> u32 {
+    42
+}
+
+#[test]
+fn once_reports_its_single_value() {
+    assert_eq!(registered_answer(), 42);
+    let cache = registered("registered_answer").unwrap();
+    assert_eq!(cache.kind(), CacheKind::Once);
+    assert_eq!(cache.len(), Some(1));
+    assert_eq!(cache.metrics().capacity, Some(1));
+    cache.clear().unwrap();
+    assert_eq!(cache.len(), Some(0));
+}
+
+#[cached]
+fn unregistered(n: u64) -> u64 {
+    n
+}
+
+#[test]
+fn caches_without_the_attribute_are_not_listed() {
+    assert_eq!(unregistered(1), 1);
+    assert!(registered("unregistered").is_none());
+    assert!(registry::all().all(|c| !c.path().ends_with("::unregistered")));
+}
+
+#[cached(register = true, sync_writes = "by_key")]
+fn registered_by_key(n: u64) -> u64 {
+    n + 1
+}
+
+#[test]
+fn by_key_statics_register_through_the_keyed_lock() {
+    assert_eq!(registered_by_key(1), 2);
+    let cache = registered("registered_by_key").unwrap();
+    assert_eq!(cache.len(), Some(1));
+    cache.clear().unwrap();
+    assert_eq!(cache.len(), Some(0));
+}
+
+#[cfg(feature = "redb_store")]
+mod disk {
+    use super::registered;
+    use cached::macros::concurrent_cached;
+
+    #[concurrent_cached(disk = true, register = true, map_error = r##"|e| format!("{e}")"##)]
+    fn registered_disk(n: u32) -> Result<u32, String> {
+        Ok(n * 10)
+    }
+
+    #[test]
+    fn disk_caches_clear_through_the_store() {
+        // The redb file outlives the process; start from an empty table.
+        assert_eq!(registered_disk(1), Ok(10));
+        registered("disk::registered_disk")
+            .unwrap()
+            .clear()
+            .unwrap();
+        assert_eq!(registered_disk(1), Ok(10));
+        let cache = registered("disk::registered_disk").unwrap();
+        assert!(
+            cache.store_type().contains("RedbCache"),
+            "{}",
+            cache.store_type()
+        );
+        assert_eq!(cache.len(), Some(1));
+        cache.clear().unwrap();
+        assert_eq!(cache.len(), Some(0));
+    }
+}
+
+#[cfg(feature = "async")]
+mod async_macros {
+    use super::registered;
+    use cached::macros::{cached, concurrent_cached, once};
+
+    #[cached(register = true)]
+    async fn registered_double(n: u64) -> u64 {
+        n * 2
+    }
+
+    #[concurrent_cached(register = true)]
+    async fn registered_triple(n: u64) -> u64 {
+        n * 3
+    }
+
+    #[once(register = true)]
+    async fn registered_async_answer() -> u32 {
+        7
+    }
+
+    // The accessors take the async locks by blocking, so they are called outside the runtime.
+    #[test]
+    fn async_caches_are_reachable_from_sync_code() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            assert_eq!(registered_double(1).await, 2);
+            assert_eq!(registered_triple(1).await, 3);
+            assert_eq!(registered_async_answer().await, 7);
+        });
+        for name in [
+            "async_macros::registered_double",
+            "async_macros::registered_triple",
+            "async_macros::registered_async_answer",
+        ] {
+            let cache = registered(name).unwrap_or_else(|| panic!("{name} registered"));
+            assert_eq!(cache.len(), Some(1), "{name}");
+            cache.clear().unwrap();
+            assert_eq!(cache.len(), Some(0), "{name}");
+        }
+    }
+}
+
+/// `register` needs a module-level static and a path that names the cache.
+#[test]
+fn register_rejections() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/cached_register_in_impl.rs");
+    t.compile_fail("tests/ui/concurrent_cached_register_async_redis.rs");
+}
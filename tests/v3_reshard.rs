@@ -0,0 +1,255 @@
+//! Online resharding (`reshard` on the sharded stores) and the per-shard counters behind
+//! `shard_stats`: entries, recency, deadlines and counters survive a move, and lookups keep
+//! finding their keys while one is running.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use cached::stores::{ShardedLruCache, ShardedUnboundCache};
+use cached::{ConcurrentCached, ConcurrentCachedExt, ReshardError, ShardHasher};
+
+/// Routes key `k` to shard `k & mask`, so tests can tell which keys share a shard.
+#[derive(Clone)]
+struct HighBits;
+
+impl ShardHasher<u32> for HighBits {
+    fn shard_hash(&self, key: &u32) -> u64 {
+        u64::from(*key) << 32
+    }
+}
+
+#[test]
+fn entries_survive_growing_and_shrinking() {
+    let cache: ShardedUnboundCache<u32, u32> =
+        ShardedUnboundCache::builder().shards(2).build().unwrap();
+    for k in 0..500 {
+        cache.set(k, k * 2);
+    }
+    for n in [8, 64, 1, 4] {
+        cache.reshard(n).unwrap();
+        assert_eq!(cache.shards(), n);
+        assert_eq!(cache.len(), 500);
+        for k in 0..500 {
+            assert_eq!(cache.get(&k), Some(k * 2), "key {k} after reshard({n})");
+        }
+    }
+}
+
+#[test]
+fn shard_count_rounds_up_and_zero_is_rejected() {
+    let cache: ShardedUnboundCache<u32, u32> =
+        ShardedUnboundCache::builder().shards(4).build().unwrap();
+    assert_eq!(cache.reshard(0), Err(ReshardError::ZeroShards));
+    assert_eq!(
+        cache.reshard(usize::MAX),
+        Err(ReshardError::ShardCountOverflow)
+    );
+    assert_eq!(cache.shards(), 4);
+    cache.reshard(5).unwrap();
+    assert_eq!(cache.shards(), 8);
+    assert_eq!(cache.shard_stats().len(), 8);
+}
+
+#[test]
+fn lru_order_within_a_shard_is_kept() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = evicted.clone();
+    let cache: ShardedLruCache<u32, u32, HighBits> = ShardedLruCache::builder()
+        .shards(1)
+        .max_size(16)
+        .hasher(HighBits)
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    for k in 0..16 {
+        cache.set(k, k);
+    }
+    // Touch the even keys from the top down, so 14 is the least recent of them.
+    for k in (0..16).step_by(2).rev() {
+        assert_eq!(cache.get(&k), Some(k));
+    }
+
+    cache.reshard(2).unwrap();
+    // Two shards get the 16-per-shard floor each.
+    assert_eq!(cache.capacity(), 32);
+    assert_eq!(cache.shard_sizes(), vec![8, 8]);
+    assert!(evicted.lock().unwrap().is_empty());
+
+    // Sixteen more even keys overflow shard 0 by eight, evicting the old evens oldest first.
+    for k in (100..132).step_by(2) {
+        cache.set(k, k);
+    }
+    assert_eq!(
+        *evicted.lock().unwrap(),
+        (0..16).step_by(2).rev().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn counters_carry_over() {
+    // With `HighBits` the 100 survivors spread 12-13 per new shard, under the 16 cap, so
+    // the move itself evicts nothing.
+    let cache: ShardedLruCache<u32, u32, HighBits> = ShardedLruCache::builder()
+        .shards(1)
+        .max_size(100)
+        .hasher(HighBits)
+        .build()
+        .unwrap();
+    for k in 0..110 {
+        cache.set(k, k);
+    }
+    for k in 0..150 {
+        let _ = cache.get(&k);
+    }
+    let before = cache.metrics();
+    assert_eq!(
+        (before.hits, before.misses, before.evictions),
+        (Some(100), Some(50), Some(10))
+    );
+
+    cache.reshard(8).unwrap();
+    let after = cache.metrics();
+    assert_eq!(
+        (after.hits, after.misses, after.evictions),
+        (before.hits, before.misses, before.evictions)
+    );
+
+    let stats = cache.shard_stats();
+    assert_eq!(stats.len(), 8);
+    assert_eq!(stats.iter().map(|s| s.hits).sum::<u64>(), 100);
+    assert_eq!(stats.iter().map(|s| s.misses).sum::<u64>(), 50);
+    assert_eq!(
+        stats.iter().map(|s| s.entries).collect::<Vec<_>>(),
+        cache.shard_sizes()
+    );
+
+    ConcurrentCached::cache_reset_metrics(&cache).unwrap();
+    assert!(
+        cache
+            .shard_stats()
+            .iter()
+            .all(|s| s.hits == 0 && s.misses == 0 && s.lock_waits == 0)
+    );
+}
+
+#[test]
+fn hot_keys_keep_counting_across_a_reshard() {
+    let cache: ShardedUnboundCache<u32, u32> = ShardedUnboundCache::builder()
+        .shards(2)
+        .hot_keys(8)
+        .build()
+        .unwrap();
+    cache.set(7, 70);
+    for _ in 0..10 {
+        let _ = ConcurrentCached::cache_get(&cache, &7);
+    }
+    cache.reshard(16).unwrap();
+    for _ in 0..5 {
+        let _ = ConcurrentCached::cache_get(&cache, &7);
+    }
+    let top = ConcurrentCachedExt::top_keys(&cache, 1).unwrap();
+    assert_eq!((top[0].key, top[0].count), (Some(7), 15));
+}
+
+#[test]
+fn lookups_find_their_keys_while_resharding() {
+    const KEYS: u32 = 2_000;
+    let cache: ShardedUnboundCache<u32, u32> =
+        ShardedUnboundCache::builder().shards(2).build().unwrap();
+    for k in 0..KEYS {
+        cache.set(k, k);
+    }
+    let stop = AtomicBool::new(false);
+    let written = std::thread::scope(|s| {
+        for _ in 0..3 {
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    for k in 0..KEYS {
+                        assert_eq!(cache.get(&k), Some(k), "key {k} missing mid-reshard");
+                    }
+                }
+            });
+        }
+        let writers: Vec<_> = (0..2u32)
+            .map(|t| {
+                let (cache, stop) = (&cache, &stop);
+                s.spawn(move || {
+                    let mut n = 0;
+                    while !stop.load(Ordering::Relaxed) {
+                        let k = KEYS + t * 1_000_000 + n;
+                        cache.set(k, k);
+                        assert_eq!(cache.get(&k), Some(k));
+                        n += 1;
+                    }
+                    n as usize
+                })
+            })
+            .collect();
+        for n in [8, 64, 4, 1, 32] {
+            cache.reshard(n).unwrap();
+        }
+        stop.store(true, Ordering::Relaxed);
+        writers
+            .into_iter()
+            .map(|w| w.join().unwrap())
+            .sum::<usize>()
+    });
+    assert_eq!(cache.shards(), 32);
+    assert_eq!(cache.len(), KEYS as usize + written);
+}
+
+#[test]
+fn reshard_from_an_on_evict_fired_by_retain_is_refused() {
+    static CACHE: OnceLock<ShardedLruCache<u32, u32>> = OnceLock::new();
+    static RESULTS: Mutex<Vec<Result<(), ReshardError>>> = Mutex::new(Vec::new());
+    let cache = CACHE.get_or_init(|| {
+        ShardedLruCache::builder()
+            .shards(2)
+            .max_size(64)
+            .on_evict(|_, _| {
+                let result = CACHE.get().unwrap().reshard(8);
+                RESULTS.lock().unwrap().push(result);
+            })
+            .build()
+            .unwrap()
+    });
+    for k in 0..4 {
+        cache.set(k, k);
+    }
+    assert_eq!(cache.retain(|k, _| k % 2 == 0), 2);
+
+    let results = RESULTS.lock().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        *results,
+        [Err(ReshardError::Reentrant), Err(ReshardError::Reentrant)]
+    );
+    assert_eq!(cache.shards(), 2);
+    cache.reshard(8).unwrap();
+    assert_eq!(cache.shards(), 8);
+}
+
+#[cfg(feature = "time_stores")]
+mod timed {
+    use cached::stores::ShardedTtlCache;
+    use std::time::Duration;
+
+    #[test]
+    fn deadlines_move_with_their_entries() {
+        let cache: ShardedTtlCache<u32, u32> = ShardedTtlCache::builder()
+            .shards(2)
+            .ttl(Duration::from_millis(400))
+            .build()
+            .unwrap();
+        for k in 0..50 {
+            cache.set(k, k);
+        }
+        std::thread::sleep(Duration::from_millis(200));
+        cache.reshard(8).unwrap();
+        assert!((0..50).all(|k| cache.get(&k) == Some(k)));
+
+        // Past the original deadline: the move neither restarted nor dropped the TTL.
+        std::thread::sleep(Duration::from_millis(300));
+        assert!((0..50).all(|k| cache.get(&k).is_none()));
+    }
+}
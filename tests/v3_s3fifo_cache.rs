@@ -0,0 +1,246 @@
+//! `S3FifoCache` / `ShardedS3FifoCache`: queue movement, builder validation, `on_evict`,
+//! shared-reference reads, and the `policy = "s3fifo"` macro selection.
+
+use std::sync::{Arc, Mutex};
+
+use cached::{
+    BuildError, Cached, CachedIter, CachedRead, ConcurrentCacheBase, ConcurrentCached, LruCache,
+    S3FifoCache, ShardedS3FifoCache,
+};
+
+#[test]
+fn build_validates_size_and_small_ratio() {
+    assert!(matches!(
+        S3FifoCache::<u32, u32>::builder().build(),
+        Err(BuildError::MissingRequired("max_size"))
+    ));
+    assert!(
+        S3FifoCache::<u32, u32>::builder()
+            .max_size(0)
+            .build()
+            .is_err()
+    );
+    for ratio in [0.0, 1.0, -0.5, f64::NAN] {
+        assert!(matches!(
+            S3FifoCache::<u32, u32>::builder()
+                .max_size(8)
+                .small_ratio(ratio)
+                .build(),
+            Err(BuildError::InvalidValue {
+                field: "small_ratio",
+                ..
+            })
+        ));
+    }
+    assert!(
+        ShardedS3FifoCache::<u32, u32>::builder()
+            .max_size(64)
+            .small_ratio(1.5)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn one_hit_wonders_leave_through_the_small_queue() {
+    let mut lru = LruCache::new(10);
+    let mut fifo = S3FifoCache::new(10);
+    for k in 0..5 {
+        lru.cache_set(k, k);
+        fifo.cache_set(k, k);
+        let _ = lru.cache_get(&k);
+        let _ = fifo.cache_get(&k);
+    }
+    for k in 1000..1100 {
+        lru.cache_set(k, k);
+        fifo.cache_set(k, k);
+    }
+    assert!((0..5).all(|k| lru.cache_get(&k).is_none()));
+    assert!((0..5).all(|k| fifo.cache_get(&k).is_some()));
+    let mut main = fifo.main_key_order();
+    main.sort_unstable();
+    assert_eq!(main, [0, 1, 2, 3, 4]);
+    assert_eq!(fifo.cache_size(), 10);
+}
+
+#[test]
+fn key_order_lists_small_then_main() {
+    let mut cache = S3FifoCache::builder()
+        .max_size(3)
+        .small_ratio(0.34)
+        .build()
+        .unwrap();
+    cache.cache_set(1, 1);
+    let _ = cache.cache_get(&1);
+    cache.cache_set(2, 2);
+    cache.cache_set(3, 3);
+    cache.cache_set(4, 4);
+    assert_eq!(cache.small_key_order(), [4, 3]);
+    assert_eq!(cache.main_key_order(), [1]);
+    assert_eq!(cache.ghost_key_order(), [2]);
+    assert_eq!(cache.key_order(), [4, 3, 1]);
+    let iterated: Vec<u32> = cache.keys().copied().collect();
+    assert_eq!(iterated, cache.key_order());
+}
+
+#[test]
+fn reads_through_a_shared_reference_count_as_hits() {
+    let mut cache = S3FifoCache::new(4);
+    cache.cache_set(1, 10);
+    let shared = &cache;
+    assert_eq!(shared.cache_get_read(&1), Some(&10));
+    assert_eq!(shared.cache_get_read(&2), None);
+    assert_eq!(cache.cache_hits(), Some(1));
+    assert_eq!(cache.cache_misses(), Some(1));
+}
+
+#[test]
+fn evictions_and_removals_fire_on_evict() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let mut cache = S3FifoCache::builder()
+        .max_size(2)
+        .on_evict(move |k: &u32, v: &u32| sink.lock().unwrap().push((*k, *v)))
+        .build()
+        .unwrap();
+    cache.cache_set(1, 10);
+    cache.cache_set(2, 20);
+    cache.cache_set(3, 30);
+    assert_eq!(cache.cache_remove(&3), Some(30));
+    assert_eq!(*evicted.lock().unwrap(), [(1, 10), (3, 30)]);
+    assert_eq!(cache.cache_evictions(), Some(2));
+
+    assert_eq!(cache.retain(|_, v| *v > 100), 1);
+    assert_eq!(evicted.lock().unwrap().last(), Some(&(2, 20)));
+    assert_eq!(cache.cache_size(), 0);
+}
+
+#[test]
+fn shrinking_evicts_down_to_the_new_bound() {
+    let mut cache = S3FifoCache::new(8);
+    for k in 0..8 {
+        cache.cache_set(k, k);
+    }
+    assert_eq!(cache.set_max_size(3), Some(8));
+    assert_eq!(cache.cache_size(), 3);
+    assert!(cache.try_set_max_size(0).is_err());
+    // Nothing was read, so the oldest writes go first.
+    let mut keys: Vec<u32> = cache.keys().copied().collect();
+    keys.sort_unstable();
+    assert_eq!(keys, [5, 6, 7]);
+}
+
+#[test]
+fn sharded_hits_keep_entries_under_a_scan() {
+    let cache: ShardedS3FifoCache<u32, u32> = ShardedS3FifoCache::builder()
+        .shards(1)
+        .max_size(16)
+        .build()
+        .unwrap();
+    for k in 0..8 {
+        cache.set(k, k);
+        assert_eq!(cache.get(&k), Some(k));
+    }
+    for k in 1000..1200 {
+        cache.set(k, k);
+    }
+    assert!((0..8).all(|k| cache.peek(&k).is_some()));
+    assert_eq!(cache.len(), 16);
+    assert_eq!(cache.key_order().len(), 16);
+    assert_eq!(cache.cache_hits(), Some(8));
+    assert_eq!(cache.cache_capacity(), Some(16));
+
+    let copy = cache.deep_clone();
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(copy.len(), 16);
+}
+
+#[test]
+fn sharded_gets_run_concurrently_with_writes() {
+    let cache: ShardedS3FifoCache<u32, u32> = ShardedS3FifoCache::builder()
+        .shards(4)
+        .per_shard_max_size(16)
+        .build()
+        .unwrap();
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let cache = &cache;
+            s.spawn(move || {
+                for k in 0..500 {
+                    cache.set(k % 97 + t, k);
+                    let _ = cache.get(&(k % 13));
+                }
+            });
+        }
+    });
+    assert!(cache.len() <= 64);
+    let hits = cache.cache_hits().unwrap();
+    let misses = cache.cache_misses().unwrap();
+    assert_eq!(hits + misses, 2000);
+}
+
+#[test]
+fn sharded_on_evict_sees_every_removal() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedS3FifoCache<u32, u32> = ShardedS3FifoCache::builder()
+        .shards(4)
+        .per_shard_max_size(16)
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    for k in 0..200 {
+        cache.cache_set(k, k).unwrap();
+    }
+    assert!(cache.len() <= 64);
+    assert_eq!(evicted.lock().unwrap().len(), 200 - cache.len());
+
+    let before = evicted.lock().unwrap().len();
+    let odd = (0..200).filter(|k| k % 2 == 1 && cache.contains(k)).count();
+    assert_eq!(cache.retain(|k, _| k % 2 == 0), odd);
+    assert_eq!(evicted.lock().unwrap().len(), before + odd);
+
+    let left = cache.len();
+    cache.cache_clear_with_on_evict();
+    assert!(cache.is_empty());
+    assert_eq!(evicted.lock().unwrap().len(), before + odd + left);
+}
+
+#[cfg(feature = "proc_macro")]
+mod macros {
+    use super::*;
+    use cached::macros::{cached, concurrent_cached};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FIFO_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static SHARDED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[cached(max_size = 4, policy = "s3fifo", unsync_reads = true)]
+    fn fifo_square(x: u32) -> u32 {
+        FIFO_CALLS.fetch_add(1, Ordering::SeqCst);
+        x * x
+    }
+
+    #[concurrent_cached(max_size = 64, shards = 2, policy = "s3fifo")]
+    fn sharded_square(x: u32) -> u32 {
+        SHARDED_CALLS.fetch_add(1, Ordering::SeqCst);
+        x * x
+    }
+
+    #[test]
+    fn policy_s3fifo_selects_the_s3fifo_stores() {
+        assert_eq!(fifo_square(3), 9);
+        assert_eq!(fifo_square(3), 9);
+        assert_eq!(FIFO_CALLS.load(Ordering::SeqCst), 1);
+        let cache: &S3FifoCache<u32, u32> = &FIFO_SQUARE.read();
+        assert_eq!(cache.key_order(), [3]);
+        assert_eq!(cache.cache_hits(), Some(1));
+
+        assert_eq!(sharded_square(5), 25);
+        assert_eq!(sharded_square(5), 25);
+        assert_eq!(SHARDED_CALLS.load(Ordering::SeqCst), 1);
+        let cache: &ShardedS3FifoCache<u32, u32> = &SHARDED_SQUARE;
+        assert_eq!(cache.shards(), 2);
+    }
+}
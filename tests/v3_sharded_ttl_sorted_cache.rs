@@ -0,0 +1,180 @@
+//! `ShardedTtlSortedCache`: builder validation, per-entry TTLs, expiry-ordered capacity
+//! eviction, head-only `evict`, read-locked hits and `on_evict`.
+#![cfg(feature = "time_stores")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cached::{
+    BuildError, ConcurrentCacheBase, ConcurrentCacheEvict, ConcurrentCacheTtl, ConcurrentCached,
+    ShardedTtlSortedCache,
+};
+
+#[test]
+fn build_validates_ttl_and_sizes() {
+    assert!(matches!(
+        ShardedTtlSortedCache::<u32, u32>::builder().build(),
+        Err(BuildError::MissingRequired("ttl"))
+    ));
+    assert!(
+        ShardedTtlSortedCache::<u32, u32>::builder()
+            .ttl(Duration::ZERO)
+            .build()
+            .is_err()
+    );
+    assert!(
+        ShardedTtlSortedCache::<u32, u32>::builder()
+            .ttl_secs(1)
+            .max_size(0)
+            .build()
+            .is_err()
+    );
+    assert!(
+        ShardedTtlSortedCache::<u32, u32>::builder()
+            .ttl_secs(1)
+            .max_size(8)
+            .per_shard_max_size(2)
+            .build()
+            .is_err()
+    );
+    let unbounded: ShardedTtlSortedCache<u32, u32> =
+        ShardedTtlSortedCache::new(Duration::from_secs(1));
+    assert_eq!(unbounded.capacity(), None);
+    assert_eq!(unbounded.cache_capacity(), None);
+}
+
+#[test]
+#[should_panic(expected = "ttl")]
+fn new_rejects_a_zero_ttl() {
+    let _ = ShardedTtlSortedCache::<u32, u32>::new(Duration::ZERO);
+}
+
+#[test]
+fn per_entry_ttl_overrides_the_default() {
+    let cache: ShardedTtlSortedCache<u32, u32> =
+        ShardedTtlSortedCache::new(Duration::from_secs(60));
+    cache.set(1, 1);
+    cache.set_with(2, 2).ttl_millis(20).set();
+    assert_eq!(cache.get(&2), Some(2));
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(cache.get(&1), Some(1));
+    assert_eq!(cache.get(&2), None);
+    assert_eq!(cache.cache_misses(), Some(1));
+    assert_eq!(cache.cache_evictions(), Some(1));
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn evict_removes_only_expired_entries() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedTtlSortedCache<u32, u32> = ShardedTtlSortedCache::builder()
+        .shards(4)
+        .ttl(Duration::from_secs(60))
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    for k in 0..40 {
+        if k % 4 == 0 {
+            cache.set_with(k, k).ttl_millis(20).set();
+        } else {
+            cache.set(k, k);
+        }
+    }
+    assert_eq!(ConcurrentCacheEvict::evict(&cache), 0);
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(ConcurrentCacheEvict::evict(&cache), 10);
+    assert_eq!(cache.len(), 30);
+    let mut keys = evicted.lock().unwrap().clone();
+    keys.sort_unstable();
+    assert_eq!(keys, (0..40).step_by(4).collect::<Vec<_>>());
+}
+
+#[test]
+fn set_with_evict_sweeps_the_key_shard() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedTtlSortedCache<u32, u32> = ShardedTtlSortedCache::builder()
+        .shards(1)
+        .ttl(Duration::from_millis(20))
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    cache.set(1, 1);
+    cache.set(2, 2);
+    std::thread::sleep(Duration::from_millis(40));
+    cache.set_with(3, 3).ttl_secs(60).evict().set();
+    assert_eq!(cache.len(), 1);
+    assert_eq!(*evicted.lock().unwrap(), [1, 2]);
+}
+
+#[test]
+fn a_full_shard_drops_its_next_entry_to_expire() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedTtlSortedCache<u32, u32> = ShardedTtlSortedCache::builder()
+        .shards(1)
+        .per_shard_max_size(2)
+        .ttl(Duration::from_secs(60))
+        .on_evict(move |k, v| sink.lock().unwrap().push((*k, *v)))
+        .build()
+        .unwrap();
+    assert_eq!(cache.capacity(), Some(2));
+    cache.set_with(1, 10).ttl_secs(300).set();
+    cache.set_with(2, 20).ttl_secs(5).set();
+    cache.set(3, 30);
+    assert_eq!(*evicted.lock().unwrap(), [(2, 20)]);
+    assert_eq!(cache.get(&1), Some(10));
+    assert_eq!(cache.get(&3), Some(30));
+
+    assert_eq!(cache.set_max_size(1), Some(2));
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&1), Some(10));
+}
+
+#[test]
+fn ttl_can_be_changed_for_future_inserts() {
+    let cache: ShardedTtlSortedCache<u32, u32> =
+        ShardedTtlSortedCache::new(Duration::from_millis(20));
+    cache.set(1, 1);
+    assert_eq!(
+        ConcurrentCacheTtl::set_ttl(&cache, Duration::from_secs(60)),
+        Some(Duration::from_millis(20))
+    );
+    cache.set(2, 2);
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(cache.get(&1), None);
+    assert_eq!(cache.get(&2), Some(2));
+    assert_eq!(
+        ConcurrentCacheTtl::ttl(&cache),
+        Some(Duration::from_secs(60))
+    );
+}
+
+#[test]
+fn removals_and_clears_fire_on_evict() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&evicted);
+    let cache: ShardedTtlSortedCache<u32, u32> = ShardedTtlSortedCache::builder()
+        .shards(4)
+        .ttl(Duration::from_secs(60))
+        .on_evict(move |k, _| sink.lock().unwrap().push(*k))
+        .build()
+        .unwrap();
+    for k in 0..20 {
+        cache.set(k, k);
+    }
+    assert_eq!(cache.remove(&3), Some(3));
+    assert_eq!(cache.cache_remove_entry(&4).unwrap(), Some((4, 4)));
+    assert_eq!(cache.retain(|k, _| k % 2 == 0), 9);
+    assert_eq!(evicted.lock().unwrap().len(), 11);
+    assert_eq!(cache.len(), 9);
+
+    let copy = cache.deep_clone();
+    cache.cache_clear_with_on_evict();
+    assert!(cache.is_empty());
+    assert_eq!(evicted.lock().unwrap().len(), 20);
+    assert_eq!(copy.len(), 9);
+    copy.clear();
+    assert_eq!(evicted.lock().unwrap().len(), 20);
+}
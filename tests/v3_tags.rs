@@ -0,0 +1,225 @@
+//! Tag-based group invalidation (`CachedTags` / `ConcurrentCachedTags`) and the
+//! `#[cached(tags = ...)]` attribute, exercised through the public API only.
+//!
+//! The Redis tests need a live server and skip (return early) when
+//! `CACHED_REDIS_CONNECTION_STRING` is unset, matching the other live Redis tests.
+
+use cached::stores::{ExpiringCache, ExpiringLruCache, LruCache, UnboundCache};
+use cached::{
+    Cached, CachedTags, ConcurrentCached, ConcurrentCachedTags, Expires, ShardedLruCache,
+    ShardedUnboundCache,
+};
+use std::sync::Arc;
+use std::thread;
+
+/// Three entries over two tenants plus a shared `report` tag: the fixture every single-owner
+/// store runs through.
+fn exercise<C: Cached<&'static str, u32> + CachedTags<&'static str, u32>>(mut cache: C) {
+    assert_eq!(cache.set_with_tags("a", 1, &["tenant:1"]), None);
+    assert_eq!(cache.set_with_tags("b", 2, &["tenant:1", "report"]), None);
+    assert_eq!(cache.set_with_tags("c", 3, &["tenant:2", "report"]), None);
+
+    assert_eq!(cache.invalidate_tag("tenant:1"), 2);
+    assert_eq!(cache.cache_get(&"a"), None);
+    assert_eq!(cache.cache_get(&"b"), None);
+    assert_eq!(cache.cache_get(&"c"), Some(&3));
+    assert_eq!(cache.invalidate_tag("tenant:1"), 0);
+    assert_eq!(cache.invalidate_tag("unknown"), 0);
+
+    // Re-tagging replaces the key's tags.
+    assert_eq!(cache.set_with_tags("c", 4, &["tenant:3"]), Some(3));
+    assert_eq!(cache.invalidate_tag("report"), 0);
+    assert_eq!(cache.invalidate_tag("tenant:3"), 1);
+    assert_eq!(cache.cache_size(), 0);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Never(u32);
+
+impl Expires for Never {
+    fn is_expired(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn every_single_owner_store_invalidates_by_tag() {
+    exercise(UnboundCache::new());
+    exercise(LruCache::new(8));
+
+    #[cfg(feature = "time_stores")]
+    {
+        use cached::stores::{LruTtlCache, TtlCache, TtlSortedCache};
+        use cached::time::Duration;
+        exercise(TtlCache::new(Duration::from_secs(60)));
+        exercise(LruTtlCache::new(8, Duration::from_secs(60)));
+        exercise(TtlSortedCache::new(Duration::from_secs(60)));
+    }
+
+    let mut expiring = ExpiringCache::new();
+    expiring.set_with_tags(1u8, Never(1), &["t"]);
+    expiring.set_with_tags(2, Never(2), &["u"]);
+    assert_eq!(expiring.invalidate_tag("t"), 1);
+    assert_eq!(expiring.cache_get(&2), Some(&Never(2)));
+
+    let mut expiring_lru = ExpiringLruCache::new(4);
+    expiring_lru.set_with_tags(1u8, Never(1), &["t"]);
+    assert_eq!(expiring_lru.invalidate_tag("t"), 1);
+    assert_eq!(expiring_lru.cache_size(), 0);
+}
+
+#[test]
+fn an_evicted_key_written_again_untagged_is_still_invalidated() {
+    let mut cache = LruCache::new(1);
+    cache.set_with_tags(1u32, "tagged", &["t"]);
+    // Capacity evicts key 1 behind the index's back.
+    cache.cache_set(2, "other");
+    cache.cache_set(1, "untagged");
+    // The index errs on the side of removing too much.
+    assert_eq!(cache.invalidate_tag("t"), 1);
+    assert_eq!(cache.cache_get(&1), None);
+}
+
+#[test]
+fn clear_forgets_every_tag() {
+    let mut cache = UnboundCache::new();
+    cache.set_with_tags("k", 1, &["t"]);
+    cache.cache_clear();
+    cache.cache_set("k", 2);
+    assert_eq!(cache.invalidate_tag("t"), 0);
+    assert_eq!(cache.cache_get(&"k"), Some(&2));
+}
+
+#[test]
+fn sharded_stores_invalidate_across_shards() {
+    let cache = ShardedUnboundCache::builder().shards(8).build().unwrap();
+    for i in 0..64u32 {
+        let tag = if i % 2 == 0 { "even" } else { "odd" };
+        cache.set_with_tags(i, i, &[tag, "all"]).unwrap();
+    }
+    assert_eq!(cache.invalidate_tag("even").unwrap(), 32);
+    assert_eq!(cache.len(), 32);
+    assert!((0..64u32).all(|i| cache.get(&i).is_some() == (i % 2 == 1)));
+    assert_eq!(cache.invalidate_tag("all").unwrap(), 32);
+    assert!(cache.is_empty());
+
+    let lru = ShardedLruCache::builder()
+        .max_size(16)
+        .shards(2)
+        .build()
+        .unwrap();
+    lru.set_with_tags("k", 1, &["t"]).unwrap();
+    assert_eq!(lru.invalidate_tag("t").unwrap(), 1);
+    assert_eq!(ConcurrentCached::cache_get(&lru, &"k").unwrap(), None);
+}
+
+#[test]
+fn concurrent_tagged_writes_are_all_invalidated() {
+    let cache = Arc::new(ShardedUnboundCache::builder().shards(4).build().unwrap());
+    let writers: Vec<_> = (0..4u32)
+        .map(|t| {
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || {
+                for i in 0..250u32 {
+                    cache.set_with_tags(t * 1000 + i, i, &["shared"]).unwrap();
+                }
+            })
+        })
+        .collect();
+    for w in writers {
+        w.join().unwrap();
+    }
+    assert_eq!(cache.invalidate_tag("shared").unwrap(), 1000);
+    assert!(cache.is_empty());
+}
+
+#[cfg(feature = "proc_macro")]
+mod macros {
+    use cached::CachedTags;
+    use cached::macros::cached;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[cached(tags = { vec![format!("tenant:{}", tenant)] })]
+    fn report(tenant: u32, page: u32) -> String {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        format!("{tenant}/{page}")
+    }
+
+    #[test]
+    fn tags_attribute_attaches_tags_per_call() {
+        assert_eq!(report(1, 1), "1/1");
+        assert_eq!(report(1, 2), "1/2");
+        assert_eq!(report(2, 1), "2/1");
+        assert_eq!(CALLS.load(Ordering::SeqCst), 3);
+
+        let removed = REPORT.write().invalidate_tag("tenant:1");
+        assert_eq!(removed, 2);
+
+        assert_eq!(report(2, 1), "2/1");
+        assert_eq!(CALLS.load(Ordering::SeqCst), 3);
+        assert_eq!(report(1, 1), "1/1");
+        assert_eq!(CALLS.load(Ordering::SeqCst), 4);
+    }
+}
+
+#[cfg(feature = "redis_store")]
+mod redis {
+    use cached::{ConcurrentCached, ConcurrentCachedTags, RedisCache};
+
+    #[test]
+    fn redis_invalidates_by_tag() {
+        if std::env::var("CACHED_REDIS_CONNECTION_STRING").is_err() {
+            return;
+        }
+        let cache: RedisCache<u32, String> = RedisCache::builder("v3_tags").build().unwrap();
+        cache.cache_clear().unwrap();
+
+        cache.set_with_tags(1, "a".into(), &["tenant:1"]).unwrap();
+        cache.set_with_tags(2, "b".into(), &["tenant:1"]).unwrap();
+        cache.set_with_tags(3, "c".into(), &["tenant:2"]).unwrap();
+        assert_eq!(cache.invalidate_tag("tenant:1").unwrap(), 2);
+        assert_eq!(cache.cache_get(&1).unwrap(), None);
+        assert_eq!(cache.cache_get(&3).unwrap(), Some("c".into()));
+        assert_eq!(cache.invalidate_tag("tenant:1").unwrap(), 0);
+
+        // `cache_clear` drops the tag sets together with the entries.
+        cache.cache_clear().unwrap();
+        cache.cache_set(3, "c".into()).unwrap();
+        assert_eq!(cache.invalidate_tag("tenant:2").unwrap(), 0);
+        assert_eq!(cache.cache_get(&3).unwrap(), Some("c".into()));
+        cache.cache_clear().unwrap();
+    }
+
+    #[test]
+    fn redis_prunes_expired_members_on_write() {
+        let Ok(url) = std::env::var("CACHED_REDIS_CONNECTION_STRING") else {
+            return;
+        };
+        let cache: RedisCache<u32, u32> = RedisCache::builder("v3_tags_prune")
+            .ttl(cached::time::Duration::from_millis(50))
+            .build()
+            .unwrap();
+        cache.cache_clear().unwrap();
+        for i in 0..100 {
+            cache.set_with_tags(i, i, &["t"]).unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        for i in 100..200 {
+            cache.set_with_tags(i, i, &["t"]).unwrap();
+        }
+
+        // Each write checks two members, so most of the expired first hundred are gone.
+        let mut conn = ::redis::Client::open(url)
+            .unwrap()
+            .get_connection()
+            .unwrap();
+        let members: usize = ::redis::cmd("SCARD")
+            .arg("cached-redis-store:v3_tags_prune:tag:t")
+            .query(&mut conn)
+            .unwrap();
+        assert!(members < 180, "{members} members left");
+        cache.cache_clear().unwrap();
+    }
+}
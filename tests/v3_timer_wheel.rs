@@ -0,0 +1,228 @@
+//! The optional timing wheel on `ExpiringCache` and `ShardedExpiringCache`: `evict` and
+//! `evict_some` check only entries whose `expires_at` deadline has passed, refile a value whose
+//! deadline moved later, and scan for values without a deadline while the store holds any.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use cached::stores::{BuildError, ShardedExpiringCache};
+use cached::time::{Duration, Instant};
+use cached::{Cached, Expires, ExpiringCache};
+
+const TICK: Duration = Duration::from_millis(1);
+
+/// A value with an optional deadline that counts how often its expiry is checked.
+#[derive(Clone, Debug)]
+struct Token {
+    deadline: Option<Instant>,
+    checks: Arc<AtomicUsize>,
+}
+
+impl Token {
+    fn new(checks: &Arc<AtomicUsize>, ttl: Option<Duration>) -> Self {
+        Self {
+            deadline: ttl.map(|ttl| Instant::now() + ttl),
+            checks: Arc::clone(checks),
+        }
+    }
+}
+
+impl Expires for Token {
+    fn is_expired(&self) -> bool {
+        self.checks.fetch_add(1, Ordering::Relaxed);
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    fn expires_at(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+/// A value that only implements `is_expired`, so it reports no deadline.
+#[derive(Clone, Debug)]
+struct Stale(bool);
+
+impl Expires for Stale {
+    fn is_expired(&self) -> bool {
+        self.0
+    }
+}
+
+const SHORT: Option<Duration> = Some(Duration::from_millis(20));
+const LONG: Option<Duration> = Some(Duration::from_secs(3600));
+
+fn sleep_past_short() {
+    std::thread::sleep(Duration::from_millis(40));
+}
+
+#[test]
+fn evict_checks_only_due_entries() {
+    let checks = Arc::new(AtomicUsize::new(0));
+    let mut cache: ExpiringCache<u32, Token> =
+        ExpiringCache::builder().timer_wheel(TICK).build().unwrap();
+    for k in 0..1000 {
+        let ttl = if k % 2 == 0 { SHORT } else { LONG };
+        cache.cache_set(k, Token::new(&checks, ttl));
+    }
+    sleep_past_short();
+    checks.store(0, Ordering::Relaxed);
+    assert_eq!(cache.evict_some(100), 100);
+    assert_eq!(cache.evict(), 400);
+    assert_eq!(checks.load(Ordering::Relaxed), 500);
+    assert_eq!(cache.cache_size(), 500);
+    assert_eq!(cache.evict(), 0);
+    assert_eq!(cache.cache_evictions(), Some(500));
+}
+
+#[test]
+fn replaced_deadlines_are_followed() {
+    let checks = Arc::new(AtomicUsize::new(0));
+    let mut cache: ExpiringCache<u32, Token> =
+        ExpiringCache::builder().timer_wheel(TICK).build().unwrap();
+    // Extended: the short filing comes due, finds a live value and refiles it.
+    cache.cache_set(1, Token::new(&checks, SHORT));
+    cache.cache_set(1, Token::new(&checks, LONG));
+    // Shortened: filed again at the earlier deadline.
+    cache.cache_set(2, Token::new(&checks, LONG));
+    cache.cache_set(2, Token::new(&checks, SHORT));
+    sleep_past_short();
+    assert_eq!(cache.evict(), 1);
+    assert!(cache.cache_get(&1).is_some());
+    assert!(cache.cache_get(&2).is_none());
+    assert_eq!(cache.cache_size(), 1);
+}
+
+#[test]
+fn values_without_a_deadline_are_scanned_for_while_any_are_stored() {
+    let checks = Arc::new(AtomicUsize::new(0));
+    let mut cache: ExpiringCache<u32, Token> =
+        ExpiringCache::builder().timer_wheel(TICK).build().unwrap();
+    for k in 0..10 {
+        cache.cache_set(k, Token::new(&checks, LONG));
+    }
+    cache.cache_set(10, Token::new(&checks, None));
+    cache.cache_set(11, Token::new(&checks, None));
+    // Given a deadline in place, where the wheel never sees it.
+    cache.cache_get_mut(&11).unwrap().deadline = Some(Instant::now());
+    checks.store(0, Ordering::Relaxed);
+    assert_eq!(cache.evict(), 1);
+    assert_eq!(checks.load(Ordering::Relaxed), 12);
+
+    // Key 10 was still live without a deadline, so the next sweep scans again, and finds none.
+    assert!(cache.cache_remove(&10).is_some());
+    checks.store(0, Ordering::Relaxed);
+    assert_eq!(cache.evict(), 0);
+    assert_eq!(checks.load(Ordering::Relaxed), 10);
+    // Back to due keys only.
+    assert_eq!(cache.evict(), 0);
+    assert_eq!(checks.load(Ordering::Relaxed), 10);
+}
+
+#[test]
+fn evict_some_budgets_entries_examined() {
+    let checks = Arc::new(AtomicUsize::new(0));
+    let mut plain: ExpiringCache<u32, Token> = ExpiringCache::new();
+    let mut wheeled: ExpiringCache<u32, Token> =
+        ExpiringCache::builder().timer_wheel(TICK).build().unwrap();
+    for k in 0..10 {
+        let ttl = if k % 2 == 0 {
+            Some(Duration::ZERO)
+        } else {
+            LONG
+        };
+        plain.cache_set(k, Token::new(&checks, ttl));
+        wheeled.cache_set(k, Token::new(&checks, ttl));
+    }
+    checks.store(0, Ordering::Relaxed);
+    let removed = plain.evict_some(4);
+    assert_eq!(checks.load(Ordering::Relaxed), 4);
+    assert!(removed <= 4);
+    assert_eq!(removed + plain.evict_some(4) + plain.evict_some(4), 5);
+    assert_eq!(checks.load(Ordering::Relaxed), 10);
+    assert_eq!(plain.evict_some(0), 0);
+
+    std::thread::sleep(TICK * 2);
+    checks.store(0, Ordering::Relaxed);
+    assert_eq!(wheeled.evict_some(4), 4);
+    assert_eq!(wheeled.evict_some(4), 1);
+    assert_eq!(checks.load(Ordering::Relaxed), 5);
+}
+
+#[test]
+fn zero_resolution_is_a_build_error() {
+    assert!(matches!(
+        ExpiringCache::<u32, Token>::builder()
+            .timer_wheel(Duration::ZERO)
+            .build(),
+        Err(BuildError::InvalidValue {
+            field: "timer_wheel",
+            ..
+        })
+    ));
+    assert!(
+        ShardedExpiringCache::<u32, Token>::builder()
+            .timer_wheel(Duration::ZERO)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn sharded_wheels_survive_a_reshard() {
+    let checks = Arc::new(AtomicUsize::new(0));
+    let evicted = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&evicted);
+    let cache: ShardedExpiringCache<u32, Token> = ShardedExpiringCache::builder()
+        .shards(4)
+        .timer_wheel(TICK)
+        .on_evict(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+        .build()
+        .unwrap();
+    for k in 0..1000 {
+        let ttl = if k % 2 == 0 { SHORT } else { LONG };
+        cache.set(k, Token::new(&checks, ttl));
+    }
+    cache.reshard(16).unwrap();
+    sleep_past_short();
+    checks.store(0, Ordering::Relaxed);
+    let mut removed = cache.evict_some(100);
+    assert_eq!(removed, 100);
+    removed += cache.evict();
+    assert_eq!(removed, 500);
+    assert_eq!(checks.load(Ordering::Relaxed), 500);
+    assert_eq!(evicted.load(Ordering::Relaxed), 500);
+    assert_eq!(cache.len(), 500);
+
+    cache.clear();
+    cache.set(1, Token::new(&checks, SHORT));
+    sleep_past_short();
+    assert_eq!(cache.evict(), 1);
+    assert!(format!("{cache:?}").contains("timer_wheel: Some(0)"));
+}
+
+#[test]
+fn sharded_wheels_scan_for_values_without_a_deadline() {
+    let cache: ShardedExpiringCache<u32, Stale> = ShardedExpiringCache::builder()
+        .shards(4)
+        .timer_wheel(TICK)
+        .build()
+        .unwrap();
+    for k in 0..100 {
+        cache.set(k, Stale(k % 2 == 0));
+    }
+    let mut removed = 0;
+    let mut steps = 0;
+    loop {
+        let step = cache.evict_budgeted(16);
+        removed += step.reclaimed;
+        steps += 1;
+        if step.finished {
+            break;
+        }
+    }
+    assert_eq!(removed, 50);
+    assert_eq!(steps, 7);
+    assert_eq!(cache.len(), 50);
+}
@@ -0,0 +1,474 @@
+//! `CacheLayer` (`cached::tower`): which requests and responses are cached, how long for,
+//! `Vary` and key headers, `If-None-Match` / `If-Modified-Since` revalidation, and bodies the
+//! layer streams through instead of buffering.
+//!
+//! Gated on `tower`. Run with `cargo test --features tower`.
+
+#![cfg(feature = "tower")]
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use bytes::Bytes;
+use cached::ConcurrentCachedAsync;
+use cached::stores::ShardedLruCache;
+use cached::tower::{CacheLayer, CachedResponse, HttpCacheKey};
+use http::header::{
+    ACCEPT_ENCODING, ACCEPT_LANGUAGE, AGE, AUTHORIZATION, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH, LAST_MODIFIED, SET_COOKIE, VARY,
+};
+use http::{HeaderValue, Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full, StreamBody};
+use tower::{Layer, Service, ServiceExt, service_fn};
+
+type Handler = fn(&Request<()>, usize) -> Response<Full<Bytes>>;
+
+/// A service answering with `handler(request, n)` for its `n`th call, counting calls.
+fn upstream(
+    handler: Handler,
+) -> (
+    impl Service<Request<()>, Response = Response<Full<Bytes>>, Error = Infallible, Future: Send>
+    + Clone,
+    Arc<AtomicUsize>,
+) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let service = service_fn(move |request: Request<()>| {
+        let n = counter.fetch_add(1, Ordering::SeqCst);
+        let response = handler(&request, n);
+        async move { Ok::<_, Infallible>(response) }
+    });
+    (service, calls)
+}
+
+fn response(cache_control: &str, n: usize) -> Response<Full<Bytes>> {
+    Response::builder()
+        .header(CACHE_CONTROL, cache_control)
+        .body(Full::new(Bytes::from(format!("response {n}"))))
+        .unwrap()
+}
+
+fn get(uri: &str) -> Request<()> {
+    Request::get(uri).body(()).unwrap()
+}
+
+async fn send<S>(service: &mut S, request: Request<()>) -> (StatusCode, http::HeaderMap, Bytes)
+where
+    S: Service<Request<()>, Response = Response<cached::tower::CacheBody<Full<Bytes>>>>,
+    S::Error: std::fmt::Debug,
+{
+    let response = service.ready().await.unwrap().call(request).await.unwrap();
+    let (parts, body) = response.into_parts();
+    (
+        parts.status,
+        parts.headers,
+        body.collect().await.unwrap().to_bytes(),
+    )
+}
+
+#[tokio::test]
+async fn max_age_responses_are_served_from_the_cache() {
+    let (inner, calls) = upstream(|_, n| response("max-age=60", n));
+    let mut service = CacheLayer::new(100).layer(inner);
+
+    let (status, headers, body) = send(&mut service, get("/a")).await;
+    assert_eq!((status, &body[..]), (StatusCode::OK, &b"response 0"[..]));
+    assert!(!headers.contains_key(AGE));
+
+    let (_, headers, body) = send(&mut service, get("/a")).await;
+    assert_eq!(&body[..], b"response 0");
+    assert_eq!(headers[AGE], "0");
+    assert_eq!(headers[CACHE_CONTROL], "max-age=60");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Another URI is another key.
+    let (_, _, body) = send(&mut service, get("/b")).await;
+    assert_eq!(&body[..], b"response 1");
+}
+
+#[tokio::test]
+async fn entries_expire_with_their_max_age() {
+    let (inner, calls) = upstream(|_, n| response("max-age=1", n));
+    let mut service = CacheLayer::new(100).layer(inner);
+    send(&mut service, get("/")).await;
+    send(&mut service, get("/")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_millis(1_100)).await;
+    let (_, _, body) = send(&mut service, get("/")).await;
+    assert_eq!(&body[..], b"response 1");
+}
+
+#[tokio::test]
+async fn uncacheable_responses_are_fetched_every_time() {
+    for cache_control in [
+        "no-store",
+        "no-cache",
+        "private, max-age=60",
+        "max-age=0",
+        "",
+    ] {
+        let handler: Handler = |request, n| {
+            let cache_control = request.headers()["x-respond-with"].to_str().unwrap();
+            response(cache_control, n)
+        };
+        let (inner, calls) = upstream(handler);
+        let mut service = CacheLayer::new(100).layer(inner);
+        for _ in 0..2 {
+            let request = Request::get("/")
+                .header("x-respond-with", cache_control)
+                .body(())
+                .unwrap();
+            send(&mut service, request).await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2, "{cache_control:?}");
+    }
+}
+
+#[tokio::test]
+async fn default_ttl_covers_responses_without_max_age() {
+    let (inner, calls) = upstream(|_, n| response("public", n));
+    let mut service = CacheLayer::new(100)
+        .default_ttl(Duration::from_secs(60))
+        .layer(inner);
+    send(&mut service, get("/")).await;
+    send(&mut service, get("/")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn a_huge_max_age_is_capped_rather_than_overflowing() {
+    let (inner, calls) = upstream(|_, n| response("max-age=18446744073709551615", n));
+    let mut service = CacheLayer::new(100).layer(inner);
+    send(&mut service, get("/")).await;
+    let (_, _, body) = send(&mut service, get("/")).await;
+    assert_eq!(&body[..], b"response 0");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn upstream_age_counts_against_the_lifetime() {
+    let handler: Handler = |request, n| {
+        let mut response = response("max-age=60", n);
+        let age = request.headers()["x-age"].clone();
+        response.headers_mut().insert(AGE, age);
+        response
+    };
+    let with_age = |age| Request::get("/").header("x-age", age).body(()).unwrap();
+
+    // Fifty of its sixty seconds spent upstream: a hit reports the total age.
+    let (inner, calls) = upstream(handler);
+    let mut service = CacheLayer::new(100).layer(inner);
+    let (_, headers, _) = send(&mut service, with_age("50")).await;
+    assert_eq!(headers[AGE], "50");
+    let (_, headers, body) = send(&mut service, with_age("50")).await;
+    assert_eq!(headers[AGE], "50");
+    assert_eq!(&body[..], b"response 0");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Already stale on arrival: not stored.
+    let (inner, calls) = upstream(handler);
+    let mut service = CacheLayer::new(100).layer(inner);
+    send(&mut service, with_age("60")).await;
+    send(&mut service, with_age("60")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn responses_setting_cookies_are_not_cached() {
+    let (inner, calls) = upstream(|_, n| {
+        let mut response = response("max-age=60", n);
+        response
+            .headers_mut()
+            .insert(SET_COOKIE, HeaderValue::from_static("session=abc"));
+        response
+    });
+    let mut service = CacheLayer::new(100).layer(inner);
+    send(&mut service, get("/")).await;
+    let (_, headers, body) = send(&mut service, get("/")).await;
+    assert_eq!(&body[..], b"response 1");
+    assert_eq!(headers[SET_COOKIE], "session=abc");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn s_maxage_takes_precedence_over_max_age() {
+    let (inner, calls) = upstream(|_, n| response("max-age=60, s-maxage=0", n));
+    let mut service = CacheLayer::new(100).layer(inner);
+    send(&mut service, get("/")).await;
+    send(&mut service, get("/")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn only_anonymous_gets_and_heads_are_cached() {
+    let (inner, calls) = upstream(|_, n| response("max-age=60", n));
+    let mut service = CacheLayer::new(100).layer(inner);
+    for _ in 0..2 {
+        send(&mut service, Request::post("/").body(()).unwrap()).await;
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    for _ in 0..2 {
+        let request = Request::get("/")
+            .header(AUTHORIZATION, "Bearer t")
+            .body(())
+            .unwrap();
+        send(&mut service, request).await;
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    for _ in 0..2 {
+        send(&mut service, Request::head("/").body(()).unwrap()).await;
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn request_cache_control_bypasses_or_refreshes() {
+    let (inner, calls) = upstream(|_, n| response("max-age=60", n));
+    let mut service = CacheLayer::new(100).layer(inner);
+    send(&mut service, get("/")).await;
+
+    let no_store = || {
+        Request::get("/")
+            .header(CACHE_CONTROL, "no-store")
+            .body(())
+            .unwrap()
+    };
+    let (_, _, body) = send(&mut service, no_store()).await;
+    assert_eq!(&body[..], b"response 1");
+    // `no-store` did not replace the entry.
+    let (_, _, body) = send(&mut service, get("/")).await;
+    assert_eq!(&body[..], b"response 0");
+
+    let no_cache = Request::get("/")
+        .header(CACHE_CONTROL, "no-cache")
+        .body(())
+        .unwrap();
+    let (_, _, body) = send(&mut service, no_cache).await;
+    assert_eq!(&body[..], b"response 2");
+    // `no-cache` did.
+    let (_, _, body) = send(&mut service, get("/")).await;
+    assert_eq!(&body[..], b"response 2");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn key_headers_split_entries() {
+    let (inner, calls) = upstream(|request, n| {
+        let language = request.headers()[ACCEPT_LANGUAGE].to_str().unwrap();
+        response("max-age=60", n).map(|_| Full::new(Bytes::from(format!("{language} {n}"))))
+    });
+    let mut service = CacheLayer::new(100)
+        .key_header(ACCEPT_LANGUAGE)
+        .layer(inner);
+    let request = |language| {
+        Request::get("/")
+            .header(ACCEPT_LANGUAGE, language)
+            .body(())
+            .unwrap()
+    };
+    for _ in 0..2 {
+        assert_eq!(&send(&mut service, request("en")).await.2[..], b"en 0");
+        assert_eq!(&send(&mut service, request("fr")).await.2[..], b"fr 1");
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn vary_headers_must_match_the_stored_request() {
+    let (inner, calls) = upstream(|_, n| {
+        let mut response = response("max-age=60", n);
+        response
+            .headers_mut()
+            .insert(VARY, HeaderValue::from_static("Accept-Encoding"));
+        response
+    });
+    let mut service = CacheLayer::new(100).layer(inner);
+    let request = |encoding| {
+        Request::get("/")
+            .header(ACCEPT_ENCODING, encoding)
+            .body(())
+            .unwrap()
+    };
+    assert_eq!(
+        &send(&mut service, request("gzip")).await.2[..],
+        b"response 0"
+    );
+    assert_eq!(
+        &send(&mut service, request("gzip")).await.2[..],
+        b"response 0"
+    );
+    // A different encoding misses and replaces the entry.
+    assert_eq!(
+        &send(&mut service, request("br")).await.2[..],
+        b"response 1"
+    );
+    assert_eq!(
+        &send(&mut service, request("br")).await.2[..],
+        b"response 1"
+    );
+    assert_eq!(
+        &send(&mut service, request("gzip")).await.2[..],
+        b"response 2"
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn vary_star_is_not_cached() {
+    let (inner, calls) = upstream(|_, n| {
+        let mut response = response("max-age=60", n);
+        response
+            .headers_mut()
+            .insert(VARY, HeaderValue::from_static("*"));
+        response
+    });
+    let mut service = CacheLayer::new(100).layer(inner);
+    send(&mut service, get("/")).await;
+    send(&mut service, get("/")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn if_none_match_is_answered_by_the_layer() {
+    let (inner, calls) = upstream(|request, n| {
+        // The upstream always sees an unconditional request.
+        assert!(!request.headers().contains_key(IF_NONE_MATCH));
+        let mut response = response("max-age=60", n);
+        response
+            .headers_mut()
+            .insert(ETAG, HeaderValue::from_static("\"v1\""));
+        response
+    });
+    let mut service = CacheLayer::new(100).layer(inner);
+    let conditional = |tags| {
+        Request::get("/")
+            .header(IF_NONE_MATCH, tags)
+            .body(())
+            .unwrap()
+    };
+
+    // On a miss the response is stored, then the condition is evaluated against it.
+    let (status, headers, body) = send(&mut service, conditional("\"v1\"")).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert_eq!(headers[ETAG], "\"v1\"");
+    assert!(body.is_empty());
+
+    let (status, headers, _) = send(&mut service, conditional("\"v0\", W/\"v1\"")).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert_eq!(headers[AGE], "0");
+
+    let (status, _, body) = send(&mut service, conditional("\"v2\"")).await;
+    assert_eq!((status, &body[..]), (StatusCode::OK, &b"response 0"[..]));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn if_modified_since_is_answered_by_the_layer() {
+    let (inner, calls) = upstream(|request, n| {
+        assert!(!request.headers().contains_key(IF_MODIFIED_SINCE));
+        let mut response = response("max-age=60", n);
+        let headers = response.headers_mut();
+        headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        response
+    });
+    let mut service = CacheLayer::new(100).layer(inner);
+    let since = |date| {
+        Request::get("/")
+            .header(IF_MODIFIED_SINCE, date)
+            .body(())
+            .unwrap()
+    };
+
+    // Evaluated against the stored response on a miss and on a hit alike.
+    let (status, _, body) = send(&mut service, since("Wed, 21 Oct 2015 07:28:00 GMT")).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert!(body.is_empty());
+    let (status, headers, _) = send(&mut service, since("Thu, 22 Oct 2015 00:00:00 GMT")).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert_eq!(headers[AGE], "0");
+
+    let (status, _, body) = send(&mut service, since("Tue, 20 Oct 2015 00:00:00 GMT")).await;
+    assert_eq!((status, &body[..]), (StatusCode::OK, &b"response 0"[..]));
+    let (status, _, _) = send(&mut service, since("not a date")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // `If-None-Match` takes precedence: a mismatched tag wins over a matching date.
+    let request = Request::get("/")
+        .header(IF_NONE_MATCH, "\"v0\"")
+        .header(IF_MODIFIED_SINCE, "Thu, 22 Oct 2015 00:00:00 GMT")
+        .body(())
+        .unwrap();
+    let (status, _, _) = send(&mut service, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn bodies_over_the_limit_stream_through_uncached() {
+    let (inner, calls) =
+        upstream(|_, _| response("max-age=60", 0).map(|_| Full::new(Bytes::from(vec![b'x'; 64]))));
+    let mut service = CacheLayer::new(100).max_body_size(32).layer(inner);
+    for _ in 0..2 {
+        let (_, _, body) = send(&mut service, get("/")).await;
+        assert_eq!(body.len(), 64);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn a_failing_body_is_replayed_and_not_cached() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let inner = service_fn(move |_: Request<()>| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let frames: Vec<Result<http_body::Frame<Bytes>, &'static str>> = vec![
+            Ok(http_body::Frame::data(Bytes::from_static(b"partial"))),
+            Err("upstream reset"),
+        ];
+        let body = StreamBody::new(futures::stream::iter(frames));
+        let response = Response::builder()
+            .header(CACHE_CONTROL, "max-age=60")
+            .body(body)
+            .unwrap();
+        async move { Ok::<_, Infallible>(response) }
+    });
+    let layer = CacheLayer::new(100);
+    for _ in 0..2 {
+        let response = layer.layer(inner.clone()).oneshot(get("/")).await.unwrap();
+        let mut body = response.into_body();
+        let first = body.frame().await.unwrap().unwrap();
+        assert_eq!(first.into_data().unwrap(), "partial");
+        assert_eq!(body.frame().await.unwrap().unwrap_err(), "upstream reset");
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn any_async_store_works_and_can_be_invalidated() {
+    let store: Arc<ShardedLruCache<HttpCacheKey, CachedResponse>> =
+        Arc::new(ShardedLruCache::new(100));
+    let layer = CacheLayer::with_shared_store(store.clone());
+    let (inner, calls) = upstream(|_, n| response("max-age=60", n));
+    let mut service = layer.layer(inner);
+
+    send(&mut service, get("/a")).await;
+    let (_, _, body) = send(&mut service, get("/a")).await;
+    assert_eq!(&body[..], b"response 0");
+
+    let key = layer.key(&get("/a"));
+    assert_eq!((key.method(), key.uri().path()), (&Method::GET, "/a"));
+    let cached = store.async_cache_remove(&key).await.unwrap().unwrap();
+    assert_eq!(cached.status(), StatusCode::OK);
+    assert_eq!(&cached.body()[..], b"response 0");
+
+    let (_, _, body) = send(&mut service, get("/a")).await;
+    assert_eq!(&body[..], b"response 1");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
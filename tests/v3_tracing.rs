@@ -0,0 +1,265 @@
+//! Spans emitted under the `tracing` feature: the `cached.call` span around macro-generated
+//! functions (with its `outcome` and `key_hash` fields) and the redb operation spans.
+//!
+//! Each test installs a capturing subscriber with `tracing::subscriber::with_default`, so the
+//! tests do not share a global subscriber and can run in parallel.
+//!
+//! Gated on `tracing`. Run with `cargo test --features tracing`.
+
+#![cfg(all(feature = "tracing", feature = "proc_macro"))]
+
+use cached::macros::{cached, concurrent_cached, once};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// A span as the subscriber saw it: its name, its parent's name, and every field value that
+/// was set at creation or recorded later, formatted with `Debug`.
+#[derive(Debug, Clone)]
+struct SeenSpan {
+    id: Id,
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl SeenSpan {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Vec<(&'static str, String)>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push((field.name(), format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), value.to_string()));
+    }
+}
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<SeenSpan>>>);
+
+impl Capture {
+    fn spans(&self, name: &str) -> Vec<SeenSpan> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.name == name)
+            .cloned()
+            .collect()
+    }
+
+    /// The `outcome` of each `cached.call` span for `function`, in call order.
+    fn outcomes(&self, function: &str) -> Vec<String> {
+        self.spans("cached.call")
+            .into_iter()
+            .filter(|s| s.field("function") == Some(function))
+            .map(|s| s.field("outcome").unwrap_or("<unset>").to_string())
+            .collect()
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for Capture
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Vec::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let parent = ctx
+            .span(id)
+            .and_then(|span| span.parent())
+            .map(|parent| parent.name());
+        self.0.lock().unwrap().push(SeenSpan {
+            id: id.clone(),
+            name: attrs.metadata().name(),
+            parent,
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut spans = self.0.lock().unwrap();
+        if let Some(span) = spans.iter_mut().rev().find(|s| s.id == *id) {
+            values.record(&mut FieldVisitor(&mut span.fields));
+        }
+    }
+}
+
+fn capture<R>(f: impl FnOnce() -> R) -> (Capture, R) {
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    let out = tracing::subscriber::with_default(subscriber, f);
+    (capture, out)
+}
+
+#[cached]
+fn traced_square(n: u64) -> u64 {
+    n * n
+}
+
+#[test]
+fn cached_call_span_records_miss_then_hit() {
+    let (capture, _) = capture(|| {
+        assert_eq!(traced_square(3), 9);
+        assert_eq!(traced_square(3), 9);
+    });
+    assert_eq!(capture.outcomes("traced_square"), ["miss", "hit"]);
+
+    // Both calls hash the same key, and the span carries only the hash.
+    let hashes: Vec<_> = capture
+        .spans("cached.call")
+        .iter()
+        .map(|s| s.field("key_hash").map(str::to_string))
+        .collect();
+    assert!(hashes[0].is_some(), "a `Hash` key records its hash");
+    assert_eq!(hashes[0], hashes[1]);
+}
+
+#[concurrent_cached]
+fn traced_cube(n: u64) -> u64 {
+    n * n * n
+}
+
+#[test]
+fn concurrent_cached_call_span_records_outcomes() {
+    let (capture, _) = capture(|| {
+        assert_eq!(traced_cube(4), 64);
+        assert_eq!(traced_cube(4), 64);
+    });
+    assert_eq!(capture.outcomes("traced_cube"), ["miss", "hit"]);
+}
+
+#[test]
+fn priming_opens_no_call_span() {
+    let (capture, _) = capture(|| traced_cube_prime_cache(2));
+    assert!(capture.outcomes("traced_cube").is_empty());
+}
+
+static FALLBACK_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+#[concurrent_cached(result_fallback = true, ttl_millis = 20)]
+fn traced_fallback(_k: u32) -> Result<u32, String> {
+    match FALLBACK_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+        0 => Ok(7),
+        _ => Err("down".to_string()),
+    }
+}
+
+#[test]
+fn result_fallback_records_stale() {
+    let (capture, _) = capture(|| {
+        assert_eq!(traced_fallback(1), Ok(7));
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(traced_fallback(1), Ok(7));
+    });
+    assert_eq!(capture.outcomes("traced_fallback"), ["miss", "stale"]);
+}
+
+#[once]
+fn traced_answer() -> u32 {
+    42
+}
+
+#[test]
+fn once_call_span_has_no_key_hash() {
+    let (capture, _) = capture(|| {
+        assert_eq!(traced_answer(), 42);
+        assert_eq!(traced_answer(), 42);
+    });
+    assert_eq!(capture.outcomes("traced_answer"), ["miss", "hit"]);
+    assert!(
+        capture
+            .spans("cached.call")
+            .iter()
+            .all(|s| s.field("key_hash").is_none())
+    );
+}
+
+#[test]
+fn no_subscriber_means_no_spans() {
+    // Without a subscriber the spans are disabled and the function behaves as usual.
+    assert_eq!(traced_square(11), 121);
+    assert_eq!(traced_square(11), 121);
+}
+
+#[cfg(feature = "async")]
+mod async_macros {
+    use super::capture;
+    use cached::macros::cached;
+
+    #[cached]
+    async fn traced_double(n: u64) -> u64 {
+        n * 2
+    }
+
+    #[test]
+    fn async_call_span_records_outcomes() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let (capture, _) = capture(|| {
+            runtime.block_on(async {
+                assert_eq!(traced_double(5).await, 10);
+                assert_eq!(traced_double(5).await, 10);
+            })
+        });
+        assert_eq!(capture.outcomes("traced_double"), ["miss", "hit"]);
+    }
+}
+
+#[cfg(feature = "redb_store")]
+mod redb {
+    use super::capture;
+    use cached::{ConcurrentCached, RedbCache};
+    use tempfile::TempDir;
+
+    #[test]
+    fn redb_operations_open_spans_with_transaction_children() {
+        let dir = TempDir::new().unwrap();
+        let cache: RedbCache<u32, u32> = RedbCache::builder("traced")
+            .disk_dir(dir.path())
+            .durable(false)
+            .build()
+            .unwrap();
+        let (capture, _) = capture(|| {
+            cache.cache_set(1, 10).unwrap();
+            assert_eq!(cache.cache_get(&1).unwrap(), Some(10));
+            assert_eq!(cache.cache_remove(&1).unwrap(), Some(10));
+        });
+
+        for op in ["redb.get", "redb.set", "redb.remove"] {
+            let spans = capture.spans(op);
+            assert_eq!(spans.len(), 1, "{op}");
+            let path = spans[0].field("path").unwrap();
+            assert!(
+                path.contains("traced"),
+                "{op} carries the file path: {path}"
+            );
+        }
+        let parents = |name| -> Vec<_> {
+            capture
+                .spans(name)
+                .into_iter()
+                .map(|s| s.parent.unwrap_or("<root>"))
+                .collect()
+        };
+        assert_eq!(parents("redb.read_txn"), ["redb.get"]);
+        assert_eq!(parents("redb.write_txn"), ["redb.set", "redb.remove"]);
+        assert_eq!(parents("cached.serialize"), ["redb.set"]);
+        assert_eq!(parents("cached.deserialize"), ["redb.get", "redb.remove"]);
+    }
+}
@@ -1690,8 +1690,8 @@ mod concurrent_trait_split_no_collision {
             .expect("build RedbCache");
 
         // cache_size / cache_is_empty live on ConcurrentCacheBase (single impl) -- no E0034.
-        assert_eq!(cache.cache_size().expect("cache_size"), None);
-        assert_eq!(cache.cache_is_empty().expect("cache_is_empty"), None);
+        assert_eq!(cache.cache_size().expect("cache_size"), Some(0));
+        assert_eq!(cache.cache_is_empty().expect("cache_is_empty"), Some(true));
 
         // set_ttl / ttl / unset_ttl live on ConcurrentCacheTtl -- no E0034 even with
         // both ConcurrentCached and ConcurrentCachedAsync in scope.
@@ -1804,8 +1804,8 @@ mod concurrent_trait_split_no_collision {
             .expect("build RedbCache");
 
         // ConcurrentCacheBase::cache_size via plain method syntax in an async fn.
-        // RedbCache reports an unknown size (Ok(None)).
-        assert_eq!(cache.cache_size().expect("cache_size"), None);
+        // RedbCache reports its table's row count.
+        assert_eq!(cache.cache_size().expect("cache_size"), Some(0));
 
         // ConcurrentCacheTtl::set_ttl via plain method syntax, interleaved with
         // `async_cache_*` IO ops from ConcurrentCachedAsync.
@@ -1834,54 +1834,52 @@ mod concurrent_trait_split_no_collision {
     }
 }
 
-// ── cache_size/len/is_empty defaults on Ok(None) stores (ConcurrentCacheBase) ──
+// ── cache_size/len/is_empty on RedbCache (ConcurrentCacheBase) ──────────────
 //
-// The author asserted `cache_size() == Ok(None)` only on `RedbCache`, and the
-// `len`/`is_empty` checks only on stores that report a real size (sharded). This
-// module pins the *default delegation* on a store whose `cache_size` is `Ok(None)`:
-// `len` must forward to `cache_size` (so also `Ok(None)`) and `is_empty` must map
-// `None` through to `Ok(None)` rather than fabricating a bool. A regression that
-// made `is_empty` return `Ok(Some(true))` for an unknown size would be caught here.
+// `RedbCache` reports its table's row count, so `is_empty` is derived from a real size.
+// The `Ok(None)` delegation of the defaults is pinned in `v3_metrics_unknown.rs`.
 #[cfg(feature = "redb_store")]
-mod concurrent_base_unknown_size_defaults {
+mod concurrent_base_redb_size {
     use cached::time::Duration;
     use cached::{ConcurrentCacheBase, ConcurrentCached, RedbCache};
 
     #[test]
-    fn redb_len_and_is_empty_default_to_unknown() {
+    fn redb_reports_its_row_count() {
         let dir = tempfile::TempDir::new().expect("temp dir");
-        let cache: RedbCache<String, u32> = RedbCache::builder("unknown-size-defaults")
+        let cache: RedbCache<String, u32> = RedbCache::builder("known-size")
             .disk_dir(dir.path())
             .ttl(Duration::from_secs(60))
             .build()
             .expect("build RedbCache");
 
-        // cache_size is unknown for redb (O(n) scan avoided). RedbCacheError does not
-        // implement PartialEq, so unwrap the Ok and compare the Option payload.
+        // RedbCacheError does not implement PartialEq, so unwrap the Ok and compare the
+        // Option payload.
         assert_eq!(
             ConcurrentCacheBase::cache_size(&cache).expect("cache_size"),
-            None
+            Some(0)
         );
-
-        // len delegates to cache_size -> also None.
-        assert_eq!(ConcurrentCacheBase::cache_size(&cache).expect("len"), None);
-
-        // is_empty maps an unknown size through to None (NOT Some(true)).
         assert_eq!(
             ConcurrentCacheBase::cache_is_empty(&cache).expect("is_empty"),
-            None
+            Some(true)
         );
 
-        // The defaults stay None even after a real write: redb still won't scan.
-        ConcurrentCached::cache_set(&cache, "k".to_string(), 1).expect("infallible set");
+        ConcurrentCached::cache_set(&cache, "a".to_string(), 1).expect("set");
+        ConcurrentCached::cache_set(&cache, "b".to_string(), 2).expect("set");
+        ConcurrentCached::cache_set(&cache, "a".to_string(), 3).expect("overwrite");
         assert_eq!(
             ConcurrentCacheBase::cache_size(&cache).expect("cache_size"),
-            None
+            Some(2)
         );
-        assert_eq!(ConcurrentCacheBase::cache_size(&cache).expect("len"), None);
         assert_eq!(
             ConcurrentCacheBase::cache_is_empty(&cache).expect("is_empty"),
-            None
+            Some(false)
+        );
+        assert_eq!(cache.metrics().entry_count, Some(2));
+
+        ConcurrentCached::cache_remove(&cache, &"a".to_string()).expect("remove");
+        assert_eq!(
+            ConcurrentCacheBase::cache_size(&cache).expect("cache_size"),
+            Some(1)
         );
     }
 }